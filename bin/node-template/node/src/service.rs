@@ -96,11 +96,13 @@ pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
 			finality_proof_provider: Some(finality_proof_provider.clone()), 
 		})?;
 
-	if config.offchain_worker.enabled {
+	let offchain_workers = if config.offchain_worker.enabled {
 		sc_service::build_offchain_workers(
 			&config, backend.clone(), task_manager.spawn_handle(), client.clone(), network.clone(),
-		);
-	}
+		)
+	} else {
+		None
+	};
 
 	let role = config.role.clone();
 	let force_authoring = config.force_authoring;
@@ -120,6 +122,8 @@ pub fn new_full(config: Configuration) -> Result<TaskManager, ServiceError> {
 		on_demand: None,
 		remote_blockchain: None,
 		backend, network_status_sinks, system_rpc_tx, config,
+		offchain_workers: offchain_workers
+			.map(|workers| workers as Arc<dyn sp_core::offchain::OffchainWorkerControl>),
 	})?;
 
 	if role.is_authority() {
@@ -251,11 +255,13 @@ pub fn new_light(config: Configuration) -> Result<TaskManager, ServiceError> {
 			finality_proof_provider: Some(finality_proof_provider),
 		})?;
 
-	if config.offchain_worker.enabled {
+	let offchain_workers = if config.offchain_worker.enabled {
 		sc_service::build_offchain_workers(
 			&config, backend.clone(), task_manager.spawn_handle(), client.clone(), network.clone(),
-		);
-	}
+		)
+	} else {
+		None
+	};
 	
 	sc_service::spawn_tasks(sc_service::SpawnTasksParams {
 		remote_blockchain: Some(backend.remote_blockchain()),
@@ -271,6 +277,8 @@ pub fn new_light(config: Configuration) -> Result<TaskManager, ServiceError> {
 		network, 
 		network_status_sinks, 
 		system_rpc_tx,
+		offchain_workers: offchain_workers
+			.map(|workers| workers as Arc<dyn sp_core::offchain::OffchainWorkerControl>),
 	 })?;
 
 	 Ok(task_manager)
@@ -194,6 +194,21 @@ impl system::Trait for Runtime {
 
 impl aura::Trait for Runtime {
 	type AuthorityId = AuraId;
+
+	type KeyOwnerProofSystem = ();
+
+	type KeyOwnerProof =
+		<Self::KeyOwnerProofSystem as KeyOwnerProofSystem<(KeyTypeId, AuraId)>>::Proof;
+
+	type KeyOwnerIdentification = <Self::KeyOwnerProofSystem as KeyOwnerProofSystem<(
+		KeyTypeId,
+		AuraId,
+	)>>::IdentificationTuple;
+
+	// There is no session pallet, so every slot belongs to the same session.
+	type CurrentSession = ();
+
+	type HandleEquivocation = ();
 }
 
 impl grandpa::Trait for Runtime {
@@ -368,6 +383,13 @@ impl_runtime_apis! {
 		fn offchain_worker(header: &<Block as BlockT>::Header) {
 			Executive::offchain_worker(header)
 		}
+
+		fn offchain_worker_filtered(
+			header: &<Block as BlockT>::Header,
+			disabled_pallets: Vec<Vec<u8>>,
+		) -> Vec<(Vec<u8>, u64)> {
+			Executive::offchain_worker_filtered(header, &disabled_pallets)
+		}
 	}
 
 	impl sp_consensus_aura::AuraApi<Block, AuraId> for Runtime {
@@ -378,6 +400,26 @@ impl_runtime_apis! {
 		fn authorities() -> Vec<AuraId> {
 			Aura::authorities()
 		}
+
+		fn submit_report_equivocation_unsigned_extrinsic(
+			_equivocation_proof: sp_consensus_aura::EquivocationProof<
+				<Block as BlockT>::Header,
+				AuraId,
+			>,
+			_key_owner_proof: sp_consensus_aura::OpaqueKeyOwnershipProof,
+		) -> Option<()> {
+			None
+		}
+
+		fn generate_key_ownership_proof(
+			_slot_number: sp_consensus_aura::SlotNumber,
+			_authority_id: AuraId,
+		) -> Option<sp_consensus_aura::OpaqueKeyOwnershipProof> {
+			// NOTE: this is the only implementation possible since we've
+			// defined our key owner proof type as a bottom type (i.e. a type
+			// with no values).
+			None
+		}
 	}
 
 	impl sp_session::SessionKeys<Block> for Runtime {
@@ -47,4 +47,11 @@ pub enum Subcommand {
 	/// The custom benchmark subcommmand benchmarking runtime pallets.
 	#[structopt(name = "benchmark", about = "Benchmark runtime pallets.")]
 	Benchmark(frame_benchmarking_cli::BenchmarkCmd),
+
+	/// The custom benchmark-compare subcommand finding weight regressions between benchmark runs.
+	#[structopt(
+		name = "benchmark-compare",
+		about = "Compare the JSON results of two benchmark runs."
+	)]
+	BenchmarkCompare(frame_benchmarking_cli::BenchmarkCompareCmd),
 }
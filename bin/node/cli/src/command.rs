@@ -19,8 +19,10 @@
 use crate::{chain_spec, service, Cli, Subcommand};
 use node_executor::Executor;
 use node_runtime::{Block, RuntimeApi};
+use node_primitives::Header;
 use sc_cli::{Result, SubstrateCli, RuntimeVersion, Role, ChainSpec};
 use sc_service::PartialComponents;
+use sp_runtime::traits::Header as _;
 use crate::service::new_partial;
 
 impl SubstrateCli for Cli {
@@ -80,7 +82,7 @@ pub fn run() -> Result<()> {
 		Some(Subcommand::Inspect(cmd)) => {
 			let runner = cli.create_runner(cmd)?;
 
-			runner.sync_run(|config| cmd.run::<Block, RuntimeApi, Executor>(config))
+			runner.sync_run(|config| cmd.run::<Block, RuntimeApi, Executor>(config, inspect_types()))
 		}
 		Some(Subcommand::Benchmark(cmd)) => {
 			if cfg!(feature = "runtime-benchmarks") {
@@ -93,6 +95,32 @@ pub fn run() -> Result<()> {
 				Ok(())
 			}
 		}
+		Some(Subcommand::BenchmarkCompare(cmd)) => cmd.run(),
+		Some(Subcommand::Base(sc_cli::Subcommand::ExportStateSnapshot(cmd))) => {
+			let runner = cli.create_runner(cmd)?;
+			runner.async_run(|config| {
+				let PartialComponents { backend, task_manager, .. } = new_partial(&config)?;
+				let aux_backend = backend.clone();
+				let aux_data = move |header: &Header| {
+					let hash = header.hash();
+					let (grandpa_oldest, mut aux) = grandpa::state_snapshot_aux_data(&*aux_backend, hash)?;
+					let (babe_oldest, babe_aux) =
+						sc_consensus_babe::state_snapshot_aux_data(&*aux_backend, hash)?;
+					aux.extend(babe_aux);
+					Ok((grandpa_oldest.min(babe_oldest), aux))
+				};
+				Ok((cmd.run::<Block, _>(backend, aux_data), task_manager))
+			})
+		}
+		Some(Subcommand::Base(sc_cli::Subcommand::ImportStateSnapshot(cmd))) => {
+			let runner = cli.create_runner(cmd)?;
+			let required_aux: Vec<_> = grandpa::STATE_SNAPSHOT_AUX_KEYS.iter()
+				.chain(sc_consensus_babe::STATE_SNAPSHOT_AUX_KEYS)
+				.copied()
+				.collect();
+
+			runner.sync_run(|config| cmd.run::<Block>(config, &required_aux))
+		}
 		Some(Subcommand::Base(subcommand)) => {
 			let runner = cli.create_runner(subcommand)?;
 			runner.run_subcommand(subcommand, |config| {
@@ -103,3 +131,28 @@ pub fn run() -> Result<()> {
 		}
 	}
 }
+
+/// The types the node runtime uses for the type names of its metadata.
+fn inspect_types() -> node_inspect::state::TypeRegistry {
+	use node_inspect::state::{Primitive, TypeRegistry};
+
+	TypeRegistry::default()
+		.with_type("BlockNumber", Primitive::U32)
+		.with_type("Index", Primitive::U32)
+		.with_type("AccountIndex", Primitive::U32)
+		.with_type("SessionIndex", Primitive::U32)
+		.with_type("EraIndex", Primitive::U32)
+		.with_type("ReferendumIndex", Primitive::U32)
+		.with_type("PropIndex", Primitive::U32)
+		.with_type("ProposalIndex", Primitive::U32)
+		.with_type("RegistrarIndex", Primitive::U32)
+		.with_type("Moment", Primitive::U64)
+		.with_type("Balance", Primitive::U128)
+		.with_type("BalanceOf<T>", Primitive::U128)
+		.with_type("BalanceOf<T, I>", Primitive::U128)
+		.with_type("AccountId", Primitive::AccountId)
+		.with_type("ValidatorId", Primitive::AccountId)
+		.with_type("Hash", Primitive::Hash)
+		// Weight, dispatch class and whether fees are paid.
+		.with_type("DispatchInfo", Primitive::Opaque(10))
+}
@@ -33,7 +33,7 @@ use sp_inherents::InherentDataProviders;
 use sc_network::{Event, NetworkService};
 use sp_runtime::traits::Block as BlockT;
 use futures::prelude::*;
-use sc_client_api::{ExecutorProvider, RemoteBackend};
+use sc_client_api::{ExecutorProvider, ExtrinsicIndex, RemoteBackend};
 use sp_core::traits::BareCryptoStorePtr;
 use node_executor::Executor;
 
@@ -55,7 +55,11 @@ pub fn new_partial(config: &Configuration) -> Result<sc_service::PartialComponen
 			grandpa::LinkHalf<Block, FullClient, FullSelectChain>,
 			sc_consensus_babe::BabeLink<Block>,
 		),
-		grandpa::SharedVoterState,
+		(
+			grandpa::SharedVoterState,
+			grandpa::SharedVotingRulesState<node_primitives::Hash, node_primitives::BlockNumber>,
+		),
+		Option<Arc<dyn ExtrinsicIndex<Block>>>,
 	)
 >, ServiceError> {
 	let (client, backend, keystore, task_manager) =
@@ -98,13 +102,22 @@ pub fn new_partial(config: &Configuration) -> Result<sc_service::PartialComponen
 
 	let import_setup = (block_import, grandpa_link, babe_link);
 
+	let extrinsic_index = if config.extrinsic_index.enabled {
+		Some(Arc::new(backend.extrinsic_index()) as Arc<dyn ExtrinsicIndex<Block>>)
+	} else {
+		None
+	};
+
 	let (rpc_extensions_builder, rpc_setup) = {
 		let (_, grandpa_link, babe_link) = &import_setup;
 
 		let shared_authority_set = grandpa_link.shared_authority_set().clone();
+		let shared_voter_set_state = grandpa_link.shared_voter_set_state().clone();
+		let justification_stream = grandpa_link.justification_stream();
 		let shared_voter_state = grandpa::SharedVoterState::empty();
+		let shared_voting_rules_state = grandpa::SharedVotingRulesState::empty();
 
-		let rpc_setup = shared_voter_state.clone();
+		let rpc_setup = (shared_voter_state.clone(), shared_voting_rules_state.clone());
 
 		let babe_config = babe_link.config().clone();
 		let shared_epoch_changes = babe_link.epoch_changes().clone();
@@ -113,6 +126,8 @@ pub fn new_partial(config: &Configuration) -> Result<sc_service::PartialComponen
 		let pool = transaction_pool.clone();
 		let select_chain = select_chain.clone();
 		let keystore = keystore.clone();
+		let subscription_executor = sc_rpc::SubscriptionTaskExecutor::new(task_manager.spawn_handle());
+		let extrinsic_index = extrinsic_index.clone();
 
 		let rpc_extensions_builder = move |deny_unsafe| {
 			let deps = node_rpc::FullDeps {
@@ -120,6 +135,8 @@ pub fn new_partial(config: &Configuration) -> Result<sc_service::PartialComponen
 				pool: pool.clone(),
 				select_chain: select_chain.clone(),
 				deny_unsafe,
+				subscription_executor: subscription_executor.clone(),
+				extrinsic_index: extrinsic_index.clone(),
 				babe: node_rpc::BabeDeps {
 					babe_config: babe_config.clone(),
 					shared_epoch_changes: shared_epoch_changes.clone(),
@@ -128,6 +145,9 @@ pub fn new_partial(config: &Configuration) -> Result<sc_service::PartialComponen
 				grandpa: node_rpc::GrandpaDeps {
 					shared_voter_state: shared_voter_state.clone(),
 					shared_authority_set: shared_authority_set.clone(),
+					shared_voting_rules_state: shared_voting_rules_state.clone(),
+					shared_voter_set_state: shared_voter_set_state.clone(),
+					justification_stream: justification_stream.clone(),
 				},
 			};
 
@@ -140,10 +160,40 @@ pub fn new_partial(config: &Configuration) -> Result<sc_service::PartialComponen
 	Ok(sc_service::PartialComponents {
 		client, backend, task_manager, keystore, select_chain, import_queue, transaction_pool,
 		inherent_data_providers,
-		other: (rpc_extensions_builder, import_setup, rpc_setup)
+		other: (rpc_extensions_builder, import_setup, rpc_setup, extrinsic_index)
 	})
 }
 
+/// Returns the accounts a node runtime extrinsic relates to, for the extrinsic index.
+///
+/// Only accounts that are given by id are known; accounts given by index would have to be
+/// looked up in the state of the block. Besides the signer, only the destinations of balance
+/// transfers are related to an extrinsic; accounts that other calls touch are not indexed.
+fn extrinsic_accounts(extrinsic: &<Block as BlockT>::Extrinsic) -> sc_extrinsic_index::Accounts {
+	use codec::{Decode, Encode};
+	use node_runtime::{Call, UncheckedExtrinsic};
+	use pallet_indices::address::Address;
+
+	let extrinsic = match UncheckedExtrinsic::decode(&mut &extrinsic.encode()[..]) {
+		Ok(extrinsic) => extrinsic,
+		Err(_) => return Default::default(),
+	};
+	let id = |address: &node_runtime::Address| match address {
+		Address::Id(id) => Some(id.encode()),
+		Address::Index(_) => None,
+	};
+
+	let signer = extrinsic.signature.as_ref().and_then(|(address, _, _)| id(address));
+	let touched = match &extrinsic.function {
+		Call::Balances(pallet_balances::Call::transfer(dest, _))
+			| Call::Balances(pallet_balances::Call::transfer_keep_alive(dest, _)) =>
+			id(dest).into_iter().collect(),
+		_ => Vec::new(),
+	};
+
+	sc_extrinsic_index::Accounts { signer, touched }
+}
+
 /// Creates a full service from the configuration.
 pub fn new_full_base(
 	config: Configuration,
@@ -159,7 +209,7 @@ pub fn new_full_base(
 	let sc_service::PartialComponents {
 		client, backend, mut task_manager, import_queue, keystore, select_chain, transaction_pool,
 		inherent_data_providers,
-		other: (rpc_extensions_builder, import_setup, rpc_setup),
+		other: (rpc_extensions_builder, import_setup, rpc_setup, extrinsic_index),
 	} = new_partial(&config)?;
 
 	let finality_proof_provider =
@@ -178,9 +228,24 @@ pub fn new_full_base(
 			finality_proof_provider: Some(finality_proof_provider.clone()),
 		})?;
 
-	if config.offchain_worker.enabled {
+	let offchain_workers = if config.offchain_worker.enabled {
 		sc_service::build_offchain_workers(
 			&config, backend.clone(), task_manager.spawn_handle(), client.clone(), network.clone(),
+		)
+	} else {
+		None
+	};
+
+	if let Some(extrinsic_index) = extrinsic_index {
+		let mut indexer = sc_extrinsic_index::Indexer::new(
+			client.clone(), extrinsic_index, Arc::new(extrinsic_accounts),
+		);
+		if let Some(retention) = config.extrinsic_index.hash_retention {
+			indexer = indexer.with_hash_retention(retention);
+		}
+		task_manager.spawn_handle().spawn_blocking(
+			"extrinsic-indexer",
+			sc_extrinsic_index::run_indexer(indexer),
 		);
 	}
 
@@ -188,6 +253,10 @@ pub fn new_full_base(
 	let force_authoring = config.force_authoring;
 	let name = config.network.node_name.clone();
 	let enable_grandpa = !config.disable_grandpa;
+	let voting_rules = config.grandpa_voting_rules.iter()
+		.map(|rule| rule.parse::<grandpa::VotingRuleConfig>())
+		.collect::<Result<Vec<_>, _>>()
+		.map_err(ServiceError::Other)?;
 	let prometheus_registry = config.prometheus_registry().cloned();
 	let telemetry_connection_sinks = sc_service::TelemetryConnectionSinks::default();
 
@@ -205,10 +274,12 @@ pub fn new_full_base(
 		telemetry_connection_sinks: telemetry_connection_sinks.clone(),
 		network_status_sinks,
 		system_rpc_tx,
+		offchain_workers: offchain_workers
+			.map(|workers| workers as Arc<dyn sp_core::offchain::OffchainWorkerControl>),
 	})?;
 	
 	let (block_import, grandpa_link, babe_link) = import_setup;
-	let shared_voter_state = rpc_setup;
+	let (shared_voter_state, shared_voting_rules_state) = rpc_setup;
 
 	(with_startup_data)(&block_import, &babe_link);
 
@@ -242,7 +313,7 @@ pub fn new_full_base(
 	// Spawn authority discovery module.
 	if matches!(role, Role::Authority{..} | Role::Sentry {..}) {
 		let (sentries, authority_discovery_role) = match role {
-			sc_service::config::Role::Authority { ref sentry_nodes } => (
+			sc_service::config::Role::Authority { ref sentry_nodes, .. } => (
 				sentry_nodes.clone(),
 				sc_authority_discovery::Role::Authority (
 					keystore.clone(),
@@ -303,7 +374,13 @@ pub fn new_full_base(
 			network: network.clone(),
 			inherent_data_providers: inherent_data_providers.clone(),
 			telemetry_on_connect: Some(telemetry_connection_sinks.on_connect_stream()),
-			voting_rule: grandpa::VotingRulesBuilder::default().build(),
+			voting_rule: grandpa::VotingRulesBuilder::from_config(
+				&voting_rules,
+				network.clone(),
+				client.clone(),
+			)
+				.report_to(shared_voting_rules_state)
+				.build(),
 			prometheus_registry,
 			shared_voter_state,
 		};
@@ -396,11 +473,13 @@ pub fn new_light_base(config: Configuration) -> Result<(
 			finality_proof_provider: Some(finality_proof_provider),
 		})?;
 	
-	if config.offchain_worker.enabled {
+	let offchain_workers = if config.offchain_worker.enabled {
 		sc_service::build_offchain_workers(
 			&config, backend.clone(), task_manager.spawn_handle(), client.clone(), network.clone(),
-		);
-	}
+		)
+	} else {
+		None
+	};
 
 	let light_deps = node_rpc::LightDeps {
 		remote_blockchain: backend.remote_blockchain(),
@@ -422,6 +501,8 @@ pub fn new_light_base(config: Configuration) -> Result<(
 			network: network.clone(),
 			telemetry_connection_sinks: sc_service::TelemetryConnectionSinks::default(),
 			task_manager: &mut task_manager,
+			offchain_workers: offchain_workers
+				.map(|workers| workers as Arc<dyn sp_core::offchain::OffchainWorkerControl>),
 		})?;
 	
 	Ok((task_manager, rpc_handlers, client, network, transaction_pool))
@@ -61,4 +61,41 @@ pub enum InspectSubCmd {
 		#[structopt(value_name = "BLOCK:INDEX or BYTES")]
 		input: String,
 	},
+	/// Decode a storage entry with help of the runtime metadata and print out its value.
+	Storage {
+		/// Name of the pallet, as given in `construct_runtime!`, e.g. `System`.
+		#[structopt(value_name = "PALLET")]
+		pallet: String,
+		/// Name of the storage item, e.g. `Account`.
+		#[structopt(value_name = "ITEM")]
+		item: String,
+		/// Keys of a map or double map item.
+		///
+		/// Accounts can be given as SS58 addresses and integers as decimal numbers, any other
+		/// key as 0x-prefixed SCALE encoded bytes. If fewer keys than the item has are given,
+		/// all the values that share them are printed.
+		#[structopt(value_name = "KEY")]
+		keys: Vec<String>,
+		/// The block to read the storage at. Defaults to the best block.
+		#[structopt(long, value_name = "HASH or NUMBER")]
+		at: Option<String>,
+	},
+	/// Print out the events deposited in a block.
+	Events {
+		/// Address of the block, either a block hash (no 0x prefix) or a number.
+		#[structopt(value_name = "HASH or NUMBER")]
+		input: String,
+	},
+	/// Print out the storage keys that changed between two blocks.
+	Diff {
+		/// The block to compare from.
+		#[structopt(value_name = "HASH or NUMBER")]
+		from: String,
+		/// The block to compare to.
+		#[structopt(value_name = "HASH or NUMBER")]
+		to: String,
+		/// Only compare the keys of a pallet (`Balances`) or of a storage item (`Balances::Account`).
+		#[structopt(long, value_name = "PALLET[::ITEM]")]
+		filter: Option<String>,
+	},
 }
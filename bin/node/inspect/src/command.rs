@@ -19,15 +19,19 @@
 //! Command ran by the CLI
 
 use crate::cli::{InspectCmd, InspectSubCmd};
-use crate::Inspector;
+use crate::{BlockAddressFor, BlockAddress, Inspector, state::{StateInspector, TypeRegistry}};
 use sc_cli::{CliConfiguration, ImportParams, Result, SharedParams};
-use sc_service::{new_full_client, Configuration, NativeExecutionDispatch};
-use sp_runtime::traits::Block;
+use sc_service::{new_full_client, Configuration, NativeExecutionDispatch, TFullBackend};
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{generic::BlockId, traits::Block};
 use std::str::FromStr;
 
 impl InspectCmd {
 	/// Run the inspect command, passing the inspector.
-	pub fn run<B, RA, EX>(&self, config: Configuration) -> Result<()>
+	///
+	/// `types` resolves the type names of the runtime metadata to the types of the runtime,
+	/// to decode storage values and events.
+	pub fn run<B, RA, EX>(&self, config: Configuration, types: TypeRegistry) -> Result<()>
 	where
 		B: Block,
 		B::Hash: FromStr,
@@ -35,21 +39,53 @@ impl InspectCmd {
 		EX: NativeExecutionDispatch + 'static,
 	{
 		let client = new_full_client::<B, RA, EX>(&config)?;
-		let inspect = Inspector::<B>::new(client);
+		let block_id = |input: &str| -> Result<BlockId<B>> {
+			let address: BlockAddressFor<B> = input.parse()?;
+			match address {
+				BlockAddress::Hash(hash) => Ok(BlockId::Hash(hash)),
+				BlockAddress::Number(number) => Ok(BlockId::Number(number)),
+				BlockAddress::Bytes(_) => Err("Expected a block hash or number".into()),
+			}
+		};
 
 		match &self.command {
 			InspectSubCmd::Block { input } => {
 				let input = input.parse()?;
+				let inspect = Inspector::<B>::new(client);
 				let res = inspect.block(input).map_err(|e| format!("{}", e))?;
 				println!("{}", res);
 				Ok(())
 			}
 			InspectSubCmd::Extrinsic { input } => {
 				let input = input.parse()?;
+				let inspect = Inspector::<B>::new(client);
 				let res = inspect.extrinsic(input).map_err(|e| format!("{}", e))?;
 				println!("{}", res);
 				Ok(())
 			}
+			InspectSubCmd::Storage { pallet, item, keys, at } => {
+				let at = match at {
+					Some(at) => block_id(at)?,
+					None => BlockId::Hash(client.info().best_hash),
+				};
+				let state = StateInspector::<B, TFullBackend<B>, _>::new(&client, types);
+				let res = state.storage(&at, pallet, item, keys).map_err(|e| format!("{}", e))?;
+				println!("{}", res);
+				Ok(())
+			}
+			InspectSubCmd::Events { input } => {
+				let state = StateInspector::<B, TFullBackend<B>, _>::new(&client, types);
+				let res = state.events(&block_id(input)?).map_err(|e| format!("{}", e))?;
+				println!("{}", res);
+				Ok(())
+			}
+			InspectSubCmd::Diff { from, to, filter } => {
+				let state = StateInspector::<B, TFullBackend<B>, _>::new(&client, types);
+				let res = state.diff(&block_id(from)?, &block_id(to)?, filter.as_deref())
+					.map_err(|e| format!("{}", e))?;
+				println!("{}", res);
+				Ok(())
+			}
 		}
 	}
 }
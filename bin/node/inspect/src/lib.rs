@@ -21,11 +21,15 @@
 //!
 //! The blocks and extrinsics can either be retrieved from the database (on-chain),
 //! or a raw SCALE-encoding can be provided.
+//!
+//! Storage entries and events are decoded with help of the runtime metadata, so that the
+//! storage keys don't have to be computed by hand.
 
 #![warn(missing_docs)]
 
 pub mod cli;
 pub mod command;
+pub mod state;
 
 use std::{
 	fmt,
@@ -88,6 +92,9 @@ pub enum Error {
 	Blockchain(sp_blockchain::Error),
 	/// Given block has not been found.
 	NotFound(String),
+	/// The runtime metadata is invalid or does not describe the requested item.
+	#[from(ignore)]
+	Metadata(String),
 }
 
 impl std::error::Error for Error {
@@ -96,6 +103,7 @@ impl std::error::Error for Error {
 			Self::Codec(ref e) => Some(e),
 			Self::Blockchain(ref e) => Some(e),
 			Self::NotFound(_) => None,
+			Self::Metadata(_) => None,
 		}
 	}
 }
@@ -0,0 +1,462 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Metadata-aware inspection of the runtime storage.
+
+use std::{
+	cmp::Ordering,
+	collections::HashMap,
+	fmt::{Debug, Write},
+	iter,
+	marker::PhantomData,
+};
+use codec::{Compact, Decode, Encode};
+use frame_metadata::decoded::{RuntimeInfo, StorageEntry};
+use frame_system::Phase;
+use sc_client_api::{
+	Backend, CallExecutor, ExecutionStrategy, ExecutorProvider, KeyIterator, StorageProvider,
+};
+use sp_core::{
+	crypto::{AccountId32, Ss58Codec},
+	hexdisplay::HexDisplay,
+	storage::StorageKey,
+	H256,
+};
+use sp_runtime::{generic::BlockId, traits::Block};
+
+use crate::Error;
+
+/// Inspects the storage of a block, using the runtime metadata of that block to compute the
+/// storage keys and to decode the values.
+pub struct StateInspector<'a, TBlock, TBackend, TClient> {
+	client: &'a TClient,
+	types: TypeRegistry,
+	_phantom: PhantomData<(TBlock, TBackend)>,
+}
+
+impl<'a, TBlock, TBackend, TClient> StateInspector<'a, TBlock, TBackend, TClient> where
+	TBlock: Block,
+	TBackend: Backend<TBlock>,
+	TClient: StorageProvider<TBlock, TBackend> + ExecutorProvider<TBlock>,
+{
+	/// Create new instance of the state inspector.
+	///
+	/// `types` resolves the type names of the metadata to the types the runtime uses.
+	pub fn new(client: &'a TClient, types: TypeRegistry) -> Self {
+		StateInspector { client, types, _phantom: PhantomData }
+	}
+
+	/// Returns the storage and event metadata of the runtime at the given block.
+	pub fn runtime_info(&self, at: &BlockId<TBlock>) -> Result<RuntimeInfo, Error> {
+		let encoded = self.client.executor().call(
+			at,
+			"Metadata_metadata",
+			&[],
+			ExecutionStrategy::NativeElseWasm,
+			None,
+		)?;
+		let metadata = Vec::<u8>::decode(&mut &encoded[..])?;
+		RuntimeInfo::decode(&metadata).map_err(Error::Metadata)
+	}
+
+	/// Get the pretty-printed value of a storage entry.
+	///
+	/// `keys` are the map keys of the entry, see [`TypeRegistry::encode_key`]. If fewer keys
+	/// than the entry has are given, all the values that share the given keys are printed.
+	pub fn storage(
+		&self,
+		at: &BlockId<TBlock>,
+		module: &str,
+		item: &str,
+		keys: &[String],
+	) -> Result<String, Error> {
+		let info = self.runtime_info(at)?;
+		let (module, entry) = info.storage_entry(module, item).map_err(Error::Metadata)?;
+		let prefix = module.storage_prefix.as_deref().unwrap_or(&module.name);
+		let encoded_keys = entry.key_types().into_iter()
+			.zip(keys)
+			.map(|(ty, key)| self.types.encode_key(ty, key))
+			.collect::<Result<Vec<_>, _>>()
+			.map_err(Error::Metadata)?;
+		let storage_key = entry.storage_key(prefix, &encoded_keys).map_err(Error::Metadata)?;
+
+		let mut out = String::new();
+		if keys.len() == entry.key_count() {
+			let value = self.client.storage(at, &StorageKey(storage_key.clone()))?;
+			let _ = writeln!(out, "{}::{} ({})", module.name, entry.name, entry.value);
+			let _ = writeln!(out, "Key: 0x{}", HexDisplay::from(&storage_key));
+			match (value, &entry.default) {
+				(Some(value), _) =>
+					out.push_str(&self.format_value(entry, &value.0)),
+				(None, Some(default)) =>
+					out.push_str(&format!("{} (default)", self.format_value(entry, default))),
+				(None, None) => out.push_str("None"),
+			}
+		} else {
+			let pairs = self.client.storage_pairs(at, &StorageKey(storage_key))?;
+			let _ = write!(out, "{}::{} ({} values)", module.name, entry.name, pairs.len());
+			for (key, value) in pairs {
+				let _ = write!(
+					out,
+					"\n0x{}: {}",
+					HexDisplay::from(&key.0),
+					self.format_value(entry, &value.0),
+				);
+			}
+		}
+		Ok(out)
+	}
+
+	/// Get the pretty-printed events deposited in the given block.
+	///
+	/// The event records are split with the argument types the metadata of the block gives
+	/// for every event. Once an argument of a type that is not known to the type registry is
+	/// met, the remaining records are printed as raw bytes.
+	pub fn events(&self, at: &BlockId<TBlock>) -> Result<String, Error> {
+		let info = self.runtime_info(at)?;
+		let (module, entry) = info.storage_entry("System", "Events").map_err(Error::Metadata)?;
+		let prefix = module.storage_prefix.as_deref().unwrap_or(&module.name);
+		let key = entry.storage_key(prefix, &[]).map_err(Error::Metadata)?;
+		let encoded = self.client.storage(at, &StorageKey(key))?
+			.map(|data| data.0)
+			.unwrap_or_default();
+		let input = &mut &encoded[..];
+		let count = <Compact<u32>>::decode(input)?.0;
+
+		let mut out = format!("Events ({})", count);
+		for index in 0..count {
+			let phase = match Phase::decode(input)? {
+				Phase::ApplyExtrinsic(index) => format!("extrinsic {}", index),
+				Phase::Finalization => "finalization".into(),
+				Phase::Initialization => "initialization".into(),
+			};
+			let (module_index, event_index) = (u8::decode(input)?, u8::decode(input)?);
+			let (module, event) = match info.event(module_index, event_index) {
+				Some(event) => event,
+				None => {
+					let _ = write!(
+						out,
+						"\n- {}: <unknown event {}:{}> ({})\n Remaining bytes: 0x{}",
+						index,
+						module_index,
+						event_index,
+						phase,
+						HexDisplay::from(*input),
+					);
+					return Ok(out);
+				},
+			};
+
+			let _ = write!(out, "\n- {}: {}::{} ({})", index, module.name, event.name, phase);
+			let mut arguments = Vec::new();
+			for ty in &event.arguments {
+				match self.types.decode_prefix(ty, input) {
+					Some(argument) => arguments.push(argument),
+					None => {
+						let _ = write!(
+							out,
+							"\n Argument of unknown type `{}`, remaining bytes: 0x{}",
+							ty,
+							HexDisplay::from(*input),
+						);
+						return Ok(out);
+					},
+				}
+			}
+			if !arguments.is_empty() {
+				let _ = write!(out, "\n {}", arguments.join(", "));
+			}
+			for topic in Vec::<TBlock::Hash>::decode(input)? {
+				let _ = write!(out, "\n Topic: {:?}", topic);
+			}
+		}
+		Ok(out)
+	}
+
+	/// Get the keys that were added, removed or changed between two blocks.
+	///
+	/// `filter` restricts the diff to a module (`Balances`) or a storage entry
+	/// (`Balances::Account`). The keys of both blocks are walked side by side in trie order,
+	/// so that the state doesn't have to be loaded into memory.
+	pub fn diff(
+		&self,
+		from: &BlockId<TBlock>,
+		to: &BlockId<TBlock>,
+		filter: Option<&str>,
+	) -> Result<String, Error> {
+		let info = self.runtime_info(to)?;
+		let prefix = StorageKey(match filter {
+			Some(filter) => {
+				let mut parts = filter.splitn(2, "::");
+				let module = parts.next().unwrap_or_default();
+				match parts.next() {
+					Some(item) => {
+						let (module, entry) = info.storage_entry(module, item)
+							.map_err(Error::Metadata)?;
+						let prefix = module.storage_prefix.as_deref().unwrap_or(&module.name);
+						entry.storage_key(prefix, &[]).map_err(Error::Metadata)?
+					},
+					None => sp_core::hashing::twox_128(module.as_bytes()).to_vec(),
+				}
+			},
+			None => Vec::new(),
+		});
+
+		let mut old_keys = self.keys(from, &prefix)?.peekable();
+		let mut new_keys = self.keys(to, &prefix)?.peekable();
+		let label = |key: &[u8]| info.entry_of_key(key)
+			.map(|(module, entry)| format!(" ({}::{})", module.name, entry.name))
+			.unwrap_or_default();
+		let mut out = String::new();
+		let mut changes = 0;
+		loop {
+			let order = match (old_keys.peek(), new_keys.peek()) {
+				(None, None) => break,
+				(Some(_), None) => Ordering::Less,
+				(None, Some(_)) => Ordering::Greater,
+				(Some(old), Some(new)) => old.0.cmp(&new.0),
+			};
+			let (marker, key) = match order {
+				Ordering::Less => ("-", old_keys.next()),
+				Ordering::Greater => ("+", new_keys.next()),
+				Ordering::Equal => {
+					old_keys.next();
+					let key = new_keys.next().expect("Both iterators have a next key; qed");
+					if self.client.storage_hash(from, &key)? == self.client.storage_hash(to, &key)? {
+						continue;
+					}
+					("~", Some(key))
+				},
+			};
+			let key = key.expect("The iterator of the smaller key has a next key; qed");
+			changes += 1;
+			let _ = write!(out, "\n{} 0x{}{}", marker, HexDisplay::from(&key.0), label(&key.0));
+		}
+		Ok(format!("Changed keys ({}){}", changes, out))
+	}
+
+	/// Returns the keys of the given block that start with `prefix`, in trie order.
+	fn keys<'b>(
+		&self,
+		at: &BlockId<TBlock>,
+		prefix: &'b StorageKey,
+	) -> Result<iter::Chain<
+		std::option::IntoIter<StorageKey>,
+		KeyIterator<'b, TBackend::State, TBlock>,
+	>, Error> {
+		// The iterator starts after the prefix, which is the key of a plain storage entry.
+		let exact = self.client.storage_hash(at, prefix)?.map(|_| prefix.clone());
+		Ok(exact.into_iter().chain(self.client.storage_keys_iter(at, Some(prefix), None)?))
+	}
+
+	fn format_value(&self, entry: &StorageEntry, value: &[u8]) -> String {
+		self.types.decode_value(&entry.value, value)
+			.unwrap_or_else(|| format!("0x{}", HexDisplay::from(&value)))
+	}
+}
+
+/// A type that storage keys, storage values and event arguments can be decoded as.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Primitive {
+	/// `bool`.
+	Bool,
+	/// `u8`.
+	U8,
+	/// `u16`.
+	U16,
+	/// `u32`.
+	U32,
+	/// `u64`.
+	U64,
+	/// `u128`.
+	U128,
+	/// A 32 byte account id, printed as SS58 address.
+	AccountId,
+	/// A 256 bit hash.
+	Hash,
+	/// A value with the given encoded size, printed as hex.
+	Opaque(usize),
+}
+
+/// Maps the type names of the runtime metadata to primitive types.
+///
+/// The metadata only contains the names used in the pallet source, e.g. `T::Balance` or
+/// `BalanceOf<T>`, so the types a runtime uses for them have to be registered. By default only
+/// the primitive Rust types, `H256` and `AccountId32` are known.
+#[derive(Clone, Debug)]
+pub struct TypeRegistry {
+	types: HashMap<String, Primitive>,
+}
+
+impl Default for TypeRegistry {
+	fn default() -> Self {
+		TypeRegistry { types: HashMap::new() }
+			.with_type("bool", Primitive::Bool)
+			.with_type("u8", Primitive::U8)
+			.with_type("u16", Primitive::U16)
+			.with_type("u32", Primitive::U32)
+			.with_type("u64", Primitive::U64)
+			.with_type("u128", Primitive::U128)
+			.with_type("AccountId32", Primitive::AccountId)
+			.with_type("H256", Primitive::Hash)
+	}
+}
+
+impl TypeRegistry {
+	/// Register the primitive type of a type name.
+	///
+	/// Paths are ignored, so `T::Balance` and `Balance` are the same type name, generic
+	/// parameters are not.
+	pub fn with_type(mut self, name: &str, primitive: Primitive) -> Self {
+		self.types.insert(type_name(name).into(), primitive);
+		self
+	}
+
+	/// Returns the primitive type a type name of the metadata stands for, if any.
+	pub fn resolve(&self, ty: &str) -> Option<Primitive> {
+		self.types.get(type_name(ty)).copied()
+	}
+
+	/// SCALE encodes a map key given on the command line.
+	///
+	/// A `0x` prefixed key is used as is. Otherwise the key is parsed according to the type of
+	/// the key in the metadata: accounts as SS58 addresses and integers as decimal numbers.
+	pub fn encode_key(&self, ty: &str, input: &str) -> Result<Vec<u8>, String> {
+		if input.starts_with("0x") {
+			return sp_core::bytes::from_hex(input)
+				.map_err(|e| format!("Invalid hex key `{}`: {:?}", input, e));
+		}
+
+		let invalid = |e: &dyn Debug| format!("Invalid `{}` key `{}`: {:?}", ty, input, e);
+		Ok(match self.resolve(ty) {
+			Some(Primitive::Bool) => input.parse::<bool>().map_err(|e| invalid(&e))?.encode(),
+			Some(Primitive::U8) => input.parse::<u8>().map_err(|e| invalid(&e))?.encode(),
+			Some(Primitive::U16) => input.parse::<u16>().map_err(|e| invalid(&e))?.encode(),
+			Some(Primitive::U32) => input.parse::<u32>().map_err(|e| invalid(&e))?.encode(),
+			Some(Primitive::U64) => input.parse::<u64>().map_err(|e| invalid(&e))?.encode(),
+			Some(Primitive::U128) => input.parse::<u128>().map_err(|e| invalid(&e))?.encode(),
+			Some(Primitive::AccountId) =>
+				AccountId32::from_ss58check(input).map_err(|e| invalid(&e))?.encode(),
+			_ => return Err(format!(
+				"Keys of type `{}` must be given as 0x-prefixed SCALE encoded bytes",
+				ty,
+			)),
+		})
+	}
+
+	/// Decodes a storage value of a known type.
+	pub fn decode_value(&self, ty: &str, mut value: &[u8]) -> Option<String> {
+		let decoded = self.decode_prefix(ty, &mut value)?;
+		// A value that is longer than its type is not what the metadata claims.
+		if value.is_empty() { Some(decoded) } else { None }
+	}
+
+	/// Decodes a value of a known type from the start of `input`.
+	pub fn decode_prefix(&self, ty: &str, input: &mut &[u8]) -> Option<String> {
+		Some(match self.resolve(ty)? {
+			Primitive::Bool => bool::decode(input).ok()?.to_string(),
+			Primitive::U8 => u8::decode(input).ok()?.to_string(),
+			Primitive::U16 => u16::decode(input).ok()?.to_string(),
+			Primitive::U32 => u32::decode(input).ok()?.to_string(),
+			Primitive::U64 => u64::decode(input).ok()?.to_string(),
+			Primitive::U128 => u128::decode(input).ok()?.to_string(),
+			Primitive::AccountId => AccountId32::decode(input).ok()?.to_ss58check(),
+			Primitive::Hash => format!("{:?}", H256::decode(input).ok()?),
+			Primitive::Opaque(size) => {
+				if input.len() < size {
+					return None;
+				}
+				let (value, rest) = input.split_at(size);
+				*input = rest;
+				format!("0x{}", HexDisplay::from(&value))
+			},
+		})
+	}
+}
+
+/// Strips the path from a type name, `T::Balance` becomes `Balance`.
+fn type_name(ty: &str) -> &str {
+	let ty = ty.trim();
+	let generics = ty.find('<').unwrap_or_else(|| ty.len());
+	let start = ty[..generics].rfind("::").map_or(0, |index| index + 2);
+	&ty[start..]
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn types() -> TypeRegistry {
+		TypeRegistry::default()
+			.with_type("BlockNumber", Primitive::U32)
+			.with_type("BalanceOf<T>", Primitive::U128)
+			.with_type("AccountId", Primitive::AccountId)
+			.with_type("Moment", Primitive::U64)
+			.with_type("DispatchInfo", Primitive::Opaque(10))
+	}
+
+	#[test]
+	fn type_names_are_resolved_without_paths() {
+		let types = types();
+
+		assert_eq!(types.resolve("T::BlockNumber"), Some(Primitive::U32));
+		assert_eq!(types.resolve(" BalanceOf<T>"), Some(Primitive::U128));
+		assert_eq!(types.resolve("BalanceOf<T, I>"), None);
+		assert_eq!(types.resolve("Option<T::AccountId>"), None);
+		assert_eq!(TypeRegistry::default().resolve("T::BlockNumber"), None);
+	}
+
+	#[test]
+	fn keys_are_encoded_by_type() {
+		let types = types();
+
+		assert_eq!(types.encode_key("T::BlockNumber", "5"), Ok(5u32.encode()));
+		assert_eq!(types.encode_key("BalanceOf<T>", "7"), Ok(7u128.encode()));
+		assert_eq!(types.encode_key("Vec<u8>", "0x0401"), Ok(vec![4, 1]));
+		assert_eq!(
+			types.encode_key("T::AccountId", "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"),
+			Ok(sp_core::crypto::AccountId32::from_ss58check(
+				"5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"
+			).unwrap().encode()),
+		);
+		assert!(types.encode_key("T::BlockNumber", "five").is_err());
+		assert!(types.encode_key("Vec<u8>", "5").is_err());
+	}
+
+	#[test]
+	fn values_are_decoded_by_type() {
+		let types = types();
+
+		assert_eq!(types.decode_value("T::Moment", &42u64.encode()), Some("42".into()));
+		assert_eq!(types.decode_value("bool", &true.encode()), Some("true".into()));
+		assert_eq!(types.decode_value("u32", &42u64.encode()), None);
+		assert_eq!(types.decode_value("Vec<u8>", &[0]), None);
+	}
+
+	#[test]
+	fn values_are_decoded_one_after_another() {
+		let types = types();
+		let encoded = ([1u8; 10], 5u32).encode();
+		let input = &mut &encoded[..];
+
+		assert_eq!(types.decode_prefix("DispatchInfo", input), Some("0x01010101010101010101".into()));
+		assert_eq!(types.decode_prefix("T::BlockNumber", input), Some("5".into()));
+		assert!(input.is_empty());
+		assert_eq!(types.decode_prefix("DispatchInfo", &mut &[1u8; 9][..]), None);
+	}
+}
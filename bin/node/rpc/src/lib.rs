@@ -43,9 +43,16 @@ use sp_consensus_babe::BabeApi;
 use sc_consensus_epochs::SharedEpochChanges;
 use sc_consensus_babe::{Config, Epoch};
 use sc_consensus_babe_rpc::BabeRpcHandler;
-use sc_finality_grandpa::{SharedVoterState, SharedAuthoritySet};
+use sc_finality_grandpa::{
+	SharedVoterState, SharedAuthoritySet, SharedVotingRulesState, SharedVoterSetState,
+	GrandpaJustificationStream,
+};
 use sc_finality_grandpa_rpc::GrandpaRpcHandler;
 use sp_block_builder::BlockBuilder;
+use sc_rpc::SubscriptionTaskExecutor;
+use sc_client_api::ExtrinsicIndex;
+use sc_extrinsic_index_rpc::ExtrinsicIndexRpc;
+use jsonrpc_pubsub::manager::SubscriptionManager;
 pub use sc_rpc_api::DenyUnsafe;
 
 /// Light client extra dependencies.
@@ -76,6 +83,12 @@ pub struct GrandpaDeps {
 	pub shared_voter_state: SharedVoterState,
 	/// Authority set info.
 	pub shared_authority_set: SharedAuthoritySet<Hash, BlockNumber>,
+	/// Voting rules info.
+	pub shared_voting_rules_state: SharedVotingRulesState<Hash, BlockNumber>,
+	/// Completed rounds info.
+	pub shared_voter_set_state: SharedVoterSetState<Block>,
+	/// Receives notifications about justification events from Grandpa.
+	pub justification_stream: GrandpaJustificationStream<Block>,
 }
 
 /// Full client dependencies.
@@ -88,6 +101,10 @@ pub struct FullDeps<C, P, SC> {
 	pub select_chain: SC,
 	/// Whether to deny unsafe calls
 	pub deny_unsafe: DenyUnsafe,
+	/// Executor of the RPC subscriptions.
+	pub subscription_executor: SubscriptionTaskExecutor,
+	/// Index of the extrinsics of the canonical chain, if enabled.
+	pub extrinsic_index: Option<Arc<dyn ExtrinsicIndex<Block>>>,
 	/// BABE specific dependencies.
 	pub babe: BabeDeps,
 	/// GRANDPA specific dependencies.
@@ -98,22 +115,25 @@ pub struct FullDeps<C, P, SC> {
 pub type IoHandler = jsonrpc_core::IoHandler<sc_rpc::Metadata>;
 
 /// Instantiate all Full RPC extensions.
-pub fn create_full<C, P, M, SC>(
+pub fn create_full<C, P, SC, BE>(
 	deps: FullDeps<C, P, SC>,
-) -> jsonrpc_core::IoHandler<M> where
+) -> jsonrpc_core::IoHandler<sc_rpc::Metadata> where
+	BE: sc_client_api::Backend<Block> + 'static,
 	C: ProvideRuntimeApi<Block>,
 	C: HeaderBackend<Block> + HeaderMetadata<Block, Error=BlockChainError> + 'static,
+	C: sc_client_api::StorageProvider<Block, BE> + sc_client_api::BlockchainEvents<Block>,
 	C: Send + Sync + 'static,
+	C::Api: sp_api::Metadata<Block, Error=BlockChainError>,
 	C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Index>,
 	C::Api: pallet_contracts_rpc::ContractsRuntimeApi<Block, AccountId, Balance, BlockNumber>,
 	C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance, UncheckedExtrinsic>,
 	C::Api: BabeApi<Block>,
 	C::Api: BlockBuilder<Block>,
 	P: TransactionPool + 'static,
-	M: jsonrpc_core::Metadata + Default,
 	SC: SelectChain<Block> +'static,
 {
 	use substrate_frame_rpc_system::{FullSystem, SystemApi};
+	use substrate_frame_rpc_events::{FullEvents, EventsApi, FullTransactions, TransactionApi};
 	use pallet_contracts_rpc::{Contracts, ContractsApi};
 	use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApi};
 
@@ -123,6 +143,8 @@ pub fn create_full<C, P, M, SC>(
 		pool,
 		select_chain,
 		deny_unsafe,
+		subscription_executor,
+		extrinsic_index,
 		babe,
 		grandpa,
 	} = deps;
@@ -134,6 +156,9 @@ pub fn create_full<C, P, M, SC>(
 	let GrandpaDeps {
 		shared_voter_state,
 		shared_authority_set,
+		shared_voting_rules_state,
+		shared_voter_set_state,
+		justification_stream,
 	} = grandpa;
 
 	io.extend_with(
@@ -148,6 +173,25 @@ pub fn create_full<C, P, M, SC>(
 	io.extend_with(
 		TransactionPaymentApi::to_delegate(TransactionPayment::new(client.clone()))
 	);
+	io.extend_with(
+		EventsApi::to_delegate(FullEvents::<_, _, _, node_runtime::Event>::new(
+			client.clone(),
+			SubscriptionManager::new(Arc::new(subscription_executor.clone())),
+		))
+	);
+	if let Some(extrinsic_index) = extrinsic_index {
+		io.extend_with(
+			sc_extrinsic_index_rpc::ExtrinsicIndexApi::to_delegate(
+				ExtrinsicIndexRpc::<_, AccountId>::new(extrinsic_index.clone()),
+			)
+		);
+		io.extend_with(
+			TransactionApi::to_delegate(FullTransactions::<_, _, _, node_runtime::Event>::new(
+				client.clone(),
+				extrinsic_index,
+			))
+		);
+	}
 	io.extend_with(
 		sc_consensus_babe_rpc::BabeApi::to_delegate(
 			BabeRpcHandler::new(
@@ -162,7 +206,14 @@ pub fn create_full<C, P, M, SC>(
 	);
 	io.extend_with(
 		sc_finality_grandpa_rpc::GrandpaApi::to_delegate(
-			GrandpaRpcHandler::new(shared_authority_set, shared_voter_state)
+			GrandpaRpcHandler::new(
+				shared_authority_set,
+				shared_voter_state,
+				shared_voting_rules_state,
+				shared_voter_set_state,
+				justification_stream,
+				SubscriptionManager::new(Arc::new(subscription_executor)),
+			)
 		)
 	);
 
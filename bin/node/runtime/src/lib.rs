@@ -105,7 +105,7 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	// and set impl_version to 0. If only runtime
 	// implementation changes and behavior does not, then leave spec_version as
 	// is and increment impl_version.
-	spec_version: 257,
+	spec_version: 258,
 	impl_version: 0,
 	apis: RUNTIME_API_VERSIONS,
 	transaction_version: 1,
@@ -968,6 +968,13 @@ impl_runtime_apis! {
 		fn offchain_worker(header: &<Block as BlockT>::Header) {
 			Executive::offchain_worker(header)
 		}
+
+		fn offchain_worker_filtered(
+			header: &<Block as BlockT>::Header,
+			disabled_pallets: Vec<Vec<u8>>,
+		) -> Vec<(Vec<u8>, u64)> {
+			Executive::offchain_worker_filtered(header, &disabled_pallets)
+		}
 	}
 
 	impl fg_primitives::GrandpaApi<Block> for Runtime {
@@ -1119,6 +1126,39 @@ impl_runtime_apis! {
 
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
+		fn benchmark_metadata() -> Vec<frame_benchmarking::BenchmarkList> {
+			use frame_benchmarking::{Benchmarking, BenchmarkList, list_benchmark};
+			use pallet_session_benchmarking::Module as SessionBench;
+			use pallet_offences_benchmarking::Module as OffencesBench;
+			use frame_system_benchmarking::Module as SystemBench;
+
+			let mut list = Vec::<BenchmarkList>::new();
+
+			list_benchmark!(list, pallet_babe, Babe);
+			list_benchmark!(list, pallet_balances, Balances);
+			list_benchmark!(list, pallet_collective, Council);
+			list_benchmark!(list, pallet_contracts, Contracts);
+			list_benchmark!(list, pallet_democracy, Democracy);
+			list_benchmark!(list, pallet_elections_phragmen, Elections);
+			list_benchmark!(list, pallet_grandpa, Grandpa);
+			list_benchmark!(list, pallet_identity, Identity);
+			list_benchmark!(list, pallet_im_online, ImOnline);
+			list_benchmark!(list, pallet_indices, Indices);
+			list_benchmark!(list, pallet_multisig, Multisig);
+			list_benchmark!(list, pallet_offences, OffencesBench::<Runtime>);
+			list_benchmark!(list, pallet_proxy, Proxy);
+			list_benchmark!(list, pallet_scheduler, Scheduler);
+			list_benchmark!(list, pallet_session, SessionBench::<Runtime>);
+			list_benchmark!(list, pallet_staking, Staking);
+			list_benchmark!(list, frame_system, SystemBench::<Runtime>);
+			list_benchmark!(list, pallet_timestamp, Timestamp);
+			list_benchmark!(list, pallet_treasury, Treasury);
+			list_benchmark!(list, pallet_utility, Utility);
+			list_benchmark!(list, pallet_vesting, Vesting);
+
+			list
+		}
+
 		fn dispatch_benchmark(
 			pallet: Vec<u8>,
 			benchmark: Vec<u8>,
@@ -0,0 +1,306 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Editing of existing chain specs.
+//!
+//! Plain chain specs are edited through the typed `GenesisConfig` of the node runtime, raw
+//! chain specs by patching the storage keys of the genesis state directly.
+
+use std::{fs, path::Path};
+
+use serde_json::{Map, Value};
+use node_cli::chain_spec::{self, AccountId, Balance};
+use node_runtime::{GenesisConfig, SessionKeys};
+use sp_core::{crypto::Ss58Codec, storage::well_known_keys};
+
+/// An authority whose session keys should be set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Authority {
+	/// The account the keys are set for, the stash account of the seed if not given.
+	pub account: Option<AccountId>,
+	/// The seed the session keys are derived from.
+	pub seed: String,
+}
+
+/// Parse an authority given as `<seed>` or as `<address>=<seed>`.
+pub fn parse_authority(s: &str) -> Result<Authority, String> {
+	match s.find('=') {
+		Some(pos) => Ok(Authority {
+			account: Some(parse_account(&s[..pos])?),
+			seed: s[pos + 1..].into(),
+		}),
+		None => Ok(Authority { account: None, seed: s.into() }),
+	}
+}
+
+/// The free balance of an account.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccountBalance {
+	/// The account.
+	pub account: AccountId,
+	/// The free balance.
+	pub balance: Balance,
+}
+
+/// Parse a balance given as `<address>=<amount>`.
+pub fn parse_balance(s: &str) -> Result<AccountBalance, String> {
+	let pos = s.find('=').ok_or_else(|| format!("Expected `<address>=<amount>`, got `{}`", s))?;
+	Ok(AccountBalance {
+		account: parse_account(&s[..pos])?,
+		balance: s[pos + 1..].parse().map_err(|e| format!("Invalid amount `{}`: {}", &s[pos + 1..], e))?,
+	})
+}
+
+/// A storage item of the raw genesis state.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StorageItem {
+	/// The storage key.
+	pub key: Vec<u8>,
+	/// The value.
+	pub value: Vec<u8>,
+}
+
+/// Parse a storage item given as `<hex key>=<hex value>`.
+pub fn parse_storage_item(s: &str) -> Result<StorageItem, String> {
+	let pos = s.find('=').ok_or_else(|| format!("Expected `<key>=<value>`, got `{}`", s))?;
+	Ok(StorageItem { key: parse_hex(&s[..pos])?, value: parse_hex(&s[pos + 1..])? })
+}
+
+/// Parse hex encoded bytes, with or without `0x` prefix.
+pub fn parse_hex(s: &str) -> Result<Vec<u8>, String> {
+	let hex = if s.starts_with("0x") { &s[2..] } else { s };
+	hex::decode(hex).map_err(|e| format!("Invalid hex `{}`: {}", s, e))
+}
+
+fn parse_account(address: &str) -> Result<AccountId, String> {
+	AccountId::from_string(address)
+		.map_err(|err| format!("Failed to parse account address: {:?}", err))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+	format!("0x{}", hex::encode(bytes))
+}
+
+/// Read a chain spec as JSON.
+pub fn read_spec(path: &Path) -> Result<Value, String> {
+	let content = fs::read(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+	serde_json::from_slice(&content).map_err(|e| format!("Error parsing spec file: {}", e))
+}
+
+/// Write a chain spec in the format of `ChainSpec::as_json`.
+pub fn write_spec(path: &Path, spec: &Value) -> Result<(), String> {
+	let json = serde_json::to_string_pretty(spec)
+		.map_err(|e| format!("Error generating spec json: {}", e))?;
+	fs::write(path, json).map_err(|e| format!("Cannot write {}: {}", path.display(), e))
+}
+
+/// Returns whether the chain spec contains the raw genesis state.
+pub fn is_raw(spec: &Value) -> bool {
+	spec.pointer("/genesis/raw").is_some()
+}
+
+/// Changes to the genesis config of a plain chain spec.
+#[derive(Debug, Default)]
+pub struct PlainEdits {
+	/// Authorities whose session keys are added or replaced.
+	pub authorities: Vec<Authority>,
+	/// Whether to remove all existing session keys first.
+	pub replace_authorities: bool,
+	/// Balances that are added or replaced.
+	pub balances: Vec<AccountBalance>,
+	/// The new runtime code.
+	pub code: Option<Vec<u8>>,
+}
+
+/// Apply `edits` to a plain chain spec.
+///
+/// Only the session keys are set for the authorities. To be elected as validator, the account
+/// needs to be among the stakers as well.
+pub fn edit_plain(spec: &mut Value, edits: PlainEdits) -> Result<(), String> {
+	let runtime = spec.pointer_mut("/genesis/runtime")
+		.ok_or_else(|| "Not a plain chain spec".to_string())?;
+	let mut genesis: GenesisConfig = serde_json::from_value(runtime.take())
+		.map_err(|e| format!("Error parsing the genesis config: {}", e))?;
+
+	if edits.replace_authorities || !edits.authorities.is_empty() {
+		let session = genesis.pallet_session.as_mut()
+			.ok_or_else(|| "The chain spec has no session genesis config".to_string())?;
+		if edits.replace_authorities {
+			session.keys.clear();
+		}
+
+		for authority in edits.authorities {
+			let (stash, _, grandpa, babe, im_online, authority_discovery) =
+				chain_spec::authority_keys_from_seed(&authority.seed);
+			let account = authority.account.unwrap_or(stash);
+			let keys = SessionKeys { grandpa, babe, im_online, authority_discovery };
+
+			match session.keys.iter_mut().find(|(validator, _, _)| *validator == account) {
+				Some(entry) => entry.2 = keys,
+				None => session.keys.push((account.clone(), account, keys)),
+			}
+		}
+	}
+
+	if !edits.balances.is_empty() {
+		let balances = genesis.pallet_balances.as_mut()
+			.ok_or_else(|| "The chain spec has no balances genesis config".to_string())?;
+		for AccountBalance { account, balance } in edits.balances {
+			match balances.balances.iter_mut().find(|(a, _)| *a == account) {
+				Some(entry) => entry.1 = balance,
+				None => balances.balances.push((account, balance)),
+			}
+		}
+	}
+
+	if let Some(code) = edits.code {
+		genesis.frame_system.as_mut()
+			.ok_or_else(|| "The chain spec has no system genesis config".to_string())?
+			.code = code;
+	}
+
+	*runtime = serde_json::to_value(&genesis)
+		.map_err(|e| format!("Error generating the genesis config: {}", e))?;
+	Ok(())
+}
+
+/// Changes to the genesis state of a raw chain spec.
+#[derive(Debug, Default)]
+pub struct RawEdits {
+	/// Storage items that are inserted or replaced.
+	pub set: Vec<StorageItem>,
+	/// Storage keys that are removed.
+	pub remove: Vec<Vec<u8>>,
+	/// The new runtime code.
+	pub code: Option<Vec<u8>>,
+}
+
+/// Apply `edits` to the top trie of a raw chain spec.
+///
+/// Removals are applied before insertions, the code is set last.
+pub fn edit_raw(spec: &mut Value, edits: RawEdits) -> Result<(), String> {
+	let top = raw_top(spec)?;
+
+	for key in edits.remove {
+		top.remove(&to_hex(&key));
+	}
+	for StorageItem { key, value } in edits.set {
+		top.insert(to_hex(&key), Value::String(to_hex(&value)));
+	}
+	if let Some(code) = edits.code {
+		top.insert(to_hex(well_known_keys::CODE), Value::String(to_hex(&code)));
+	}
+	Ok(())
+}
+
+/// Read the storage changes of a file that is merged into a raw chain spec.
+///
+/// The file is either a raw chain spec, whose top trie is merged completely, or a JSON object
+/// with hex encoded storage keys and values. `null` values remove the key.
+pub fn read_merge(path: &Path) -> Result<RawEdits, String> {
+	let mut other = read_spec(path)?;
+	let items = if is_raw(&other) {
+		raw_top(&mut other)?.clone()
+	} else {
+		other.as_object()
+			.cloned()
+			.ok_or_else(|| format!("{} is neither a raw chain spec nor a JSON object", path.display()))?
+	};
+
+	let mut edits = RawEdits::default();
+	for (key, value) in items {
+		let key = parse_hex(&key)?;
+		match value {
+			Value::Null => edits.remove.push(key),
+			Value::String(value) => edits.set.push(StorageItem { key, value: parse_hex(&value)? }),
+			value => return Err(format!("Expected a hex encoded storage value, got `{}`", value)),
+		}
+	}
+	Ok(edits)
+}
+
+fn raw_top(spec: &mut Value) -> Result<&mut Map<String, Value>, String> {
+	spec.pointer_mut("/genesis/raw/top")
+		.and_then(Value::as_object_mut)
+		.ok_or_else(|| "Not a raw chain spec".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::json;
+
+	fn raw_spec() -> Value {
+		json!({
+			"name": "Test",
+			"genesis": {
+				"raw": {
+					"top": {
+						"0x01": "0x0a",
+						"0x02": "0x0b",
+					},
+					"childrenDefault": {},
+				},
+			},
+		})
+	}
+
+	#[test]
+	fn raw_storage_is_patched() {
+		let mut spec = raw_spec();
+		assert!(is_raw(&spec));
+
+		edit_raw(&mut spec, RawEdits {
+			set: vec![parse_storage_item("0x02=0xff").unwrap(), parse_storage_item("03=00").unwrap()],
+			remove: vec![vec![1]],
+			code: Some(vec![0, 97, 115, 109]),
+		}).unwrap();
+
+		assert_eq!(spec["genesis"]["raw"]["top"], json!({
+			"0x02": "0xff",
+			"0x03": "0x00",
+			"0x3a636f6465": "0x0061736d",
+		}));
+	}
+
+	#[test]
+	fn plain_specs_are_not_patched_as_raw() {
+		let mut spec = json!({ "genesis": { "runtime": {} } });
+		assert!(!is_raw(&spec));
+		assert!(edit_raw(&mut spec, RawEdits::default()).is_err());
+	}
+
+	#[test]
+	fn arguments_are_parsed() {
+		let alice = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+
+		let balance = parse_balance(&format!("{}=1000", alice)).unwrap();
+		assert_eq!(balance.account, parse_account(alice).unwrap());
+		assert_eq!(balance.balance, 1000);
+		assert!(parse_balance(alice).is_err());
+
+		assert_eq!(parse_authority("Alice").unwrap(), Authority { account: None, seed: "Alice".into() });
+		assert_eq!(
+			parse_authority(&format!("{}=//Bob", alice)).unwrap(),
+			Authority { account: Some(parse_account(alice).unwrap()), seed: "//Bob".into() },
+		);
+
+		assert_eq!(parse_storage_item("0x0102=0x").unwrap(), StorageItem { key: vec![1, 2], value: vec![] });
+		assert!(parse_storage_item("0x0102").is_err());
+	}
+}
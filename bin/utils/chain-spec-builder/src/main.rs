@@ -25,9 +25,14 @@ use structopt::StructOpt;
 use sc_keystore::{Store as Keystore};
 use node_cli::chain_spec::{self, AccountId};
 use sp_core::{sr25519, crypto::{Public, Ss58Codec}, traits::BareCryptoStore};
+use node_primitives::Hash;
+
+mod edit;
+mod validate;
 
 /// A utility to easily create a testnet chain spec definition with a given set
-/// of authorities and endowed accounts and/or generate random accounts.
+/// of authorities and endowed accounts and/or generate random accounts, and to
+/// edit, convert and validate existing chain specs.
 #[derive(StructOpt)]
 #[structopt(rename_all = "kebab-case")]
 enum ChainSpecBuilder {
@@ -67,6 +72,65 @@ enum ChainSpecBuilder {
 		#[structopt(long, short)]
 		keystore_path: Option<PathBuf>,
 	},
+	/// Edit an existing chain spec.
+	///
+	/// Session keys and balances can only be changed in a plain chain spec, storage items only
+	/// in a raw one.
+	Edit {
+		/// The path of the chain spec to edit.
+		#[structopt(long, short, default_value = "./chain_spec.json")]
+		chain_spec_path: PathBuf,
+		/// The path where the edited chain spec should be saved. The chain spec is edited in
+		/// place if not given.
+		#[structopt(long, short)]
+		output: Option<PathBuf>,
+		/// Add or replace the session keys of an authority, given as `<seed>` or as
+		/// `<address>=<seed>`.
+		///
+		/// The keys are derived from the seed. Without address, they are set for the stash
+		/// account of the seed.
+		#[structopt(long, parse(try_from_str = edit::parse_authority))]
+		authority: Vec<edit::Authority>,
+		/// Remove all existing session keys before adding the given authorities.
+		#[structopt(long)]
+		replace_authorities: bool,
+		/// Add or replace the free balance of an account, given as `<address>=<amount>`.
+		#[structopt(long, parse(try_from_str = edit::parse_balance))]
+		balance: Vec<edit::AccountBalance>,
+		/// Insert or replace a raw storage item, given as `<hex key>=<hex value>`.
+		#[structopt(long, parse(try_from_str = edit::parse_storage_item))]
+		set: Vec<edit::StorageItem>,
+		/// Remove a raw storage item, given as hex encoded key.
+		#[structopt(long, parse(try_from_str = edit::parse_hex))]
+		remove: Vec<Vec<u8>>,
+		/// Merge the storage items of a file into the raw chain spec.
+		///
+		/// The file is either another raw chain spec or a JSON object with hex encoded keys
+		/// and values, where `null` removes the key.
+		#[structopt(long)]
+		merge: Vec<PathBuf>,
+		/// Replace the runtime code with the given Wasm blob.
+		#[structopt(long)]
+		code: Option<PathBuf>,
+	},
+	/// Convert a plain chain spec into a raw one.
+	Raw {
+		/// The path of the plain chain spec.
+		#[structopt(long, short, default_value = "./chain_spec.json")]
+		chain_spec_path: PathBuf,
+		/// The path where the raw chain spec should be saved.
+		#[structopt(long, short, default_value = "./chain_spec_raw.json")]
+		output: PathBuf,
+	},
+	/// Check that the genesis state of a chain spec builds and its runtime can be executed.
+	Validate {
+		/// The path of the chain spec to validate.
+		#[structopt(long, short, default_value = "./chain_spec.json")]
+		chain_spec_path: PathBuf,
+		/// Fail if the genesis hash differs from the given one.
+		#[structopt(long)]
+		genesis_hash: Option<Hash>,
+	},
 }
 
 impl ChainSpecBuilder {
@@ -77,6 +141,12 @@ impl ChainSpecBuilder {
 				chain_spec_path.as_path(),
 			ChainSpecBuilder::Generate { chain_spec_path, .. } =>
 				chain_spec_path.as_path(),
+			ChainSpecBuilder::Edit { chain_spec_path, output, .. } =>
+				output.as_ref().unwrap_or(chain_spec_path).as_path(),
+			ChainSpecBuilder::Raw { output, .. } =>
+				output.as_path(),
+			ChainSpecBuilder::Validate { chain_spec_path, .. } =>
+				chain_spec_path.as_path(),
 		}
 	}
 }
@@ -179,6 +249,51 @@ fn generate_authority_keys_and_store(
 	Ok(())
 }
 
+fn edit_chain_spec(
+	chain_spec_path: &Path,
+	output: &Path,
+	plain_edits: edit::PlainEdits,
+	raw_edits: edit::RawEdits,
+	merge: Vec<PathBuf>,
+) -> Result<(), String> {
+	let mut spec = edit::read_spec(chain_spec_path)?;
+
+	if edit::is_raw(&spec) {
+		if plain_edits.replace_authorities
+			|| !plain_edits.authorities.is_empty()
+			|| !plain_edits.balances.is_empty()
+		{
+			return Err("Session keys and balances can only be changed in a plain chain spec".into());
+		}
+
+		for path in merge {
+			edit::edit_raw(&mut spec, edit::read_merge(&path)?)?;
+		}
+		edit::edit_raw(&mut spec, raw_edits)?;
+	} else {
+		if !raw_edits.set.is_empty() || !raw_edits.remove.is_empty() || !merge.is_empty() {
+			return Err("Storage items can only be changed in a raw chain spec".into());
+		}
+
+		edit::edit_plain(&mut spec, plain_edits)?;
+	}
+
+	edit::write_spec(output, &spec)
+}
+
+fn validate_chain_spec(chain_spec_path: &Path, genesis_hash: Option<Hash>) -> Result<(), String> {
+	let json = fs::read(chain_spec_path)
+		.map_err(|e| format!("Cannot read {}: {}", chain_spec_path.display(), e))?;
+	let validation = validate::validate(json, genesis_hash)?;
+
+	let entry = Style::new().bold();
+	println!("{} {}", entry.paint("Runtime:"), validation.runtime_version);
+	println!("{} {:?}", entry.paint("State root:"), validation.state_root);
+	println!("{} {:?}", entry.paint("Genesis hash:"), validation.genesis_hash);
+
+	Ok(())
+}
+
 fn print_seeds(
 	authority_seeds: &[String],
 	endowed_seeds: &[String],
@@ -226,6 +341,28 @@ fn main() -> Result<(), String> {
 	let chain_spec_path = builder.chain_spec_path().to_path_buf();
 
 	let (authority_seeds, endowed_accounts, sudo_account) = match builder {
+		ChainSpecBuilder::Edit {
+			chain_spec_path: input, authority, replace_authorities, balance, set, remove, merge, code, ..
+		} => {
+			let code = code
+				.map(|path| fs::read(&path).map_err(|e| format!("Cannot read {}: {}", path.display(), e)))
+				.transpose()?;
+			let plain_edits = edit::PlainEdits {
+				authorities: authority,
+				replace_authorities,
+				balances: balance,
+				code: code.clone(),
+			};
+			let raw_edits = edit::RawEdits { set, remove, code };
+
+			return edit_chain_spec(&input, &chain_spec_path, plain_edits, raw_edits, merge);
+		},
+		ChainSpecBuilder::Raw { chain_spec_path: plain_path, .. } => {
+			let json = chain_spec::ChainSpec::from_json_file(plain_path)?.as_json(true)?;
+			return fs::write(chain_spec_path, json).map_err(|err| err.to_string());
+		},
+		ChainSpecBuilder::Validate { genesis_hash, .. } =>
+			return validate_chain_spec(&chain_spec_path, genesis_hash),
 		ChainSpecBuilder::Generate { authorities, endowed, keystore_path, .. } => {
 			let authorities = authorities.max(1);
 			let rand_str = || -> String {
@@ -0,0 +1,173 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Validation of the genesis state of a chain spec.
+
+use codec::Decode;
+use node_cli::chain_spec;
+use node_primitives::{Block, Hash};
+use sc_executor::{NativeExecutor, RuntimeInfo, RuntimeVersion, WasmExecutionMethod};
+use sp_core::{
+	blake2_256, storage::{Storage, well_known_keys},
+	traits::{RuntimeCode, WrappedRuntimeCode},
+};
+use sp_runtime::{BuildStorage, traits::Block as BlockT};
+use sp_state_machine::BasicExternalities;
+
+/// The result of a successful validation.
+pub struct Validation {
+	/// The hash of the genesis block.
+	pub genesis_hash: Hash,
+	/// The state root of the genesis block.
+	pub state_root: Hash,
+	/// The version of the genesis runtime.
+	pub runtime_version: RuntimeVersion,
+}
+
+/// Build the genesis state of the chain spec and execute its runtime.
+///
+/// Fails if the genesis state can not be built, the runtime can not be executed or the
+/// genesis hash differs from `expected_genesis_hash`.
+pub fn validate(
+	json: Vec<u8>,
+	expected_genesis_hash: Option<Hash>,
+) -> Result<Validation, String> {
+	let spec = chain_spec::ChainSpec::from_json_bytes(json)?;
+	let storage = spec.build_storage()
+		.map_err(|e| format!("Failed to build the genesis state: {}", e))?;
+
+	let runtime_version = runtime_version(&storage)?;
+	let state_root = sc_service::client::genesis::genesis_state_root::<Block>(&storage);
+	let block: Block = sc_service::client::genesis::construct_genesis_block(state_root);
+	let genesis_hash = block.hash();
+
+	if let Some(expected) = expected_genesis_hash {
+		if expected != genesis_hash {
+			return Err(format!(
+				"Genesis hash mismatch: expected {:?}, got {:?}",
+				expected,
+				genesis_hash,
+			));
+		}
+	}
+
+	Ok(Validation { genesis_hash, state_root, runtime_version })
+}
+
+/// Execute `Core_version` of the runtime in the genesis state.
+fn runtime_version(storage: &Storage) -> Result<RuntimeVersion, String> {
+	let code = storage.top.get(well_known_keys::CODE)
+		.filter(|code| !code.is_empty())
+		.ok_or_else(|| "The genesis state has no runtime code".to_string())?;
+	let heap_pages = storage.top.get(well_known_keys::HEAP_PAGES)
+		.and_then(|pages| u64::decode(&mut &pages[..]).ok());
+
+	let code_fetcher = WrappedRuntimeCode(code.as_slice().into());
+	let runtime_code = RuntimeCode {
+		code_fetcher: &code_fetcher,
+		heap_pages,
+		hash: blake2_256(code).to_vec(),
+	};
+
+	let executor = NativeExecutor::<node_executor::Executor>::new(
+		WasmExecutionMethod::Interpreted,
+		None,
+		1,
+	);
+	let mut ext = BasicExternalities::new(storage.clone());
+	executor.runtime_version(&mut ext, &runtime_code)
+		.map_err(|e| format!("Failed to execute the genesis runtime: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::edit::{self, Authority, PlainEdits};
+	use sc_executor::NativeExecutor;
+	use sp_api::ProvideRuntimeApi;
+	use sp_consensus_babe::BabeApi;
+	use sp_runtime::{generic::BlockId, traits::Header as _};
+
+	type TestClient = sc_service::client::Client<
+		sc_client_api::in_mem::Backend<Block>,
+		sc_service::client::LocalCallExecutor<
+			sc_client_api::in_mem::Backend<Block>,
+			NativeExecutor<node_executor::Executor>,
+		>,
+		Block,
+		node_runtime::RuntimeApi,
+	>;
+
+	fn client(json: Vec<u8>) -> TestClient {
+		let spec = chain_spec::ChainSpec::from_json_bytes(json).unwrap();
+		sc_service::client::new_in_mem(
+			NativeExecutor::<node_executor::Executor>::new(WasmExecutionMethod::Interpreted, None, 8),
+			&spec,
+			None,
+			None,
+			Box::new(sp_core::testing::TaskExecutor::new()),
+			Default::default(),
+		).unwrap()
+	}
+
+	fn dev_spec() -> Vec<u8> {
+		chain_spec::development_config().as_json(false).unwrap().into_bytes()
+	}
+
+	#[test]
+	fn genesis_of_the_dev_spec_matches_the_client() {
+		let client = client(dev_spec());
+		let genesis = BlockId::Number(0);
+
+		let validation = validate(dev_spec(), Some(client.chain_info().genesis_hash)).unwrap();
+
+		assert_eq!(validation.state_root, *client.header(&genesis).unwrap().unwrap().state_root());
+		assert_eq!(validation.runtime_version, client.runtime_version_at(&genesis).unwrap());
+	}
+
+	#[test]
+	fn wrong_genesis_hash_is_rejected() {
+		assert!(validate(dev_spec(), Some(Default::default())).is_err());
+	}
+
+	#[test]
+	fn edited_plain_spec_builds_the_edited_genesis() {
+		let alice_stash = chain_spec::authority_keys_from_seed("Alice").0;
+		let charlie_babe = chain_spec::authority_keys_from_seed("Charlie").3;
+
+		let mut spec: serde_json::Value = serde_json::from_slice(&dev_spec()).unwrap();
+		edit::edit_plain(&mut spec, PlainEdits {
+			authorities: vec![Authority { account: Some(alice_stash), seed: "Charlie".into() }],
+			..Default::default()
+		}).unwrap();
+		let json = serde_json::to_vec(&spec).unwrap();
+
+		let client = client(json.clone());
+		let genesis = BlockId::Number(0);
+
+		let validation = validate(json, None).unwrap();
+		assert_eq!(validation.genesis_hash, client.chain_info().genesis_hash);
+		assert_ne!(validation.genesis_hash, validate(dev_spec(), None).unwrap().genesis_hash);
+
+		let authorities = client.runtime_api().configuration(&genesis).unwrap().genesis_authorities;
+		assert_eq!(
+			authorities.into_iter().map(|(authority, _)| authority).collect::<Vec<_>>(),
+			vec![charlie_babe],
+		);
+	}
+}
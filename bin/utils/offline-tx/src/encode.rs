@@ -0,0 +1,281 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! SCALE encoding of JSON call arguments, based on the type names of the metadata.
+//!
+//! The metadata only contains the type names used in the pallet sources, so the common
+//! associated types are mapped to the types the node runtime uses for them. Arguments of any
+//! other type can always be given as `0x` prefixed SCALE encoded bytes.
+
+use codec::{Compact, Encode};
+use serde_json::Value;
+use sp_core::crypto::{AccountId32, Ss58Codec};
+
+/// Normalize a type name of the metadata: remove whitespace and the `T::` qualification.
+fn normalize(ty: &str) -> String {
+	let ty: String = ty.chars().filter(|c| !c.is_whitespace()).collect();
+	match ty.as_str() {
+		"<T::LookupasStaticLookup>::Source" | "LookupSourceFor<T>" => "LookupSource".into(),
+		_ => ty.replace("T::", ""),
+	}
+}
+
+/// Split `Name<Inner>` into `Name` and `Inner`.
+fn generic(ty: &str) -> Option<(&str, &str)> {
+	let start = ty.find('<')?;
+	if !ty.ends_with('>') {
+		return None;
+	}
+	Some((&ty[..start], &ty[start + 1..ty.len() - 1]))
+}
+
+/// Split the elements of a tuple type, respecting nested generics and tuples.
+fn tuple_elements(ty: &str) -> Vec<&str> {
+	let mut elements = Vec::new();
+	let mut depth = 0;
+	let mut start = 0;
+	for (i, c) in ty.char_indices() {
+		match c {
+			'<' | '(' => depth += 1,
+			'>' | ')' => depth -= 1,
+			',' if depth == 0 => {
+				elements.push(&ty[start..i]);
+				start = i + 1;
+			},
+			_ => {},
+		}
+	}
+	if start < ty.len() {
+		elements.push(&ty[start..]);
+	}
+	elements
+}
+
+fn raw_bytes(value: &Value) -> Option<Result<Vec<u8>, String>> {
+	let s = value.as_str()?;
+	if !s.starts_with("0x") {
+		return None;
+	}
+	Some(hex::decode(&s[2..]).map_err(|e| format!("Invalid hex `{}`: {}", s, e)))
+}
+
+fn integer(ty: &str, value: &Value) -> Result<u128, String> {
+	match value {
+		Value::Number(n) => n.as_u64().map(Into::into),
+		// Large numbers are given as strings, as JSON numbers are not precise enough.
+		Value::String(s) => s.parse().ok(),
+		_ => None,
+	}.ok_or_else(|| format!("Expected an unsigned integer of type `{}`, got `{}`", ty, value))
+}
+
+fn fixed<T: std::convert::TryFrom<u128> + Encode>(ty: &str, value: &Value) -> Result<Vec<u8>, String> {
+	let n = integer(ty, value)?;
+	T::try_from(n)
+		.map(|n| n.encode())
+		.map_err(|_| format!("`{}` is out of range for `{}`", n, ty))
+}
+
+/// Returns the unsigned integer type a type name stands for, if any.
+fn integer_type(ty: &str) -> Option<&'static str> {
+	Some(match ty {
+		"u8" => "u8",
+		"u16" => "u16",
+		"u32" | "BlockNumber" | "Index" | "SessionIndex" | "EraIndex" | "ReferendumIndex"
+			| "PropIndex" | "ProposalIndex" | "RegistrarIndex" | "AccountIndex" | "MemberCount" => "u32",
+		"u64" | "Moment" | "Weight" => "u64",
+		"u128" | "Balance" | "BalanceOf<T>" | "BalanceOf<T,I>" => "u128",
+		_ => return None,
+	})
+}
+
+fn account_id(value: &Value) -> Result<AccountId32, String> {
+	let s = value.as_str().ok_or_else(|| format!("Expected an account, got `{}`", value))?;
+	if s.starts_with("0x") {
+		let bytes = hex::decode(&s[2..]).map_err(|e| format!("Invalid hex `{}`: {}", s, e))?;
+		let bytes: [u8; 32] = std::convert::TryFrom::try_from(&bytes[..])
+			.map_err(|_| format!("Account `{}` is not 32 bytes long", s))?;
+		Ok(bytes.into())
+	} else {
+		AccountId32::from_ss58check(s).map_err(|e| format!("Invalid address `{}`: {:?}", s, e))
+	}
+}
+
+/// SCALE encode the JSON `value` as the type `ty` of the metadata.
+pub fn encode_value(ty: &str, value: &Value) -> Result<Vec<u8>, String> {
+	let ty = normalize(ty);
+
+	if let Some(integer_type) = integer_type(&ty) {
+		return match integer_type {
+			"u8" => fixed::<u8>(&ty, value),
+			"u16" => fixed::<u16>(&ty, value),
+			"u32" => fixed::<u32>(&ty, value),
+			"u64" => fixed::<u64>(&ty, value),
+			_ => fixed::<u128>(&ty, value),
+		};
+	}
+
+	match ty.as_str() {
+		"bool" => return value.as_bool()
+			.map(|b| b.encode())
+			.ok_or_else(|| format!("Expected a bool, got `{}`", value)),
+		"AccountId" => return account_id(value).map(|a| a.encode()),
+		// The `Address` of the indices pallet, which the node runtime uses as lookup source.
+		"LookupSource" | "Address" => return account_id(value).map(|a| {
+			let mut encoded = vec![0xff];
+			encoded.extend(a.encode());
+			encoded
+		}),
+		"Hash" | "H256" => {
+			let bytes = raw_bytes(value)
+				.ok_or_else(|| format!("Expected a 0x prefixed hash, got `{}`", value))??;
+			if bytes.len() != 32 {
+				return Err(format!("Hash `{}` is not 32 bytes long", value));
+			}
+			return Ok(bytes);
+		},
+		"Vec<u8>" | "Bytes" => return match value {
+			Value::String(s) if s.starts_with("0x") =>
+				hex::decode(&s[2..]).map(|b| b.encode()).map_err(|e| format!("Invalid hex `{}`: {}", s, e)),
+			Value::String(s) => Ok(s.as_bytes().encode()),
+			_ => Err(format!("Expected a string, got `{}`", value)),
+		},
+		_ => {},
+	}
+
+	if ty.starts_with('(') && ty.ends_with(')') {
+		let elements = tuple_elements(&ty[1..ty.len() - 1]);
+		let values = value.as_array()
+			.filter(|values| values.len() == elements.len())
+			.ok_or_else(|| format!("Expected an array of {} values for `{}`", elements.len(), ty))?;
+		return elements.iter().zip(values)
+			.map(|(ty, value)| encode_value(ty, value))
+			.collect::<Result<Vec<_>, _>>()
+			.map(|encoded| encoded.concat());
+	}
+
+	if let Some((outer, inner)) = generic(&ty) {
+		match outer {
+			"Compact" => {
+				let inner = normalize(inner);
+				if integer_type(&inner).is_some() {
+					return Ok(Compact(integer(&inner, value)?).encode());
+				}
+			},
+			"Option" => return match value {
+				Value::Null => Ok(vec![0]),
+				value => encode_value(inner, value).map(|encoded| [&[1][..], &encoded].concat()),
+			},
+			"Vec" => {
+				if let Some(values) = value.as_array() {
+					let mut encoded = Compact(values.len() as u32).encode();
+					for value in values {
+						encoded.extend(encode_value(inner, value)?);
+					}
+					return Ok(encoded);
+				}
+			},
+			_ => {},
+		}
+	}
+
+	raw_bytes(value).unwrap_or_else(|| Err(format!(
+		"Values of type `{}` must be given as 0x prefixed SCALE encoded bytes",
+		ty,
+	)))
+}
+
+/// SCALE encode the arguments of a call.
+///
+/// `args` is either an object with the argument names as keys or an array with the arguments
+/// in the order of the call.
+pub fn encode_args(arguments: &[(String, String)], args: &Value) -> Result<Vec<u8>, String> {
+	let values = match args {
+		Value::Array(values) => values.iter().collect::<Vec<_>>(),
+		Value::Object(values) => arguments.iter()
+			.map(|(name, _)| values.get(name).ok_or_else(|| format!("Argument `{}` is missing", name)))
+			.collect::<Result<_, _>>()?,
+		_ => return Err("The arguments must be a JSON array or object".into()),
+	};
+	if values.len() != arguments.len() {
+		return Err(format!("Expected {} arguments, got {}", arguments.len(), values.len()));
+	}
+
+	let mut encoded = Vec::new();
+	for ((name, ty), value) in arguments.iter().zip(values) {
+		encoded.extend(
+			encode_value(ty, value).map_err(|e| format!("Argument `{}`: {}", name, e))?
+		);
+	}
+	Ok(encoded)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use serde_json::json;
+
+	const ALICE: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+
+	#[test]
+	fn primitives_are_encoded() {
+		assert_eq!(encode_value("T::BlockNumber", &json!(5)), Ok(5u32.encode()));
+		assert_eq!(encode_value("Weight", &json!(5)), Ok(5u64.encode()));
+		assert_eq!(encode_value("BalanceOf<T>", &json!("1000000000000000000000")), Ok(1_000_000_000_000_000_000_000u128.encode()));
+		assert_eq!(encode_value("Compact<T::Balance>", &json!(100)), Ok(Compact(100u128).encode()));
+		assert_eq!(encode_value("bool", &json!(true)), Ok(true.encode()));
+		assert_eq!(encode_value("Vec<u8>", &json!("0x0102")), Ok(vec![1u8, 2].encode()));
+		assert_eq!(encode_value("Vec<u8>", &json!("hi")), Ok(b"hi".to_vec().encode()));
+		assert!(encode_value("u8", &json!(256)).is_err());
+		assert!(encode_value("Box<<T as Trait>::Call>", &json!(1)).is_err());
+		assert_eq!(encode_value("Box<<T as Trait>::Call>", &json!("0x0001")), Ok(vec![0, 1]));
+	}
+
+	#[test]
+	fn accounts_and_containers_are_encoded() {
+		let alice = AccountId32::from_ss58check(ALICE).unwrap();
+		assert_eq!(encode_value("T::AccountId", &json!(ALICE)), Ok(alice.encode()));
+		assert_eq!(
+			encode_value("<T::Lookup as StaticLookup>::Source", &json!(ALICE)),
+			Ok([&[0xff][..], alice.as_ref()].concat()),
+		);
+		assert_eq!(
+			encode_value("Vec<T::AccountId>", &json!([ALICE, ALICE])),
+			Ok(vec![alice.clone(), alice.clone()].encode()),
+		);
+		assert_eq!(encode_value("Option<u32>", &json!(null)), Ok(None::<u32>.encode()));
+		assert_eq!(encode_value("Option<u32>", &json!(7)), Ok(Some(7u32).encode()));
+		assert_eq!(
+			encode_value("(u32, Vec<u8>)", &json!([1, "0x02"])),
+			Ok((1u32, vec![2u8]).encode()),
+		);
+	}
+
+	#[test]
+	fn arguments_are_encoded_by_name_or_position() {
+		let arguments = vec![
+			("dest".to_string(), "<T::Lookup as StaticLookup>::Source".to_string()),
+			("value".to_string(), "Compact<T::Balance>".to_string()),
+		];
+		let by_name = encode_args(&arguments, &json!({ "value": 10, "dest": ALICE })).unwrap();
+		let by_position = encode_args(&arguments, &json!([ALICE, 10])).unwrap();
+
+		assert_eq!(by_name, by_position);
+		assert!(encode_args(&arguments, &json!({ "dest": ALICE })).is_err());
+		assert!(encode_args(&arguments, &json!([ALICE])).is_err());
+	}
+}
@@ -0,0 +1,230 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Offline construction of signed transactions.
+//!
+//! The transaction is built in three steps, none of which needs network access:
+//!
+//! 1. `offline-tx payload` encodes a call with help of the runtime metadata and writes the
+//!    unsigned transaction, including the bytes that need to be signed.
+//! 2. The signing payload is signed on an air-gapped machine, e.g. with `subkey sign --hex`.
+//! 3. `offline-tx assemble` combines the unsigned transaction with the signature to the
+//!    signed extrinsic, which can be submitted with `author_submitExtrinsic`.
+
+use std::{fs, path::PathBuf};
+
+use sp_core::{crypto::Ss58Codec, ecdsa, ed25519, sr25519, H256};
+use sp_runtime::{
+	generic::Era, traits::{IdentifyAccount, Verify}, MultiSignature, MultiSigner,
+};
+use structopt::StructOpt;
+
+mod encode;
+mod metadata;
+mod transaction;
+
+use metadata::Metadata;
+use transaction::{AddressFormat, ExtensionParams, UnsignedTransaction};
+
+/// Build and assemble signed transactions without network access.
+#[derive(StructOpt)]
+#[structopt(rename_all = "kebab-case")]
+enum OfflineTx {
+	/// Encode a call and write the unsigned transaction, including the payload to sign.
+	Payload {
+		/// Path to the SCALE encoded runtime metadata, binary or hex encoded.
+		///
+		/// This is the result of the `state_getMetadata` RPC.
+		#[structopt(long)]
+		metadata: PathBuf,
+		/// Name of the pallet, e.g. `Balances`.
+		#[structopt(long)]
+		pallet: String,
+		/// Name of the call, e.g. `transfer`.
+		#[structopt(long)]
+		call: String,
+		/// The arguments of the call, as JSON array or as JSON object with the argument names
+		/// as keys.
+		///
+		/// Accounts are given as SS58 addresses, large numbers as strings and values of types
+		/// that are not known to the tool as 0x prefixed SCALE encoded bytes.
+		#[structopt(long, default_value = "[]")]
+		args: String,
+		/// The nonce of the signing account.
+		#[structopt(long)]
+		nonce: u32,
+		/// The tip for the block author.
+		#[structopt(long, default_value = "0")]
+		tip: u128,
+		/// The genesis hash of the chain, hex encoded.
+		#[structopt(long)]
+		genesis_hash: H256,
+		/// The `spec_version` of the runtime.
+		#[structopt(long)]
+		spec_version: u32,
+		/// The `transaction_version` of the runtime.
+		#[structopt(long)]
+		transaction_version: u32,
+		/// The number of blocks the transaction is valid for. Immortal if not given.
+		#[structopt(long, requires_all = &["era-block", "checkpoint"])]
+		era_period: Option<u64>,
+		/// The number of the block the mortality period starts at.
+		#[structopt(long)]
+		era_block: Option<u64>,
+		/// The hash of the block the mortality period starts at, hex encoded.
+		#[structopt(long)]
+		checkpoint: Option<H256>,
+		/// Where to write the unsigned transaction. Printed if not given.
+		#[structopt(long)]
+		output: Option<PathBuf>,
+	},
+	/// Assemble the signed extrinsic from an unsigned transaction and its signature.
+	Assemble {
+		/// Path to the unsigned transaction, as written by `payload`.
+		#[structopt(long)]
+		unsigned: PathBuf,
+		/// The public key of the signer, SS58 or hex encoded.
+		#[structopt(long)]
+		signer: String,
+		/// The signature of the signing payload, hex encoded.
+		#[structopt(long)]
+		signature: String,
+		/// The signature scheme of the signer. One of `sr25519`, `ed25519` and `ecdsa`.
+		#[structopt(long, default_value = "sr25519")]
+		scheme: String,
+		/// How the runtime expects the address of the signer. One of `indices`, as used by
+		/// the node runtime, and `account-id`.
+		#[structopt(long, default_value = "indices")]
+		address_format: AddressFormat,
+	},
+}
+
+fn read_metadata(path: &PathBuf) -> Result<Metadata, String> {
+	let content = fs::read(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+	let encoded = match std::str::from_utf8(&content).map(str::trim) {
+		Ok(hex) if hex.starts_with("0x") =>
+			hex::decode(&hex[2..]).map_err(|e| format!("Invalid hex metadata: {}", e))?,
+		_ => content,
+	};
+	Metadata::decode(&encoded)
+}
+
+fn decode_hex(name: &str, value: &str) -> Result<Vec<u8>, String> {
+	let value = if value.starts_with("0x") { &value[2..] } else { value };
+	hex::decode(value).map_err(|e| format!("Invalid hex {}: {}", name, e))
+}
+
+fn read_public<P: Ss58Codec + AsMut<[u8]> + Default>(signer: &str) -> Result<P, String> {
+	if let Ok(public) = P::from_ss58check(signer) {
+		return Ok(public);
+	}
+
+	let bytes = decode_hex("signer", signer)?;
+	let mut public = P::default();
+	if public.as_mut().len() != bytes.len() {
+		return Err(format!("The public key of the signer must be {} bytes long", public.as_mut().len()));
+	}
+	public.as_mut().copy_from_slice(&bytes);
+	Ok(public)
+}
+
+fn read_signature<S: AsMut<[u8]> + Default>(signature: &str) -> Result<S, String> {
+	let bytes = decode_hex("signature", signature)?;
+	let mut sig = S::default();
+	if sig.as_mut().len() != bytes.len() {
+		return Err(format!("The signature must be {} bytes long", sig.as_mut().len()));
+	}
+	sig.as_mut().copy_from_slice(&bytes);
+	Ok(sig)
+}
+
+fn run(command: OfflineTx) -> Result<(), String> {
+	match command {
+		OfflineTx::Payload {
+			metadata, pallet, call, args, nonce, tip, genesis_hash, spec_version,
+			transaction_version, era_period, era_block, checkpoint, output,
+		} => {
+			let metadata = read_metadata(&metadata)?;
+			let info = metadata.call(&pallet, &call)?;
+			let args = serde_json::from_str(&args).map_err(|e| format!("Invalid arguments: {}", e))?;
+			let encoded_call = [
+				&[info.pallet_index, info.call_index][..],
+				&encode::encode_args(&info.arguments, &args)?,
+			].concat();
+
+			let (era, checkpoint) = match (era_period, era_block, checkpoint) {
+				(Some(period), Some(block), Some(checkpoint)) => (Era::mortal(period, block), checkpoint),
+				_ => (Era::Immortal, genesis_hash),
+			};
+			let params = ExtensionParams {
+				nonce,
+				tip,
+				era,
+				checkpoint,
+				genesis_hash,
+				spec_version,
+				transaction_version,
+			};
+			let description = format!("{}::{}({})", pallet, call, args);
+			let unsigned = UnsignedTransaction::new(&metadata, description, encoded_call, &params)?;
+
+			let json = serde_json::to_string_pretty(&unsigned).map_err(|e| e.to_string())?;
+			match output {
+				Some(output) => fs::write(&output, json)
+					.map_err(|e| format!("Cannot write {}: {}", output.display(), e))?,
+				None => println!("{}", json),
+			}
+		},
+		OfflineTx::Assemble { unsigned, signer, signature, scheme, address_format } => {
+			let unsigned: UnsignedTransaction = fs::read(&unsigned)
+				.map_err(|e| format!("Cannot read {}: {}", unsigned.display(), e))
+				.and_then(|content| serde_json::from_slice(&content)
+					.map_err(|e| format!("Invalid unsigned transaction: {}", e)))?;
+
+			let (signer, signature): (MultiSigner, MultiSignature) = match scheme.as_str() {
+				"sr25519" => (
+					read_public::<sr25519::Public>(&signer)?.into(),
+					read_signature::<sr25519::Signature>(&signature)?.into(),
+				),
+				"ed25519" => (
+					read_public::<ed25519::Public>(&signer)?.into(),
+					read_signature::<ed25519::Signature>(&signature)?.into(),
+				),
+				"ecdsa" => (
+					read_public::<ecdsa::Public>(&signer)?.into(),
+					read_signature::<ecdsa::Signature>(&signature)?.into(),
+				),
+				_ => return Err(format!("Unknown signature scheme `{}`", scheme)),
+			};
+
+			let account = signer.clone().into_account();
+			if !signature.verify(&unsigned.signing_payload[..], &account) {
+				return Err("The signature does not match the signing payload and the signer".into());
+			}
+
+			let address = transaction::encode_address(signer, address_format);
+			let extrinsic = unsigned.assemble(&address, &signature)?;
+			println!("0x{}", hex::encode(extrinsic));
+		},
+	}
+	Ok(())
+}
+
+fn main() -> Result<(), String> {
+	run(OfflineTx::from_args())
+}
@@ -0,0 +1,123 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! The call and extrinsic related parts of the runtime metadata.
+
+use codec::Decode;
+use frame_metadata::{DecodeDifferent, RuntimeMetadata, RuntimeMetadataPrefixed, META_RESERVED};
+
+/// A dispatchable call of a pallet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CallInfo {
+	/// Index of the pallet in the outer call enum.
+	pub pallet_index: u8,
+	/// Index of the call in the call enum of the pallet.
+	pub call_index: u8,
+	/// Names and type names of the arguments of the call.
+	pub arguments: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct PalletCalls {
+	name: String,
+	calls: Vec<(String, Vec<(String, String)>)>,
+}
+
+/// The parts of the runtime metadata that are needed to build an extrinsic.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Metadata {
+	pallets: Vec<PalletCalls>,
+	/// Version of the extrinsic format.
+	pub extrinsic_version: u8,
+	/// Names of the signed extensions, in the order they appear in the extrinsic.
+	pub signed_extensions: Vec<String>,
+}
+
+impl Metadata {
+	/// Decode the metadata.
+	///
+	/// Accepts SCALE encoded `RuntimeMetadataPrefixed`, as returned by `state_getMetadata`,
+	/// as well as the result of the `Metadata_metadata` runtime call, which is additionally
+	/// length prefixed.
+	pub fn decode(encoded: &[u8]) -> Result<Self, String> {
+		let prefixed = RuntimeMetadataPrefixed::decode(&mut &encoded[..])
+			.ok()
+			.filter(|prefixed| prefixed.0 == META_RESERVED)
+			.or_else(|| {
+				let inner = Vec::<u8>::decode(&mut &encoded[..]).ok()?;
+				RuntimeMetadataPrefixed::decode(&mut &inner[..]).ok()
+			})
+			.filter(|prefixed| prefixed.0 == META_RESERVED)
+			.ok_or_else(|| "Invalid runtime metadata".to_string())?;
+		let metadata = match prefixed.1 {
+			RuntimeMetadata::V11(metadata) => metadata,
+			_ => return Err("Unsupported runtime metadata version".into()),
+		};
+
+		let mut pallets = Vec::new();
+		for module in decoded(metadata.modules)? {
+			// Pallets without calls are not part of the outer call enum.
+			let calls = match module.calls {
+				Some(calls) => decoded(calls)?,
+				None => continue,
+			};
+			let calls = calls.into_iter()
+				.map(|call| {
+					let arguments = decoded(call.arguments)?.into_iter()
+						.map(|argument| Ok((decoded(argument.name)?, decoded(argument.ty)?)))
+						.collect::<Result<_, String>>()?;
+					Ok((decoded(call.name)?, arguments))
+				})
+				.collect::<Result<_, String>>()?;
+			pallets.push(PalletCalls { name: decoded(module.name)?, calls });
+		}
+
+		Ok(Metadata {
+			pallets,
+			extrinsic_version: metadata.extrinsic.version,
+			signed_extensions: metadata.extrinsic.signed_extensions.into_iter()
+				.map(decoded)
+				.collect::<Result<_, _>>()?,
+		})
+	}
+
+	/// Returns the call `call` of the pallet `pallet`.
+	pub fn call(&self, pallet: &str, call: &str) -> Result<CallInfo, String> {
+		let (pallet_index, pallet) = self.pallets.iter()
+			.enumerate()
+			.find(|(_, p)| p.name == pallet)
+			.ok_or_else(|| format!("Unknown pallet `{}`", pallet))?;
+		let (call_index, (_, arguments)) = pallet.calls.iter()
+			.enumerate()
+			.find(|(_, (name, _))| name == call)
+			.ok_or_else(|| format!("Unknown call `{}::{}`", pallet.name, call))?;
+
+		Ok(CallInfo {
+			pallet_index: pallet_index as u8,
+			call_index: call_index as u8,
+			arguments: arguments.clone(),
+		})
+	}
+}
+
+fn decoded<B, O>(value: DecodeDifferent<B, O>) -> Result<O, String> {
+	match value {
+		DecodeDifferent::Decoded(value) => Ok(value),
+		DecodeDifferent::Encode(_) => Err("Runtime metadata is not decoded".into()),
+	}
+}
@@ -0,0 +1,282 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Construction of the signing payload and of the signed extrinsic.
+
+use codec::{Compact, Encode};
+use serde::{Deserialize, Serialize};
+use sp_core::{blake2_256, Bytes, H256};
+use sp_runtime::{
+	generic::Era, traits::IdentifyAccount, MultiSignature, MultiSigner,
+};
+
+use crate::metadata::Metadata;
+
+/// Bit of the extrinsic version byte that marks a signed extrinsic.
+const SIGNED_EXTRINSIC_BIT: u8 = 0b1000_0000;
+
+/// The values of the signed extensions of a transaction.
+#[derive(Debug, Clone)]
+pub struct ExtensionParams {
+	/// The nonce of the signing account.
+	pub nonce: u32,
+	/// The tip for the block author.
+	pub tip: u128,
+	/// The mortality of the transaction.
+	pub era: Era,
+	/// The hash of the block the mortality period starts at, the genesis hash for immortal
+	/// transactions.
+	pub checkpoint: H256,
+	/// The genesis hash of the chain.
+	pub genesis_hash: H256,
+	/// The `spec_version` of the runtime.
+	pub spec_version: u32,
+	/// The `transaction_version` of the runtime.
+	pub transaction_version: u32,
+}
+
+/// Encode the extra data and the additional signed data of the signed extensions.
+///
+/// The extensions known to this tool are the ones of `frame_system` and
+/// `pallet_transaction_payment`.
+pub fn encode_extensions(
+	extensions: &[String],
+	params: &ExtensionParams,
+) -> Result<(Vec<u8>, Vec<u8>), String> {
+	let mut extra = Vec::new();
+	let mut additional_signed = Vec::new();
+	for extension in extensions {
+		match extension.as_str() {
+			"CheckSpecVersion" => params.spec_version.encode_to(&mut additional_signed),
+			"CheckTxVersion" => params.transaction_version.encode_to(&mut additional_signed),
+			"CheckGenesis" => params.genesis_hash.encode_to(&mut additional_signed),
+			"CheckEra" | "CheckMortality" => {
+				params.era.encode_to(&mut extra);
+				params.checkpoint.encode_to(&mut additional_signed);
+			},
+			"CheckNonce" => Compact(params.nonce).encode_to(&mut extra),
+			"CheckWeight" => {},
+			"ChargeTransactionPayment" => Compact(params.tip).encode_to(&mut extra),
+			unknown => return Err(format!("Unknown signed extension `{}`", unknown)),
+		}
+	}
+	Ok((extra, additional_signed))
+}
+
+/// A transaction that waits for its signature.
+///
+/// This is what is carried to the air-gapped machine for signing and back again.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnsignedTransaction {
+	/// Human readable description of the call.
+	pub description: String,
+	/// Version byte of the extrinsic format, without the signed bit.
+	pub extrinsic_version: u8,
+	/// The SCALE encoded call.
+	pub call: Bytes,
+	/// The SCALE encoded extra data of the signed extensions.
+	pub extra: Bytes,
+	/// The SCALE encoded additional signed data of the signed extensions.
+	pub additional_signed: Bytes,
+	/// The bytes that need to be signed.
+	///
+	/// This is the `SignedPayload`, i.e. the call, extra and additional signed data, or its
+	/// blake2-256 hash if it is longer than 256 bytes.
+	pub signing_payload: Bytes,
+}
+
+impl UnsignedTransaction {
+	/// Build the transaction for an encoded call.
+	pub fn new(
+		metadata: &Metadata,
+		description: String,
+		call: Vec<u8>,
+		params: &ExtensionParams,
+	) -> Result<Self, String> {
+		let (extra, additional_signed) = encode_extensions(&metadata.signed_extensions, params)?;
+		let signing_payload = signing_payload(&call, &extra, &additional_signed);
+
+		Ok(UnsignedTransaction {
+			description,
+			extrinsic_version: metadata.extrinsic_version,
+			call: call.into(),
+			extra: extra.into(),
+			additional_signed: additional_signed.into(),
+			signing_payload: signing_payload.into(),
+		})
+	}
+
+	/// Assemble the signed extrinsic.
+	///
+	/// `address` is the encoded address of the signer, see [`encode_address`]. Fails if the
+	/// signing payload doesn't match the call and the signed extensions, as the signature would
+	/// then not be for the extrinsic.
+	pub fn assemble(&self, address: &[u8], signature: &MultiSignature) -> Result<Vec<u8>, String> {
+		if signing_payload(&self.call, &self.extra, &self.additional_signed) != self.signing_payload.0 {
+			return Err("The signing payload does not match the call and the signed extensions".into());
+		}
+
+		let mut extrinsic = vec![self.extrinsic_version | SIGNED_EXTRINSIC_BIT];
+		extrinsic.extend_from_slice(address);
+		signature.encode_to(&mut extrinsic);
+		extrinsic.extend_from_slice(&self.extra);
+		extrinsic.extend_from_slice(&self.call);
+
+		// Extrinsics are encoded as `Vec<u8>`, i.e. with a length prefix.
+		Ok(extrinsic.encode())
+	}
+}
+
+/// Build the bytes that need to be signed, i.e. the `SignedPayload` of the call and of the
+/// signed extensions, or its blake2-256 hash if it is longer than 256 bytes.
+fn signing_payload(call: &[u8], extra: &[u8], additional_signed: &[u8]) -> Vec<u8> {
+	let payload = [call, extra, additional_signed].concat();
+	if payload.len() > 256 {
+		blake2_256(&payload).to_vec()
+	} else {
+		payload
+	}
+}
+
+/// How the runtime expects the address of the signer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AddressFormat {
+	/// The `Address` of the indices pallet, as the node runtime uses it.
+	Indices,
+	/// The plain account ID.
+	AccountId,
+}
+
+impl std::str::FromStr for AddressFormat {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"indices" => Ok(AddressFormat::Indices),
+			"account-id" => Ok(AddressFormat::AccountId),
+			_ => Err(format!("Unknown address format `{}`, expected `indices` or `account-id`", s)),
+		}
+	}
+}
+
+/// Encode the address of the signer.
+pub fn encode_address(signer: MultiSigner, format: AddressFormat) -> Vec<u8> {
+	let account = signer.into_account();
+	match format {
+		AddressFormat::Indices => [&[0xff][..], account.as_ref()].concat(),
+		AddressFormat::AccountId => account.encode(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::Decode;
+	use node_primitives::{Balance, Index};
+	use node_runtime::{BalancesCall, Call, Runtime, SignedPayload, UncheckedExtrinsic, VERSION};
+	use sp_core::{sr25519, Pair};
+	use sp_runtime::AccountId32;
+
+	fn runtime_metadata() -> Metadata {
+		Metadata::decode(&Runtime::metadata().encode()).unwrap()
+	}
+
+	#[test]
+	fn assembles_the_same_extrinsic_as_the_node_runtime() {
+		let metadata = runtime_metadata();
+		let signer = sr25519::Pair::from_string("//Alice", None).unwrap();
+		let dest: AccountId32 = sr25519::Pair::from_string("//Bob", None).unwrap().public().into();
+		let genesis_hash = H256::repeat_byte(7);
+		let checkpoint = H256::repeat_byte(9);
+		let era = Era::mortal(64, 100);
+		let (nonce, tip): (Index, Balance) = (3, 10);
+
+		let info = metadata.call("Balances", "transfer").unwrap();
+		let call = [
+			&[info.pallet_index, info.call_index][..],
+			&crate::encode::encode_args(
+				&info.arguments,
+				&serde_json::json!([dest.to_string(), "1000"]),
+			).unwrap(),
+		].concat();
+		let expected_call = Call::Balances(BalancesCall::transfer(dest.clone().into(), 1000));
+		assert_eq!(call, expected_call.encode());
+
+		let params = ExtensionParams {
+			nonce,
+			tip,
+			era,
+			checkpoint,
+			genesis_hash,
+			spec_version: VERSION.spec_version,
+			transaction_version: VERSION.transaction_version,
+		};
+		let unsigned = UnsignedTransaction::new(&metadata, "transfer".into(), call, &params).unwrap();
+
+		let extra = (
+			frame_system::CheckSpecVersion::<Runtime>::new(),
+			frame_system::CheckTxVersion::<Runtime>::new(),
+			frame_system::CheckGenesis::<Runtime>::new(),
+			frame_system::CheckEra::<Runtime>::from(era),
+			frame_system::CheckNonce::<Runtime>::from(nonce),
+			frame_system::CheckWeight::<Runtime>::new(),
+			pallet_transaction_payment::ChargeTransactionPayment::<Runtime>::from(tip),
+		);
+		let payload = SignedPayload::from_raw(
+			expected_call,
+			extra,
+			(VERSION.spec_version, VERSION.transaction_version, genesis_hash, checkpoint, (), (), ()),
+		);
+		assert_eq!(payload.encode(), unsigned.signing_payload.0);
+
+		let signature: MultiSignature = signer.sign(&unsigned.signing_payload).into();
+		let address = encode_address(signer.public().into(), AddressFormat::Indices);
+		let extrinsic = unsigned.assemble(&address, &signature).unwrap();
+
+		let (call, extra, _) = payload.deconstruct();
+		let expected = UncheckedExtrinsic::new_signed(
+			call,
+			AccountId32::from(signer.public()).into(),
+			signature,
+			extra,
+		);
+		assert_eq!(extrinsic, expected.encode());
+		assert_eq!(UncheckedExtrinsic::decode(&mut &extrinsic[..]).unwrap(), expected);
+
+		// A signing payload that doesn't match the call is rejected.
+		let mut tampered = unsigned.clone();
+		tampered.call.0[2] ^= 1;
+		assert!(tampered.assemble(&address, &signature).is_err());
+	}
+
+	#[test]
+	fn unknown_extensions_are_rejected() {
+		let params = ExtensionParams {
+			nonce: 0,
+			tip: 0,
+			era: Era::Immortal,
+			checkpoint: Default::default(),
+			genesis_hash: Default::default(),
+			spec_version: 1,
+			transaction_version: 1,
+		};
+		assert!(encode_extensions(&["CheckNonce".into()], &params).is_ok());
+		assert!(encode_extensions(&["CheckSomething".into()], &params).is_err());
+	}
+}
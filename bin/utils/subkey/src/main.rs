@@ -20,8 +20,9 @@
 #[cfg(feature = "bench")]
 extern crate test;
 
+use base58::FromBase58;
 use bip39::{Language, Mnemonic, MnemonicType};
-use clap::{App, ArgMatches, SubCommand};
+use clap::{App, Arg, ArgMatches, SubCommand};
 use codec::{Decode, Encode};
 use hex_literal::hex;
 use itertools::Itertools;
@@ -41,6 +42,7 @@ use std::{
 mod rpc;
 mod vanity;
 
+#[derive(Clone, Copy)]
 enum OutputType {
 	Json,
 	Text,
@@ -73,113 +75,112 @@ trait Crypto: Sized {
 	fn public_from_pair(pair: &Self::Pair) -> Self::Public {
 		pair.public()
 	}
-	fn print_from_uri(
+	/// Describe the key behind the given URI.
+	///
+	/// The URI may be a secret phrase, a secret key URI or a public key URI. Public keys can
+	/// be given hex encoded or as SS58 address of any network, in which case the network is
+	/// detected from the address.
+	fn info_from_uri(
 		uri: &str,
 		password: Option<&str>,
 		network_override: Option<Ss58AddressFormat>,
-		output: OutputType,
-	) where
+	) -> Option<serde_json::Value> where
 		<Self::Pair as Pair>::Public: PublicT,
 	{
 		let v = network_override.unwrap_or_default();
 		if let Ok((pair, seed)) = Self::Pair::from_phrase(uri, password) {
 			let public_key = Self::public_from_pair(&pair);
 
-			match output {
-				OutputType::Json => {
-					let json = json!({
-						"secretPhrase": uri,
-						"networkId": String::from(v),
-						"secretSeed": format_seed::<Self>(seed),
-						"publicKey": format_public_key::<Self>(public_key.clone()),
-						"accountId": format_account_id::<Self>(public_key),
-						"ss58Address": Self::ss58_from_pair(&pair),
-					});
-					println!("{}", serde_json::to_string_pretty(&json).expect("Json pretty print failed"));
-				},
-				OutputType::Text => {
-					println!("Secret phrase `{}` is account:\n  \
-						Network ID/version: {}\n  \
-						Secret seed:        {}\n  \
-						Public key (hex):   {}\n  \
-						Account ID:         {}\n  \
-						SS58 Address:       {}",
-						uri,
-						String::from(v),
-						format_seed::<Self>(seed),
-						format_public_key::<Self>(public_key.clone()),
-						format_account_id::<Self>(public_key),
-						Self::ss58_from_pair(&pair),
-					);
-				},
-			}
+			Some(json!({
+				"secretPhrase": uri,
+				"networkId": String::from(v),
+				"secretSeed": format_seed::<Self>(seed),
+				"publicKey": format_public_key::<Self>(public_key.clone()),
+				"accountId": format_account_id::<Self>(public_key),
+				"ss58Address": Self::ss58_from_pair(&pair),
+			}))
 		} else if let Ok((pair, seed)) = Self::Pair::from_string_with_seed(uri, password) {
 			let public_key = Self::public_from_pair(&pair);
 
-			match output {
-				OutputType::Json => {
-					let json = json!({
-						"secretKeyUri": uri,
-						"networkId": String::from(v),
-						"secretSeed": if let Some(seed) = seed { format_seed::<Self>(seed) } else { "n/a".into() },
-						"publicKey": format_public_key::<Self>(public_key.clone()),
-						"accountId": format_account_id::<Self>(public_key),
-						"ss58Address": Self::ss58_from_pair(&pair),
-					});
-					println!("{}", serde_json::to_string_pretty(&json).expect("Json pretty print failed"));
-				},
-				OutputType::Text => {
-					println!("Secret Key URI `{}` is account:\n  \
-						Network ID/version: {}\n  \
-						Secret seed:        {}\n  \
-						Public key (hex):   {}\n  \
-						Account ID:         {}\n  \
-						SS58 Address:       {}",
-						uri,
-						String::from(v),
-						if let Some(seed) = seed { format_seed::<Self>(seed) } else { "n/a".into() },
-						format_public_key::<Self>(public_key.clone()),
-						format_account_id::<Self>(public_key),
-						Self::ss58_from_pair(&pair),
-					);
-				},
-			}
-		} else if let Ok((public_key, v)) =
-			<Self::Pair as Pair>::Public::from_string_with_version(uri)
-		{
+			Some(json!({
+				"secretKeyUri": uri,
+				"networkId": String::from(v),
+				"secretSeed": if let Some(seed) = seed { format_seed::<Self>(seed) } else { "n/a".into() },
+				"publicKey": format_public_key::<Self>(public_key.clone()),
+				"accountId": format_account_id::<Self>(public_key),
+				"ss58Address": Self::ss58_from_pair(&pair),
+			}))
+		} else if let Some((public_key, v)) = read_public_with_version::<Self>(uri) {
 			let v = network_override.unwrap_or(v);
 
-			match output {
-				OutputType::Json => {
-					let json = json!({
-						"publicKeyUri": uri,
-						"networkId": String::from(v),
-						"publicKey": format_public_key::<Self>(public_key.clone()),
-						"accountId": format_account_id::<Self>(public_key.clone()),
-						"ss58Address": public_key.to_ss58check_with_version(v),
-					});
-					println!("{}", serde_json::to_string_pretty(&json).expect("Json pretty print failed"));
-				},
-				OutputType::Text => {
-					println!("Public Key URI `{}` is account:\n  \
-						Network ID/version: {}\n  \
-						Public key (hex):   {}\n  \
-						Account ID:         {}\n  \
-						SS58 Address:       {}",
-						uri,
-						String::from(v),
-						format_public_key::<Self>(public_key.clone()),
-						format_account_id::<Self>(public_key.clone()),
-						public_key.to_ss58check_with_version(v),
-					);
-				},
-			}
+			Some(json!({
+				"publicKeyUri": uri,
+				"networkId": String::from(v),
+				"publicKey": format_public_key::<Self>(public_key.clone()),
+				"accountId": format_account_id::<Self>(public_key.clone()),
+				"ss58Address": public_key.to_ss58check_with_version(v),
+			}))
 		} else {
-			eprintln!("Invalid phrase/URI given");
+			None
+		}
+	}
+
+	fn print_from_uri(
+		uri: &str,
+		password: Option<&str>,
+		network_override: Option<Ss58AddressFormat>,
+		output: OutputType,
+	) where
+		<Self::Pair as Pair>::Public: PublicT,
+	{
+		match Self::info_from_uri(uri, password, network_override) {
+			Some(info) => match output {
+				OutputType::Json => print_json(&info),
+				OutputType::Text => println!("{}", format_key_info(&info)),
+			},
+			None => eprintln!("Invalid phrase/URI given"),
 		}
 	}
 }
 
+/// Format the description of a key, as returned by `Crypto::info_from_uri`, as text.
+fn format_key_info(info: &serde_json::Value) -> String {
+	let field = |name: &str| info[name].as_str().unwrap_or_default().to_string();
+	let (title, uri) = if info["secretPhrase"].is_string() {
+		("Secret phrase", field("secretPhrase"))
+	} else if info["secretKeyUri"].is_string() {
+		("Secret Key URI", field("secretKeyUri"))
+	} else {
+		("Public Key URI", field("publicKeyUri"))
+	};
+
+	let mut text = format!("{} `{}` is account:\n  Network ID/version: {}\n", title, uri, field("networkId"));
+	if info["secretSeed"].is_string() {
+		text.push_str(&format!("  Secret seed:        {}\n", field("secretSeed")));
+	}
+	text.push_str(&format!(
+		"  Public key (hex):   {}\n  \
+		Account ID:         {}\n  \
+		SS58 Address:       {}",
+		field("publicKey"),
+		field("accountId"),
+		field("ss58Address"),
+	));
+	text
+}
+
+fn print_json(json: &serde_json::Value) {
+	println!("{}", serde_json::to_string_pretty(json).expect("Json pretty print failed"));
+}
+
+/// Print `json` or `text`, depending on the requested output type.
+fn print_output(output: OutputType, json: serde_json::Value, text: impl fmt::Display) {
+	match output {
+		OutputType::Json => print_json(&json),
+		OutputType::Text => println!("{}", text),
+	}
+}
+
 struct Ed25519;
 
 impl Crypto for Ed25519 {
@@ -240,7 +241,6 @@ fn get_usage() -> String {
 		[network] -n, --network <network> 'Specify a network. One of {}. Default is {}'
 		[password] -p, --password <password> 'The password for the key'
 		--password-interactive 'You will be prompted for the password for the key.'
-		[output] -o, --output <output> 'Specify an output format. One of text, json. Default is text.'
 	", networks, default_network)
 }
 
@@ -250,6 +250,15 @@ fn get_app<'a, 'b>(usage: &'a str) -> App<'a, 'b> {
 		.about("Utility for generating and restoring with Substrate keys")
 		.version(env!("CARGO_PKG_VERSION"))
 		.args_from_usage(usage)
+		.arg(
+			Arg::with_name("output")
+				.short("o")
+				.long("output-type")
+				.alias("output")
+				.takes_value(true)
+				.value_name("output")
+				.help("Specify an output format. One of text, json. Default is text.")
+		)
 		.subcommands(vec![
 			SubCommand::with_name("generate")
 				.about("Generate a random account")
@@ -263,10 +272,25 @@ fn get_app<'a, 'b>(usage: &'a str) -> App<'a, 'b> {
 			SubCommand::with_name("inspect")
 				.about("Gets a public key and a SS58 address from the provided Secret URI")
 				.args_from_usage("[uri] 'A Key URI to be inspected. May be a secret seed, \
-						secret URI (with derivation paths and password), SS58 or public URI. \
+						secret URI (with derivation paths and password), SS58 address of any \
+						network, hex encoded public key or public URI. \
 						If the value is a file, the file content is used as URI. \
 						If not given, you will be prompted for the URI.'
 				"),
+			SubCommand::with_name("derive")
+				.about("Derive a batch of keys from the provided Secret URI")
+				.args_from_usage("
+					<uri> 'The Key URI to derive from, a secret or a public URI. \
+						If the value is a file, the file content is used as URI.'
+					<path>... 'The derivation paths, e.g. `//hard/soft`. Only soft \
+						derivations are possible for a public URI.'
+				"),
+			SubCommand::with_name("multisig")
+				.about("Derive the account ID of a multisig account of the multisig pallet")
+				.args_from_usage("
+					-t, --threshold <threshold> 'The number of signatories that need to approve.'
+					<signatories>... 'The signatories, as SS58 addresses or hex encoded account IDs.'
+				"),
 			SubCommand::with_name("inspect-node-key")
 				.about("Print the peer ID corresponding to the node key in the given file")
 				.args_from_usage("[file] 'Name of file to read the secret key from'"),
@@ -437,11 +461,57 @@ where
 
 			fs::write(file, secret.as_ref())?;
 
-			println!("{}", peer_id);
+			print_output(output, json!({ "peerId": peer_id.to_string() }), peer_id);
 		}
 		("inspect", Some(matches)) => {
 			C::print_from_uri(&get_uri("uri", &matches)?, password, maybe_network, output);
 		}
+		("derive", Some(matches)) => {
+			let uri = get_uri("uri", &matches)?;
+			let keys = matches.values_of("path")
+				.into_iter()
+				.flatten()
+				.map(|path| {
+					let derived = format!("{}{}", uri, path);
+					C::info_from_uri(&derived, password, maybe_network)
+						.ok_or_else(|| Error::Formatted(format!("Cannot derive `{}`", path)))
+				})
+				.collect::<Result<Vec<_>, _>>()?;
+
+			match output {
+				OutputType::Json => print_json(&serde_json::Value::Array(keys)),
+				OutputType::Text => println!("{}", keys.iter().map(format_key_info).join("\n")),
+			}
+		}
+		("multisig", Some(matches)) => {
+			let threshold = read_required_parameter::<u16>(matches, "threshold")?;
+			let signatories = matches.values_of("signatories")
+				.into_iter()
+				.flatten()
+				.map(parse_account_id)
+				.collect::<Result<Vec<_>, _>>()?;
+			let (account_id, signatories) = multisig_account_id(signatories, threshold)?;
+			let v = maybe_network.unwrap_or_default();
+
+			let json = json!({
+				"threshold": threshold,
+				"signatories": signatories.iter()
+					.map(|s| s.to_ss58check_with_version(v))
+					.collect::<Vec<_>>(),
+				"accountId": format!("0x{}", HexDisplay::from(&account_id.as_ref())),
+				"ss58Address": account_id.to_ss58check_with_version(v),
+			});
+			let text = format!(
+				"Multisig account of {} signatories with threshold {}:\n  \
+				Account ID:         0x{}\n  \
+				SS58 Address:       {}",
+				signatories.len(),
+				threshold,
+				HexDisplay::from(&account_id.as_ref()),
+				account_id.to_ss58check_with_version(v),
+			);
+			print_output(output, json, text);
+		}
 		("inspect-node-key", Some(matches)) => {
 			let file = matches.value_of("file").ok_or(Error::Static("Input file name is required"))?;
 
@@ -451,7 +521,7 @@ where
 			let keypair = libp2p_ed25519::Keypair::from(secret);
 			let peer_id = PublicKey::Ed25519(keypair.public()).into_peer_id();
 
-			println!("{}", peer_id);
+			print_output(output, json!({ "peerId": peer_id.to_string() }), peer_id);
 		}
 		("sign", Some(matches)) => {
 			let suri = get_uri("suri", &matches)?;
@@ -459,7 +529,7 @@ where
 
 			let message = read_message_from_stdin(should_decode)?;
 			let signature = do_sign::<C>(&suri, message, password)?;
-			println!("{}", signature);
+			print_output(output, json!({ "signature": signature }), &signature);
 		}
 		("verify", Some(matches)) => {
 			let uri = get_uri("uri", &matches)?;
@@ -468,8 +538,11 @@ where
 			let message = read_message_from_stdin(should_decode)?;
 			let is_valid_signature = do_verify::<C>(matches, &uri, message)?;
 			if is_valid_signature {
-				println!("Signature verifies correctly.");
+				print_output(output, json!({ "valid": true }), "Signature verifies correctly.");
 			} else {
+				if let OutputType::Json = output {
+					print_json(&json!({ "valid": false }));
+				}
 				return static_err("Signature invalid.");
 			}
 		}
@@ -493,7 +566,7 @@ where
 
 			let extrinsic = create_extrinsic::<C>(function, index, signer, genesis_hash);
 
-			print_extrinsic(extrinsic);
+			print_extrinsic(extrinsic, output);
 		}
 		("sign-transaction", Some(matches)) => {
 			let signer = read_pair::<C>(matches.value_of("suri"), password)?;
@@ -508,7 +581,7 @@ where
 
 			let extrinsic = create_extrinsic::<C>(function, index, signer, genesis_hash);
 
-			print_extrinsic(extrinsic);
+			print_extrinsic(extrinsic, output);
 		}
 		("insert", Some(matches)) => {
 			let suri = get_uri("suri", &matches)?;
@@ -522,11 +595,12 @@ where
 
 			let rpc = rpc::RpcClient::new(node_url.to_string());
 
-			rpc.insert_key(
-				key_type.to_string(),
-				suri,
-				sp_core::Bytes(pair.public().as_ref().to_vec()),
-			);
+			let public = sp_core::Bytes(pair.public().as_ref().to_vec());
+			rpc.insert_key(key_type.to_string(), suri, public.clone());
+
+			if let OutputType::Json = output {
+				print_json(&json!({ "keyType": key_type, "publicKey": public }));
+			}
 		}
 		("moduleid", Some(matches)) => {
 			let id = get_uri("id", &matches)?;
@@ -615,7 +689,7 @@ fn read_genesis_hash(matches: &ArgMatches) -> Result<H256, Error> {
 		h => Decode::decode(&mut &decode_hex(h)?[..])
 			.expect("Invalid genesis hash or unrecognized chain identifier"),
 	};
-	println!(
+	eprintln!(
 		"Using a genesis hash of {}",
 		HexDisplay::from(&genesis_hash.as_ref())
 	);
@@ -664,6 +738,10 @@ where
 
 fn read_account_id(matched_uri: Option<&str>) -> AccountId {
 	let uri = matched_uri.expect("parameter is required; thus it can't be None; qed");
+	parse_account_id(uri).unwrap_or_else(|e| panic!("{}", e))
+}
+
+fn parse_account_id(uri: &str) -> Result<AccountId, Error> {
 	let uri = if uri.starts_with("0x") {
 		&uri[2..]
 	} else {
@@ -671,13 +749,76 @@ fn read_account_id(matched_uri: Option<&str>) -> AccountId {
 	};
 	if let Ok(data_vec) = hex::decode(uri) {
 		AccountId::try_from(data_vec.as_slice())
-			.expect("Invalid hex length for account ID; should be 32 bytes")
+			.map_err(|_| Error::Static("Invalid hex length for account ID; should be 32 bytes"))
 	} else {
-		AccountId::from_ss58check(uri).ok()
-			.expect("Invalid SS58-check address given for account ID.")
+		read_public_with_version::<Sr25519>(uri)
+			.map(|(public, _)| public.into())
+			.ok_or(Error::Static("Invalid SS58-check address given for account ID."))
 	}
 }
 
+/// Read a public key, given hex encoded or as SS58 address with optional derivation path.
+///
+/// Unlike `Ss58Codec::from_string_with_version`, SS58 addresses with a version that is not
+/// known to `Ss58AddressFormat` are accepted as well, the version is returned as custom format.
+fn read_public_with_version<C: Crypto>(uri: &str) -> Option<(PublicOf<C>, Ss58AddressFormat)> {
+	if uri.starts_with("0x") {
+		let data = hex::decode(&uri[2..]).ok()?;
+		let mut public = PublicOf::<C>::default();
+		if data.len() != public.as_ref().len() {
+			return None;
+		}
+		public.as_mut().copy_from_slice(&data);
+		return Some((public, Ss58AddressFormat::default()));
+	}
+
+	if let Ok(public) = PublicOf::<C>::from_string_with_version(uri) {
+		return Some(public);
+	}
+
+	let data = uri.from_base58().ok()?;
+	let mut public = PublicOf::<C>::default();
+	let len = public.as_ref().len();
+	if data.len() != len + 3 {
+		return None;
+	}
+
+	let mut hasher = blake2_rfc::blake2b::Blake2b::new(64);
+	hasher.update(b"SS58PRE");
+	hasher.update(&data[..len + 1]);
+	if data[len + 1..] != hasher.finalize().as_bytes()[..2] {
+		return None;
+	}
+
+	public.as_mut().copy_from_slice(&data[1..len + 1]);
+	Some((public, Ss58AddressFormat::Custom(data[0])))
+}
+
+/// Derive the account ID of a multisig account, the same way `pallet_multisig` does.
+///
+/// Returns the account ID together with the sorted and deduplicated signatories.
+fn multisig_account_id(
+	mut signatories: Vec<AccountId>,
+	threshold: u16,
+) -> Result<(AccountId, Vec<AccountId>), Error> {
+	signatories.sort();
+	signatories.dedup();
+
+	if threshold < 2 {
+		return Err(Error::Static("The threshold of a multisig account must be at least 2"));
+	}
+	if usize::from(threshold) > signatories.len() {
+		return Err(Error::Formatted(format!(
+			"The threshold {} is higher than the number of distinct signatories {}",
+			threshold,
+			signatories.len(),
+		)));
+	}
+
+	let account_id = pallet_multisig::Module::<Runtime>::multi_account_id(&signatories, threshold);
+	Ok((account_id, signatories))
+}
+
 fn read_pair<C: Crypto>(
 	matched_suri: Option<&str>,
 	password: Option<&str>,
@@ -752,8 +893,9 @@ fn create_extrinsic<C: Crypto>(
 	)
 }
 
-fn print_extrinsic(extrinsic: UncheckedExtrinsic) {
-	println!("0x{}", HexDisplay::from(&extrinsic.encode()));
+fn print_extrinsic(extrinsic: UncheckedExtrinsic, output: OutputType) {
+	let encoded = format!("0x{}", HexDisplay::from(&extrinsic.encode()));
+	print_output(output, json!({ "extrinsic": encoded }), &encoded);
 }
 
 fn print_usage(matches: &ArgMatches) {
@@ -809,6 +951,62 @@ mod tests {
 		test_generate_sign_verify::<Sr25519>();
 	}
 
+	#[test]
+	fn multisig_account_id_does_not_depend_on_order() {
+		let alice = parse_account_id("5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY").unwrap();
+		let bob = parse_account_id("5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty").unwrap();
+		let charlie = parse_account_id("5FLSigC9HGRKVhB9FiEo4Y3koPsNmBmLJbpXg2mp1hXcS59Y").unwrap();
+
+		let (account_id, signatories) = multisig_account_id(
+			vec![charlie.clone(), alice.clone(), bob.clone(), alice.clone()],
+			2,
+		).unwrap();
+		let (same_account_id, _) = multisig_account_id(
+			vec![alice.clone(), bob.clone(), charlie.clone()],
+			2,
+		).unwrap();
+
+		assert_eq!(signatories.len(), 3);
+		assert_eq!(account_id, same_account_id);
+		assert_eq!(
+			account_id,
+			pallet_multisig::Module::<Runtime>::multi_account_id(&signatories, 2),
+		);
+		assert_ne!(account_id, multisig_account_id(signatories.clone(), 3).unwrap().0);
+		assert!(multisig_account_id(signatories.clone(), 1).is_err());
+		assert!(multisig_account_id(signatories, 4).is_err());
+	}
+
+	#[test]
+	fn public_keys_of_any_network_are_detected() {
+		let public = Sr25519::public_from_pair(&Sr25519::pair_from_suri("//Alice", None));
+
+		let kusama = public.to_ss58check_with_version(Ss58AddressFormat::KusamaAccount);
+		let (detected, version) = read_public_with_version::<Sr25519>(&kusama).unwrap();
+		assert_eq!((detected, version), (public.clone(), Ss58AddressFormat::KusamaAccount));
+
+		let custom = public.to_ss58check_with_version(Ss58AddressFormat::Custom(200));
+		let (detected, version) = read_public_with_version::<Sr25519>(&custom).unwrap();
+		assert_eq!((detected, version), (public.clone(), Ss58AddressFormat::Custom(200)));
+
+		let hex = format_public_key::<Sr25519>(public.clone());
+		assert_eq!(read_public_with_version::<Sr25519>(&hex).unwrap().0, public);
+		assert!(read_public_with_version::<Sr25519>("0x1234").is_none());
+	}
+
+	#[test]
+	fn key_info_is_formatted_as_text() {
+		let info = Sr25519::info_from_uri("//Alice", None, None).unwrap();
+		assert_eq!(info["ss58Address"], "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY");
+
+		let text = format_key_info(&info);
+		assert!(text.starts_with("Secret Key URI `//Alice` is account:"));
+		assert!(text.ends_with("SS58 Address:       5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY"));
+
+		let derived = Sr25519::info_from_uri("//Alice//stash", None, None).unwrap();
+		assert_ne!(derived["publicKey"], info["publicKey"]);
+	}
+
 	#[test]
 	fn should_work() {
 		let s = "0123456789012345678901234567890123456789012345678901234567890123";
@@ -0,0 +1,116 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Index of the extrinsics of the canonical chain by hash and by account.
+
+use codec::{Encode, Decode};
+use sp_runtime::traits::{Block as BlockT, NumberFor};
+
+/// Location of an extrinsic in the canonical chain.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct IndexedExtrinsic<Hash, Number> {
+	/// Hash of the extrinsic.
+	pub hash: Hash,
+	/// Hash of the block that includes the extrinsic.
+	pub block_hash: Hash,
+	/// Number of the block that includes the extrinsic.
+	pub block_number: Number,
+	/// Index of the extrinsic in the block body.
+	pub index: u32,
+}
+
+/// An extrinsic of a block that is about to be indexed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtrinsicIndexEntry<Hash> {
+	/// Hash of the extrinsic.
+	pub hash: Hash,
+	/// Index of the extrinsic in the block body.
+	pub index: u32,
+	/// SCALE encoded id of the account that signed the extrinsic.
+	pub signer: Option<Vec<u8>>,
+	/// SCALE encoded ids of all accounts touched by the extrinsic, other than the signer.
+	pub accounts: Vec<Vec<u8>>,
+}
+
+/// Which relation of an account to an extrinsic is queried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountRelation {
+	/// The account signed the extrinsic.
+	Signer,
+	/// The account signed the extrinsic or is otherwise touched by it.
+	Touched,
+}
+
+/// Persistent index of the extrinsics of the canonical chain.
+///
+/// Blocks are indexed in order of the chain and reverted in reverse order, so the index
+/// always reflects a single chain that ends at `last_indexed`.
+pub trait ExtrinsicIndex<Block: BlockT>: Send + Sync {
+	/// Returns the last block that was indexed.
+	fn last_indexed(&self) -> sp_blockchain::Result<Option<(Block::Hash, NumberFor<Block>)>>;
+
+	/// Index the extrinsics of a block that is a child of `last_indexed`.
+	fn index_block(
+		&self,
+		hash: Block::Hash,
+		number: NumberFor<Block>,
+		extrinsics: Vec<ExtrinsicIndexEntry<Block::Hash>>,
+	) -> sp_blockchain::Result<()>;
+
+	/// Remove the extrinsics of `last_indexed` from the index, making `parent` the last
+	/// indexed block.
+	fn revert_block(
+		&self,
+		hash: Block::Hash,
+		parent: (Block::Hash, NumberFor<Block>),
+	) -> sp_blockchain::Result<()>;
+
+	/// Returns the number of the last block that was pruned.
+	fn last_pruned(&self) -> sp_blockchain::Result<Option<NumberFor<Block>>>;
+
+	/// Remove the hash lookups of the extrinsics of a finalized block.
+	///
+	/// The extrinsics stay in the lists of their accounts, but the block can no longer be
+	/// reverted.
+	fn prune_block(&self, hash: Block::Hash, number: NumberFor<Block>) -> sp_blockchain::Result<()>;
+
+	/// Returns the location of the extrinsic with the given hash.
+	fn extrinsic_by_hash(
+		&self,
+		hash: &Block::Hash,
+	) -> sp_blockchain::Result<Option<IndexedExtrinsic<Block::Hash, NumberFor<Block>>>>;
+
+	/// Returns the number of extrinsics that are related to the given account.
+	fn account_extrinsics_count(
+		&self,
+		account: &[u8],
+		relation: AccountRelation,
+	) -> sp_blockchain::Result<u64>;
+
+	/// Returns up to `count` extrinsics related to the given account, newest first.
+	///
+	/// `before` is the position in the list of the account's extrinsics to start at
+	/// (exclusive), `None` starts at the newest extrinsic.
+	fn account_extrinsics(
+		&self,
+		account: &[u8],
+		relation: AccountRelation,
+		before: Option<u64>,
+		count: u32,
+	) -> sp_blockchain::Result<Vec<(u64, IndexedExtrinsic<Block::Hash, NumberFor<Block>>)>>;
+}
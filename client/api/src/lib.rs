@@ -22,6 +22,7 @@ pub mod call_executor;
 pub mod client;
 pub mod cht;
 pub mod execution_extensions;
+pub mod extrinsic_index;
 pub mod in_mem;
 pub mod light;
 pub mod leaves;
@@ -36,6 +37,7 @@ pub use client::*;
 pub use light::*;
 pub use notifications::*;
 pub use proof_provider::*;
+pub use extrinsic_index::{ExtrinsicIndex, ExtrinsicIndexEntry, IndexedExtrinsic, AccountRelation};
 
 pub use sp_state_machine::{StorageProof, ExecutionStrategy};
 
@@ -45,7 +45,9 @@
 //! When run as a sentry node, the authority discovery module does not
 //! publish any addresses to the DHT but still discovers validators and
 //! sentry nodes of validators, i.e. only step 2 (Discovers other authorities)
-//! is executed.
+//! is executed. In addition, a sentry node re-publishes any address record it
+//! finds for the validator it is guarding, so that the record remains available
+//! even while the validator itself only peers with its sentries.
 
 use std::collections::{HashMap, HashSet};
 use std::convert::TryInto;
@@ -433,7 +435,7 @@ where
 
 		let local_peer_id = self.network.local_peer_id();
 
-		let remote_addresses: Vec<Multiaddr> = values.into_iter()
+		let remote_addresses: Vec<Multiaddr> = values.iter()
 			.map(|(_k, v)| {
 				let schema::SignedAuthorityAddresses { signature, addresses } =
 					schema::SignedAuthorityAddresses::decode(v.as_slice())
@@ -459,6 +461,19 @@ where
 			.collect::<Result<Vec<Vec<Multiaddr>>>>()?
 			.into_iter()
 			.flatten()
+			.collect();
+
+		// A sentry node that is listed in the addresses of an authority guards said authority.
+		let guards_authority = matches!(self.role, Role::Sentry) &&
+			remote_addresses.iter().any(|addr: &Multiaddr| addr.iter().any(|protocol| {
+				match protocol {
+					multiaddr::Protocol::P2p(hash) => PeerId::from_multihash(hash)
+						.map_or(false, |peer_id| peer_id == local_peer_id),
+					_ => false,
+				}
+			}));
+
+		let remote_addresses: Vec<Multiaddr> = remote_addresses.into_iter()
 			// Ignore own addresses.
 			.filter(|addr| !addr.iter().any(|protocol| {
 				// Parse to PeerId first as Multihashes of old and new PeerId
@@ -479,6 +494,19 @@ where
 			}))
 			.collect();
 
+		// In order for the guarded authority to not have to reach out to the Dht itself, its
+		// sentry nodes republish the (signed) addresses from their own position in the Dht.
+		if guards_authority {
+			debug!(
+				target: LOG_TARGET,
+				"Republishing addresses of guarded authority {:?}.", authority_id,
+			);
+
+			for (key, value) in values {
+				self.network.put_value(key, value);
+			}
+		}
+
 		if !remote_addresses.is_empty() {
 			self.addr_cache.insert(authority_id.clone(), remote_addresses);
 			if let Some(metrics) = &self.metrics {
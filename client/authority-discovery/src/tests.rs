@@ -544,7 +544,7 @@ fn never_add_own_address_to_priority_group() {
 		None,
 	);
 
-	sentry_authority_discovery.handle_dht_value_found_event(vec![dht_event]).unwrap();
+	sentry_authority_discovery.handle_dht_value_found_event(vec![dht_event.clone()]).unwrap();
 
 	assert_eq!(
 		sentry_network.set_priority_group_call.lock().unwrap().len(), 1,
@@ -559,4 +559,10 @@ fn never_add_own_address_to_priority_group() {
 		),
 		"Expect authority discovery to only add `random_multiaddr`."
 	);
+
+	assert_eq!(
+		*sentry_network.put_value_call.lock().unwrap(),
+		vec![dht_event],
+		"Expect sentry node to republish the addresses of the authority it is guarding.",
+	);
 }
@@ -174,6 +174,22 @@ arg_enum! {
 	}
 }
 
+arg_enum! {
+	/// Which peers the transactions of the local pool are gossiped to.
+	#[allow(missing_docs)]
+	#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+	pub enum TransactionPropagationMode {
+		// Gossip transactions to all the full nodes we're connected to.
+		All,
+		// Only gossip transactions to reserved peers.
+		ReservedOnly,
+		// Gossip transactions to all the full nodes, within a per-peer rate limit.
+		RateLimited,
+		// Never gossip transactions.
+		Disabled,
+	}
+}
+
 /// Default value for the `--execution-syncing` parameter.
 pub const DEFAULT_EXECUTION_SYNCING: ExecutionStrategy = ExecutionStrategy::NativeElseWasm;
 /// Default value for the `--execution-import-block` parameter.
@@ -0,0 +1,88 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+use crate::{
+	CliConfiguration, error,
+	params::{DatabaseParams, PruningParams, SharedParams},
+};
+use log::info;
+use sc_service::{Configuration, chain_ops::import_state_snapshot};
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT};
+use std::{fs, path::PathBuf};
+use structopt::StructOpt;
+
+/// The `import-state-snapshot` command used to initialize an empty database from a state
+/// snapshot.
+///
+/// The node starts at the block of the snapshot. Neither the bodies of the earlier blocks nor
+/// their state are available, including the genesis state. Consensus engines that read the
+/// genesis state or rely on data of earlier blocks, like GRANDPA and BABE, need their aux data
+/// in the snapshot, which the node passes as required keys.
+#[derive(Debug, StructOpt)]
+pub struct ImportStateSnapshotCmd {
+	/// Input file.
+	#[structopt(parse(from_os_str))]
+	pub input: PathBuf,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub pruning_params: PruningParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+impl ImportStateSnapshotCmd {
+	/// Run the `import-state-snapshot` command
+	///
+	/// The import is refused if the snapshot lacks any of the `required_aux` keys of the
+	/// consensus engines.
+	pub fn run<B: BlockT>(&self, config: Configuration, required_aux: &[&[u8]]) -> error::Result<()> {
+		let file = fs::File::open(&self.input)?;
+
+		let backend = sc_service::new_full_backend::<B>(&config)?;
+		let header = import_state_snapshot(
+			&backend,
+			config.chain_spec.as_storage_builder(),
+			required_aux,
+			file,
+		)?;
+		info!("Imported the state of #{} ({})", header.number(), header.hash());
+
+		Ok(())
+	}
+}
+
+impl CliConfiguration for ImportStateSnapshotCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
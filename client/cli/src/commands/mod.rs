@@ -19,7 +19,9 @@ mod build_spec_cmd;
 mod check_block_cmd;
 mod export_blocks_cmd;
 mod export_state_cmd;
+mod export_state_snapshot_cmd;
 mod import_blocks_cmd;
+mod import_state_snapshot_cmd;
 mod purge_chain_cmd;
 mod revert_cmd;
 mod run_cmd;
@@ -28,7 +30,9 @@ pub use self::build_spec_cmd::BuildSpecCmd;
 pub use self::check_block_cmd::CheckBlockCmd;
 pub use self::export_blocks_cmd::ExportBlocksCmd;
 pub use self::export_state_cmd::ExportStateCmd;
+pub use self::export_state_snapshot_cmd::ExportStateSnapshotCmd;
 pub use self::import_blocks_cmd::ImportBlocksCmd;
+pub use self::import_state_snapshot_cmd::ImportStateSnapshotCmd;
 pub use self::purge_chain_cmd::PurgeChainCmd;
 pub use self::revert_cmd::RevertCmd;
 pub use self::run_cmd::RunCmd;
@@ -62,6 +66,13 @@ pub enum Subcommand {
 
 	/// Export state as raw chain spec.
 	ExportState(ExportStateCmd),
+
+	/// Export the state of a block as binary state snapshot.
+	ExportStateSnapshot(ExportStateSnapshotCmd),
+
+	/// Initialize an empty database from a state snapshot. The genesis state is not available
+	/// afterwards, consensus engines need their data in the snapshot.
+	ImportStateSnapshot(ImportStateSnapshotCmd),
 }
 
 // TODO: move to config.rs?
@@ -372,6 +383,12 @@ macro_rules! substrate_cli_subcommands {
 				}
 			}
 
+			fn grandpa_voting_rules(&self) -> $crate::Result<Vec<String>> {
+				match self {
+					$($enum::$variant(cmd) => cmd.grandpa_voting_rules()),*
+				}
+			}
+
 			fn dev_key_seed(&self, is_dev: bool) -> $crate::Result<::std::option::Option<String>> {
 				match self {
 					$($enum::$variant(cmd) => cmd.dev_key_seed(is_dev)),*
@@ -413,5 +430,6 @@ macro_rules! substrate_cli_subcommands {
 }
 
 substrate_cli_subcommands!(
-	Subcommand => BuildSpec, ExportBlocks, ImportBlocks, CheckBlock, Revert, PurgeChain, ExportState
+	Subcommand => BuildSpec, ExportBlocks, ImportBlocks, CheckBlock, Revert, PurgeChain, ExportState,
+	ExportStateSnapshot, ImportStateSnapshot
 );
@@ -27,7 +27,10 @@ use crate::params::TransactionPoolParams;
 use crate::CliConfiguration;
 use regex::Regex;
 use sc_service::{
-	config::{BasePath, MultiaddrWithPeerId, PrometheusConfig, TransactionPoolOptions},
+	config::{
+		BasePath, ExtrinsicIndexConfig, MultiaddrWithPeerId, PrometheusConfig,
+		TransactionPoolOptions,
+	},
 	ChainSpec, Role,
 };
 use sc_telemetry::TelemetryEndpoints;
@@ -68,6 +71,14 @@ pub struct RunCmd {
 	#[structopt(long)]
 	pub no_grandpa: bool,
 
+	/// Select a GRANDPA voting rule restricting the blocks this node votes on.
+	///
+	/// Can be given multiple times, the rules are applied in the given order. One of
+	/// `before-best:<blocks>`, `three-quarters`, `pause-at:<block>`, `seen-by-peers:<percent>`
+	/// or `runtime-pause`. Defaults to `before-best:2` followed by `three-quarters`.
+	#[structopt(long = "grandpa-voting-rule", value_name = "RULE")]
+	pub grandpa_voting_rules: Vec<String>,
+
 	/// Experimental: Run in light client mode.
 	#[structopt(long = "light", conflicts_with = "sentry")]
 	pub light: bool,
@@ -234,6 +245,20 @@ pub struct RunCmd {
 	#[structopt(long = "force-authoring")]
 	pub force_authoring: bool,
 
+	/// Index the extrinsics of the canonical chain by hash and by account.
+	///
+	/// The index is built from the blocks imported while this flag is set and is exposed
+	/// through the `index_*` RPC methods.
+	#[structopt(long = "index-extrinsics")]
+	pub index_extrinsics: bool,
+
+	/// Number of finalized blocks whose extrinsics can be looked up by hash.
+	///
+	/// Extrinsics of older blocks are removed from the lookup by hash, but stay in the
+	/// extrinsic lists of their accounts.
+	#[structopt(long = "index-extrinsics-blocks", value_name = "COUNT", default_value = "100800")]
+	pub index_extrinsics_blocks: u32,
+
 	#[allow(missing_docs)]
 	#[structopt(flatten)]
 	pub keystore_params: KeystoreParams,
@@ -255,6 +280,13 @@ pub struct RunCmd {
 	)]
 	pub sentry_nodes: Vec<MultiaddrWithPeerId>,
 
+	/// Only connect to the nodes passed with --sentry-nodes (and reserved nodes).
+	///
+	/// Bootnodes, mDNS and random DHT queries are not used, and incoming connections from any
+	/// other node are refused.
+	#[structopt(long = "sentry-nodes-only", requires = "sentry-nodes")]
+	pub sentry_nodes_only: bool,
+
 	/// Run a temporary node.
 	///
 	/// A temporary directory will be created to store the configuration and will be deleted
@@ -367,6 +399,7 @@ impl CliConfiguration for RunCmd {
 		} else if is_authority {
 			sc_service::Role::Authority {
 				sentry_nodes: self.sentry_nodes.clone(),
+				sentries_only: self.sentry_nodes_only,
 			}
 		} else if !self.sentry.is_empty() {
 			sc_service::Role::Sentry {
@@ -402,6 +435,17 @@ impl CliConfiguration for RunCmd {
 		Ok(self.no_grandpa)
 	}
 
+	fn grandpa_voting_rules(&self) -> Result<Vec<String>> {
+		Ok(self.grandpa_voting_rules.clone())
+	}
+
+	fn extrinsic_index(&self) -> Result<ExtrinsicIndexConfig> {
+		Ok(ExtrinsicIndexConfig {
+			enabled: self.index_extrinsics,
+			hash_retention: Some(self.index_extrinsics_blocks),
+		})
+	}
+
 	fn rpc_ws_max_connections(&self) -> Result<Option<usize>> {
 		Ok(self.ws_max_connections)
 	}
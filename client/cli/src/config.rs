@@ -27,8 +27,8 @@ use crate::{
 use names::{Generator, Name};
 use sc_client_api::execution_extensions::ExecutionStrategies;
 use sc_service::config::{
-	BasePath, Configuration, DatabaseConfig, ExtTransport, KeystoreConfig, NetworkConfiguration,
-	NodeKeyConfig, OffchainWorkerConfig, PrometheusConfig, PruningMode, Role, RpcMethods,
+	BasePath, Configuration, DatabaseConfig, ExtTransport, ExtrinsicIndexConfig, KeystoreConfig,
+	NetworkConfiguration, NodeKeyConfig, OffchainWorkerConfig, PrometheusConfig, PruningMode, Role, RpcMethods,
 	TaskExecutor, TelemetryEndpoints, TransactionPoolOptions, WasmExecutionMethod,
 };
 use sc_service::{ChainSpec, TracingReceiver};
@@ -351,6 +351,20 @@ pub trait CliConfiguration: Sized {
 		Ok(Default::default())
 	}
 
+	/// Get the GRANDPA voting rules selected by the user
+	///
+	/// By default this is empty, i.e. the default rules are used.
+	fn grandpa_voting_rules(&self) -> Result<Vec<String>> {
+		Ok(Default::default())
+	}
+
+	/// Get the extrinsic index configuration from the current object
+	///
+	/// By default the extrinsics are not indexed.
+	fn extrinsic_index(&self) -> Result<ExtrinsicIndexConfig> {
+		Ok(Default::default())
+	}
+
 	/// Get the development key seed from the current object
 	///
 	/// By default this is `None`.
@@ -466,6 +480,8 @@ pub trait CliConfiguration: Sized {
 			offchain_worker: self.offchain_worker(&role)?,
 			force_authoring: self.force_authoring()?,
 			disable_grandpa: self.disable_grandpa()?,
+			grandpa_voting_rules: self.grandpa_voting_rules()?,
+			extrinsic_index: self.extrinsic_index()?,
 			dev_key_seed: self.dev_key_seed(is_dev)?,
 			tracing_targets: self.tracing_targets()?,
 			tracing_receiver: self.tracing_receiver()?,
@@ -16,9 +16,13 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
+use crate::arg_enums::TransactionPropagationMode;
 use crate::params::node_key_params::NodeKeyParams;
 use sc_network::{
-	config::{NetworkConfiguration, NodeKeyConfig, NonReservedPeerMode, TransportConfig},
+	config::{
+		NetworkConfiguration, NodeKeyConfig, NonReservedPeerMode, RateLimitedProtocol,
+		TransactionPropagation, TransportConfig, DEFAULT_BANNED_THRESHOLD,
+	},
 	multiaddr::Protocol,
 };
 use sc_service::{ChainSpec, config::{Multiaddr, MultiaddrWithPeerId}};
@@ -102,6 +106,47 @@ pub struct NetworkParams {
 	/// By default this option is true for `--dev` and false otherwise.
 	#[structopt(long)]
 	pub discover_local: bool,
+
+	/// Reputation under which peers are disconnected and refused.
+	///
+	/// Reputations range from -2147483648 to 2147483647.
+	#[structopt(long = "reputation-ban-threshold", value_name = "REPUTATION", allow_hyphen_values = true)]
+	pub reputation_ban_threshold: Option<i32>,
+
+	/// Limit the upload rate of the answers to the requests of a protocol.
+	///
+	/// Given as `PROTOCOL=BYTES`, where `PROTOCOL` is one of `block-requests`,
+	/// `finality-proof-requests` or `light-client-requests` and `BYTES` is the maximum number of
+	/// bytes per second. The rate is shared equally between the peers being served.
+	///
+	/// Gossip protocols, such as GRANDPA, can't be limited. See `--transaction-propagation` for
+	/// limiting transactions.
+	#[structopt(
+		long = "upload-rate-limit",
+		value_name = "PROTOCOL=BYTES",
+		parse(try_from_str = parse_upload_rate_limit)
+	)]
+	pub upload_rate_limits: Vec<(RateLimitedProtocol, u64)>,
+
+	/// Which peers the transactions of the local pool are gossiped to.
+	///
+	/// Transactions submitted with gossiping disabled are never propagated, whatever the mode.
+	#[structopt(
+		long = "transaction-propagation",
+		value_name = "MODE",
+		possible_values = &TransactionPropagationMode::variants(),
+		case_insensitive = true,
+		default_value = "All"
+	)]
+	pub transaction_propagation: TransactionPropagationMode,
+
+	/// Maximum average number of transactions sent per second to each peer when
+	/// `--transaction-propagation` is `RateLimited`.
+	///
+	/// Transactions are propagated every few seconds, each time sending up to the number of
+	/// transactions allowed since the previous time. The others are sent later.
+	#[structopt(long = "transaction-rate-limit", value_name = "COUNT", default_value = "50")]
+	pub transaction_rate_limit: u32,
 }
 
 impl NetworkParams {
@@ -160,6 +205,32 @@ impl NetworkParams {
 			},
 			max_parallel_downloads: self.max_parallel_downloads,
 			allow_non_globals_in_dht: self.discover_local || is_dev,
+			reputation_ban_threshold: self.reputation_ban_threshold
+				.unwrap_or(DEFAULT_BANNED_THRESHOLD),
+			upload_rate_limits: self.upload_rate_limits.iter().cloned().collect(),
+			transaction_propagation: match self.transaction_propagation {
+				TransactionPropagationMode::All => TransactionPropagation::All,
+				TransactionPropagationMode::ReservedOnly => TransactionPropagation::ReservedOnly,
+				TransactionPropagationMode::RateLimited =>
+					TransactionPropagation::RateLimited(self.transaction_rate_limit),
+				TransactionPropagationMode::Disabled => TransactionPropagation::Disabled,
+			},
 		}
 	}
 }
+
+fn parse_upload_rate_limit(s: &str) -> Result<(RateLimitedProtocol, u64), String> {
+	let mut parts = s.splitn(2, '=');
+	let protocol = parts.next().unwrap_or_default();
+	let protocol = RateLimitedProtocol::parse(protocol)
+		.ok_or_else(|| format!("Unknown rate limited protocol: {}", protocol))?;
+	let rate = parts.next()
+		.ok_or_else(|| "Expected PROTOCOL=BYTES".to_string())?
+		.parse::<u64>()
+		.map_err(|e| format!("Invalid upload rate: {}", e))?;
+	if rate == 0 {
+		return Err("Upload rate must be greater than zero".into());
+	}
+
+	Ok((protocol, rate))
+}
@@ -24,7 +24,7 @@
 //! a reasonable abstraction.
 
 use structopt::StructOpt;
-use sc_service::config::OffchainWorkerConfig;
+use sc_service::config::{OffchainWorkerConfig, OffchainWorkerOptions};
 use sc_network::config::Role;
 
 use crate::error;
@@ -55,6 +55,29 @@ pub struct OffchainWorkerParams {
 		value_name = "ENABLE_OFFCHAIN_INDEXING"
 	)]
 	pub indexing_enabled: bool,
+
+	/// Run the offchain workers only for every `PERIOD`-th block.
+	#[structopt(long = "offchain-worker-period", value_name = "PERIOD", default_value = "1")]
+	pub period: u32,
+
+	/// Skip the offchain workers of a block while those of a previous block are running.
+	#[structopt(long = "offchain-worker-skip-if-running")]
+	pub skip_if_running: bool,
+
+	/// Maximum number of blocks whose offchain workers run at the same time.
+	///
+	/// The offchain workers of further blocks are skipped.
+	#[structopt(long = "offchain-worker-max-concurrent", value_name = "COUNT")]
+	pub max_concurrent: Option<usize>,
+
+	/// Do not run the offchain worker of the given pallet, as named in the runtime's
+	/// `construct_runtime!`, e.g. `ImOnline`.
+	///
+	/// Workers can be enabled and disabled at run time with the `offchain_setWorkerEnabled`
+	/// RPC method. Only supported by runtimes that implement version 3 of the offchain worker
+	/// API.
+	#[structopt(long = "offchain-worker-disable", value_name = "PALLET")]
+	pub disabled_pallets: Vec<String>,
 }
 
 impl OffchainWorkerParams {
@@ -73,6 +96,22 @@ impl OffchainWorkerParams {
 
 		let indexing_enabled = enabled && self.indexing_enabled;
 
-		Ok(OffchainWorkerConfig { enabled, indexing_enabled })
+		if self.period == 0 {
+			return Err(error::Error::Input("--offchain-worker-period must be at least 1".into()));
+		}
+		if self.max_concurrent == Some(0) {
+			return Err(error::Error::Input(
+				"--offchain-worker-max-concurrent must be at least 1".into()
+			));
+		}
+
+		let options = OffchainWorkerOptions {
+			period: self.period,
+			skip_if_running: self.skip_if_running,
+			max_concurrent: self.max_concurrent,
+			disabled_pallets: self.disabled_pallets.clone(),
+		};
+
+		Ok(OffchainWorkerConfig { enabled, indexing_enabled, options })
 	}
 }
@@ -218,6 +218,14 @@ impl<C: SubstrateCli> Runner<C> {
 				let (client, _, _, task_manager) = builder(self.config)?;
 				run_until_exit(self.tokio_runtime, cmd.run(client, chain_spec), task_manager)
 			},
+			// Nodes whose consensus engines keep aux data handle the snapshot commands themselves.
+			Subcommand::ExportStateSnapshot(cmd) => {
+				let (_, backend, _, task_manager) = builder(self.config)?;
+				let aux_data = |header: &B::Header| Ok((*header.number(), Vec::new()));
+				run_until_exit(self.tokio_runtime, cmd.run(backend, aux_data), task_manager)
+			},
+			// The database is initialized from the snapshot, not from the genesis block.
+			Subcommand::ImportStateSnapshot(cmd) => cmd.run::<B>(self.config, &[]),
 		}
 	}
 
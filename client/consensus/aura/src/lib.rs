@@ -432,8 +432,7 @@ fn find_pre_digest<B: BlockT, P: Pair>(header: &B::Header) -> Result<u64, Error<
 ///
 /// This digest item will always return `Some` when used with `as_aura_seal`.
 //
-fn check_header<C, B: BlockT, P: Pair>(
-	client: &C,
+fn check_header<B: BlockT, P: Pair>(
 	slot_now: u64,
 	mut header: B::Header,
 	hash: B::Hash,
@@ -441,7 +440,6 @@ fn check_header<C, B: BlockT, P: Pair>(
 ) -> Result<CheckedHeader<B::Header, (u64, DigestItemFor<B>)>, Error<B>> where
 	DigestItemFor<B>: CompatibleDigestItem<P>,
 	P::Signature: Decode,
-	C: sc_client_api::backend::AuxStore,
 	P::Public: Encode + Decode + PartialEq + Clone,
 {
 	let seal = match header.digest_mut().pop() {
@@ -469,21 +467,6 @@ fn check_header<C, B: BlockT, P: Pair>(
 		let pre_hash = header.hash();
 
 		if P::verify(&sig, pre_hash.as_ref(), expected_author) {
-			if let Some(equivocation_proof) = check_equivocation(
-				client,
-				slot_now,
-				slot_num,
-				&header,
-				expected_author,
-			).map_err(Error::Client)? {
-				info!(
-					"Slot author is equivocating at slot {} with headers {:?} and {:?}",
-					slot_num,
-					equivocation_proof.first_header.hash(),
-					equivocation_proof.second_header.hash(),
-				);
-			}
-
 			Ok(CheckedHeader::Checked(header, (slot_num, seal)))
 		} else {
 			Err(Error::BadSignature(hash))
@@ -501,6 +484,97 @@ pub struct AuraVerifier<C, P> {
 impl<C, P> AuraVerifier<C, P>
 	where P: Send + Sync + 'static
 {
+	fn check_and_report_equivocation<B: BlockT>(
+		&self,
+		slot_now: u64,
+		slot: u64,
+		header: &B::Header,
+		author: &AuthorityId<P>,
+		origin: &BlockOrigin,
+	) -> Result<(), Error<B>> where
+		C: ProvideRuntimeApi<B> + HeaderBackend<B> + AuxStore,
+		C::Api: AuraApi<B, AuthorityId<P>, Error = sp_blockchain::Error>,
+		P: Pair,
+		P::Public: Encode + Decode + PartialEq + Clone + Debug,
+	{
+		// don't report any equivocations during initial sync
+		// as they are most likely stale.
+		if *origin == BlockOrigin::NetworkInitialSync {
+			return Ok(());
+		}
+
+		// check if authorship of this header is an equivocation and return a proof if so.
+		let equivocation_proof =
+			match check_equivocation(&*self.client, slot_now, slot, header, author)
+				.map_err(Error::Client)?
+			{
+				Some(proof) => proof,
+				None => return Ok(()),
+			};
+
+		info!(
+			"Slot author {:?} is equivocating at slot {} with headers {:?} and {:?}",
+			author,
+			slot,
+			equivocation_proof.first_header.hash(),
+			equivocation_proof.second_header.hash(),
+		);
+
+		// get the best block on which we will build and send the equivocation report.
+		let best_id = BlockId::Hash(self.client.info().best_hash);
+
+		// equivocation reporting was added in version 2 of the runtime api.
+		let can_report = self.client
+			.runtime_api()
+			.has_api_with::<dyn AuraApi<B, AuthorityId<P>, Error = ()>, _>(&best_id, |v| v >= 2)
+			.map_err(Error::Client)?;
+		if !can_report {
+			debug!(target: "aura", "Runtime does not support equivocation reports.");
+			return Ok(());
+		}
+
+		// generate a key ownership proof. we start by trying to generate the
+		// key owernship proof at the parent of the equivocating header, this
+		// will make sure that proof generation is successful since it happens
+		// during the on-going session (i.e. session keys are available in the
+		// state to be able to generate the proof). this might fail if the
+		// equivocation happens on the first block of the session, in which case
+		// its parent would be on the previous session. if generation on the
+		// parent header fails we try with best block as well.
+		let generate_key_owner_proof = |block_id: &BlockId<B>| {
+			self.client
+				.runtime_api()
+				.generate_key_ownership_proof(block_id, slot, equivocation_proof.offender.clone())
+				.map_err(Error::Client)
+		};
+
+		let parent_id = BlockId::Hash(*header.parent_hash());
+		let key_owner_proof = match generate_key_owner_proof(&parent_id)? {
+			Some(proof) => proof,
+			None => match generate_key_owner_proof(&best_id)? {
+				Some(proof) => proof,
+				None => {
+					debug!(target: "aura", "Equivocation offender is not part of the authority set.");
+					return Ok(());
+				}
+			},
+		};
+
+		// submit equivocation report at best block.
+		self.client
+			.runtime_api()
+			.submit_report_equivocation_unsigned_extrinsic(
+				&best_id,
+				equivocation_proof,
+				key_owner_proof,
+			)
+			.map_err(Error::Client)?;
+
+		info!(target: "aura", "Submitted equivocation report for author {:?}", author);
+
+		Ok(())
+	}
+
 	fn check_inherents<B: BlockT>(
 		&self,
 		block: B,
@@ -559,6 +633,7 @@ impl<B: BlockT, C, P> Verifier<B> for AuraVerifier<C, P> where
 		Sync +
 		sc_client_api::backend::AuxStore +
 		ProvideCache<B> +
+		HeaderBackend<B> +
 		BlockOf,
 	C::Api: BlockBuilderApi<B> + AuraApi<B, AuthorityId<P>> + ApiExt<B, Error = sp_blockchain::Error>,
 	DigestItemFor<B>: CompatibleDigestItem<P>,
@@ -583,11 +658,14 @@ impl<B: BlockT, C, P> Verifier<B> for AuraVerifier<C, P> where
 		let authorities = authorities(self.client.as_ref(), &BlockId::Hash(parent_hash))
 			.map_err(|e| format!("Could not fetch authorities at {:?}: {:?}", parent_hash, e))?;
 
+		// equivocation proofs must contain the sealed headers, so that the
+		// runtime can check the signatures of the offender.
+		let sealed_header = header.clone();
+
 		// we add one to allow for some small drift.
 		// FIXME #1019 in the future, alter this queue to allow deferring of
 		// headers
-		let checked_header = check_header::<C, B, P>(
-			&self.client,
+		let checked_header = check_header::<B, P>(
 			slot_now + 1,
 			header,
 			hash,
@@ -595,6 +673,19 @@ impl<B: BlockT, C, P> Verifier<B> for AuraVerifier<C, P> where
 		).map_err(|e| e.to_string())?;
 		match checked_header {
 			CheckedHeader::Checked(pre_header, (slot_num, seal)) => {
+				// the header is valid but let's check if there was something else already
+				// proposed at the same slot by the given author. if there was, we will
+				// report the equivocation to the runtime.
+				let author = slot_author::<P>(slot_num, &authorities)
+					.expect("the slot author was found when checking the header; qed");
+				self.check_and_report_equivocation(
+					slot_now + 1,
+					slot_num,
+					&sealed_header,
+					author,
+					&origin,
+				).map_err(|e| e.to_string())?;
+
 				// if the body is passed through, we need to use the runtime
 				// to check that the internally-set timestamp in the inherents
 				// actually matches the slot set in the seal.
@@ -1042,6 +1133,64 @@ mod tests {
 		));
 	}
 
+	#[test]
+	fn equivocation_proofs_contain_sealed_headers() {
+		let mut net = AuraTestNet::new(1);
+		let peer_client = net.peer(0).client().clone();
+		let client = peer_client.as_full().expect("full clients are created");
+		let mut verifier = net.make_verifier(peer_client, &Default::default(), &());
+
+		let inherent_data = verifier.inherent_data_providers.create_inherent_data()
+			.expect("Creates inherent data");
+		let (_, slot_now, _) = AuraSlotCompatible.extract_timestamp_and_slot(&inherent_data)
+			.expect("Extracts the current slot");
+
+		let authorities = [Keyring::Alice, Keyring::Bob, Keyring::Charlie];
+		let author: AuthorityPair = authorities[(slot_now % authorities.len() as u64) as usize]
+			.pair()
+			.into();
+		let genesis_hash = client.chain_info().genesis_hash;
+
+		// two different headers authored by the slot author for the same slot.
+		let sealed_header = |state_root| {
+			let mut header = Header::new(
+				1,
+				Default::default(),
+				H256::from_low_u64_be(state_root),
+				genesis_hash,
+				Default::default(),
+			);
+			header.digest_mut().push(
+				<DigestItemFor<TestBlock> as CompatibleDigestItem<AuthorityPair>>::aura_pre_digest(slot_now),
+			);
+			let signature = author.sign(header.hash().as_ref());
+			header.digest_mut().push(
+				<DigestItemFor<TestBlock> as CompatibleDigestItem<AuthorityPair>>::aura_seal(signature),
+			);
+			header
+		};
+		let first_header = sealed_header(1);
+		let second_header = sealed_header(2);
+
+		for header in vec![first_header.clone(), second_header.clone()] {
+			verifier.verify(BlockOrigin::NetworkBroadcast, header, None, None)
+				.expect("Both headers are valid");
+		}
+
+		// the verifier has recorded the sealed headers, so the proof it reported is accepted by
+		// the runtime.
+		let proof = check_equivocation(
+			&*client,
+			slot_now,
+			slot_now,
+			&second_header,
+			&author.public(),
+		).unwrap().expect("The author equivocated");
+		assert_eq!(proof.first_header, first_header);
+		assert_eq!(proof.second_header, second_header);
+		assert!(sp_consensus_aura::check_equivocation_proof::<_, AuthorityId<AuthorityPair>>(proof));
+	}
+
 	#[test]
 	fn authorities_call_works() {
 		let client = substrate_test_runtime_client::new();
@@ -25,7 +25,7 @@ use jsonrpc_core::{
 	futures::future as rpc_future,
 };
 use jsonrpc_derive::rpc;
-use sc_consensus_epochs::{descendent_query, Epoch as EpochT, SharedEpochChanges};
+use sc_consensus_epochs::{descendent_query, Epoch as EpochT, PersistedEpoch, SharedEpochChanges};
 use sp_consensus_babe::{
 	AuthorityId,
 	BabeApi as BabeRuntimeApi,
@@ -33,6 +33,7 @@ use sp_consensus_babe::{
 };
 use serde::{Deserialize, Serialize};
 use sp_core::{
+	Bytes,
 	crypto::Public,
 	traits::BareCryptoStore,
 };
@@ -40,7 +41,7 @@ use sp_application_crypto::AppKey;
 use sc_keystore::KeyStorePtr;
 use sc_rpc_api::DenyUnsafe;
 use sp_api::{ProvideRuntimeApi, BlockId};
-use sp_runtime::traits::{Block as BlockT, Header as _};
+use sp_runtime::traits::{Block as BlockT, Header as _, UniqueSaturatedInto};
 use sp_consensus::{SelectChain, Error as ConsensusError};
 use sp_blockchain::{HeaderBackend, HeaderMetadata, Error as BlockChainError};
 use std::{collections::HashMap, sync::Arc};
@@ -49,11 +50,21 @@ type FutureResult<T> = Box<dyn rpc_future::Future<Item = T, Error = RpcError> +
 
 /// Provides rpc methods for interacting with Babe.
 #[rpc]
-pub trait BabeApi {
+pub trait BabeApi<Hash> {
 	/// Returns data about which slots (primary or secondary) can be claimed in the current epoch
 	/// with the keys in the keystore.
 	#[rpc(name = "babe_epochAuthorship")]
 	fn epoch_authorship(&self) -> FutureResult<HashMap<AuthorityId, EpochAuthorship>>;
+
+	/// Returns the current epoch of the best chain, with its start and end slots,
+	/// its randomness and authorities.
+	#[rpc(name = "babe_currentEpoch")]
+	fn current_epoch(&self) -> FutureResult<EpochInfo>;
+
+	/// Returns the nodes of the tree of epoch changes, which tracks the epochs
+	/// announced on every fork that hasn't been pruned on finality yet.
+	#[rpc(name = "babe_epochChanges")]
+	fn epoch_changes(&self) -> FutureResult<Vec<EpochChangesNode<Hash>>>;
 }
 
 /// Implements the BabeRpc trait for interacting with Babe.
@@ -93,7 +104,7 @@ impl<B: BlockT, C, SC> BabeRpcHandler<B, C, SC> {
 	}
 }
 
-impl<B, C, SC> BabeApi for BabeRpcHandler<B, C, SC>
+impl<B, C, SC> BabeApi<B::Hash> for BabeRpcHandler<B, C, SC>
 	where
 		B: BlockT,
 		C: ProvideRuntimeApi<B> + HeaderBackend<B> + HeaderMetadata<B, Error=BlockChainError> + 'static,
@@ -119,13 +130,7 @@ impl<B, C, SC> BabeApi for BabeRpcHandler<B, C, SC>
 			self.select_chain.clone(),
 		);
 		let future = async move {
-			let header = select_chain.best_chain().map_err(Error::Consensus)?;
-			let epoch_start = client.runtime_api()
-				.current_epoch_start(&BlockId::Hash(header.hash()))
-				.map_err(|err| {
-					Error::StringError(format!("{:?}", err))
-				})?;
-			let epoch = epoch_data(&shared_epoch, &client, &babe_config, epoch_start, &select_chain)?;
+			let epoch = current_epoch(&shared_epoch, &client, &babe_config, &select_chain)?;
 			let (epoch_start, epoch_end) = (epoch.start_slot(), epoch.end_slot());
 
 			let mut claims: HashMap<AuthorityId, EpochAuthorship> = HashMap::new();
@@ -167,6 +172,55 @@ impl<B, C, SC> BabeApi for BabeRpcHandler<B, C, SC>
 
 		Box::new(future.compat())
 	}
+
+	fn current_epoch(&self) -> FutureResult<EpochInfo> {
+		let (
+			babe_config,
+			shared_epoch,
+			client,
+			select_chain,
+		) = (
+			self.babe_config.clone(),
+			self.shared_epoch_changes.clone(),
+			self.client.clone(),
+			self.select_chain.clone(),
+		);
+		let future = async move {
+			let epoch = current_epoch(&shared_epoch, &client, &babe_config, &select_chain)?;
+
+			Ok(EpochInfo::from(&epoch))
+		}.boxed();
+
+		Box::new(future.compat())
+	}
+
+	fn epoch_changes(&self) -> FutureResult<Vec<EpochChangesNode<B::Hash>>> {
+		if let Err(err) = self.deny_unsafe.check_if_safe() {
+			return Box::new(rpc_future::err(err.into()));
+		}
+
+		let epoch_changes = self.shared_epoch_changes.lock();
+		let nodes = epoch_changes.tree()
+			.iter_with_parents()
+			.map(|(parent_hash, hash, number, _)| {
+				let epochs = match epoch_changes.persisted_epoch(hash, number) {
+					Some(PersistedEpoch::Genesis(epoch_0, epoch_1)) =>
+						vec![EpochInfo::from(epoch_0), EpochInfo::from(epoch_1)],
+					Some(PersistedEpoch::Regular(epoch_n)) => vec![EpochInfo::from(epoch_n)],
+					None => Vec::new(),
+				};
+
+				EpochChangesNode {
+					hash: *hash,
+					number: (*number).unique_saturated_into(),
+					parent_hash: parent_hash.cloned(),
+					epochs,
+				}
+			})
+			.collect();
+
+		Box::new(rpc_future::ok(nodes))
+	}
 }
 
 /// Holds information about the `slot_number`'s that can be claimed by a given key.
@@ -180,6 +234,48 @@ pub struct EpochAuthorship {
 	secondary_vrf: Vec<u64>,
 }
 
+/// Data of a BABE epoch.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EpochInfo {
+	/// The epoch index.
+	epoch_index: u64,
+	/// The first slot of the epoch.
+	start_slot: u64,
+	/// The first slot after the epoch, i.e. not part of it.
+	end_slot: u64,
+	/// The randomness used for the slot leader election in the epoch.
+	randomness: Bytes,
+	/// The authorities of the epoch and their weights.
+	authorities: Vec<(AuthorityId, u64)>,
+}
+
+impl From<&Epoch> for EpochInfo {
+	fn from(epoch: &Epoch) -> Self {
+		EpochInfo {
+			epoch_index: epoch.epoch_index,
+			start_slot: epoch.start_slot(),
+			end_slot: epoch.end_slot(),
+			randomness: epoch.randomness.to_vec().into(),
+			authorities: epoch.authorities.clone(),
+		}
+	}
+}
+
+/// A node of the tree of epoch changes, i.e. a block that announced an epoch.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EpochChangesNode<Hash> {
+	/// The hash of the block.
+	hash: Hash,
+	/// The number of the block.
+	number: u64,
+	/// The hash of the block of the parent node, if this isn't a root.
+	parent_hash: Option<Hash>,
+	/// The epochs announced by the block, the genesis block announces two.
+	epochs: Vec<EpochInfo>,
+}
+
 /// Errors encountered by the RPC
 #[derive(Debug, derive_more::Display, derive_more::From)]
 pub enum Error {
@@ -199,6 +295,29 @@ impl From<Error> for jsonrpc_core::Error {
 	}
 }
 
+/// fetches the epoch data of the current epoch of the best chain.
+fn current_epoch<B, C, SC>(
+	epoch_changes: &SharedEpochChanges<B, Epoch>,
+	client: &Arc<C>,
+	babe_config: &Config,
+	select_chain: &SC,
+) -> Result<Epoch, Error>
+	where
+		B: BlockT,
+		C: ProvideRuntimeApi<B> + HeaderBackend<B> + HeaderMetadata<B, Error=BlockChainError> + 'static,
+		C::Api: BabeRuntimeApi<B>,
+		SC: SelectChain<B>,
+{
+	let header = select_chain.best_chain().map_err(Error::Consensus)?;
+	let epoch_start = client.runtime_api()
+		.current_epoch_start(&BlockId::Hash(header.hash()))
+		.map_err(|err| {
+			Error::StringError(format!("{:?}", err))
+		})?;
+
+	epoch_data(epoch_changes, client, babe_config, epoch_start, select_chain)
+}
+
 /// fetches the epoch data for a given slot_number.
 fn epoch_data<B, C, SC>(
 	epoch_changes: &SharedEpochChanges<B, Epoch>,
@@ -236,13 +355,24 @@ mod tests {
 		TestClientBuilder,
 	};
 	use sp_application_crypto::AppPair;
-	use sp_keyring::Ed25519Keyring;
+	use sp_keyring::{Ed25519Keyring, Sr25519Keyring};
 	use sc_keystore::Store;
+	use sc_block_builder::BlockBuilderProvider;
+	use sp_consensus::{BlockImport, BlockImportParams, BlockOrigin, ForkChoiceStrategy, ImportResult};
+	use sp_consensus_babe::{
+		BABE_ENGINE_ID, ConsensusLog,
+		digests::{CompatibleDigestItem, NextEpochDescriptor, SecondaryPlainPreDigest},
+	};
+	use sp_runtime::{codec::Encode, generic::{Digest, DigestItem}};
 
-	use std::sync::Arc;
-	use sc_consensus_babe::{Config, block_import, AuthorityPair};
+	use std::{any::Any, borrow::Cow, sync::Arc};
+	use sc_consensus_babe::{
+		Config, block_import, AuthorityPair, BabeBlockImport, BabeIntermediate, INTERMEDIATE_KEY,
+	};
 	use jsonrpc_core::IoHandler;
 
+	type TestBlockImport = BabeBlockImport<Block, TestClient, Arc<TestClient>>;
+
 	/// creates keystore backed by a temp file
 	fn create_temp_keystore<P: AppPair>(authority: Ed25519Keyring) -> (KeyStorePtr, tempfile::TempDir) {
 		let keystore_path = tempfile::tempdir().expect("Creates keystore path");
@@ -256,11 +386,22 @@ mod tests {
 	fn test_babe_rpc_handler(
 		deny_unsafe: DenyUnsafe
 	) -> BabeRpcHandler<Block, TestClient, sc_consensus::LongestChain<Backend, Block>> {
+		test_babe_rpc_handler_with_import(deny_unsafe).0
+	}
+
+	/// creates the rpc handler together with the block import sharing its epoch changes.
+	fn test_babe_rpc_handler_with_import(
+		deny_unsafe: DenyUnsafe
+	) -> (
+		BabeRpcHandler<Block, TestClient, sc_consensus::LongestChain<Backend, Block>>,
+		Arc<TestClient>,
+		TestBlockImport,
+	) {
 		let builder = TestClientBuilder::new();
 		let (client, longest_chain) = builder.build_with_longest_chain();
 		let client = Arc::new(client);
 		let config = Config::get_or_compute(&*client).expect("config available");
-		let (_, link) = block_import(
+		let (block_import, link) = block_import(
 			config.clone(),
 			client.clone(),
 			client.clone(),
@@ -269,14 +410,82 @@ mod tests {
 		let epoch_changes = link.epoch_changes().clone();
 		let keystore = create_temp_keystore::<AuthorityPair>(Ed25519Keyring::Alice).0;
 
-		BabeRpcHandler::new(
+		let handler = BabeRpcHandler::new(
 			client.clone(),
 			epoch_changes,
 			keystore,
 			config,
 			longest_chain,
 			deny_unsafe,
-		)
+		);
+
+		(handler, client, block_import)
+	}
+
+	/// builds and imports a block on top of the given parent, announcing the next epoch if the
+	/// block is the first of its epoch, since the test runtime doesn't.
+	fn import_block(
+		handler: &BabeRpcHandler<Block, TestClient, sc_consensus::LongestChain<Backend, Block>>,
+		client: &TestClient,
+		block_import: &mut TestBlockImport,
+		(parent_hash, parent_number, parent_slot): (<Block as BlockT>::Hash, u64, u64),
+		slot_number: u64,
+	) -> <Block as BlockT>::Hash {
+		let pre_digest = Digest {
+			logs: vec![
+				DigestItem::babe_pre_digest(PreDigest::SecondaryPlain(SecondaryPlainPreDigest {
+					authority_index: 0,
+					slot_number,
+				})),
+			],
+		};
+
+		let mut block = client.new_block_at(&BlockId::Hash(parent_hash), pre_digest, false)
+			.expect("can create block builder")
+			.build()
+			.expect("can build block")
+			.block;
+
+		let (epoch, epoch_descriptor) = {
+			let epoch_changes = handler.shared_epoch_changes.lock();
+			let epoch = epoch_changes.epoch_data_for_child_of(
+				descendent_query(client),
+				&parent_hash,
+				parent_number,
+				slot_number,
+				|slot| Epoch::genesis(&handler.babe_config, slot),
+			).unwrap().expect("parent is imported");
+			let epoch_descriptor = epoch_changes.epoch_descriptor_for_child_of(
+				descendent_query(client),
+				&parent_hash,
+				parent_number,
+				slot_number,
+			).unwrap().expect("parent is imported");
+
+			(epoch, epoch_descriptor)
+		};
+
+		if parent_slot < epoch.start_slot {
+			let next_epoch = ConsensusLog::NextEpochData(NextEpochDescriptor {
+				authorities: epoch.authorities,
+				randomness: epoch.randomness,
+			});
+			block.header.digest_mut().push(DigestItem::Consensus(BABE_ENGINE_ID, next_epoch.encode()));
+		}
+
+		let hash = block.header.hash();
+		let mut import = BlockImportParams::new(BlockOrigin::Own, block.header);
+		import.body = Some(block.extrinsics);
+		import.intermediates.insert(
+			Cow::from(INTERMEDIATE_KEY),
+			Box::new(BabeIntermediate::<Block> { epoch_descriptor }) as Box<dyn Any>,
+		);
+		import.fork_choice = Some(ForkChoiceStrategy::LongestChain);
+
+		match block_import.import_block(import, Default::default()).unwrap() {
+			ImportResult::Imported(_) => hash,
+			result => panic!("expected block to be imported, got {:?}", result),
+		}
 	}
 
 	#[test]
@@ -291,6 +500,81 @@ mod tests {
 		assert_eq!(Some(response.into()), io.handle_request_sync(request));
 	}
 
+	#[test]
+	fn current_epoch_works() {
+		let handler = test_babe_rpc_handler(DenyUnsafe::Yes);
+		let mut io = IoHandler::new();
+
+		io.extend_with(BabeApi::to_delegate(handler));
+		let request = r#"{"jsonrpc":"2.0","method":"babe_currentEpoch","params": [],"id":1}"#;
+
+		let response = io.handle_request_sync(request).unwrap();
+		let mut response: serde_json::Value = serde_json::from_str(&response).unwrap();
+		let epoch: EpochInfo = serde_json::from_value(response["result"].take()).unwrap();
+
+		assert_eq!(epoch.epoch_index, 0);
+		assert_eq!(epoch.start_slot, 0);
+		assert_eq!(epoch.end_slot, 6);
+		assert_eq!(epoch.randomness.len(), 32);
+		assert!(epoch.authorities.contains(&(Sr25519Keyring::Alice.public().into(), 1)));
+	}
+
+	#[test]
+	fn epoch_changes_works() {
+		let (handler, client, mut block_import) = test_babe_rpc_handler_with_import(DenyUnsafe::No);
+		let mut io = IoHandler::new();
+
+		// block 1 announces the genesis epoch and the one after it, the epoch duration is 6
+		// slots. block 2 is the first block of epoch 1 and announces epoch 2.
+		let genesis_hash = client.info().genesis_hash;
+		let block_1 = import_block(&handler, &client, &mut block_import, (genesis_hash, 0, 0), 1);
+		let block_2 = import_block(&handler, &client, &mut block_import, (block_1, 1, 1), 7);
+		// block 3 is still in epoch 1, hence doesn't announce any epoch.
+		import_block(&handler, &client, &mut block_import, (block_2, 2, 7), 8);
+
+		io.extend_with(BabeApi::to_delegate(handler));
+		let request = r#"{"jsonrpc":"2.0","method":"babe_epochChanges","params": [],"id":1}"#;
+
+		let response = io.handle_request_sync(request).unwrap();
+		let mut response: serde_json::Value = serde_json::from_str(&response).unwrap();
+		let nodes: Vec<EpochChangesNode<<Block as BlockT>::Hash>> =
+			serde_json::from_value(response["result"].take()).unwrap();
+
+		let summary = nodes.iter()
+			.map(|node| (
+				node.hash,
+				node.number,
+				node.parent_hash,
+				node.epochs.iter().map(|epoch| (epoch.epoch_index, epoch.start_slot)).collect::<Vec<_>>(),
+			))
+			.collect::<Vec<_>>();
+
+		assert_eq!(
+			summary,
+			vec![
+				// genesis epoch entry, i.e. epochs 0 and 1.
+				(block_1, 1, None, vec![(0, 1), (1, 7)]),
+				// regular entry.
+				(block_2, 2, Some(block_1), vec![(2, 13)]),
+			],
+		);
+	}
+
+	#[test]
+	fn epoch_changes_is_unsafe() {
+		let handler = test_babe_rpc_handler(DenyUnsafe::Yes);
+		let mut io = IoHandler::new();
+
+		io.extend_with(BabeApi::to_delegate(handler));
+		let request = r#"{"jsonrpc":"2.0","method":"babe_epochChanges","params": [],"id":1}"#;
+
+		let response = io.handle_request_sync(request).unwrap();
+		let mut response: serde_json::Value = serde_json::from_str(&response).unwrap();
+		let error: RpcError = serde_json::from_value(response["error"].take()).unwrap();
+
+		assert_eq!(error, RpcError::method_not_found())
+	}
+
 	#[test]
 	fn epoch_authorship_is_unsafe() {
 		let handler = test_babe_rpc_handler(DenyUnsafe::Yes);
@@ -21,9 +21,9 @@ use parking_lot::Mutex;
 use log::info;
 use codec::{Decode, Encode};
 
-use sc_client_api::backend::AuxStore;
-use sp_blockchain::{Result as ClientResult, Error as ClientError};
-use sp_runtime::traits::Block as BlockT;
+use sc_client_api::backend::{AuxStore, Backend};
+use sp_blockchain::{HeaderBackend, Result as ClientResult, Error as ClientError};
+use sp_runtime::traits::{Block as BlockT, NumberFor};
 use sp_consensus_babe::{BabeBlockWeight, BabeGenesisConfiguration};
 use sc_consensus_epochs::{EpochChangesFor, SharedEpochChanges, migration::EpochChangesForV0};
 use crate::{Epoch, migration::EpochV0};
@@ -133,6 +133,52 @@ pub(crate) fn load_block_weight<H: Encode, B: AuxStore>(
 	load_decode(backend, block_weight_key(block_hash).as_slice())
 }
 
+/// The keys of the aux data that is returned by [`state_snapshot_aux_data`], besides the weight
+/// of the snapshot block.
+pub const STATE_SNAPSHOT_AUX_KEYS: &[&[u8]] = &[
+	BABE_EPOCH_CHANGES_VERSION,
+	BABE_EPOCH_CHANGES_KEY,
+];
+
+/// Returns the aux data BABE needs to continue the chain on a database that is initialized from
+/// a state snapshot of the block `hash`, together with the number of the oldest block the data
+/// refers to.
+///
+/// The epoch changes only keep the epochs that were announced on the chain of the block.
+pub fn state_snapshot_aux_data<Block: BlockT, BE: Backend<Block>>(
+	backend: &BE,
+	hash: Block::Hash,
+) -> ClientResult<(NumberFor<Block>, Vec<(Vec<u8>, Vec<u8>)>)> {
+	if load_decode::<_, u32>(backend, BABE_EPOCH_CHANGES_VERSION)?
+		!= Some(BABE_EPOCH_CHANGES_CURRENT_VERSION)
+	{
+		return Err(ClientError::Backend(
+			"Outdated BABE database, start the node once to migrate it".into(),
+		));
+	}
+	let epoch_changes = load_decode::<_, EpochChangesFor<Block, Epoch>>(backend, BABE_EPOCH_CHANGES_KEY)?
+		.ok_or_else(|| ClientError::Backend("Missing BABE epoch changes".into()))?;
+	let weight = load_block_weight(backend, hash)?
+		.ok_or_else(|| ClientError::Backend(format!("Missing BABE weight of block {}", hash)))?;
+	let number = backend.blockchain().number(hash)?
+		.ok_or_else(|| ClientError::UnknownBlock(hash.to_string()))?;
+
+	let is_descendent_of = sc_client_api::utils::is_descendent_of(backend.blockchain(), None);
+	let epoch_changes = epoch_changes
+		.retain_chain(|change, _| Ok::<_, ClientError>(change == &hash || is_descendent_of(change, &hash)?))
+		.map_err(|e| ClientError::Backend(format!("Invalid BABE epoch changes: {:?}", e)))?;
+	let oldest = epoch_changes.tree().iter().map(|(_, number, _)| *number).min().unwrap_or(number);
+
+	let mut aux = Vec::new();
+	write_epoch_changes::<Block, _, _>(&epoch_changes, |values| {
+		aux.extend(values.iter().map(|(key, value)| (key.to_vec(), value.to_vec())))
+	});
+	write_block_weight(hash, weight, |values| {
+		aux.extend(values.iter().map(|(key, value)| (key.clone(), value.to_vec())))
+	});
+	Ok((oldest, aux))
+}
+
 #[cfg(test)]
 mod test {
 	use super::*;
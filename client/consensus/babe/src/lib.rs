@@ -125,6 +125,7 @@ use codec::{Encode, Decode};
 use sp_api::ApiExt;
 
 mod aux_schema;
+pub use aux_schema::{state_snapshot_aux_data, STATE_SNAPSHOT_AUX_KEYS};
 mod verification;
 mod migration;
 pub mod authorship;
@@ -437,6 +437,12 @@ impl<Hash, Number, E: Epoch> EpochChanges<Hash, Number, E> where
 			})
 	}
 
+	/// Get the epoch data persisted for the epoch change signaled at the
+	/// given block, if any.
+	pub fn persisted_epoch(&self, hash: &Hash, number: &Number) -> Option<&PersistedEpoch<E>> {
+		self.epochs.get(&(*hash, *number))
+	}
+
 	/// Get a reference to a viable epoch with given descriptor.
 	pub fn viable_epoch<G>(
 		&self,
@@ -636,6 +642,44 @@ impl<Hash, Number, E: Epoch> EpochChanges<Hash, Number, E> where
 		}
 	}
 
+	/// Returns the epoch changes that were announced on the chain ending at some block.
+	///
+	/// `is_on_chain` tells whether an announcing block is that block or one of its ancestors.
+	/// The changes announced on other forks or on descendants of the block are left out, so
+	/// that the result can be handed to a node that only knows the chain up to that block.
+	pub fn retain_chain<F, Err>(&self, mut is_on_chain: F) -> Result<Self, fork_tree::Error<Err>> where
+		F: FnMut(&Hash, &Number) -> Result<bool, Err>,
+		Err: std::error::Error,
+		E: Clone,
+	{
+		let mut nodes = Vec::new();
+		for (hash, number, header) in self.inner.iter() {
+			if is_on_chain(hash, number).map_err(fork_tree::Error::Client)? {
+				nodes.push((*hash, *number, header.clone()));
+			}
+		}
+		nodes.sort_by_key(|(_, number, _)| *number);
+
+		// all the retained changes are on one chain, so their numbers give the order of their
+		// blocks.
+		let numbers: BTreeMap<_, _> = nodes.iter().map(|(hash, number, _)| (*hash, *number)).collect();
+		let is_descendent_of = |base: &Hash, block: &Hash| Ok::<_, Err>(
+			match (numbers.get(base), numbers.get(block)) {
+				(Some(base), Some(block)) => base < block,
+				_ => false,
+			}
+		);
+
+		let mut changes = Self::default();
+		for (hash, number, header) in nodes {
+			changes.inner.import(hash, number, header, &is_descendent_of)?;
+			if let Some(epoch) = self.epochs.get(&(hash, number)) {
+				changes.epochs.insert((hash, number), epoch.clone());
+			}
+		}
+		Ok(changes)
+	}
+
 	/// Return the inner fork tree.
 	pub fn tree(&self) -> &ForkTree<Hash, Number, PersistedEpochHeader<E>> {
 		&self.inner
@@ -967,4 +1011,67 @@ mod tests {
 			assert!(epoch_for_x_child_before_genesis.is_none());
 		}
 	}
+
+	#[test]
+	fn epoch_changes_of_a_chain_are_retained() {
+		//
+		// 0 - A - B - D
+		//      \
+		//       — C
+		//
+		let is_descendent_of = |base: &Hash, block: &Hash| -> Result<bool, TestError> {
+			match (base, *block) {
+				(b"A", b) => Ok(b == *b"B" || b == *b"C" || b == *b"D"),
+				(b"B", b) => Ok(b == *b"D"),
+				(b"0", _) => Ok(true),
+				_ => Ok(false),
+			}
+		};
+
+		let make_genesis = |slot| Epoch {
+			start_slot: slot,
+			duration: 100,
+		};
+
+		let mut epoch_changes = EpochChanges::<_, _, Epoch>::new();
+		let mut import = |hash: Hash, number, parent_hash: Hash, parent_number, slot| {
+			let descriptor = epoch_changes.epoch_descriptor_for_child_of(
+				&is_descendent_of,
+				&parent_hash,
+				parent_number,
+				slot,
+			).unwrap().unwrap();
+			let incremented_epoch = epoch_changes
+				.viable_epoch(&descriptor, &make_genesis)
+				.unwrap()
+				.increment(());
+
+			epoch_changes.import(
+				&is_descendent_of,
+				hash,
+				number,
+				parent_hash,
+				incremented_epoch,
+			).unwrap();
+		};
+
+		import(*b"A", 1, *b"0", 0, 100);
+		import(*b"B", 2, *b"A", 1, 200);
+		import(*b"C", 2, *b"A", 1, 200);
+		import(*b"D", 3, *b"B", 2, 300);
+
+		// only `A` and `B` are on the chain ending at `B`.
+		let retained = epoch_changes.retain_chain(
+			|hash: &Hash, _: &u64| Ok::<_, TestError>(hash == b"A" || hash == b"B"),
+		).unwrap();
+
+		assert_eq!(
+			retained.tree().iter().map(|(hash, number, _)| (*hash, *number)).collect::<Vec<_>>(),
+			vec![(*b"A", 1), (*b"B", 2)],
+		);
+		assert!(retained.persisted_epoch(b"A", &1).is_some());
+		assert!(retained.persisted_epoch(b"B", &2).is_some());
+		assert!(retained.persisted_epoch(b"C", &2).is_none());
+		assert!(retained.persisted_epoch(b"D", &3).is_none());
+	}
 }
@@ -0,0 +1,76 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Aura pre-runtime digests for manual seal.
+
+use super::ConsensusDataProvider;
+use crate::Error;
+use std::{marker::PhantomData, sync::Arc};
+use codec::{Codec, Encode};
+use sp_api::{ProvideRuntimeApi, TransactionFor};
+use sp_consensus_aura::{AuraApi, AURA_ENGINE_ID};
+use sp_inherents::InherentData;
+use sp_runtime::{
+	generic::{BlockId, Digest, DigestItem},
+	traits::{Block as BlockT, DigestFor, Header as HeaderT},
+};
+use sp_timestamp::TimestampInherentData;
+
+/// Adds the Aura pre-runtime digest to the blocks, with the slot of the block's timestamp.
+///
+/// The slot of each block has to be larger than the one of its parent, so the timestamp
+/// needs to advance by at least the slot duration per block.
+pub struct AuraConsensusDataProvider<B, C, AuthorityId> {
+	slot_duration: u64,
+	_phantom: PhantomData<(B, C, AuthorityId)>,
+}
+
+impl<B, C, AuthorityId> AuraConsensusDataProvider<B, C, AuthorityId> where
+	B: BlockT,
+	C: ProvideRuntimeApi<B>,
+	C::Api: AuraApi<B, AuthorityId, Error = sp_blockchain::Error>,
+	AuthorityId: Codec,
+{
+	/// Create a new provider, reading the slot duration from the runtime at the best block.
+	pub fn new(client: Arc<C>, best: &B::Header) -> Result<Self, Error> {
+		let slot_duration = client.runtime_api()
+			.slot_duration(&BlockId::Hash(best.hash()))?;
+		if slot_duration == 0 {
+			return Err(Error::StringError("The runtime reports a slot duration of zero".into()));
+		}
+
+		Ok(Self { slot_duration, _phantom: PhantomData })
+	}
+}
+
+impl<B, C, AuthorityId> ConsensusDataProvider<B> for AuraConsensusDataProvider<B, C, AuthorityId> where
+	B: BlockT,
+	C: ProvideRuntimeApi<B> + Send + Sync,
+	AuthorityId: Send + Sync,
+{
+	type Transaction = TransactionFor<C, B>;
+
+	fn create_digest(&self, _parent: &B::Header, inherents: &InherentData) -> Result<DigestFor<B>, Error> {
+		let timestamp = inherents.timestamp_inherent_data()?;
+		let slot_number = timestamp / self.slot_duration;
+
+		Ok(Digest {
+			logs: vec![DigestItem::PreRuntime(AURA_ENGINE_ID, slot_number.encode())],
+		})
+	}
+}
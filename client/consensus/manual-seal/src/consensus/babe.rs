@@ -0,0 +1,150 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! BABE pre-runtime digests for manual seal.
+
+use super::ConsensusDataProvider;
+use crate::Error;
+use std::{any::Any, borrow::Cow, marker::PhantomData, sync::Arc};
+use codec::Encode;
+use sc_consensus_babe::{BabeIntermediate, BabeLink, Config, Epoch, INTERMEDIATE_KEY};
+use sc_consensus_epochs::{descendent_query, SharedEpochChanges};
+use sp_api::{ProvideRuntimeApi, TransactionFor};
+use sp_blockchain::{HeaderBackend, HeaderMetadata};
+use sp_consensus::BlockImportParams;
+use sp_consensus_babe::{
+	AuthorityIndex, BABE_ENGINE_ID, ConsensusLog, SlotNumber,
+	digests::{CompatibleDigestItem, NextEpochDescriptor, PreDigest, SecondaryPlainPreDigest},
+};
+use sp_inherents::InherentData;
+use sp_runtime::{
+	generic::{Digest, DigestItem},
+	traits::{Block as BlockT, DigestFor, Header as HeaderT, Zero},
+};
+use sp_timestamp::TimestampInherentData;
+
+/// Adds a BABE pre-runtime digest for a secondary plain slot to the blocks, claimed by a fixed
+/// authority, with the slot of the block's timestamp.
+///
+/// The runtime doesn't verify the slot claim, so the blocks are executed like the ones of the
+/// given authority. The slot of each block has to be larger than the one of its parent, so the
+/// timestamp needs to advance by at least the slot duration per block.
+///
+/// The blocks are set up to be imported through the BABE block import of the given
+/// [`BabeLink`]. If the runtime doesn't announce the next epoch in the first block of an epoch,
+/// it is announced with the authorities and randomness of the current epoch.
+pub struct BabeConsensusDataProvider<B: BlockT, C> {
+	client: Arc<C>,
+	epoch_changes: SharedEpochChanges<B, Epoch>,
+	config: Config,
+	authority_index: AuthorityIndex,
+	_phantom: PhantomData<B>,
+}
+
+impl<B, C> BabeConsensusDataProvider<B, C> where
+	B: BlockT,
+	C: HeaderBackend<B> + HeaderMetadata<B, Error = sp_blockchain::Error> + ProvideRuntimeApi<B>,
+{
+	/// Create a new provider claiming the slots for the authority with the given index, for the
+	/// block import of `babe_link`.
+	pub fn new(client: Arc<C>, babe_link: &BabeLink<B>, authority_index: AuthorityIndex) -> Result<Self, Error> {
+		if babe_link.config().slot_duration == 0 {
+			return Err(Error::StringError("The runtime reports a slot duration of zero".into()));
+		}
+
+		Ok(Self {
+			client,
+			epoch_changes: babe_link.epoch_changes().clone(),
+			config: babe_link.config().clone(),
+			authority_index,
+			_phantom: PhantomData,
+		})
+	}
+
+	fn slot_number(&self, inherents: &InherentData) -> Result<SlotNumber, Error> {
+		Ok(inherents.timestamp_inherent_data()? / self.config.slot_duration)
+	}
+}
+
+impl<B, C> ConsensusDataProvider<B> for BabeConsensusDataProvider<B, C> where
+	B: BlockT,
+	C: HeaderBackend<B> + HeaderMetadata<B, Error = sp_blockchain::Error>
+		+ ProvideRuntimeApi<B> + Send + Sync,
+{
+	type Transaction = TransactionFor<C, B>;
+
+	fn create_digest(&self, _parent: &B::Header, inherents: &InherentData) -> Result<DigestFor<B>, Error> {
+		let pre_digest = PreDigest::SecondaryPlain(SecondaryPlainPreDigest {
+			authority_index: self.authority_index,
+			slot_number: self.slot_number(inherents)?,
+		});
+
+		Ok(Digest {
+			logs: vec![<DigestItem<B::Hash> as CompatibleDigestItem>::babe_pre_digest(pre_digest)],
+		})
+	}
+
+	fn append_block_import(
+		&self,
+		parent: &B::Header,
+		params: &mut BlockImportParams<B, Self::Transaction>,
+		inherents: &InherentData,
+	) -> Result<(), Error> {
+		let slot_number = self.slot_number(inherents)?;
+		let epoch_changes = self.epoch_changes.lock();
+		let epoch_descriptor = epoch_changes.epoch_descriptor_for_child_of(
+			descendent_query(&*self.client),
+			&parent.hash(),
+			*parent.number(),
+			slot_number,
+		)
+			.map_err(|e| Error::StringError(format!("Failed to look up the epoch: {:?}", e)))?
+			.ok_or_else(|| Error::StringError("No epoch for the new block".into()))?;
+
+		let first_in_epoch = parent_slot_number::<B>(parent)? < epoch_descriptor.start_slot();
+		let announces_next_epoch = params.header.digest().logs().iter()
+			.any(|log| log.as_next_epoch_descriptor().is_some());
+		if first_in_epoch && !announces_next_epoch {
+			let epoch = epoch_changes.viable_epoch(&epoch_descriptor, |slot| Epoch::genesis(&self.config, slot))
+				.ok_or_else(|| Error::StringError("No epoch data for the new block".into()))?;
+			let next_epoch = ConsensusLog::NextEpochData(NextEpochDescriptor {
+				authorities: epoch.as_ref().authorities.clone(),
+				randomness: epoch.as_ref().randomness,
+			});
+			params.header.digest_mut().push(DigestItem::Consensus(BABE_ENGINE_ID, next_epoch.encode()));
+		}
+
+		params.intermediates.insert(
+			Cow::from(INTERMEDIATE_KEY),
+			Box::new(BabeIntermediate::<B> { epoch_descriptor }) as Box<dyn Any>,
+		);
+		Ok(())
+	}
+}
+
+/// The slot of the parent block, zero for the genesis block.
+fn parent_slot_number<B: BlockT>(parent: &B::Header) -> Result<SlotNumber, Error> {
+	if parent.number().is_zero() {
+		return Ok(0)
+	}
+
+	parent.digest().logs().iter()
+		.find_map(|log| log.as_babe_pre_digest())
+		.map(|pre_digest| pre_digest.slot_number())
+		.ok_or_else(|| Error::StringError("The parent block has no BABE pre-runtime digest".into()))
+}
@@ -32,6 +32,8 @@ mod codes {
 	pub const CONSENSUS_ERROR: i64 = 14_000;
 	pub const INHERENTS_ERROR: i64 = 15_000;
 	pub const BLOCKCHAIN_ERROR: i64 = 16_000;
+	pub const NO_TIMESTAMP_CONTROL: i64 = 17_000;
+	pub const TOO_MANY_BLOCKS: i64 = 18_000;
 	pub const UNKNOWN_ERROR: i64 = 20_000;
 }
 
@@ -58,6 +60,13 @@ pub enum Error {
 	#[display(fmt = "Supplied parent_hash: {} doesn't exist in chain", _0)]
 	#[from(ignore)]
 	BlockNotFound(String),
+	/// The engine doesn't control the timestamp
+	#[display(fmt = "Timestamp control is not enabled for this engine")]
+	NoTimestampControl,
+	/// More blocks were requested at once than allowed
+	#[display(fmt = "Cannot seal {} blocks at once, the maximum is {}", _0, _1)]
+	#[from(ignore)]
+	TooManyBlocks(u32, u32),
 	/// Some string error
 	#[display(fmt = "{}", _0)]
 	#[from(ignore)]
@@ -83,6 +92,8 @@ impl Error {
 			ConsensusError(_) => codes::CONSENSUS_ERROR,
 			InherentError(_) => codes::INHERENTS_ERROR,
 			BlockchainError(_) => codes::BLOCKCHAIN_ERROR,
+			NoTimestampControl => codes::NO_TIMESTAMP_CONTROL,
+			TooManyBlocks(..) => codes::TOO_MANY_BLOCKS,
 			SendError(_) | Canceled(_) => codes::SERVER_SHUTTING_DOWN,
 			_ => codes::UNKNOWN_ERROR
 		}
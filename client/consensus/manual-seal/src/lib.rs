@@ -18,6 +18,10 @@
 
 //! A manual sealing engine: the engine listens for rpc calls to seal blocks and create forks.
 //! This is suitable for a testing environment.
+//!
+//! For runtimes that depend on the passage of time, the engine can control the timestamp
+//! inherent through a [`ManualTimestamp`], and produce the pre-runtime digests of Aura or BABE
+//! through a [`ConsensusDataProvider`](consensus::ConsensusDataProvider).
 
 use futures::prelude::*;
 use sp_consensus::{
@@ -27,6 +31,7 @@ use sp_consensus::{
 use sp_blockchain::HeaderBackend;
 use sp_inherents::InherentDataProviders;
 use sp_runtime::{traits::Block as BlockT, Justification};
+use sp_timestamp::INHERENT_IDENTIFIER as TIMESTAMP_INHERENT_IDENTIFIER;
 use sc_client_api::backend::{Backend as ClientBackend, Finalizer};
 use sc_transaction_pool::txpool;
 use std::{sync::Arc, marker::PhantomData};
@@ -35,15 +40,19 @@ use prometheus_endpoint::Registry;
 mod error;
 mod finalize_block;
 mod seal_new_block;
+mod timestamp;
+pub mod consensus;
 pub mod rpc;
 
 use self::{
 	finalize_block::{finalize_block, FinalizeBlockParams},
-	seal_new_block::{seal_new_block, SealBlockParams},
+	seal_new_block::{seal_block, seal_new_block, SealBlockParams},
 };
 pub use self::{
+	consensus::ConsensusDataProvider,
 	error::Error,
 	rpc::{EngineCommand, CreatedBlock},
+	timestamp::ManualTimestamp,
 };
 
 /// The verifier for the manual seal engine; instantly finalizes.
@@ -87,15 +96,77 @@ pub fn import_queue<Block, Transaction>(
 	)
 }
 
+/// Params required to start the manual sealing authorship task.
+pub struct ManualSealParams<B: BlockT, BI, E, C, A: txpool::ChainApi, SC, CS, T> {
+	/// Block import instance for importing the sealed blocks.
+	pub block_import: BI,
+
+	/// The environment we are producing blocks for.
+	pub env: E,
+
+	/// Client instance
+	pub client: Arc<C>,
+
+	/// Shared reference to the transaction pool.
+	pub pool: Arc<txpool::Pool<A>>,
+
+	/// Stream<Item = EngineCommands>, Basically the receiving end of a channel for sending commands to
+	/// the authorship task.
+	pub commands_stream: CS,
+
+	/// SelectChain strategy.
+	pub select_chain: SC,
+
+	/// Digest provider for inclusion in blocks.
+	pub consensus_data_provider: Option<Box<dyn ConsensusDataProvider<B, Transaction = T>>>,
+
+	/// Timestamp control, registered as the provider of the timestamp inherent.
+	pub timestamp: Option<ManualTimestamp>,
+
+	/// Provider for inherents to include in blocks.
+	pub inherent_data_providers: InherentDataProviders,
+}
+
+/// Params required to start the instant sealing authorship task.
+pub struct InstantSealParams<B: BlockT, BI, E, C, A: txpool::ChainApi, SC, T> {
+	/// Block import instance for importing the sealed blocks.
+	pub block_import: BI,
+
+	/// The environment we are producing blocks for.
+	pub env: E,
+
+	/// Client instance
+	pub client: Arc<C>,
+
+	/// Shared reference to the transaction pool.
+	pub pool: Arc<txpool::Pool<A>>,
+
+	/// SelectChain strategy.
+	pub select_chain: SC,
+
+	/// Digest provider for inclusion in blocks.
+	pub consensus_data_provider: Option<Box<dyn ConsensusDataProvider<B, Transaction = T>>>,
+
+	/// Timestamp control, registered as the provider of the timestamp inherent.
+	pub timestamp: Option<ManualTimestamp>,
+
+	/// Provider for inherents to include in blocks.
+	pub inherent_data_providers: InherentDataProviders,
+}
+
 /// Creates the background authorship task for the manual seal engine.
-pub async fn run_manual_seal<B, CB, E, C, A, SC, S, T>(
-	mut block_import: BoxBlockImport<B, T>,
-	mut env: E,
-	client: Arc<C>,
-	pool: Arc<txpool::Pool<A>>,
-	mut commands_stream: S,
-	select_chain: SC,
-	inherent_data_providers: InherentDataProviders,
+pub async fn run_manual_seal<B, CB, E, C, A, SC, CS, T>(
+	ManualSealParams {
+		mut block_import,
+		mut env,
+		client,
+		pool,
+		mut commands_stream,
+		select_chain,
+		consensus_data_provider,
+		timestamp,
+		inherent_data_providers,
+	}: ManualSealParams<B, BoxBlockImport<B, T>, E, C, A, SC, CS, T>
 )
 	where
 		A: txpool::ChainApi<Block=B> + 'static,
@@ -105,9 +176,20 @@ pub async fn run_manual_seal<B, CB, E, C, A, SC, S, T>(
 		E: Environment<B> + 'static,
 		E::Error: std::fmt::Display,
 		<E::Proposer as Proposer<B>>::Error: std::fmt::Display,
-		S: Stream<Item=EngineCommand<<B as BlockT>::Hash>> + Unpin + 'static,
+		CS: Stream<Item=EngineCommand<<B as BlockT>::Hash>> + Unpin + 'static,
 		SC: SelectChain<B> + 'static,
 {
+	if let Some(ref timestamp) = timestamp {
+		if inherent_data_providers.has_provider(&TIMESTAMP_INHERENT_IDENTIFIER) {
+			log::warn!(
+				"A timestamp inherent data provider is already registered, \
+				timestamp control of the manual seal engine has no effect."
+			);
+		} else if let Err(err) = inherent_data_providers.register_provider(timestamp.clone()) {
+			log::warn!("Failed to register the manual timestamp: {}", err);
+		}
+	}
+
 	while let Some(command) = commands_stream.next().await {
 		match command {
 			EngineCommand::SealNewBlock {
@@ -126,11 +208,68 @@ pub async fn run_manual_seal<B, CB, E, C, A, SC, S, T>(
 						select_chain: &select_chain,
 						block_import: &mut block_import,
 						inherent_data_provider: &inherent_data_providers,
+						consensus_data_provider: consensus_data_provider.as_deref(),
+						timestamp: timestamp.as_ref(),
 						pool: pool.clone(),
 						client: client.clone(),
 					}
 				).await;
 			}
+			EngineCommand::SealBlocks {
+				count,
+				create_empty,
+				finalize,
+				mut sender,
+			} => {
+				if count > rpc::MAX_BLOCKS_PER_COMMAND {
+					let error = Error::TooManyBlocks(count, rpc::MAX_BLOCKS_PER_COMMAND);
+					rpc::send_result(&mut sender, Err(error));
+					continue
+				}
+
+				let mut created = Vec::with_capacity(count as usize);
+				let mut result = Ok(());
+				for _ in 0..count {
+					let block = seal_block(
+						&mut SealBlockParams {
+							sender: None,
+							parent_hash: None,
+							finalize,
+							create_empty,
+							env: &mut env,
+							select_chain: &select_chain,
+							block_import: &mut block_import,
+							inherent_data_provider: &inherent_data_providers,
+							consensus_data_provider: consensus_data_provider.as_deref(),
+							timestamp: timestamp.as_ref(),
+							pool: pool.clone(),
+							client: client.clone(),
+						}
+					).await;
+
+					match block {
+						Ok(block) => created.push(block),
+						Err(err) => {
+							result = Err(err);
+							break
+						}
+					}
+				}
+
+				rpc::send_result(&mut sender, result.map(|_| created));
+			}
+			EngineCommand::SetTimestamp { timestamp: now, mut sender } => {
+				let result = timestamp.as_ref()
+					.map(|timestamp| timestamp.set(now))
+					.ok_or(Error::NoTimestampControl);
+				rpc::send_result(&mut sender, result);
+			}
+			EngineCommand::AdvanceTime { by, mut sender } => {
+				let result = timestamp.as_ref()
+					.map(|timestamp| timestamp.advance(by))
+					.ok_or(Error::NoTimestampControl);
+				rpc::send_result(&mut sender, result);
+			}
 			EngineCommand::FinalizeBlock { hash, sender, justification } => {
 				finalize_block(
 					FinalizeBlockParams {
@@ -150,12 +289,16 @@ pub async fn run_manual_seal<B, CB, E, C, A, SC, S, T>(
 /// instant-seal creates a new block for every transaction imported into
 /// the transaction pool.
 pub async fn run_instant_seal<B, CB, E, C, A, SC, T>(
-	block_import: BoxBlockImport<B, T>,
-	env: E,
-	client: Arc<C>,
-	pool: Arc<txpool::Pool<A>>,
-	select_chain: SC,
-	inherent_data_providers: InherentDataProviders,
+	InstantSealParams {
+		block_import,
+		env,
+		client,
+		pool,
+		select_chain,
+		consensus_data_provider,
+		timestamp,
+		inherent_data_providers,
+	}: InstantSealParams<B, BoxBlockImport<B, T>, E, C, A, SC, T>
 )
 	where
 		A: txpool::ChainApi<Block=B> + 'static,
@@ -181,13 +324,17 @@ pub async fn run_instant_seal<B, CB, E, C, A, SC, T>(
 		});
 
 	run_manual_seal(
-		block_import,
-		env,
-		client,
-		pool,
-		commands_stream,
-		select_chain,
-		inherent_data_providers,
+		ManualSealParams {
+			block_import,
+			env,
+			client,
+			pool,
+			commands_stream,
+			select_chain,
+			consensus_data_provider,
+			timestamp,
+			inherent_data_providers,
+		}
 	).await
 }
 
@@ -207,6 +354,8 @@ mod tests {
 	use sp_consensus::ImportedAux;
 	use sp_inherents::InherentDataProviders;
 	use sc_basic_authorship::ProposerFactory;
+	use sp_runtime::generic::DigestItem;
+	use crate::consensus::{aura::AuraConsensusDataProvider, babe::BabeConsensusDataProvider};
 
 	fn api() -> Arc<TestApi> {
 		Arc::new(TestApi::empty())
@@ -245,13 +394,17 @@ mod tests {
 				}
 			});
 		let future = run_manual_seal(
-			Box::new(client.clone()),
-			env,
-			client.clone(),
-			pool.pool().clone(),
-			stream,
-			select_chain,
-			inherent_data_providers,
+			ManualSealParams {
+				block_import: Box::new(client.clone()),
+				env,
+				client: client.clone(),
+				pool: pool.pool().clone(),
+				commands_stream: stream,
+				select_chain,
+				consensus_data_provider: None,
+				timestamp: None,
+				inherent_data_providers,
+			}
 		);
 		std::thread::spawn(|| {
 			let mut rt = tokio::runtime::Runtime::new().unwrap();
@@ -300,13 +453,17 @@ mod tests {
 		// this test checks that blocks are created as soon as an engine command is sent over the stream.
 		let (mut sink, stream) = futures::channel::mpsc::channel(1024);
 		let future = run_manual_seal(
-			Box::new(client.clone()),
-			env,
-			client.clone(),
-			pool.pool().clone(),
-			stream,
-			select_chain,
-			inherent_data_providers,
+			ManualSealParams {
+				block_import: Box::new(client.clone()),
+				env,
+				client: client.clone(),
+				pool: pool.pool().clone(),
+				commands_stream: stream,
+				select_chain,
+				consensus_data_provider: None,
+				timestamp: None,
+				inherent_data_providers,
+			}
 		);
 		std::thread::spawn(|| {
 			let mut rt = tokio::runtime::Runtime::new().unwrap();
@@ -372,13 +529,17 @@ mod tests {
 		// this test checks that blocks are created as soon as an engine command is sent over the stream.
 		let (mut sink, stream) = futures::channel::mpsc::channel(1024);
 		let future = run_manual_seal(
-			Box::new(client.clone()),
-			env,
-			client.clone(),
-			pool.pool().clone(),
-			stream,
-			select_chain,
-			inherent_data_providers,
+			ManualSealParams {
+				block_import: Box::new(client.clone()),
+				env,
+				client: client.clone(),
+				pool: pool.pool().clone(),
+				commands_stream: stream,
+				select_chain,
+				consensus_data_provider: None,
+				timestamp: None,
+				inherent_data_providers,
+			}
 		);
 		std::thread::spawn(|| {
 			let mut rt = tokio::runtime::Runtime::new().unwrap();
@@ -453,4 +614,216 @@ mod tests {
 		// assert that fork block is in the db
 		assert!(client.header(&BlockId::Hash(imported.hash)).unwrap().is_some())
 	}
+
+	#[tokio::test]
+	async fn manual_seal_batch_with_timestamp_control() {
+		let builder = TestClientBuilder::new();
+		let (client, select_chain) = builder.build_with_longest_chain();
+		let client = Arc::new(client);
+		let inherent_data_providers = InherentDataProviders::new();
+		let spawner = sp_core::testing::TaskExecutor::new();
+		let pool = Arc::new(BasicPool::with_revalidation_type(
+			Options::default(), api(), None, RevalidationType::Full, spawner,
+		));
+		let env = ProposerFactory::new(
+			client.clone(),
+			pool.clone(),
+			None,
+		);
+		let timestamp = ManualTimestamp::new(1_000).with_auto_advance(6_000);
+		// this test checks that a batch of blocks is sealed and the time advances with each block.
+		let (mut sink, stream) = futures::channel::mpsc::channel(1024);
+		let future = run_manual_seal(
+			ManualSealParams {
+				block_import: Box::new(client.clone()),
+				env,
+				client: client.clone(),
+				pool: pool.pool().clone(),
+				commands_stream: stream,
+				select_chain,
+				consensus_data_provider: None,
+				timestamp: Some(timestamp.clone()),
+				inherent_data_providers,
+			}
+		);
+		std::thread::spawn(|| {
+			let mut rt = tokio::runtime::Runtime::new().unwrap();
+			// spawn the background authorship task
+			rt.block_on(future);
+		});
+
+		let (tx, rx) = futures::channel::oneshot::channel();
+		sink.send(EngineCommand::SealBlocks {
+			count: 3,
+			create_empty: true,
+			finalize: false,
+			sender: Some(tx),
+		}).await.unwrap();
+		let created_blocks = rx.await.unwrap().unwrap();
+
+		// assert that the blocks were built on top of each other.
+		assert_eq!(created_blocks.len(), 3);
+		let header = client.header(&BlockId::Number(3)).unwrap().unwrap();
+		assert_eq!(header.hash(), created_blocks[2].hash);
+		assert_eq!(header.parent_hash, created_blocks[1].hash);
+		// assert that the time advanced once per block.
+		assert_eq!(timestamp.now(), 19_000);
+
+		let (tx, rx) = futures::channel::oneshot::channel();
+		sink.send(EngineCommand::AdvanceTime { by: 1_000, sender: Some(tx) }).await.unwrap();
+		assert_eq!(rx.await.unwrap().unwrap(), 20_000);
+
+		let (tx, rx) = futures::channel::oneshot::channel();
+		sink.send(EngineCommand::SetTimestamp { timestamp: 50_000, sender: Some(tx) }).await.unwrap();
+		assert!(rx.await.unwrap().is_ok());
+		assert_eq!(timestamp.now(), 50_000);
+
+		// batches that are too large are rejected without sealing anything.
+		let (tx, rx) = futures::channel::oneshot::channel();
+		sink.send(EngineCommand::SealBlocks {
+			count: rpc::MAX_BLOCKS_PER_COMMAND + 1,
+			create_empty: true,
+			finalize: false,
+			sender: Some(tx),
+		}).await.unwrap();
+		assert_matches::assert_matches!(rx.await.unwrap(), Err(Error::TooManyBlocks(..)));
+		assert_eq!(client.info().best_number, 3);
+	}
+
+	#[tokio::test]
+	async fn manual_seal_with_aura_digests() {
+		use sc_consensus_aura::{AuraBlockImport, CompatibleDigestItem};
+		use sp_consensus_aura::sr25519::{AuthorityId, AuthorityPair};
+
+		let builder = TestClientBuilder::new();
+		let (client, select_chain) = builder.build_with_longest_chain();
+		let client = Arc::new(client);
+		let inherent_data_providers = InherentDataProviders::new();
+		let spawner = sp_core::testing::TaskExecutor::new();
+		let pool = Arc::new(BasicPool::with_revalidation_type(
+			Options::default(), api(), None, RevalidationType::Full, spawner,
+		));
+		let env = ProposerFactory::new(
+			client.clone(),
+			pool.clone(),
+			None,
+		);
+		let genesis = client.header(&BlockId::Number(0)).unwrap().unwrap();
+		let consensus_data_provider = AuraConsensusDataProvider::<_, _, AuthorityId>::new(
+			client.clone(),
+			&genesis,
+		).unwrap();
+		let block_import = AuraBlockImport::<_, _, _, AuthorityPair>::new(client.clone(), client.clone());
+		// the test runtime has a slot duration of one second.
+		let timestamp = ManualTimestamp::new(1_000).with_auto_advance(1_000);
+		// this test checks that the blocks pass the Aura block import, with the slot of their timestamp.
+		let (mut sink, stream) = futures::channel::mpsc::channel(1024);
+		let future = run_manual_seal(
+			ManualSealParams {
+				block_import: Box::new(block_import),
+				env,
+				client: client.clone(),
+				pool: pool.pool().clone(),
+				commands_stream: stream,
+				select_chain,
+				consensus_data_provider: Some(Box::new(consensus_data_provider)),
+				timestamp: Some(timestamp),
+				inherent_data_providers,
+			}
+		);
+		std::thread::spawn(|| {
+			let mut rt = tokio::runtime::Runtime::new().unwrap();
+			// spawn the background authorship task
+			rt.block_on(future);
+		});
+
+		let (tx, rx) = futures::channel::oneshot::channel();
+		sink.send(EngineCommand::SealBlocks {
+			count: 3,
+			create_empty: true,
+			finalize: false,
+			sender: Some(tx),
+		}).await.unwrap();
+		let created_blocks = rx.await.unwrap().unwrap();
+
+		assert_eq!(created_blocks.len(), 3);
+		for (number, created_block) in (1..=3).zip(created_blocks) {
+			let header = client.header(&BlockId::Hash(created_block.hash)).unwrap().unwrap();
+			assert_eq!(header.number, number);
+			let slot_number = header.digest.logs().iter()
+				.find_map(<DigestItem<_> as CompatibleDigestItem<AuthorityPair>>::as_aura_pre_digest);
+			assert_eq!(slot_number, Some(number));
+		}
+	}
+
+	#[tokio::test]
+	async fn manual_seal_with_babe_digests() {
+		use sp_consensus_babe::digests::CompatibleDigestItem;
+
+		let builder = TestClientBuilder::new();
+		let (client, select_chain) = builder.build_with_longest_chain();
+		let client = Arc::new(client);
+		let inherent_data_providers = InherentDataProviders::new();
+		let spawner = sp_core::testing::TaskExecutor::new();
+		let pool = Arc::new(BasicPool::with_revalidation_type(
+			Options::default(), api(), None, RevalidationType::Full, spawner,
+		));
+		let env = ProposerFactory::new(
+			client.clone(),
+			pool.clone(),
+			None,
+		);
+		let (block_import, babe_link) = sc_consensus_babe::block_import(
+			sc_consensus_babe::Config::get_or_compute(&*client).unwrap(),
+			client.clone(),
+			client.clone(),
+		).unwrap();
+		let consensus_data_provider = BabeConsensusDataProvider::new(client.clone(), &babe_link, 0).unwrap();
+		// the test runtime has a slot duration of one second and epochs of six slots.
+		let timestamp = ManualTimestamp::new(1_000).with_auto_advance(1_000);
+		// this test checks that the blocks pass the BABE block import, across an epoch change.
+		let (mut sink, stream) = futures::channel::mpsc::channel(1024);
+		let future = run_manual_seal(
+			ManualSealParams {
+				block_import: Box::new(block_import),
+				env,
+				client: client.clone(),
+				pool: pool.pool().clone(),
+				commands_stream: stream,
+				select_chain,
+				consensus_data_provider: Some(Box::new(consensus_data_provider)),
+				timestamp: Some(timestamp),
+				inherent_data_providers,
+			}
+		);
+		std::thread::spawn(|| {
+			let mut rt = tokio::runtime::Runtime::new().unwrap();
+			// spawn the background authorship task
+			rt.block_on(future);
+		});
+
+		let (tx, rx) = futures::channel::oneshot::channel();
+		sink.send(EngineCommand::SealBlocks {
+			count: 8,
+			create_empty: true,
+			finalize: false,
+			sender: Some(tx),
+		}).await.unwrap();
+		let created_blocks = rx.await.unwrap().unwrap();
+
+		assert_eq!(created_blocks.len(), 8);
+		for (number, created_block) in (1..=8).zip(created_blocks) {
+			let header = client.header(&BlockId::Hash(created_block.hash)).unwrap().unwrap();
+			assert_eq!(header.number, number);
+			let slot_number = header.digest.logs().iter()
+				.find_map(|log| log.as_babe_pre_digest())
+				.map(|pre_digest| pre_digest.slot_number());
+			assert_eq!(slot_number, Some(number));
+
+			// the first blocks of the genesis epoch and of the next one announce the epoch after.
+			let announces_next_epoch = header.digest.logs().iter()
+				.any(|log| log.as_next_epoch_descriptor().is_some());
+			assert_eq!(announces_next_epoch, number == 1 || number == 7);
+		}
+	}
 }
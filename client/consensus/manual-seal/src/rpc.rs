@@ -33,6 +33,9 @@ type FutureResult<T> = Box<dyn jsonrpc_core::futures::Future<Item = T, Error = E
 /// sender passed to the authorship task to report errors or successes.
 pub type Sender<T> = Option<oneshot::Sender<std::result::Result<T, crate::Error>>>;
 
+/// Maximum number of blocks that can be sealed with a single [`EngineCommand::SealBlocks`].
+pub const MAX_BLOCKS_PER_COMMAND: u32 = 1024;
+
 /// Message sent to the background authorship task, usually by RPC.
 pub enum EngineCommand<Hash> {
 	/// Tells the engine to propose a new block
@@ -60,7 +63,40 @@ pub enum EngineCommand<Hash> {
 		sender: Sender<()>,
 		/// finalization justification
 		justification: Option<Justification>,
-	}
+	},
+	/// Tells the engine to seal `count` blocks in a row, each on top of the previous one.
+	///
+	/// Stops at the first block that can't be sealed and reports its error. `count` can't be
+	/// larger than [`MAX_BLOCKS_PER_COMMAND`].
+	SealBlocks {
+		/// number of blocks to seal.
+		count: u32,
+		/// if true, empty blocks(without extrinsics) will be created.
+		/// otherwise, will return Error::EmptyTransactionPool.
+		create_empty: bool,
+		/// instantly finalize the blocks?
+		finalize: bool,
+		/// sender to report errors/success to the rpc.
+		sender: Sender<Vec<CreatedBlock<Hash>>>,
+	},
+	/// Tells the engine to seal the next block with the given timestamp, in milliseconds.
+	///
+	/// Requires the engine to run with a [`ManualTimestamp`](crate::ManualTimestamp).
+	SetTimestamp {
+		/// the new timestamp.
+		timestamp: u64,
+		/// sender to report errors/success to the rpc.
+		sender: Sender<()>,
+	},
+	/// Tells the engine to move the timestamp forward by the given milliseconds.
+	///
+	/// Requires the engine to run with a [`ManualTimestamp`](crate::ManualTimestamp).
+	AdvanceTime {
+		/// milliseconds to advance by.
+		by: u64,
+		/// sender to report the new timestamp or errors to the rpc.
+		sender: Sender<u64>,
+	},
 }
 
 /// RPC trait that provides methods for interacting with the manual-seal authorship task over rpc.
@@ -82,6 +118,24 @@ pub trait ManualSealApi<Hash> {
 		hash: Hash,
 		justification: Option<Justification>
 	) -> FutureResult<bool>;
+
+	/// Instructs the manual-seal authorship task to create `count` blocks in a row, up to
+	/// `MAX_BLOCKS_PER_COMMAND`
+	#[rpc(name = "engine_createBlocks")]
+	fn create_blocks(
+		&self,
+		count: u32,
+		create_empty: bool,
+		finalize: bool
+	) -> FutureResult<Vec<CreatedBlock<Hash>>>;
+
+	/// Sets the timestamp of the next block, in milliseconds
+	#[rpc(name = "engine_setTimestamp")]
+	fn set_timestamp(&self, timestamp: u64) -> FutureResult<bool>;
+
+	/// Moves the timestamp forward by the given milliseconds, returns the new timestamp
+	#[rpc(name = "engine_advanceTime")]
+	fn advance_time(&self, by: u64) -> FutureResult<u64>;
 }
 
 /// A struct that implements the [`ManualSealApi`].
@@ -141,6 +195,56 @@ impl<Hash: Send + 'static> ManualSealApi<Hash> for ManualSeal<Hash> {
 
 		Box::new(future.boxed().map_err(Error::from).compat())
 	}
+
+	fn create_blocks(
+		&self,
+		count: u32,
+		create_empty: bool,
+		finalize: bool
+	) -> FutureResult<Vec<CreatedBlock<Hash>>> {
+		if count > MAX_BLOCKS_PER_COMMAND {
+			let error = crate::Error::TooManyBlocks(count, MAX_BLOCKS_PER_COMMAND);
+			return Box::new(jsonrpc_core::futures::future::err::<Vec<CreatedBlock<Hash>>, _>(
+				Error::from(error),
+			));
+		}
+
+		let mut sink = self.import_block_channel.clone();
+		let future = async move {
+			let (sender, receiver) = oneshot::channel();
+			sink.send(
+				EngineCommand::SealBlocks { count, create_empty, finalize, sender: Some(sender) }
+			).await?;
+
+			receiver.await?
+		};
+
+		Box::new(future.boxed().map_err(Error::from).compat())
+	}
+
+	fn set_timestamp(&self, timestamp: u64) -> FutureResult<bool> {
+		let mut sink = self.import_block_channel.clone();
+		let future = async move {
+			let (sender, receiver) = oneshot::channel();
+			sink.send(EngineCommand::SetTimestamp { timestamp, sender: Some(sender) }).await?;
+
+			receiver.await?.map(|_| true)
+		};
+
+		Box::new(future.boxed().map_err(Error::from).compat())
+	}
+
+	fn advance_time(&self, by: u64) -> FutureResult<u64> {
+		let mut sink = self.import_block_channel.clone();
+		let future = async move {
+			let (sender, receiver) = oneshot::channel();
+			sink.send(EngineCommand::AdvanceTime { by, sender: Some(sender) }).await?;
+
+			receiver.await?
+		};
+
+		Box::new(future.boxed().map_err(Error::from).compat())
+	}
 }
 
 /// report any errors or successes encountered by the authorship task back
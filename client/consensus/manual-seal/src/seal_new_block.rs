@@ -16,10 +16,10 @@
 
 //! Block sealing utilities
 
-use crate::{Error, rpc};
+use crate::{Error, rpc, ConsensusDataProvider, ManualTimestamp};
 use std::sync::Arc;
 use sp_runtime::{
-	traits::{Block as BlockT, Header as HeaderT},
+	traits::Block as BlockT,
 	generic::BlockId,
 };
 use futures::prelude::*;
@@ -63,10 +63,31 @@ pub struct SealBlockParams<'a, B: BlockT, SC, HB, E, T, P: txpool::ChainApi> {
 	pub block_import: &'a mut BoxBlockImport<B, T>,
 	/// inherent data provider
 	pub inherent_data_provider: &'a InherentDataProviders,
+	/// digest provider for inclusion in blocks.
+	pub consensus_data_provider: Option<&'a dyn ConsensusDataProvider<B, Transaction = T>>,
+	/// timestamp control, advanced after every sealed block.
+	pub timestamp: Option<&'a ManualTimestamp>,
 }
 
 /// seals a new block with the given params
 pub async fn seal_new_block<B, SC, HB, E, T, P>(
+	mut params: SealBlockParams<'_, B, SC, HB, E, T, P>
+)
+	where
+		B: BlockT,
+		HB: HeaderBackend<B>,
+		E: Environment<B>,
+		<E as Environment<B>>::Error: std::fmt::Display,
+		<E::Proposer as Proposer<B>>::Error: std::fmt::Display,
+		P: txpool::ChainApi<Block=B>,
+		SC: SelectChain<B>,
+{
+	let result = seal_block(&mut params).await;
+	rpc::send_result(&mut params.sender, result)
+}
+
+/// seals a new block with the given params and returns it, without reporting to the rpc.
+pub(crate) async fn seal_block<B, SC, HB, E, T, P>(
 	SealBlockParams {
 		create_empty,
 		finalize,
@@ -77,10 +98,11 @@ pub async fn seal_new_block<B, SC, HB, E, T, P>(
 		block_import,
 		env,
 		inherent_data_provider,
-		mut sender,
+		consensus_data_provider,
+		timestamp,
 		..
-	}: SealBlockParams<'_, B, SC, HB, E, T, P>
-)
+	}: &mut SealBlockParams<'_, B, SC, HB, E, T, P>
+) -> Result<CreatedBlock<<B as BlockT>::Hash>, Error>
 	where
 		B: BlockT,
 		HB: HeaderBackend<B>,
@@ -90,48 +112,64 @@ pub async fn seal_new_block<B, SC, HB, E, T, P>(
 		P: txpool::ChainApi<Block=B>,
 		SC: SelectChain<B>,
 {
-	let future = async {
-		if pool.validated_pool().status().ready == 0 && !create_empty {
-			return Err(Error::EmptyTransactionPool)
-		}
+	if pool.validated_pool().status().ready == 0 && !*create_empty {
+		return Err(Error::EmptyTransactionPool)
+	}
 
-		// get the header to build this new block on.
-		// use the parent_hash supplied via `EngineCommand`
-		// or fetch the best_block.
-		let header = match parent_hash {
-			Some(hash) => {
-				match client.header(BlockId::Hash(hash))? {
-					Some(header) => header,
-					None => return Err(Error::BlockNotFound(format!("{}", hash))),
-				}
+	// get the header to build this new block on.
+	// use the parent_hash supplied via `EngineCommand`
+	// or fetch the best_block.
+	let parent = match parent_hash {
+		Some(hash) => {
+			match client.header(BlockId::Hash(*hash))? {
+				Some(header) => header,
+				None => return Err(Error::BlockNotFound(format!("{}", hash))),
 			}
-			None => select_chain.best_chain()?
-		};
-
-		let proposer = env.init(&header)
-			.map_err(|err| Error::StringError(format!("{}", err))).await?;
-		let id = inherent_data_provider.create_inherent_data()?;
-		let inherents_len = id.len();
-		let proposal = proposer.propose(id, Default::default(), Duration::from_secs(MAX_PROPOSAL_DURATION), false.into())
-			.map_err(|err| Error::StringError(format!("{}", err))).await?;
-
-		if proposal.block.extrinsics().len() == inherents_len && !create_empty {
-			return Err(Error::EmptyTransactionPool)
 		}
+		None => select_chain.best_chain()?
+	};
 
-		let (header, body) = proposal.block.deconstruct();
-		let mut params = BlockImportParams::new(BlockOrigin::Own, header.clone());
-		params.body = Some(body);
-		params.finalized = finalize;
-		params.fork_choice = Some(ForkChoiceStrategy::LongestChain);
-
-		match block_import.import_block(params, HashMap::new())? {
-			ImportResult::Imported(aux) => {
-				Ok(CreatedBlock { hash: <B as BlockT>::Header::hash(&header), aux })
-			},
-			other => Err(other.into()),
-		}
+	let proposer = env.init(&parent)
+		.map_err(|err| Error::StringError(format!("{}", err))).await?;
+	let id = inherent_data_provider.create_inherent_data()?;
+	let inherents_len = id.len();
+
+	let digest = match consensus_data_provider {
+		Some(provider) => provider.create_digest(&parent, &id)?,
+		None => Default::default(),
 	};
 
-	rpc::send_result(&mut sender, future.await)
+	let proposal = proposer.propose(
+		id.clone(),
+		digest,
+		Duration::from_secs(MAX_PROPOSAL_DURATION),
+		false.into(),
+	).map_err(|err| Error::StringError(format!("{}", err))).await?;
+
+	if proposal.block.extrinsics().len() == inherents_len && !*create_empty {
+		return Err(Error::EmptyTransactionPool)
+	}
+
+	let (header, body) = proposal.block.deconstruct();
+	let mut params = BlockImportParams::new(BlockOrigin::Own, header);
+	params.body = Some(body);
+	params.finalized = *finalize;
+	params.fork_choice = Some(ForkChoiceStrategy::LongestChain);
+
+	if let Some(provider) = consensus_data_provider {
+		provider.append_block_import(&parent, &mut params, &id)?;
+	}
+
+	// the consensus data provider may have added digests to the header.
+	let hash = params.post_hash();
+	match block_import.import_block(params, HashMap::new())? {
+		ImportResult::Imported(aux) => {
+			if let Some(timestamp) = timestamp {
+				timestamp.on_block_sealed();
+			}
+
+			Ok(CreatedBlock { hash, aux })
+		},
+		other => Err(other.into()),
+	}
 }
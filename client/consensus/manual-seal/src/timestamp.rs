@@ -0,0 +1,122 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Deterministic control of the timestamp inherent.
+
+use std::sync::Arc;
+use parking_lot::Mutex;
+use sp_inherents::{InherentData, InherentIdentifier, ProvideInherentData};
+use sp_timestamp::{InherentError, InherentType, INHERENT_IDENTIFIER};
+
+struct State {
+	now: InherentType,
+	auto_advance: Option<InherentType>,
+}
+
+/// A timestamp inherent data provider whose time only changes when told to.
+///
+/// The engine registers the provider in place of `sp_timestamp::InherentDataProvider`. The time
+/// is moved by the `SetTimestamp` and `AdvanceTime` engine commands and, if configured, advanced
+/// by a fixed step after every sealed block. All clones share the same time.
+#[derive(Clone)]
+pub struct ManualTimestamp {
+	state: Arc<Mutex<State>>,
+}
+
+impl ManualTimestamp {
+	/// Create a new provider starting at `timestamp`, in milliseconds since the unix epoch.
+	pub fn new(timestamp: InherentType) -> Self {
+		Self {
+			state: Arc::new(Mutex::new(State { now: timestamp, auto_advance: None })),
+		}
+	}
+
+	/// Advance the time by `step` milliseconds after every sealed block.
+	///
+	/// Using the slot duration of the chain puts every block into the next slot.
+	pub fn with_auto_advance(self, step: InherentType) -> Self {
+		self.state.lock().auto_advance = Some(step);
+		self
+	}
+
+	/// The timestamp the next block is sealed with.
+	pub fn now(&self) -> InherentType {
+		self.state.lock().now
+	}
+
+	/// Set the timestamp of the next block.
+	///
+	/// Note that runtimes usually reject timestamps that don't increase.
+	pub fn set(&self, timestamp: InherentType) {
+		self.state.lock().now = timestamp;
+	}
+
+	/// Move the time forward by `duration` milliseconds, returns the new timestamp.
+	pub fn advance(&self, duration: InherentType) -> InherentType {
+		let mut state = self.state.lock();
+		state.now = state.now.saturating_add(duration);
+		state.now
+	}
+
+	/// Apply the automatic advance after a block was sealed.
+	pub(crate) fn on_block_sealed(&self) {
+		let mut state = self.state.lock();
+		if let Some(step) = state.auto_advance {
+			state.now = state.now.saturating_add(step);
+		}
+	}
+}
+
+impl ProvideInherentData for ManualTimestamp {
+	fn inherent_identifier(&self) -> &'static InherentIdentifier {
+		&INHERENT_IDENTIFIER
+	}
+
+	fn provide_inherent_data(
+		&self,
+		inherent_data: &mut InherentData,
+	) -> Result<(), sp_inherents::Error> {
+		inherent_data.put_data(INHERENT_IDENTIFIER, &self.now())
+	}
+
+	fn error_to_string(&self, error: &[u8]) -> Option<String> {
+		InherentError::try_from(&INHERENT_IDENTIFIER, error).map(|e| format!("{:?}", e))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_timestamp::TimestampInherentData;
+
+	#[test]
+	fn time_only_moves_when_told_to() {
+		let timestamp = ManualTimestamp::new(1_000).with_auto_advance(6_000);
+		let clone = timestamp.clone();
+
+		let mut data = InherentData::new();
+		timestamp.provide_inherent_data(&mut data).unwrap();
+		assert_eq!(data.timestamp_inherent_data().unwrap(), 1_000);
+
+		clone.on_block_sealed();
+		assert_eq!(timestamp.now(), 7_000);
+		assert_eq!(clone.advance(500), 7_500);
+		timestamp.set(20_000);
+		assert_eq!(clone.now(), 20_000);
+	}
+}
@@ -23,6 +23,12 @@
 //! with other necessary client references to `import_queue` to setup
 //! the queue. Use the `start_mine` function for basic CPU mining.
 //!
+//! Mining is split into the authorship thread started by `start_mining_worker`,
+//! which builds the blocks to be mined, and miners that search for seals. The
+//! `MiningWorker` returned by it hands the current work to the CPU miner of
+//! `start_cpu_miner` as well as to external miners, which fetch work and submit
+//! seals over the RPC in the `rpc` module.
+//!
 //! The auxiliary storage for PoW engine only stores the total difficulty.
 //! For other storage requirements for particular PoW algorithm (such as
 //! the actual difficulty for each particular blocks), you can take a client
@@ -31,6 +37,11 @@
 //! as the storage, but it is not recommended as it won't work well with light
 //! clients.
 
+mod worker;
+pub mod rpc;
+
+pub use crate::worker::{MiningWorker, MiningMetadata, MiningBuild};
+
 use std::sync::Arc;
 use std::any::Any;
 use std::borrow::Cow;
@@ -68,6 +79,8 @@ pub enum Error<B: BlockT> {
 	HeaderUnsealed(B::Hash),
 	#[display(fmt = "PoW validation error: invalid seal")]
 	InvalidSeal,
+	#[display(fmt = "Submitted seal is for outdated work")]
+	StaleWork,
 	#[display(fmt = "PoW validation error: preliminary verification failed")]
 	FailedPreliminaryVerify,
 	#[display(fmt = "Rejecting block too far in future")]
@@ -500,41 +513,97 @@ pub fn import_queue<B, Transaction, Algorithm>(
 /// information, or just be a graffiti. `round` is for number of rounds the
 /// CPU miner runs each time. This parameter should be tweaked so that each
 /// mining round is within sub-second time.
+///
+/// This is a combination of `start_mining_worker` and `start_cpu_miner`. The
+/// returned worker can be used to let external miners work alongside the CPU
+/// miner.
 pub fn start_mine<B: BlockT, C, Algorithm, E, SO, S, CAW>(
-	mut block_import: BoxBlockImport<B, sp_api::TransactionFor<C, B>>,
+	block_import: BoxBlockImport<B, sp_api::TransactionFor<C, B>>,
 	client: Arc<C>,
 	algorithm: Algorithm,
-	mut env: E,
+	env: E,
 	preruntime: Option<Vec<u8>>,
 	round: u32,
+	sync_oracle: SO,
+	build_time: std::time::Duration,
+	select_chain: Option<S>,
+	inherent_data_providers: sp_inherents::InherentDataProviders,
+	can_author_with: CAW,
+) -> MiningWorker<B, Algorithm, sp_api::TransactionFor<C, B>> where
+	C: HeaderBackend<B> + AuxStore + ProvideRuntimeApi<B> + Send + Sync + 'static,
+	Algorithm: PowAlgorithm<B> + Send + Sync + 'static,
+	Algorithm::Difficulty: Send + 'static,
+	E: Environment<B> + Send + Sync + 'static,
+	E::Error: std::fmt::Debug,
+	E::Proposer: Proposer<B, Transaction = sp_api::TransactionFor<C, B>>,
+	SO: SyncOracle + Send + Sync + 'static,
+	S: SelectChain<B> + 'static,
+	CAW: CanAuthorWith<B> + Send + 'static,
+	sp_api::TransactionFor<C, B>: Send + 'static,
+{
+	let worker = start_mining_worker(
+		block_import,
+		client,
+		algorithm,
+		env,
+		preruntime,
+		sync_oracle,
+		build_time,
+		select_chain,
+		inherent_data_providers,
+		can_author_with,
+		None,
+	);
+	start_cpu_miner(worker.clone(), round);
+	worker
+}
+
+/// Start the background authorship thread for PoW, which builds the blocks to
+/// be mined and hands them to the returned mining worker.
+///
+/// A new block is built whenever the best block changes, and at the latest
+/// after `REBUILD_INTERVAL` so that new transactions are included. While the
+/// node is major syncing, there is no work. The worker does not mine by itself,
+/// seals are found by `start_cpu_miner` or by external miners, e.g. through
+/// the RPC in the `rpc` module.
+pub fn start_mining_worker<B: BlockT, C, Algorithm, E, SO, S, CAW>(
+	block_import: BoxBlockImport<B, sp_api::TransactionFor<C, B>>,
+	client: Arc<C>,
+	algorithm: Algorithm,
+	mut env: E,
+	preruntime: Option<Vec<u8>>,
 	mut sync_oracle: SO,
 	build_time: std::time::Duration,
 	select_chain: Option<S>,
 	inherent_data_providers: sp_inherents::InherentDataProviders,
 	can_author_with: CAW,
-) where
-	C: HeaderBackend<B> + AuxStore + ProvideRuntimeApi<B> + 'static,
+	registry: Option<&Registry>,
+) -> MiningWorker<B, Algorithm, sp_api::TransactionFor<C, B>> where
+	C: HeaderBackend<B> + AuxStore + ProvideRuntimeApi<B> + Send + Sync + 'static,
 	Algorithm: PowAlgorithm<B> + Send + Sync + 'static,
+	Algorithm::Difficulty: Send + 'static,
 	E: Environment<B> + Send + Sync + 'static,
 	E::Error: std::fmt::Debug,
 	E::Proposer: Proposer<B, Transaction = sp_api::TransactionFor<C, B>>,
 	SO: SyncOracle + Send + Sync + 'static,
 	S: SelectChain<B> + 'static,
 	CAW: CanAuthorWith<B> + Send + 'static,
+	sp_api::TransactionFor<C, B>: Send + 'static,
 {
 	if let Err(_) = register_pow_inherent_data_provider(&inherent_data_providers) {
 		warn!("Registering inherent data provider for timestamp failed");
 	}
 
+	let worker = MiningWorker::new(block_import, algorithm, registry);
+	let thread_worker = worker.clone();
+
 	thread::spawn(move || {
 		loop {
-			match mine_loop(
-				&mut block_import,
+			match build_loop(
+				&thread_worker,
 				client.as_ref(),
-				&algorithm,
 				&mut env,
 				preruntime.as_ref(),
-				round,
 				&mut sync_oracle,
 				build_time.clone(),
 				select_chain.as_ref(),
@@ -543,22 +612,75 @@ pub fn start_mine<B: BlockT, C, Algorithm, E, SO, S, CAW>(
 			) {
 				Ok(()) => (),
 				Err(e) => error!(
-					"Mining block failed with {:?}. Sleep for 1 second before restarting...",
+					"Building block for mining failed with {:?}. Sleep for 1 second before restarting...",
 					e
 				),
 			}
+			thread_worker.invalidate();
 			std::thread::sleep(std::time::Duration::new(1, 0));
 		}
 	});
+
+	worker
 }
 
-fn mine_loop<B: BlockT, C, Algorithm, E, SO, S, CAW>(
-	block_import: &mut BoxBlockImport<B, sp_api::TransactionFor<C, B>>,
+/// Start the background CPU mining thread, which mines the work of `worker`.
+///
+/// `round` is for number of rounds the CPU miner runs each time, before it
+/// checks whether the work changed.
+pub fn start_cpu_miner<B, Algorithm, Transaction>(
+	worker: MiningWorker<B, Algorithm, Transaction>,
+	round: u32,
+) where
+	B: BlockT,
+	Algorithm: PowAlgorithm<B> + Send + Sync + 'static,
+	Algorithm::Difficulty: Send + 'static,
+	Transaction: Send + 'static,
+{
+	thread::spawn(move || {
+		loop {
+			let metadata = match worker.metadata() {
+				Some(metadata) => metadata,
+				None => {
+					std::thread::sleep(WORK_POLL_INTERVAL);
+					continue
+				},
+			};
+
+			match worker.algorithm().mine(
+				&BlockId::Hash(metadata.best_hash),
+				&metadata.pre_hash,
+				metadata.difficulty,
+				round,
+			) {
+				Ok(Some(seal)) => if let Err(e) = worker.submit(metadata.pre_hash, seal) {
+					warn!(target: "pow", "Submitting mined seal failed with {:?}", e);
+				},
+				Ok(None) => (),
+				Err(e) => {
+					error!(
+						"Mining block failed with {:?}. Sleep for 1 second before restarting...",
+						e
+					);
+					std::thread::sleep(std::time::Duration::new(1, 0));
+				},
+			}
+		}
+	});
+}
+
+/// How often the authorship thread checks for a new best block, and the CPU
+/// miner for new work while there is none.
+const WORK_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// Maximum age of the work before the block is built again.
+const REBUILD_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+fn build_loop<B: BlockT, C, Algorithm, E, SO, S, CAW>(
+	worker: &MiningWorker<B, Algorithm, sp_api::TransactionFor<C, B>>,
 	client: &C,
-	algorithm: &Algorithm,
 	env: &mut E,
 	preruntime: Option<&Vec<u8>>,
-	round: u32,
 	sync_oracle: &mut SO,
 	build_time: std::time::Duration,
 	select_chain: Option<&S>,
@@ -573,14 +695,17 @@ fn mine_loop<B: BlockT, C, Algorithm, E, SO, S, CAW>(
 	E::Error: std::fmt::Debug,
 	SO: SyncOracle,
 	S: SelectChain<B>,
-	sp_api::TransactionFor<C, B>: 'static,
+	sp_api::TransactionFor<C, B>: Send + 'static,
 	CAW: CanAuthorWith<B>,
 {
-	'outer: loop {
+	let mut last_build = std::time::Instant::now();
+
+	loop {
 		if sync_oracle.is_major_syncing() {
 			debug!(target: "pow", "Skipping proposal due to sync.");
+			worker.invalidate();
 			std::thread::sleep(std::time::Duration::new(1, 0));
-			continue 'outer
+			continue
 		}
 
 		let (best_hash, best_header) = match select_chain {
@@ -599,6 +724,16 @@ fn mine_loop<B: BlockT, C, Algorithm, E, SO, S, CAW>(
 			},
 		};
 
+		if worker.best_hash() == Some(best_hash) {
+			if last_build.elapsed() < REBUILD_INTERVAL {
+				std::thread::sleep(WORK_POLL_INTERVAL);
+				continue
+			}
+		} else {
+			// Seals for the previous work would no longer extend the best chain.
+			worker.invalidate();
+		}
+
 		if let Err(err) = can_author_with.can_author_with(&BlockId::Hash(best_hash)) {
 			warn!(
 				target: "pow",
@@ -607,7 +742,7 @@ fn mine_loop<B: BlockT, C, Algorithm, E, SO, S, CAW>(
 				err,
 			);
 			std::thread::sleep(std::time::Duration::from_secs(1));
-			continue 'outer
+			continue
 		}
 
 		let proposer = futures::executor::block_on(env.init(&best_header))
@@ -626,53 +761,13 @@ fn mine_loop<B: BlockT, C, Algorithm, E, SO, S, CAW>(
 			RecordProof::No,
 		)).map_err(|e| Error::BlockProposingError(format!("{:?}", e)))?;
 
-		let (header, body) = proposal.block.deconstruct();
-		let (difficulty, seal) = {
-			let difficulty = algorithm.difficulty(best_hash)?;
-
-			loop {
-				let seal = algorithm.mine(
-					&BlockId::Hash(best_hash),
-					&header.hash(),
-					difficulty,
-					round,
-				)?;
-
-				if let Some(seal) = seal {
-					break (difficulty, seal)
-				}
-
-				if best_hash != client.info().best_hash {
-					continue 'outer
-				}
-			}
+		let metadata = MiningMetadata {
+			best_hash,
+			pre_hash: proposal.block.header().hash(),
+			difficulty: worker.algorithm().difficulty(best_hash)?,
 		};
 
-		let (hash, seal) = {
-			let seal = DigestItem::Seal(POW_ENGINE_ID, seal);
-			let mut header = header.clone();
-			header.digest_mut().push(seal);
-			let hash = header.hash();
-			let seal = header.digest_mut().pop()
-				.expect("Pushed one seal above; length greater than zero; qed");
-			(hash, seal)
-		};
-
-		let intermediate = PowIntermediate::<Algorithm::Difficulty> {
-			difficulty: Some(difficulty),
-		};
-
-		let mut import_block = BlockImportParams::new(BlockOrigin::Own, header);
-		import_block.post_digests.push(seal);
-		import_block.body = Some(body);
-		import_block.storage_changes = Some(proposal.storage_changes);
-		import_block.intermediates.insert(
-			Cow::from(INTERMEDIATE_KEY),
-			Box::new(intermediate) as Box<dyn Any>
-		);
-		import_block.post_hash = Some(hash);
-
-		block_import.import_block(import_block, HashMap::default())
-			.map_err(|e| Error::BlockBuiltError(best_hash, e))?;
+		worker.on_build(MiningBuild { metadata, proposal });
+		last_build = std::time::Instant::now();
 	}
 }
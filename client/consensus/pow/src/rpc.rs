@@ -0,0 +1,132 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! RPC interface for external miners.
+//!
+//! Miners poll `pow_getWork` for the block currently being mined and submit the seals they
+//! found with `pow_submitWork`. The work changes with every new best block, so miners should
+//! poll frequently and abandon the search when the pre-hash changes.
+
+use jsonrpc_core::{Error, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use serde::{de::DeserializeOwned, Serialize};
+use sp_core::Bytes;
+use sp_runtime::traits::Block as BlockT;
+
+use crate::{MiningMetadata, MiningWorker, PowAlgorithm};
+
+pub use self::gen_client::Client as PowClient;
+
+/// RPC methods for external miners.
+#[rpc]
+pub trait PowApi<Hash, Difficulty> {
+	/// Returns the metadata of the block currently being mined, or `null` if there is no work,
+	/// e.g. because the node is syncing.
+	#[rpc(name = "pow_getWork")]
+	fn get_work(&self) -> Result<Option<MiningMetadata<Hash, Difficulty>>>;
+
+	/// Submits a seal for the block with the given pre-hash and returns the hash of the sealed
+	/// block.
+	///
+	/// Fails if the seal is invalid or the work is outdated.
+	#[rpc(name = "pow_submitWork")]
+	fn submit_work(&self, pre_hash: Hash, seal: Bytes) -> Result<Hash>;
+}
+
+/// Implements the `PowApi` on top of a mining worker.
+pub struct Pow<B: BlockT, Algorithm: PowAlgorithm<B>, Transaction> {
+	worker: MiningWorker<B, Algorithm, Transaction>,
+}
+
+impl<B: BlockT, Algorithm: PowAlgorithm<B>, Transaction> Pow<B, Algorithm, Transaction> {
+	/// Create a new `Pow` RPC handler.
+	pub fn new(worker: MiningWorker<B, Algorithm, Transaction>) -> Self {
+		Self { worker }
+	}
+}
+
+impl<B, Algorithm, Transaction> PowApi<B::Hash, Algorithm::Difficulty>
+	for Pow<B, Algorithm, Transaction> where
+	B: BlockT,
+	Algorithm: PowAlgorithm<B> + Send + Sync + 'static,
+	Algorithm::Difficulty: Serialize + DeserializeOwned + Send + Sync + 'static,
+	Transaction: Send + 'static,
+{
+	fn get_work(&self) -> Result<Option<MiningMetadata<B::Hash, Algorithm::Difficulty>>> {
+		Ok(self.worker.metadata())
+	}
+
+	fn submit_work(&self, pre_hash: B::Hash, seal: Bytes) -> Result<B::Hash> {
+		self.worker.submit(pre_hash, seal.0).map_err(|e| Error {
+			code: ErrorCode::ServerError(1),
+			message: e.to_string(),
+			data: None,
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::worker::tests::{build, sealed_hash, worker, TestBlockImport, VALID_SEAL};
+
+	#[test]
+	fn work_is_fetched_and_submitted() {
+		let block_import = TestBlockImport::default();
+		let worker = worker(&block_import);
+		let mut io = jsonrpc_core::IoHandler::new();
+		io.extend_with(Pow::new(worker.clone()).to_delegate());
+
+		let get_work = r#"{"jsonrpc":"2.0","method":"pow_getWork","params":[],"id":1}"#;
+		assert_eq!(
+			io.handle_request_sync(get_work),
+			Some(r#"{"jsonrpc":"2.0","result":null,"id":1}"#.into()),
+		);
+
+		let build = build(1);
+		let metadata = build.metadata.clone();
+		worker.on_build(build);
+		assert_eq!(
+			io.handle_request_sync(get_work),
+			Some(format!(
+				r#"{{"jsonrpc":"2.0","result":{{"bestHash":"{:?}","preHash":"{:?}","difficulty":1}},"id":1}}"#,
+				metadata.best_hash,
+				metadata.pre_hash,
+			)),
+		);
+
+		let submit_work = format!(
+			r#"{{"jsonrpc":"2.0","method":"pow_submitWork","params":["{:?}","{}"],"id":2}}"#,
+			metadata.pre_hash,
+			sp_core::bytes::to_hex(VALID_SEAL, false),
+		);
+		assert_eq!(
+			io.handle_request_sync(&submit_work),
+			Some(format!(r#"{{"jsonrpc":"2.0","result":"{:?}","id":2}}"#, sealed_hash(1, VALID_SEAL))),
+		);
+
+		// The work was used up by the first submission.
+		assert_eq!(
+			io.handle_request_sync(&submit_work),
+			Some(
+				r#"{"jsonrpc":"2.0","error":{"code":1,"message":"Submitted seal is for outdated work"},"id":2}"#
+					.into()
+			),
+		);
+	}
+}
@@ -0,0 +1,405 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! The mining worker, which hands out work to miners and imports the sealed blocks.
+//!
+//! The worker holds the block currently being mined. Miners fetch its metadata, search for a
+//! seal and submit it back to the worker. The work is replaced whenever the authorship task
+//! builds a block on a new best block, so that seals for outdated work are rejected.
+
+use std::sync::Arc;
+use std::any::Any;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use parking_lot::Mutex;
+use serde::{Serialize, Deserialize};
+use sp_runtime::generic::{BlockId, DigestItem};
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT};
+use sp_consensus::{BlockImportParams, BlockOrigin, Proposal};
+use sp_consensus::import_queue::BoxBlockImport;
+use sp_consensus_pow::{Seal, POW_ENGINE_ID};
+use prometheus_endpoint::{register, Counter, PrometheusError, Registry, U64};
+use log::*;
+
+use crate::{Error, PowAlgorithm, PowIntermediate, INTERMEDIATE_KEY};
+
+/// Metadata of the block currently being mined.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MiningMetadata<H, D> {
+	/// The hash of the parent of the block being mined.
+	pub best_hash: H,
+	/// The hash of the block being mined, without the seal.
+	pub pre_hash: H,
+	/// The difficulty the seal has to satisfy.
+	pub difficulty: D,
+}
+
+/// A block built for mining, together with its metadata.
+pub struct MiningBuild<B: BlockT, Difficulty, Transaction> {
+	/// Metadata of the block.
+	pub metadata: MiningMetadata<B::Hash, Difficulty>,
+	/// The built block, including its storage changes.
+	pub proposal: Proposal<B, Transaction>,
+}
+
+struct Metrics {
+	seals_accepted: Counter<U64>,
+	seals_rejected: Counter<U64>,
+}
+
+impl Metrics {
+	fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			seals_accepted: register(
+				Counter::new(
+					"pow_seals_accepted",
+					"Total number of submitted seals that lead to an imported block",
+				)?,
+				registry,
+			)?,
+			seals_rejected: register(
+				Counter::new(
+					"pow_seals_rejected",
+					"Total number of submitted seals that were invalid or for outdated work",
+				)?,
+				registry,
+			)?,
+		})
+	}
+}
+
+/// Handle to the mining worker.
+///
+/// The handle is cheap to clone, all clones refer to the same work.
+pub struct MiningWorker<B: BlockT, Algorithm: PowAlgorithm<B>, Transaction> {
+	algorithm: Arc<Algorithm>,
+	build: Arc<Mutex<Option<MiningBuild<B, Algorithm::Difficulty, Transaction>>>>,
+	/// Locked separately from the work, so that importing a block doesn't block the miners
+	/// and the authorship task.
+	block_import: Arc<Mutex<BoxBlockImport<B, Transaction>>>,
+	metrics: Arc<Option<Metrics>>,
+}
+
+impl<B: BlockT, Algorithm: PowAlgorithm<B>, Transaction> Clone for MiningWorker<B, Algorithm, Transaction> {
+	fn clone(&self) -> Self {
+		Self {
+			algorithm: self.algorithm.clone(),
+			build: self.build.clone(),
+			block_import: self.block_import.clone(),
+			metrics: self.metrics.clone(),
+		}
+	}
+}
+
+impl<B, Algorithm, Transaction> MiningWorker<B, Algorithm, Transaction> where
+	B: BlockT,
+	Algorithm: PowAlgorithm<B>,
+	Algorithm::Difficulty: 'static,
+	Transaction: Send + 'static,
+{
+	pub(crate) fn new(
+		block_import: BoxBlockImport<B, Transaction>,
+		algorithm: Algorithm,
+		registry: Option<&Registry>,
+	) -> Self {
+		let metrics = registry.and_then(|registry|
+			Metrics::register(registry)
+				.map_err(|err| warn!("Failed to register prometheus metrics: {}", err))
+				.ok()
+		);
+
+		Self {
+			algorithm: Arc::new(algorithm),
+			build: Arc::new(Mutex::new(None)),
+			block_import: Arc::new(Mutex::new(block_import)),
+			metrics: Arc::new(metrics),
+		}
+	}
+
+	/// The algorithm the seals are verified with.
+	pub fn algorithm(&self) -> &Algorithm {
+		&self.algorithm
+	}
+
+	/// The parent hash of the block currently being mined.
+	pub fn best_hash(&self) -> Option<B::Hash> {
+		self.build.lock().as_ref().map(|build| build.metadata.best_hash)
+	}
+
+	/// Metadata of the block currently being mined, `None` if there is no work.
+	pub fn metadata(&self) -> Option<MiningMetadata<B::Hash, Algorithm::Difficulty>> {
+		self.build.lock().as_ref().map(|build| build.metadata.clone())
+	}
+
+	/// Drop the current work, e.g. because the node is syncing or the best block changed.
+	pub(crate) fn invalidate(&self) {
+		*self.build.lock() = None;
+	}
+
+	/// Replace the current work.
+	pub(crate) fn on_build(&self, build: MiningBuild<B, Algorithm::Difficulty, Transaction>) {
+		*self.build.lock() = Some(build);
+	}
+
+	/// Submit a seal for the block with the given pre-hash.
+	///
+	/// The seal is verified against the current work and the sealed block is imported. Returns
+	/// the hash of the sealed block.
+	pub fn submit(&self, pre_hash: B::Hash, seal: Seal) -> Result<B::Hash, Error<B>> {
+		let result = self.import_sealed(pre_hash, seal);
+
+		if let Some(metrics) = self.metrics.as_ref() {
+			match result {
+				Ok(_) => metrics.seals_accepted.inc(),
+				Err(_) => metrics.seals_rejected.inc(),
+			}
+		}
+
+		result
+	}
+
+	fn import_sealed(&self, pre_hash: B::Hash, seal: Seal) -> Result<B::Hash, Error<B>> {
+		let metadata = match self.metadata() {
+			Some(metadata) if metadata.pre_hash == pre_hash => metadata,
+			_ => return Err(Error::StaleWork),
+		};
+
+		if !self.algorithm.verify(
+			&BlockId::Hash(metadata.best_hash),
+			&metadata.pre_hash,
+			&seal,
+			metadata.difficulty,
+		)? {
+			return Err(Error::InvalidSeal)
+		}
+
+		// The work may have been replaced while the seal was being verified.
+		let build = {
+			let mut build = self.build.lock();
+			match build.take() {
+				Some(taken) if taken.metadata.pre_hash == pre_hash => taken,
+				other => {
+					*build = other;
+					return Err(Error::StaleWork)
+				},
+			}
+		};
+		let (header, body) = build.proposal.block.deconstruct();
+
+		let (hash, seal) = {
+			let seal = DigestItem::Seal(POW_ENGINE_ID, seal);
+			let mut header = header.clone();
+			header.digest_mut().push(seal);
+			let hash = header.hash();
+			let seal = header.digest_mut().pop()
+				.expect("Pushed one seal above; length greater than zero; qed");
+			(hash, seal)
+		};
+
+		let intermediate = PowIntermediate::<Algorithm::Difficulty> {
+			difficulty: Some(metadata.difficulty),
+		};
+
+		let mut import_block = BlockImportParams::new(BlockOrigin::Own, header);
+		import_block.post_digests.push(seal);
+		import_block.body = Some(body);
+		import_block.storage_changes = Some(build.proposal.storage_changes);
+		import_block.intermediates.insert(
+			Cow::from(INTERMEDIATE_KEY),
+			Box::new(intermediate) as Box<dyn Any>
+		);
+		import_block.post_hash = Some(hash);
+
+		self.block_import.lock().import_block(import_block, HashMap::default())
+			.map_err(|e| Error::BlockBuiltError(metadata.best_hash, e))?;
+
+		info!(target: "pow", "✅ Successfully mined block on top of: {}", metadata.best_hash);
+		Ok(hash)
+	}
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+	use super::*;
+	use sp_consensus::{BlockCheckParams, Error as ConsensusError, ImportResult, BlockImport};
+	use sp_runtime::testing::{Block as RawBlock, ExtrinsicWrapper, Header};
+
+	pub(crate) type Block = RawBlock<ExtrinsicWrapper<u64>>;
+	type Worker = MiningWorker<Block, TestAlgorithm, ()>;
+
+	pub(crate) const VALID_SEAL: &[u8] = b"valid";
+
+	pub(crate) struct TestAlgorithm;
+
+	impl PowAlgorithm<Block> for TestAlgorithm {
+		type Difficulty = u128;
+
+		fn difficulty(&self, _parent: <Block as BlockT>::Hash) -> Result<u128, Error<Block>> {
+			Ok(1)
+		}
+
+		fn verify(
+			&self,
+			_parent: &BlockId<Block>,
+			_pre_hash: &<Block as BlockT>::Hash,
+			seal: &Seal,
+			_difficulty: u128,
+		) -> Result<bool, Error<Block>> {
+			Ok(seal.as_slice() == VALID_SEAL)
+		}
+
+		fn mine(
+			&self,
+			_parent: &BlockId<Block>,
+			_pre_hash: &<Block as BlockT>::Hash,
+			_difficulty: u128,
+			_round: u32,
+		) -> Result<Option<Seal>, Error<Block>> {
+			Ok(None)
+		}
+	}
+
+	/// Records the imported blocks, along with whether the worker had work while importing them.
+	#[derive(Clone, Default)]
+	pub(crate) struct TestBlockImport {
+		worker: Arc<Mutex<Option<Worker>>>,
+		imported: Arc<Mutex<Vec<(<Block as BlockT>::Hash, bool)>>>,
+		fail: bool,
+	}
+
+	impl BlockImport<Block> for TestBlockImport {
+		type Error = ConsensusError;
+		type Transaction = ();
+
+		fn check_block(
+			&mut self,
+			_block: BlockCheckParams<Block>,
+		) -> Result<ImportResult, Self::Error> {
+			Ok(ImportResult::imported(false))
+		}
+
+		fn import_block(
+			&mut self,
+			block: BlockImportParams<Block, ()>,
+			_cache: HashMap<sp_consensus::import_queue::CacheKeyId, Vec<u8>>,
+		) -> Result<ImportResult, Self::Error> {
+			if self.fail {
+				return Err(ConsensusError::ClientImport("Import failed".into()))
+			}
+
+			// Deadlocks if the worker is still locked during the import.
+			let has_work = self.worker.lock().as_ref().map_or(false, |worker| worker.metadata().is_some());
+			self.imported.lock().push((block.post_hash.expect("Set by the worker"), has_work));
+			Ok(ImportResult::imported(true))
+		}
+	}
+
+	pub(crate) fn worker(block_import: &TestBlockImport) -> Worker {
+		let worker = MiningWorker::new(Box::new(block_import.clone()), TestAlgorithm, None);
+		*block_import.worker.lock() = Some(worker.clone());
+		worker
+	}
+
+	pub(crate) fn build(number: u64) -> MiningBuild<Block, u128, ()> {
+		let header = Header::new_from_number(number);
+		MiningBuild {
+			metadata: MiningMetadata {
+				best_hash: header.parent_hash,
+				pre_hash: header.hash(),
+				difficulty: 1,
+			},
+			proposal: Proposal {
+				block: RawBlock { header, extrinsics: Vec::new() },
+				proof: None,
+				storage_changes: Default::default(),
+			},
+		}
+	}
+
+	pub(crate) fn sealed_hash(number: u64, seal: &[u8]) -> <Block as BlockT>::Hash {
+		let mut header = Header::new_from_number(number);
+		header.digest_mut().push(DigestItem::Seal(POW_ENGINE_ID, seal.to_vec()));
+		header.hash()
+	}
+
+	#[test]
+	fn valid_seal_is_accepted_and_imported() {
+		let block_import = TestBlockImport::default();
+		let worker = worker(&block_import);
+		let build = build(1);
+		let pre_hash = build.metadata.pre_hash;
+		worker.on_build(build);
+
+		let hash = worker.submit(pre_hash, VALID_SEAL.to_vec()).unwrap();
+
+		assert_eq!(hash, sealed_hash(1, VALID_SEAL));
+		// The work is used up before the block is imported.
+		assert_eq!(*block_import.imported.lock(), vec![(hash, false)]);
+		assert!(worker.metadata().is_none());
+	}
+
+	#[test]
+	fn seal_for_outdated_work_is_rejected() {
+		let block_import = TestBlockImport::default();
+		let worker = worker(&block_import);
+		let outdated = build(1);
+		let pre_hash = outdated.metadata.pre_hash;
+		worker.on_build(outdated);
+		worker.on_build(build(2));
+
+		let result = worker.submit(pre_hash, VALID_SEAL.to_vec());
+
+		assert!(matches!(result, Err(Error::StaleWork)));
+		assert!(block_import.imported.lock().is_empty());
+		assert_eq!(worker.metadata(), Some(build(2).metadata));
+
+		worker.invalidate();
+		let result = worker.submit(build(2).metadata.pre_hash, VALID_SEAL.to_vec());
+		assert!(matches!(result, Err(Error::StaleWork)));
+	}
+
+	#[test]
+	fn invalid_seal_is_rejected() {
+		let block_import = TestBlockImport::default();
+		let worker = worker(&block_import);
+		let build = build(1);
+		let metadata = build.metadata.clone();
+		worker.on_build(build);
+
+		let result = worker.submit(metadata.pre_hash, b"invalid".to_vec());
+
+		assert!(matches!(result, Err(Error::InvalidSeal)));
+		assert!(block_import.imported.lock().is_empty());
+		// The work can still be mined.
+		assert_eq!(worker.metadata(), Some(metadata));
+	}
+
+	#[test]
+	fn failed_import_is_reported() {
+		let block_import = TestBlockImport { fail: true, ..Default::default() };
+		let worker = worker(&block_import);
+		let build = build(1);
+		let pre_hash = build.metadata.pre_hash;
+		worker.on_build(build);
+
+		let result = worker.submit(pre_hash, VALID_SEAL.to_vec());
+
+		assert!(matches!(result, Err(Error::BlockBuiltError(..))));
+	}
+}
@@ -20,9 +20,11 @@
 
 use std::sync::Arc;
 use std::cell::{Cell, RefCell};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
+use codec::{Compact, CompactLen};
 use hash_db::{Prefix, Hasher};
+use parking_lot::Mutex;
 use sp_trie::{MemoryDB, prefixed_key};
 use sp_core::{storage::ChildInfo, hexdisplay::HexDisplay};
 use sp_runtime::traits::{Block as BlockT, HashFor};
@@ -37,16 +39,52 @@ type DbState<B> = sp_state_machine::TrieBackend<
 
 type State<B> = CachingState<DbState<B>, B>;
 
+/// Records the trie nodes read from the database, which make up the storage proof.
+#[derive(Default)]
+struct ProofRecorder {
+	/// The nodes of the proof, by hash, with their encoded size.
+	nodes: HashMap<Vec<u8>, u32>,
+	/// The nodes on the path to the whitelisted keys, by hash. These are left out of the proof.
+	whitelisted: HashSet<Vec<u8>>,
+	/// Whether the nodes that are read are on the path to a whitelisted key.
+	reading_whitelist: bool,
+}
+
+impl ProofRecorder {
+	fn record(&mut self, hash: &[u8], node: &[u8]) {
+		if self.reading_whitelist {
+			self.whitelisted.insert(hash.to_vec());
+		} else if !self.whitelisted.contains(hash) {
+			let len = node.len() as u32;
+			// A node is encoded in the proof with its length prefix.
+			let size = Compact::<u32>::compact_len(&len) as u32 + len;
+			self.nodes.insert(hash.to_vec(), size);
+		}
+	}
+
+	/// The encoded size of the proof made of the recorded nodes.
+	fn proof_size(&self) -> u32 {
+		let size = self.nodes.values().sum::<u32>();
+		// The proof is encoded as a vector of nodes.
+		size + Compact::<u32>::compact_len(&(self.nodes.len() as u32)) as u32
+	}
+}
+
 struct StorageDb<Block: BlockT> {
 	db: Arc<dyn KeyValueDB>,
+	proof_recorder: Arc<Mutex<ProofRecorder>>,
 	_block: std::marker::PhantomData<Block>,
 }
 
 impl<Block: BlockT> sp_state_machine::Storage<HashFor<Block>> for StorageDb<Block> {
 	fn get(&self, key: &Block::Hash, prefix: Prefix) -> Result<Option<DBValue>, String> {
-		let key = prefixed_key::<HashFor<Block>>(key, prefix);
-		self.db.get(0, &key)
-			.map_err(|e| format!("Database backend error: {:?}", e))
+		let prefixed_key = prefixed_key::<HashFor<Block>>(key, prefix);
+		let value = self.db.get(0, &prefixed_key)
+			.map_err(|e| format!("Database backend error: {:?}", e))?;
+		if let Some(ref node) = value {
+			self.proof_recorder.lock().record(key.as_ref(), node);
+		}
+		Ok(value)
 	}
 }
 
@@ -96,6 +134,7 @@ pub struct BenchmarkingState<B: BlockT> {
 	key_tracker: RefCell<HashMap<Vec<u8>, KeyTracker>>,
 	read_write_tracker: RefCell<ReadWriteTracker>,
 	whitelist: RefCell<Vec<Vec<u8>>>,
+	proof_recorder: Arc<Mutex<ProofRecorder>>,
 }
 
 impl<B: BlockT> BenchmarkingState<B> {
@@ -116,6 +155,7 @@ impl<B: BlockT> BenchmarkingState<B> {
 			key_tracker: Default::default(),
 			read_write_tracker: Default::default(),
 			whitelist: Default::default(),
+			proof_recorder: Default::default(),
 		};
 
 		state.add_whitelist_to_tracker();
@@ -143,12 +183,26 @@ impl<B: BlockT> BenchmarkingState<B> {
 			None => Arc::new(::kvdb_memorydb::create(1)),
 		};
 		self.db.set(Some(db.clone()));
-		let storage_db = Arc::new(StorageDb::<B> { db, _block: Default::default() });
-		*self.state.borrow_mut() = Some(State::new(
-			DbState::<B>::new(storage_db, self.root.get()),
-			self.shared_cache.clone(),
-			None
-		));
+		let storage_db = Arc::new(StorageDb::<B> {
+			db,
+			proof_recorder: self.proof_recorder.clone(),
+			_block: Default::default(),
+		});
+		let db_state = DbState::<B>::new(storage_db, self.root.get());
+
+		// Read the whitelisted keys straight from the trie, so that the nodes on their path are
+		// known before any of them gets recorded in the proof.
+		{
+			let mut proof_recorder = self.proof_recorder.lock();
+			proof_recorder.whitelisted.clear();
+			proof_recorder.reading_whitelist = true;
+		}
+		for key in self.whitelist.borrow().iter() {
+			let _ = db_state.storage(key);
+		}
+		self.proof_recorder.lock().reading_whitelist = false;
+
+		*self.state.borrow_mut() = Some(State::new(db_state, self.shared_cache.clone(), None));
 		Ok(())
 	}
 
@@ -170,6 +224,7 @@ impl<B: BlockT> BenchmarkingState<B> {
 	fn wipe_tracker(&self) {
 		*self.key_tracker.borrow_mut() = HashMap::new();
 		self.add_whitelist_to_tracker();
+		self.proof_recorder.lock().nodes.clear();
 		*self.read_write_tracker.borrow_mut() = Default::default();
 	}
 
@@ -423,6 +478,11 @@ impl<B: BlockT> StateBackend<HashFor<B>> for BenchmarkingState<B> {
 
 	/// Reset the key tracking information for the state db.
 	fn reset_read_write_count(&self) {
+		// Drop the values cached by the state, so that every read made from now on reaches the
+		// database and is recorded in the proof.
+		if let Err(e) = self.reopen() {
+			log::error!(target: "benchmark", "Failed to reopen the state: {}", e);
+		}
 		self.wipe_tracker()
 	}
 
@@ -430,6 +490,12 @@ impl<B: BlockT> StateBackend<HashFor<B>> for BenchmarkingState<B> {
 		*self.whitelist.borrow_mut() = new;
 	}
 
+	/// Get the encoded size of the proof of the trie nodes read from the state db, including the
+	/// ones read to compute the storage root, but not the ones on the path to whitelisted keys.
+	fn proof_size(&self) -> u32 {
+		self.proof_recorder.lock().proof_size()
+	}
+
 	fn register_overlay_stats(&mut self, stats: &sp_state_machine::StateMachineStats) {
 		self.state.borrow_mut().as_mut().map(|s| s.register_overlay_stats(stats));
 	}
@@ -444,3 +510,67 @@ impl<Block: BlockT> std::fmt::Debug for BenchmarkingState<Block> {
 		write!(f, "Bench DB")
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::Encode;
+	use sp_runtime::traits::BlakeTwo256;
+	use sp_state_machine::{InMemoryBackend, StorageProof, prove_read};
+	use substrate_test_runtime_client::runtime::Block;
+
+	// Values are long enough for no leaf to be inlined in its parent node.
+	fn storage() -> Storage {
+		Storage {
+			top: (0..64u8).map(|i| (vec![i; 4], vec![i; 40])).collect(),
+			children_default: Default::default(),
+		}
+	}
+
+	fn proof(keys: &[&[u8]]) -> StorageProof {
+		let backend: InMemoryBackend<BlakeTwo256> = storage().into();
+		prove_read(backend, keys.to_vec()).unwrap()
+	}
+
+	#[test]
+	fn proof_size_is_the_size_of_the_proof_of_the_keys_read() {
+		let state = BenchmarkingState::<Block>::new(storage(), None).unwrap();
+		// Cached by the state before the proof starts being recorded.
+		state.storage(&[1; 4]).unwrap();
+		state.reset_read_write_count();
+
+		state.storage(&[1; 4]).unwrap();
+		state.storage(&[42; 4]).unwrap();
+		state.storage(&[42; 4]).unwrap();
+
+		assert_eq!(state.proof_size(), proof(&[&[1; 4], &[42; 4]]).encoded_size() as u32);
+	}
+
+	#[test]
+	fn proof_size_includes_the_nodes_read_to_compute_the_storage_root() {
+		let state = BenchmarkingState::<Block>::new(storage(), None).unwrap();
+		state.reset_read_write_count();
+
+		let _ = state.storage_root(vec![(&[1u8; 4][..], Some(&[0u8; 40][..]))].into_iter());
+
+		assert_eq!(state.proof_size(), proof(&[&[1; 4]]).encoded_size() as u32);
+	}
+
+	#[test]
+	fn proof_size_excludes_the_nodes_of_whitelisted_keys() {
+		let state = BenchmarkingState::<Block>::new(storage(), None).unwrap();
+		state.set_whitelist(vec![vec![2; 4]]);
+		state.reset_read_write_count();
+
+		state.storage(&[2; 4]).unwrap();
+		state.storage(&[42; 4]).unwrap();
+
+		// The root node is on the path to the whitelisted key as well.
+		let whitelisted = proof(&[&[2; 4]]).iter_nodes().collect::<HashSet<_>>();
+		let nodes = proof(&[&[42; 4]]).iter_nodes()
+			.filter(|node| !whitelisted.contains(node))
+			.collect::<Vec<_>>();
+		assert_eq!(nodes.len(), 2);
+		assert_eq!(state.proof_size(), StorageProof::new(nodes).encoded_size() as u32);
+	}
+}
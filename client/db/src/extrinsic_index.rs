@@ -0,0 +1,434 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! DB-backed index of the extrinsics of the canonical chain.
+//!
+//! The database only supports point lookups, so the extrinsics of an account are kept as
+//! an append-only list: a counter plus one entry per position. Reverting a block pops the
+//! entries it appended, which is always the tail of every list since blocks are reverted
+//! in reverse order.
+
+use std::{collections::HashMap, marker::PhantomData, sync::Arc};
+
+use codec::{Decode, Encode};
+use parking_lot::Mutex;
+use sc_client_api::extrinsic_index::{
+	AccountRelation, ExtrinsicIndex, ExtrinsicIndexEntry, IndexedExtrinsic,
+};
+use sp_blockchain::{Error as ClientError, Result as ClientResult};
+use sp_runtime::traits::{Block as BlockT, NumberFor};
+
+use crate::{columns, Database, DbHash, Transaction};
+
+/// Key of the last indexed block.
+const LAST_INDEXED: &[u8] = b"last";
+/// Key of the number of the last pruned block.
+const LAST_PRUNED: &[u8] = b"pruned";
+/// Prefix of the extrinsic locations by extrinsic hash.
+const EXTRINSIC_PREFIX: u8 = b'h';
+/// Prefix of the records of what was written for a block.
+const BLOCK_PREFIX: u8 = b'b';
+/// Prefixes of the list lengths, by relation.
+const SIGNER_COUNT_PREFIX: u8 = b'S';
+const TOUCHED_COUNT_PREFIX: u8 = b'T';
+/// Prefixes of the list entries, by relation.
+const SIGNER_ENTRY_PREFIX: u8 = b's';
+const TOUCHED_ENTRY_PREFIX: u8 = b't';
+
+/// What was written to the index for a block, needed to revert it.
+#[derive(Encode, Decode)]
+struct BlockRecord<Hash> {
+	/// Hashes of the indexed extrinsics.
+	extrinsics: Vec<Hash>,
+	/// Account lists that were appended to, in order.
+	appended: Vec<(AccountList, Vec<u8>)>,
+}
+
+/// Identifies the list of extrinsics of an account.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Encode, Decode)]
+enum AccountList {
+	Signer,
+	Touched,
+}
+
+impl From<AccountRelation> for AccountList {
+	fn from(relation: AccountRelation) -> Self {
+		match relation {
+			AccountRelation::Signer => AccountList::Signer,
+			AccountRelation::Touched => AccountList::Touched,
+		}
+	}
+}
+
+impl AccountList {
+	fn count_key(self, account: &[u8]) -> Vec<u8> {
+		let prefix = match self {
+			AccountList::Signer => SIGNER_COUNT_PREFIX,
+			AccountList::Touched => TOUCHED_COUNT_PREFIX,
+		};
+		// the length prefix makes keys of accounts of different size distinct
+		let mut key = vec![prefix];
+		account.encode_to(&mut key);
+		key
+	}
+
+	fn entry_key(self, account: &[u8], position: u64) -> Vec<u8> {
+		let prefix = match self {
+			AccountList::Signer => SIGNER_ENTRY_PREFIX,
+			AccountList::Touched => TOUCHED_ENTRY_PREFIX,
+		};
+		let mut key = vec![prefix];
+		account.encode_to(&mut key);
+		key.extend_from_slice(&position.to_be_bytes());
+		key
+	}
+}
+
+fn prefixed_key(prefix: u8, hash: &[u8]) -> Vec<u8> {
+	let mut key = Vec::with_capacity(hash.len() + 1);
+	key.push(prefix);
+	key.extend_from_slice(hash);
+	key
+}
+
+/// Index of the extrinsics of the canonical chain, stored in the client database.
+pub struct DbExtrinsicIndex<Block: BlockT> {
+	db: Arc<dyn Database<DbHash>>,
+	write_lock: Arc<Mutex<()>>,
+	_marker: PhantomData<Block>,
+}
+
+impl<Block: BlockT> Clone for DbExtrinsicIndex<Block> {
+	fn clone(&self) -> Self {
+		DbExtrinsicIndex {
+			db: self.db.clone(),
+			write_lock: self.write_lock.clone(),
+			_marker: PhantomData,
+		}
+	}
+}
+
+impl<Block: BlockT> DbExtrinsicIndex<Block> {
+	/// Create the index on top of the given database.
+	pub fn new(db: Arc<dyn Database<DbHash>>) -> Self {
+		DbExtrinsicIndex {
+			db,
+			write_lock: Default::default(),
+			_marker: PhantomData,
+		}
+	}
+
+	fn read<T: Decode>(&self, key: &[u8]) -> ClientResult<Option<T>> {
+		match self.db.get(columns::EXTRINSIC_INDEX, key) {
+			Some(value) => T::decode(&mut &value[..])
+				.map(Some)
+				.map_err(|e| ClientError::Backend(format!("Corrupted extrinsic index: {:?}", e))),
+			None => Ok(None),
+		}
+	}
+
+	fn count(&self, list: AccountList, account: &[u8]) -> ClientResult<u64> {
+		self.read(&list.count_key(account)).map(Option::unwrap_or_default)
+	}
+
+	/// Removes the hash lookups of the given extrinsics of a block.
+	fn remove_locations(
+		&self,
+		transaction: &mut Transaction<DbHash>,
+		block: Block::Hash,
+		extrinsics: Vec<Block::Hash>,
+	) -> ClientResult<()> {
+		for extrinsic in extrinsics {
+			let key = prefixed_key(EXTRINSIC_PREFIX, extrinsic.as_ref());
+			let location: Option<IndexedExtrinsic<Block::Hash, NumberFor<Block>>> = self.read(&key)?;
+			// the same extrinsic might have been indexed again in a later block
+			if location.map_or(false, |location| location.block_hash == block) {
+				transaction.remove(columns::EXTRINSIC_INDEX, &key);
+			}
+		}
+		Ok(())
+	}
+
+	fn commit(&self, transaction: Transaction<DbHash>) -> ClientResult<()> {
+		self.db.commit(transaction)
+			.map_err(|e| ClientError::Backend(format!("Failed to write extrinsic index: {}", e)))
+	}
+}
+
+impl<Block: BlockT> ExtrinsicIndex<Block> for DbExtrinsicIndex<Block> {
+	fn last_indexed(&self) -> ClientResult<Option<(Block::Hash, NumberFor<Block>)>> {
+		self.read(LAST_INDEXED)
+	}
+
+	fn index_block(
+		&self,
+		hash: Block::Hash,
+		number: NumberFor<Block>,
+		extrinsics: Vec<ExtrinsicIndexEntry<Block::Hash>>,
+	) -> ClientResult<()> {
+		let _lock = self.write_lock.lock();
+		let mut transaction = Transaction::new();
+		let mut counts = HashMap::new();
+		let mut record = BlockRecord { extrinsics: Vec::new(), appended: Vec::new() };
+
+		for entry in extrinsics {
+			let location = IndexedExtrinsic {
+				hash: entry.hash,
+				block_hash: hash,
+				block_number: number,
+				index: entry.index,
+			}.encode();
+
+			let mut appended = Vec::new();
+			let mut touched = entry.accounts;
+			if let Some(signer) = entry.signer {
+				touched.push(signer.clone());
+				appended.push((AccountList::Signer, signer));
+			}
+			touched.sort();
+			touched.dedup();
+			appended.extend(touched.into_iter().map(|account| (AccountList::Touched, account)));
+
+			for (list, account) in appended {
+				let count = match counts.get(&(list, account.clone())) {
+					Some(count) => *count,
+					None => self.count(list, &account)?,
+				};
+				transaction.set(columns::EXTRINSIC_INDEX, &list.entry_key(&account, count), &location);
+				counts.insert((list, account.clone()), count + 1);
+				record.appended.push((list, account));
+			}
+
+			transaction.set(
+				columns::EXTRINSIC_INDEX,
+				&prefixed_key(EXTRINSIC_PREFIX, entry.hash.as_ref()),
+				&location,
+			);
+			record.extrinsics.push(entry.hash);
+		}
+
+		for ((list, account), count) in counts {
+			transaction.set(columns::EXTRINSIC_INDEX, &list.count_key(&account), &count.encode());
+		}
+		transaction.set(columns::EXTRINSIC_INDEX, &prefixed_key(BLOCK_PREFIX, hash.as_ref()), &record.encode());
+		transaction.set(columns::EXTRINSIC_INDEX, LAST_INDEXED, &(hash, number).encode());
+		self.commit(transaction)
+	}
+
+	fn revert_block(
+		&self,
+		hash: Block::Hash,
+		parent: (Block::Hash, NumberFor<Block>),
+	) -> ClientResult<()> {
+		let _lock = self.write_lock.lock();
+		let block_key = prefixed_key(BLOCK_PREFIX, hash.as_ref());
+		let record: BlockRecord<Block::Hash> = self.read(&block_key)?
+			.ok_or_else(|| ClientError::Backend(format!("Block {} is not indexed", hash)))?;
+
+		let mut transaction = Transaction::new();
+		let mut counts = HashMap::new();
+		for (list, account) in record.appended.into_iter().rev() {
+			let count = match counts.get(&(list, account.clone())) {
+				Some(count) => *count,
+				None => self.count(list, &account)?,
+			};
+			let count = count.saturating_sub(1);
+			transaction.remove(columns::EXTRINSIC_INDEX, &list.entry_key(&account, count));
+			counts.insert((list, account), count);
+		}
+		for ((list, account), count) in counts {
+			let key = list.count_key(&account);
+			if count == 0 {
+				transaction.remove(columns::EXTRINSIC_INDEX, &key);
+			} else {
+				transaction.set(columns::EXTRINSIC_INDEX, &key, &count.encode());
+			}
+		}
+
+		self.remove_locations(&mut transaction, hash, record.extrinsics)?;
+		transaction.remove(columns::EXTRINSIC_INDEX, &block_key);
+		transaction.set(columns::EXTRINSIC_INDEX, LAST_INDEXED, &parent.encode());
+		self.commit(transaction)
+	}
+
+	fn last_pruned(&self) -> ClientResult<Option<NumberFor<Block>>> {
+		self.read(LAST_PRUNED)
+	}
+
+	fn prune_block(&self, hash: Block::Hash, number: NumberFor<Block>) -> ClientResult<()> {
+		let _lock = self.write_lock.lock();
+		let block_key = prefixed_key(BLOCK_PREFIX, hash.as_ref());
+		let mut transaction = Transaction::new();
+
+		// blocks indexed before the last restart may have been pruned already
+		if let Some(record) = self.read::<BlockRecord<Block::Hash>>(&block_key)? {
+			self.remove_locations(&mut transaction, hash, record.extrinsics)?;
+			transaction.remove(columns::EXTRINSIC_INDEX, &block_key);
+		}
+
+		transaction.set(columns::EXTRINSIC_INDEX, LAST_PRUNED, &number.encode());
+		self.commit(transaction)
+	}
+
+	fn extrinsic_by_hash(
+		&self,
+		hash: &Block::Hash,
+	) -> ClientResult<Option<IndexedExtrinsic<Block::Hash, NumberFor<Block>>>> {
+		self.read(&prefixed_key(EXTRINSIC_PREFIX, hash.as_ref()))
+	}
+
+	fn account_extrinsics_count(
+		&self,
+		account: &[u8],
+		relation: AccountRelation,
+	) -> ClientResult<u64> {
+		self.count(relation.into(), account)
+	}
+
+	fn account_extrinsics(
+		&self,
+		account: &[u8],
+		relation: AccountRelation,
+		before: Option<u64>,
+		count: u32,
+	) -> ClientResult<Vec<(u64, IndexedExtrinsic<Block::Hash, NumberFor<Block>>)>> {
+		let list = AccountList::from(relation);
+		let total = self.count(list, account)?;
+		let end = before.map_or(total, |before| before.min(total));
+		let start = end.saturating_sub(count as u64);
+
+		let mut extrinsics = Vec::with_capacity((end - start) as usize);
+		for position in (start..end).rev() {
+			let location = self.read(&list.entry_key(account, position))?
+				.ok_or_else(|| ClientError::Backend(format!(
+					"Missing extrinsic index entry {} of account {:?}", position, account,
+				)))?;
+			extrinsics.push((position, location));
+		}
+		Ok(extrinsics)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_core::H256;
+	use crate::tests::Block;
+
+	fn index() -> DbExtrinsicIndex<Block> {
+		let db = kvdb_memorydb::create(crate::utils::NUM_COLUMNS);
+		DbExtrinsicIndex::new(sp_database::as_database(db))
+	}
+
+	fn entry(hash: u8, index: u32, signer: Option<&[u8]>, accounts: &[&[u8]]) -> ExtrinsicIndexEntry<H256> {
+		ExtrinsicIndexEntry {
+			hash: H256::repeat_byte(hash),
+			index,
+			signer: signer.map(|signer| signer.to_vec()),
+			accounts: accounts.iter().map(|account| account.to_vec()).collect(),
+		}
+	}
+
+	fn hashes(extrinsics: Vec<(u64, IndexedExtrinsic<H256, u64>)>) -> Vec<(u64, H256)> {
+		extrinsics.into_iter().map(|(position, extrinsic)| (position, extrinsic.hash)).collect()
+	}
+
+	#[test]
+	fn indexes_by_hash_and_account() {
+		let index = index();
+		let block1 = H256::repeat_byte(101);
+		let block2 = H256::repeat_byte(102);
+
+		index.index_block(block1, 1, vec![
+			entry(1, 0, None, &[]),
+			entry(2, 1, Some(b"alice"), &[b"bob", b"alice"]),
+		]).unwrap();
+		index.index_block(block2, 2, vec![
+			entry(3, 1, Some(b"bob"), &[b"alice"]),
+		]).unwrap();
+
+		assert_eq!(index.last_indexed().unwrap(), Some((block2, 2)));
+		assert_eq!(index.extrinsic_by_hash(&H256::repeat_byte(2)).unwrap(), Some(IndexedExtrinsic {
+			hash: H256::repeat_byte(2),
+			block_hash: block1,
+			block_number: 1,
+			index: 1,
+		}));
+
+		assert_eq!(index.account_extrinsics_count(b"alice", AccountRelation::Signer).unwrap(), 1);
+		assert_eq!(index.account_extrinsics_count(b"alice", AccountRelation::Touched).unwrap(), 2);
+		assert_eq!(
+			hashes(index.account_extrinsics(b"alice", AccountRelation::Touched, None, 10).unwrap()),
+			vec![(1, H256::repeat_byte(3)), (0, H256::repeat_byte(2))],
+		);
+		assert_eq!(
+			hashes(index.account_extrinsics(b"alice", AccountRelation::Touched, Some(1), 10).unwrap()),
+			vec![(0, H256::repeat_byte(2))],
+		);
+		assert_eq!(
+			hashes(index.account_extrinsics(b"bob", AccountRelation::Touched, None, 1).unwrap()),
+			vec![(1, H256::repeat_byte(3))],
+		);
+		assert!(index.account_extrinsics(b"carol", AccountRelation::Touched, None, 10).unwrap().is_empty());
+	}
+
+	#[test]
+	fn revert_removes_block_entries() {
+		let index = index();
+		let block1 = H256::repeat_byte(101);
+		let block2 = H256::repeat_byte(102);
+
+		index.index_block(block1, 1, vec![entry(1, 0, Some(b"alice"), &[])]).unwrap();
+		index.index_block(block2, 2, vec![
+			entry(2, 0, Some(b"alice"), &[]),
+			entry(3, 1, Some(b"alice"), &[b"bob"]),
+		]).unwrap();
+		index.revert_block(block2, (block1, 1)).unwrap();
+
+		assert_eq!(index.last_indexed().unwrap(), Some((block1, 1)));
+		assert_eq!(index.extrinsic_by_hash(&H256::repeat_byte(3)).unwrap(), None);
+		assert_eq!(index.account_extrinsics_count(b"alice", AccountRelation::Signer).unwrap(), 1);
+		assert_eq!(index.account_extrinsics_count(b"bob", AccountRelation::Touched).unwrap(), 0);
+		assert_eq!(
+			hashes(index.account_extrinsics(b"alice", AccountRelation::Signer, None, 10).unwrap()),
+			vec![(0, H256::repeat_byte(1))],
+		);
+		assert!(index.revert_block(block2, (block1, 1)).is_err());
+	}
+
+	#[test]
+	fn prune_keeps_account_entries() {
+		let index = index();
+		let block1 = H256::repeat_byte(101);
+		let block2 = H256::repeat_byte(102);
+
+		index.index_block(block1, 1, vec![entry(1, 0, Some(b"alice"), &[])]).unwrap();
+		index.index_block(block2, 2, vec![entry(2, 0, Some(b"alice"), &[])]).unwrap();
+		assert_eq!(index.last_pruned().unwrap(), None);
+		index.prune_block(block1, 1).unwrap();
+
+		assert_eq!(index.last_pruned().unwrap(), Some(1));
+		assert_eq!(index.extrinsic_by_hash(&H256::repeat_byte(1)).unwrap(), None);
+		assert!(index.extrinsic_by_hash(&H256::repeat_byte(2)).unwrap().is_some());
+		assert_eq!(
+			hashes(index.account_extrinsics(b"alice", AccountRelation::Signer, None, 10).unwrap()),
+			vec![(1, H256::repeat_byte(2)), (0, H256::repeat_byte(1))],
+		);
+		assert!(index.revert_block(block1, (H256::zero(), 0)).is_err());
+	}
+}
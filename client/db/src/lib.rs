@@ -30,6 +30,7 @@
 
 pub mod light;
 pub mod offchain;
+pub mod extrinsic_index;
 
 #[cfg(any(feature = "with-kvdb-rocksdb", test))]
 pub mod bench;
@@ -42,6 +43,7 @@ mod storage_cache;
 mod upgrade;
 mod utils;
 mod stats;
+mod state_snapshot;
 #[cfg(feature = "with-parity-db")]
 mod parity_db;
 #[cfg(feature = "with-subdb")]
@@ -90,6 +92,7 @@ use log::{trace, debug, warn};
 // Re-export the Database trait so that one can pass an implementation of it.
 pub use sp_database::Database;
 pub use sc_state_db::PruningMode;
+pub use state_snapshot::StateSnapshotImport;
 
 #[cfg(any(feature = "with-kvdb-rocksdb", test))]
 pub use bench::BenchmarkingState;
@@ -342,6 +345,8 @@ pub(crate) mod columns {
 	/// Offchain workers local storage
 	pub const OFFCHAIN: u32 = 9;
 	pub const CACHE: u32 = 10;
+	/// Index of the extrinsics of the canonical chain.
+	pub const EXTRINSIC_INDEX: u32 = 11;
 }
 
 struct PendingBlock<Block: BlockT> {
@@ -776,6 +781,7 @@ impl<T: Clone> FrozenForDuration<T> {
 pub struct Backend<Block: BlockT> {
 	storage: Arc<StorageDb<Block>>,
 	offchain_storage: offchain::LocalStorage,
+	extrinsic_index: extrinsic_index::DbExtrinsicIndex<Block>,
 	changes_tries_storage: DbChangesTrieStorage<Block>,
 	blockchain: BlockchainDb<Block>,
 	canonicalization_delay: u64,
@@ -832,6 +838,7 @@ impl<Block: BlockT> Backend<Block> {
 			prefix_keys: !config.source.supports_ref_counting(),
 		};
 		let offchain_storage = offchain::LocalStorage::new(db.clone());
+		let extrinsic_index = extrinsic_index::DbExtrinsicIndex::new(db.clone());
 		let changes_tries_storage = DbChangesTrieStorage::new(
 			db,
 			blockchain.header_metadata_cache.clone(),
@@ -851,6 +858,7 @@ impl<Block: BlockT> Backend<Block> {
 		Ok(Backend {
 			storage: Arc::new(storage_db),
 			offchain_storage,
+			extrinsic_index,
 			changes_tries_storage,
 			blockchain,
 			canonicalization_delay,
@@ -865,6 +873,13 @@ impl<Block: BlockT> Backend<Block> {
 		})
 	}
 
+	/// Returns the index of the extrinsics of the canonical chain.
+	///
+	/// The index is only filled when an indexer is running for this backend.
+	pub fn extrinsic_index(&self) -> extrinsic_index::DbExtrinsicIndex<Block> {
+		self.extrinsic_index.clone()
+	}
+
 	/// Handle setting head within a transaction. `route_to` should be the last
 	/// block that existed in the database. `best_to` should be the best block
 	/// to be set.
@@ -34,13 +34,34 @@ fn handle_err<T>(result: parity_db::Result<T>) -> T {
 
 /// Wrap RocksDb database into a trait object that implements `sp_database::Database`
 pub fn open<H: Clone>(path: &std::path::Path) -> parity_db::Result<std::sync::Arc<dyn Database<H>>> {
-	let mut config = parity_db::Options::with_columns(path, NUM_COLUMNS as u8);
+	let config = options(path, NUM_COLUMNS as u8);
+	add_new_columns(&config)?;
+	let db = parity_db::Db::open(&config)?;
+	Ok(std::sync::Arc::new(DbAdapter(db)))
+}
+
+fn options(path: &std::path::Path, num_columns: u8) -> parity_db::Options {
+	let mut config = parity_db::Options::with_columns(path, num_columns);
 	let mut state_col = &mut config.columns[columns::STATE as usize];
 	state_col.ref_counted = true;
 	state_col.preimage = true;
 	state_col.uniform = true;
-	let db = parity_db::Db::open(&config)?;
-	Ok(std::sync::Arc::new(DbAdapter(db)))
+	config
+}
+
+/// Records the columns that were added since the database was created, e.g. the extrinsic
+/// index, in its metadata.
+///
+/// parity-db creates the files of a column on first use, so a database of an older version
+/// only lacks the entries of the new columns in its metadata file.
+fn add_new_columns(config: &parity_db::Options) -> parity_db::Result<()> {
+	let metadata = config.path.join("metadata");
+	if metadata.exists() {
+		// fails if the configuration of an existing column differs
+		config.validate_metadata()?;
+		config.write_metadata(&metadata)?;
+	}
+	Ok(())
 }
 
 impl<H: Clone> Database<H> for DbAdapter {
@@ -64,3 +85,23 @@ impl<H: Clone> Database<H> for DbAdapter {
 		unimplemented!();
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn columns_are_added_to_existing_database() {
+		let path = tempfile::tempdir().unwrap();
+		{
+			let db = parity_db::Db::open(&options(path.path(), NUM_COLUMNS as u8 - 1)).unwrap();
+			db.commit(vec![(columns::META as u8, b"key".to_vec(), Some(b"value".to_vec()))]).unwrap();
+		}
+
+		let db = open::<sp_core::H256>(path.path()).unwrap();
+		assert_eq!(db.get(columns::META, b"key"), Some(b"value".to_vec()));
+
+		let metadata = std::fs::read_to_string(path.path().join("metadata")).unwrap();
+		assert!(metadata.contains(&format!("col{}=", columns::EXTRINSIC_INDEX)));
+	}
+}
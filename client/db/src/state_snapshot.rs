@@ -0,0 +1,445 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Initialization of an empty database from a state snapshot.
+
+use std::{io, marker::PhantomData, mem};
+
+use codec::{Decode, Encode};
+use hash_db::{Hasher, Prefix};
+use parking_lot::RwLockWriteGuard;
+use sp_blockchain::{Error as ClientError, Result as ClientResult};
+use sp_core::ChangesTrieConfiguration;
+use sp_core::storage::{well_known_keys, ChildInfo};
+use sp_database::{Database, Transaction};
+use sp_runtime::{Justification, SaturatedConversion};
+use sp_runtime::traits::{Block as BlockT, HashFor, Header as HeaderT, One, Zero};
+use sp_trie::{
+	keyspace_as_prefix_alloc, prefixed_key, trie_visit, ChildReference, Layout, ProcessEncodedNode,
+};
+
+use crate::{apply_state_commit, cache::ComplexBlockId, columns, utils, Backend, DbHash};
+use crate::utils::meta_keys;
+
+/// Size of the trie nodes that are committed to the database at once.
+const COMMIT_SIZE: usize = 16 * 1024 * 1024;
+
+impl<Block: BlockT> Backend<Block> {
+	/// Start to initialize an empty database from a state snapshot.
+	///
+	/// The tries of the state are written with [`StateSnapshotImport::import_trie`], then
+	/// [`StateSnapshotImport::finish`] adds the block of the snapshot and the aux data of the
+	/// consensus engines.
+	///
+	/// Fails if the database is not empty.
+	pub fn begin_state_snapshot_import(&self) -> ClientResult<StateSnapshotImport<Block>> {
+		let lock = self.import_lock.write();
+
+		if self.blockchain.meta.read().best_hash != Default::default() {
+			return Err(ClientError::Backend(
+				"State snapshots can only be imported into an empty database".into(),
+			));
+		}
+
+		Ok(StateSnapshotImport {
+			backend: self,
+			_lock: lock,
+			state_root: None,
+			changes_trie_config: None,
+		})
+	}
+}
+
+/// An import of a state snapshot into an empty database.
+///
+/// The trie nodes are committed while they are built, so a failed import leaves a database
+/// that contains state, but no blocks. Such a database must be removed before the import is
+/// retried.
+pub struct StateSnapshotImport<'a, Block: BlockT> {
+	backend: &'a Backend<Block>,
+	_lock: RwLockWriteGuard<'a, ()>,
+	state_root: Option<Block::Hash>,
+	changes_trie_config: Option<Vec<u8>>,
+}
+
+impl<'a, Block: BlockT> StateSnapshotImport<'a, Block> {
+	/// Write the trie with the given key value pairs, which must be sorted by key, and
+	/// return its root.
+	///
+	/// The pairs of a child trie are passed with its `child_info`, the pairs of the top trie,
+	/// including the roots of the child tries, with `None`. The pairs are consumed while the
+	/// trie is built, so they are never held in memory all at once.
+	pub fn import_trie(
+		&mut self,
+		child_info: Option<&ChildInfo>,
+		pairs: impl Iterator<Item = (Vec<u8>, Vec<u8>)>,
+	) -> ClientResult<Block::Hash> {
+		let keyspace = child_info.map(|child_info| child_info.keyspace().to_vec());
+		let mut writer = NodeWriter::<HashFor<Block>> {
+			db: &*self.backend.storage.db,
+			prefix_keys: self.backend.storage.prefix_keys,
+			keyspace: keyspace.as_ref().map(|keyspace| &keyspace[..]),
+			transaction: Transaction::new(),
+			pending: 0,
+			root: None,
+			result: Ok(()),
+			_marker: PhantomData,
+		};
+
+		let changes_trie_config = &mut self.changes_trie_config;
+		let is_top = child_info.is_none();
+		trie_visit::<Layout<HashFor<Block>>, _, _, _, _>(
+			pairs.inspect(|(key, value)| if is_top && &key[..] == well_known_keys::CHANGES_TRIE_CONFIG {
+				*changes_trie_config = Some(value.clone());
+			}),
+			&mut writer,
+		);
+		writer.commit();
+		writer.result?;
+
+		let root = writer.root.expect("`trie_visit` always processes a root node; qed");
+		if is_top {
+			self.state_root = Some(root);
+		}
+		Ok(root)
+	}
+
+	/// Finish the import by adding the block `header` of the snapshot.
+	///
+	/// The block becomes the best and finalized block of the database. Besides the genesis
+	/// header, which still identifies the chain, only the headers of its last `ancestry` are
+	/// stored, which must end with the parent of the block. Neither the bodies of the ancestors
+	/// nor any state but the one of the block are available. The `aux` data of the consensus
+	/// engines is written with the block, the engines can only refer to blocks whose headers
+	/// are stored.
+	///
+	/// Fails if the imported top trie doesn't match the state root of `header`.
+	pub fn finish(
+		self,
+		genesis_header: Block::Header,
+		ancestry: Vec<Block::Header>,
+		header: Block::Header,
+		justification: Option<Justification>,
+		aux: Vec<(Vec<u8>, Vec<u8>)>,
+	) -> ClientResult<()> {
+		if !genesis_header.number().is_zero() || header.number().is_zero() {
+			return Err(ClientError::Backend(
+				"State snapshots need a genesis header and a non-genesis block".into(),
+			));
+		}
+		let mut child_parent_hash = *header.parent_hash();
+		for ancestor in ancestry.iter().rev() {
+			if ancestor.number().is_zero() || ancestor.hash() != child_parent_hash {
+				return Err(ClientError::Backend(
+					"The ancestry of a state snapshot must be a chain of non-genesis headers".into(),
+				));
+			}
+			child_parent_hash = *ancestor.parent_hash();
+		}
+		if self.state_root != Some(*header.state_root()) {
+			return Err(ClientError::InvalidStateRoot);
+		}
+		let changes_trie_config = self.changes_trie_config.as_ref()
+			.map(|config| ChangesTrieConfiguration::decode(&mut &config[..]))
+			.transpose()
+			.map_err(|e| ClientError::Backend(
+				format!("Invalid changes trie configuration in the state snapshot: {}", e.what()),
+			))?;
+
+		let backend = self.backend;
+		let genesis_hash = genesis_header.hash();
+		let hash = header.hash();
+		let number = *header.number();
+		let parent_hash = *header.parent_hash();
+		let mut transaction = Transaction::new();
+
+		let headers = std::iter::once((&genesis_header, None))
+			.chain(ancestry.iter().map(|ancestor| (ancestor, None)))
+			.chain(std::iter::once((&header, justification)));
+		for (header, justification) in headers {
+			let (hash, number) = (header.hash(), *header.number());
+			let lookup_key = utils::number_and_hash_to_lookup_key(number, hash)?;
+
+			utils::insert_hash_to_key_mapping(&mut transaction, columns::KEY_LOOKUP, number, hash)?;
+			utils::insert_number_to_key_mapping(&mut transaction, columns::KEY_LOOKUP, number, hash)?;
+			transaction.set_from_vec(columns::HEADER, &lookup_key, header.encode());
+			if let Some(justification) = justification {
+				transaction.set_from_vec(columns::JUSTIFICATION, &lookup_key, justification.encode());
+			}
+		}
+
+		for (key, value) in aux {
+			transaction.set_from_vec(columns::AUX, &key, value);
+		}
+
+		let lookup_key = utils::number_and_hash_to_lookup_key(number, hash)?;
+		transaction.set(columns::META, meta_keys::GENESIS_HASH, genesis_hash.as_ref());
+		transaction.set_from_vec(columns::META, meta_keys::BEST_BLOCK, lookup_key.clone());
+		transaction.set_from_vec(columns::META, meta_keys::FINALIZED_BLOCK, lookup_key);
+
+		// The trie nodes are already written, the block is inserted with an empty changeset and
+		// canonicalized right away. The state database assumes the unknown parent to be
+		// canonicalized already.
+		let map_e = |e: sc_state_db::Error<io::Error>| ClientError::from(
+			format!("State database error: {:?}", e)
+		);
+		let commit = backend.storage.state_db.insert_block(
+			&hash,
+			number.saturated_into::<u64>(),
+			&parent_hash,
+			Default::default(),
+		).map_err(map_e)?;
+		apply_state_commit(&mut transaction, commit);
+		let commit = backend.storage.state_db.canonicalize_block(&hash).map_err(map_e)?;
+		apply_state_commit(&mut transaction, commit);
+
+		let changes_trie_cache_ops = backend.changes_tries_storage.commit(
+			&mut transaction,
+			Default::default(),
+			ComplexBlockId::new(parent_hash, number - One::one()),
+			ComplexBlockId::new(hash, number),
+			&header,
+			true,
+			Some(changes_trie_config),
+			None,
+		)?;
+
+		{
+			let mut leaves = backend.blockchain.leaves.write();
+			leaves.import(hash, number, parent_hash);
+			leaves.prepare_transaction(&mut transaction, columns::META, meta_keys::LEAF_PREFIX);
+		}
+
+		backend.storage.db.commit(transaction)?;
+		backend.changes_tries_storage.post_commit(Some(changes_trie_cache_ops));
+
+		backend.blockchain.update_meta(genesis_hash, Zero::zero(), false, false);
+		backend.blockchain.update_meta(hash, number, true, true);
+
+		Ok(())
+	}
+}
+
+/// Writes the nodes of a trie built by `trie_visit` to the state column.
+///
+/// Like `TrieBuilder`, nodes that are shorter than a hash are inlined into their parent.
+struct NodeWriter<'a, H: Hasher> {
+	db: &'a dyn Database<DbHash>,
+	prefix_keys: bool,
+	keyspace: Option<&'a [u8]>,
+	transaction: Transaction<DbHash>,
+	/// Size of the nodes in `transaction`.
+	pending: usize,
+	root: Option<H::Out>,
+	result: ClientResult<()>,
+	_marker: PhantomData<H>,
+}
+
+impl<'a, H: Hasher> NodeWriter<'a, H> {
+	fn commit(&mut self) {
+		let transaction = mem::replace(&mut self.transaction, Transaction::new());
+		self.pending = 0;
+		if self.result.is_ok() {
+			self.result = self.db.commit(transaction).map_err(Into::into);
+		}
+	}
+}
+
+impl<'a, H: Hasher> ProcessEncodedNode<H::Out> for NodeWriter<'a, H> {
+	fn process(&mut self, prefix: Prefix, encoded_node: Vec<u8>, is_root: bool) -> ChildReference<H::Out> {
+		let len = encoded_node.len();
+		if !is_root && len < H::LENGTH {
+			let mut inline = H::Out::default();
+			inline.as_mut()[..len].copy_from_slice(&encoded_node);
+			return ChildReference::Inline(inline, len);
+		}
+
+		let hash = H::hash(&encoded_node);
+		let key = match (self.prefix_keys, self.keyspace) {
+			(false, _) => hash.as_ref().to_vec(),
+			(true, None) => prefixed_key::<H>(&hash, prefix),
+			(true, Some(keyspace)) => {
+				let (prefix, padded) = keyspace_as_prefix_alloc(keyspace, prefix);
+				prefixed_key::<H>(&hash, (&prefix, padded))
+			},
+		};
+		self.pending += len;
+		self.transaction.set_from_vec(columns::STATE, &key, encoded_node);
+		if self.pending >= COMMIT_SIZE {
+			self.commit();
+		}
+
+		if is_root {
+			self.root = Some(hash);
+		}
+		ChildReference::Hash(hash)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use sc_client_api::backend::{AuxStore as _, Backend as _};
+	use sc_client_api::blockchain::{Backend as _, HeaderBackend as _};
+	use sp_core::H256;
+	use sp_runtime::{Storage, generic::BlockId};
+	use sp_runtime::testing::Header;
+	use sp_runtime::traits::{BlakeTwo256, Hash};
+	use sp_state_machine::Backend as _;
+
+	use super::*;
+	use crate::tests::{Block, insert_header};
+
+	fn header(number: u64, parent_hash: H256, state_root: H256) -> Header {
+		Header {
+			number,
+			parent_hash,
+			state_root,
+			digest: Default::default(),
+			extrinsics_root: Default::default(),
+		}
+	}
+
+	fn snapshot() -> (Header, Header, Storage) {
+		let child_info = ChildInfo::new_default(b"child");
+		let child_data: Vec<_> = (0u32..100).map(|i| (i.encode(), vec![i as u8; 40])).collect();
+		let child_root = BlakeTwo256::trie_root(child_data.clone());
+		let storage = Storage {
+			top: vec![(b"key".to_vec(), b"value".to_vec())].into_iter().collect(),
+			children_default: vec![(b"child".to_vec(), sp_core::storage::StorageChild {
+				data: child_data.into_iter().collect(),
+				child_info: child_info.clone(),
+			})].into_iter().collect(),
+		};
+		let state_root = BlakeTwo256::trie_root(storage.top.clone().into_iter()
+			.chain(Some((child_info.prefixed_storage_key().into_inner(), child_root.encode())))
+			.collect());
+		let genesis = header(0, Default::default(), Default::default());
+		let block = header(10, H256::repeat_byte(9), state_root);
+
+		(genesis, block, storage)
+	}
+
+	fn import(backend: &Backend<Block>, genesis: Header, block: Header, storage: Storage) -> ClientResult<()> {
+		import_with(backend, genesis, Vec::new(), block, storage, Vec::new())
+	}
+
+	fn import_with(
+		backend: &Backend<Block>,
+		genesis: Header,
+		ancestry: Vec<Header>,
+		block: Header,
+		storage: Storage,
+		aux: Vec<(Vec<u8>, Vec<u8>)>,
+	) -> ClientResult<()> {
+		let mut import = backend.begin_state_snapshot_import()?;
+		let mut top = storage.top;
+		for child in storage.children_default.values() {
+			let root = import.import_trie(Some(&child.child_info), child.data.clone().into_iter())?;
+			top.insert(child.child_info.prefixed_storage_key().into_inner(), root.encode());
+		}
+		import.import_trie(None, top.into_iter())?;
+		import.finish(genesis, ancestry, block, None, aux)
+	}
+
+	#[test]
+	fn snapshot_becomes_best_and_finalized_block() {
+		let backend = Backend::<Block>::new_test(10, 0);
+		let (genesis, block, storage) = snapshot();
+
+		import(&backend, genesis.clone(), block.clone(), storage).unwrap();
+
+		let info = backend.blockchain().info();
+		assert_eq!(info.genesis_hash, genesis.hash());
+		assert_eq!(info.best_hash, block.hash());
+		assert_eq!(info.finalized_number, 10);
+		assert_eq!(backend.blockchain().header(BlockId::Number(0)).unwrap(), Some(genesis));
+		assert_eq!(backend.blockchain().leaves().unwrap(), vec![block.hash()]);
+
+		let state = backend.state_at(BlockId::Number(10)).unwrap();
+		assert_eq!(state.storage(b"key").unwrap(), Some(b"value".to_vec()));
+		let child_info = ChildInfo::new_default(b"child");
+		assert_eq!(state.child_storage(&child_info, &7u32.encode()).unwrap(), Some(vec![7; 40]));
+
+		// The chain continues on top of the snapshot.
+		let next = insert_header(&backend, 11, block.hash(), None, Default::default());
+		assert_eq!(backend.blockchain().info().best_hash, next);
+	}
+
+	#[test]
+	fn genesis_state_is_not_available_after_snapshot_import() {
+		let backend = Backend::<Block>::new_test(10, 0);
+		let (genesis, block, storage) = snapshot();
+
+		import(&backend, genesis, block, storage).unwrap();
+
+		// Consensus engines that read the genesis state, like GRANDPA on the first start of a
+		// node, need their aux data in the snapshot.
+		assert!(backend.state_at(BlockId::Number(0)).is_err());
+	}
+
+	#[test]
+	fn ancestry_and_aux_data_are_stored() {
+		let backend = Backend::<Block>::new_test(10, 0);
+		let (genesis, block, storage) = snapshot();
+		let eight = header(8, H256::repeat_byte(7), Default::default());
+		let nine = header(9, eight.hash(), Default::default());
+		let block = header(10, nine.hash(), *block.state_root());
+		let aux = vec![(b"aux".to_vec(), b"data".to_vec())];
+
+		import_with(&backend, genesis, vec![eight.clone(), nine.clone()], block.clone(), storage, aux)
+			.unwrap();
+
+		assert_eq!(backend.blockchain().header(BlockId::Number(8)).unwrap(), Some(eight.clone()));
+		assert_eq!(backend.blockchain().hash(9).unwrap(), Some(nine.hash()));
+		assert_eq!(backend.get_aux(b"aux").unwrap(), Some(b"data".to_vec()));
+		let ancestor = sp_blockchain::lowest_common_ancestor(
+			backend.blockchain(),
+			eight.hash(),
+			block.hash(),
+		).unwrap();
+		assert_eq!(ancestor.hash, eight.hash());
+		assert!(backend.blockchain().header(BlockId::Number(7)).unwrap().is_none());
+	}
+
+	#[test]
+	fn snapshot_is_rejected_with_broken_ancestry() {
+		let backend = Backend::<Block>::new_test(10, 0);
+		let (genesis, block, storage) = snapshot();
+		let eight = header(8, H256::repeat_byte(7), Default::default());
+		let nine = header(9, eight.hash(), Default::default());
+		let block = header(10, nine.hash(), *block.state_root());
+
+		assert!(import_with(&backend, genesis, vec![eight], block, storage, Vec::new()).is_err());
+		assert_eq!(backend.blockchain().info().best_hash, Default::default());
+	}
+
+	#[test]
+	fn snapshot_is_rejected_with_wrong_state_root_or_existing_chain() {
+		let backend = Backend::<Block>::new_test(10, 0);
+		let (genesis, block, storage) = snapshot();
+
+		let invalid = header(10, H256::repeat_byte(9), H256::repeat_byte(1));
+		assert!(matches!(
+			import(&backend, genesis.clone(), invalid, storage.clone()),
+			Err(ClientError::InvalidStateRoot),
+		));
+
+		insert_header(&backend, 0, Default::default(), None, Default::default());
+		assert!(import(&backend, genesis, block, storage).is_err());
+	}
+}
@@ -27,7 +27,13 @@ use crate::utils::DatabaseType;
 const VERSION_FILE_NAME: &'static str = "db_version";
 
 /// Current db version.
-const CURRENT_VERSION: u32 = 1;
+const CURRENT_VERSION: u32 = 2;
+
+/// Number of columns in v1.
+const V1_NUM_COLUMNS: u32 = 11;
+
+/// Number of columns in v2.
+const V2_NUM_COLUMNS: u32 = 12;
 
 /// Upgrade database to current version.
 pub fn upgrade_db<Block: BlockT>(db_path: &Path, _db_type: DatabaseType) -> sp_blockchain::Result<()> {
@@ -36,7 +42,8 @@ pub fn upgrade_db<Block: BlockT>(db_path: &Path, _db_type: DatabaseType) -> sp_b
 		let db_version = current_version(db_path)?;
 		match db_version {
 			0 => Err(sp_blockchain::Error::Backend(format!("Unsupported database version: {}", db_version)))?,
-			1 => (),
+			1 => migrate_1_to_2::<Block>(db_path)?,
+			CURRENT_VERSION => (),
 			_ => Err(sp_blockchain::Error::Backend(format!("Future database version: {}", db_version)))?,
 		}
 	}
@@ -44,6 +51,25 @@ pub fn upgrade_db<Block: BlockT>(db_path: &Path, _db_type: DatabaseType) -> sp_b
 	update_version(db_path)
 }
 
+/// Migration from version1 to version2:
+/// 1) the extrinsic index column is added.
+fn migrate_1_to_2<Block: BlockT>(db_path: &Path) -> sp_blockchain::Result<()> {
+	let db_path = db_path.to_str()
+		.ok_or_else(|| sp_blockchain::Error::Backend("Invalid database path".into()))?;
+	let db_cfg = kvdb_rocksdb::DatabaseConfig::with_columns(V1_NUM_COLUMNS);
+	let db = match kvdb_rocksdb::Database::open(&db_cfg, db_path) {
+		Ok(db) => db,
+		// The column already exists if an earlier migration was interrupted before the version
+		// file was updated.
+		Err(_) if kvdb_rocksdb::Database::open(
+			&kvdb_rocksdb::DatabaseConfig::with_columns(V2_NUM_COLUMNS),
+			db_path,
+		).is_ok() => return Ok(()),
+		Err(err) => return Err(db_err(err)),
+	};
+	db.add_column().map_err(db_err)
+}
+
 
 /// Reads current database version from the file at given path.
 /// If the file does not exist returns 0.
@@ -84,6 +110,7 @@ fn version_file_path(path: &Path) -> PathBuf {
 
 #[cfg(test)]
 mod tests {
+	use kvdb::KeyValueDB;
 	use sc_state_db::PruningMode;
 	use crate::{DatabaseSettings, DatabaseSettingsSrc};
 	use crate::tests::Block;
@@ -113,6 +140,46 @@ mod tests {
 		assert!(open_database(db_dir.path()).is_err());
 	}
 
+	#[test]
+	fn upgrade_from_1_to_2_works() {
+		let db_dir = tempfile::TempDir::new().unwrap();
+		let db_path = db_dir.path().to_str().unwrap();
+		let db_cfg = kvdb_rocksdb::DatabaseConfig::with_columns(V1_NUM_COLUMNS);
+		let db = kvdb_rocksdb::Database::open(&db_cfg, db_path).unwrap();
+		let mut transaction = db.transaction();
+		transaction.put(crate::columns::AUX, b"key", b"value");
+		db.write(transaction).unwrap();
+		drop(db);
+		create_db(db_dir.path(), Some(1));
+
+		open_database(db_dir.path()).unwrap();
+		assert_eq!(current_version(db_dir.path()).unwrap(), CURRENT_VERSION);
+
+		let db_cfg = kvdb_rocksdb::DatabaseConfig::with_columns(V2_NUM_COLUMNS);
+		let db = kvdb_rocksdb::Database::open(&db_cfg, db_path).unwrap();
+		assert_eq!(db.get(crate::columns::AUX, b"key").unwrap().unwrap(), b"value".to_vec());
+		let mut transaction = db.transaction();
+		transaction.put(crate::columns::EXTRINSIC_INDEX, b"key", b"value");
+		db.write(transaction).unwrap();
+	}
+
+	#[test]
+	fn interrupted_upgrade_from_1_to_2_is_completed() {
+		let db_dir = tempfile::TempDir::new().unwrap();
+		let db_path = db_dir.path().to_str().unwrap();
+		let db_cfg = kvdb_rocksdb::DatabaseConfig::with_columns(V2_NUM_COLUMNS);
+		drop(kvdb_rocksdb::Database::open(&db_cfg, db_path).unwrap());
+		create_db(db_dir.path(), Some(1));
+
+		open_database(db_dir.path()).unwrap();
+		assert_eq!(current_version(db_dir.path()).unwrap(), CURRENT_VERSION);
+	}
+
+	#[test]
+	fn current_version_has_all_columns() {
+		assert_eq!(V2_NUM_COLUMNS, crate::utils::NUM_COLUMNS);
+	}
+
 	#[test]
 	fn open_empty_database_works() {
 		let db_dir = tempfile::TempDir::new().unwrap();
@@ -73,6 +73,35 @@ fn returning_should_work(wasm_method: WasmExecutionMethod) {
 	assert_eq!(output, vec![0u8; 0]);
 }
 
+#[test_case(WasmExecutionMethod::Interpreted)]
+#[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Compiled))]
+fn compressed_code_should_work(wasm_method: WasmExecutionMethod) {
+	let mut ext = TestExternalities::default();
+	let mut ext = ext.ext();
+
+	let code = sp_maybe_compressed_blob::compress(
+		&wasm_binary_unwrap()[..],
+		sp_maybe_compressed_blob::CODE_BLOB_BOMB_LIMIT,
+	).unwrap();
+	assert!(code.len() < wasm_binary_unwrap().len());
+
+	let executor = crate::WasmExecutor::new(
+		wasm_method,
+		Some(1024),
+		HostFunctions::host_functions(),
+		8,
+	);
+	let output = executor.call_in_wasm(
+		&code,
+		None,
+		"test_empty_return",
+		&[],
+		&mut ext,
+		sp_core::traits::MissingHostFunctions::Allow,
+	).unwrap();
+	assert_eq!(output, vec![0u8; 0]);
+}
+
 #[test_case(WasmExecutionMethod::Interpreted)]
 #[cfg_attr(feature = "wasmtime", test_case(WasmExecutionMethod::Compiled))]
 fn call_not_existing_function(wasm_method: WasmExecutionMethod) {
@@ -261,6 +261,10 @@ impl RuntimeCache {
 }
 
 /// Create a wasm runtime with the given `code`.
+///
+/// The `code` may be compressed with [`sp_maybe_compressed_blob::compress`], in which case it
+/// is decompressed first. Code that decompresses to more than
+/// [`sp_maybe_compressed_blob::CODE_BLOB_BOMB_LIMIT`] bytes is rejected.
 pub fn create_wasm_runtime_with_code(
 	wasm_method: WasmExecutionMethod,
 	heap_pages: u64,
@@ -268,6 +272,12 @@ pub fn create_wasm_runtime_with_code(
 	host_functions: Vec<&'static dyn Function>,
 	allow_missing_func_imports: bool,
 ) -> Result<Box<dyn WasmModule>, WasmError> {
+	let code = sp_maybe_compressed_blob::decompress(
+		code,
+		sp_maybe_compressed_blob::CODE_BLOB_BOMB_LIMIT,
+	).map_err(|e| WasmError::Other(format!("Decompression error: {}", e)))?;
+	let code = &code[..];
+
 	match wasm_method {
 		WasmExecutionMethod::Interpreted =>
 			sc_executor_wasmi::create_runtime(
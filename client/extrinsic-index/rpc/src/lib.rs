@@ -0,0 +1,344 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! RPC API for the extrinsic index.
+
+#![warn(missing_docs)]
+
+use std::{marker::PhantomData, sync::Arc};
+
+use codec::Encode;
+use jsonrpc_derive::rpc;
+use sc_client_api::{AccountRelation, ExtrinsicIndex, IndexedExtrinsic};
+use serde::{Deserialize, Serialize};
+use sp_runtime::traits::{Block as BlockT, NumberFor};
+
+/// Maximal number of extrinsics returned by a single query.
+pub const MAX_PAGE_SIZE: u32 = 256;
+
+/// Number of extrinsics returned by a query that doesn't specify a count.
+pub const DEFAULT_PAGE_SIZE: u32 = 32;
+
+/// Error code of errors of the underlying index.
+pub const INDEX_ERROR_CODE: i64 = 1;
+
+/// Error code of requests for more than [`MAX_PAGE_SIZE`] extrinsics.
+pub const PAGE_SIZE_ERROR_CODE: i64 = 2;
+
+/// Errors of the extrinsic index RPC.
+#[derive(Debug, derive_more::Display, derive_more::From)]
+pub enum Error {
+	/// The index failed to answer the query.
+	#[display(fmt = "Extrinsic index error: {}", _0)]
+	Index(sp_blockchain::Error),
+	/// More extrinsics were requested than are returned by a single query.
+	#[display(fmt = "Requested {} extrinsics, at most {} are returned per query", _0, MAX_PAGE_SIZE)]
+	#[from(ignore)]
+	PageSize(u32),
+}
+
+impl From<Error> for jsonrpc_core::Error {
+	fn from(error: Error) -> Self {
+		let code = match error {
+			Error::Index(_) => INDEX_ERROR_CODE,
+			Error::PageSize(_) => PAGE_SIZE_ERROR_CODE,
+		};
+		jsonrpc_core::Error {
+			message: format!("{}", error),
+			code: jsonrpc_core::ErrorCode::ServerError(code),
+			data: None,
+		}
+	}
+}
+
+/// Location of an extrinsic in the canonical chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtrinsicLocation<Hash, Number> {
+	/// Hash of the extrinsic.
+	pub hash: Hash,
+	/// Hash of the block that includes the extrinsic.
+	pub block_hash: Hash,
+	/// Number of the block that includes the extrinsic.
+	pub block_number: Number,
+	/// Index of the extrinsic in the block body.
+	pub index: u32,
+}
+
+impl<Hash, Number> From<IndexedExtrinsic<Hash, Number>> for ExtrinsicLocation<Hash, Number> {
+	fn from(extrinsic: IndexedExtrinsic<Hash, Number>) -> Self {
+		ExtrinsicLocation {
+			hash: extrinsic.hash,
+			block_hash: extrinsic.block_hash,
+			block_number: extrinsic.block_number,
+			index: extrinsic.index,
+		}
+	}
+}
+
+/// A page of the extrinsics of an account, newest first.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtrinsicsPage<Hash, Number> {
+	/// The extrinsics of this page.
+	pub extrinsics: Vec<ExtrinsicLocation<Hash, Number>>,
+	/// Total number of extrinsics of the account.
+	pub total: u64,
+	/// Cursor to pass as `before` to get the next page, `None` if this is the last page.
+	pub next: Option<u64>,
+}
+
+/// Extrinsic index RPC methods.
+#[rpc]
+pub trait ExtrinsicIndexApi<AccountId, Hash, Number> {
+	/// Returns the extrinsics signed by the given account, newest first.
+	///
+	/// `before` is the cursor returned with the previous page, `count` defaults to
+	/// [`DEFAULT_PAGE_SIZE`].
+	#[rpc(name = "index_extrinsicsBySigner")]
+	fn extrinsics_by_signer(
+		&self,
+		account: AccountId,
+		before: Option<u64>,
+		count: Option<u32>,
+	) -> jsonrpc_core::Result<ExtrinsicsPage<Hash, Number>>;
+
+	/// Returns the extrinsics signed by or otherwise touching the given account, newest first.
+	#[rpc(name = "index_extrinsicsByAccount")]
+	fn extrinsics_by_account(
+		&self,
+		account: AccountId,
+		before: Option<u64>,
+		count: Option<u32>,
+	) -> jsonrpc_core::Result<ExtrinsicsPage<Hash, Number>>;
+
+	/// Returns the location of the extrinsic with the given hash in the canonical chain.
+	#[rpc(name = "index_extrinsicByHash")]
+	fn extrinsic_by_hash(
+		&self,
+		hash: Hash,
+	) -> jsonrpc_core::Result<Option<ExtrinsicLocation<Hash, Number>>>;
+}
+
+/// Implements the [`ExtrinsicIndexApi`] on top of an [`ExtrinsicIndex`].
+pub struct ExtrinsicIndexRpc<Block: BlockT, AccountId> {
+	index: Arc<dyn ExtrinsicIndex<Block>>,
+	_marker: PhantomData<AccountId>,
+}
+
+impl<Block: BlockT, AccountId> ExtrinsicIndexRpc<Block, AccountId> {
+	/// Create a new handler on top of the given index.
+	pub fn new(index: Arc<dyn ExtrinsicIndex<Block>>) -> Self {
+		ExtrinsicIndexRpc { index, _marker: PhantomData }
+	}
+
+	fn page(
+		&self,
+		account: AccountId,
+		relation: AccountRelation,
+		before: Option<u64>,
+		count: Option<u32>,
+	) -> Result<ExtrinsicsPage<Block::Hash, NumberFor<Block>>, Error> where AccountId: Encode {
+		let count = count.unwrap_or(DEFAULT_PAGE_SIZE);
+		if count > MAX_PAGE_SIZE {
+			return Err(Error::PageSize(count));
+		}
+
+		let account = account.encode();
+		let total = self.index.account_extrinsics_count(&account, relation)?;
+		let extrinsics = self.index.account_extrinsics(&account, relation, before, count)?;
+		let next = match extrinsics.last() {
+			Some((position, _)) if *position > 0 && extrinsics.len() == count as usize =>
+				Some(*position),
+			_ => None,
+		};
+
+		Ok(ExtrinsicsPage {
+			extrinsics: extrinsics.into_iter().map(|(_, extrinsic)| extrinsic.into()).collect(),
+			total,
+			next,
+		})
+	}
+}
+
+impl<Block, AccountId> ExtrinsicIndexApi<AccountId, Block::Hash, NumberFor<Block>>
+	for ExtrinsicIndexRpc<Block, AccountId>
+where
+	Block: BlockT,
+	AccountId: Encode + Send + Sync + 'static,
+{
+	fn extrinsics_by_signer(
+		&self,
+		account: AccountId,
+		before: Option<u64>,
+		count: Option<u32>,
+	) -> jsonrpc_core::Result<ExtrinsicsPage<Block::Hash, NumberFor<Block>>> {
+		self.page(account, AccountRelation::Signer, before, count).map_err(Into::into)
+	}
+
+	fn extrinsics_by_account(
+		&self,
+		account: AccountId,
+		before: Option<u64>,
+		count: Option<u32>,
+	) -> jsonrpc_core::Result<ExtrinsicsPage<Block::Hash, NumberFor<Block>>> {
+		self.page(account, AccountRelation::Touched, before, count).map_err(Into::into)
+	}
+
+	fn extrinsic_by_hash(
+		&self,
+		hash: Block::Hash,
+	) -> jsonrpc_core::Result<Option<ExtrinsicLocation<Block::Hash, NumberFor<Block>>>> {
+		self.index.extrinsic_by_hash(&hash)
+			.map(|extrinsic| extrinsic.map(Into::into))
+			.map_err(|e| Error::from(e).into())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use jsonrpc_core::IoHandler;
+	use sc_client_api::ExtrinsicIndexEntry;
+	use substrate_test_runtime_client::runtime::{Block, Hash};
+
+	/// Index of a single account that signed `count` extrinsics, one per block.
+	struct TestIndex {
+		count: u64,
+	}
+
+	fn extrinsic(position: u64) -> IndexedExtrinsic<Hash, u64> {
+		IndexedExtrinsic {
+			hash: Hash::repeat_byte(position as u8),
+			block_hash: Hash::repeat_byte(0xff - position as u8),
+			block_number: position + 1,
+			index: 1,
+		}
+	}
+
+	impl ExtrinsicIndex<Block> for TestIndex {
+		fn last_indexed(&self) -> sp_blockchain::Result<Option<(Hash, u64)>> {
+			Ok(None)
+		}
+
+		fn index_block(
+			&self,
+			_: Hash,
+			_: u64,
+			_: Vec<ExtrinsicIndexEntry<Hash>>,
+		) -> sp_blockchain::Result<()> {
+			unimplemented!()
+		}
+
+		fn revert_block(&self, _: Hash, _: (Hash, u64)) -> sp_blockchain::Result<()> {
+			unimplemented!()
+		}
+
+		fn last_pruned(&self) -> sp_blockchain::Result<Option<u64>> {
+			Ok(None)
+		}
+
+		fn prune_block(&self, _: Hash, _: u64) -> sp_blockchain::Result<()> {
+			unimplemented!()
+		}
+
+		fn extrinsic_by_hash(
+			&self,
+			hash: &Hash,
+		) -> sp_blockchain::Result<Option<IndexedExtrinsic<Hash, u64>>> {
+			Ok((0..self.count).map(extrinsic).find(|extrinsic| extrinsic.hash == *hash))
+		}
+
+		fn account_extrinsics_count(
+			&self,
+			account: &[u8],
+			_: AccountRelation,
+		) -> sp_blockchain::Result<u64> {
+			Ok(if account == 1u64.encode().as_slice() { self.count } else { 0 })
+		}
+
+		fn account_extrinsics(
+			&self,
+			account: &[u8],
+			relation: AccountRelation,
+			before: Option<u64>,
+			count: u32,
+		) -> sp_blockchain::Result<Vec<(u64, IndexedExtrinsic<Hash, u64>)>> {
+			let total = self.account_extrinsics_count(account, relation)?;
+			let end = before.unwrap_or(total).min(total);
+			Ok((0..end).rev().take(count as usize).map(|p| (p, extrinsic(p))).collect())
+		}
+	}
+
+	fn io(count: u64) -> IoHandler {
+		let mut io = IoHandler::new();
+		io.extend_with(ExtrinsicIndexApi::to_delegate(
+			ExtrinsicIndexRpc::<Block, u64>::new(Arc::new(TestIndex { count })),
+		));
+		io
+	}
+
+	fn call(io: &IoHandler, method: &str, params: &str) -> serde_json::Value {
+		let request = format!(
+			r#"{{"jsonrpc":"2.0","method":"{}","params":{},"id":1}}"#,
+			method,
+			params,
+		);
+		let response = io.handle_request_sync(&request).unwrap();
+		serde_json::from_str(&response).unwrap()
+	}
+
+	#[test]
+	fn pages_through_account_extrinsics() {
+		let io = io(5);
+
+		let first = call(&io, "index_extrinsicsBySigner", "[1, null, 2]");
+		let first: ExtrinsicsPage<Hash, u64> =
+			serde_json::from_value(first["result"].clone()).unwrap();
+		assert_eq!(first.total, 5);
+		assert_eq!(first.extrinsics, vec![extrinsic(4).into(), extrinsic(3).into()]);
+		assert_eq!(first.next, Some(3));
+
+		let last = call(&io, "index_extrinsicsByAccount", "[1, 1, 2]");
+		let last: ExtrinsicsPage<Hash, u64> =
+			serde_json::from_value(last["result"].clone()).unwrap();
+		assert_eq!(last.extrinsics, vec![extrinsic(0).into()]);
+		assert_eq!(last.next, None);
+	}
+
+	#[test]
+	fn rejects_too_large_pages() {
+		let params = format!("[1, null, {}]", MAX_PAGE_SIZE + 1);
+		let response = call(&io(5), "index_extrinsicsBySigner", &params);
+		assert_eq!(response["error"]["code"], PAGE_SIZE_ERROR_CODE);
+	}
+
+	fn hash_params(byte: u8) -> String {
+		format!("[\"{:?}\"]", Hash::repeat_byte(byte))
+	}
+
+	#[test]
+	fn finds_extrinsic_by_hash() {
+		let response = call(&io(5), "index_extrinsicByHash", &hash_params(2));
+		assert_eq!(response["result"]["blockNumber"], 3);
+		assert_eq!(response["result"]["blockHash"], format!("{:?}", Hash::repeat_byte(0xfd)));
+
+		let response = call(&io(5), "index_extrinsicByHash", &hash_params(7));
+		assert!(response["result"].is_null());
+	}
+}
@@ -0,0 +1,411 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Indexer of the extrinsics of the canonical chain.
+//!
+//! The indexer follows new best and finalized blocks and keeps an [`ExtrinsicIndex`] in line
+//! with the canonical chain: blocks that are retracted by a re-org are reverted from the index
+//! before the blocks of the new chain are added. Blocks that were imported without a
+//! notification, e.g. during major sync, are picked up with the next notification.
+//!
+//! Which accounts an extrinsic relates to depends on the runtime, so it is provided through
+//! [`ExtrinsicAccounts`].
+//!
+//! The hash lookups of finalized blocks that are older than the configured retention are
+//! pruned, which bounds the size of the index by hash.
+//!
+//! Blocks are added and pruned in batches of at most [`MAX_BLOCKS_PER_BATCH`] blocks, so
+//! catching up with a long chain neither builds a route over the whole chain nor holds up
+//! the indexer for long. All of the indexer's work is database work, so [`run_indexer`]
+//! should be spawned as a blocking task.
+
+#![warn(missing_docs)]
+
+use std::sync::Arc;
+
+use futures::{future, stream, prelude::*};
+use log::{debug, warn};
+use sc_client_api::{BlockBackend, BlockchainEvents, ExtrinsicIndex, ExtrinsicIndexEntry};
+use sp_blockchain::{HeaderBackend, HeaderMetadata, Error as ClientError, Result as ClientResult};
+use sp_runtime::{
+	generic::BlockId,
+	traits::{Block as BlockT, Hash as HashT, HashFor, Header as HeaderT, NumberFor, One, Zero},
+};
+
+/// Maximal number of blocks that are added to or pruned from the index at once.
+pub const MAX_BLOCKS_PER_BATCH: u32 = 512;
+
+/// Accounts an extrinsic relates to.
+///
+/// Only the accounts that can be told from the extrinsic itself are known, accounts that are
+/// touched as a side effect of its dispatch are not.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Accounts {
+	/// SCALE encoded id of the account that signed the extrinsic.
+	pub signer: Option<Vec<u8>>,
+	/// SCALE encoded ids of other accounts that are touched by the extrinsic.
+	pub touched: Vec<Vec<u8>>,
+}
+
+/// Extracts the accounts an extrinsic relates to.
+pub trait ExtrinsicAccounts<Block: BlockT>: Send + Sync {
+	/// Returns the accounts the given extrinsic relates to.
+	fn accounts(&self, extrinsic: &Block::Extrinsic) -> Accounts;
+}
+
+impl<Block: BlockT, F> ExtrinsicAccounts<Block> for F
+	where F: Fn(&Block::Extrinsic) -> Accounts + Send + Sync
+{
+	fn accounts(&self, extrinsic: &Block::Extrinsic) -> Accounts {
+		(self)(extrinsic)
+	}
+}
+
+/// Keeps an extrinsic index in line with the canonical chain of a client.
+pub struct Indexer<Block: BlockT, Client> {
+	client: Arc<Client>,
+	index: Arc<dyn ExtrinsicIndex<Block>>,
+	accounts: Arc<dyn ExtrinsicAccounts<Block>>,
+	hash_retention: Option<NumberFor<Block>>,
+	batch_size: NumberFor<Block>,
+}
+
+impl<Block, Client> Indexer<Block, Client> where
+	Block: BlockT,
+	Client: HeaderBackend<Block> + HeaderMetadata<Block, Error = ClientError> + BlockBackend<Block>,
+{
+	/// Create a new indexer.
+	pub fn new(
+		client: Arc<Client>,
+		index: Arc<dyn ExtrinsicIndex<Block>>,
+		accounts: Arc<dyn ExtrinsicAccounts<Block>>,
+	) -> Self {
+		Indexer {
+			client,
+			index,
+			accounts,
+			hash_retention: None,
+			batch_size: MAX_BLOCKS_PER_BATCH.into(),
+		}
+	}
+
+	/// Only keep the hash lookups of the given number of finalized blocks.
+	///
+	/// By default the hash lookups of all blocks are kept.
+	pub fn with_hash_retention(mut self, blocks: u32) -> Self {
+		self.hash_retention = Some(blocks.into());
+		self
+	}
+
+	/// Makes the chain ending at `target` the indexed chain.
+	pub fn sync_to(&self, target: Block::Hash) -> ClientResult<()> {
+		while !self.sync_batch(target)? {}
+		Ok(())
+	}
+
+	/// Moves the indexed chain towards the chain ending at `target`, adding at most one batch
+	/// of blocks. Returns `true` once `target` is the last indexed block.
+	fn sync_batch(&self, target: Block::Hash) -> ClientResult<bool> {
+		let (last, last_number) = match self.index.last_indexed()? {
+			Some(last) => last,
+			None => {
+				let genesis = self.client.hash(Zero::zero())?
+					.ok_or_else(|| ClientError::UnknownBlock("genesis".into()))?;
+				self.index_block(genesis, Zero::zero())?;
+				(genesis, Zero::zero())
+			},
+		};
+
+		// When far behind, e.g. after a major sync, approach the target along the canonical
+		// chain. If that doesn't lead to the target, the next batch reverts the difference.
+		let batch_end = last_number + self.batch_size;
+		let (to, reached) = if self.client.header_metadata(target)?.number > batch_end {
+			let hash = self.client.hash(batch_end)?
+				.ok_or_else(|| ClientError::UnknownBlock(format!("{}", batch_end)))?;
+			(hash, false)
+		} else {
+			(target, true)
+		};
+
+		let route = sp_blockchain::tree_route(&*self.client, last, to)?;
+		for retracted in route.retracted() {
+			let parent = self.client.header_metadata(retracted.hash)?.parent;
+			let parent = (parent, retracted.number - One::one());
+			self.index.revert_block(retracted.hash, parent)?;
+		}
+		for enacted in route.enacted() {
+			self.index_block(enacted.hash, enacted.number)?;
+		}
+
+		if !route.retracted().is_empty() || route.enacted().len() > 1 {
+			debug!(
+				target: "extrinsic-index",
+				"Indexed up to {}, reverted {} and added {} blocks",
+				to,
+				route.retracted().len(),
+				route.enacted().len(),
+			);
+		}
+		Ok(reached)
+	}
+
+	/// Makes the finalized chain ending at `finalized` part of the indexed chain.
+	///
+	/// Does nothing if the index is already ahead of the given block, as it then follows
+	/// the best chain which contains the finalized block.
+	pub fn sync_finalized(&self, finalized: Block::Hash, number: NumberFor<Block>) -> ClientResult<()> {
+		match self.index.last_indexed()? {
+			Some((_, last)) if last >= number => (),
+			_ => self.sync_to(finalized)?,
+		}
+		self.prune(number)
+	}
+
+	/// Prunes the hash lookups of the blocks that are out of the retention window of the
+	/// given finalized block.
+	pub fn prune(&self, finalized: NumberFor<Block>) -> ClientResult<()> {
+		let retention = match self.hash_retention {
+			Some(retention) if finalized > retention => retention,
+			_ => return Ok(()),
+		};
+		let target = finalized - retention;
+		while !self.prune_batch(target)? {}
+		Ok(())
+	}
+
+	/// Prunes at most one batch of the blocks up to `target`. Returns `true` once `target`
+	/// is pruned.
+	fn prune_batch(&self, target: NumberFor<Block>) -> ClientResult<bool> {
+		let mut number = match self.index.last_pruned()? {
+			Some(last) => last + One::one(),
+			None => Zero::zero(),
+		};
+		let batch_end = std::cmp::min(target, number + self.batch_size - One::one());
+
+		while number <= batch_end {
+			let hash = self.client.hash(number)?
+				.ok_or_else(|| ClientError::UnknownBlock(format!("{}", number)))?;
+			self.index.prune_block(hash, number)?;
+			number += One::one();
+		}
+		Ok(batch_end >= target)
+	}
+
+	fn index_block(&self, hash: Block::Hash, number: NumberFor<Block>) -> ClientResult<()> {
+		let extrinsics = self.client.block_body(&BlockId::Hash(hash))?
+			.unwrap_or_default()
+			.iter()
+			.enumerate()
+			.map(|(index, extrinsic)| {
+				let accounts = self.accounts.accounts(extrinsic);
+				ExtrinsicIndexEntry {
+					hash: HashFor::<Block>::hash_of(extrinsic),
+					index: index as u32,
+					signer: accounts.signer,
+					accounts: accounts.touched,
+				}
+			})
+			.collect();
+
+		self.index.index_block(hash, number, extrinsics)
+	}
+}
+
+/// Runs the indexer until the client shuts down.
+///
+/// The indexer reads and writes the database in between awaiting notifications, so the
+/// returned future should be spawned as a blocking task.
+pub async fn run_indexer<Block, Client>(indexer: Indexer<Block, Client>) where
+	Block: BlockT,
+	Client: HeaderBackend<Block> + HeaderMetadata<Block, Error = ClientError> + BlockBackend<Block>
+		+ BlockchainEvents<Block>,
+{
+	enum Target<Block: BlockT> {
+		Best(Block::Hash),
+		Finalized(Block::Hash, NumberFor<Block>),
+	}
+
+	let best = indexer.client.import_notification_stream()
+		.filter(|notification| future::ready(notification.is_new_best))
+		.map(|notification| Target::<Block>::Best(notification.hash));
+	let finalized = indexer.client.finality_notification_stream()
+		.map(|notification| Target::Finalized(notification.hash, *notification.header.number()));
+
+	// catch up with blocks imported while the indexer was not running
+	let info = indexer.client.info();
+	if let Err(e) = indexer.sync_to(info.best_hash) {
+		warn!(target: "extrinsic-index", "Failed to index up to {}: {:?}", info.best_hash, e);
+	}
+	if let Err(e) = indexer.prune(info.finalized_number) {
+		warn!(target: "extrinsic-index", "Failed to prune up to {}: {:?}", info.finalized_number, e);
+	}
+
+	let mut targets = stream::select(best, finalized);
+	while let Some(target) = targets.next().await {
+		let (hash, result) = match target {
+			Target::Best(hash) => (hash, indexer.sync_to(hash)),
+			Target::Finalized(hash, number) => (hash, indexer.sync_finalized(hash, number)),
+		};
+		if let Err(e) = result {
+			warn!(target: "extrinsic-index", "Failed to index up to {}: {:?}", hash, e);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::Encode;
+	use sc_block_builder::BlockBuilderProvider;
+	use sc_client_api::AccountRelation;
+	use sp_consensus::BlockOrigin;
+	use substrate_test_runtime_client::{
+		prelude::*,
+		runtime::{Block, Extrinsic, Transfer},
+		TestClientBuilder,
+	};
+
+	fn accounts(extrinsic: &Extrinsic) -> Accounts {
+		match extrinsic {
+			Extrinsic::Transfer { transfer, .. } => Accounts {
+				signer: Some(transfer.from.encode()),
+				touched: vec![transfer.to.encode()],
+			},
+			_ => Accounts::default(),
+		}
+	}
+
+	fn transfer(nonce: u64) -> Transfer {
+		Transfer {
+			from: AccountKeyring::Alice.into(),
+			to: AccountKeyring::Bob.into(),
+			amount: 1,
+			nonce,
+		}
+	}
+
+	#[test]
+	fn follows_reorgs() {
+		let builder = TestClientBuilder::new();
+		let index = Arc::new(builder.backend().extrinsic_index());
+		let mut client = Arc::new(builder.build());
+		let indexer = Indexer::new(client.clone(), index.clone(), Arc::new(accounts));
+		let genesis = client.info().best_hash;
+		let alice = AccountKeyring::Alice.public().encode();
+
+		let mut builder = client.new_block(Default::default()).unwrap();
+		builder.push_transfer(transfer(0)).unwrap();
+		let a1 = builder.build().unwrap().block;
+		client.import(BlockOrigin::Own, a1.clone()).unwrap();
+		indexer.sync_to(a1.hash()).unwrap();
+
+		let a1_transfer = HashFor::<Block>::hash_of(&a1.extrinsics[0]);
+		assert_eq!(index.extrinsic_by_hash(&a1_transfer).unwrap().unwrap().block_hash, a1.hash());
+		assert_eq!(index.account_extrinsics_count(&alice, AccountRelation::Signer).unwrap(), 1);
+
+		// fork at genesis that becomes the best chain
+		let mut builder = client.new_block_at(&BlockId::Hash(genesis), Default::default(), false).unwrap();
+		builder.push_transfer(transfer(0)).unwrap();
+		builder.push_transfer(transfer(1)).unwrap();
+		let b1 = builder.build().unwrap().block;
+		client.import(BlockOrigin::Own, b1.clone()).unwrap();
+		let b2 = client.new_block_at(&BlockId::Hash(b1.hash()), Default::default(), false)
+			.unwrap().build().unwrap().block;
+		client.import(BlockOrigin::Own, b2.clone()).unwrap();
+		indexer.sync_to(b2.hash()).unwrap();
+
+		assert_eq!(index.last_indexed().unwrap(), Some((b2.hash(), 2)));
+		assert_eq!(index.extrinsic_by_hash(&a1_transfer).unwrap().unwrap().block_hash, b1.hash());
+		assert_eq!(index.account_extrinsics_count(&alice, AccountRelation::Signer).unwrap(), 2);
+		assert_eq!(
+			index.account_extrinsics(&alice, AccountRelation::Touched, None, 10).unwrap()
+				.into_iter()
+				.map(|(_, extrinsic)| (extrinsic.block_hash, extrinsic.index))
+				.collect::<Vec<_>>(),
+			vec![(b1.hash(), 1), (b1.hash(), 0)],
+		);
+
+		// finality of an already indexed block does not change anything
+		indexer.sync_finalized(b1.hash(), 1).unwrap();
+		assert_eq!(index.last_indexed().unwrap(), Some((b2.hash(), 2)));
+	}
+
+	#[test]
+	fn prunes_hash_lookups_out_of_retention() {
+		let builder = TestClientBuilder::new();
+		let index = Arc::new(builder.backend().extrinsic_index());
+		let mut client = Arc::new(builder.build());
+		let indexer = Indexer::new(client.clone(), index.clone(), Arc::new(accounts))
+			.with_hash_retention(1);
+		let alice = AccountKeyring::Alice.public().encode();
+
+		let mut hashes = Vec::new();
+		for nonce in 0..3 {
+			let mut builder = client.new_block(Default::default()).unwrap();
+			builder.push_transfer(transfer(nonce)).unwrap();
+			let block = builder.build().unwrap().block;
+			hashes.push(HashFor::<Block>::hash_of(&block.extrinsics[0]));
+			client.import(BlockOrigin::Own, block.clone()).unwrap();
+			indexer.sync_to(block.hash()).unwrap();
+		}
+		let finalized = client.info().best_hash;
+		indexer.sync_finalized(finalized, 3).unwrap();
+
+		assert_eq!(index.last_pruned().unwrap(), Some(2));
+		assert!(index.extrinsic_by_hash(&hashes[0]).unwrap().is_none());
+		assert!(index.extrinsic_by_hash(&hashes[1]).unwrap().is_none());
+		assert!(index.extrinsic_by_hash(&hashes[2]).unwrap().is_some());
+		assert_eq!(index.account_extrinsics_count(&alice, AccountRelation::Signer).unwrap(), 3);
+	}
+
+	#[test]
+	fn catches_up_and_prunes_in_batches() {
+		let builder = TestClientBuilder::new();
+		let index = Arc::new(builder.backend().extrinsic_index());
+		let mut client = Arc::new(builder.build());
+		let mut indexer = Indexer::new(client.clone(), index.clone(), Arc::new(accounts))
+			.with_hash_retention(1);
+		indexer.batch_size = 2;
+		let alice = AccountKeyring::Alice.public().encode();
+
+		let mut hashes = Vec::new();
+		for nonce in 0..5 {
+			let mut builder = client.new_block(Default::default()).unwrap();
+			builder.push_transfer(transfer(nonce)).unwrap();
+			let block = builder.build().unwrap().block;
+			hashes.push(HashFor::<Block>::hash_of(&block.extrinsics[0]));
+			client.import(BlockOrigin::Own, block).unwrap();
+		}
+		let best = client.info().best_hash;
+
+		assert!(!indexer.sync_batch(best).unwrap());
+		assert_eq!(index.last_indexed().unwrap().map(|(_, number)| number), Some(2));
+
+		indexer.sync_to(best).unwrap();
+		assert_eq!(index.last_indexed().unwrap(), Some((best, 5)));
+		assert_eq!(index.account_extrinsics_count(&alice, AccountRelation::Signer).unwrap(), 5);
+
+		assert!(!indexer.prune_batch(4).unwrap());
+		assert_eq!(index.last_pruned().unwrap(), Some(1));
+
+		indexer.sync_finalized(best, 5).unwrap();
+		assert_eq!(index.last_pruned().unwrap(), Some(4));
+		assert!(index.extrinsic_by_hash(&hashes[3]).unwrap().is_none());
+		assert!(index.extrinsic_by_hash(&hashes[4]).unwrap().is_some());
+	}
+}
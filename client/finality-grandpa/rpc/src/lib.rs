@@ -19,13 +19,23 @@
 //! RPC API for GRANDPA.
 #![warn(missing_docs)]
 
-use futures::{FutureExt, TryFutureExt};
+use futures::{FutureExt, StreamExt, TryFutureExt, TryStreamExt};
+use jsonrpc_core::futures::{Future as Future01, Sink};
 use jsonrpc_derive::rpc;
+use jsonrpc_pubsub::{manager::SubscriptionManager, typed::Subscriber, SubscriptionId};
+use log::warn;
+use sc_finality_grandpa::GrandpaJustificationStream;
+use sp_runtime::traits::Block as BlockT;
 
 mod error;
+mod notification;
 mod report;
 
-use report::{ReportAuthoritySet, ReportVoterState, ReportedRoundStates};
+use report::{
+	ReportAuthoritySet, ReportLastCompletedRound, ReportVoterState, ReportVotingRules,
+	ReportedLastCompletedRound, ReportedRoundStates, ReportedVotingRules,
+};
+pub use notification::JustificationNotification;
 
 /// Returned when Grandpa RPC endpoint is not ready.
 pub const NOT_READY_ERROR_CODE: i64 = 1;
@@ -36,51 +46,166 @@ type FutureResult<T> =
 /// Provides RPC methods for interacting with GRANDPA.
 #[rpc]
 pub trait GrandpaApi {
+	/// RPC Metadata
+	type Metadata;
+
 	/// Returns the state of the current best round state as well as the
-	/// ongoing background rounds.
+	/// ongoing background rounds. For each round the authorities that have
+	/// prevoted and precommitted are reported, together with the ones that
+	/// are missing and their weight.
 	#[rpc(name = "grandpa_roundState")]
 	fn round_state(&self) -> FutureResult<ReportedRoundStates>;
+
+	/// Returns the last round completed by the voter, with its base, its
+	/// estimate and the block finalized in it.
+	#[rpc(name = "grandpa_lastCompletedRound")]
+	fn last_completed_round(&self) -> FutureResult<ReportedLastCompletedRound>;
+
+	/// Returns the active voting rules and the vote target they restricted
+	/// the vote to in the last round, together with the rule that restricted it.
+	#[rpc(name = "grandpa_votingRules")]
+	fn voting_rules(&self) -> FutureResult<ReportedVotingRules>;
+
+	/// Subscribe to the justifications of finalized blocks. A justification is
+	/// sent at the end of each GRANDPA round that finalizes a block, and when
+	/// one is imported with a block.
+	#[pubsub(
+		subscription = "grandpa_justifications",
+		subscribe,
+		name = "grandpa_subscribeJustifications"
+	)]
+	fn subscribe_justifications(
+		&self,
+		metadata: Self::Metadata,
+		subscriber: Subscriber<JustificationNotification>,
+	);
+
+	/// Unsubscribe from receiving justifications.
+	#[pubsub(
+		subscription = "grandpa_justifications",
+		unsubscribe,
+		name = "grandpa_unsubscribeJustifications"
+	)]
+	fn unsubscribe_justifications(
+		&self,
+		metadata: Option<Self::Metadata>,
+		id: SubscriptionId,
+	) -> jsonrpc_core::Result<bool>;
 }
 
 /// Implements the GrandpaApi RPC trait for interacting with GRANDPA.
-pub struct GrandpaRpcHandler<AuthoritySet, VoterState> {
+pub struct GrandpaRpcHandler<AuthoritySet, VoterState, VotingRules, VoterSetState, Block: BlockT> {
 	authority_set: AuthoritySet,
 	voter_state: VoterState,
+	voting_rules: VotingRules,
+	voter_set_state: VoterSetState,
+	justification_stream: GrandpaJustificationStream<Block>,
+	subscriptions: SubscriptionManager,
 }
 
-impl<AuthoritySet, VoterState> GrandpaRpcHandler<AuthoritySet, VoterState> {
+impl<AuthoritySet, VoterState, VotingRules, VoterSetState, Block: BlockT>
+	GrandpaRpcHandler<AuthoritySet, VoterState, VotingRules, VoterSetState, Block>
+{
 	/// Creates a new GrandpaRpcHander instance.
-	pub fn new(authority_set: AuthoritySet, voter_state: VoterState) -> Self {
+	pub fn new(
+		authority_set: AuthoritySet,
+		voter_state: VoterState,
+		voting_rules: VotingRules,
+		voter_set_state: VoterSetState,
+		justification_stream: GrandpaJustificationStream<Block>,
+		subscriptions: SubscriptionManager,
+	) -> Self {
 		Self {
 			authority_set,
 			voter_state,
+			voting_rules,
+			voter_set_state,
+			justification_stream,
+			subscriptions,
 		}
 	}
 }
 
-impl<AuthoritySet, VoterState> GrandpaApi for GrandpaRpcHandler<AuthoritySet, VoterState>
+impl<AuthoritySet, VoterState, VotingRules, VoterSetState, Block> GrandpaApi
+	for GrandpaRpcHandler<AuthoritySet, VoterState, VotingRules, VoterSetState, Block>
 where
 	VoterState: ReportVoterState + Send + Sync + 'static,
 	AuthoritySet: ReportAuthoritySet + Send + Sync + 'static,
+	VotingRules: ReportVotingRules + Send + Sync + 'static,
+	VoterSetState: ReportLastCompletedRound + Send + Sync + 'static,
+	Block: BlockT,
 {
+	type Metadata = sc_rpc::Metadata;
+
 	fn round_state(&self) -> FutureResult<ReportedRoundStates> {
 		let round_states = ReportedRoundStates::from(&self.authority_set, &self.voter_state);
 		let future = async move { round_states }.boxed();
 		Box::new(future.map_err(jsonrpc_core::Error::from).compat())
 	}
+
+	fn last_completed_round(&self) -> FutureResult<ReportedLastCompletedRound> {
+		let last_completed_round = ReportedLastCompletedRound::from(&self.voter_set_state);
+		let future = async move { Ok::<_, error::Error>(last_completed_round) }.boxed();
+		Box::new(future.map_err(jsonrpc_core::Error::from).compat())
+	}
+
+	fn voting_rules(&self) -> FutureResult<ReportedVotingRules> {
+		let voting_rules = ReportedVotingRules::from(&self.voting_rules);
+		let future = async move { Ok::<_, error::Error>(voting_rules) }.boxed();
+		Box::new(future.map_err(jsonrpc_core::Error::from).compat())
+	}
+
+	fn subscribe_justifications(
+		&self,
+		_metadata: Self::Metadata,
+		subscriber: Subscriber<JustificationNotification>,
+	) {
+		let stream = self.justification_stream.subscribe()
+			.map(|justification| Ok::<_, ()>(Ok(JustificationNotification::from(justification))))
+			.compat();
+
+		self.subscriptions.add(subscriber, |sink| {
+			sink
+				.sink_map_err(|e| warn!("Error sending notifications: {:?}", e))
+				.send_all(stream)
+				.map(|_| ())
+		});
+	}
+
+	fn unsubscribe_justifications(
+		&self,
+		_metadata: Option<Self::Metadata>,
+		id: SubscriptionId,
+	) -> jsonrpc_core::Result<bool> {
+		Ok(self.subscriptions.cancel(id))
+	}
 }
 
 #[cfg(test)]
 mod tests {
 	use super::*;
-	use jsonrpc_core::IoHandler;
-	use sc_finality_grandpa::{report, AuthorityId};
+	use jsonrpc_core::{futures::future::{ExecuteError, Executor}, Output};
+	use sc_finality_grandpa::{report, AuthorityId, LastCompletedRound, VoteTargetReport};
 	use sp_core::crypto::Public;
-	use std::{collections::HashSet, convert::TryInto};
+	use std::{collections::HashSet, convert::TryInto, sync::Arc};
+	use substrate_test_runtime_client::runtime::Block;
 
 	struct TestAuthoritySet;
 	struct TestVoterState;
 	struct EmptyVoterState;
+	struct TestVotingRules;
+	struct TestVoterSetState;
+
+	type Boxed01Future01 = Box<dyn Future01<Item = (), Error = ()> + Send + 'static>;
+
+	struct TestExecutor;
+
+	impl Executor<Boxed01Future01> for TestExecutor {
+		fn execute(&self, future: Boxed01Future01) -> Result<(), ExecuteError<Boxed01Future01>> {
+			std::thread::spawn(move || { let _ = future.wait(); });
+			Ok(())
+		}
+	}
 
 	fn voters() -> HashSet<AuthorityId> {
 		let voter_id_1 = AuthorityId::from_slice(&[1; 32]);
@@ -95,6 +220,31 @@ mod tests {
 		}
 	}
 
+	impl ReportVotingRules for TestVotingRules {
+		fn get(&self) -> (Vec<String>, Option<VoteTargetReport<String, u64>>) {
+			let rules = vec!["before best block by 2".into(), "pause at block 10".into()];
+			let vote_target = VoteTargetReport {
+				base: ("0x01".into(), 5),
+				best: ("0x02".into(), 20),
+				target: ("0x03".into(), 10),
+				restricted_by: Some("pause at block 10".into()),
+			};
+
+			(rules, Some(vote_target))
+		}
+	}
+
+	impl ReportLastCompletedRound for TestVoterSetState {
+		fn get(&self) -> LastCompletedRound<String, u64> {
+			LastCompletedRound {
+				number: 1,
+				base: ("0x01".into(), 5),
+				estimate: Some(("0x02".into(), 8)),
+				finalized: Some(("0x02".into(), 8)),
+			}
+		}
+	}
+
 	impl ReportVoterState for EmptyVoterState {
 		fn get(&self) -> Option<report::VoterState<AuthorityId>> {
 			None
@@ -133,39 +283,126 @@ mod tests {
 		}
 	}
 
+	fn setup_io_handler<VoterState>(
+		voter_state: VoterState,
+	) -> jsonrpc_core::MetaIoHandler<sc_rpc::Metadata>
+	where
+		VoterState: ReportVoterState + Send + Sync + 'static,
+	{
+		let (_, justification_stream) = GrandpaJustificationStream::<Block>::channel();
+		let handler = GrandpaRpcHandler::new(
+			TestAuthoritySet,
+			voter_state,
+			TestVotingRules,
+			TestVoterSetState,
+			justification_stream,
+			SubscriptionManager::new(Arc::new(TestExecutor)),
+		);
+
+		let mut io = jsonrpc_core::MetaIoHandler::default();
+		io.extend_with(GrandpaApi::to_delegate(handler));
+		io
+	}
+
 	#[test]
 	fn uninitialized_rpc_handler() {
-		let handler = GrandpaRpcHandler::new(TestAuthoritySet, EmptyVoterState);
-		let mut io = IoHandler::new();
-		io.extend_with(GrandpaApi::to_delegate(handler));
+		let io = setup_io_handler(EmptyVoterState);
 
 		let request = r#"{"jsonrpc":"2.0","method":"grandpa_roundState","params":[],"id":1}"#;
 		let response = r#"{"jsonrpc":"2.0","error":{"code":1,"message":"GRANDPA RPC endpoint not ready"},"id":1}"#;
 
-		assert_eq!(Some(response.into()), io.handle_request_sync(request));
+		assert_eq!(Some(response.into()), io.handle_request_sync(request, Default::default()));
 	}
 
 	#[test]
 	fn working_rpc_handler() {
-		let handler = GrandpaRpcHandler::new(TestAuthoritySet, TestVoterState);
-		let mut io = IoHandler::new();
-		io.extend_with(GrandpaApi::to_delegate(handler));
+		let io = setup_io_handler(TestVoterState);
 
 		let request = r#"{"jsonrpc":"2.0","method":"grandpa_roundState","params":[],"id":1}"#;
 		let response = "{\"jsonrpc\":\"2.0\",\"result\":{\
 			\"background\":[{\
-				\"precommits\":{\"currentWeight\":100,\"missing\":[]},\
-				\"prevotes\":{\"currentWeight\":100,\"missing\":[]},\
+				\"precommits\":{\"currentWeight\":100,\"missing\":[],\"missingWeight\":0,\
+					\"voted\":[\"5C62Ck4UrFPiBtoCmeSrgF7x9yv9mn38446dhCpsi2mLHiFT\",\"5C7LYpP2ZH3tpKbvVvwiVe54AapxErdPBbvkYhe6y9ZBkqWt\"]},\
+				\"prevotes\":{\"currentWeight\":100,\"missing\":[],\"missingWeight\":0,\
+					\"voted\":[\"5C62Ck4UrFPiBtoCmeSrgF7x9yv9mn38446dhCpsi2mLHiFT\",\"5C7LYpP2ZH3tpKbvVvwiVe54AapxErdPBbvkYhe6y9ZBkqWt\"]},\
 				\"round\":1,\"thresholdWeight\":67,\"totalWeight\":100\
 			}],\
 			\"best\":{\
-				\"precommits\":{\"currentWeight\":0,\"missing\":[\"5C62Ck4UrFPiBtoCmeSrgF7x9yv9mn38446dhCpsi2mLHiFT\",\"5C7LYpP2ZH3tpKbvVvwiVe54AapxErdPBbvkYhe6y9ZBkqWt\"]},\
-				\"prevotes\":{\"currentWeight\":50,\"missing\":[\"5C7LYpP2ZH3tpKbvVvwiVe54AapxErdPBbvkYhe6y9ZBkqWt\"]},\
+				\"precommits\":{\"currentWeight\":0,\"missing\":[\"5C62Ck4UrFPiBtoCmeSrgF7x9yv9mn38446dhCpsi2mLHiFT\",\"5C7LYpP2ZH3tpKbvVvwiVe54AapxErdPBbvkYhe6y9ZBkqWt\"],\
+					\"missingWeight\":100,\"voted\":[]},\
+				\"prevotes\":{\"currentWeight\":50,\"missing\":[\"5C7LYpP2ZH3tpKbvVvwiVe54AapxErdPBbvkYhe6y9ZBkqWt\"],\
+					\"missingWeight\":50,\"voted\":[\"5C62Ck4UrFPiBtoCmeSrgF7x9yv9mn38446dhCpsi2mLHiFT\"]},\
 				\"round\":2,\"thresholdWeight\":67,\"totalWeight\":100\
 			},\
 			\"setId\":1\
 		},\"id\":1}";
 
-		assert_eq!(io.handle_request_sync(request), Some(response.into()));
+		assert_eq!(io.handle_request_sync(request, Default::default()), Some(response.into()));
+	}
+
+	#[test]
+	fn last_completed_round_rpc_handler() {
+		let io = setup_io_handler(EmptyVoterState);
+
+		let request = r#"{"jsonrpc":"2.0","method":"grandpa_lastCompletedRound","params":[],"id":1}"#;
+		let response = "{\"jsonrpc\":\"2.0\",\"result\":{\
+			\"base\":{\"hash\":\"0x01\",\"number\":5},\
+			\"estimate\":{\"hash\":\"0x02\",\"number\":8},\
+			\"finalized\":{\"hash\":\"0x02\",\"number\":8},\
+			\"round\":1\
+		},\"id\":1}";
+
+		assert_eq!(io.handle_request_sync(request, Default::default()), Some(response.into()));
+	}
+
+	#[test]
+	fn voting_rules_rpc_handler() {
+		let io = setup_io_handler(EmptyVoterState);
+
+		let request = r#"{"jsonrpc":"2.0","method":"grandpa_votingRules","params":[],"id":1}"#;
+		let response = "{\"jsonrpc\":\"2.0\",\"result\":{\
+			\"rules\":[\"before best block by 2\",\"pause at block 10\"],\
+			\"voteTarget\":{\
+				\"base\":{\"hash\":\"0x01\",\"number\":5},\
+				\"best\":{\"hash\":\"0x02\",\"number\":20},\
+				\"restrictedBy\":\"pause at block 10\",\
+				\"target\":{\"hash\":\"0x03\",\"number\":10}\
+			}\
+		},\"id\":1}";
+
+		assert_eq!(io.handle_request_sync(request, Default::default()), Some(response.into()));
+	}
+
+	#[test]
+	fn subscribe_and_unsubscribe_to_justifications() {
+		let io = setup_io_handler(TestVoterState);
+		let (tx, _rx) = jsonrpc_core::futures::sync::mpsc::channel(1);
+		let meta = sc_rpc::Metadata::new(tx);
+
+		// Subscribe
+		let sub_request = r#"{"jsonrpc":"2.0","method":"grandpa_subscribeJustifications","params":[],"id":1}"#;
+		let resp = io.handle_request_sync(sub_request, meta.clone());
+		let resp: Output = serde_json::from_str(&resp.unwrap()).unwrap();
+
+		let sub_id = match resp {
+			Output::Success(success) => success.result,
+			_ => panic!(),
+		};
+
+		// Unsubscribe
+		let unsub_req = format!(
+			"{{\"jsonrpc\":\"2.0\",\"method\":\"grandpa_unsubscribeJustifications\",\"params\":[{}],\"id\":1}}",
+			sub_id
+		);
+		assert_eq!(
+			io.handle_request_sync(&unsub_req, meta.clone()),
+			Some(r#"{"jsonrpc":"2.0","result":true,"id":1}"#.into()),
+		);
+
+		// Unsubscribe again and fail
+		assert_eq!(
+			io.handle_request_sync(&unsub_req, meta),
+			Some(r#"{"jsonrpc":"2.0","result":false,"id":1}"#.into()),
+		);
 	}
 }
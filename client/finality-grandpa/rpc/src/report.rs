@@ -24,7 +24,11 @@ use std::{
 
 use serde::{Deserialize, Serialize};
 
-use sc_finality_grandpa::{report, AuthorityId, SharedAuthoritySet, SharedVoterState};
+use sc_finality_grandpa::{
+	report, AuthorityId, LastCompletedRound, SharedAuthoritySet, SharedVoterSetState,
+	SharedVoterState, SharedVotingRulesState, VoteTargetReport,
+};
+use sp_runtime::traits::{Block as BlockT, NumberFor};
 
 use crate::error::Error;
 
@@ -38,6 +42,16 @@ pub trait ReportVoterState {
 	fn get(&self) -> Option<report::VoterState<AuthorityId>>;
 }
 
+/// Utility trait to get reporting data for the GRANDPA voting rules.
+pub trait ReportVotingRules {
+	fn get(&self) -> (Vec<String>, Option<VoteTargetReport<String, u64>>);
+}
+
+/// Utility trait to get reporting data for the last completed GRANDPA round.
+pub trait ReportLastCompletedRound {
+	fn get(&self) -> LastCompletedRound<String, u64>;
+}
+
 impl<H, N> ReportAuthoritySet for SharedAuthoritySet<H, N>
 where
 	N: Add<Output = N> + Ord + Clone + Debug,
@@ -60,10 +74,50 @@ impl ReportVoterState for SharedVoterState {
 	}
 }
 
+impl<H, N> ReportVotingRules for SharedVotingRulesState<H, N>
+where
+	H: Clone + Debug,
+	N: Copy + Into<u64>,
+{
+	fn get(&self) -> (Vec<String>, Option<VoteTargetReport<String, u64>>) {
+		let block = |(hash, number): (H, N)| (format!("{:?}", hash), number.into());
+		let vote_target = self.vote_target().map(|vote_target| VoteTargetReport {
+			base: block(vote_target.base),
+			best: block(vote_target.best),
+			target: block(vote_target.target),
+			restricted_by: vote_target.restricted_by,
+		});
+
+		(self.rules(), vote_target)
+	}
+}
+
+impl<Block> ReportLastCompletedRound for SharedVoterSetState<Block>
+where
+	Block: BlockT,
+	NumberFor<Block>: Into<u64>,
+{
+	fn get(&self) -> LastCompletedRound<String, u64> {
+		let block = |(hash, number): (Block::Hash, NumberFor<Block>)| {
+			(format!("{:?}", hash), number.into())
+		};
+		let round = self.last_completed_round();
+
+		LastCompletedRound {
+			number: round.number,
+			base: block(round.base),
+			estimate: round.estimate.map(block),
+			finalized: round.finalized.map(block),
+		}
+	}
+}
+
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct Prevotes {
 	current_weight: u32,
+	missing_weight: u32,
+	voted: BTreeSet<AuthorityId>,
 	missing: BTreeSet<AuthorityId>,
 }
 
@@ -71,6 +125,8 @@ struct Prevotes {
 #[serde(rename_all = "camelCase")]
 struct Precommits {
 	current_weight: u32,
+	missing_weight: u32,
+	voted: BTreeSet<AuthorityId>,
 	missing: BTreeSet<AuthorityId>,
 }
 
@@ -98,16 +154,24 @@ impl RoundState {
 		let precommits = &round_state.precommit_ids;
 		let missing_precommits = voters.difference(&precommits).cloned().collect();
 
+		let total_weight = round_state.total_weight.get();
+		let prevote_current_weight = round_state.prevote_current_weight.0;
+		let precommit_current_weight = round_state.precommit_current_weight.0;
+
 		Ok(Self {
 			round: round.try_into()?,
-			total_weight: round_state.total_weight.get().try_into()?,
+			total_weight: total_weight.try_into()?,
 			threshold_weight: round_state.threshold_weight.get().try_into()?,
 			prevotes: Prevotes {
-				current_weight: round_state.prevote_current_weight.0.try_into()?,
+				current_weight: prevote_current_weight.try_into()?,
+				missing_weight: total_weight.saturating_sub(prevote_current_weight).try_into()?,
+				voted: prevotes.iter().cloned().collect(),
 				missing: missing_prevotes,
 			},
 			precommits: Precommits {
-				current_weight: round_state.precommit_current_weight.0.try_into()?,
+				current_weight: precommit_current_weight.try_into()?,
+				missing_weight: total_weight.saturating_sub(precommit_current_weight).try_into()?,
+				voted: precommits.iter().cloned().collect(),
 				missing: missing_precommits,
 			},
 		})
@@ -159,3 +223,79 @@ impl ReportedRoundStates {
 		})
 	}
 }
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReportedBlock {
+	hash: String,
+	number: u64,
+}
+
+impl From<(String, u64)> for ReportedBlock {
+	fn from((hash, number): (String, u64)) -> Self {
+		Self { hash, number }
+	}
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReportedVoteTarget {
+	base: ReportedBlock,
+	best: ReportedBlock,
+	target: ReportedBlock,
+	restricted_by: Option<String>,
+}
+
+/// The active voting rules and the vote target they restricted the vote to in
+/// the last round, in a form suitable for serialization.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportedVotingRules {
+	rules: Vec<String>,
+	vote_target: Option<ReportedVoteTarget>,
+}
+
+impl ReportedVotingRules {
+	pub fn from<VotingRules>(voting_rules: &VotingRules) -> Self
+	where
+		VotingRules: ReportVotingRules,
+	{
+		let (rules, vote_target) = voting_rules.get();
+
+		Self {
+			rules,
+			vote_target: vote_target.map(|vote_target| ReportedVoteTarget {
+				base: vote_target.base.into(),
+				best: vote_target.best.into(),
+				target: vote_target.target.into(),
+				restricted_by: vote_target.restricted_by,
+			}),
+		}
+	}
+}
+
+/// The last round completed by the voter, in a form suitable for serialization.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportedLastCompletedRound {
+	round: u64,
+	base: ReportedBlock,
+	estimate: Option<ReportedBlock>,
+	finalized: Option<ReportedBlock>,
+}
+
+impl ReportedLastCompletedRound {
+	pub fn from<VoterSetState>(voter_set_state: &VoterSetState) -> Self
+	where
+		VoterSetState: ReportLastCompletedRound,
+	{
+		let round = voter_set_state.get();
+
+		Self {
+			round: round.number,
+			base: round.base.into(),
+			estimate: round.estimate.map(Into::into),
+			finalized: round.finalized.map(Into::into),
+		}
+	}
+}
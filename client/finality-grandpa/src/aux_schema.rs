@@ -16,11 +16,12 @@
 
 //! Schema for stuff in the aux-db.
 
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::sync::Arc;
 use parity_scale_codec::{Encode, Decode};
-use sc_client_api::backend::AuxStore;
-use sp_blockchain::{Result as ClientResult, Error as ClientError};
+use sc_client_api::backend::{AuxStore, Backend};
+use sp_blockchain::{HeaderBackend, Result as ClientResult, Error as ClientError};
 use fork_tree::ForkTree;
 use finality_grandpa::round::State as RoundState;
 use sp_runtime::traits::{Block as BlockT, NumberFor};
@@ -433,6 +434,86 @@ pub(crate) fn update_consensus_changes<H, N, F, R>(
 	write_aux(&[(CONSENSUS_CHANGES_KEY, set.encode().as_slice())])
 }
 
+/// The keys of the aux data that is returned by [`state_snapshot_aux_data`].
+pub const STATE_SNAPSHOT_AUX_KEYS: &[&[u8]] = &[
+	VERSION_KEY,
+	AUTHORITY_SET_KEY,
+	SET_STATE_KEY,
+	CONSENSUS_CHANGES_KEY,
+];
+
+/// Returns the aux data GRANDPA needs to start on a database that is initialized from a state
+/// snapshot of the last finalized block `hash`, together with the number of the oldest block the
+/// data refers to.
+///
+/// The authority set only keeps the pending changes that were signaled on the chain of the
+/// block, the voter starts from a blank slate at the block.
+pub fn state_snapshot_aux_data<Block: BlockT, BE: Backend<Block>>(
+	backend: &BE,
+	hash: Block::Hash,
+) -> ClientResult<(NumberFor<Block>, Vec<(Vec<u8>, Vec<u8>)>)> {
+	let blockchain = backend.blockchain();
+	let info = blockchain.info();
+	if hash != info.finalized_hash {
+		return Err(ClientError::Backend(
+			"GRANDPA data can only be exported at the last finalized block".into(),
+		));
+	}
+	let number = info.finalized_number;
+
+	if load_decode::<_, u32>(backend, VERSION_KEY)? != Some(CURRENT_VERSION) {
+		return Err(ClientError::Backend(
+			"Outdated GRANDPA database, start the node once to migrate it".into(),
+		));
+	}
+	let set = load_decode::<_, AuthoritySet<Block::Hash, NumberFor<Block>>>(backend, AUTHORITY_SET_KEY)?
+		.ok_or_else(|| ClientError::Backend("Missing GRANDPA authority set".into()))?;
+
+	let is_descendent_of = sc_client_api::utils::is_descendent_of(blockchain, None);
+	let mut changes = Vec::new();
+	for change in set.pending_changes() {
+		if change.canon_hash == hash || is_descendent_of(&change.canon_hash, &hash)? {
+			changes.push(change.clone());
+		}
+	}
+	changes.sort_by(|a, b| a.canon_height.cmp(&b.canon_height));
+	let oldest = changes.first().map_or(number, |change| change.canon_height);
+
+	// all kept changes are on one chain, so their heights order them.
+	let heights = changes.iter()
+		.map(|change| (change.canon_hash, change.canon_height))
+		.collect::<HashMap<_, _>>();
+	let on_chain_descendent_of = |base: &Block::Hash, block: &Block::Hash| {
+		Ok::<_, ClientError>(match (heights.get(base), heights.get(block)) {
+			(Some(base), Some(block)) => base < block,
+			_ => false,
+		})
+	};
+
+	let mut snapshot_set = AuthoritySet::new(
+		set.current_authorities.clone(),
+		set.set_id,
+		ForkTree::new(),
+		Vec::new(),
+	).ok_or_else(|| ClientError::Backend("Invalid GRANDPA authority set".into()))?;
+	for change in changes {
+		snapshot_set.add_pending_change(change, &on_chain_descendent_of)
+			.map_err(|e| ClientError::Backend(format!("Invalid pending GRANDPA change: {:?}", e)))?;
+	}
+
+	let set_state = VoterSetState::<Block>::live(set.set_id, &snapshot_set, (hash, number));
+
+	Ok((oldest, vec![
+		(VERSION_KEY.to_vec(), CURRENT_VERSION.encode()),
+		(AUTHORITY_SET_KEY.to_vec(), snapshot_set.encode()),
+		(SET_STATE_KEY.to_vec(), set_state.encode()),
+		(
+			CONSENSUS_CHANGES_KEY.to_vec(),
+			ConsensusChanges::<Block::Hash, NumberFor<Block>>::empty().encode(),
+		),
+	]))
+}
+
 #[cfg(test)]
 pub(crate) fn load_authorities<B: AuxStore, H: Decode, N: Decode>(backend: &B)
 	-> Option<AuthoritySet<H, N>> {
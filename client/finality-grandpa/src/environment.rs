@@ -52,6 +52,7 @@ use crate::authorities::{AuthoritySet, SharedAuthoritySet};
 use crate::communication::Network as NetworkT;
 use crate::consensus_changes::SharedConsensusChanges;
 use crate::justification::GrandpaJustification;
+use crate::notification::GrandpaJustificationSender;
 use crate::until_imported::UntilVoteTargetImported;
 use crate::voting_rule::VotingRule;
 use sp_finality_grandpa::{
@@ -365,6 +366,18 @@ impl<Block: BlockT> SharedVoterSetState<Block> {
 		}
 	}
 
+	/// Return a report of the last completed round.
+	pub fn last_completed_round(&self) -> LastCompletedRound<Block::Hash, NumberFor<Block>> {
+		let round = self.inner.read().last_completed_round();
+
+		LastCompletedRound {
+			number: round.number,
+			base: round.base,
+			estimate: round.state.estimate,
+			finalized: round.state.finalized,
+		}
+	}
+
 	// NOTE: not exposed outside of this module intentionally.
 	fn with<F, R>(&self, f: F) -> R
 		where F: FnOnce(&mut VoterSetState<Block>) -> R
@@ -373,6 +386,21 @@ impl<Block: BlockT> SharedVoterSetState<Block> {
 	}
 }
 
+/// Report of the last round completed by the voter, in the current or a
+/// previous voter set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LastCompletedRound<H, N> {
+	/// The round number.
+	pub number: RoundNumber,
+	/// The target block base used for voting in the round.
+	pub base: (H, N),
+	/// The estimate of the round, i.e. the highest block that could still be
+	/// finalized in it.
+	pub estimate: Option<(H, N)>,
+	/// The block finalized in the round, if any.
+	pub finalized: Option<(H, N)>,
+}
+
 /// Prometheus metrics for GRANDPA.
 #[derive(Clone)]
 pub(crate) struct Metrics {
@@ -404,6 +432,7 @@ pub(crate) struct Environment<Backend, Block: BlockT, C, N: NetworkT<Block>, SC,
 	pub(crate) voter_set_state: SharedVoterSetState<Block>,
 	pub(crate) voting_rule: VR,
 	pub(crate) metrics: Option<Metrics>,
+	pub(crate) justification_sender: Option<GrandpaJustificationSender<Block>>,
 	pub(crate) _phantom: PhantomData<Backend>,
 }
 
@@ -1017,6 +1046,7 @@ where
 			number,
 			(round, commit).into(),
 			false,
+			self.justification_sender.as_ref(),
 		)
 	}
 
@@ -1081,6 +1111,7 @@ pub(crate) fn finalize_block<BE, Block, Client>(
 	number: NumberFor<Block>,
 	justification_or_commit: JustificationOrCommit<Block>,
 	initial_sync: bool,
+	justification_sender: Option<&GrandpaJustificationSender<Block>>,
 ) -> Result<(), CommandOrError<Block::Hash, NumberFor<Block>>> where
 	Block:  BlockT,
 	BE: Backend<Block>,
@@ -1117,6 +1148,10 @@ pub(crate) fn finalize_block<BE, Block, Client>(
 		canonical_at_height(&*client, (hash, number), true, canon_number)
 	};
 
+	// the justification of the finalized block, if any, to be sent to the
+	// subscribers once finality has been applied successfully.
+	let mut notify_justification = None;
+
 	let update_res: Result<_, Error> = client.lock_import_and_run(|import_op| {
 		let status = authority_set.apply_standard_changes(
 			hash,
@@ -1151,8 +1186,8 @@ pub(crate) fn finalize_block<BE, Block, Client>(
 		// `N+1`. this assumption is required to make sure we store
 		// justifications for transition blocks which will be requested by
 		// syncing clients.
-		let justification = match justification_or_commit {
-			JustificationOrCommit::Justification(justification) => Some(justification.encode()),
+		let (justification_required, justification) = match justification_or_commit {
+			JustificationOrCommit::Justification(justification) => (true, Some(justification)),
 			JustificationOrCommit::Commit((round_number, commit)) => {
 				let mut justification_required =
 					// justification is always required when block that enacts new authorities
@@ -1172,28 +1207,40 @@ pub(crate) fn finalize_block<BE, Block, Client>(
 					}
 				}
 
-				if justification_required {
+				// the justification is also created when someone subscribed to
+				// justification notifications, even if it isn't persisted.
+				let has_subscribers = justification_sender
+					.map_or(false, |sender| sender.has_subscribers());
+
+				if justification_required || has_subscribers {
 					let justification = GrandpaJustification::from_commit(
 						&client,
 						round_number,
 						commit,
 					)?;
 
-					Some(justification.encode())
+					(justification_required, Some(justification))
 				} else {
-					None
+					(false, None)
 				}
 			},
 		};
 
+		let persisted_justification = if justification_required {
+			justification.as_ref().map(Encode::encode)
+		} else {
+			None
+		};
+
 		debug!(target: "afg", "Finalizing blocks up to ({:?}, {})", number, hash);
 
 		// ideally some handle to a synchronization oracle would be used
 		// to avoid unconditionally notifying.
-		client.apply_finality(import_op, BlockId::Hash(hash), justification, true).map_err(|e| {
+		client.apply_finality(import_op, BlockId::Hash(hash), persisted_justification, true).map_err(|e| {
 			warn!(target: "afg", "Error applying finality to block {:?}: {:?}", (hash, number), e);
 			e
 		})?;
+		notify_justification = justification;
 		telemetry!(CONSENSUS_INFO; "afg.finalized_blocks_up_to";
 			"number" => ?number, "hash" => ?hash,
 		);
@@ -1247,6 +1294,12 @@ pub(crate) fn finalize_block<BE, Block, Client>(
 		Ok(new_authorities.map(VoterCommand::ChangeAuthorities))
 	});
 
+	if update_res.is_ok() {
+		if let (Some(sender), Some(justification)) = (justification_sender, notify_justification) {
+			sender.notify(justification);
+		}
+	}
+
 	match update_res {
 		Ok(Some(command)) => Err(CommandOrError::VoterCommand(command)),
 		Ok(None) => Ok(()),
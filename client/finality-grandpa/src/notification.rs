@@ -0,0 +1,83 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Notifications about justifications of finalized blocks.
+
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use sp_runtime::traits::Block as BlockT;
+use sp_utils::mpsc::{tracing_unbounded, TracingUnboundedReceiver, TracingUnboundedSender};
+
+use crate::justification::GrandpaJustification;
+
+type JustificationSubscribers<Block> = Arc<Mutex<Vec<TracingUnboundedSender<GrandpaJustification<Block>>>>>;
+
+/// The sending half of the GRANDPA justification channel.
+///
+/// Used to send notifications about justifications generated at the end of a
+/// GRANDPA round or imported with a block.
+#[derive(Clone)]
+pub struct GrandpaJustificationSender<Block: BlockT> {
+	subscribers: JustificationSubscribers<Block>,
+}
+
+impl<Block: BlockT> GrandpaJustificationSender<Block> {
+	/// Returns true if anyone is listening to justification notifications.
+	///
+	/// Justifications that aren't persisted are only created when this is the case.
+	pub(crate) fn has_subscribers(&self) -> bool {
+		!self.subscribers.lock().is_empty()
+	}
+
+	/// Send a justification to all subscribers, dropping the ones that have
+	/// gone away.
+	pub(crate) fn notify(&self, justification: GrandpaJustification<Block>) {
+		self.subscribers.lock()
+			.retain(|sender| sender.unbounded_send(justification.clone()).is_ok());
+	}
+}
+
+/// The receiving half of the GRANDPA justification channel.
+///
+/// Used to subscribe to notifications about justifications of finalized
+/// blocks. The `GrandpaJustificationStream` shares its list of subscribers with the
+/// sender, so it can be cloned and used to add more subscriptions.
+#[derive(Clone)]
+pub struct GrandpaJustificationStream<Block: BlockT> {
+	subscribers: JustificationSubscribers<Block>,
+}
+
+impl<Block: BlockT> GrandpaJustificationStream<Block> {
+	/// Creates a new pair of receiver and sender of justification notifications.
+	pub fn channel() -> (GrandpaJustificationSender<Block>, Self) {
+		let subscribers = Arc::new(Mutex::new(Vec::new()));
+		let sender = GrandpaJustificationSender { subscribers: subscribers.clone() };
+		let receiver = GrandpaJustificationStream { subscribers };
+
+		(sender, receiver)
+	}
+
+	/// Subscribe to a channel through which justifications are sent at the end
+	/// of each GRANDPA voting round and when they are imported with a block.
+	pub fn subscribe(&self) -> TracingUnboundedReceiver<GrandpaJustification<Block>> {
+		let (sender, receiver) = tracing_unbounded("mpsc_justification_notification_stream");
+		self.subscribers.lock().push(sender);
+		receiver
+	}
+}
@@ -438,6 +438,36 @@ fn finalize_3_voters_no_observers() {
 	);
 }
 
+#[test]
+fn finalized_blocks_justifications_are_sent_to_subscribers() {
+	let _ = env_logger::try_init();
+	let mut runtime = Runtime::new().unwrap();
+	let peers = &[Ed25519Keyring::Alice, Ed25519Keyring::Bob, Ed25519Keyring::Charlie];
+	let voters = make_ids(peers);
+
+	let mut net = GrandpaTestNet::new(TestApi::new(voters), 3);
+	net.peer(0).push_blocks(20, false);
+	net.block_until_sync();
+
+	let mut justifications = net.peer(0).data.lock()
+		.as_ref()
+		.expect("link initialized at startup; qed")
+		.justification_stream()
+		.subscribe();
+
+	let net = Arc::new(Mutex::new(net));
+	run_to_completion(&mut runtime, 20, net.clone(), peers);
+
+	// the justification isn't persisted, but it is still sent to subscribers
+	assert!(net.lock().peer(0).client().justification(&BlockId::Number(20)).unwrap().is_none());
+
+	let mut last_finalized = None;
+	while let Ok(Some(justification)) = justifications.try_next() {
+		last_finalized = Some(justification.commit.target_number);
+	}
+	assert_eq!(last_finalized, Some(20));
+}
+
 #[test]
 fn finalize_3_voters_1_full_observer() {
 	let mut runtime = Runtime::new().unwrap();
@@ -1569,6 +1599,7 @@ fn grandpa_environment_respects_voting_rules() {
 			network,
 			voting_rule,
 			metrics: None,
+			justification_sender: None,
 			_phantom: PhantomData,
 		}
 	};
@@ -1648,6 +1679,213 @@ fn grandpa_environment_respects_voting_rules() {
 	);
 }
 
+#[derive(Clone)]
+struct TestPeers(Vec<(Hash, BlockNumber)>);
+
+impl PeerBestBlocks<Block> for TestPeers {
+	fn peers_best_blocks(&self) -> Vec<(Hash, BlockNumber)> {
+		self.0.clone()
+	}
+}
+
+#[test]
+fn configured_voting_rules_restrict_and_report_vote_target() {
+	let peers = &[Ed25519Keyring::Alice];
+	let voters = make_ids(peers);
+
+	let mut net = GrandpaTestNet::new(TestApi::new(voters), 1);
+	let peer = net.peer(0);
+
+	// add 21 blocks
+	peer.push_blocks(21, false);
+
+	let client = peer.client().as_full().expect("only full clients are used in test");
+	let header = |number: BlockNumber| client.header(&BlockId::Number(number)).unwrap().unwrap();
+
+	// half of the peers have seen block 18, three quarters have seen block 12
+	let test_peers = TestPeers(
+		[12, 21, 10, 18].iter().map(|number| (header(*number).hash(), *number)).collect()
+	);
+
+	let voting_rules = |config: &[&str], state: SharedVotingRulesState<Hash, BlockNumber>| {
+		let config = config.iter()
+			.map(|rule| rule.parse::<VotingRuleConfig>().unwrap())
+			.collect::<Vec<_>>();
+
+		VotingRulesBuilder::<Block, PeersFullClient>::from_config(&config, test_peers.clone(), client.clone())
+			.report_to(state)
+			.build()
+	};
+
+	// the pause restricts the vote further than the peers.
+	let state = SharedVotingRulesState::empty();
+	let voting_rule = voting_rules(&["seen-by-peers:50", "pause-at:15"], state.clone());
+
+	assert_eq!(state.rules(), vec!["seen by 50% of peers", "pause at block 15"]);
+	assert_eq!(
+		voting_rule.restrict_vote(&*client, &header(0), &header(21), &header(21)),
+		Some((header(15).hash(), 15)),
+	);
+	assert_eq!(
+		state.vote_target(),
+		Some(VoteTargetReport {
+			base: (header(0).hash(), 0),
+			best: (header(21).hash(), 21),
+			target: (header(15).hash(), 15),
+			restricted_by: Some("pause at block 15".into()),
+		}),
+	);
+
+	// the peers restrict the vote further than the pause.
+	let state = SharedVotingRulesState::empty();
+	let voting_rule = voting_rules(&["pause-at:15", "seen-by-peers:75"], state.clone());
+
+	assert_eq!(
+		voting_rule.restrict_vote(&*client, &header(0), &header(21), &header(21)),
+		Some((header(12).hash(), 12)),
+	);
+	assert_eq!(
+		state.vote_target().unwrap().restricted_by,
+		Some("seen by 75% of peers".into()),
+	);
+
+	// the vote is never restricted below the base.
+	assert_eq!(
+		voting_rule.restrict_vote(&*client, &header(14), &header(21), &header(21)),
+		Some((header(14).hash(), 14)),
+	);
+
+	// unknown rules and missing parameters are rejected.
+	assert!("pause-at".parse::<VotingRuleConfig>().is_err());
+	assert!("seen-by-peers:101".parse::<VotingRuleConfig>().is_err());
+	assert!("three-quarters:1".parse::<VotingRuleConfig>().is_err());
+	assert!("unknown".parse::<VotingRuleConfig>().is_err());
+}
+
+#[test]
+fn seen_by_peers_only_counts_blocks_of_the_target_chain() {
+	let peers = &[Ed25519Keyring::Alice];
+	let voters = make_ids(peers);
+
+	let mut net = GrandpaTestNet::new(TestApi::new(voters), 1);
+	let peer = net.peer(0);
+
+	// add 21 blocks, and a fork of 8 blocks on top of block 10
+	peer.push_blocks(21, false);
+	let fork = peer.push_blocks_at(BlockId::Number(10), 8, true);
+
+	let client = peer.client().as_full().expect("only full clients are used in test");
+	let header = |number: BlockNumber| client.header(&BlockId::Number(number)).unwrap().unwrap();
+
+	// all the peers are at block 18, but only one of them on our chain: the
+	// others have only seen block 10, or a block we don't know.
+	let rule = SeenByPeers::new(
+		TestPeers(vec![(header(18).hash(), 18), (fork, 18), (H256::random(), 18)]),
+		sp_arithmetic::Percent::from_percent(50),
+	);
+
+	assert_eq!(
+		VotingRule::<Block, PeersFullClient>::restrict_vote(&rule, &*client, &header(0), &header(21), &header(21)),
+		Some((header(10).hash(), 10)),
+	);
+
+	// the unknown block is ignored, but never restricts the vote below the base.
+	assert_eq!(
+		VotingRule::<Block, PeersFullClient>::restrict_vote(&rule, &*client, &header(12), &header(21), &header(21)),
+		Some((header(12).hash(), 12)),
+	);
+}
+
+#[test]
+fn runtime_pause_is_tracked_per_fork() {
+	use sp_finality_grandpa::{ConsensusLog, StoredState};
+	use substrate_test_runtime_client::{BlockBuilderExt, runtime::Header};
+
+	// the pallet state is written like the GRANDPA pallet does along with its
+	// signals.
+	fn set_state(
+		mut builder: sc_block_builder::BlockBuilder<Block, PeersFullClient, substrate_test_runtime_client::Backend>,
+		state: StoredState<BlockNumber>,
+		signal: Option<ConsensusLog<BlockNumber>>,
+	) -> Block {
+		let key = [sp_core::hashing::twox_128(b"GrandpaFinality"), sp_core::hashing::twox_128(b"State")].concat();
+		builder.push_storage_change(key, Some(state.encode())).unwrap();
+		let mut block = builder.build().unwrap().block;
+		if let Some(signal) = signal {
+			block.header.digest_mut().push(DigestItem::Consensus(GRANDPA_ENGINE_ID, signal.encode()));
+		}
+		block
+	}
+
+	let peers = &[Ed25519Keyring::Alice];
+	let voters = make_ids(peers);
+
+	let mut net = GrandpaTestNet::new(TestApi::new(voters), 1);
+	let peer = net.peer(0);
+
+	// add 10 blocks, then a pause that takes effect at block 13 and 6 more
+	// blocks.
+	peer.push_blocks(10, false);
+	peer.generate_blocks(1, BlockOrigin::File, |builder| set_state(
+		builder,
+		StoredState::PendingPause { scheduled_at: 11, delay: 2 },
+		Some(ConsensusLog::Pause(2)),
+	));
+	peer.push_blocks(1, false);
+	peer.generate_blocks(1, BlockOrigin::File, |builder| set_state(builder, StoredState::Paused, None));
+	peer.push_blocks(6, false);
+
+	// the fork on top of block 10 doesn't have the pause.
+	let fork = peer.push_blocks_at(BlockId::Number(10), 9, true);
+
+	let client = peer.client().as_full().expect("only full clients are used in test");
+	let header = |number: BlockNumber| client.header(&BlockId::Number(number)).unwrap().unwrap();
+	let fork = client.header(&BlockId::Hash(fork)).unwrap().unwrap();
+
+	let rule = RuntimePause::new(client.clone());
+	let restrict_vote = |rule: &RuntimePause<Hash, BlockNumber>, base: &Header, target: &Header| {
+		VotingRule::<Block, PeersFullClient>::restrict_vote(rule, &*client, base, target, target)
+	};
+
+	assert_eq!(restrict_vote(&rule, &header(0), &header(19)), Some((header(13).hash(), 13)));
+	assert_eq!(restrict_vote(&rule, &header(0), &fork), None);
+	// the pause isn't in effect yet.
+	assert_eq!(restrict_vote(&rule, &header(0), &header(12)), None);
+
+	// after a restart the state at the base is read from the runtime, the
+	// chain below it isn't walked.
+	let restarted = RuntimePause::new(client.clone());
+	assert_eq!(restrict_vote(&restarted, &header(13), &header(19)), Some((header(13).hash(), 13)));
+	let pending = RuntimePause::new(client.clone());
+	assert_eq!(restrict_vote(&pending, &header(12), &header(19)), Some((header(13).hash(), 13)));
+
+	// a resume that takes effect at block 22 ends the pause there.
+	peer.generate_blocks(1, BlockOrigin::File, |builder| set_state(
+		builder,
+		StoredState::PendingResume { scheduled_at: 20, delay: 2 },
+		Some(ConsensusLog::Resume(2)),
+	));
+	peer.push_blocks(1, false);
+	peer.generate_blocks(1, BlockOrigin::File, |builder| set_state(builder, StoredState::Live, None));
+	peer.push_blocks(1, false);
+
+	let client = peer.client().as_full().expect("only full clients are used in test");
+	let header = |number: BlockNumber| client.header(&BlockId::Number(number)).unwrap().unwrap();
+	let restrict_vote = |rule: &RuntimePause<Hash, BlockNumber>, base: &Header, target: &Header| {
+		VotingRule::<Block, PeersFullClient>::restrict_vote(rule, &*client, base, target, target)
+	};
+
+	assert_eq!(restrict_vote(&rule, &header(13), &header(21)), Some((header(13).hash(), 13)));
+	assert_eq!(restrict_vote(&rule, &header(13), &header(23)), None);
+	assert_eq!(restrict_vote(&restarted, &header(13), &header(23)), None);
+
+	// the pending resume read at the base keeps the pause until it takes
+	// effect.
+	let resuming = RuntimePause::new(client.clone());
+	assert_eq!(restrict_vote(&resuming, &header(20), &header(21)), Some((header(20).hash(), 20)));
+	assert_eq!(restrict_vote(&resuming, &header(20), &header(23)), None);
+}
+
 #[test]
 fn imports_justification_for_regular_blocks_on_import() {
 	// NOTE: this is a regression test since initially we would only import
@@ -21,11 +21,27 @@
 //! This exposes the `VotingRule` trait used to implement arbitrary voting
 //! restrictions that are taken into account by the GRANDPA environment when
 //! selecting a finality target to vote on.
+//!
+//! Besides the rules that can be combined in code through the
+//! `VotingRulesBuilder`, the built-in rules can be selected from the node
+//! configuration through `VotingRuleConfig`.
 
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
 use std::sync::Arc;
 
+use log::warn;
+use parity_scale_codec::Decode;
+use parking_lot::{Mutex, RwLock};
+use sc_client_api::{Backend, StorageProvider};
 use sc_client_api::blockchain::HeaderBackend;
-use sp_runtime::generic::BlockId;
+use sc_network::{ExHashT, NetworkService};
+use sp_arithmetic::Percent;
+use sp_blockchain::Result as ClientResult;
+use sp_core::{hashing::twox_128, storage::StorageKey};
+use sp_finality_grandpa::{ConsensusLog, StoredState, GRANDPA_ENGINE_ID};
+use sp_runtime::generic::{BlockId, OpaqueDigestItemId};
 use sp_runtime::traits::{Block as BlockT, Header, NumberFor, One, Zero};
 
 /// A trait for custom voting rules in GRANDPA.
@@ -52,6 +68,12 @@ pub trait VotingRule<Block, B>: Send + Sync where
 		best_target: &Block::Header,
 		current_target: &Block::Header,
 	) -> Option<(Block::Hash, NumberFor<Block>)>;
+
+	/// A short human readable description of the rule, used when reporting
+	/// the active voting rules.
+	fn description(&self) -> String {
+		String::from("custom")
+	}
 }
 
 impl<Block, B> VotingRule<Block, B> for () where
@@ -67,6 +89,10 @@ impl<Block, B> VotingRule<Block, B> for () where
 	) -> Option<(Block::Hash, NumberFor<Block>)> {
 		None
 	}
+
+	fn description(&self) -> String {
+		String::from("none")
+	}
 }
 
 /// A custom voting rule that guarantees that our vote is always behind the best
@@ -106,6 +132,10 @@ impl<Block, B> VotingRule<Block, B> for BeforeBestBlockBy<NumberFor<Block>> wher
 			current_target,
 		)
 	}
+
+	fn description(&self) -> String {
+		format!("before best block by {}", self.0)
+	}
 }
 
 /// A custom voting rule that limits votes towards 3/4 of the unfinalized chain,
@@ -148,6 +178,327 @@ impl<Block, B> VotingRule<Block, B> for ThreeQuartersOfTheUnfinalizedChain where
 			current_target,
 		)
 	}
+
+	fn description(&self) -> String {
+		String::from("three quarters of the unfinalized chain")
+	}
+}
+
+/// A custom voting rule that never votes past the given block, e.g. to halt
+/// finality at a fixed height for maintenance. Blocks below the round base
+/// are already final, so the vote is never restricted below the base.
+#[derive(Clone)]
+pub struct PauseAtBlock<N>(pub N);
+
+impl<Block, B> VotingRule<Block, B> for PauseAtBlock<NumberFor<Block>> where
+	Block: BlockT,
+	B: HeaderBackend<Block>,
+{
+	fn restrict_vote(
+		&self,
+		backend: &B,
+		base: &Block::Header,
+		_best_target: &Block::Header,
+		current_target: &Block::Header,
+	) -> Option<(Block::Hash, NumberFor<Block>)> {
+		let target_number = std::cmp::max(self.0, *base.number());
+
+		// our current target is already lower than this rule would restrict
+		if target_number >= *current_target.number() {
+			return None;
+		}
+
+		find_target(
+			backend,
+			target_number,
+			current_target,
+		)
+	}
+
+	fn description(&self) -> String {
+		format!("pause at block {}", self.0)
+	}
+}
+
+/// A source of the best blocks of the peers we're connected to.
+pub trait PeerBestBlocks<Block: BlockT>: Send + Sync {
+	/// Returns the hashes and numbers of the best blocks announced by our
+	/// peers, in no particular order.
+	fn peers_best_blocks(&self) -> Vec<(Block::Hash, NumberFor<Block>)>;
+}
+
+impl<Block: BlockT, H: ExHashT> PeerBestBlocks<Block> for NetworkService<Block, H> {
+	fn peers_best_blocks(&self) -> Vec<(Block::Hash, NumberFor<Block>)> {
+		NetworkService::peers_best_blocks(self)
+	}
+}
+
+impl<Block: BlockT, T: PeerBestBlocks<Block>> PeerBestBlocks<Block> for Arc<T> {
+	fn peers_best_blocks(&self) -> Vec<(Block::Hash, NumberFor<Block>)> {
+		(**self).peers_best_blocks()
+	}
+}
+
+/// A custom voting rule that never votes past the highest block of the target
+/// chain that was seen by at least the given share of our peers, i.e. that is
+/// the best block announced by the peer or one of its ancestors. Peers on
+/// another fork have only seen the common ancestor, and peers whose best
+/// block we don't know haven't seen anything past the round base. If we aren't
+/// connected to any peers the vote isn't restricted.
+pub struct SeenByPeers<P> {
+	peers: P,
+	threshold: Percent,
+}
+
+impl<P> SeenByPeers<P> {
+	/// Create a new rule requiring `threshold` of the peers provided by
+	/// `peers` to have seen the block we vote on.
+	pub fn new(peers: P, threshold: Percent) -> Self {
+		SeenByPeers { peers, threshold }
+	}
+}
+
+impl<Block, B, P> VotingRule<Block, B> for SeenByPeers<P> where
+	Block: BlockT,
+	B: HeaderBackend<Block>,
+	P: PeerBestBlocks<Block>,
+{
+	fn restrict_vote(
+		&self,
+		backend: &B,
+		base: &Block::Header,
+		_best_target: &Block::Header,
+		current_target: &Block::Header,
+	) -> Option<(Block::Hash, NumberFor<Block>)> {
+		let peers = self.peers.peers_best_blocks();
+		if peers.is_empty() {
+			return None;
+		}
+
+		let base_number = *base.number();
+
+		// the blocks of the target chain above the base.
+		let mut chain = HashSet::new();
+		let mut header = current_target.clone();
+		while *header.number() > base_number {
+			chain.insert(header.hash());
+			header = backend.header(BlockId::Hash(*header.parent_hash())).ok()?
+				.expect("Header known to exist due to the existence of one of its descendents; qed");
+		}
+
+		// the highest block of the target chain that the peer has seen.
+		let seen_number = |mut hash: Block::Hash, mut number: NumberFor<Block>| {
+			while number > base_number {
+				if chain.contains(&hash) {
+					return number;
+				}
+
+				match backend.header(BlockId::Hash(hash)) {
+					Ok(Some(header)) => {
+						hash = *header.parent_hash();
+						number = *header.number() - One::one();
+					},
+					_ => return base_number,
+				}
+			}
+
+			number
+		};
+
+		let mut numbers = peers.into_iter()
+			.map(|(hash, number)| seen_number(hash, number))
+			.collect::<Vec<_>>();
+
+		// the highest block that was seen by at least `required` peers.
+		numbers.sort_unstable_by(|a, b| b.cmp(a));
+		let required = std::cmp::max(self.threshold.mul_ceil(numbers.len() as u32), 1);
+
+		let target_number = std::cmp::max(numbers[required as usize - 1], base_number);
+
+		// our current target is already lower than this rule would restrict
+		if target_number >= *current_target.number() {
+			return None;
+		}
+
+		find_target(
+			backend,
+			target_number,
+			current_target,
+		)
+	}
+
+	fn description(&self) -> String {
+		format!("seen by {}% of peers", self.threshold.deconstruct())
+	}
+}
+
+/// A custom voting rule that respects the pauses signalled by the runtime
+/// through GRANDPA `Pause` and `Resume` consensus logs. Once a pause takes
+/// effect we don't vote past the block it takes effect at until a `Resume`
+/// signal takes effect.
+///
+/// The pause in effect is tracked for each block, so that signals on one fork
+/// don't affect the votes on the others. It is derived from the chain by
+/// walking back from the vote target to the last block whose state is known,
+/// to the last `Pause` signal or to the round base. The state at the base is
+/// read from the GRANDPA pallet, so that the walk never goes further back.
+pub struct RuntimePause<H, N> {
+	/// The pause in effect at the blocks we looked at, along with their
+	/// number. Blocks below the round base are forgotten.
+	states: Mutex<HashMap<H, (N, Option<Pause<N>>)>>,
+	/// Reads the encoded state of the GRANDPA pallet at a block.
+	read_state: Box<dyn Fn(&H) -> ClientResult<Option<Vec<u8>>> + Send + Sync>,
+}
+
+/// A pause signalled by the runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Pause<N> {
+	/// The block the pause takes effect at.
+	at: N,
+	/// The block the pause ends at, if a `Resume` was signalled.
+	until: Option<N>,
+}
+
+impl<H, N> RuntimePause<H, N> {
+	/// Create a new rule that reads the state of the GRANDPA pallet from the
+	/// storage of `client`.
+	pub fn new<Block, BE, C>(client: Arc<C>) -> Self where
+		Block: BlockT<Hash = H>,
+		BE: Backend<Block>,
+		C: StorageProvider<Block, BE> + Send + Sync + 'static,
+	{
+		let key = StorageKey([twox_128(b"GrandpaFinality"), twox_128(b"State")].concat());
+		RuntimePause {
+			states: Mutex::new(HashMap::new()),
+			read_state: Box::new(move |hash: &H| Ok(
+				client.storage(&BlockId::Hash(*hash), &key)?.map(|data| data.0)
+			)),
+		}
+	}
+}
+
+impl<Block, B> VotingRule<Block, B> for RuntimePause<Block::Hash, NumberFor<Block>> where
+	Block: BlockT,
+	B: HeaderBackend<Block>,
+{
+	fn restrict_vote(
+		&self,
+		backend: &B,
+		base: &Block::Header,
+		_best_target: &Block::Header,
+		current_target: &Block::Header,
+	) -> Option<(Block::Hash, NumberFor<Block>)> {
+		let id = OpaqueDigestItemId::Consensus(&GRANDPA_ENGINE_ID);
+		let base_number = *base.number();
+		let mut states = self.states.lock();
+
+		// walk back from the target, collecting the signals, until we find a
+		// block whose state is known, a pause that overrides the previous
+		// state or the base.
+		let mut blocks = Vec::new();
+		let mut header = current_target.clone();
+		let mut state = loop {
+			let hash = header.hash();
+			if let Some((_, state)) = states.get(&hash) {
+				break *state;
+			}
+
+			let number = *header.number();
+			if number <= base_number {
+				// the runtime state at the base includes the signals of the
+				// base itself.
+				let state = runtime_state(&self.read_state, &hash, number)?;
+				states.insert(hash, (number, state));
+				break state;
+			}
+
+			let signals = header.digest().logs().iter()
+				.filter_map(|log| match log.try_to::<ConsensusLog<NumberFor<Block>>>(id) {
+					Some(ConsensusLog::Pause(delay)) => Some((true, number + delay)),
+					Some(ConsensusLog::Resume(delay)) => Some((false, number + delay)),
+					_ => None,
+				})
+				.collect::<Vec<_>>();
+			let paused = signals.iter().any(|(pause, _)| *pause);
+			blocks.push((hash, number, signals));
+
+			if paused {
+				break None;
+			}
+
+			header = backend.header(BlockId::Hash(*header.parent_hash())).ok()?
+				.expect("Header known to exist due to the existence of one of its descendents; qed");
+		};
+
+		for (hash, number, signals) in blocks.into_iter().rev() {
+			for (pause, effective_at) in signals {
+				state = if pause {
+					Some(Pause { at: effective_at, until: None })
+				} else {
+					state.map(|state| Pause { until: Some(effective_at), ..state })
+				};
+			}
+
+			if state.map_or(false, |state| state.until.map_or(false, |until| until <= number)) {
+				state = None;
+			}
+
+			states.insert(hash, (number, state));
+		}
+
+		// blocks below the base are final, votes never go back to them.
+		states.retain(|_, (number, _)| *number >= base_number);
+
+		let target_number = std::cmp::max(state?.at, base_number);
+
+		// our current target is already lower than this rule would restrict
+		if target_number >= *current_target.number() {
+			return None;
+		}
+
+		find_target(
+			backend,
+			target_number,
+			current_target,
+		)
+	}
+
+	fn description(&self) -> String {
+		String::from("runtime signalled pause")
+	}
+}
+
+/// The pause in effect at the block `hash` with the given `number`, derived
+/// from the state of the GRANDPA pallet. Returns `None` if the state can't be
+/// read, in which case the vote isn't restricted.
+fn runtime_state<H: fmt::Debug, N: Copy + std::ops::Add<Output = N> + Decode>(
+	read_state: &dyn Fn(&H) -> ClientResult<Option<Vec<u8>>>,
+	hash: &H,
+	number: N,
+) -> Option<Option<Pause<N>>> {
+	let stored = match read_state(hash) {
+		Ok(None) => StoredState::Live,
+		Ok(Some(encoded)) => match StoredState::decode(&mut &encoded[..]) {
+			Ok(stored) => stored,
+			Err(e) => {
+				warn!(target: "afg", "Invalid GRANDPA pallet state at {:?}: {}", hash, e.what());
+				return None;
+			},
+		},
+		Err(e) => {
+			warn!(target: "afg", "Failed to read the GRANDPA pallet state at {:?}: {}", hash, e);
+			return None;
+		},
+	};
+
+	Some(match stored {
+		StoredState::Live => None,
+		StoredState::PendingPause { scheduled_at, delay } =>
+			Some(Pause { at: scheduled_at + delay, until: None }),
+		StoredState::Paused => Some(Pause { at: number, until: None }),
+		StoredState::PendingResume { scheduled_at, delay } =>
+			Some(Pause { at: number, until: Some(scheduled_at + delay) }),
+	})
 }
 
 // walk backwards until we find the target block
@@ -181,14 +532,83 @@ fn find_target<Block, B>(
 	}
 }
 
-struct VotingRules<Block, B> {
+/// The vote target as restricted by the voting rules in the last round we
+/// voted in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VoteTargetReport<H, N> {
+	/// The block the votes were based on, i.e. the last round estimate.
+	pub base: (H, N),
+	/// The best vote target before any voting rules were applied.
+	pub best: (H, N),
+	/// The vote target after applying the voting rules.
+	pub target: (H, N),
+	/// The description of the rule that restricted the vote to `target`, if
+	/// the vote was restricted.
+	pub restricted_by: Option<String>,
+}
+
+struct VotingRulesState<H, N> {
+	rules: Vec<String>,
+	vote_target: Option<VoteTargetReport<H, N>>,
+}
+
+/// Shared state of the voting rules, e.g. for reporting over RPC. Filled by a
+/// composite rule created with `VotingRulesBuilder::report_to`.
+pub struct SharedVotingRulesState<H, N> {
+	inner: Arc<RwLock<VotingRulesState<H, N>>>,
+}
+
+impl<H, N> SharedVotingRulesState<H, N> {
+	/// Create a new empty `SharedVotingRulesState` instance.
+	pub fn empty() -> Self {
+		SharedVotingRulesState {
+			inner: Arc::new(RwLock::new(VotingRulesState {
+				rules: Vec::new(),
+				vote_target: None,
+			})),
+		}
+	}
+
+	/// The descriptions of the active voting rules, in the order they're
+	/// applied.
+	pub fn rules(&self) -> Vec<String> {
+		self.inner.read().rules.clone()
+	}
+
+	/// The vote target as restricted by the voting rules in the last round
+	/// we voted in.
+	pub fn vote_target(&self) -> Option<VoteTargetReport<H, N>> where
+		H: Clone,
+		N: Clone,
+	{
+		self.inner.read().vote_target.clone()
+	}
+
+	fn set_rules(&self, rules: Vec<String>) {
+		self.inner.write().rules = rules;
+	}
+
+	fn set_vote_target(&self, vote_target: VoteTargetReport<H, N>) {
+		self.inner.write().vote_target = Some(vote_target);
+	}
+}
+
+impl<H, N> Clone for SharedVotingRulesState<H, N> {
+	fn clone(&self) -> Self {
+		SharedVotingRulesState { inner: self.inner.clone() }
+	}
+}
+
+struct VotingRules<Block: BlockT, B> {
 	rules: Arc<Vec<Box<dyn VotingRule<Block, B>>>>,
+	state: Option<SharedVotingRulesState<Block::Hash, NumberFor<Block>>>,
 }
 
-impl<B, Block> Clone for VotingRules<B, Block> {
+impl<Block: BlockT, B> Clone for VotingRules<Block, B> {
 	fn clone(&self) -> Self {
 		VotingRules {
 			rules: self.rules.clone(),
+			state: self.state.clone(),
 		}
 	}
 }
@@ -204,35 +624,124 @@ impl<Block, B> VotingRule<Block, B> for VotingRules<Block, B> where
 		best_target: &Block::Header,
 		current_target: &Block::Header,
 	) -> Option<(Block::Hash, NumberFor<Block>)> {
+		let mut restricted_by = None;
 		let restricted_target = self.rules.iter().fold(
 			current_target.clone(),
 			|current_target, rule| {
-				rule.restrict_vote(
+				let restricted = rule.restrict_vote(
 					backend,
 					base,
 					best_target,
 					&current_target,
 				)
 					.and_then(|(hash, _)| backend.header(BlockId::Hash(hash)).ok())
-					.and_then(std::convert::identity)
-					.unwrap_or(current_target)
+					.and_then(std::convert::identity);
+
+				match restricted {
+					Some(header) if header.hash() != current_target.hash() => {
+						restricted_by = Some(rule.description());
+						header
+					},
+					_ => current_target,
+				}
 			},
 		);
 
 		let restricted_hash = restricted_target.hash();
 
+		if let Some(state) = &self.state {
+			state.set_vote_target(VoteTargetReport {
+				base: (base.hash(), *base.number()),
+				best: (best_target.hash(), *best_target.number()),
+				target: (restricted_hash, *restricted_target.number()),
+				restricted_by,
+			});
+		}
+
 		if restricted_hash != current_target.hash() {
 			Some((restricted_hash, *restricted_target.number()))
 		} else {
 			None
 		}
 	}
+
+	fn description(&self) -> String {
+		self.rules.iter()
+			.map(|rule| rule.description())
+			.collect::<Vec<_>>()
+			.join(", ")
+	}
+}
+
+/// A built-in voting rule, selectable from the node configuration.
+///
+/// Parsed from `before-best:<blocks>`, `three-quarters`, `pause-at:<block>`,
+/// `seen-by-peers:<percent>` and `runtime-pause`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VotingRuleConfig {
+	/// `BeforeBestBlockBy` the given number of blocks.
+	BeforeBestBlockBy(u32),
+	/// `ThreeQuartersOfTheUnfinalizedChain`.
+	ThreeQuartersOfTheUnfinalizedChain,
+	/// `PauseAtBlock` with the given block number.
+	PauseAtBlock(u32),
+	/// `SeenByPeers` with the given share of peers.
+	SeenByPeers(Percent),
+	/// `RuntimePause`.
+	RuntimePause,
+}
+
+impl FromStr for VotingRuleConfig {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut parts = s.splitn(2, ':');
+		let name = parts.next().unwrap_or_default();
+		let param = parts.next();
+
+		let number = |param: Option<&str>| -> Result<u32, String> {
+			param
+				.ok_or_else(|| format!("Voting rule `{}` requires a parameter", name))?
+				.parse::<u32>()
+				.map_err(|e| format!("Invalid parameter for voting rule `{}`: {}", name, e))
+		};
+
+		match (name, param) {
+			("before-best", _) => number(param).map(VotingRuleConfig::BeforeBestBlockBy),
+			("three-quarters", None) => Ok(VotingRuleConfig::ThreeQuartersOfTheUnfinalizedChain),
+			("pause-at", _) => number(param).map(VotingRuleConfig::PauseAtBlock),
+			("seen-by-peers", _) => {
+				let percent = number(param)?;
+				if percent > 100 {
+					return Err(format!("Voting rule `{}` requires a percentage, got {}", name, percent));
+				}
+				Ok(VotingRuleConfig::SeenByPeers(Percent::from_percent(percent as u8)))
+			},
+			("runtime-pause", None) => Ok(VotingRuleConfig::RuntimePause),
+			("three-quarters", Some(_)) | ("runtime-pause", Some(_)) =>
+				Err(format!("Voting rule `{}` doesn't take a parameter", name)),
+			_ => Err(format!("Unknown voting rule `{}`", s)),
+		}
+	}
+}
+
+impl fmt::Display for VotingRuleConfig {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			VotingRuleConfig::BeforeBestBlockBy(n) => write!(f, "before-best:{}", n),
+			VotingRuleConfig::ThreeQuartersOfTheUnfinalizedChain => write!(f, "three-quarters"),
+			VotingRuleConfig::PauseAtBlock(n) => write!(f, "pause-at:{}", n),
+			VotingRuleConfig::SeenByPeers(p) => write!(f, "seen-by-peers:{}", p.deconstruct()),
+			VotingRuleConfig::RuntimePause => write!(f, "runtime-pause"),
+		}
+	}
 }
 
 /// A builder of a composite voting rule that applies a set of rules to
 /// progressively restrict the vote.
-pub struct VotingRulesBuilder<Block, B> {
+pub struct VotingRulesBuilder<Block: BlockT, B> {
 	rules: Vec<Box<dyn VotingRule<Block, B>>>,
+	state: Option<SharedVotingRulesState<Block::Hash, NumberFor<Block>>>,
 }
 
 impl<Block, B> Default for VotingRulesBuilder<Block, B> where
@@ -254,9 +763,36 @@ impl<Block, B> VotingRulesBuilder<Block, B> where
 	pub fn new() -> Self {
 		VotingRulesBuilder {
 			rules: Vec::new(),
+			state: None,
 		}
 	}
 
+	/// Return a new voting rule builder with the rules selected in the node
+	/// configuration, or the default rules if none were selected. `peers` is
+	/// used by the `SeenByPeers` rule, `client` by the `RuntimePause` rule.
+	pub fn from_config<P, BE, C>(config: &[VotingRuleConfig], peers: P, client: Arc<C>) -> Self where
+		P: PeerBestBlocks<Block> + Clone + 'static,
+		BE: Backend<Block>,
+		C: StorageProvider<Block, BE> + Send + Sync + 'static,
+	{
+		if config.is_empty() {
+			return Self::default();
+		}
+
+		config.iter().fold(Self::new(), |builder, rule| match rule {
+			VotingRuleConfig::BeforeBestBlockBy(n) =>
+				builder.add(BeforeBestBlockBy((*n).into())),
+			VotingRuleConfig::ThreeQuartersOfTheUnfinalizedChain =>
+				builder.add(ThreeQuartersOfTheUnfinalizedChain),
+			VotingRuleConfig::PauseAtBlock(n) =>
+				builder.add(PauseAtBlock((*n).into())),
+			VotingRuleConfig::SeenByPeers(threshold) =>
+				builder.add(SeenByPeers::new(peers.clone(), *threshold)),
+			VotingRuleConfig::RuntimePause =>
+				builder.add(RuntimePause::new(client.clone())),
+		})
+	}
+
 	/// Add a new voting rule to the builder.
 	pub fn add<R>(mut self, rule: R) -> Self where
 		R: VotingRule<Block, B> + 'static,
@@ -273,11 +809,26 @@ impl<Block, B> VotingRulesBuilder<Block, B> where
 		self
 	}
 
+	/// Report the active rules and the restricted vote targets to the given
+	/// shared state.
+	pub fn report_to(
+		mut self,
+		state: SharedVotingRulesState<Block::Hash, NumberFor<Block>>,
+	) -> Self {
+		self.state = Some(state);
+		self
+	}
+
 	/// Return a new `VotingRule` that applies all of the previously added
 	/// voting rules in-order.
 	pub fn build(self) -> impl VotingRule<Block, B> + Clone {
+		if let Some(state) = &self.state {
+			state.set_rules(self.rules.iter().map(|rule| rule.description()).collect());
+		}
+
 		VotingRules {
 			rules: Arc::new(self.rules),
+			state: self.state,
 		}
 	}
 }
@@ -295,4 +846,8 @@ impl<Block, B> VotingRule<Block, B> for Box<dyn VotingRule<Block, B>> where
 	) -> Option<(Block::Hash, NumberFor<Block>)> {
 		(**self).restrict_vote(backend, base, best_target, current_target)
 	}
+
+	fn description(&self) -> String {
+		(**self).description()
+	}
 }
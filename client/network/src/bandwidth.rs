@@ -0,0 +1,343 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Per-protocol and per-peer bandwidth accounting.
+//!
+//! The transport only knows about the total number of bytes that go through the sockets. The
+//! [`ProtocolBandwidth`] struct is shared between the network behaviours, which report the size
+//! of the messages they send and receive on each protocol.
+//!
+//! It also enforces the upload rate limits of the request-response protocols. Before writing a
+//! response, the behaviour calls [`ProtocolBandwidth::reserve_upload`] and waits for the
+//! returned duration. The configured rate is shared equally between the peers that are being
+//! served at the same time, so that a single peer can't starve the others. Requests that can't
+//! be answered before the requester gives up, or that come from a peer which already has too
+//! many responses waiting, are dropped instead.
+//!
+//! Notifications protocols, such as GRANDPA gossip, and transactions aren't limited in bytes:
+//! they are only accounted for.
+
+use crate::config::RateLimitedProtocol;
+use crate::network_state::BandwidthCounters;
+
+use libp2p::PeerId;
+use parking_lot::Mutex;
+use std::{borrow::Cow, cmp, collections::{HashMap, VecDeque}, time::Duration};
+use wasm_timer::Instant;
+
+/// Name under which the transactions protocol is reported.
+pub const TRANSACTIONS: &str = "transactions";
+
+/// Maximum number of responses of a rate limited protocol that can be waiting to be sent to a
+/// single peer. Further requests of that peer are dropped.
+const MAX_WAITING_UPLOADS_PER_PEER: usize = 8;
+
+/// Number of bytes exchanged per protocol and per peer, and upload rate limits.
+pub struct ProtocolBandwidth {
+	inner: Mutex<Inner>,
+}
+
+struct Inner {
+	/// Total number of bytes exchanged, by protocol.
+	protocols: HashMap<Cow<'static, str>, BandwidthCounters>,
+	/// Number of bytes exchanged with each connected peer, by protocol.
+	peers: HashMap<PeerId, HashMap<Cow<'static, str>, BandwidthCounters>>,
+	/// Upload rate limiters of the protocols that have one.
+	limiters: HashMap<RateLimitedProtocol, UploadLimiter>,
+}
+
+impl ProtocolBandwidth {
+	/// Builds a new `ProtocolBandwidth`, with the given upload rate limits in bytes per second.
+	///
+	/// Limits of zero are ignored.
+	pub fn new(upload_rate_limits: &HashMap<RateLimitedProtocol, u64>) -> Self {
+		let limiters = upload_rate_limits.iter()
+			.filter(|(_, rate)| **rate != 0)
+			.map(|(protocol, rate)| (*protocol, UploadLimiter::new(*rate)))
+			.collect();
+
+		ProtocolBandwidth {
+			inner: Mutex::new(Inner {
+				protocols: HashMap::new(),
+				peers: HashMap::new(),
+				limiters,
+			}),
+		}
+	}
+
+	/// Reports that `bytes` have been received from `peer` on `protocol`.
+	pub fn report_inbound(&self, protocol: impl Into<Cow<'static, str>>, peer: &PeerId, bytes: usize) {
+		self.inner.lock().report(protocol.into(), peer, |counters| counters.inbound += bytes as u64);
+	}
+
+	/// Reports that `bytes` have been sent to `peer` on `protocol`.
+	pub fn report_outbound(&self, protocol: impl Into<Cow<'static, str>>, peer: &PeerId, bytes: usize) {
+		self.inner.lock().report(protocol.into(), peer, |counters| counters.outbound += bytes as u64);
+	}
+
+	/// Reserves `bytes` of upload bandwidth to answer a request of `peer`.
+	///
+	/// Returns how long to wait before sending the answer. Always zero if `protocol` isn't
+	/// rate limited. Returns `None`, without reserving anything, if the answer would have to
+	/// wait longer than `max_wait` or if `peer` already has too many answers waiting. The
+	/// request must then be dropped.
+	pub fn reserve_upload(
+		&self,
+		protocol: RateLimitedProtocol,
+		peer: &PeerId,
+		bytes: usize,
+		max_wait: Duration,
+	) -> Option<Duration> {
+		self.reserve_upload_at(Instant::now(), protocol, peer, bytes, max_wait)
+	}
+
+	fn reserve_upload_at(
+		&self,
+		now: Instant,
+		protocol: RateLimitedProtocol,
+		peer: &PeerId,
+		bytes: usize,
+		max_wait: Duration,
+	) -> Option<Duration> {
+		match self.inner.lock().limiters.get_mut(&protocol) {
+			Some(limiter) => limiter.reserve(now, peer, bytes, max_wait),
+			None => Some(Duration::from_secs(0)),
+		}
+	}
+
+	/// Forgets about the per-peer counters of `peer`. Must be called when the last connection to
+	/// that peer closes.
+	pub fn remove_peer(&self, peer: &PeerId) {
+		self.inner.lock().peers.remove(peer);
+	}
+
+	/// Returns the total number of bytes exchanged, by protocol.
+	pub fn protocols(&self) -> HashMap<String, BandwidthCounters> {
+		self.inner.lock().protocols.iter()
+			.map(|(protocol, counters)| (protocol.to_string(), *counters))
+			.collect()
+	}
+
+	/// Returns the number of bytes exchanged with `peer`, by protocol.
+	pub fn peer(&self, peer: &PeerId) -> HashMap<String, BandwidthCounters> {
+		self.inner.lock().peers.get(peer)
+			.map(|protocols| protocols.iter()
+				.map(|(protocol, counters)| (protocol.to_string(), *counters))
+				.collect())
+			.unwrap_or_default()
+	}
+}
+
+impl Inner {
+	fn report(
+		&mut self,
+		protocol: Cow<'static, str>,
+		peer: &PeerId,
+		update: impl Fn(&mut BandwidthCounters),
+	) {
+		update(self.peers.entry(peer.clone()).or_default().entry(protocol.clone()).or_default());
+		update(self.protocols.entry(protocol).or_default());
+	}
+}
+
+/// Spreads the uploads of a protocol over time so that they don't exceed a certain rate.
+struct UploadLimiter {
+	/// Maximum number of bytes per second.
+	rate: u64,
+	/// Moment when all the bytes reserved so far will have been sent.
+	next_free: Instant,
+	/// Uploads reserved by each peer. Only contains the peers that still have bytes waiting to
+	/// be sent.
+	peers: HashMap<PeerId, PeerUploads>,
+}
+
+/// Uploads reserved by a peer.
+struct PeerUploads {
+	/// Same as `UploadLimiter::next_free`, but for the bytes reserved by this peer.
+	until: Instant,
+	/// Moments when the answers that are still waiting will be sent, in order.
+	waiting: VecDeque<Instant>,
+}
+
+impl UploadLimiter {
+	fn new(rate: u64) -> Self {
+		UploadLimiter {
+			rate,
+			next_free: Instant::now(),
+			peers: HashMap::new(),
+		}
+	}
+
+	/// Reserves `bytes` for `peer` and returns how long to wait before sending them, or `None`
+	/// if they can't be sent within `max_wait` or if `peer` has too many answers waiting.
+	fn reserve(&mut self, now: Instant, peer: &PeerId, bytes: usize, max_wait: Duration) -> Option<Duration> {
+		self.peers.retain(|_, uploads| uploads.until > now);
+		if let Some(uploads) = self.peers.get_mut(peer) {
+			while uploads.waiting.front().map_or(false, |start| *start <= now) {
+				uploads.waiting.pop_front();
+			}
+			if uploads.waiting.len() >= MAX_WAITING_UPLOADS_PER_PEER {
+				return None;
+			}
+		}
+
+		// Every peer with bytes waiting to be sent, including this one, gets an equal share of
+		// the rate.
+		let sharing = self.peers.len() + if self.peers.contains_key(peer) { 0 } else { 1 };
+		let cost = Duration::from_secs_f64(bytes as f64 / self.rate as f64);
+
+		let global_start = cmp::max(now, self.next_free);
+		let peer_start = self.peers.get(peer).map_or(now, |uploads| cmp::max(now, uploads.until));
+		let start = cmp::max(global_start, peer_start);
+		if start - now > max_wait {
+			return None;
+		}
+
+		self.next_free = global_start + cost;
+		let uploads = self.peers.entry(peer.clone())
+			.or_insert_with(|| PeerUploads { until: now, waiting: VecDeque::new() });
+		uploads.until = start + cost * sharing as u32;
+		if start > now {
+			uploads.waiting.push_back(start);
+		}
+		Some(start - now)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const MAX_WAIT: Duration = Duration::from_secs(3600);
+
+	fn limited(rate: u64) -> ProtocolBandwidth {
+		let mut limits = HashMap::new();
+		limits.insert(RateLimitedProtocol::BlockRequests, rate);
+		ProtocolBandwidth::new(&limits)
+	}
+
+	#[test]
+	fn counters_are_reported_by_protocol_and_peer() {
+		let bandwidth = ProtocolBandwidth::new(&HashMap::new());
+		let peer1 = PeerId::random();
+		let peer2 = PeerId::random();
+
+		bandwidth.report_inbound(TRANSACTIONS, &peer1, 10);
+		bandwidth.report_outbound(TRANSACTIONS, &peer2, 20);
+		bandwidth.report_outbound("FRNK".to_owned(), &peer1, 5);
+
+		let protocols = bandwidth.protocols();
+		assert_eq!(protocols[TRANSACTIONS], BandwidthCounters { inbound: 10, outbound: 20 });
+		assert_eq!(protocols["FRNK"], BandwidthCounters { inbound: 0, outbound: 5 });
+
+		let peer = bandwidth.peer(&peer1);
+		assert_eq!(peer[TRANSACTIONS], BandwidthCounters { inbound: 10, outbound: 0 });
+		assert_eq!(peer["FRNK"], BandwidthCounters { inbound: 0, outbound: 5 });
+
+		bandwidth.remove_peer(&peer1);
+		assert!(bandwidth.peer(&peer1).is_empty());
+		assert_eq!(bandwidth.protocols()[TRANSACTIONS].inbound, 10);
+	}
+
+	#[test]
+	fn unlimited_protocols_are_not_delayed() {
+		let bandwidth = limited(1000);
+		let peer = PeerId::random();
+
+		for _ in 0..10 {
+			let wait = bandwidth.reserve_upload(
+				RateLimitedProtocol::LightClientRequests,
+				&peer,
+				1_000_000,
+				Duration::from_secs(0),
+			);
+			assert_eq!(wait, Some(Duration::from_secs(0)));
+		}
+	}
+
+	#[test]
+	fn uploads_are_spread_at_configured_rate() {
+		let bandwidth = limited(1000);
+		let peer = PeerId::random();
+		let now = Instant::now();
+
+		let protocol = RateLimitedProtocol::BlockRequests;
+		assert_eq!(bandwidth.reserve_upload_at(now, protocol, &peer, 500, MAX_WAIT), Some(Duration::from_secs(0)));
+		assert_eq!(bandwidth.reserve_upload_at(now, protocol, &peer, 500, MAX_WAIT), Some(Duration::from_millis(500)));
+		assert_eq!(bandwidth.reserve_upload_at(now, protocol, &peer, 500, MAX_WAIT), Some(Duration::from_secs(1)));
+
+		// Once the reserved bytes are sent, the next upload is immediate.
+		let later = now + Duration::from_secs(2);
+		assert_eq!(bandwidth.reserve_upload_at(later, protocol, &peer, 500, MAX_WAIT), Some(Duration::from_secs(0)));
+	}
+
+	#[test]
+	fn peers_get_a_fair_share() {
+		let bandwidth = limited(1000);
+		let greedy = PeerId::random();
+		let other = PeerId::random();
+		let now = Instant::now();
+
+		let protocol = RateLimitedProtocol::BlockRequests;
+		assert_eq!(bandwidth.reserve_upload_at(now, protocol, &greedy, 1000, MAX_WAIT), Some(Duration::from_secs(0)));
+		assert_eq!(bandwidth.reserve_upload_at(now, protocol, &other, 100, MAX_WAIT), Some(Duration::from_secs(1)));
+
+		// Two peers are now being served, so each one only gets half of the rate.
+		let wait = bandwidth.reserve_upload_at(now, protocol, &greedy, 1000, MAX_WAIT);
+		assert_eq!(wait, Some(Duration::from_millis(1100)));
+		let wait = bandwidth.reserve_upload_at(now, protocol, &greedy, 100, MAX_WAIT);
+		assert_eq!(wait, Some(Duration::from_millis(3100)));
+	}
+
+	#[test]
+	fn uploads_waiting_too_long_are_rejected() {
+		let bandwidth = limited(1000);
+		let peer = PeerId::random();
+		let other = PeerId::random();
+		let now = Instant::now();
+		let max_wait = Duration::from_secs(1);
+
+		let protocol = RateLimitedProtocol::BlockRequests;
+		assert_eq!(bandwidth.reserve_upload_at(now, protocol, &peer, 1000, max_wait), Some(Duration::from_secs(0)));
+		assert_eq!(bandwidth.reserve_upload_at(now, protocol, &peer, 1000, max_wait), Some(Duration::from_secs(1)));
+		assert_eq!(bandwidth.reserve_upload_at(now, protocol, &peer, 1000, max_wait), None);
+
+		// Rejected uploads don't reserve anything.
+		assert_eq!(bandwidth.reserve_upload_at(now, protocol, &other, 10, MAX_WAIT), Some(Duration::from_secs(2)));
+	}
+
+	#[test]
+	fn waiting_uploads_are_bounded_per_peer() {
+		let bandwidth = limited(1000);
+		let greedy = PeerId::random();
+		let other = PeerId::random();
+		let now = Instant::now();
+
+		let protocol = RateLimitedProtocol::BlockRequests;
+		assert_eq!(bandwidth.reserve_upload_at(now, protocol, &greedy, 1, MAX_WAIT), Some(Duration::from_secs(0)));
+		for _ in 0..MAX_WAITING_UPLOADS_PER_PEER {
+			assert!(bandwidth.reserve_upload_at(now, protocol, &greedy, 1, MAX_WAIT).is_some());
+		}
+		assert_eq!(bandwidth.reserve_upload_at(now, protocol, &greedy, 1, MAX_WAIT), None);
+		assert!(bandwidth.reserve_upload_at(now, protocol, &other, 1, MAX_WAIT).is_some());
+
+		// Once the first waiting answer is sent, the peer can queue another one.
+		let later = now + Duration::from_millis(1);
+		assert!(bandwidth.reserve_upload_at(later, protocol, &greedy, 1, MAX_WAIT).is_some());
+	}
+}
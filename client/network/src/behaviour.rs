@@ -264,7 +264,7 @@ impl<B: BlockT, H: ExHashT> Behaviour<B, H> {
 fn reported_roles_to_observed_role(local_role: &Role, remote: &PeerId, roles: Roles) -> ObservedRole {
 	if roles.is_authority() {
 		match local_role {
-			Role::Authority { sentry_nodes }
+			Role::Authority { sentry_nodes, .. }
 				if sentry_nodes.iter().any(|s| s.peer_id == *remote) => ObservedRole::OurSentry,
 			Role::Sentry { validators }
 				if validators.iter().any(|s| s.peer_id == *remote) => ObservedRole::OurGuardedAuthority,
@@ -25,8 +25,9 @@
 use bytes::Bytes;
 use codec::{Encode, Decode};
 use crate::{
+	bandwidth::ProtocolBandwidth,
 	chain::Client,
-	config::ProtocolId,
+	config::{ProtocolId, RateLimitedProtocol},
 	protocol::{message::{self, BlockAttributes}},
 	schema,
 };
@@ -195,6 +196,8 @@ pub struct BlockRequests<B: Block> {
 	outgoing: FuturesUnordered<BoxFuture<'static, (PeerId, Duration)>>,
 	/// Events to return as soon as possible from `poll`.
 	pending_events: VecDeque<NetworkBehaviourAction<OutboundProtocol<B>, Event<B>>>,
+	/// Accounting of the bytes exchanged, and upload rate limit of the responses.
+	bandwidth: Arc<ProtocolBandwidth>,
 }
 
 /// Local tracking of a libp2p connection.
@@ -236,13 +239,14 @@ impl<B> BlockRequests<B>
 where
 	B: Block,
 {
-	pub fn new(cfg: Config, chain: Arc<dyn Client<B>>) -> Self {
+	pub fn new(cfg: Config, chain: Arc<dyn Client<B>>, bandwidth: Arc<ProtocolBandwidth>) -> Self {
 		BlockRequests {
 			config: cfg,
 			chain,
 			peers: HashMap::new(),
 			outgoing: FuturesUnordered::new(),
 			pending_events: VecDeque::new(),
+			bandwidth,
 		}
 	}
 
@@ -304,6 +308,7 @@ where
 		});
 
 		log::trace!(target: "sync", "Enqueueing block request to {:?}: {:?}", target, protobuf_rq);
+		self.bandwidth.report_outbound(RateLimitedProtocol::BlockRequests.name(), target, buf.len());
 		self.pending_events.push_back(NetworkBehaviourAction::NotifyHandler {
 			peer_id: target.clone(),
 			handler: NotifyHandler::One(connection.id),
@@ -527,6 +532,8 @@ where
 	) {
 		match node_event {
 			NodeEvent::Request(request, mut stream, handling_start) => {
+				let protocol = RateLimitedProtocol::BlockRequests;
+				self.bandwidth.report_inbound(protocol.name(), &peer, request.encoded_len());
 				match self.on_block_request(&peer, &request) {
 					Ok(res) => {
 						log::trace!(
@@ -542,16 +549,31 @@ where
 								peer, e
 							)
 						} else {
-							self.outgoing.push(async move {
-								if let Err(e) = write_one(&mut stream, data).await {
-									log::debug!(
-										target: "sync",
-										"Error writing block response: {}",
-										e
-									);
-								}
-								(peer, handling_start.elapsed())
-							}.boxed());
+							let bandwidth = self.bandwidth.clone();
+							let max_wait = self.config.request_timeout;
+							if let Some(wait) = bandwidth.reserve_upload(protocol, &peer, data.len(), max_wait) {
+								self.outgoing.push(async move {
+									if wait > Duration::from_secs(0) {
+										Delay::new(wait).await;
+									}
+									let len = data.len();
+									match write_one(&mut stream, data).await {
+										Ok(()) => bandwidth.report_outbound(protocol.name(), &peer, len),
+										Err(e) => log::debug!(
+											target: "sync",
+											"Error writing block response: {}",
+											e
+										),
+									}
+									(peer, handling_start.elapsed())
+								}.boxed());
+							} else {
+								log::debug!(
+									target: "sync",
+									"Dropping block request from peer {} above the upload rate limit",
+									peer
+								)
+							}
 						}
 					}
 					Err(e) => log::debug!(
@@ -566,6 +588,11 @@ where
 					"Received block response from peer {} with {} blocks",
 					peer, response.blocks.len()
 				);
+				self.bandwidth.report_inbound(
+					RateLimitedProtocol::BlockRequests.name(),
+					&peer,
+					response.encoded_len(),
+				);
 				let request_duration = if let Some(connections) = self.peers.get_mut(&peer) {
 					if let Some(connection) = connections.iter_mut().find(|c| c.id == connection_id) {
 						if let Some(ongoing_request) = &mut connection.ongoing_request {
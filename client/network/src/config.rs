@@ -29,6 +29,7 @@ pub use libp2p::{identity, core::PublicKey, wasm_ext::ExtTransport, build_multia
 // the future.
 #[doc(hidden)]
 pub use crate::protocol::ProtocolConfig;
+pub use sc_peerset::DEFAULT_BANNED_THRESHOLD;
 
 use crate::ExHashT;
 
@@ -123,6 +124,10 @@ pub enum Role {
 	Authority {
 		/// List of public addresses and identities of our sentry nodes.
 		sentry_nodes: Vec<MultiaddrWithPeerId>,
+		/// If true, only connect to the sentry nodes (and explicitly reserved nodes). Bootnodes,
+		/// the DHT and mDNS are not used to discover other peers, and connections with any other
+		/// node are closed as soon as the remote has identified itself.
+		sentries_only: bool,
 	}
 }
 
@@ -425,6 +430,15 @@ pub struct NetworkConfiguration {
 	pub max_parallel_downloads: u32,
 	/// Should we insert non-global addresses into the DHT?
 	pub allow_non_globals_in_dht: bool,
+	/// Reputation under which nodes are disconnected and refused.
+	pub reputation_ban_threshold: i32,
+	/// Maximum number of bytes per second to upload when answering requests, by protocol.
+	///
+	/// Each peer gets a fair share of this rate. Requests whose answer would be sent after the
+	/// requester gives up are dropped. Protocols not in the map aren't limited.
+	pub upload_rate_limits: HashMap<RateLimitedProtocol, u64>,
+	/// Which peers the transactions of the local pool are gossiped to.
+	pub transaction_propagation: TransactionPropagation,
 }
 
 impl NetworkConfiguration {
@@ -456,6 +470,9 @@ impl NetworkConfiguration {
 			},
 			max_parallel_downloads: 5,
 			allow_non_globals_in_dht: false,
+			reputation_ban_threshold: DEFAULT_BANNED_THRESHOLD,
+			upload_rate_limits: HashMap::new(),
+			transaction_propagation: TransactionPropagation::All,
 		}
 	}
 }
@@ -551,6 +568,67 @@ impl NonReservedPeerMode {
 	}
 }
 
+/// Policy regarding the gossiping of the transactions of the local pool.
+///
+/// Transactions submitted with propagation disabled are never gossiped, whatever the policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionPropagation {
+	/// Gossip transactions to all the full nodes we're connected to. This is the default.
+	All,
+	/// Only gossip transactions to reserved peers.
+	ReservedOnly,
+	/// Gossip transactions to all the full nodes we're connected to, but send on average at most
+	/// the given number of transactions per second to each of them.
+	///
+	/// Transactions are propagated in rounds, every few seconds, so each round sends up to the
+	/// number of transactions allowed since the previous one. The others are sent in later rounds.
+	RateLimited(u32),
+	/// Never gossip transactions, keeping the pool private.
+	Disabled,
+}
+
+impl Default for TransactionPropagation {
+	fn default() -> Self {
+		TransactionPropagation::All
+	}
+}
+
+/// Request-response protocol whose upload rate can be limited.
+///
+/// Only the answers to requests can be delayed. Notifications protocols, such as GRANDPA
+/// gossip, are sent as soon as they are produced and can't be limited. The number of
+/// transactions sent to each peer can be limited with [`TransactionPropagation::RateLimited`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RateLimitedProtocol {
+	/// Answers to block requests.
+	BlockRequests,
+	/// Answers to finality proof requests.
+	FinalityProofRequests,
+	/// Answers to light client requests.
+	LightClientRequests,
+}
+
+impl RateLimitedProtocol {
+	/// Attempt to parse the protocol from its name.
+	pub fn parse(s: &str) -> Option<Self> {
+		match s {
+			"block-requests" => Some(RateLimitedProtocol::BlockRequests),
+			"finality-proof-requests" => Some(RateLimitedProtocol::FinalityProofRequests),
+			"light-client-requests" => Some(RateLimitedProtocol::LightClientRequests),
+			_ => None,
+		}
+	}
+
+	/// Name of the protocol, as used in the bandwidth reports.
+	pub fn name(&self) -> &'static str {
+		match self {
+			RateLimitedProtocol::BlockRequests => "block-requests",
+			RateLimitedProtocol::FinalityProofRequests => "finality-proof-requests",
+			RateLimitedProtocol::LightClientRequests => "light-client-requests",
+		}
+	}
+}
+
 /// The configuration of a node's secret key, describing the type of key
 /// and how it is obtained. A node's identity keypair is the result of
 /// the evaluation of the node key configuration.
@@ -78,6 +78,7 @@ pub struct DiscoveryConfig {
 	allow_non_globals_in_dht: bool,
 	discovery_only_if_under_num: u64,
 	enable_mdns: bool,
+	user_defined_only: bool,
 	kademlias: HashMap<ProtocolId, Kademlia<MemoryStore>>
 }
 
@@ -91,6 +92,7 @@ impl DiscoveryConfig {
 			allow_non_globals_in_dht: false,
 			discovery_only_if_under_num: std::u64::MAX,
 			enable_mdns: false,
+			user_defined_only: false,
 			kademlias: HashMap::new()
 		}
 	}
@@ -136,6 +138,15 @@ impl DiscoveryConfig {
 		self
 	}
 
+	/// Should discovery be restricted to the user-defined nodes?
+	///
+	/// If true, mDNS is disabled, no random Kademlia queries are started and only user-defined
+	/// addresses are ever dialed. Kademlia is still usable to store and fetch values.
+	pub fn restrict_to_user_defined(&mut self, value: bool) -> &mut Self {
+		self.user_defined_only = value;
+		self
+	}
+
 	/// Add discovery via Kademlia for the given protocol.
 	pub fn add_protocol(&mut self, id: ProtocolId) -> &mut Self {
 		let name = protocol_name_from_protocol_id(&id);
@@ -179,7 +190,7 @@ impl DiscoveryConfig {
 			allow_private_ipv4: self.allow_private_ipv4,
 			discovery_only_if_under_num: self.discovery_only_if_under_num,
 			#[cfg(not(target_os = "unknown"))]
-			mdns: if self.enable_mdns {
+			mdns: if self.enable_mdns && !self.user_defined_only {
 				match Mdns::new() {
 					Ok(mdns) => Some(mdns).into(),
 					Err(err) => {
@@ -190,7 +201,8 @@ impl DiscoveryConfig {
 			} else {
 				None.into()
 			},
-			allow_non_globals_in_dht: self.allow_non_globals_in_dht
+			allow_non_globals_in_dht: self.allow_non_globals_in_dht,
+			user_defined_only: self.user_defined_only,
 		}
 	}
 }
@@ -221,7 +233,10 @@ pub struct DiscoveryBehaviour {
 	/// Number of active connections over which we interrupt the discovery process.
 	discovery_only_if_under_num: u64,
 	/// Should non-global addresses be added to the DHT?
-	allow_non_globals_in_dht: bool
+	allow_non_globals_in_dht: bool,
+	/// If true, only the addresses in `user_defined` are reported and no random Kademlia
+	/// queries are performed.
+	user_defined_only: bool,
 }
 
 impl DiscoveryBehaviour {
@@ -416,7 +431,7 @@ impl NetworkBehaviour for DiscoveryBehaviour {
 			.filter_map(|(p, a)| if p == peer_id { Some(a.clone()) } else { None })
 			.collect::<Vec<_>>();
 
-		{
+		if !self.user_defined_only {
 			let mut list_to_filter = Vec::new();
 			for k in self.kademlias.values_mut() {
 				list_to_filter.extend(k.addresses_of_peer(peer_id))
@@ -552,7 +567,9 @@ impl NetworkBehaviour for DiscoveryBehaviour {
 
 		// Poll the stream that fires when we need to start a random Kademlia query.
 		while let Poll::Ready(_) = self.next_kad_random_query.poll_unpin(cx) {
-			let actually_started = if self.num_connections < self.discovery_only_if_under_num {
+			let actually_started = if self.user_defined_only {
+				false
+			} else if self.num_connections < self.discovery_only_if_under_num {
 				let random_peer_id = PeerId::random();
 				debug!(target: "sub-libp2p",
 					"Libp2p <= Starting random Kademlia request for {:?}",
@@ -25,12 +25,14 @@
 use bytes::Bytes;
 use codec::{Encode, Decode};
 use crate::{
+	bandwidth::ProtocolBandwidth,
 	chain::FinalityProofProvider,
-	config::ProtocolId,
+	config::{ProtocolId, RateLimitedProtocol},
 	protocol::message,
 	schema,
 };
 use futures::{future::BoxFuture, prelude::*, stream::FuturesUnordered};
+use futures_timer::Delay;
 use libp2p::{
 	core::{
 		ConnectedPoint,
@@ -146,6 +148,8 @@ pub struct FinalityProofRequests<B: Block> {
 	outgoing: FuturesUnordered<BoxFuture<'static, ()>>,
 	/// Events to return as soon as possible from `poll`.
 	pending_events: VecDeque<NetworkBehaviourAction<OutboundProtocol<B>, Event<B>>>,
+	/// Accounting of the bytes exchanged, and upload rate limit of the responses.
+	bandwidth: Arc<ProtocolBandwidth>,
 }
 
 impl<B> FinalityProofRequests<B>
@@ -156,12 +160,17 @@ where
 	///
 	/// If the proof provider is `None`, then the behaviour will not support the finality proof
 	/// requests protocol.
-	pub fn new(cfg: Config, finality_proof_provider: Option<Arc<dyn FinalityProofProvider<B>>>) -> Self {
+	pub fn new(
+		cfg: Config,
+		finality_proof_provider: Option<Arc<dyn FinalityProofProvider<B>>>,
+		bandwidth: Arc<ProtocolBandwidth>,
+	) -> Self {
 		FinalityProofRequests {
 			config: cfg,
 			finality_proof_provider,
 			outgoing: FuturesUnordered::new(),
 			pending_events: VecDeque::new(),
+			bandwidth,
 		}
 	}
 
@@ -182,6 +191,7 @@ where
 		}
 
 		log::trace!("enqueueing finality proof request to {:?}: {:?}", target, protobuf_rq);
+		self.bandwidth.report_outbound(RateLimitedProtocol::FinalityProofRequests.name(), target, buf.len());
 		self.pending_events.push_back(NetworkBehaviourAction::NotifyHandler {
 			peer_id: target.clone(),
 			handler: NotifyHandler::Any,
@@ -256,6 +266,8 @@ where
 	) {
 		match event {
 			NodeEvent::Request(request, mut stream) => {
+				let protocol = RateLimitedProtocol::FinalityProofRequests;
+				self.bandwidth.report_inbound(protocol.name(), &peer, request.encoded_len());
 				match self.on_finality_request(&peer, &request) {
 					Ok(res) => {
 						log::trace!("enqueueing finality response for peer {}", peer);
@@ -263,18 +275,35 @@ where
 						if let Err(e) = res.encode(&mut data) {
 							log::debug!("error encoding finality response for peer {}: {}", peer, e)
 						} else {
-							let future = async move {
-								if let Err(e) = write_one(&mut stream, data).await {
-									log::debug!("error writing finality response: {}", e)
-								}
-							};
-							self.outgoing.push(future.boxed())
+							let bandwidth = self.bandwidth.clone();
+							// Requesters give up after the default outbound substream timeout.
+							let max_wait = OneShotHandlerConfig::default().outbound_substream_timeout;
+							if let Some(wait) = bandwidth.reserve_upload(protocol, &peer, data.len(), max_wait) {
+								let future = async move {
+									if wait > Duration::from_secs(0) {
+										Delay::new(wait).await;
+									}
+									let len = data.len();
+									match write_one(&mut stream, data).await {
+										Ok(()) => bandwidth.report_outbound(protocol.name(), &peer, len),
+										Err(e) => log::debug!("error writing finality response: {}", e),
+									}
+								};
+								self.outgoing.push(future.boxed())
+							} else {
+								log::debug!("dropping finality request from peer {} above the upload rate limit", peer)
+							}
 						}
 					}
 					Err(e) => log::debug!("error handling finality request from peer {}: {}", peer, e)
 				}
 			}
 			NodeEvent::Response(response, block_hash) => {
+				self.bandwidth.report_inbound(
+					RateLimitedProtocol::FinalityProofRequests.name(),
+					&peer,
+					response.encoded_len(),
+				);
 				let ev = Event::Response {
 					peer,
 					block_hash,
@@ -242,6 +242,7 @@
 //! More precise usage details are still being worked on and will likely change in the future.
 //!
 
+mod bandwidth;
 mod behaviour;
 mod block_requests;
 mod chain;
@@ -268,7 +269,7 @@ pub use libp2p::{Multiaddr, PeerId};
 #[doc(inline)]
 pub use libp2p::multiaddr;
 
-pub use sc_peerset::ReputationChange;
+pub use sc_peerset::{BanTarget, PeerReputation, ReputationChange};
 use sp_runtime::traits::{Block as BlockT, NumberFor};
 
 /// The maximum allowed number of established connections per peer.
@@ -27,13 +27,15 @@
 use bytes::Bytes;
 use codec::{self, Encode, Decode};
 use crate::{
+	bandwidth::ProtocolBandwidth,
 	block_requests::build_protobuf_block_request,
 	chain::Client,
-	config::ProtocolId,
+	config::{ProtocolId, RateLimitedProtocol},
 	protocol::message::{BlockAttributes, Direction, FromBlock},
 	schema,
 };
 use futures::{channel::oneshot, future::BoxFuture, prelude::*, stream::FuturesUnordered};
+use futures_timer::Delay;
 use libp2p::{
 	core::{
 		ConnectedPoint,
@@ -303,6 +305,8 @@ pub struct LightClientHandler<B: Block> {
 	next_request_id: RequestId,
 	/// Handle to use for reporting misbehaviour of peers.
 	peerset: sc_peerset::PeersetHandle,
+	/// Accounting of the bytes exchanged, and upload rate limit of the responses.
+	bandwidth: Arc<ProtocolBandwidth>,
 }
 
 impl<B> LightClientHandler<B>
@@ -315,6 +319,7 @@ where
 		chain: Arc<dyn Client<B>>,
 		checker: Arc<dyn light::FetchChecker<B>>,
 		peerset: sc_peerset::PeersetHandle,
+		bandwidth: Arc<ProtocolBandwidth>,
 	) -> Self {
 		LightClientHandler {
 			config: cfg,
@@ -326,6 +331,7 @@ where
 			outstanding: IntMap::default(),
 			next_request_id: 1,
 			peerset,
+			bandwidth,
 		}
 	}
 
@@ -822,6 +828,8 @@ where
 			// An incoming request from remote has been received.
 			Event::Request(request, mut stream) => {
 				log::trace!("incoming request from {}", peer);
+				let protocol = RateLimitedProtocol::LightClientRequests;
+				self.bandwidth.report_inbound(protocol.name(), &peer, request.encoded_len());
 				let result = match &request.request {
 					Some(schema::v1::light::request::Request::RemoteCallRequest(r)) =>
 						self.on_remote_call_request(&peer, r),
@@ -845,12 +853,23 @@ where
 						if let Err(e) = response.encode(&mut data) {
 							log::debug!("error encoding response for peer {}: {}", peer, e)
 						} else {
-							let future = async move {
-								if let Err(e) = write_one(&mut stream, data).await {
-									log::debug!("error writing response: {}", e)
-								}
-							};
-							self.responses.push(future.boxed())
+							let bandwidth = self.bandwidth.clone();
+							let max_wait = self.config.request_timeout;
+							if let Some(wait) = bandwidth.reserve_upload(protocol, &peer, data.len(), max_wait) {
+								let future = async move {
+									if wait > Duration::from_secs(0) {
+										Delay::new(wait).await;
+									}
+									let len = data.len();
+									match write_one(&mut stream, data).await {
+										Ok(()) => bandwidth.report_outbound(protocol.name(), &peer, len),
+										Err(e) => log::debug!("error writing response: {}", e),
+									}
+								};
+								self.responses.push(future.boxed())
+							} else {
+								log::debug!("dropping request from peer {} above the upload rate limit", peer)
+							}
 						}
 					}
 					Err(Error::BadRequest(_)) => {
@@ -862,6 +881,11 @@ where
 			}
 			// A response to one of our own requests has been received.
 			Event::Response(id, response) => {
+				let (protocol, len) = match &response {
+					Response::Light(r) => (RateLimitedProtocol::LightClientRequests, r.encoded_len()),
+					Response::Block(r) => (RateLimitedProtocol::BlockRequests, r.encoded_len()),
+				};
+				self.bandwidth.report_inbound(protocol.name(), &peer, len);
 				if let Some(request) = self.outstanding.remove(&id) {
 					// We first just check if the response originates from the expected peer
 					// and connection.
@@ -995,6 +1019,11 @@ where
 					};
 
 					log::trace!("sending request {} to peer {}", request_id, peer_id);
+					let protocol = match event.expected {
+						ExpectedResponseTy::Light => RateLimitedProtocol::LightClientRequests,
+						ExpectedResponseTy::Block => RateLimitedProtocol::BlockRequests,
+					};
+					self.bandwidth.report_outbound(protocol.name(), &peer_id, event.request.len());
 
 					return Poll::Ready(NetworkBehaviourAction::NotifyHandler {
 						peer_id,
@@ -1303,6 +1332,7 @@ mod tests {
 	use assert_matches::assert_matches;
 	use codec::Encode;
 	use crate::{
+		bandwidth::ProtocolBandwidth,
 		chain::Client,
 		config::ProtocolId,
 		schema,
@@ -1359,7 +1389,8 @@ mod tests {
 			.map(|(peer, muxer), _| (peer, StreamMuxerBox::new(muxer)))
 			.map_err(|e| io::Error::new(io::ErrorKind::Other, e))
 			.boxed();
-		Swarm::new(transport, LightClientHandler::new(cf, client, checker, ps), local_peer)
+		let bandwidth = Arc::new(ProtocolBandwidth::new(&HashMap::new()));
+		Swarm::new(transport, LightClientHandler::new(cf, client, checker, ps, bandwidth), local_peer)
 	}
 
 	struct DummyFetchChecker<B> {
@@ -1491,6 +1522,8 @@ mod tests {
 			bootnodes: Vec::new(),
 			reserved_only: false,
 			priority_groups: Vec::new(),
+			banned_threshold: sc_peerset::DEFAULT_BANNED_THRESHOLD,
+			ban_list_path: None,
 		};
 		sc_peerset::Peerset::from_config(cfg)
 	}
@@ -1503,7 +1536,8 @@ mod tests {
 	{
 		let client = Arc::new(substrate_test_runtime_client::new());
 		let checker = Arc::new(DummyFetchChecker { ok, _mark: std::marker::PhantomData });
-		LightClientHandler::new(cf, client, checker, ps)
+		let bandwidth = Arc::new(ProtocolBandwidth::new(&HashMap::new()));
+		LightClientHandler::new(cf, client, checker, ps, bandwidth)
 	}
 
 	fn empty_dialer() -> ConnectedPoint {
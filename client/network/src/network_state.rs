@@ -47,6 +47,8 @@ pub struct NetworkState {
 	pub average_download_per_sec: u64,
 	/// Uploaded bytes per second averaged over the past few seconds.
 	pub average_upload_per_sec: u64,
+	/// Total number of bytes exchanged since startup, by protocol.
+	pub protocols_bandwidth: HashMap<String, BandwidthCounters>,
 	/// State of the peerset manager.
 	pub peerset: serde_json::Value,
 }
@@ -69,6 +71,8 @@ pub struct Peer {
 	pub open: bool,
 	/// List of addresses known for this node.
 	pub known_addresses: HashSet<Multiaddr>,
+	/// Number of bytes exchanged with this node since it connected, by protocol.
+	pub bandwidth: HashMap<String, BandwidthCounters>,
 }
 
 /// Part of the `NetworkState` struct. Unstable.
@@ -83,6 +87,16 @@ pub struct NotConnectedPeer {
 	pub latest_ping_time: Option<Duration>,
 }
 
+/// Part of the `NetworkState` struct. Unstable.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BandwidthCounters {
+	/// Number of bytes received.
+	pub inbound: u64,
+	/// Number of bytes sent.
+	pub outbound: u64,
+}
+
 /// Part of the `NetworkState` struct. Unstable.
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
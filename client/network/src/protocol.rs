@@ -18,8 +18,12 @@
 
 use crate::{
 	ExHashT,
+	bandwidth::{self, ProtocolBandwidth},
 	chain::{Client, FinalityProofProvider},
-	config::{BoxFinalityProofRequestBuilder, ProtocolId, TransactionPool, TransactionImportFuture, TransactionImport},
+	config::{
+		BoxFinalityProofRequestBuilder, ProtocolId, TransactionPool, TransactionImportFuture,
+		TransactionImport, TransactionPropagation,
+	},
 	error,
 	utils::interval
 };
@@ -142,6 +146,8 @@ struct Metrics {
 	finality_proofs: GaugeVec<U64>,
 	justifications: GaugeVec<U64>,
 	propagated_transactions: Counter<U64>,
+	rate_limited_transactions: Counter<U64>,
+	received_transactions: Counter<U64>,
 	legacy_requests_received: Counter<U64>,
 }
 
@@ -188,6 +194,14 @@ impl Metrics {
 				"sync_propagated_transactions",
 				"Number of transactions propagated to at least one peer",
 			)?, r)?,
+			rate_limited_transactions: register(Counter::new(
+				"sync_rate_limited_transactions",
+				"Number of transactions whose sending to a peer was delayed by the rate limit",
+			)?, r)?,
+			received_transactions: register(Counter::new(
+				"sync_received_transactions",
+				"Number of transactions received from peers",
+			)?, r)?,
 			legacy_requests_received: register(Counter::new(
 				"sync_legacy_requests_received",
 				"Number of block/finality/light-client requests received on the legacy substream",
@@ -258,6 +272,8 @@ pub struct Protocol<B: BlockT, H: ExHashT> {
 	metrics: Option<Metrics>,
 	/// The `PeerId`'s of all boot nodes.
 	boot_node_ids: Arc<HashSet<PeerId>>,
+	/// Number of bytes exchanged per protocol and per peer.
+	bandwidth: Arc<ProtocolBandwidth>,
 }
 
 #[derive(Default)]
@@ -277,12 +293,63 @@ struct Peer<B: BlockT, H: ExHashT> {
 	obsolete_requests: HashMap<message::RequestId, Instant>,
 	/// Holds a set of transactions known to this peer.
 	known_transactions: LruHashSet<H>,
+	/// Number of transactions that can still be sent to this peer when transaction propagation
+	/// is rate limited.
+	transaction_allowance: TransactionAllowance,
 	/// Holds a set of blocks known to this peer.
 	known_blocks: LruHashSet<B::Hash>,
 	/// Request counter,
 	next_request_id: message::RequestId,
 }
 
+/// Number of transactions that can be sent to a peer, refilled at a constant rate.
+#[derive(Debug, Clone)]
+struct TransactionAllowance {
+	/// Last time the allowance was refilled.
+	refilled_at: Instant,
+	/// Number of transactions that can be sent right now.
+	remaining: u32,
+}
+
+impl TransactionAllowance {
+	/// Creates an allowance that starts full.
+	fn new(now: Instant) -> Self {
+		TransactionAllowance { refilled_at: now, remaining: u32::max_value() }
+	}
+
+	/// Refills the allowance at `per_sec` transactions per second and returns the number of
+	/// transactions that can be sent right now.
+	///
+	/// Transactions are propagated every `PROPAGATE_TIMEOUT`, so the allowance is capped to what
+	/// accumulates over one such interval: on average, at most `per_sec` transactions per second
+	/// are sent, whatever the interval.
+	fn refill(&mut self, per_sec: u32, now: Instant) -> u32 {
+		let max = ((per_sec as u128 * PROPAGATE_TIMEOUT.as_millis() + 999) / 1000)
+			.min(u32::max_value() as u128);
+		let elapsed = now.duration_since(self.refilled_at).as_millis();
+		let refill = elapsed.saturating_mul(per_sec as u128) / 1000;
+		let remaining = self.remaining as u128 + refill;
+
+		if remaining >= max {
+			self.refilled_at = now;
+			self.remaining = max as u32;
+		} else if refill > 0 {
+			// Only account for the time that produced whole transactions, so that the rate
+			// isn't rounded down.
+			let used = time::Duration::from_millis((refill * 1000 / per_sec as u128) as u64);
+			self.refilled_at = self.refilled_at + used;
+			self.remaining = remaining as u32;
+		}
+
+		self.remaining
+	}
+
+	/// Takes `count` transactions out of the allowance.
+	fn consume(&mut self, count: u32) {
+		self.remaining = self.remaining.saturating_sub(count);
+	}
+}
+
 /// Info about a peer's known state.
 #[derive(Clone, Debug)]
 pub struct PeerInfo<B: BlockT> {
@@ -311,6 +378,8 @@ pub struct ProtocolConfig {
 	pub roles: Roles,
 	/// Maximum number of peers to ask the same blocks in parallel.
 	pub max_parallel_downloads: u32,
+	/// Which peers the transactions of the local pool are gossiped to.
+	pub transaction_propagation: TransactionPropagation,
 }
 
 impl Default for ProtocolConfig {
@@ -318,6 +387,7 @@ impl Default for ProtocolConfig {
 		ProtocolConfig {
 			roles: Roles::FULL,
 			max_parallel_downloads: 5,
+			transaction_propagation: TransactionPropagation::All,
 		}
 	}
 }
@@ -388,6 +458,7 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 		block_announce_validator: Box<dyn BlockAnnounceValidator<B> + Send>,
 		metrics_registry: Option<&Registry>,
 		boot_node_ids: Arc<HashSet<PeerId>>,
+		bandwidth: Arc<ProtocolBandwidth>,
 	) -> error::Result<(Protocol<B, H>, sc_peerset::PeersetHandle)> {
 		let info = chain.info();
 		let sync = ChainSync::new(
@@ -470,6 +541,7 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 				None
 			},
 			boot_node_ids,
+			bandwidth,
 		};
 
 		Ok((protocol, peerset_handle))
@@ -510,6 +582,11 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 		self.behaviour.peerset_debug_info()
 	}
 
+	/// Returns the reputation of all the nodes known to the peerset manager.
+	pub fn peers_reputation(&mut self) -> Vec<sc_peerset::PeerReputation> {
+		self.behaviour.peers_reputation()
+	}
+
 	/// Returns the number of peers we're connected to.
 	pub fn num_connected_peers(&self) -> usize {
 		self.context_data.peers.values().count()
@@ -617,8 +694,10 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 				self.update_peer_info(&who);
 				return outcome;
 			},
-			GenericMessage::Transactions(m) =>
-				self.on_transactions(who, m),
+			GenericMessage::Transactions(m) => {
+				self.bandwidth.report_inbound(bandwidth::TRANSACTIONS, &who, data.len());
+				self.on_transactions(who, m)
+			},
 			GenericMessage::RemoteCallRequest(request) => self.on_remote_call_request(who, request),
 			GenericMessage::RemoteCallResponse(_) =>
 				warn!(target: "sub-libp2p", "Received unexpected RemoteCallResponse"),
@@ -1028,6 +1107,7 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 				block_request: None,
 				known_transactions: LruHashSet::new(NonZeroUsize::new(MAX_KNOWN_TRANSACTIONS)
 					.expect("Constant is nonzero")),
+				transaction_allowance: TransactionAllowance::new(Instant::now()),
 				known_blocks: LruHashSet::new(NonZeroUsize::new(MAX_KNOWN_BLOCKS)
 					.expect("Constant is nonzero")),
 				next_request_id: 0,
@@ -1118,6 +1198,9 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 		}
 
 		trace!(target: "sync", "Received {} transactions from {}", transactions.len(), who);
+		if let Some(ref metrics) = self.metrics {
+			metrics.received_transactions.inc_by(transactions.len() as _)
+		}
 		if let Some(ref mut peer) = self.context_data.peers.get_mut(&who) {
 			for t in transactions {
 				if self.pending_transactions.len() > MAX_PENDING_TRANSACTIONS {
@@ -1181,6 +1264,15 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 	) -> HashMap<H, Vec<String>> {
 		let mut propagated_to = HashMap::<_, Vec<_>>::new();
 		let mut propagated_transactions = 0;
+		let mut rate_limited_transactions = 0;
+
+		let reserved_peers = match self.config.transaction_propagation {
+			TransactionPropagation::Disabled => return propagated_to,
+			TransactionPropagation::ReservedOnly =>
+				Some(self.behaviour.reserved_peers().cloned().collect::<HashSet<_>>()),
+			TransactionPropagation::All | TransactionPropagation::RateLimited(_) => None,
+		};
+		let now = Instant::now();
 
 		for (who, peer) in self.context_data.peers.iter_mut() {
 			// never send transactions to the light node
@@ -1188,12 +1280,34 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 				continue;
 			}
 
-			let (hashes, to_send): (Vec<_>, Vec<_>) = transactions
+			if reserved_peers.as_ref().map_or(false, |reserved| !reserved.contains(who)) {
+				continue;
+			}
+
+			let budget = match self.config.transaction_propagation {
+				TransactionPropagation::RateLimited(per_sec) =>
+					peer.transaction_allowance.refill(per_sec, now) as usize,
+				_ => usize::max_value(),
+			};
+
+			let unknown = transactions
 				.iter()
-				.filter(|&(ref hash, _)| peer.known_transactions.insert(hash.clone()))
+				.filter(|&(ref hash, _)| !peer.known_transactions.contains(hash))
+				.collect::<Vec<_>>();
+			// Transactions above the budget aren't marked as known, so that they are sent the
+			// next time transactions are propagated.
+			rate_limited_transactions += unknown.len().saturating_sub(budget);
+
+			let (hashes, to_send): (Vec<_>, Vec<_>) = unknown
+				.into_iter()
+				.take(budget)
 				.cloned()
 				.unzip();
 
+			for hash in &hashes {
+				peer.known_transactions.insert(hash.clone());
+			}
+			peer.transaction_allowance.consume(hashes.len() as u32);
 			propagated_transactions += hashes.len();
 
 			if !to_send.is_empty() {
@@ -1205,6 +1319,7 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 				}
 				trace!(target: "sync", "Sending {} transactions to {}", to_send.len(), who);
 				let encoded = to_send.encode();
+				self.bandwidth.report_outbound(bandwidth::TRANSACTIONS, who, encoded.len());
 				send_message::<B> (
 					&mut self.behaviour,
 					&mut self.context_data.stats,
@@ -1216,7 +1331,8 @@ impl<B: BlockT, H: ExHashT> Protocol<B, H> {
 		}
 
 		if let Some(ref metrics) = self.metrics {
-			metrics.propagated_transactions.inc_by(propagated_transactions as _)
+			metrics.propagated_transactions.inc_by(propagated_transactions as _);
+			metrics.rate_limited_transactions.inc_by(rate_limited_transactions as _);
 		}
 
 		propagated_to
@@ -2072,6 +2188,7 @@ impl<B: BlockT, H: ExHashT> NetworkBehaviour for Protocol<B, H> {
 						}
 					}
 					Some(Fallback::Transactions) => {
+						self.bandwidth.report_inbound(bandwidth::TRANSACTIONS, &peer_id, message.len());
 						if let Ok(m) = message::Transactions::decode(&mut message.as_ref()) {
 							self.on_transactions(peer_id, m);
 						} else {
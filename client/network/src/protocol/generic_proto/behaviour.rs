@@ -24,6 +24,7 @@ use bytes::BytesMut;
 use fnv::FnvHashMap;
 use futures::prelude::*;
 use libp2p::core::{ConnectedPoint, Multiaddr, PeerId, connection::ConnectionId};
+use libp2p::multiaddr::Protocol;
 use libp2p::swarm::{
 	DialPeerCondition,
 	NetworkBehaviour,
@@ -606,6 +607,16 @@ impl GenericProto {
 		self.peerset.debug_info()
 	}
 
+	/// Returns the reserved peers of the peerset manager.
+	pub fn reserved_peers(&self) -> impl Iterator<Item = &PeerId> {
+		self.peerset.reserved_peers()
+	}
+
+	/// Returns the reputation of all the nodes known to the peerset manager.
+	pub fn peers_reputation(&mut self) -> Vec<sc_peerset::PeerReputation> {
+		self.peerset.peers_reputation()
+	}
+
 	/// Function that is called when the peerset wants us to connect to a peer.
 	fn peerset_report_connect(&mut self, peer_id: PeerId) {
 		let mut occ_entry = match self.peers.entry(peer_id) {
@@ -892,6 +903,18 @@ impl NetworkBehaviour for GenericProto {
 	fn inject_connection_established(&mut self, peer_id: &PeerId, conn: &ConnectionId, endpoint: &ConnectedPoint) {
 		debug!(target: "sub-libp2p", "Libp2p => Connection ({:?},{:?}) to {} established.",
 			conn, endpoint, peer_id);
+
+		// Let the peerset know where the connection comes from, so that nodes reachable at
+		// banned addresses get banned too.
+		let remote_addr = match endpoint {
+			ConnectedPoint::Dialer { address } => address,
+			ConnectedPoint::Listener { send_back_addr, .. } => send_back_addr,
+		};
+		match remote_addr.iter().next() {
+			Some(Protocol::Ip4(ip)) => self.peerset.connected_from(peer_id.clone(), ip.into()),
+			Some(Protocol::Ip6(ip)) => self.peerset.connected_from(peer_id.clone(), ip.into()),
+			_ => {}
+		}
 		match (self.peers.entry(peer_id.clone()).or_insert(PeerState::Poisoned), endpoint) {
 			(st @ &mut PeerState::Requested, endpoint) |
 			(st @ &mut PeerState::PendingRequest { .. }, endpoint) => {
@@ -80,6 +80,8 @@ fn build_nodes() -> (Swarm<CustomProtoWithAddr>, Swarm<CustomProtoWithAddr>) {
 			},
 			reserved_only: false,
 			priority_groups: Vec::new(),
+			banned_threshold: sc_peerset::DEFAULT_BANNED_THRESHOLD,
+			ban_list_path: None,
 		});
 
 		let behaviour = CustomProtoWithAddr {
@@ -46,6 +46,11 @@ impl<T: Hash + Eq> LruHashSet<T> {
 		}
 		false
 	}
+
+	/// Returns `true` if the set contains the given element.
+	pub(crate) fn contains(&self, e: &T) -> bool {
+		self.set.contains(e)
+	}
 }
 
 #[cfg(test)]
@@ -29,6 +29,7 @@
 
 use crate::{
 	ExHashT, NetworkStateInfo,
+	bandwidth::ProtocolBandwidth,
 	behaviour::{Behaviour, BehaviourOut},
 	config::{parse_addr, parse_str_addr, NonReservedPeerMode, Params, Role, TransportConfig},
 	discovery::DiscoveryConfig,
@@ -53,7 +54,7 @@ use prometheus_endpoint::{
 	register, Counter, CounterVec, Gauge, GaugeVec, Histogram, HistogramOpts, HistogramVec, Opts,
 	PrometheusError, Registry, U64,
 };
-use sc_peerset::PeersetHandle;
+use sc_peerset::{BanTarget, PeerReputation, PeersetHandle};
 use sp_consensus::import_queue::{BlockImportError, BlockImportResult, ImportQueue, Link};
 use sp_runtime::{
 	traits::{Block as BlockT, NumberFor},
@@ -73,24 +74,32 @@ use std::{
 		Arc,
 	},
 	task::Poll,
+	time::Duration,
 };
 
 mod out_events;
 #[cfg(test)]
 mod tests;
 
+/// Name of the file, in the network configuration directory, that the list of bans is saved to.
+const BAN_LIST_FILE: &str = "banned_peers.json";
+
 /// Substrate network service. Handles network IO and manages connectivity.
 pub struct NetworkService<B: BlockT + 'static, H: ExHashT> {
 	/// Number of peers we're connected to.
 	num_connected: Arc<AtomicUsize>,
 	/// The local external addresses.
 	external_addresses: Arc<Mutex<Vec<Multiaddr>>>,
+	/// The best blocks announced by the peers we're connected to.
+	peers_best_blocks: Arc<Mutex<Vec<(B::Hash, NumberFor<B>)>>>,
 	/// Are we actively catching up with the chain?
 	is_major_syncing: Arc<AtomicBool>,
 	/// Local copy of the `PeerId` of the local node.
 	local_peer_id: PeerId,
 	/// Bandwidth logging system. Can be queried to know the average bandwidth consumed.
 	bandwidth: Arc<transport::BandwidthSinks>,
+	/// Number of bytes exchanged per protocol and per peer.
+	protocol_bandwidth: Arc<ProtocolBandwidth>,
 	/// Peerset manager (PSM); manages the reputation of nodes and indicates the network which
 	/// nodes it should be connected to or not.
 	peerset: PeersetHandle,
@@ -101,6 +110,10 @@ pub struct NetworkService<B: BlockT + 'static, H: ExHashT> {
 	peers_notifications_sinks: Arc<Mutex<HashMap<(PeerId, ConsensusEngineId), NotificationsSink>>>,
 	/// For each legacy gossiping engine ID, the corresponding new protocol name.
 	protocol_name_by_engine: Mutex<HashMap<ConsensusEngineId, Cow<'static, [u8]>>>,
+	/// If we are an authority that only peers with its sentry nodes, the nodes we keep our
+	/// connections with. Shared with the transport, which closes the connections with any other
+	/// node.
+	sentries_only_peers: Option<Arc<Mutex<SentriesOnlyPeers>>>,
 	/// Field extracted from the [`Metrics`] struct and necessary to report the
 	/// notifications-related metrics.
 	notifications_sizes_metric: Option<HistogramVec>,
@@ -136,8 +149,8 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 
 		let (to_worker, from_service) = tracing_unbounded("mpsc_network_worker");
 
-		if let Some(path) = params.network_config.net_config_path {
-			fs::create_dir_all(&path)?;
+		if let Some(path) = &params.network_config.net_config_path {
+			fs::create_dir_all(path)?;
 		}
 
 		// List of multiaddresses that we know in the network.
@@ -145,11 +158,31 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 		let mut bootnodes = Vec::new();
 		let mut boot_node_ids = HashSet::new();
 
+		// An authority that only peers with its sentry nodes doesn't make use of bootnodes,
+		// mDNS or the DHT to find other peers.
+		let sentries_only = matches!(params.role, Role::Authority { sentries_only: true, .. });
+
+		// The only nodes such an authority keeps its connections with.
+		let sentries_only_peers = match &params.role {
+			Role::Authority { sentry_nodes, sentries_only: true } => {
+				let peers = SentriesOnlyPeers {
+					sentries: sentry_nodes.iter().map(|sentry| sentry.peer_id.clone()).collect(),
+					reserved: params.network_config.reserved_nodes.iter()
+						.map(|reserved| reserved.peer_id.clone())
+						.collect(),
+				};
+				Some(Arc::new(Mutex::new(peers)))
+			},
+			_ => None,
+		};
+
 		// Process the bootnodes.
-		for bootnode in params.network_config.boot_nodes.iter() {
-			bootnodes.push(bootnode.peer_id.clone());
-			boot_node_ids.insert(bootnode.peer_id.clone());
-			known_addresses.push((bootnode.peer_id.clone(), bootnode.multiaddr.clone()));
+		if !sentries_only {
+			for bootnode in params.network_config.boot_nodes.iter() {
+				bootnodes.push(bootnode.peer_id.clone());
+				boot_node_ids.insert(bootnode.peer_id.clone());
+				known_addresses.push((bootnode.peer_id.clone(), bootnode.multiaddr.clone()));
+			}
 		}
 
 		let boot_node_ids = Arc::new(boot_node_ids);
@@ -188,7 +221,7 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 						known_addresses.push((validator.peer_id.clone(), validator.multiaddr.clone()));
 					}
 				}
-				Role::Authority { sentry_nodes } => {
+				Role::Authority { sentry_nodes, .. } => {
 					for sentry_node in sentry_nodes {
 						sentries_and_validators.insert(sentry_node.peer_id.clone());
 						reserved_nodes.insert(sentry_node.peer_id.clone());
@@ -208,8 +241,12 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 			in_peers: params.network_config.in_peers,
 			out_peers: params.network_config.out_peers,
 			bootnodes,
-			reserved_only: params.network_config.non_reserved_mode == NonReservedPeerMode::Deny,
+			reserved_only: sentries_only ||
+				params.network_config.non_reserved_mode == NonReservedPeerMode::Deny,
 			priority_groups,
+			banned_threshold: params.network_config.reputation_ban_threshold,
+			ban_list_path: params.network_config.net_config_path.as_ref()
+				.map(|path| path.join(BAN_LIST_FILE)),
 		};
 
 		// Private and public keys configuration.
@@ -234,12 +271,17 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 			.map(|od| od.checker().clone())
 			.unwrap_or_else(|| Arc::new(AlwaysBadChecker));
 
+		let protocol_bandwidth = Arc::new(
+			ProtocolBandwidth::new(&params.network_config.upload_rate_limits)
+		);
+
 		let num_connected = Arc::new(AtomicUsize::new(0));
 		let is_major_syncing = Arc::new(AtomicBool::new(false));
 		let (protocol, peerset_handle) = Protocol::new(
 			protocol::ProtocolConfig {
 				roles: From::from(&params.role),
 				max_parallel_downloads: params.network_config.max_parallel_downloads,
+				transaction_propagation: params.network_config.transaction_propagation,
 			},
 			local_peer_id.clone(),
 			params.chain.clone(),
@@ -251,6 +293,7 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 			params.block_announce_validator,
 			params.metrics_registry.as_ref(),
 			boot_node_ids.clone(),
+			protocol_bandwidth.clone(),
 		)?;
 
 		// Build the swarm.
@@ -262,11 +305,15 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 			);
 			let block_requests = {
 				let config = block_requests::Config::new(&params.protocol_id);
-				block_requests::BlockRequests::new(config, params.chain.clone())
+				block_requests::BlockRequests::new(config, params.chain.clone(), protocol_bandwidth.clone())
 			};
 			let finality_proof_requests = {
 				let config = finality_requests::Config::new(&params.protocol_id);
-				finality_requests::FinalityProofRequests::new(config, params.finality_proof_provider.clone())
+				finality_requests::FinalityProofRequests::new(
+					config,
+					params.finality_proof_provider.clone(),
+					protocol_bandwidth.clone(),
+				)
 			};
 			let light_client_handler = {
 				let config = light_client_handler::Config::new(&params.protocol_id);
@@ -275,6 +322,7 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 					params.chain,
 					checker,
 					peerset_handle.clone(),
+					protocol_bandwidth.clone(),
 				)
 			};
 
@@ -284,6 +332,7 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 				config.discovery_limit(u64::from(params.network_config.out_peers) + 15);
 				config.add_protocol(params.protocol_id.clone());
 				config.allow_non_globals_in_dht(params.network_config.allow_non_globals_in_dht);
+				config.restrict_to_user_defined(sentries_only);
 
 				match params.network_config.transport {
 					TransportConfig::MemoryOnly => {
@@ -319,7 +368,11 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 					TransportConfig::Normal { wasm_external_transport, use_yamux_flow_control, .. } =>
 						(false, wasm_external_transport, use_yamux_flow_control)
 				};
-				transport::build_transport(local_identity, config_mem, config_wasm, flowctrl)
+				let peer_filter = sentries_only_peers.clone().map(|peers| {
+					Arc::new(move |peer_id: &PeerId| peers.lock().contains(peer_id))
+						as Arc<dyn Fn(&PeerId) -> bool + Send + Sync>
+				});
+				transport::build_transport(local_identity, config_mem, config_wasm, flowctrl, peer_filter)
 			};
 			let mut builder = SwarmBuilder::new(transport, behaviour, local_peer_id.clone())
 				.peer_connection_limit(crate::MAX_CONNECTIONS_PER_PEER)
@@ -350,6 +403,7 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 		}
 
 		let external_addresses = Arc::new(Mutex::new(Vec::new()));
+		let peers_best_blocks = Arc::new(Mutex::new(Vec::new()));
 		let peers_notifications_sinks = Arc::new(Mutex::new(HashMap::new()));
 		let protocol_name_by_engine = Mutex::new({
 			params.network_config.notifications_protocols.iter().cloned().collect()
@@ -357,7 +411,9 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 
 		let service = Arc::new(NetworkService {
 			bandwidth,
+			protocol_bandwidth,
 			external_addresses: external_addresses.clone(),
+			peers_best_blocks: peers_best_blocks.clone(),
 			num_connected: num_connected.clone(),
 			is_major_syncing: is_major_syncing.clone(),
 			peerset: peerset_handle,
@@ -365,6 +421,7 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 			to_worker,
 			peers_notifications_sinks: peers_notifications_sinks.clone(),
 			protocol_name_by_engine,
+			sentries_only_peers,
 			notifications_sizes_metric:
 				metrics.as_ref().map(|metrics| metrics.notifications_sizes.clone()),
 			_marker: PhantomData,
@@ -372,6 +429,7 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 
 		Ok(NetworkWorker {
 			external_addresses,
+			peers_best_blocks,
 			num_connected,
 			is_major_syncing,
 			network_service: swarm,
@@ -481,6 +539,7 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 
 		let connected_peers = {
 			let swarm = &mut *swarm;
+			let protocol_bandwidth = &self.service.protocol_bandwidth;
 			open.iter().filter_map(move |peer_id| {
 				let known_addresses = NetworkBehaviour::addresses_of_peer(&mut **swarm, peer_id)
 					.into_iter().collect();
@@ -501,6 +560,7 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 					enabled: swarm.user_protocol().is_enabled(&peer_id),
 					open: swarm.user_protocol().is_open(&peer_id),
 					known_addresses,
+					bandwidth: protocol_bandwidth.peer(peer_id),
 				}))
 			}).collect()
 		};
@@ -527,12 +587,19 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkWorker<B, H> {
 			external_addresses: Swarm::<B, H>::external_addresses(&swarm).cloned().collect(),
 			average_download_per_sec: self.service.bandwidth.average_download_per_sec(),
 			average_upload_per_sec: self.service.bandwidth.average_upload_per_sec(),
+			protocols_bandwidth: self.service.protocol_bandwidth.protocols(),
 			connected_peers,
 			not_connected_peers,
 			peerset: swarm.user_protocol_mut().peerset_debug_info(),
 		}
 	}
 
+	/// Returns the reputation and the most recent reputation changes of all the nodes known to
+	/// the peerset manager.
+	pub fn peers_reputation(&mut self) -> Vec<PeerReputation> {
+		self.network_service.user_protocol_mut().peers_reputation()
+	}
+
 	/// Get currently connected peers.
 	pub fn peers_debug_info(&mut self) -> Vec<(PeerId, PeerInfo<B>)> {
 		self.network_service.user_protocol_mut()
@@ -584,7 +651,7 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkService<B, H> {
 		// `peers_notifications_sinks` mutex as soon as possible.
 		let sink = {
 			let peers_notifications_sinks = self.peers_notifications_sinks.lock();
-			if let Some(sink) = peers_notifications_sinks.get(&(target, engine_id)) {
+			if let Some(sink) = peers_notifications_sinks.get(&(target.clone(), engine_id)) {
 				sink.clone()
 			} else {
 				// Notification silently discarded, as documented.
@@ -614,6 +681,12 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkService<B, H> {
 			return;
 		}
 
+		self.protocol_bandwidth.report_outbound(
+			maybe_utf8_bytes_to_string(&engine_id).into_owned(),
+			&target,
+			message_len,
+		);
+
 		if let Some(notifications_sizes_metric) = self.notifications_sizes_metric.as_ref() {
 			notifications_sizes_metric
 				.with_label_values(&["out", &maybe_utf8_bytes_to_string(&engine_id)])
@@ -694,7 +767,7 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkService<B, H> {
 		// `peers_notifications_sinks` mutex as soon as possible.
 		let sink = {
 			let peers_notifications_sinks = self.peers_notifications_sinks.lock();
-			if let Some(sink) = peers_notifications_sinks.get(&(target, engine_id)) {
+			if let Some(sink) = peers_notifications_sinks.get(&(target.clone(), engine_id)) {
 				sink.clone()
 			} else {
 				return Err(NotificationSenderError::Closed);
@@ -711,6 +784,8 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkService<B, H> {
 			sink,
 			protocol_name,
 			engine_id,
+			peer_id: target,
+			protocol_bandwidth: self.protocol_bandwidth.clone(),
 			notification_size_metric: self.notifications_sizes_metric.as_ref().map(|histogram| {
 				histogram.with_label_values(&["out", &maybe_utf8_bytes_to_string(&engine_id)])
 			}),
@@ -843,9 +918,25 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkService<B, H> {
 
 	/// Removes a `PeerId` from the list of reserved peers.
 	pub fn remove_reserved_peer(&self, peer: PeerId) {
+		if let Some(peers) = &self.sentries_only_peers {
+			peers.lock().reserved.remove(&peer);
+		}
 		self.peerset.remove_reserved_peer(peer);
 	}
 
+	/// Bans a node or an IP address for the given duration, or until [`NetworkService::unban`]
+	/// is called if `duration` is `None`.
+	///
+	/// The ban is saved in the network configuration directory, if any, and survives restarts.
+	pub fn ban(&self, target: BanTarget, duration: Option<Duration>, reason: String) {
+		self.peerset.ban(target, duration, reason);
+	}
+
+	/// Lifts a ban previously put in place with [`NetworkService::ban`].
+	pub fn unban(&self, target: BanTarget) {
+		self.peerset.unban(target);
+	}
+
 	/// Adds a `PeerId` and its address as reserved. The string should encode the address
 	/// and peer ID of the remote node.
 	///
@@ -857,6 +948,9 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkService<B, H> {
 		if peer_id == self.local_peer_id {
 			return Err("Local peer ID cannot be added as a reserved peer.".to_string())
 		}
+		if let Some(peers) = &self.sentries_only_peers {
+			peers.lock().reserved.insert(peer_id.clone());
+		}
 		self.peerset.add_reserved_peer(peer_id.clone());
 		let _ = self
 			.to_worker
@@ -913,6 +1007,12 @@ impl<B: BlockT + 'static, H: ExHashT> NetworkService<B, H> {
 		self.num_connected.load(Ordering::Relaxed)
 	}
 
+	/// Returns the hashes and numbers of the best blocks announced by the peers we're connected
+	/// to, in no particular order.
+	pub fn peers_best_blocks(&self) -> Vec<(B::Hash, NumberFor<B>)> {
+		self.peers_best_blocks.lock().clone()
+	}
+
 	/// This function should be called when blocks are added to the chain by something other
 	/// than the import queue.
 	///
@@ -982,6 +1082,12 @@ pub struct NotificationSender {
 	/// Engine ID used for the fallback message.
 	engine_id: ConsensusEngineId,
 
+	/// Peer the notifications are sent to.
+	peer_id: PeerId,
+
+	/// Number of bytes exchanged per protocol and per peer.
+	protocol_bandwidth: Arc<ProtocolBandwidth>,
+
 	/// Field extracted from the [`Metrics`] struct and necessary to report the
 	/// notifications-related metrics.
 	notification_size_metric: Option<Histogram>,
@@ -996,6 +1102,8 @@ impl NotificationSender {
 				Err(()) => return Err(NotificationSenderError::Closed),
 			},
 			engine_id: self.engine_id,
+			peer_id: &self.peer_id,
+			protocol_bandwidth: &self.protocol_bandwidth,
 			notification_size_metric: self.notification_size_metric.clone(),
 		})
 	}
@@ -1009,6 +1117,12 @@ pub struct NotificationSenderReady<'a> {
 	/// Engine ID used for the fallback message.
 	engine_id: ConsensusEngineId,
 
+	/// Peer the notification is sent to.
+	peer_id: &'a PeerId,
+
+	/// Number of bytes exchanged per protocol and per peer.
+	protocol_bandwidth: &'a ProtocolBandwidth,
+
 	/// Field extracted from the [`Metrics`] struct and necessary to report the
 	/// notifications-related metrics.
 	notification_size_metric: Option<Histogram>,
@@ -1022,6 +1136,11 @@ impl<'a> NotificationSenderReady<'a> {
 		if let Some(notification_size_metric) = &self.notification_size_metric {
 			notification_size_metric.observe(notification.len() as f64);
 		}
+		self.protocol_bandwidth.report_outbound(
+			maybe_utf8_bytes_to_string(&self.engine_id).into_owned(),
+			self.peer_id,
+			notification.len(),
+		);
 
 		// For backwards-compatibility reason, we have to duplicate the message and pass it
 		// in the situation where the remote still uses the legacy substream.
@@ -1074,6 +1193,21 @@ enum ServiceToWorkerMsg<B: BlockT, H: ExHashT> {
 	OwnBlockImported(B::Hash, NumberFor<B>),
 }
 
+/// Nodes a sentries-only authority keeps its connections with.
+struct SentriesOnlyPeers {
+	/// The sentry nodes of the authority.
+	sentries: HashSet<PeerId>,
+	/// The reserved nodes, including the ones added after start-up.
+	reserved: HashSet<PeerId>,
+}
+
+impl SentriesOnlyPeers {
+	/// True if we keep our connections with the given peer.
+	fn contains(&self, peer_id: &PeerId) -> bool {
+		self.sentries.contains(peer_id) || self.reserved.contains(peer_id)
+	}
+}
+
 /// Main network worker. Must be polled in order for the network to advance.
 ///
 /// You are encouraged to poll this in a separate background thread or task.
@@ -1082,6 +1216,8 @@ pub struct NetworkWorker<B: BlockT + 'static, H: ExHashT> {
 	/// Updated by the `NetworkWorker` and loaded by the `NetworkService`.
 	external_addresses: Arc<Mutex<Vec<Multiaddr>>>,
 	/// Updated by the `NetworkWorker` and loaded by the `NetworkService`.
+	peers_best_blocks: Arc<Mutex<Vec<(B::Hash, NumberFor<B>)>>>,
+	/// Updated by the `NetworkWorker` and loaded by the `NetworkService`.
 	num_connected: Arc<AtomicUsize>,
 	/// Updated by the `NetworkWorker` and loaded by the `NetworkService`.
 	is_major_syncing: Arc<AtomicBool>,
@@ -1134,6 +1270,7 @@ struct Metrics {
 	peerset_num_requested: Gauge<U64>,
 	pending_connections: Gauge<U64>,
 	pending_connections_errors_total: CounterVec<U64>,
+	protocol_bytes_total: CounterVec<U64>,
 	requests_in_total: HistogramVec,
 	requests_out_finished: HistogramVec,
 	requests_out_started_total: CounterVec<U64>,
@@ -1283,6 +1420,13 @@ impl Metrics {
 				),
 				&["reason"]
 			)?, registry)?,
+			protocol_bytes_total: register(CounterVec::new(
+				Opts::new(
+					"sub_libp2p_protocol_bytes_total",
+					"Total number of bytes exchanged, by direction and protocol"
+				),
+				&["direction", "protocol"]
+			)?, registry)?,
 			requests_in_total: register(HistogramVec::new(
 				HistogramOpts {
 					common_opts: Opts::new(
@@ -1503,6 +1647,13 @@ impl<B: BlockT + 'static, H: ExHashT> Future for NetworkWorker<B, H> {
 								.observe(message.len() as f64);
 						}
 					}
+					for (engine_id, message) in &messages {
+						this.service.protocol_bandwidth.report_inbound(
+							maybe_utf8_bytes_to_string(engine_id).into_owned(),
+							&remote,
+							message.len(),
+						);
+					}
 					this.event_streams.send(Event::NotificationsReceived {
 						remote,
 						messages,
@@ -1554,6 +1705,9 @@ impl<B: BlockT + 'static, H: ExHashT> Future for NetworkWorker<B, H> {
 							metrics.distinct_peers_connections_closed_total.inc();
 						}
 					}
+					if num_established == 0 {
+						this.service.protocol_bandwidth.remove_peer(&peer_id);
+					}
 				},
 				Poll::Ready(SwarmEvent::NewListenAddr(addr)) => {
 					trace!(target: "sub-libp2p", "Libp2p => NewListenAddr({})", addr);
@@ -1665,6 +1819,13 @@ impl<B: BlockT + 'static, H: ExHashT> Future for NetworkWorker<B, H> {
 			let external_addresses = Swarm::<B, H>::external_addresses(&this.network_service).cloned().collect();
 			*this.external_addresses.lock() = external_addresses;
 		}
+		{
+			let peers_best_blocks = this.network_service.user_protocol()
+				.peers_info()
+				.map(|(_, info)| (info.best_hash, info.best_number))
+				.collect();
+			*this.peers_best_blocks.lock() = peers_best_blocks;
+		}
 
 		let is_major_syncing = match this.network_service.user_protocol_mut().sync_state() {
 			SyncState::Idle => false,
@@ -1676,6 +1837,12 @@ impl<B: BlockT + 'static, H: ExHashT> Future for NetworkWorker<B, H> {
 		if let Some(metrics) = this.metrics.as_ref() {
 			metrics.network_per_sec_bytes.with_label_values(&["in"]).set(this.service.bandwidth.average_download_per_sec());
 			metrics.network_per_sec_bytes.with_label_values(&["out"]).set(this.service.bandwidth.average_upload_per_sec());
+			for (proto, counters) in this.service.protocol_bandwidth.protocols() {
+				let inbound = metrics.protocol_bytes_total.with_label_values(&["in", &proto]);
+				inbound.inc_by(counters.inbound.saturating_sub(inbound.get()));
+				let outbound = metrics.protocol_bytes_total.with_label_values(&["out", &proto]);
+				outbound.inc_by(counters.outbound.saturating_sub(outbound.get()));
+			}
 			metrics.is_major_syncing.set(is_major_syncing as u64);
 			for (proto, num_entries) in this.network_service.num_kbuckets_entries() {
 				let proto = maybe_utf8_bytes_to_string(proto.as_bytes());
@@ -16,12 +16,14 @@
 // You should have received a copy of the GNU General Public License
 // along with this program. If not, see <https://www.gnu.org/licenses/>.
 
-use crate::{config, Event, NetworkService, NetworkWorker};
+use crate::{config, DhtEvent, Event, NetworkService, NetworkWorker};
 
+use codec::Encode;
 use libp2p::PeerId;
 use futures::prelude::*;
+use parking_lot::Mutex;
 use sp_runtime::traits::{Block as BlockT, Header as _};
-use std::{sync::Arc, time::Duration};
+use std::{collections::HashMap, sync::Arc, task::Poll, time::Duration};
 use substrate_test_runtime_client::{TestClientBuilder, TestClientBuilderExt as _};
 
 type TestNetworkService = NetworkService<
@@ -29,6 +31,11 @@ type TestNetworkService = NetworkService<
 	substrate_test_runtime_client::runtime::Hash,
 >;
 
+type TestNetworkWorker = NetworkWorker<
+	substrate_test_runtime_client::runtime::Block,
+	substrate_test_runtime_client::runtime::Hash,
+>;
+
 /// Builds a full node to be used for testing. Returns the node service and its associated events
 /// stream.
 ///
@@ -37,6 +44,56 @@ type TestNetworkService = NetworkService<
 fn build_test_full_node(config: config::NetworkConfiguration)
 	-> (Arc<TestNetworkService>, impl Stream<Item = Event>)
 {
+	build_test_node(config::Role::Full, config)
+}
+
+/// Builds a node with the given role to be used for testing. Returns the node service and its
+/// associated events stream.
+fn build_test_node(role: config::Role, config: config::NetworkConfiguration)
+	-> (Arc<TestNetworkService>, impl Stream<Item = Event>)
+{
+	build_test_node_with_pool(role, config, Arc::new(crate::config::EmptyTransactionPool))
+}
+
+/// Builds a node with the given role and transaction pool to be used for testing. Returns the
+/// node service and its associated events stream.
+fn build_test_node_with_pool(
+	role: config::Role,
+	config: config::NetworkConfiguration,
+	transaction_pool: Arc<dyn config::TransactionPool<
+		substrate_test_runtime_client::runtime::Hash,
+		substrate_test_runtime_client::runtime::Block,
+	>>,
+) -> (Arc<TestNetworkService>, impl Stream<Item = Event>) {
+	let (worker, event_stream) = build_test_worker_with_pool(role, config, transaction_pool);
+	let service = worker.service().clone();
+
+	async_std::task::spawn(async move {
+		futures::pin_mut!(worker);
+		let _ = worker.await;
+	});
+
+	(service, event_stream)
+}
+
+/// Builds a node with the given role to be used for testing, without spawning its worker. Returns
+/// the node worker and its associated events stream.
+fn build_test_worker(role: config::Role, config: config::NetworkConfiguration)
+	-> (TestNetworkWorker, impl Stream<Item = Event>)
+{
+	build_test_worker_with_pool(role, config, Arc::new(crate::config::EmptyTransactionPool))
+}
+
+/// Builds a node with the given role and transaction pool to be used for testing, without
+/// spawning its worker. Returns the node worker and its associated events stream.
+fn build_test_worker_with_pool(
+	role: config::Role,
+	config: config::NetworkConfiguration,
+	transaction_pool: Arc<dyn config::TransactionPool<
+		substrate_test_runtime_client::runtime::Hash,
+		substrate_test_runtime_client::runtime::Block,
+	>>,
+) -> (TestNetworkWorker, impl Stream<Item = Event>) {
 	let client = Arc::new(
 		TestClientBuilder::with_default_backend()
 			.build_with_longest_chain()
@@ -93,14 +150,14 @@ fn build_test_full_node(config: config::NetworkConfiguration)
 	));
 
 	let worker = NetworkWorker::new(config::Params {
-		role: config::Role::Full,
+		role,
 		executor: None,
 		network_config: config,
 		chain: client.clone(),
 		finality_proof_provider: None,
 		finality_proof_request_builder: None,
 		on_demand: None,
-		transaction_pool: Arc::new(crate::config::EmptyTransactionPool),
+		transaction_pool,
 		protocol_id: config::ProtocolId::from(&b"/test-protocol-name"[..]),
 		import_queue,
 		block_announce_validator: Box::new(
@@ -110,15 +167,8 @@ fn build_test_full_node(config: config::NetworkConfiguration)
 	})
 	.unwrap();
 
-	let service = worker.service().clone();
-	let event_stream = service.event_stream("test");
-
-	async_std::task::spawn(async move {
-		futures::pin_mut!(worker);
-		let _ = worker.await;
-	});
-
-	(service, event_stream)
+	let event_stream = worker.service().event_stream("test");
+	(worker, event_stream)
 }
 
 const ENGINE_ID: sp_runtime::ConsensusEngineId = *b"foo\0";
@@ -345,6 +395,161 @@ fn lots_of_incoming_peers_works() {
 	});
 }
 
+#[test]
+fn sentries_only_authority_only_connects_to_sentries() {
+	// An authority configured to only peer with its sentry nodes must open a notifications
+	// substream with its sentry, but close the connections of any other node, even one that has
+	// the authority among its reserved nodes.
+
+	let sentry_addr = config::build_multiaddr![Memory(rand::random::<u64>())];
+	let authority_addr = config::build_multiaddr![Memory(rand::random::<u64>())];
+
+	let (sentry, _) = build_test_full_node(config::NetworkConfiguration {
+		notifications_protocols: vec![(ENGINE_ID, From::from(&b"/foo"[..]))],
+		listen_addresses: vec![sentry_addr.clone()],
+		transport: config::TransportConfig::MemoryOnly,
+		.. config::NetworkConfiguration::new_local()
+	});
+	let sentry_peer_id = sentry.local_peer_id().clone();
+
+	let (mut authority, authority_events) = build_test_worker(
+		config::Role::Authority {
+			sentry_nodes: vec![config::MultiaddrWithPeerId {
+				multiaddr: sentry_addr,
+				peer_id: sentry_peer_id.clone(),
+			}],
+			sentries_only: true,
+		},
+		config::NetworkConfiguration {
+			notifications_protocols: vec![(ENGINE_ID, From::from(&b"/foo"[..]))],
+			listen_addresses: vec![authority_addr.clone()],
+			transport: config::TransportConfig::MemoryOnly,
+			.. config::NetworkConfiguration::new_local()
+		},
+	);
+	let authority_peer_id = authority.local_peer_id().clone();
+
+	let (mut outsider, outsider_events) = build_test_worker(
+		config::Role::Full,
+		config::NetworkConfiguration {
+			notifications_protocols: vec![(ENGINE_ID, From::from(&b"/foo"[..]))],
+			listen_addresses: vec![],
+			reserved_nodes: vec![config::MultiaddrWithPeerId {
+				multiaddr: authority_addr,
+				peer_id: authority_peer_id.clone(),
+			}],
+			transport: config::TransportConfig::MemoryOnly,
+			.. config::NetworkConfiguration::new_local()
+		},
+	);
+	let outsider_peer_id = outsider.local_peer_id().clone();
+
+	// The outsider keeps dialing the authority, which must close every one of these connections
+	// before they are reported as established, on either side.
+	let mut workers = future::poll_fn(move |cx| {
+		let _ = authority.poll_unpin(cx);
+		let _ = outsider.poll_unpin(cx);
+		let authority_swarm = &mut authority.network_service;
+		assert!(libp2p::swarm::Swarm::connection_info(authority_swarm, &outsider_peer_id).is_none());
+		let outsider_swarm = &mut outsider.network_service;
+		assert!(libp2p::swarm::Swarm::connection_info(outsider_swarm, &authority_peer_id).is_none());
+		Poll::<()>::Pending
+	}).fuse();
+
+	async_std::task::block_on(async move {
+		// Test succeeds 5 seconds after the authority opened a substream with its sentry, as
+		// long as no other substream has been opened in the meantime.
+		let mut timer = futures_timer::Delay::new(Duration::from_secs(3600 * 24 * 7)).fuse();
+
+		let mut authority_events = authority_events.fuse();
+		let mut outsider_events = outsider_events.fuse();
+		loop {
+			futures::select! {
+				_ = timer => return,
+				_ = workers => unreachable!(),
+				ev = authority_events.next() => match ev.unwrap() {
+					Event::NotificationStreamOpened { remote, .. } => {
+						assert_eq!(remote, sentry_peer_id);
+						timer = futures_timer::Delay::new(Duration::from_secs(5)).fuse();
+					}
+					Event::NotificationStreamClosed { .. } => panic!(),
+					_ => {}
+				},
+				ev = outsider_events.next() => match ev.unwrap() {
+					Event::NotificationStreamOpened { .. } => panic!(),
+					_ => {}
+				},
+			}
+		}
+	});
+}
+
+#[test]
+fn sentries_only_authority_record_is_resolvable_through_sentry() {
+	// An authority configured to only peer with its sentry nodes stores a record in the DHT.
+	// A node that only knows about the sentry must be able to fetch said record, even though it
+	// is never connected to the authority itself.
+
+	let sentry_addr = config::build_multiaddr![Memory(rand::random::<u64>())];
+
+	let (sentry, _) = build_test_full_node(config::NetworkConfiguration {
+		listen_addresses: vec![sentry_addr.clone()],
+		transport: config::TransportConfig::MemoryOnly,
+		.. config::NetworkConfiguration::new_local()
+	});
+	let sentry_node = config::MultiaddrWithPeerId {
+		multiaddr: sentry_addr,
+		peer_id: sentry.local_peer_id().clone(),
+	};
+
+	let (authority, _) = build_test_node(
+		config::Role::Authority {
+			sentry_nodes: vec![sentry_node.clone()],
+			sentries_only: true,
+		},
+		config::NetworkConfiguration {
+			listen_addresses: vec![config::build_multiaddr![Memory(rand::random::<u64>())]],
+			transport: config::TransportConfig::MemoryOnly,
+			.. config::NetworkConfiguration::new_local()
+		},
+	);
+	let authority_peer_id = authority.local_peer_id().clone();
+
+	let (outsider, outsider_events) = build_test_full_node(config::NetworkConfiguration {
+		listen_addresses: vec![],
+		boot_nodes: vec![sentry_node],
+		transport: config::TransportConfig::MemoryOnly,
+		.. config::NetworkConfiguration::new_local()
+	});
+
+	let key = libp2p::kad::record::Key::new(&b"authority-record"[..]);
+	let value = b"signed authority addresses".to_vec();
+
+	async_std::task::block_on(async move {
+		// The authority might not be connected to its sentry yet when first storing the record,
+		// hence both storing and fetching are retried until the outsider finds the record.
+		let mut retry = futures_timer::Delay::new(Duration::from_secs(0)).fuse();
+		let mut outsider_events = outsider_events.fuse();
+		loop {
+			futures::select! {
+				_ = retry => {
+					authority.put_value(key.clone(), value.clone());
+					outsider.get_value(&key);
+					retry = futures_timer::Delay::new(Duration::from_secs(1)).fuse();
+				},
+				ev = outsider_events.next() => match ev.unwrap() {
+					Event::Dht(DhtEvent::ValueFound(values)) => {
+						assert_eq!(values, vec![(key.clone(), value.clone())]);
+						break;
+					}
+					Event::NotificationStreamOpened { remote, .. } => assert_ne!(remote, authority_peer_id),
+					_ => {}
+				},
+			}
+		}
+	});
+}
+
 #[test]
 fn notifications_back_pressure() {
 	// Node 1 floods node 2 with notifications. Random sleeps are done on node 2 to simulate the
@@ -511,3 +716,164 @@ fn ensure_public_addresses_consistent_with_transport_not_memory() {
 		.. config::NetworkConfiguration::new("test-node", "test-client", Default::default(), None)
 	});
 }
+
+type Extrinsic = substrate_test_runtime_client::runtime::Extrinsic;
+
+/// Transaction pool that offers fixed transactions for propagation and records the
+/// transactions it receives.
+#[derive(Default)]
+struct TestTransactionPool {
+	ready: Vec<Extrinsic>,
+	imported: Mutex<Vec<Extrinsic>>,
+}
+
+impl config::TransactionPool<
+	substrate_test_runtime_client::runtime::Hash,
+	substrate_test_runtime_client::runtime::Block,
+> for TestTransactionPool {
+	fn transactions(&self) -> Vec<(substrate_test_runtime_client::runtime::Hash, Extrinsic)> {
+		self.ready.iter().map(|xt| (self.hash_of(xt), xt.clone())).collect()
+	}
+
+	fn hash_of(&self, transaction: &Extrinsic) -> substrate_test_runtime_client::runtime::Hash {
+		sp_core::blake2_256(&transaction.encode()).into()
+	}
+
+	fn import(&self, transaction: Extrinsic) -> config::TransactionImportFuture {
+		self.imported.lock().push(transaction);
+		Box::pin(future::ready(config::TransactionImport::NewGood))
+	}
+
+	fn on_broadcasted(
+		&self,
+		_: HashMap<substrate_test_runtime_client::runtime::Hash, Vec<String>>,
+	) {}
+
+	fn transaction(
+		&self,
+		hash: &substrate_test_runtime_client::runtime::Hash,
+	) -> Option<Extrinsic> {
+		self.ready.iter().find(|xt| self.hash_of(xt) == *hash).cloned()
+	}
+}
+
+/// Builds a node that propagates `count` transactions with the given policy, and a node that
+/// receives them. The sender has the receiver as reserved peer if `reserved` is `true`, and as
+/// boot node otherwise.
+///
+/// Waits for the nodes to be connected, then returns the transactions and the pool of the
+/// receiver, along with the nodes to keep them alive.
+async fn build_transaction_nodes(
+	propagation: config::TransactionPropagation,
+	reserved: bool,
+	count: usize,
+) -> (Vec<Extrinsic>, Arc<TestTransactionPool>, Arc<TestNetworkService>, Arc<TestNetworkService>) {
+	let listen_addr = config::build_multiaddr![Memory(rand::random::<u64>())];
+	let transactions = (0..count)
+		.map(|n| Extrinsic::IncludeData(vec![n as u8]))
+		.collect::<Vec<_>>();
+
+	let receiver_pool = Arc::new(TestTransactionPool::default());
+	let (receiver, _) = build_test_node_with_pool(
+		config::Role::Full,
+		config::NetworkConfiguration {
+			listen_addresses: vec![listen_addr.clone()],
+			transport: config::TransportConfig::MemoryOnly,
+			.. config::NetworkConfiguration::new_local()
+		},
+		receiver_pool.clone(),
+	);
+
+	let receiver_addr = config::MultiaddrWithPeerId {
+		multiaddr: listen_addr,
+		peer_id: receiver.local_peer_id().clone(),
+	};
+	let (reserved_nodes, boot_nodes) = if reserved {
+		(vec![receiver_addr], vec![])
+	} else {
+		(vec![], vec![receiver_addr])
+	};
+	let (sender, _) = build_test_node_with_pool(
+		config::Role::Full,
+		config::NetworkConfiguration {
+			listen_addresses: vec![],
+			reserved_nodes,
+			boot_nodes,
+			transaction_propagation: propagation,
+			transport: config::TransportConfig::MemoryOnly,
+			.. config::NetworkConfiguration::new_local()
+		},
+		Arc::new(TestTransactionPool { ready: transactions.clone(), .. Default::default() }),
+	);
+
+	while receiver.num_connected() == 0 || sender.num_connected() == 0 {
+		futures_timer::Delay::new(Duration::from_millis(50)).await;
+	}
+
+	(transactions, receiver_pool, sender, receiver)
+}
+
+/// Waits for a few transaction propagation rounds.
+async fn wait_propagation_rounds() {
+	futures_timer::Delay::new(Duration::from_secs(7)).await;
+}
+
+#[test]
+fn transactions_are_not_propagated_when_disabled() {
+	async_std::task::block_on(async move {
+		let (_, receiver_pool, _sender, _receiver) =
+			build_transaction_nodes(config::TransactionPropagation::Disabled, true, 5).await;
+
+		wait_propagation_rounds().await;
+		assert!(receiver_pool.imported.lock().is_empty());
+	});
+}
+
+#[test]
+fn transactions_are_propagated_to_reserved_peers() {
+	async_std::task::block_on(async move {
+		let (transactions, receiver_pool, _sender, _receiver) =
+			build_transaction_nodes(config::TransactionPropagation::ReservedOnly, true, 5).await;
+
+		while receiver_pool.imported.lock().len() < transactions.len() {
+			futures_timer::Delay::new(Duration::from_millis(50)).await;
+		}
+		assert_eq!(*receiver_pool.imported.lock(), transactions);
+	});
+}
+
+#[test]
+fn transactions_are_not_propagated_to_non_reserved_peers() {
+	async_std::task::block_on(async move {
+		let (_, receiver_pool, _sender, _receiver) =
+			build_transaction_nodes(config::TransactionPropagation::ReservedOnly, false, 5).await;
+
+		wait_propagation_rounds().await;
+		assert!(receiver_pool.imported.lock().is_empty());
+	});
+}
+
+#[test]
+fn rate_limited_transactions_are_sent_in_later_rounds() {
+	// Transactions are propagated every 2.9 seconds, so at most 6 transactions are sent to a
+	// peer in each round with a limit of 2 per second.
+	async_std::task::block_on(async move {
+		let (transactions, receiver_pool, _sender, _receiver) =
+			build_transaction_nodes(config::TransactionPropagation::RateLimited(2), false, 10).await;
+
+		let mut rounds = Vec::new();
+		let mut received = 0;
+		while received < transactions.len() {
+			futures_timer::Delay::new(Duration::from_millis(50)).await;
+			let now_received = receiver_pool.imported.lock().len();
+			if now_received > received {
+				rounds.push(now_received - received);
+				received = now_received;
+			}
+		}
+
+		assert!(rounds.len() >= 2, "All transactions were sent at once: {:?}", rounds);
+		assert!(rounds.iter().all(|&sent| sent <= 6), "Rate limit exceeded: {:?}", rounds);
+		assert_eq!(*receiver_pool.imported.lock(), transactions);
+	});
+}
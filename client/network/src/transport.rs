@@ -36,13 +36,17 @@ pub use self::bandwidth::BandwidthSinks;
 /// If `memory_only` is true, then only communication within the same process are allowed. Only
 /// addresses with the format `/memory/...` are allowed.
 ///
+/// If `peer_filter` is `Some`, connections with the nodes it returns `false` for are closed as soon
+/// as the remote has been authenticated, before any protocol is negotiated on top of them.
+///
 /// Returns a `BandwidthSinks` object that allows querying the average bandwidth produced by all
 /// the connections spawned with this transport.
 pub fn build_transport(
 	keypair: identity::Keypair,
 	memory_only: bool,
 	wasm_external_transport: Option<wasm_ext::ExtTransport>,
-	use_yamux_flow_control: bool
+	use_yamux_flow_control: bool,
+	peer_filter: Option<Arc<dyn Fn(&PeerId) -> bool + Send + Sync>>,
 ) -> (Boxed<(PeerId, StreamMuxerBox), io::Error>, Arc<bandwidth::BandwidthSinks>) {
 	// Build configuration objects for encryption mechanisms.
 	let noise_config = {
@@ -129,6 +133,16 @@ pub fn build_transport(
 			})
 	});
 
+	// Filtering
+	let transport = transport.and_then(move |(stream, peer_id): (_, PeerId), _| {
+		let allowed = peer_filter.as_ref().map_or(true, |filter| filter(&peer_id));
+		future::ready(if allowed {
+			Ok((stream, peer_id))
+		} else {
+			Err(io::Error::new(io::ErrorKind::PermissionDenied, format!("{} is not allowed", peer_id)))
+		})
+	});
+
 	// Multiplexing
 	let transport = transport.and_then(move |(stream, peer_id), endpoint| {
 			let peer_id2 = peer_id.clone();
@@ -25,6 +25,7 @@ use sp_core::offchain::OffchainStorage;
 use futures::Future;
 use log::error;
 use sc_network::{PeerId, Multiaddr, NetworkStateInfo};
+use crate::metrics::Metrics;
 use codec::{Encode, Decode};
 use sp_core::offchain::{
 	Externalities as OffchainExt, HttpRequestId, Timestamp, HttpRequestStatus, HttpError,
@@ -55,6 +56,8 @@ pub(crate) struct Api<Storage> {
 	is_validator: bool,
 	/// Everything HTTP-related is handled by a different struct.
 	http: http::HttpApi,
+	/// Offchain workers metrics, if enabled.
+	metrics: Option<Arc<Metrics>>,
 }
 
 fn unavailable_yet<R: Default>(name: &str) -> R {
@@ -136,6 +139,9 @@ impl<Storage: OffchainStorage> OffchainExt for Api<Storage> {
 		uri: &str,
 		_meta: &[u8]
 	) -> Result<HttpRequestId, ()> {
+		if let Some(metrics) = &self.metrics {
+			metrics.http_requests.inc();
+		}
 		self.http.request_start(method, uri)
 	}
 
@@ -162,7 +168,27 @@ impl<Storage: OffchainStorage> OffchainExt for Api<Storage> {
 		ids: &[HttpRequestId],
 		deadline: Option<Timestamp>
 	) -> Vec<HttpRequestStatus> {
-		self.http.response_wait(ids, deadline)
+		let statuses = self.http.response_wait(ids, deadline);
+		if let Some(metrics) = &self.metrics {
+			for status in &statuses {
+				let outcome = match status {
+					HttpRequestStatus::Finished(code) => match code / 100 {
+						1 => "1xx",
+						2 => "2xx",
+						3 => "3xx",
+						4 => "4xx",
+						5 => "5xx",
+						_ => "other",
+					},
+					HttpRequestStatus::IoError => "io_error",
+					HttpRequestStatus::Invalid => "invalid",
+					// the request can still be waited for
+					HttpRequestStatus::DeadlineReached => continue,
+				};
+				metrics.http_responses.with_label_values(&[outcome]).inc();
+			}
+		}
+		statuses
 	}
 
 	fn http_response_headers(
@@ -262,6 +288,7 @@ impl AsyncApi {
 		network_state: Arc<dyn NetworkStateInfo + Send + Sync>,
 		is_validator: bool,
 		shared_client: SharedClient,
+		metrics: Option<Arc<Metrics>>,
 	) -> (Api<S>, Self) {
 		let (http_api, http_worker) = http::http(shared_client);
 
@@ -270,6 +297,7 @@ impl AsyncApi {
 			network_state,
 			is_validator,
 			http: http_api,
+			metrics,
 		};
 
 		let async_api = Self {
@@ -318,6 +346,7 @@ mod tests {
 			mock,
 			false,
 			shared_client,
+			None,
 		)
 	}
 
@@ -698,7 +698,8 @@ impl fmt::Debug for HttpWorkerRequest {
 mod tests {
 	use core::convert::Infallible;
 	use crate::api::timestamp;
-	use super::{http, SharedClient};
+	use super::{http, HttpApi, SharedClient};
+	use crate::testing::{Fixture, HttpMockServer, MockResponse};
 	use sp_core::offchain::{HttpError, HttpRequestId, HttpRequestStatus, Duration};
 	use futures::future;
 	use lazy_static::lazy_static;
@@ -988,4 +989,125 @@ mod tests {
 			}
 		}
 	}
+
+	/// Returns an `HttpApi` whose worker is ran in the background.
+	fn mock_api() -> HttpApi {
+		let (api, worker) = http(SHARED_CLIENT.clone());
+		std::thread::spawn(move || {
+			let mut rt = tokio::runtime::Runtime::new().unwrap();
+			rt.block_on(worker);
+		});
+		api
+	}
+
+	/// Performs a `GET` request and reads the whole response body.
+	fn get(
+		api: &mut HttpApi,
+		url: &str,
+		deadline_ms: u64,
+	) -> Result<(u16, Vec<u8>), HttpRequestStatus> {
+		let deadline = timestamp::now().add(Duration::from_millis(deadline_ms));
+		let id = api.request_start("GET", url).unwrap();
+		api.request_add_header(id, "X-Test", "1").unwrap();
+		api.request_write_body(id, &[], Some(deadline)).unwrap();
+
+		match api.response_wait(&[id], Some(deadline))[0] {
+			HttpRequestStatus::Finished(status) => {
+				let mut body = Vec::new();
+				let mut buf = [0; 1024];
+				loop {
+					match api.response_read_body(id, &mut buf, Some(deadline)) {
+						Ok(0) => break,
+						Ok(n) => body.extend_from_slice(&buf[..n]),
+						Err(e) => panic!("Failed to read the body: {:?}", e),
+					}
+				}
+				Ok((status, body))
+			},
+			status => Err(status),
+		}
+	}
+
+	#[test]
+	fn replays_fixtures_in_order() {
+		let server = HttpMockServer::replay(vec![
+			Fixture::respond("GET", "/price", MockResponse::ok(&b"1"[..])),
+			Fixture::respond("GET", "/price", MockResponse::ok(&b"2"[..]).with_header("X-Source", "mock"))
+				.with_header("x-test", "1"),
+		]);
+		let mut api = mock_api();
+
+		assert_eq!(get(&mut api, &server.url("/price"), 10_000), Ok((200, b"1".to_vec())));
+		assert_eq!(get(&mut api, &server.url("/price"), 10_000), Ok((200, b"2".to_vec())));
+		server.assert_done();
+
+		let received = server.received();
+		assert_eq!(received.len(), 2);
+		assert_eq!(received[0].header("X-Test"), Some("1"));
+	}
+
+	#[test]
+	fn unexpected_requests_are_rejected() {
+		let server = HttpMockServer::replay(vec![
+			Fixture::respond("POST", "/submit", MockResponse::status(204)),
+		]);
+		let mut api = mock_api();
+
+		assert_eq!(
+			get(&mut api, &server.url("/submit"), 10_000),
+			Ok((501, b"no matching fixture".to_vec())),
+		);
+		assert!(std::panic::catch_unwind(|| server.assert_done()).is_err());
+	}
+
+	#[test]
+	fn slow_and_missing_responses_run_into_the_deadline() {
+		let server = HttpMockServer::replay(vec![
+			Fixture::respond(
+				"GET",
+				"/slow",
+				MockResponse::ok(&b"late"[..]).with_delay(std::time::Duration::from_millis(2_000)),
+			),
+			Fixture::timeout("GET", "/never"),
+			Fixture::respond(
+				"GET",
+				"/slow",
+				MockResponse::ok(&b"in time"[..]).with_delay(std::time::Duration::from_millis(100)),
+			),
+		]);
+		let mut api = mock_api();
+
+		let timed_out = Err(HttpRequestStatus::DeadlineReached);
+		assert_eq!(get(&mut api, &server.url("/slow"), 200), timed_out);
+		assert_eq!(get(&mut api, &server.url("/never"), 200), timed_out);
+		assert_eq!(get(&mut api, &server.url("/slow"), 10_000), Ok((200, b"in time".to_vec())));
+		server.assert_done();
+	}
+
+	#[test]
+	fn records_and_replays_exchanges() {
+		let upstream = HttpMockServer::replay(vec![
+			Fixture::respond("GET", "/price?currency=USD", MockResponse::ok(&b"155"[..])),
+		]);
+		let recorder = HttpMockServer::record(&upstream.url(""));
+		let mut api = mock_api();
+
+		assert_eq!(
+			get(&mut api, &recorder.url("/price?currency=USD"), 10_000),
+			Ok((200, b"155".to_vec())),
+		);
+		upstream.assert_done();
+
+		let path = std::env::temp_dir()
+			.join(format!("offchain-http-fixtures-{}.json", rand::random::<u64>()));
+		recorder.save(&path).unwrap();
+		let replay = HttpMockServer::load(&path).unwrap();
+		let _ = std::fs::remove_file(&path);
+
+		assert_eq!(
+			get(&mut api, &replay.url("/price?currency=USD"), 10_000),
+			Ok((200, b"155".to_vec())),
+		);
+		replay.assert_done();
+	}
 }
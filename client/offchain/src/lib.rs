@@ -33,23 +33,74 @@
 
 #![warn(missing_docs)]
 
-use std::{fmt, marker::PhantomData, sync::Arc};
+use std::{
+	collections::HashSet, fmt, marker::PhantomData, sync::Arc,
+	sync::atomic::{AtomicUsize, Ordering}, time::Instant,
+};
 
-use parking_lot::Mutex;
+use parking_lot::{Mutex, RwLock};
 use threadpool::ThreadPool;
 use sp_api::{ApiExt, ProvideRuntimeApi};
 use futures::future::Future;
 use log::{debug, warn};
 use sc_network::NetworkStateInfo;
 use sp_core::{offchain::{self, OffchainStorage}, ExecutionContext, traits::SpawnNamed};
-use sp_runtime::{generic::BlockId, traits::{self, Header}};
+use sp_runtime::{generic::BlockId, traits::{self, Header, UniqueSaturatedInto}};
 use futures::{prelude::*, future::ready};
+use prometheus_endpoint::Registry;
 
 mod api;
+mod metrics;
+#[cfg(all(not(target_os = "unknown"), any(test, feature = "test-helpers")))]
+pub mod testing;
 use api::SharedClient;
+use metrics::Metrics;
 
 pub use sp_offchain::{OffchainWorkerApi, STORAGE_PREFIX};
 
+/// When and how many offchain workers are run.
+#[derive(Debug, Clone)]
+pub struct OffchainWorkerOptions {
+	/// Run the workers only for blocks whose number is a multiple of this period.
+	pub period: u32,
+	/// Skip a block if the workers of a previous block are still running.
+	pub skip_if_running: bool,
+	/// Maximum number of runs in progress at the same time, further blocks are skipped.
+	pub max_concurrent: Option<usize>,
+	/// Names of the pallets whose workers are not run, as given in `construct_runtime!`,
+	/// e.g. `ImOnline`.
+	///
+	/// Needs version 3 of the `OffchainWorkerApi`, older runtimes run all workers.
+	pub disabled_pallets: Vec<String>,
+}
+
+impl Default for OffchainWorkerOptions {
+	fn default() -> Self {
+		OffchainWorkerOptions {
+			period: 1,
+			skip_if_running: false,
+			max_concurrent: None,
+			disabled_pallets: Vec::new(),
+		}
+	}
+}
+
+impl OffchainWorkerOptions {
+	/// Returns why the workers should not run for the block with the given number,
+	/// given the number of runs in progress.
+	fn skip_reason(&self, number: u64, running: usize) -> Option<&'static str> {
+		if self.period > 1 && number % u64::from(self.period) != 0 {
+			Some("skipped_period")
+		} else if self.skip_if_running && running > 0 {
+			Some("skipped_running")
+		} else if self.max_concurrent.map_or(false, |max| running >= max) {
+			Some("skipped_concurrency")
+		} else {
+			None
+		}
+	}
+}
+
 /// An offchain workers manager.
 pub struct OffchainWorkers<Client, Storage, Block: traits::Block> {
 	client: Arc<Client>,
@@ -57,22 +108,92 @@ pub struct OffchainWorkers<Client, Storage, Block: traits::Block> {
 	_block: PhantomData<Block>,
 	thread_pool: Mutex<ThreadPool>,
 	shared_client: SharedClient,
+	options: OffchainWorkerOptions,
+	/// Names of the pallets whose workers are disabled.
+	disabled_pallets: Arc<RwLock<HashSet<String>>>,
+	/// Number of runs in progress.
+	running: Arc<AtomicUsize>,
+	metrics: Option<Arc<Metrics>>,
 }
 
 impl<Client, Storage, Block: traits::Block> OffchainWorkers<Client, Storage, Block> {
 	/// Creates new `OffchainWorkers`.
 	pub fn new(client: Arc<Client>, db: Storage) -> Self {
+		Self::new_with_options(client, db, Default::default(), None)
+	}
+
+	/// Creates new `OffchainWorkers` that are run according to the given options.
+	pub fn new_with_options(
+		client: Arc<Client>,
+		db: Storage,
+		options: OffchainWorkerOptions,
+		prometheus_registry: Option<&Registry>,
+	) -> Self {
 		let shared_client = SharedClient::new();
+		let metrics = prometheus_registry.and_then(|registry| {
+			Metrics::register(registry)
+				.map_err(|err| warn!("Failed to register offchain worker metrics: {}", err))
+				.ok()
+		});
+		let disabled_pallets = options.disabled_pallets.iter().cloned().collect();
 		Self {
 			client,
 			db,
 			_block: PhantomData,
 			thread_pool: Mutex::new(ThreadPool::new(num_cpus::get())),
 			shared_client,
+			options,
+			disabled_pallets: Arc::new(RwLock::new(disabled_pallets)),
+			running: Arc::new(AtomicUsize::new(0)),
+			metrics: metrics.map(Arc::new),
+		}
+	}
+
+	/// Enables or disables the worker of the given pallet for subsequent runs.
+	///
+	/// The pallets are named as in `construct_runtime!`, e.g. `ImOnline`, see
+	/// [`OffchainWorkerOptions::disabled_pallets`].
+	pub fn set_pallet_enabled(&self, pallet: &str, enabled: bool) {
+		let mut disabled = self.disabled_pallets.write();
+		if enabled {
+			disabled.remove(pallet);
+		} else {
+			disabled.insert(pallet.to_owned());
 		}
 	}
 }
 
+/// Notes the end of a run when dropped.
+struct RunGuard {
+	running: Arc<AtomicUsize>,
+	metrics: Option<Arc<Metrics>>,
+	started: Instant,
+}
+
+impl Drop for RunGuard {
+	fn drop(&mut self) {
+		self.running.fetch_sub(1, Ordering::SeqCst);
+		if let Some(metrics) = &self.metrics {
+			metrics.running.dec();
+			metrics.run_duration.observe(self.started.elapsed().as_secs_f64());
+		}
+	}
+}
+
+impl<Client, Storage, Block> offchain::OffchainWorkerControl for OffchainWorkers<
+	Client,
+	Storage,
+	Block,
+> where
+	Block: traits::Block,
+	Client: Send + Sync,
+	Storage: Send + Sync,
+{
+	fn set_pallet_enabled(&self, pallet: &str, enabled: bool) {
+		OffchainWorkers::set_pallet_enabled(self, pallet, enabled)
+	}
+}
+
 impl<Client, Storage, Block: traits::Block> fmt::Debug for OffchainWorkers<
 	Client,
 	Storage,
@@ -101,6 +222,15 @@ impl<Client, Storage, Block> OffchainWorkers<
 		network_state: Arc<dyn NetworkStateInfo + Send + Sync>,
 		is_validator: bool,
 	) -> impl Future<Output = ()> {
+		let number: u64 = (*header.number()).unique_saturated_into();
+		if let Some(reason) = self.options.skip_reason(number, self.running.load(Ordering::SeqCst)) {
+			debug!("Skipping offchain workers at #{}: {}", number, reason);
+			if let Some(metrics) = &self.metrics {
+				metrics.runs.with_label_values(&[reason]).inc();
+			}
+			return futures::future::Either::Right(futures::future::ready(()));
+		}
+
 		let runtime = self.client.runtime_api();
 		let at = BlockId::hash(header.hash());
 		let has_api_v1 = runtime.has_api_with::<dyn OffchainWorkerApi<Block, Error = ()>, _>(
@@ -109,9 +239,13 @@ impl<Client, Storage, Block> OffchainWorkers<
 		let has_api_v2 = runtime.has_api_with::<dyn OffchainWorkerApi<Block, Error = ()>, _>(
 			&at, |v| v == 2
 		);
-		let version = match (has_api_v1, has_api_v2) {
-			(_, Ok(true)) => 2,
-			(Ok(true), _) => 1,
+		let has_api_v3 = runtime.has_api_with::<dyn OffchainWorkerApi<Block, Error = ()>, _>(
+			&at, |v| v == 3
+		);
+		let version = match (has_api_v1, has_api_v2, has_api_v3) {
+			(_, _, Ok(true)) => 3,
+			(_, Ok(true), _) => 2,
+			(Ok(true), _, _) => 1,
 			err => {
 				let help = "Consider turning off offchain workers if they are not part of your runtime.";
 				log::error!("Unsupported Offchain Worker API version: {:?}. {}.", err, help);
@@ -125,24 +259,53 @@ impl<Client, Storage, Block> OffchainWorkers<
 				network_state.clone(),
 				is_validator,
 				self.shared_client.clone(),
+				self.metrics.clone(),
 			);
+			let disabled_pallets = self.disabled_pallets.read().iter()
+				.map(|pallet| pallet.as_bytes().to_vec())
+				.collect::<Vec<_>>();
+			if version < 3 && !disabled_pallets.is_empty() {
+				warn!("The runtime doesn't support disabling offchain workers, running all of them.");
+			}
 			debug!("Spawning offchain workers at {:?}", at);
 			let header = header.clone();
 			let client = self.client.clone();
+			let metrics = self.metrics.clone();
+			self.running.fetch_add(1, Ordering::SeqCst);
+			if let Some(metrics) = &self.metrics {
+				metrics.runs.with_label_values(&["started"]).inc();
+				metrics.running.inc();
+			}
+			let guard = RunGuard {
+				running: self.running.clone(),
+				metrics: self.metrics.clone(),
+				started: Instant::now(),
+			};
 			self.spawn_worker(move || {
+				let _guard = guard;
 				let runtime = client.runtime_api();
 				let api = Box::new(api);
 				debug!("Running offchain workers at {:?}", at);
 				let context = ExecutionContext::OffchainCall(Some(
 					(api, offchain::Capabilities::all())
 				));
-				let run = if version == 2 {
-					runtime.offchain_worker_with_context(&at, context, &header)
-				} else {
-					#[allow(deprecated)]
-					runtime.offchain_worker_before_version_2_with_context(
-						&at, context, *header.number()
-					)
+				let run = match version {
+					3 => runtime.offchain_worker_filtered_with_context(
+						&at, context, &header, disabled_pallets,
+					).map(|runs| if let Some(metrics) = &metrics {
+						for (pallet, millis) in runs {
+							metrics.pallet_run_duration
+								.with_label_values(&[&String::from_utf8_lossy(&pallet)])
+								.observe(millis as f64 / 1000.0);
+						}
+					}),
+					2 => runtime.offchain_worker_with_context(&at, context, &header),
+					_ => {
+						#[allow(deprecated)]
+						runtime.offchain_worker_before_version_2_with_context(
+							&at, context, *header.number()
+						)
+					},
 				};
 				if let Err(e) =	run {
 					log::error!("Error running offchain workers at {:?}: {:?}", at, e);
@@ -266,4 +429,54 @@ mod tests {
 		assert_eq!(pool.0.status().ready, 1);
 		assert_eq!(pool.0.ready().next().unwrap().is_propagable(), false);
 	}
+
+	#[test]
+	fn should_not_run_disabled_pallet_workers() {
+		let _ = env_logger::try_init();
+
+		let client = Arc::new(substrate_test_runtime_client::new());
+		let spawner = sp_core::testing::TaskExecutor::new();
+		let pool = TestPool(BasicPool::new_full(
+			Default::default(),
+			None,
+			spawner,
+			client.clone(),
+		));
+		let db = sc_client_db::offchain::LocalStorage::new_test();
+		let network_state = Arc::new(MockNetworkStateInfo());
+		let header = client.header(&BlockId::number(0)).unwrap().unwrap();
+		let options = OffchainWorkerOptions {
+			disabled_pallets: vec!["TestRuntime".into()],
+			..Default::default()
+		};
+
+		// when
+		let offchain = OffchainWorkers::new_with_options(client, db, options, None);
+		futures::executor::block_on(offchain.on_block_imported(&header, network_state.clone(), false));
+
+		// then
+		assert_eq!(pool.0.status().ready, 0);
+
+		// when
+		offchain.set_pallet_enabled("TestRuntime", true);
+		futures::executor::block_on(offchain.on_block_imported(&header, network_state, false));
+
+		// then
+		assert_eq!(pool.0.status().ready, 1);
+	}
+
+	#[test]
+	fn should_skip_runs_according_to_options() {
+		let periodic = OffchainWorkerOptions { period: 4, ..Default::default() };
+		assert_eq!(periodic.skip_reason(8, 3), None);
+		assert_eq!(periodic.skip_reason(9, 0), Some("skipped_period"));
+
+		let exclusive = OffchainWorkerOptions { skip_if_running: true, ..Default::default() };
+		assert_eq!(exclusive.skip_reason(9, 0), None);
+		assert_eq!(exclusive.skip_reason(9, 1), Some("skipped_running"));
+
+		let limited = OffchainWorkerOptions { max_concurrent: Some(2), ..Default::default() };
+		assert_eq!(limited.skip_reason(9, 1), None);
+		assert_eq!(limited.skip_reason(9, 2), Some("skipped_concurrency"));
+	}
 }
@@ -0,0 +1,100 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Offchain workers Prometheus metrics.
+
+use prometheus_endpoint::{
+	register, exponential_buckets, Counter, CounterVec, Gauge, Histogram, HistogramOpts,
+	HistogramVec, Opts, PrometheusError, Registry, U64,
+};
+
+/// Offchain workers Prometheus metrics.
+pub(crate) struct Metrics {
+	/// Runs of the offchain workers by outcome: started or why they were skipped.
+	pub runs: CounterVec<U64>,
+	/// Number of runs in progress.
+	pub running: Gauge<U64>,
+	/// Duration of whole runs.
+	pub run_duration: Histogram,
+	/// Duration of the runs of the workers of single pallets.
+	pub pallet_run_duration: HistogramVec,
+	/// HTTP requests started by the workers.
+	pub http_requests: Counter<U64>,
+	/// HTTP requests that ended, by status code class or error.
+	pub http_responses: CounterVec<U64>,
+}
+
+impl Metrics {
+	pub(crate) fn register(registry: &Registry) -> Result<Self, PrometheusError> {
+		Ok(Self {
+			runs: register(
+				CounterVec::new(
+					Opts::new(
+						"sub_offchain_worker_runs",
+						"Number of offchain worker runs by outcome",
+					),
+					&["outcome"],
+				)?,
+				registry,
+			)?,
+			running: register(
+				Gauge::new(
+					"sub_offchain_worker_running",
+					"Number of offchain worker runs in progress",
+				)?,
+				registry,
+			)?,
+			run_duration: register(
+				Histogram::with_opts(
+					HistogramOpts::new(
+						"sub_offchain_worker_run_duration_seconds",
+						"Duration of offchain worker runs",
+					).buckets(exponential_buckets(0.01, 2.0, 14)?),
+				)?,
+				registry,
+			)?,
+			pallet_run_duration: register(
+				HistogramVec::new(
+					HistogramOpts::new(
+						"sub_offchain_worker_pallet_run_duration_seconds",
+						"Duration of the offchain worker runs of single pallets",
+					).buckets(exponential_buckets(0.001, 2.0, 16)?),
+					&["pallet"],
+				)?,
+				registry,
+			)?,
+			http_requests: register(
+				Counter::new(
+					"sub_offchain_worker_http_requests",
+					"Number of HTTP requests started by offchain workers",
+				)?,
+				registry,
+			)?,
+			http_responses: register(
+				CounterVec::new(
+					Opts::new(
+						"sub_offchain_worker_http_responses",
+						"Number of HTTP requests of offchain workers that ended, by outcome",
+					),
+					&["outcome"],
+				)?,
+				registry,
+			)?,
+		})
+	}
+}
@@ -0,0 +1,520 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Local HTTP server to test offchain workers against.
+//!
+//! Unlike `sp_core::offchain::testing::TestOffchainExt`, which replaces the HTTP externalities,
+//! [`HttpMockServer`] is a real HTTP server on localhost, so the requests of an offchain worker
+//! go through the same HTTP client a node uses.
+//!
+//! The server either replays [`Fixture`]s, which can be written by hand or loaded from a JSON
+//! file, or forwards requests to an upstream server and records the exchanges as fixtures, so
+//! that they can be saved and replayed later. Responses can be delayed to simulate latency,
+//! and a fixture without a response never answers, which runs the request into its deadline.
+//!
+//! Only available with the `test-helpers` feature.
+//!
+//! ```ignore
+//! use sc_offchain::testing::{Fixture, HttpMockServer, MockResponse};
+//!
+//! let server = HttpMockServer::replay(vec![
+//! 	Fixture::respond("GET", "/price", MockResponse::ok(&b"{\"USD\":155.23}"[..])),
+//! ]);
+//! // point the offchain worker at `server.url("/price")` and run it, then
+//! server.assert_done();
+//! ```
+//!
+//! [`offchain_api`] provides offchain externalities that talk to the server through the HTTP
+//! client of a node, so that the offchain worker of a pallet can be run against it in tests.
+
+use std::{
+	collections::VecDeque,
+	convert::Infallible,
+	fs,
+	io,
+	net::SocketAddr,
+	path::Path,
+	sync::Arc,
+	thread,
+	time::Duration,
+};
+
+use futures::{channel::oneshot, future, FutureExt};
+use hyper::{
+	Body, Client as HyperClient, Request, Response, Server, StatusCode,
+	client::HttpConnector,
+	service::{make_service_fn, service_fn},
+};
+use hyper_rustls::HttpsConnector;
+use log::{debug, warn};
+use parking_lot::Mutex;
+use sc_network::{Multiaddr, NetworkStateInfo, PeerId};
+use serde::{Deserialize, Serialize};
+use sp_core::{Bytes, offchain::{Externalities, storage::InMemOffchainStorage}};
+
+use crate::api::{AsyncApi, SharedClient};
+
+/// Offchain externalities that make HTTP requests with the HTTP client of a node, to run an
+/// offchain worker against a [`HttpMockServer`].
+///
+/// The local storage is kept in memory. The requests are processed on a background thread
+/// until the externalities are dropped.
+pub fn offchain_api() -> impl Externalities + Send + 'static {
+	let (api, worker) = AsyncApi::new(
+		InMemOffchainStorage::default(),
+		Arc::new(NoNetwork),
+		false,
+		SharedClient::new(),
+		None,
+	);
+	thread::Builder::new()
+		.name("offchain-http-test".into())
+		.spawn(move || {
+			let mut runtime = tokio::runtime::Builder::new()
+				.basic_scheduler()
+				.enable_all()
+				.build()
+				.expect("Failed to start the runtime of the offchain HTTP worker");
+			runtime.block_on(worker.process());
+		})
+		.expect("Failed to spawn the thread of the offchain HTTP worker");
+	api
+}
+
+/// The network state of a node without network.
+struct NoNetwork;
+
+impl NetworkStateInfo for NoNetwork {
+	fn external_addresses(&self) -> Vec<Multiaddr> {
+		Vec::new()
+	}
+
+	fn local_peer_id(&self) -> PeerId {
+		PeerId::random()
+	}
+}
+
+/// A request the server expects, as part of a [`Fixture`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpectedRequest {
+	/// HTTP method, e.g. `GET`.
+	pub method: String,
+	/// Path and query of the request, e.g. `/price?currency=USD`.
+	pub path: String,
+	/// Headers the request has to contain. Header names are compared case-insensitively.
+	#[serde(default)]
+	pub headers: Vec<(String, String)>,
+	/// The body the request has to have, any body matches if `None`.
+	#[serde(default)]
+	pub body: Option<Bytes>,
+}
+
+impl ExpectedRequest {
+	fn new(method: &str, path: &str) -> Self {
+		ExpectedRequest { method: method.into(), path: path.into(), headers: Vec::new(), body: None }
+	}
+
+	fn matches(&self, request: &ReceivedRequest) -> bool {
+		self.method.eq_ignore_ascii_case(&request.method)
+			&& self.path == request.path
+			&& self.headers.iter().all(|(name, value)| request.header(name) == Some(value.as_str()))
+			&& self.body.as_ref().map_or(true, |body| body.0 == request.body)
+	}
+}
+
+/// A response of the server, as part of a [`Fixture`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MockResponse {
+	/// HTTP status code.
+	pub status: u16,
+	/// Response headers.
+	#[serde(default)]
+	pub headers: Vec<(String, String)>,
+	/// Response body.
+	#[serde(default, with = "sp_core::bytes")]
+	pub body: Vec<u8>,
+	/// Time to wait before responding, in milliseconds.
+	#[serde(default)]
+	pub delay_ms: u64,
+}
+
+impl MockResponse {
+	/// A `200 OK` response with the given body.
+	pub fn ok(body: impl Into<Vec<u8>>) -> Self {
+		Self::status(200).with_body(body)
+	}
+
+	/// An empty response with the given status code.
+	pub fn status(status: u16) -> Self {
+		MockResponse { status, headers: Vec::new(), body: Vec::new(), delay_ms: 0 }
+	}
+
+	/// Set the response body.
+	pub fn with_body(mut self, body: impl Into<Vec<u8>>) -> Self {
+		self.body = body.into();
+		self
+	}
+
+	/// Add a response header.
+	pub fn with_header(mut self, name: &str, value: &str) -> Self {
+		self.headers.push((name.into(), value.into()));
+		self
+	}
+
+	/// Respond only after the given delay.
+	pub fn with_delay(mut self, delay: Duration) -> Self {
+		self.delay_ms = delay.as_millis() as u64;
+		self
+	}
+}
+
+/// A request and the response the server answers it with.
+///
+/// Every fixture answers a single request. Fixtures are matched in order, the first one that
+/// matches a request is used.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Fixture {
+	/// The request this fixture answers.
+	pub request: ExpectedRequest,
+	/// The response to the request, the server never answers if `None`.
+	pub response: Option<MockResponse>,
+}
+
+impl Fixture {
+	/// A fixture that answers a request with the given method and path with `response`.
+	pub fn respond(method: &str, path: &str, response: MockResponse) -> Self {
+		Fixture { request: ExpectedRequest::new(method, path), response: Some(response) }
+	}
+
+	/// A fixture that never answers a request with the given method and path, so that the
+	/// request runs into its deadline.
+	pub fn timeout(method: &str, path: &str) -> Self {
+		Fixture { request: ExpectedRequest::new(method, path), response: None }
+	}
+
+	/// Only match requests that contain the given header.
+	pub fn with_header(mut self, name: &str, value: &str) -> Self {
+		self.request.headers.push((name.into(), value.into()));
+		self
+	}
+
+	/// Only match requests with the given body.
+	pub fn with_body(mut self, body: impl Into<Vec<u8>>) -> Self {
+		self.request.body = Some(Bytes(body.into()));
+		self
+	}
+}
+
+/// A request that was received by the server.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReceivedRequest {
+	/// HTTP method.
+	pub method: String,
+	/// Path and query of the request.
+	pub path: String,
+	/// Request headers.
+	pub headers: Vec<(String, String)>,
+	/// Request body.
+	#[serde(with = "sp_core::bytes")]
+	pub body: Vec<u8>,
+}
+
+impl ReceivedRequest {
+	/// Returns the value of the first header with the given name.
+	pub fn header(&self, name: &str) -> Option<&str> {
+		self.headers.iter()
+			.find(|(header, _)| header.eq_ignore_ascii_case(name))
+			.map(|(_, value)| value.as_str())
+	}
+}
+
+enum Mode {
+	Replay(VecDeque<Fixture>),
+	Record {
+		upstream: String,
+		client: HyperClient<HttpsConnector<HttpConnector>, Body>,
+		recorded: Vec<Fixture>,
+	},
+}
+
+struct State {
+	mode: Mode,
+	received: Vec<ReceivedRequest>,
+	unexpected: Vec<ReceivedRequest>,
+}
+
+/// A local HTTP server that replays or records [`Fixture`]s.
+///
+/// The server runs on a background thread until it is dropped.
+pub struct HttpMockServer {
+	addr: SocketAddr,
+	state: Arc<Mutex<State>>,
+	shutdown: Option<oneshot::Sender<()>>,
+	thread: Option<thread::JoinHandle<()>>,
+}
+
+impl HttpMockServer {
+	/// Start a server that answers requests with the given fixtures.
+	///
+	/// Requests that match none of the remaining fixtures are answered with
+	/// `501 Not Implemented` and reported by [`HttpMockServer::assert_done`].
+	pub fn replay(fixtures: Vec<Fixture>) -> Self {
+		Self::start(Mode::Replay(fixtures.into()))
+	}
+
+	/// Start a server that replays the fixtures saved in the given JSON file.
+	pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+		let fixtures = serde_json::from_slice(&fs::read(path)?)
+			.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+		Ok(Self::replay(fixtures))
+	}
+
+	/// Start a server that forwards all requests to `upstream`, e.g. `https://example.com`,
+	/// and records the exchanges.
+	pub fn record(upstream: &str) -> Self {
+		Self::start(Mode::Record {
+			upstream: upstream.trim_end_matches('/').into(),
+			client: HyperClient::builder().build(HttpsConnector::new()),
+			recorded: Vec::new(),
+		})
+	}
+
+	fn start(mode: Mode) -> Self {
+		let state = Arc::new(Mutex::new(State {
+			mode,
+			received: Vec::new(),
+			unexpected: Vec::new(),
+		}));
+		let (addr_tx, addr_rx) = std::sync::mpsc::channel();
+		let (shutdown, shutdown_rx) = oneshot::channel();
+
+		let server_state = state.clone();
+		let thread = thread::Builder::new()
+			.name("offchain-http-mock".into())
+			.spawn(move || {
+				let mut runtime = tokio::runtime::Builder::new()
+					.basic_scheduler()
+					.enable_all()
+					.build()
+					.expect("Failed to start the runtime of the HTTP mock server");
+				runtime.block_on(async move {
+					let make_service = make_service_fn(move |_| {
+						let state = server_state.clone();
+						async move {
+							Ok::<_, Infallible>(service_fn(move |request| {
+								handle(state.clone(), request).map(Ok::<_, Infallible>)
+							}))
+						}
+					});
+					let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service);
+					let _ = addr_tx.send(server.local_addr());
+					// requests that never get a response are dropped with the runtime
+					let _ = future::select(server, shutdown_rx).await;
+				});
+			})
+			.expect("Failed to spawn the thread of the HTTP mock server");
+
+		HttpMockServer {
+			addr: addr_rx.recv().expect("The HTTP mock server sends its address after binding"),
+			state,
+			shutdown: Some(shutdown),
+			thread: Some(thread),
+		}
+	}
+
+	/// The address the server listens on.
+	pub fn addr(&self) -> SocketAddr {
+		self.addr
+	}
+
+	/// The URL of the given path on this server.
+	pub fn url(&self, path: &str) -> String {
+		format!("http://{}{}", self.addr, path)
+	}
+
+	/// All requests that were received so far, in order.
+	pub fn received(&self) -> Vec<ReceivedRequest> {
+		self.state.lock().received.clone()
+	}
+
+	/// The exchanges recorded so far, empty unless the server records.
+	pub fn recorded(&self) -> Vec<Fixture> {
+		match &self.state.lock().mode {
+			Mode::Record { recorded, .. } => recorded.clone(),
+			Mode::Replay(_) => Vec::new(),
+		}
+	}
+
+	/// Save the recorded exchanges to a JSON file that can be replayed with
+	/// [`HttpMockServer::load`].
+	pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+		let json = serde_json::to_vec_pretty(&self.recorded())
+			.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+		fs::write(path, json)
+	}
+
+	/// Panics if a fixture was not used or a request matched no fixture.
+	pub fn assert_done(&self) {
+		let state = self.state.lock();
+		if let Mode::Replay(remaining) = &state.mode {
+			assert!(
+				remaining.is_empty(),
+				"Expected requests were not received: {:?}",
+				remaining.iter().map(|fixture| &fixture.request).collect::<Vec<_>>(),
+			);
+		}
+		assert!(state.unexpected.is_empty(), "Unexpected requests: {:?}", state.unexpected);
+	}
+}
+
+impl Drop for HttpMockServer {
+	fn drop(&mut self) {
+		if let Some(shutdown) = self.shutdown.take() {
+			let _ = shutdown.send(());
+		}
+		if let Some(thread) = self.thread.take() {
+			let _ = thread.join();
+		}
+	}
+}
+
+/// What to do with a received request.
+enum Action {
+	Respond(MockResponse),
+	NeverRespond,
+	Forward(HyperClient<HttpsConnector<HttpConnector>, Body>, String),
+	Reject,
+}
+
+async fn handle(state: Arc<Mutex<State>>, request: Request<Body>) -> Response<Body> {
+	let (parts, body) = request.into_parts();
+	let body = match hyper::body::to_bytes(body).await {
+		Ok(body) => body.to_vec(),
+		Err(e) => return error_response(StatusCode::BAD_REQUEST, e),
+	};
+	let received = ReceivedRequest {
+		method: parts.method.to_string(),
+		path: parts.uri.path_and_query()
+			.map_or_else(|| parts.uri.path().into(), |path| path.to_string()),
+		headers: headers(&parts.headers),
+		body,
+	};
+	debug!(target: "offchain-http-mock", "Received {} {}", received.method, received.path);
+
+	let action = {
+		let mut state = state.lock();
+		state.received.push(received.clone());
+		let action = match &mut state.mode {
+			Mode::Replay(fixtures) => {
+				match fixtures.iter().position(|fixture| fixture.request.matches(&received)) {
+					Some(position) => match fixtures.remove(position).and_then(|f| f.response) {
+						Some(response) => Action::Respond(response),
+						None => Action::NeverRespond,
+					},
+					None => Action::Reject,
+				}
+			},
+			Mode::Record { upstream, client, .. } =>
+				Action::Forward(client.clone(), format!("{}{}", upstream, received.path)),
+		};
+		if let Action::Reject = action {
+			warn!(
+				target: "offchain-http-mock",
+				"Unexpected request {} {}", received.method, received.path,
+			);
+			state.unexpected.push(received.clone());
+		}
+		action
+	};
+
+	let response = match action {
+		Action::Respond(response) => response,
+		Action::NeverRespond => future::pending().await,
+		Action::Reject =>
+			return error_response(StatusCode::NOT_IMPLEMENTED, "no matching fixture"),
+		Action::Forward(client, uri) => match forward(client, &uri, &received).await {
+			Ok(response) => {
+				if let Mode::Record { recorded, .. } = &mut state.lock().mode {
+					recorded.push(Fixture {
+						request: ExpectedRequest {
+							method: received.method.clone(),
+							path: received.path.clone(),
+							headers: Vec::new(),
+							body: Some(Bytes(received.body.clone())),
+						},
+						response: Some(response.clone()),
+					});
+				}
+				response
+			},
+			Err(e) => return error_response(StatusCode::BAD_GATEWAY, e),
+		},
+	};
+
+	if response.delay_ms > 0 {
+		tokio::time::delay_for(Duration::from_millis(response.delay_ms)).await;
+	}
+	let mut builder = Response::builder().status(response.status);
+	for (name, value) in &response.headers {
+		builder = builder.header(name.as_str(), value.as_str());
+	}
+	builder.body(Body::from(response.body))
+		.unwrap_or_else(|e| error_response(StatusCode::INTERNAL_SERVER_ERROR, e))
+}
+
+async fn forward(
+	client: HyperClient<HttpsConnector<HttpConnector>, Body>,
+	uri: &str,
+	request: &ReceivedRequest,
+) -> Result<MockResponse, String> {
+	let mut builder = Request::builder().method(request.method.as_str()).uri(uri);
+	for (name, value) in &request.headers {
+		// the upstream server has a different host
+		if !name.eq_ignore_ascii_case("host") {
+			builder = builder.header(name.as_str(), value.as_str());
+		}
+	}
+	let request = builder.body(Body::from(request.body.clone())).map_err(|e| e.to_string())?;
+
+	let response = client.request(request).await.map_err(|e| e.to_string())?;
+	let (parts, body) = response.into_parts();
+	let body = hyper::body::to_bytes(body).await.map_err(|e| e.to_string())?;
+
+	Ok(MockResponse {
+		status: parts.status.as_u16(),
+		headers: headers(&parts.headers),
+		body: body.to_vec(),
+		delay_ms: 0,
+	})
+}
+
+fn headers(headers: &hyper::HeaderMap) -> Vec<(String, String)> {
+	headers.iter()
+		.map(|(name, value)| (name.to_string(), String::from_utf8_lossy(value.as_bytes()).into()))
+		.collect()
+}
+
+fn error_response(status: StatusCode, error: impl std::fmt::Display) -> Response<Body> {
+	let mut response = Response::new(Body::from(error.to_string()));
+	*response.status_mut() = status;
+	response
+}
@@ -23,18 +23,21 @@ mod peersstate;
 
 use std::{collections::{HashSet, HashMap}, collections::VecDeque};
 use futures::prelude::*;
-use log::{debug, error, trace};
+use log::{debug, error, trace, warn};
 use serde_json::json;
+use std::{fmt, fs, io, net::IpAddr, path::{Path, PathBuf}, str::FromStr};
 use std::{pin::Pin, task::{Context, Poll}, time::Duration};
-use wasm_timer::Instant;
+use wasm_timer::{Instant, SystemTime, UNIX_EPOCH};
 use sp_utils::mpsc::{tracing_unbounded, TracingUnboundedSender, TracingUnboundedReceiver};
 
 pub use libp2p::PeerId;
 
-/// We don't accept nodes whose reputation is under this value.
-const BANNED_THRESHOLD: i32 = 82 * (i32::min_value() / 100);
+/// By default, we don't accept nodes whose reputation is under this value.
+pub const DEFAULT_BANNED_THRESHOLD: i32 = 82 * (i32::min_value() / 100);
 /// Reputation change for a node when we get disconnected from it.
-const DISCONNECT_REPUTATION_CHANGE: i32 = -256;
+const DISCONNECT_REPUTATION_CHANGE: ReputationChange = ReputationChange::new(-256, "Disconnected");
+/// Maximum number of reputation changes that are remembered for each node.
+const MAX_REPUTATION_CHANGES_HISTORY: usize = 32;
 /// Reserved peers group ID
 const RESERVED_NODES: &'static str = "reserved";
 /// Amount of time between the moment we disconnect from a node and the moment we remove it from
@@ -50,6 +53,8 @@ enum Action {
 	SetPriorityGroup(String, HashSet<PeerId>),
 	AddToPriorityGroup(String, PeerId),
 	RemoveFromPriorityGroup(String, PeerId),
+	Ban(BanTarget, Option<Duration>, String),
+	Unban(BanTarget),
 }
 
 /// Description of a reputation adjustment for a node.
@@ -73,6 +78,75 @@ impl ReputationChange {
 	}
 }
 
+/// Node or address that can be banned with [`PeersetHandle::ban`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum BanTarget {
+	/// Node with the given identity.
+	Peer(PeerId),
+	/// Any node that we connect to, or that connects to us, at the given IP address.
+	Ip(IpAddr),
+}
+
+impl fmt::Display for BanTarget {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			BanTarget::Peer(peer_id) => write!(f, "{}", peer_id.to_base58()),
+			BanTarget::Ip(ip) => write!(f, "{}", ip),
+		}
+	}
+}
+
+impl FromStr for BanTarget {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		if let Ok(peer_id) = s.parse::<PeerId>() {
+			return Ok(BanTarget::Peer(peer_id))
+		}
+
+		s.parse::<IpAddr>()
+			.map(BanTarget::Ip)
+			.map_err(|_| format!("{:?} is neither a PeerId nor an IP address", s))
+	}
+}
+
+/// A ban put in place with [`PeersetHandle::ban`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ban {
+	/// What is banned.
+	pub target: BanTarget,
+	/// When the ban expires. `None` if the ban only ends with a call to [`PeersetHandle::unban`].
+	pub until: Option<SystemTime>,
+	/// Why the ban was put in place.
+	pub reason: String,
+	/// Banned address the node connected from, if the ban of the node derives from it. Lifting
+	/// the ban of that address lifts this ban as well.
+	pub source: Option<IpAddr>,
+}
+
+impl Ban {
+	/// Returns true if the ban has expired at the given time.
+	fn is_expired(&self, now: SystemTime) -> bool {
+		self.until.map_or(false, |until| until <= now)
+	}
+}
+
+/// Reputation of a node known to the peerset, as returned by [`Peerset::peers_reputation`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeerReputation {
+	/// Identity of the node.
+	pub peer_id: PeerId,
+	/// Current reputation of the node.
+	pub reputation: i32,
+	/// True if the peerset wants us to be connected to the node.
+	pub connected: bool,
+	/// True if the node is banned.
+	pub banned: bool,
+	/// Most recent reputation changes of the node, oldest first, along with the time that has
+	/// elapsed since they happened.
+	pub changes: Vec<(Duration, ReputationChange)>,
+}
+
 /// Shared handle to the peer set manager (PSM). Distributed around the code.
 #[derive(Debug, Clone)]
 pub struct PeersetHandle {
@@ -122,6 +196,22 @@ impl PeersetHandle {
 	pub fn remove_from_priority_group(&self, group_id: String, peer_id: PeerId) {
 		let _ = self.tx.unbounded_send(Action::RemoveFromPriorityGroup(group_id, peer_id));
 	}
+
+	/// Bans a node or an IP address for the given duration, or until [`PeersetHandle::unban`] is
+	/// called if `duration` is `None` or too long to be represented.
+	///
+	/// Banned nodes are disconnected, and no connection with them is attempted or accepted,
+	/// even if they are reserved.
+	pub fn ban(&self, target: BanTarget, duration: Option<Duration>, reason: String) {
+		let _ = self.tx.unbounded_send(Action::Ban(target, duration, reason));
+	}
+
+	/// Lifts a ban previously put in place with [`PeersetHandle::ban`].
+	///
+	/// Has no effect if the target wasn't banned.
+	pub fn unban(&self, target: BanTarget) {
+		let _ = self.tx.unbounded_send(Action::Unban(target));
+	}
 }
 
 /// Message that can be sent by the peer set manager (PSM).
@@ -174,6 +264,13 @@ pub struct PeersetConfig {
 	/// > **Note**: Keep in mind that the networking has to know an address for these nodes,
 	/// >			otherwise it will not be able to connect to them.
 	pub priority_groups: Vec<(String, HashSet<PeerId>)>,
+
+	/// Reputation under which nodes are disconnected and refused.
+	pub banned_threshold: i32,
+
+	/// File the list of bans is loaded from and saved to. If `None`, bans don't survive a
+	/// restart.
+	pub ban_list_path: Option<PathBuf>,
 }
 
 /// Side of the peer set manager owned by the network. In other words, the "receiving" side.
@@ -199,6 +296,15 @@ pub struct Peerset {
 	created: Instant,
 	/// Last time when we updated the reputations of connected nodes.
 	latest_time_update: Instant,
+	/// Reputation under which we don't accept nodes.
+	banned_threshold: i32,
+	/// Nodes and addresses that are banned. Banned nodes have their reputation pinned to the
+	/// minimum.
+	bans: HashMap<BanTarget, Ban>,
+	/// File the list of bans is saved to.
+	ban_list_path: Option<PathBuf>,
+	/// Most recent reputation changes of the nodes we know about.
+	reputation_changes: HashMap<PeerId, VecDeque<(Instant, ReputationChange)>>,
 }
 
 impl Peerset {
@@ -221,12 +327,23 @@ impl Peerset {
 			message_queue: VecDeque::new(),
 			created: now,
 			latest_time_update: now,
+			banned_threshold: config.banned_threshold,
+			bans: HashMap::new(),
+			ban_list_path: config.ban_list_path,
+			reputation_changes: HashMap::new(),
 		};
 
 		for node in config.priority_groups.into_iter().flat_map(|(_, l)| l) {
 			peerset.data.add_no_slot_node(node);
 		}
 
+		let saved_bans = peerset.ban_list_path.as_ref()
+			.map(|path| load_ban_list(path))
+			.unwrap_or_default();
+		for ban in saved_bans {
+			peerset.insert_ban(ban);
+		}
+
 		for peer_id in config.bootnodes {
 			if let peersstate::Peer::Unknown(entry) = peerset.data.peer(&peer_id) {
 				entry.discover();
@@ -331,11 +448,12 @@ impl Peerset {
 	fn on_report_peer(&mut self, peer_id: PeerId, change: ReputationChange) {
 		// We want reputations to be up-to-date before adjusting them.
 		self.update_time();
+		self.record_reputation_change(&peer_id, change);
 
 		match self.data.peer(&peer_id) {
 			peersstate::Peer::Connected(mut peer) => {
 				peer.add_reputation(change.value);
-				if peer.reputation() < BANNED_THRESHOLD {
+				if peer.reputation() < self.banned_threshold {
 					debug!(target: "peerset", "Report {}: {:+} to {}. Reason: {}, Disconnecting",
 						peer_id, change.value, peer.reputation(), change.reason
 					);
@@ -352,9 +470,130 @@ impl Peerset {
 		}
 	}
 
+	/// Remembers a reputation change of a node, forgetting the oldest one if necessary.
+	fn record_reputation_change(&mut self, peer_id: &PeerId, change: ReputationChange) {
+		let changes = self.reputation_changes.entry(peer_id.clone()).or_default();
+		if changes.len() >= MAX_REPUTATION_CHANGES_HISTORY {
+			changes.pop_front();
+		}
+		changes.push_back((Instant::now(), change));
+	}
+
+	fn on_ban(&mut self, target: BanTarget, duration: Option<Duration>, reason: String) {
+		self.update_time();
+		let until = duration.and_then(|duration| SystemTime::now().checked_add(duration));
+		self.insert_ban(Ban { target, until, reason, source: None });
+		self.save_ban_list();
+	}
+
+	fn on_unban(&mut self, target: BanTarget) {
+		if self.bans.remove(&target).is_none() {
+			return
+		}
+
+		debug!(target: "peerset", "Unbanning {}", target);
+		match &target {
+			BanTarget::Peer(peer_id) => self.reset_reputation(peer_id),
+			BanTarget::Ip(ip) => {
+				// Lift the bans of the nodes that were banned because they connected from there.
+				let derived = self.bans.values()
+					.filter(|ban| ban.source == Some(*ip))
+					.map(|ban| ban.target.clone())
+					.collect::<Vec<_>>();
+				for target in derived {
+					debug!(target: "peerset", "Unbanning {}", target);
+					self.bans.remove(&target);
+					if let BanTarget::Peer(peer_id) = &target {
+						self.reset_reputation(peer_id);
+					}
+				}
+			}
+		}
+
+		self.save_ban_list();
+		self.alloc_slots();
+	}
+
+	/// Inserts a ban, and disconnects from the banned node if necessary.
+	fn insert_ban(&mut self, ban: Ban) {
+		debug!(target: "peerset", "Banning {} until {:?}. Reason: {}", ban.target, ban.until, ban.reason);
+
+		if let BanTarget::Peer(peer_id) = &ban.target {
+			match self.data.peer(peer_id) {
+				peersstate::Peer::Connected(mut peer) => {
+					peer.set_reputation(i32::min_value());
+					peer.disconnect();
+					self.message_queue.push_back(Message::Drop(peer_id.clone()));
+				}
+				peersstate::Peer::NotConnected(mut peer) => peer.set_reputation(i32::min_value()),
+				peersstate::Peer::Unknown(peer) => peer.discover().set_reputation(i32::min_value()),
+			}
+		}
+
+		self.bans.insert(ban.target.clone(), ban);
+	}
+
+	/// Removes the bans that have expired.
+	fn remove_expired_bans(&mut self) {
+		let now = SystemTime::now();
+		let expired = self.bans.values()
+			.filter(|ban| ban.is_expired(now))
+			.map(|ban| ban.target.clone())
+			.collect::<Vec<_>>();
+
+		if expired.is_empty() {
+			return
+		}
+
+		for target in expired {
+			debug!(target: "peerset", "Ban of {} expired", target);
+			self.bans.remove(&target);
+			if let BanTarget::Peer(peer_id) = &target {
+				self.reset_reputation(peer_id);
+			}
+		}
+
+		self.save_ban_list();
+	}
+
+	/// Gives a node whose ban has been lifted a neutral reputation.
+	fn reset_reputation(&mut self, peer_id: &PeerId) {
+		if let peersstate::Peer::NotConnected(mut peer) = self.data.peer(peer_id) {
+			peer.set_reputation(0);
+		}
+	}
+
+	/// Returns true if the given node is banned.
+	///
+	/// Doesn't take expiration into account, which is handled by `update_time`.
+	fn is_banned(&self, peer_id: &PeerId) -> bool {
+		self.bans.contains_key(&BanTarget::Peer(peer_id.clone()))
+	}
+
+	/// Writes the list of bans to `ban_list_path`, if any.
+	fn save_ban_list(&self) {
+		let path = match &self.ban_list_path {
+			Some(path) => path,
+			None => return,
+		};
+
+		let bans = self.bans.values().map(|ban| json!({
+			"target": ban.target.to_string(),
+			"until": ban.until.map(|until| until.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()),
+			"reason": ban.reason,
+			"source": ban.source.map(|ip| ip.to_string()),
+		})).collect::<Vec<_>>();
+
+		if let Err(err) = fs::write(path, serde_json::Value::Array(bans).to_string()) {
+			warn!(target: "peerset", "Failed to save ban list to {}: {}", path.display(), err);
+		}
+	}
+
 	/// Updates the value of `self.latest_time_update` and performs all the updates that happen
 	/// over time, such as reputation increases for staying connected.
 	fn update_time(&mut self) {
+		self.remove_expired_bans();
+
 		let now = Instant::now();
 
 		// We basically do `(now - self.latest_update).as_secs()`, except that by the way we do it
@@ -372,6 +611,11 @@ impl Peerset {
 		// empirically determine a value of `k` that looks correct.
 		for _ in 0..secs_diff {
 			for peer_id in self.data.peers().cloned().collect::<Vec<_>>() {
+				// The reputation of banned nodes stays at the minimum for the duration of the ban.
+				if self.is_banned(&peer_id) {
+					continue;
+				}
+
 				// We use `k = 0.98`, so we divide by `50`. With that value, it takes 34.3 seconds
 				// to reduce the reputation by half.
 				fn reput_tick(reput: i32) -> i32 {
@@ -395,6 +639,7 @@ impl Peerset {
 							peer.last_connected_or_discovered() + FORGET_AFTER < now
 						{
 							peer.forget_peer();
+							self.reputation_changes.remove(&peer_id);
 						} else {
 							let before = peer.reputation();
 							let after = reput_tick(before);
@@ -416,12 +661,14 @@ impl Peerset {
 		loop {
 			let next = {
 				let data = &mut self.data;
+				let bans = &self.bans;
 				self.priority_groups
 					.get(RESERVED_NODES)
 					.into_iter()
 					.flatten()
 					.filter(move |n| {
-						data.peer(n).into_connected().is_none()
+						data.peer(n).into_connected().is_none() &&
+							!bans.contains_key(&BanTarget::Peer((*n).clone()))
 					})
 					.next()
 					.cloned()
@@ -456,11 +703,13 @@ impl Peerset {
 		loop {
 			let next = {
 				let data = &mut self.data;
+				let bans = &self.bans;
 				self.priority_groups
 					.values()
 					.flatten()
 					.filter(move |n| {
-						data.peer(n).into_connected().is_none()
+						data.peer(n).into_connected().is_none() &&
+							!bans.contains_key(&BanTarget::Peer((*n).clone()))
 					})
 					.next()
 					.cloned()
@@ -494,8 +743,11 @@ impl Peerset {
 				None => break,	// No known node to add.
 			};
 
-			// Don't connect to nodes with an abysmal reputation.
-			if next.reputation() < BANNED_THRESHOLD {
+			// Don't connect to nodes with an abysmal reputation or that are banned. Banned nodes
+			// have the lowest possible reputation, so there is no better node left to try.
+			if next.reputation() < self.banned_threshold ||
+				self.bans.contains_key(&BanTarget::Peer(next.peer_id().clone()))
+			{
 				break;
 			}
 
@@ -520,6 +772,11 @@ impl Peerset {
 		trace!(target: "peerset", "Incoming {:?}", peer_id);
 		self.update_time();
 
+		if self.is_banned(&peer_id) {
+			self.message_queue.push_back(Message::Reject(index));
+			return;
+		}
+
 		if self.reserved_only {
 			if !self.priority_groups.get(RESERVED_NODES).map_or(false, |n| n.contains(&peer_id)) {
 				self.message_queue.push_back(Message::Reject(index));
@@ -537,7 +794,7 @@ impl Peerset {
 			peersstate::Peer::Unknown(entry) => entry.discover(),
 		};
 
-		if not_connected.reputation() < BANNED_THRESHOLD {
+		if not_connected.reputation() < self.banned_threshold {
 			self.message_queue.push_back(Message::Reject(index));
 			return
 		}
@@ -561,8 +818,9 @@ impl Peerset {
 		match self.data.peer(&peer_id) {
 			peersstate::Peer::Connected(mut entry) => {
 				// Decrease the node's reputation so that we don't try it again and again and again.
-				entry.add_reputation(DISCONNECT_REPUTATION_CHANGE);
+				entry.add_reputation(DISCONNECT_REPUTATION_CHANGE.value);
 				entry.disconnect();
+				self.record_reputation_change(&peer_id, DISCONNECT_REPUTATION_CHANGE);
 			}
 			peersstate::Peer::NotConnected(_) | peersstate::Peer::Unknown(_) =>
 				error!(target: "peerset", "Received dropped() for non-connected node"),
@@ -598,6 +856,67 @@ impl Peerset {
 		let _ = self.tx.unbounded_send(Action::ReportPeer(peer_id, score_diff));
 	}
 
+	/// Indicate the IP address of a node we have established a connection with.
+	///
+	/// If that address is banned, the node gets banned as well until the ban of the address
+	/// expires or is lifted.
+	pub fn connected_from(&mut self, peer_id: PeerId, ip: IpAddr) {
+		self.update_time();
+
+		if self.is_banned(&peer_id) {
+			return
+		}
+
+		let until = match self.bans.get(&BanTarget::Ip(ip)) {
+			Some(ban) => ban.until,
+			None => return,
+		};
+
+		self.insert_ban(Ban {
+			target: BanTarget::Peer(peer_id),
+			until,
+			reason: format!("Connected from banned address {}", ip),
+			source: Some(ip),
+		});
+		self.save_ban_list();
+	}
+
+	/// Returns the reputation, the connection state and the most recent reputation changes of
+	/// all the nodes we know about.
+	pub fn peers_reputation(&mut self) -> Vec<PeerReputation> {
+		self.update_time();
+
+		let now = Instant::now();
+		self.data.peers().cloned().collect::<Vec<_>>().into_iter().map(|peer_id| {
+			let (reputation, connected) = match self.data.peer(&peer_id) {
+				peersstate::Peer::Connected(entry) => (entry.reputation(), true),
+				peersstate::Peer::NotConnected(entry) => (entry.reputation(), false),
+				peersstate::Peer::Unknown(_) =>
+					unreachable!("We iterate over the known peers; QED")
+			};
+
+			let changes = self.reputation_changes.get(&peer_id)
+				.into_iter()
+				.flatten()
+				.map(|(at, change)| (now - *at, *change))
+				.collect();
+
+			PeerReputation {
+				banned: self.is_banned(&peer_id),
+				peer_id,
+				reputation,
+				connected,
+				changes,
+			}
+		}).collect()
+	}
+
+	/// Returns the list of bans that are currently in place.
+	pub fn bans(&mut self) -> Vec<Ban> {
+		self.update_time();
+		self.bans.values().cloned().collect()
+	}
+
 	/// Produces a JSON object containing the state of the peerset manager, for debugging purposes.
 	pub fn debug_info(&mut self) -> serde_json::Value {
 		self.update_time();
@@ -620,6 +939,12 @@ impl Peerset {
 				(peer_id.to_base58(), state)
 			}).collect::<HashMap<_, _>>(),
 			"reserved_only": self.reserved_only,
+			"bans": self.bans.values().map(|ban| json!({
+				"target": ban.target.to_string(),
+				"until": ban.until.map(|until| until.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()),
+				"reason": ban.reason,
+				"source": ban.source.map(|ip| ip.to_string()),
+			})).collect::<Vec<_>>(),
 			"message_queue": self.message_queue.len(),
 		})
 	}
@@ -629,6 +954,11 @@ impl Peerset {
 		self.data.peers().len()
 	}
 
+	/// Returns the reserved peers, including the ones added with `add_reserved_peer`.
+	pub fn reserved_peers(&self) -> impl Iterator<Item = &PeerId> {
+		self.priority_groups.get(RESERVED_NODES).into_iter().flatten()
+	}
+
 	/// Returns the content of a priority group.
 	pub fn priority_group(&self, group_id: &str) -> Option<impl ExactSizeIterator<Item = &PeerId>> {
 		self.priority_groups.get(group_id).map(|l| l.iter())
@@ -665,17 +995,62 @@ impl Stream for Peerset {
 					self.on_add_to_priority_group(&group_id, peer_id),
 				Action::RemoveFromPriorityGroup(group_id, peer_id) =>
 					self.on_remove_from_priority_group(&group_id, peer_id),
+				Action::Ban(target, duration, reason) =>
+					self.on_ban(target, duration, reason),
+				Action::Unban(target) =>
+					self.on_unban(target),
 			}
 		}
 	}
 }
 
+/// Loads the list of bans saved at the given path, skipping the ones that have expired.
+fn load_ban_list(path: &Path) -> Vec<Ban> {
+	let content = match fs::read(path) {
+		Ok(content) => content,
+		Err(err) if err.kind() == io::ErrorKind::NotFound => return Vec::new(),
+		Err(err) => {
+			warn!(target: "peerset", "Failed to read ban list from {}: {}", path.display(), err);
+			return Vec::new()
+		}
+	};
+
+	let entries = match serde_json::from_slice::<Vec<serde_json::Value>>(&content) {
+		Ok(entries) => entries,
+		Err(err) => {
+			warn!(target: "peerset", "Failed to decode ban list from {}: {}", path.display(), err);
+			return Vec::new()
+		}
+	};
+
+	let now = SystemTime::now();
+	entries.into_iter()
+		.filter_map(|entry| {
+			let target = entry.get("target")?.as_str()?.parse().ok()?;
+			let until = match entry.get("until")? {
+				serde_json::Value::Null => None,
+				until => Some(UNIX_EPOCH.checked_add(Duration::from_secs(until.as_u64()?))?),
+			};
+			let reason = entry.get("reason")?.as_str()?.to_owned();
+			let source = match entry.get("source") {
+				None | Some(serde_json::Value::Null) => None,
+				Some(source) => Some(source.as_str()?.parse().ok()?),
+			};
+			Some(Ban { target, until, reason, source })
+		})
+		.filter(|ban| !ban.is_expired(now))
+		.collect()
+}
+
 #[cfg(test)]
 mod tests {
 	use libp2p::PeerId;
 	use futures::prelude::*;
-	use super::{PeersetConfig, Peerset, Message, IncomingIndex, ReputationChange, BANNED_THRESHOLD};
-	use std::{pin::Pin, task::Poll, thread, time::Duration};
+	use super::{
+		BanTarget, PeersetConfig, Peerset, Message, IncomingIndex, ReputationChange,
+		DEFAULT_BANNED_THRESHOLD,
+	};
+	use std::{net::IpAddr, pin::Pin, task::Poll, thread, time::Duration};
 
 	fn assert_messages(mut peerset: Peerset, messages: Vec<Message>) -> Peerset {
 		for expected_message in messages {
@@ -703,6 +1078,8 @@ mod tests {
 			bootnodes: vec![bootnode],
 			reserved_only: true,
 			priority_groups: Vec::new(),
+			banned_threshold: DEFAULT_BANNED_THRESHOLD,
+			ban_list_path: None,
 		};
 
 		let (peerset, handle) = Peerset::from_config(config);
@@ -731,6 +1108,8 @@ mod tests {
 			bootnodes: vec![bootnode.clone()],
 			reserved_only: false,
 			priority_groups: Vec::new(),
+			banned_threshold: DEFAULT_BANNED_THRESHOLD,
+			ban_list_path: None,
 		};
 
 		let (mut peerset, _handle) = Peerset::from_config(config);
@@ -757,6 +1136,8 @@ mod tests {
 			bootnodes: vec![],
 			reserved_only: true,
 			priority_groups: vec![],
+			banned_threshold: DEFAULT_BANNED_THRESHOLD,
+			ban_list_path: None,
 		};
 
 		let (mut peerset, _) = Peerset::from_config(config);
@@ -778,6 +1159,8 @@ mod tests {
 			bootnodes: vec![bootnode.clone()],
 			reserved_only: false,
 			priority_groups: vec![],
+			banned_threshold: DEFAULT_BANNED_THRESHOLD,
+			ban_list_path: None,
 		};
 
 		let (mut peerset, _handle) = Peerset::from_config(config);
@@ -799,11 +1182,13 @@ mod tests {
 			bootnodes: vec![],
 			reserved_only: false,
 			priority_groups: vec![],
+			banned_threshold: DEFAULT_BANNED_THRESHOLD,
+			ban_list_path: None,
 		});
 
 		// We ban a node by setting its reputation under the threshold.
 		let peer_id = PeerId::random();
-		handle.report_peer(peer_id.clone(), ReputationChange::new(BANNED_THRESHOLD - 1, ""));
+		handle.report_peer(peer_id.clone(), ReputationChange::new(DEFAULT_BANNED_THRESHOLD - 1, ""));
 
 		let fut = futures::future::poll_fn(move |cx| {
 			// We need one polling for the message to be processed.
@@ -831,4 +1216,206 @@ mod tests {
 
 		futures::executor::block_on(fut);
 	}
+
+	#[test]
+	fn test_peerset_ban_and_unban() {
+		let bootnode = PeerId::random();
+		let (peerset, handle) = Peerset::from_config(PeersetConfig {
+			in_peers: 25,
+			out_peers: 25,
+			bootnodes: vec![bootnode.clone()],
+			reserved_only: false,
+			priority_groups: vec![],
+			banned_threshold: DEFAULT_BANNED_THRESHOLD,
+			ban_list_path: None,
+		});
+		let peerset = assert_messages(peerset, vec![Message::Connect(bootnode.clone())]);
+
+		// Banning the node disconnects it.
+		handle.ban(BanTarget::Peer(bootnode.clone()), None, "test".into());
+		let mut peerset = assert_messages(peerset, vec![Message::Drop(bootnode.clone())]);
+		assert!(peerset.peers_reputation().iter().any(|p| p.peer_id == bootnode && p.banned));
+
+		// Incoming connections from the node are refused.
+		peerset.incoming(bootnode.clone(), IncomingIndex(1));
+		let peerset = assert_messages(peerset, vec![Message::Reject(IncomingIndex(1))]);
+
+		// Once unbanned, we connect to the node again.
+		handle.unban(BanTarget::Peer(bootnode.clone()));
+		assert_messages(peerset, vec![Message::Connect(bootnode)]);
+	}
+
+	#[test]
+	fn test_peerset_ban_list_is_saved() {
+		let path = std::env::temp_dir()
+			.join(format!("peerset-ban-list-{}.json", rand::random::<u64>()));
+		let config = || PeersetConfig {
+			in_peers: 25,
+			out_peers: 25,
+			bootnodes: vec![],
+			reserved_only: false,
+			priority_groups: vec![],
+			banned_threshold: DEFAULT_BANNED_THRESHOLD,
+			ban_list_path: Some(path.clone()),
+		};
+
+		let banned_peer = PeerId::random();
+		let banned_ip: IpAddr = "198.51.100.19".parse().unwrap();
+
+		let (mut peerset, handle) = Peerset::from_config(config());
+		handle.ban(BanTarget::Peer(banned_peer.clone()), Some(Duration::from_secs(3600)), "misbehaving".into());
+		handle.ban(BanTarget::Ip(banned_ip), None, "spam".into());
+		futures::executor::block_on(futures::future::poll_fn(|cx| {
+			// We need one polling for the messages to be processed.
+			assert_eq!(Stream::poll_next(Pin::new(&mut peerset), cx), Poll::Pending);
+			Poll::Ready(())
+		}));
+		drop(peerset);
+
+		// The bans survive a restart of the peerset.
+		let (mut peerset, _) = Peerset::from_config(config());
+		let mut bans = peerset.bans();
+		bans.sort_by_key(|ban| ban.reason.clone());
+		assert_eq!(bans.len(), 2);
+		assert_eq!(bans[0].target, BanTarget::Peer(banned_peer.clone()));
+		assert!(bans[0].until.is_some());
+		assert_eq!(bans[1].target, BanTarget::Ip(banned_ip));
+		assert_eq!(bans[1].until, None);
+
+		// A node connecting from a banned address is banned as well.
+		let other_peer = PeerId::random();
+		peerset.connected_from(other_peer.clone(), banned_ip);
+
+		peerset.incoming(banned_peer, IncomingIndex(1));
+		peerset.incoming(other_peer, IncomingIndex(2));
+		let _ = std::fs::remove_file(&path);
+
+		assert_messages(peerset, vec![
+			Message::Reject(IncomingIndex(1)),
+			Message::Reject(IncomingIndex(2)),
+		]);
+	}
+
+	#[test]
+	fn test_peerset_ban_with_overlong_duration_is_permanent() {
+		let path = std::env::temp_dir()
+			.join(format!("peerset-ban-list-{}.json", rand::random::<u64>()));
+		let (mut peerset, handle) = Peerset::from_config(PeersetConfig {
+			in_peers: 25,
+			out_peers: 25,
+			bootnodes: vec![],
+			reserved_only: false,
+			priority_groups: vec![],
+			banned_threshold: DEFAULT_BANNED_THRESHOLD,
+			ban_list_path: Some(path.clone()),
+		});
+
+		let banned_peer = PeerId::random();
+		let duration = Duration::from_secs(u64::max_value());
+		handle.ban(BanTarget::Peer(banned_peer.clone()), Some(duration), "test".into());
+		futures::executor::block_on(futures::future::poll_fn(|cx| {
+			assert_eq!(Stream::poll_next(Pin::new(&mut peerset), cx), Poll::Pending);
+			Poll::Ready(())
+		}));
+
+		let bans = peerset.bans();
+		assert_eq!(bans.len(), 1);
+		assert_eq!(bans[0].target, BanTarget::Peer(banned_peer));
+		assert_eq!(bans[0].until, None);
+		let _ = std::fs::remove_file(&path);
+	}
+
+	#[test]
+	fn test_peerset_ban_list_skips_unrepresentable_expiries() {
+		let path = std::env::temp_dir()
+			.join(format!("peerset-ban-list-{}.json", rand::random::<u64>()));
+		let banned_peer = PeerId::random();
+		let ban_list = serde_json::json!([
+			{ "target": banned_peer.to_base58(), "until": null, "reason": "test" },
+			{ "target": "198.51.100.19", "until": u64::max_value(), "reason": "test" },
+		]);
+		std::fs::write(&path, ban_list.to_string()).unwrap();
+
+		let bans = super::load_ban_list(&path);
+		let _ = std::fs::remove_file(&path);
+
+		assert_eq!(bans.len(), 1);
+		assert_eq!(bans[0].target, BanTarget::Peer(banned_peer));
+	}
+
+	#[test]
+	fn test_peerset_unbanning_ip_unbans_derived_peers() {
+		let path = std::env::temp_dir()
+			.join(format!("peerset-ban-list-{}.json", rand::random::<u64>()));
+		let config = || PeersetConfig {
+			in_peers: 25,
+			out_peers: 25,
+			bootnodes: vec![],
+			reserved_only: false,
+			priority_groups: vec![],
+			banned_threshold: DEFAULT_BANNED_THRESHOLD,
+			ban_list_path: Some(path.clone()),
+		};
+
+		let banned_ip: IpAddr = "198.51.100.19".parse().unwrap();
+		let peer = PeerId::random();
+
+		let (mut peerset, handle) = Peerset::from_config(config());
+		handle.ban(BanTarget::Ip(banned_ip), None, "spam".into());
+		futures::executor::block_on(futures::future::poll_fn(|cx| {
+			// We need one polling for the messages to be processed.
+			assert_eq!(Stream::poll_next(Pin::new(&mut peerset), cx), Poll::Pending);
+			Poll::Ready(())
+		}));
+		peerset.connected_from(peer.clone(), banned_ip);
+		drop(peerset);
+
+		// The derived ban survives a restart, and still knows where it comes from.
+		let (mut peerset, handle) = Peerset::from_config(config());
+		let derived = peerset.bans().into_iter()
+			.find(|ban| ban.target == BanTarget::Peer(peer.clone()))
+			.expect("peer connected from a banned address");
+		assert_eq!(derived.source, Some(banned_ip));
+
+		// Once the address is unbanned, we connect to the node again.
+		handle.unban(BanTarget::Ip(banned_ip));
+		let mut peerset = assert_messages(peerset, vec![Message::Connect(peer)]);
+		let _ = std::fs::remove_file(&path);
+		assert!(peerset.bans().is_empty());
+	}
+
+	#[test]
+	fn test_peerset_reputation_changes_are_remembered() {
+		let (mut peerset, _) = Peerset::from_config(PeersetConfig {
+			in_peers: 25,
+			out_peers: 25,
+			bootnodes: vec![],
+			reserved_only: false,
+			priority_groups: vec![],
+			banned_threshold: DEFAULT_BANNED_THRESHOLD,
+			ban_list_path: None,
+		});
+
+		let peer_id = PeerId::random();
+		peerset.report_peer(peer_id.clone(), ReputationChange::new(-10, "Bad message"));
+		peerset.report_peer(peer_id.clone(), ReputationChange::new(5, "Good transaction"));
+		futures::executor::block_on(futures::future::poll_fn(|cx| {
+			// We need one polling for the messages to be processed.
+			assert_eq!(Stream::poll_next(Pin::new(&mut peerset), cx), Poll::Pending);
+			Poll::Ready(())
+		}));
+
+		let reputations = peerset.peers_reputation();
+		assert_eq!(reputations.len(), 1);
+		assert_eq!(reputations[0].peer_id, peer_id);
+		assert!(!reputations[0].connected);
+		assert!(!reputations[0].banned);
+		assert_eq!(
+			reputations[0].changes.iter().map(|(_, change)| *change).collect::<Vec<_>>(),
+			vec![
+				ReputationChange::new(-10, "Bad message"),
+				ReputationChange::new(5, "Good transaction"),
+			],
+		);
+	}
 }
@@ -57,6 +57,8 @@ fn test_once() {
 		reserved_only: Uniform::new_inclusive(0, 10).sample(&mut rng) == 0,
 		in_peers: Uniform::new_inclusive(0, 25).sample(&mut rng),
 		out_peers: Uniform::new_inclusive(0, 25).sample(&mut rng),
+		banned_threshold: sc_peerset::DEFAULT_BANNED_THRESHOLD,
+		ban_list_path: None,
 	});
 
 	futures::executor::block_on(futures::future::poll_fn(move |cx| {
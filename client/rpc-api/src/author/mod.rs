@@ -36,8 +36,10 @@ pub trait AuthorApi<Hash, BlockHash> {
 	type Metadata;
 
 	/// Submit hex-encoded extrinsic for inclusion in block.
+	///
+	/// If `do_not_gossip` is `true`, the extrinsic is never propagated to other nodes.
 	#[rpc(name = "author_submitExtrinsic")]
-	fn submit_extrinsic(&self, extrinsic: Bytes) -> FutureResult<Hash>;
+	fn submit_extrinsic(&self, extrinsic: Bytes, do_not_gossip: Option<bool>) -> FutureResult<Hash>;
 
 	/// Insert a key into the keystore.
 	#[rpc(name = "author_insertKey")]
@@ -29,6 +29,9 @@ pub enum Error {
 	/// Unavailable storage kind error.
 	#[display(fmt="This storage kind is not available yet.")]
 	UnavailableStorageKind,
+	/// The node doesn't run offchain workers.
+	#[display(fmt="Offchain workers are not enabled.")]
+	WorkersDisabled,
 	/// Call to an unsafe RPC was denied.
 	UnsafeRpcCalled(crate::policy::UnsafeRpcError),
 }
@@ -53,6 +56,11 @@ impl From<Error> for rpc::Error {
 				message: "This storage kind is not available yet" .into(),
 				data: None,
 			},
+			Error::WorkersDisabled => rpc::Error {
+				code: rpc::ErrorCode::ServerError(BASE_ERROR + 2),
+				message: "Offchain workers are not enabled".into(),
+				data: None,
+			},
 			Error::UnsafeRpcCalled(e) => e.into(),
 		}
 	}
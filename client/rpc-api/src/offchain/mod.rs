@@ -36,4 +36,10 @@ pub trait OffchainApi {
 	/// Get offchain local storage under given key and prefix.
 	#[rpc(name = "offchain_localStorageGet")]
 	fn get_local_storage(&self, kind: StorageKind, key: Bytes) -> Result<Option<Bytes>>;
+
+	/// Enable or disable the offchain worker of the given pallet for subsequent blocks.
+	///
+	/// Pallets are named as in the runtime's `construct_runtime!`, e.g. `ImOnline`.
+	#[rpc(name = "offchain_setWorkerEnabled")]
+	fn set_worker_enabled(&self, pallet: String, enabled: bool) -> Result<()>;
 }
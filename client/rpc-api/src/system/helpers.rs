@@ -75,6 +75,34 @@ pub struct PeerInfo<Hash, Number> {
 	pub best_number: Number,
 }
 
+/// Reputation of a node known to the peerset manager
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeerReputation {
+	/// Peer ID
+	pub peer_id: String,
+	/// Current reputation
+	pub reputation: i32,
+	/// Is the peerset manager connected to this peer
+	pub connected: bool,
+	/// Is this peer banned
+	pub banned: bool,
+	/// Most recent reputation changes, oldest first
+	pub reputation_changes: Vec<ReputationChange>,
+}
+
+/// Adjustment of the reputation of a node
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReputationChange {
+	/// Reputation delta
+	pub value: i32,
+	/// Reason for the reputation change
+	pub reason: String,
+	/// Number of seconds elapsed since the change
+	pub seconds_ago: u64,
+}
+
 /// The role the node is running as
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub enum NodeRole {
@@ -117,4 +145,22 @@ mod tests {
 			r#"{"peerId":"2","roles":"a","protocolVersion":2,"bestHash":5,"bestNumber":6}"#,
 		);
 	}
+
+	#[test]
+	fn should_serialize_peer_reputation() {
+		assert_eq!(
+			::serde_json::to_string(&PeerReputation {
+				peer_id: "2".into(),
+				reputation: -10,
+				connected: true,
+				banned: false,
+				reputation_changes: vec![ReputationChange {
+					value: -10,
+					reason: "a".into(),
+					seconds_ago: 3,
+				}],
+			}).unwrap(),
+			r#"{"peerId":"2","reputation":-10,"connected":true,"banned":false,"reputationChanges":[{"value":-10,"reason":"a","secondsAgo":3}]}"#,
+		);
+	}
 }
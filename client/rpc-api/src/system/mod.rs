@@ -27,7 +27,7 @@ use futures::{future::BoxFuture, compat::Compat};
 
 use self::error::Result as SystemResult;
 
-pub use self::helpers::{SystemInfo, Health, PeerInfo, NodeRole};
+pub use self::helpers::{SystemInfo, Health, PeerInfo, PeerReputation, ReputationChange, NodeRole};
 pub use self::gen_client::Client as SystemClient;
 
 /// Substrate system RPC API
@@ -100,6 +100,27 @@ pub trait SystemApi<Hash, Number> {
 	fn system_remove_reserved_peer(&self, peer_id: String)
 		-> Compat<BoxFuture<'static, Result<(), jsonrpc_core::Error>>>;
 
+	/// Returns the reputation, the connection state and the most recent reputation changes of
+	/// all the peers known to the node.
+	#[rpc(name = "system_peerReputations", returns = "Vec<PeerReputation>")]
+	fn system_peer_reputations(&self)
+		-> Compat<BoxFuture<'static, jsonrpc_core::Result<Vec<PeerReputation>>>>;
+
+	/// Bans a peer or an IP address for the given number of seconds, or until
+	/// `system_unban` is called if no duration is given. The string should encode either a
+	/// PeerId e.g. `QmSk5HQbn6LhUwDiNMseVUjuRYhEtYj4aUZ6WfWoGURpdV` or an IP address
+	/// e.g. `198.51.100.19`.
+	///
+	/// Bans are persisted and survive restarts of the node.
+	#[rpc(name = "system_ban", returns = "()")]
+	fn system_ban(&self, target: String, duration: Option<u64>)
+		-> Compat<BoxFuture<'static, Result<(), jsonrpc_core::Error>>>;
+
+	/// Lifts a ban put in place with `system_ban`.
+	#[rpc(name = "system_unban", returns = "()")]
+	fn system_unban(&self, target: String)
+		-> Compat<BoxFuture<'static, Result<(), jsonrpc_core::Error>>>;
+
 	/// Returns the roles the node is running as.
 	#[rpc(name = "system_nodeRoles", returns = "Vec<NodeRole>")]
 	fn system_node_roles(&self) -> Receiver<Vec<NodeRole>>;
@@ -141,14 +141,18 @@ impl<P, Client> AuthorApi<TxHash<P>, BlockHash<P>> for Author<P, Client>
 		Ok(self.keystore.read().has_keys(&[(public_key.to_vec(), key_type)]))
 	}
 
-	fn submit_extrinsic(&self, ext: Bytes) -> FutureResult<TxHash<P>> {
+	fn submit_extrinsic(&self, ext: Bytes, do_not_gossip: Option<bool>) -> FutureResult<TxHash<P>> {
 		let xt = match Decode::decode(&mut &ext[..]) {
 			Ok(xt) => xt,
 			Err(err) => return Box::new(result(Err(err.into()))),
 		};
-		let best_block_hash = self.client.info().best_hash;
-		Box::new(self.pool
-			.submit_one(&generic::BlockId::hash(best_block_hash), TX_SOURCE, xt)
+		let at = generic::BlockId::hash(self.client.info().best_hash);
+		let submit = if do_not_gossip.unwrap_or(false) {
+			self.pool.submit_one_without_propagation(&at, TX_SOURCE, xt)
+		} else {
+			self.pool.submit_one(&at, TX_SOURCE, xt)
+		};
+		Box::new(submit
 			.compat()
 			.map_err(|e| e.into_pool_error()
 				.map(Into::into)
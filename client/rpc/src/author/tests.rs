@@ -95,11 +95,11 @@ fn submit_transaction_should_not_cause_error() {
 	let h: H256 = blake2_256(&xt).into();
 
 	assert_matches!(
-		AuthorApi::submit_extrinsic(&p, xt.clone().into()).wait(),
+		AuthorApi::submit_extrinsic(&p, xt.clone().into(), None).wait(),
 		Ok(h2) if h == h2
 	);
 	assert!(
-		AuthorApi::submit_extrinsic(&p, xt.into()).wait().is_err()
+		AuthorApi::submit_extrinsic(&p, xt.into(), None).wait().is_err()
 	);
 }
 
@@ -110,14 +110,31 @@ fn submit_rich_transaction_should_not_cause_error() {
 	let h: H256 = blake2_256(&xt).into();
 
 	assert_matches!(
-		AuthorApi::submit_extrinsic(&p, xt.clone().into()).wait(),
+		AuthorApi::submit_extrinsic(&p, xt.clone().into(), None).wait(),
 		Ok(h2) if h == h2
 	);
 	assert!(
-		AuthorApi::submit_extrinsic(&p, xt.into()).wait().is_err()
+		AuthorApi::submit_extrinsic(&p, xt.into(), None).wait().is_err()
 	);
 }
 
+#[test]
+fn submit_transaction_without_gossip_should_not_be_propagable() {
+	let setup = TestSetup::default();
+	let p = setup.author();
+	let xt = uxt(AccountKeyring::Alice, 0).encode();
+	let h: H256 = blake2_256(&xt).into();
+
+	assert_matches!(
+		AuthorApi::submit_extrinsic(&p, xt.into(), Some(true)).wait(),
+		Ok(h2) if h == h2
+	);
+	let ready = setup.pool.ready().collect::<Vec<_>>();
+	assert_eq!(ready.len(), 1);
+	assert_eq!(*ready[0].hash(), h);
+	assert!(!ready[0].is_propagable());
+}
+
 #[test]
 fn should_watch_extrinsic() {
 	//given
@@ -151,7 +168,7 @@ fn should_watch_extrinsic() {
 		};
 		tx.into_signed_tx()
 	};
-	AuthorApi::submit_extrinsic(&p, replacement.encode().into()).wait().unwrap();
+	AuthorApi::submit_extrinsic(&p, replacement.encode().into(), None).wait().unwrap();
 	let (res, data) = executor::block_on(data.into_future().compat()).unwrap();
 
 	let expected = Some(format!(
@@ -192,7 +209,7 @@ fn should_return_pending_extrinsics() {
 	let p = TestSetup::default().author();
 
 	let ex = uxt(AccountKeyring::Alice, 0);
-	AuthorApi::submit_extrinsic(&p, ex.encode().into()).wait().unwrap();
+	AuthorApi::submit_extrinsic(&p, ex.encode().into(), None).wait().unwrap();
 	assert_matches!(
 		p.pending_extrinsics(),
 		Ok(ref expected) if *expected == vec![Bytes(ex.encode())]
@@ -205,11 +222,11 @@ fn should_remove_extrinsics() {
 	let p = setup.author();
 
 	let ex1 = uxt(AccountKeyring::Alice, 0);
-	p.submit_extrinsic(ex1.encode().into()).wait().unwrap();
+	p.submit_extrinsic(ex1.encode().into(), None).wait().unwrap();
 	let ex2 = uxt(AccountKeyring::Alice, 1);
-	p.submit_extrinsic(ex2.encode().into()).wait().unwrap();
+	p.submit_extrinsic(ex2.encode().into(), None).wait().unwrap();
 	let ex3 = uxt(AccountKeyring::Bob, 0);
-	let hash3 = p.submit_extrinsic(ex3.encode().into()).wait().unwrap();
+	let hash3 = p.submit_extrinsic(ex3.encode().into(), None).wait().unwrap();
 	assert_eq!(setup.pool.status().ready, 3);
 
 	// now remove all 3
@@ -27,7 +27,7 @@ use sc_rpc_api::DenyUnsafe;
 use self::error::{Error, Result};
 use sp_core::{
 	Bytes,
-	offchain::{OffchainStorage, StorageKind},
+	offchain::{OffchainStorage, OffchainWorkerControl, StorageKind},
 };
 use parking_lot::RwLock;
 use std::sync::Arc;
@@ -37,6 +37,8 @@ use std::sync::Arc;
 pub struct Offchain<T: OffchainStorage> {
 	/// Offchain storage
 	storage: Arc<RwLock<T>>,
+	/// Offchain workers, if the node runs them.
+	workers: Option<Arc<dyn OffchainWorkerControl>>,
 	deny_unsafe: DenyUnsafe,
 }
 
@@ -45,9 +47,16 @@ impl<T: OffchainStorage> Offchain<T> {
 	pub fn new(storage: T, deny_unsafe: DenyUnsafe) -> Self {
 		Offchain {
 			storage: Arc::new(RwLock::new(storage)),
+			workers: None,
 			deny_unsafe,
 		}
 	}
+
+	/// Control the given offchain workers through this API.
+	pub fn with_workers(mut self, workers: Arc<dyn OffchainWorkerControl>) -> Self {
+		self.workers = Some(workers);
+		self
+	}
 }
 
 impl<T: OffchainStorage + 'static> OffchainApi for Offchain<T> {
@@ -73,4 +82,13 @@ impl<T: OffchainStorage + 'static> OffchainApi for Offchain<T> {
 		};
 		Ok(self.storage.read().get(prefix, &*key).map(Into::into))
 	}
+
+	/// Enable or disable the offchain worker of the given pallet.
+	fn set_worker_enabled(&self, pallet: String, enabled: bool) -> Result<()> {
+		self.deny_unsafe.check_if_safe()?;
+
+		let workers = self.workers.as_ref().ok_or(Error::WorkersDisabled)?;
+		workers.set_pallet_enabled(&pallet, enabled);
+		Ok(())
+	}
 }
@@ -53,3 +53,27 @@ fn offchain_calls_considered_unsafe() {
 		Err(Error::UnsafeRpcCalled(_))
 	);
 }
+
+#[test]
+fn offchain_workers_are_toggled() {
+	#[derive(Debug, Default)]
+	struct Workers(parking_lot::Mutex<Vec<(String, bool)>>);
+
+	impl OffchainWorkerControl for Workers {
+		fn set_pallet_enabled(&self, pallet: &str, enabled: bool) {
+			self.0.lock().push((pallet.into(), enabled));
+		}
+	}
+
+	let storage = InMemOffchainStorage::default();
+	let offchain = Offchain::new(storage.clone(), DenyUnsafe::No);
+	assert_matches!(
+		offchain.set_worker_enabled("ImOnline".into(), false),
+		Err(Error::WorkersDisabled)
+	);
+
+	let workers = Arc::new(Workers::default());
+	let offchain = Offchain::new(storage, DenyUnsafe::No).with_workers(workers.clone());
+	assert_matches!(offchain.set_worker_enabled("ImOnline".into(), false), Ok(()));
+	assert_eq!(*workers.0.lock(), vec![("ImOnline".to_string(), false)]);
+}
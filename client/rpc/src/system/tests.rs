@@ -89,6 +89,7 @@ fn api<T: Into<Option<Status>>>(sync: T) -> System<Block> {
 						not_connected_peers: Default::default(),
 						average_download_per_sec: 0,
 						average_upload_per_sec: 0,
+						protocols_bandwidth: Default::default(),
 						peerset: serde_json::Value::Null,
 					}).unwrap());
 				},
@@ -104,6 +105,29 @@ fn api<T: Into<Option<Status>>>(sync: T) -> System<Block> {
 						Err(s) => sender.send(Err(error::Error::MalformattedPeerArg(s.to_string()))),
 					};
 				}
+				Request::PeerReputations(sender) => {
+					let mut peers = vec![];
+					for _peer in 0..status.peers {
+						peers.push(PeerReputation {
+							peer_id: status.peer_id.to_base58(),
+							reputation: -256,
+							connected: false,
+							banned: false,
+							reputation_changes: vec![ReputationChange {
+								value: -256,
+								reason: "Disconnected".into(),
+								seconds_ago: 0,
+							}],
+						});
+					}
+					let _ = sender.send(peers);
+				}
+				Request::NetworkBan(target, _, sender) | Request::NetworkUnban(target, sender) => {
+					let _ = match target.parse::<sc_network::BanTarget>() {
+						Ok(_) => sender.send(Ok(())),
+						Err(s) => sender.send(Err(error::Error::MalformattedPeerArg(s))),
+					};
+				}
 				Request::NodeRoles(sender) => {
 					let _ = sender.send(vec![NodeRole::Authority]);
 				}
@@ -284,6 +308,7 @@ fn system_network_state() {
 			not_connected_peers: Default::default(),
 			average_download_per_sec: 0,
 			average_upload_per_sec: 0,
+			protocols_bandwidth: Default::default(),
 			peerset: serde_json::Value::Null,
 		}
 	);
@@ -320,3 +345,46 @@ fn system_network_remove_reserved() {
 	assert_eq!(runtime.block_on(good_fut), Ok(()));
 	assert!(runtime.block_on(bad_fut).is_err());
 }
+
+#[test]
+fn system_peer_reputations() {
+	let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+
+	let peer_id = PeerId::random();
+	let req = api(Status {
+		peer_id: peer_id.clone(),
+		peers: 1,
+		is_syncing: false,
+		is_dev: true,
+	}).system_peer_reputations();
+	let res = runtime.block_on(req).unwrap();
+
+	assert_eq!(
+		res,
+		vec![PeerReputation {
+			peer_id: peer_id.to_base58(),
+			reputation: -256,
+			connected: false,
+			banned: false,
+			reputation_changes: vec![ReputationChange {
+				value: -256,
+				reason: "Disconnected".into(),
+				seconds_ago: 0,
+			}],
+		}]
+	);
+}
+
+#[test]
+fn system_network_ban_and_unban() {
+	let peer_id = "QmSk5HQbn6LhUwDiNMseVUjuRYhEtYj4aUZ6WfWoGURpdV";
+	let ip = "198.51.100.19";
+	let bad_target = "/ip4/198.51.100.19/tcp/30333";
+	let mut runtime = tokio::runtime::current_thread::Runtime::new().unwrap();
+
+	assert_eq!(runtime.block_on(api(None).system_ban(peer_id.into(), Some(60))), Ok(()));
+	assert_eq!(runtime.block_on(api(None).system_ban(ip.into(), None)), Ok(()));
+	assert!(runtime.block_on(api(None).system_ban(bad_target.into(), None)).is_err());
+	assert_eq!(runtime.block_on(api(None).system_unban(peer_id.into())), Ok(()));
+	assert!(runtime.block_on(api(None).system_unban(bad_target.into())).is_err());
+}
@@ -232,21 +232,13 @@ pub fn new_full_parts<TBl, TRtApi, TExecDisp>(
 		.unwrap_or_default();
 
 	let (client, backend) = {
-		let db_config = sc_client_db::DatabaseSettings {
-			state_cache_size: config.state_cache_size,
-			state_cache_child_ratio:
-			config.state_cache_child_ratio.map(|v| (v, 100)),
-			pruning: config.pruning.clone(),
-			source: config.database.clone(),
-		};
-
 		let extensions = sc_client_api::execution_extensions::ExecutionExtensions::new(
 			config.execution_strategies.clone(),
 			Some(keystore.clone()),
 		);
 
 		new_client(
-			db_config,
+			database_settings(config),
 			executor,
 			chain_spec.as_storage_builder(),
 			fork_blocks,
@@ -291,16 +283,7 @@ pub fn new_light_parts<TBl, TRtApi, TExecDisp>(
 		config.max_runtime_instances,
 	);
 
-	let db_storage = {
-		let db_settings = sc_client_db::DatabaseSettings {
-			state_cache_size: config.state_cache_size,
-			state_cache_child_ratio:
-				config.state_cache_child_ratio.map(|v| (v, 100)),
-			pruning: config.pruning.clone(),
-			source: config.database.clone(),
-		};
-		sc_client_db::light::LightStorage::new(db_settings)?
-	};
+	let db_storage = sc_client_db::light::LightStorage::new(database_settings(config))?;
 	let light_blockchain = sc_light::new_light_blockchain(db_storage);
 	let fetch_checker = Arc::new(
 		sc_light::new_fetch_checker::<_, TBl, _>(
@@ -322,6 +305,28 @@ pub fn new_light_parts<TBl, TRtApi, TExecDisp>(
 	Ok((client, backend, keystore, task_manager, on_demand))
 }
 
+/// Number of blocks after which the state of non-finalized blocks is canonicalized.
+const CANONICALIZATION_DELAY: u64 = 4096;
+
+/// Open the database backend of a full node, without initializing the genesis block.
+///
+/// This is useful to initialize the database differently, e.g. from a state snapshot.
+pub fn new_full_backend<TBl: BlockT>(
+	config: &Configuration,
+) -> Result<Arc<TFullBackend<TBl>>, Error> {
+	Ok(Arc::new(Backend::new(database_settings(config), CANONICALIZATION_DELAY)?))
+}
+
+/// The database settings of the given configuration.
+fn database_settings(config: &Configuration) -> DatabaseSettings {
+	DatabaseSettings {
+		state_cache_size: config.state_cache_size,
+		state_cache_child_ratio: config.state_cache_child_ratio.map(|v| (v, 100)),
+		pruning: config.pruning.clone(),
+		source: config.database.clone(),
+	}
+}
+
 /// Create an instance of db-backed client.
 pub fn new_client<E, Block, RA>(
 	settings: DatabaseSettings,
@@ -348,8 +353,6 @@ pub fn new_client<E, Block, RA>(
 		Block: BlockT,
 		E: CodeExecutor + RuntimeInfo,
 {
-	const CANONICALIZATION_DELAY: u64 = 4096;
-
 	let backend = Arc::new(Backend::new(settings, CANONICALIZATION_DELAY)?);
 	let executor = crate::client::LocalCallExecutor::new(backend.clone(), executor, spawn_handle, config.clone());
 	Ok((
@@ -396,6 +399,8 @@ pub struct SpawnTasksParams<'a, TBl: BlockT, TCl, TExPool, TRpc, Backend> {
 	pub system_rpc_tx: TracingUnboundedSender<sc_rpc::system::Request<TBl>>,
 	/// Shared Telemetry connection sinks,
 	pub telemetry_connection_sinks: TelemetryConnectionSinks,
+	/// The offchain workers returned by `build_offchain_workers`, controlled over RPC.
+	pub offchain_workers: Option<Arc<dyn sp_core::offchain::OffchainWorkerControl>>,
 }
 
 /// Build a shared offchain workers instance.
@@ -414,7 +419,12 @@ pub fn build_offchain_workers<TBl, TBackend, TCl>(
 {
 	let offchain_workers = match backend.offchain_storage() {
 		Some(db) => {
-			Some(Arc::new(sc_offchain::OffchainWorkers::new(client.clone(), db)))
+			Some(Arc::new(sc_offchain::OffchainWorkers::new_with_options(
+				client.clone(),
+				db,
+				config.offchain_worker.options.clone(),
+				config.prometheus_registry(),
+			)))
 		},
 		None => {
 			warn!("Offchain workers disabled, due to lack of offchain storage support in backend.");
@@ -474,6 +484,7 @@ pub fn spawn_tasks<TBl, TBackend, TExPool, TRpc, TCl>(
 		remote_blockchain,
 		network, network_status_sinks, system_rpc_tx,
 		telemetry_connection_sinks,
+		offchain_workers,
 	} = params;
 
 	let chain_info = client.usage_info().chain;
@@ -536,7 +547,7 @@ pub fn spawn_tasks<TBl, TBackend, TExPool, TRpc, TCl>(
 	let gen_handler = |deny_unsafe: sc_rpc::DenyUnsafe| gen_handler(
 		deny_unsafe, &config, task_manager.spawn_handle(), client.clone(), transaction_pool.clone(),
 		keystore.clone(), on_demand.clone(), remote_blockchain.clone(), &*rpc_extensions_builder,
-		backend.offchain_storage(), system_rpc_tx.clone()
+		backend.offchain_storage(), offchain_workers.clone(), system_rpc_tx.clone()
 	);
 	let rpc = start_rpc_servers(&config, gen_handler)?;
 	// This is used internally, so don't restrict access to unsafe RPC
@@ -709,6 +720,7 @@ fn gen_handler<TBl, TBackend, TExPool, TRpc, TCl>(
 	remote_blockchain: Option<Arc<dyn RemoteBlockchain<TBl>>>,
 	rpc_extensions_builder: &(dyn RpcExtensionBuilder<Output = TRpc> + Send),
 	offchain_storage: Option<<TBackend as sc_client_api::backend::Backend<TBl>>::OffchainStorage>,
+	offchain_workers: Option<Arc<dyn sp_core::offchain::OffchainWorkerControl>>,
 	system_rpc_tx: TracingUnboundedSender<sc_rpc::system::Request<TBl>>
 ) -> jsonrpc_pubsub::PubSubHandler<sc_rpc::Metadata>
 	where
@@ -773,6 +785,10 @@ fn gen_handler<TBl, TBackend, TExPool, TRpc, TCl>(
 	let maybe_offchain_rpc = offchain_storage
 	.map(|storage| {
 		let offchain = sc_rpc::offchain::Offchain::new(storage, deny_unsafe);
+		let offchain = match offchain_workers {
+			Some(workers) => offchain.with_workers(workers),
+			None => offchain,
+		};
 		// FIXME: Use plain Option (don't collect into HashMap) when we upgrade to jsonrpc 14.1
 		// https://github.com/paritytech/jsonrpc/commit/20485387ed06a48f1a70bf4d609a7cde6cf0accf
 		let delegate = offchain::OffchainApi::to_delegate(offchain);
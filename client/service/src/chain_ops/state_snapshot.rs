@@ -0,0 +1,571 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: GPL-3.0-or-later WITH Classpath-exception-2.0
+
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with this program. If not, see <https://www.gnu.org/licenses/>.
+
+//! Export and import of state snapshots.
+//!
+//! A state snapshot contains the full state of a block, including the child tries, in a
+//! compact binary format that is written and read as a stream. It is a sequence of SCALE
+//! encoded items:
+//!
+//! 1. The magic bytes `SUBSNAP` and the format version.
+//! 2. The [`StateSnapshotBlocks`], with the header and justification of the block, the headers
+//!    of its last ancestors and the aux data of the consensus engines.
+//! 3. The child tries, each an [`Entry::ChildTrie`] followed by one [`Entry::Child`] per key
+//!    value pair of the child trie, sorted by key.
+//! 4. One [`Entry::Top`] per key value pair of the top trie, sorted by key. The roots of the
+//!    child tries are left out, they are recomputed on import.
+//! 5. [`Entry::End`] with the number of key value pairs and the blake2-256 checksum of all
+//!    bytes before it.
+//!
+//! The ordering allows to import a snapshot without holding the state in memory.
+//!
+//! A node started from an imported snapshot has neither the bodies of the ancestors of the block
+//! nor the genesis state. Consensus engines that read the genesis state or rely on data of
+//! earlier blocks, like GRANDPA and BABE, can only be started on such a node if their aux data
+//! is part of the snapshot. The ancestors it refers to are included in the snapshot.
+
+use crate::error::Error;
+use crate::TFullBackend;
+use blake2_rfc::blake2b::Blake2b;
+use codec::{Decode, Encode, Input};
+use log::info;
+use sc_client_api::backend::{Backend, StateBackend};
+use sc_client_api::blockchain::{Backend as BlockchainBackend, HeaderBackend};
+use sp_core::storage::{well_known_keys, ChildInfo};
+use sp_runtime::{BuildStorage, Justification};
+use sp_runtime::generic::BlockId;
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT, NumberFor, One, Zero};
+use std::collections::BTreeMap;
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::iter::Peekable;
+use std::sync::Arc;
+
+/// Magic bytes at the start of every state snapshot.
+const MAGIC: &[u8; 7] = b"SUBSNAP";
+
+/// Version of the state snapshot format.
+const VERSION: u8 = 3;
+
+/// Number of key value pairs between two progress messages.
+const PROGRESS_INTERVAL: u64 = 1_000_000;
+
+/// The blocks of a state snapshot and the aux data of the consensus engines, which precede the
+/// state.
+#[derive(Debug, PartialEq, Encode, Decode)]
+pub struct StateSnapshotBlocks<B: BlockT> {
+	/// The header of the genesis block.
+	pub genesis_header: B::Header,
+	/// The headers of the last ancestors of the block, ending with its parent.
+	pub ancestry: Vec<B::Header>,
+	/// The header of the block.
+	pub header: B::Header,
+	/// The justification of the block.
+	pub justification: Option<Justification>,
+	/// The aux data of the consensus engines.
+	pub aux: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+/// An entry of a state snapshot.
+#[derive(Debug, PartialEq, Encode, Decode)]
+pub enum Entry {
+	/// A key value pair of the top trie.
+	Top(Vec<u8>, Vec<u8>),
+	/// The start of a default child trie, with its unprefixed storage key.
+	ChildTrie(Vec<u8>),
+	/// A key value pair of the child trie started last.
+	Child(Vec<u8>, Vec<u8>),
+	/// The end of the snapshot.
+	End {
+		/// Number of key value pairs in the snapshot.
+		entries: u64,
+		/// Blake2-256 checksum of all bytes before this entry.
+		checksum: [u8; 32],
+	},
+}
+
+fn new_hasher() -> Blake2b {
+	Blake2b::new(32)
+}
+
+fn checksum(hasher: &Blake2b) -> [u8; 32] {
+	let mut checksum = [0; 32];
+	checksum.copy_from_slice(hasher.clone().finalize().as_bytes());
+	checksum
+}
+
+/// A writer that keeps track of the checksum of all written bytes.
+struct HashingWriter<W> {
+	inner: W,
+	hasher: Blake2b,
+}
+
+impl<W: Write> HashingWriter<W> {
+	fn write_encoded(&mut self, value: &impl Encode) -> io::Result<()> {
+		let encoded = value.encode();
+		self.hasher.update(&encoded);
+		self.inner.write_all(&encoded)
+	}
+}
+
+/// A reader that keeps track of the checksum of all read bytes.
+struct HashingReader<R> {
+	inner: R,
+	hasher: Blake2b,
+}
+
+impl<R: Read> Input for HashingReader<R> {
+	fn remaining_len(&mut self) -> Result<Option<usize>, codec::Error> {
+		Ok(None)
+	}
+
+	fn read(&mut self, into: &mut [u8]) -> Result<(), codec::Error> {
+		self.inner.read_exact(into).map_err(|_| "Unexpected end of the state snapshot")?;
+		self.hasher.update(into);
+		Ok(())
+	}
+}
+
+fn decode<T: Decode, R: Read>(reader: &mut HashingReader<R>) -> Result<T, Error> {
+	T::decode(reader).map_err(|e| Error::Other(format!("Invalid state snapshot: {}", e.what())))
+}
+
+/// Export the state of the given `block` as state snapshot. If `block` is `None`, the last
+/// finalized block will be used.
+///
+/// `aux_data` returns the aux data of the consensus engines for the header of the block,
+/// together with the number of the oldest block the data refers to. The headers from that block
+/// on are part of the snapshot.
+pub fn export_state_snapshot<B, BA>(
+	backend: Arc<BA>,
+	block: Option<BlockId<B>>,
+	aux_data: impl FnOnce(&B::Header) -> sp_blockchain::Result<(NumberFor<B>, Vec<(Vec<u8>, Vec<u8>)>)>,
+	output: impl Write,
+) -> Result<(), Error>
+where
+	B: BlockT,
+	BA: Backend<B>,
+{
+	let blockchain = backend.blockchain();
+	let block = block.unwrap_or_else(|| BlockId::Hash(blockchain.info().finalized_hash));
+	let header = blockchain.header(block)?
+		.ok_or_else(|| Error::Other(format!("Unknown block {}", block)))?;
+	if header.number().is_zero() {
+		return Err("The genesis block can't be exported as state snapshot".into());
+	}
+	let genesis_header = blockchain.header(BlockId::Number(Zero::zero()))?
+		.ok_or_else(|| Error::Other("The genesis header is not available".into()))?;
+	let justification = blockchain.justification(block)?;
+	let (oldest, aux) = aux_data(&header)?;
+	let state = backend.state_at(block)?;
+
+	let mut ancestry = Vec::new();
+	let mut parent_hash = *header.parent_hash();
+	let mut parent_number = *header.number() - One::one();
+	while parent_number >= oldest.max(One::one()) {
+		let parent = blockchain.header(BlockId::Hash(parent_hash))?
+			.ok_or_else(|| Error::Other(format!("Missing header of #{}", parent_number)))?;
+		parent_hash = *parent.parent_hash();
+		parent_number = *parent.number() - One::one();
+		ancestry.push(parent);
+	}
+	ancestry.reverse();
+
+	info!("Exporting the state of #{} ({})", header.number(), header.hash());
+
+	let mut writer = HashingWriter { inner: BufWriter::new(output), hasher: new_hasher() };
+	writer.write_encoded(MAGIC)?;
+	writer.write_encoded(&VERSION)?;
+	writer.write_encoded(&StateSnapshotBlocks::<B> {
+		genesis_header,
+		ancestry,
+		header,
+		justification,
+		aux,
+	})?;
+
+	let mut entries = 0u64;
+	let mut result = Ok(());
+	let mut write_entry = |writer: &mut HashingWriter<_>, entry: Entry| {
+		if result.is_ok() {
+			entries += 1;
+			if entries % PROGRESS_INTERVAL == 0 {
+				info!("#{} entries exported", entries);
+			}
+			result = writer.write_encoded(&entry);
+		}
+	};
+
+	let mut child_tries = Vec::new();
+	state.for_keys_with_prefix(well_known_keys::DEFAULT_CHILD_STORAGE_KEY_PREFIX, |key| {
+		child_tries.push(key[well_known_keys::DEFAULT_CHILD_STORAGE_KEY_PREFIX.len()..].to_vec());
+	});
+
+	for storage_key in child_tries {
+		let child_info = ChildInfo::new_default(&storage_key);
+		writer.write_encoded(&Entry::ChildTrie(storage_key))?;
+
+		let mut child_result = Ok(());
+		state.for_keys_in_child_storage(&child_info, |key| {
+			match state.child_storage(&child_info, key) {
+				Ok(Some(value)) => write_entry(&mut writer, Entry::Child(key.to_vec(), value)),
+				Ok(None) => {},
+				Err(e) => if child_result.is_ok() {
+					child_result = Err(Error::Other(format!("Failed to read child storage: {}", e)));
+				},
+			}
+		});
+		child_result?;
+	}
+
+	// The roots of the child tries are part of the top trie, but are recomputed on import.
+	state.for_key_values_with_prefix(&[], |key, value| {
+		if !key.starts_with(well_known_keys::DEFAULT_CHILD_STORAGE_KEY_PREFIX) {
+			write_entry(&mut writer, Entry::Top(key.to_vec(), value.to_vec()));
+		}
+	});
+	result?;
+
+	let checksum = checksum(&writer.hasher);
+	writer.write_encoded(&Entry::End { entries, checksum })?;
+	writer.inner.flush()?;
+
+	info!("Exported {} entries", entries);
+	Ok(())
+}
+
+/// A reader of a state snapshot.
+pub struct StateSnapshotReader<R> {
+	reader: HashingReader<BufReader<R>>,
+}
+
+impl<R: Read> StateSnapshotReader<R> {
+	/// Start to read a state snapshot.
+	///
+	/// Returns the reader of the state and the blocks of the snapshot.
+	pub fn new<B: BlockT>(input: R) -> Result<(Self, StateSnapshotBlocks<B>), Error> {
+		let mut reader = HashingReader { inner: BufReader::new(input), hasher: new_hasher() };
+
+		let magic: [u8; 7] = decode(&mut reader)?;
+		if &magic != MAGIC {
+			return Err("Not a state snapshot".into());
+		}
+		let version: u8 = decode(&mut reader)?;
+		if version != VERSION {
+			return Err(Error::Other(format!("Unsupported state snapshot version {}", version)));
+		}
+		let blocks = decode(&mut reader)?;
+
+		Ok((StateSnapshotReader { reader }, blocks))
+	}
+
+	/// Read the state and verify the checksum of the snapshot.
+	///
+	/// The key value pairs of every trie are passed to `import_trie` while they are read,
+	/// together with the `ChildInfo` of child tries. `import_trie` returns the root of the
+	/// trie, the roots of the child tries are added to the pairs of the top trie. Returns the
+	/// root of the top trie.
+	pub fn read_state<H: Encode>(
+		self,
+		mut import_trie: impl FnMut(
+			Option<&ChildInfo>,
+			&mut dyn Iterator<Item = (Vec<u8>, Vec<u8>)>,
+		) -> Result<H, Error>,
+	) -> Result<H, Error> {
+		let mut reader = self.reader;
+		let mut entries = 0u64;
+		let mut child_roots = BTreeMap::new();
+
+		let mut next = (checksum(&reader.hasher), decode(&mut reader)?);
+		while let (_, Entry::ChildTrie(storage_key)) = next {
+			let child_info = ChildInfo::new_default(&storage_key);
+			let mut pairs = Pairs { reader: &mut reader, entries: &mut entries, child: true, next: None };
+			let root = import_trie(Some(&child_info), &mut pairs)?;
+			for _ in &mut pairs {}
+			next = pairs.next.take().expect("Pairs are read until the next entry; qed")?;
+			child_roots.insert(child_info.prefixed_storage_key().into_inner(), root.encode());
+		}
+
+		let mut pairs = Pairs { reader: &mut reader, entries: &mut entries, child: false, next: None };
+		let root = {
+			let first = match next.1 {
+				Entry::Top(key, value) => {
+					*pairs.entries += 1;
+					Some((key, value))
+				},
+				entry => {
+					pairs.next = Some(Ok((next.0, entry)));
+					None
+				},
+			};
+			let mut top = MergeSorted {
+				a: first.into_iter().chain(&mut pairs).peekable(),
+				b: child_roots.into_iter().peekable(),
+			};
+			let root = import_trie(None, &mut top)?;
+			for _ in &mut top {}
+			root
+		};
+		for _ in &mut pairs {}
+		let (expected_checksum, entry) = pairs.next.take()
+			.expect("Pairs are read until the next entry; qed")?;
+
+		match entry {
+			Entry::End { entries: expected_entries, checksum } => {
+				if checksum != expected_checksum {
+					return Err("Invalid state snapshot: checksum mismatch".into());
+				}
+				if entries != expected_entries {
+					return Err(Error::Other(format!(
+						"Invalid state snapshot: expected {} entries, found {}",
+						expected_entries,
+						entries,
+					)));
+				}
+			},
+			Entry::ChildTrie(_) | Entry::Child(..) =>
+				return Err("Invalid state snapshot: unexpected child trie entry".into()),
+			Entry::Top(..) => unreachable!("Top trie entries are read by `Pairs`; qed"),
+		}
+
+		Ok(root)
+	}
+}
+
+/// Iterator over the consecutive key value pairs of one trie of a state snapshot.
+///
+/// Stops at the first entry that doesn't belong to the trie, which is kept in `next` together
+/// with the checksum of the bytes before it. Read errors are kept in `next` as well.
+struct Pairs<'a, R> {
+	reader: &'a mut HashingReader<BufReader<R>>,
+	entries: &'a mut u64,
+	child: bool,
+	next: Option<Result<([u8; 32], Entry), Error>>,
+}
+
+impl<'a, R: Read> Iterator for Pairs<'a, R> {
+	type Item = (Vec<u8>, Vec<u8>);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.next.is_some() {
+			return None;
+		}
+
+		let checksum = checksum(&self.reader.hasher);
+		let pair = match decode(self.reader) {
+			Ok(Entry::Top(key, value)) if !self.child => (key, value),
+			Ok(Entry::Child(key, value)) if self.child => (key, value),
+			Ok(entry) => {
+				self.next = Some(Ok((checksum, entry)));
+				return None;
+			},
+			Err(e) => {
+				self.next = Some(Err(e));
+				return None;
+			},
+		};
+
+		*self.entries += 1;
+		if *self.entries % PROGRESS_INTERVAL == 0 {
+			info!("#{} entries read", self.entries);
+		}
+		Some(pair)
+	}
+}
+
+/// Iterator that merges two iterators of key value pairs sorted by key.
+struct MergeSorted<A: Iterator, B: Iterator> {
+	a: Peekable<A>,
+	b: Peekable<B>,
+}
+
+impl<A, B> Iterator for MergeSorted<A, B> where
+	A: Iterator<Item = (Vec<u8>, Vec<u8>)>,
+	B: Iterator<Item = (Vec<u8>, Vec<u8>)>,
+{
+	type Item = (Vec<u8>, Vec<u8>);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let next_is_a = match (self.a.peek(), self.b.peek()) {
+			(Some((a, _)), Some((b, _))) => a <= b,
+			(a, _) => a.is_some(),
+		};
+		if next_is_a {
+			self.a.next()
+		} else {
+			self.b.next()
+		}
+	}
+}
+
+/// Initialize an empty database from a state snapshot.
+///
+/// The snapshot must belong to the chain whose genesis state is built by `genesis_storage` and
+/// contain the aux data of all `required_aux` keys. The snapshot is read twice, its checksum is
+/// verified before the state is written to the database. Returns the header of the block the
+/// database starts at.
+pub fn import_state_snapshot<B: BlockT>(
+	backend: &TFullBackend<B>,
+	genesis_storage: &dyn BuildStorage,
+	required_aux: &[&[u8]],
+	mut input: impl Read + Seek,
+) -> Result<B::Header, Error> {
+	let (reader, blocks) = StateSnapshotReader::new::<B>(&mut input)?;
+
+	if blocks.genesis_header.hash() != genesis_hash::<B>(genesis_storage)? {
+		return Err("The state snapshot belongs to a different chain".into());
+	}
+	if let Some(missing) = required_aux.iter().find(|key| blocks.aux.iter().all(|(k, _)| k != *key)) {
+		return Err(Error::Other(format!(
+			"The state snapshot is missing the consensus data {}",
+			String::from_utf8_lossy(missing),
+		)));
+	}
+
+	info!("Verifying the state snapshot of #{} ({})", blocks.header.number(), blocks.header.hash());
+	reader.read_state(|_, _| Ok(()))?;
+
+	input.seek(SeekFrom::Start(0))?;
+	let (reader, blocks) = StateSnapshotReader::new::<B>(&mut input)?;
+
+	info!("Importing the state of #{} ({})", blocks.header.number(), blocks.header.hash());
+	let mut import = backend.begin_state_snapshot_import()?;
+	reader.read_state(|child_info, pairs| import.import_trie(child_info, pairs).map_err(Into::into))?;
+	import.finish(
+		blocks.genesis_header,
+		blocks.ancestry,
+		blocks.header.clone(),
+		blocks.justification,
+		blocks.aux,
+	)?;
+	Ok(blocks.header)
+}
+
+/// Calculate the hash of the genesis block of the given genesis state.
+fn genesis_hash<B: BlockT>(genesis_storage: &dyn BuildStorage) -> Result<B::Hash, Error> {
+	let state_root = crate::client::genesis::genesis_state_root::<B>(&genesis_storage.build_storage()?);
+
+	Ok(crate::client::genesis::construct_genesis_block::<B>(state_root).hash())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use sp_core::H256;
+	use sp_runtime::testing::{Block as RawBlock, ExtrinsicWrapper, Header};
+	use sp_runtime::traits::{BlakeTwo256, Hash as _};
+
+	type Block = RawBlock<ExtrinsicWrapper<u64>>;
+
+	fn snapshot(entries: Vec<Entry>, corrupt_checksum: bool) -> Vec<u8> {
+		let header = |number| Header {
+			number,
+			parent_hash: Default::default(),
+			state_root: Default::default(),
+			extrinsics_root: Default::default(),
+			digest: Default::default(),
+		};
+
+		let mut writer = HashingWriter { inner: Vec::new(), hasher: new_hasher() };
+		writer.write_encoded(MAGIC).unwrap();
+		writer.write_encoded(&VERSION).unwrap();
+		writer.write_encoded(&StateSnapshotBlocks::<Block> {
+			genesis_header: header(0),
+			ancestry: vec![header(4)],
+			header: header(5),
+			justification: None,
+			aux: vec![(b"aux".to_vec(), b"data".to_vec())],
+		}).unwrap();
+		let count = entries.iter().filter(|e| !matches!(e, Entry::ChildTrie(_))).count() as u64;
+		for entry in entries {
+			writer.write_encoded(&entry).unwrap();
+		}
+		let mut checksum = checksum(&writer.hasher);
+		if corrupt_checksum {
+			checksum[0] ^= 1;
+		}
+		writer.write_encoded(&Entry::End { entries: count, checksum }).unwrap();
+		writer.inner
+	}
+
+	/// Read a snapshot, returning the pairs of every trie and the root of the top trie.
+	fn read(encoded: &[u8]) -> Result<(Vec<(Option<Vec<u8>>, Vec<(Vec<u8>, Vec<u8>)>)>, H256), Error> {
+		let (reader, blocks) = StateSnapshotReader::new::<Block>(encoded)?;
+		assert_eq!(blocks.genesis_header.number, 0);
+		assert_eq!(blocks.ancestry.len(), 1);
+		assert_eq!(blocks.header.number, 5);
+		assert!(blocks.justification.is_none());
+		assert_eq!(blocks.aux, vec![(b"aux".to_vec(), b"data".to_vec())]);
+
+		let mut tries = Vec::new();
+		let root = reader.read_state(|child_info, pairs| {
+			let pairs: Vec<_> = pairs.collect();
+			let root = BlakeTwo256::trie_root(pairs.clone());
+			tries.push((child_info.map(|child_info| child_info.storage_key().to_vec()), pairs));
+			Ok(root)
+		})?;
+		Ok((tries, root))
+	}
+
+	#[test]
+	fn snapshot_is_read_with_child_tries() {
+		let encoded = snapshot(vec![
+			Entry::ChildTrie(b"child".to_vec()),
+			Entry::Child(b"b".to_vec(), b"2".to_vec()),
+			Entry::Child(b"c".to_vec(), b"3".to_vec()),
+			Entry::Top(b"a".to_vec(), b"1".to_vec()),
+			Entry::Top(b"z".to_vec(), b"4".to_vec()),
+		], false);
+
+		let (tries, root) = read(&encoded[..]).unwrap();
+
+		let child_pairs = vec![(b"b".to_vec(), b"2".to_vec()), (b"c".to_vec(), b"3".to_vec())];
+		let child_root = BlakeTwo256::trie_root(child_pairs.clone());
+		let child_root_key = ChildInfo::new_default(b"child").prefixed_storage_key().into_inner();
+		let top_pairs = vec![
+			(b"a".to_vec(), b"1".to_vec()),
+			(child_root_key, child_root.encode()),
+			(b"z".to_vec(), b"4".to_vec()),
+		];
+		assert_eq!(root, BlakeTwo256::trie_root(top_pairs.clone()));
+		assert_eq!(tries, vec![(Some(b"child".to_vec()), child_pairs), (None, top_pairs)]);
+	}
+
+	#[test]
+	fn corrupted_snapshots_are_rejected() {
+		let entries = || vec![Entry::Top(b"a".to_vec(), b"1".to_vec())];
+
+		assert!(read(&snapshot(entries(), true)[..]).is_err());
+
+		let mut encoded = snapshot(entries(), false);
+		let len = encoded.len();
+		encoded.truncate(len - 1);
+		assert!(read(&encoded[..]).is_err());
+
+		let orphan = snapshot(vec![Entry::Child(b"b".to_vec(), b"2".to_vec())], false);
+		assert!(read(&orphan[..]).is_err());
+
+		let late_child_trie = snapshot(vec![
+			Entry::Top(b"a".to_vec(), b"1".to_vec()),
+			Entry::ChildTrie(b"child".to_vec()),
+		], false);
+		assert!(read(&late_child_trie[..]).is_err());
+
+		assert!(StateSnapshotReader::new::<Block>(&b"SUBSNAQ\x03"[..]).is_err());
+		assert!(StateSnapshotReader::new::<Block>(&b"SUBSNAP\x02"[..]).is_err());
+	}
+}
@@ -18,7 +18,24 @@
 
 //! Tool for creating the genesis block.
 
-use sp_runtime::traits::{Block as BlockT, Header as HeaderT, Hash as HashT, Zero};
+use codec::Encode;
+use sp_runtime::Storage;
+use sp_runtime::traits::{Block as BlockT, Header as HeaderT, Hash as HashT, HashFor, Zero};
+
+/// Calculate the state root of the given initial storage.
+///
+/// This is the root the client backends compute when they build the genesis state, which
+/// leaves out the roots of empty child tries.
+pub fn genesis_state_root<Block: BlockT>(storage: &Storage) -> Block::Hash {
+	let child_roots = storage.children_default.values()
+		.filter(|child| !child.data.is_empty())
+		.map(|child| {
+			let root = HashFor::<Block>::trie_root(child.data.clone().into_iter().collect());
+			(child.child_info.prefixed_storage_key().into_inner(), root.encode())
+		});
+
+	HashFor::<Block>::trie_root(storage.top.clone().into_iter().chain(child_roots).collect())
+}
 
 /// Create a genesis block, given the initial storage.
 pub fn construct_genesis_block<
@@ -26,6 +26,7 @@ use sc_client_api::execution_extensions::ExecutionStrategies;
 
 use std::{io, future::Future, path::{PathBuf, Path}, pin::Pin, net::SocketAddr, sync::Arc};
 pub use sc_transaction_pool::txpool::Options as TransactionPoolOptions;
+pub use sc_offchain::OffchainWorkerOptions;
 use sc_chain_spec::ChainSpec;
 use sp_core::crypto::SecretString;
 pub use sc_telemetry::TelemetryEndpoints;
@@ -91,6 +92,10 @@ pub struct Configuration {
 	pub force_authoring: bool,
 	/// Disable GRANDPA when running in validator mode
 	pub disable_grandpa: bool,
+	/// The GRANDPA voting rules selected by the user, empty for the default rules.
+	pub grandpa_voting_rules: Vec<String>,
+	/// Configuration of the index of the extrinsics of the canonical chain.
+	pub extrinsic_index: ExtrinsicIndexConfig,
 	/// Development key seed.
 	///
 	/// When running in development mode, the seed will be used to generate authority keys by the keystore.
@@ -152,6 +157,20 @@ pub struct OffchainWorkerConfig {
 	pub enabled: bool,
 	/// allow writes from the runtime to the offchain worker database.
 	pub indexing_enabled: bool,
+	/// When and how many offchain workers are run.
+	pub options: OffchainWorkerOptions,
+}
+
+/// Configuration of the index of the extrinsics of the canonical chain.
+#[derive(Debug, Clone, Default)]
+pub struct ExtrinsicIndexConfig {
+	/// If the extrinsics should be indexed.
+	pub enabled: bool,
+	/// Number of finalized blocks whose extrinsics can be looked up by hash.
+	///
+	/// Extrinsics of older blocks are only kept in the lists of their accounts. `None`
+	/// keeps the extrinsics of all blocks.
+	pub hash_retention: Option<u32>,
 }
 
 /// Configuration of the Prometheus endpoint.
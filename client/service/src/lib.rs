@@ -52,7 +52,7 @@ use sp_utils::{status_sinks, mpsc::{tracing_unbounded, TracingUnboundedReceiver,
 
 pub use self::error::Error;
 pub use self::builder::{
-	new_full_client, new_client, new_full_parts, new_light_parts,
+	new_full_client, new_client, new_full_backend, new_full_parts, new_light_parts,
 	spawn_tasks, build_network, BuildNetworkParams, build_offchain_workers,
 	SpawnTasksParams, TFullClient, TLightClient, TFullBackend, TLightBackend,
 	TLightBackendWithHash, TLightClientWithBackend,
@@ -293,6 +293,45 @@ async fn build_network_future<
 							))),
 						};
 					}
+					sc_rpc::system::Request::PeerReputations(sender) => {
+						let _ = sender.send(network.peers_reputation().into_iter().map(|p|
+							sc_rpc::system::PeerReputation {
+								peer_id: p.peer_id.to_base58(),
+								reputation: p.reputation,
+								connected: p.connected,
+								banned: p.banned,
+								reputation_changes: p.changes.into_iter().map(|(elapsed, change)|
+									sc_rpc::system::ReputationChange {
+										value: change.value,
+										reason: change.reason.to_owned(),
+										seconds_ago: elapsed.as_secs(),
+									}
+								).collect(),
+							}
+						).collect());
+					}
+					sc_rpc::system::Request::NetworkBan(target, duration, sender) => {
+						let _ = match target.parse::<sc_network::BanTarget>() {
+							Ok(target) => {
+								network.service().ban(
+									target,
+									duration.map(Duration::from_secs),
+									"Banned through RPC".into(),
+								);
+								sender.send(Ok(()))
+							}
+							Err(e) => sender.send(Err(sc_rpc::system::error::Error::MalformattedPeerArg(e))),
+						};
+					}
+					sc_rpc::system::Request::NetworkUnban(target, sender) => {
+						let _ = match target.parse::<sc_network::BanTarget>() {
+							Ok(target) => {
+								network.service().unban(target);
+								sender.send(Ok(()))
+							}
+							Err(e) => sender.send(Err(sc_rpc::system::error::Error::MalformattedPeerArg(e))),
+						};
+					}
 					sc_rpc::system::Request::NodeRoles(sender) => {
 						use sc_rpc::system::NodeRole;
 
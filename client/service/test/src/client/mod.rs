@@ -1802,3 +1802,79 @@ fn cleans_up_closed_notification_sinks_on_block_import() {
 	assert_eq!(client.finality_notification_sinks().lock().len(), 0);
 }
 
+
+#[test]
+fn state_snapshot_round_trip() {
+	use sc_client_api::backend::{AuxStore as _, Backend as _};
+	use sc_service::chain_ops::{export_state_snapshot, import_state_snapshot};
+	use sp_blockchain::HeaderBackend as _;
+	use substrate_test_runtime_client::GenesisInit;
+
+	let backend = Arc::new(Backend::new_test(u32::max_value(), u64::max_value()));
+	let mut client = TestClientBuilder::with_backend(backend.clone()).build();
+
+	let mut hashes = Vec::new();
+	for nonce in 0..3 {
+		let mut builder = client.new_block(Default::default()).unwrap();
+		builder.push_transfer(Transfer {
+			from: AccountKeyring::Alice.into(),
+			to: AccountKeyring::Ferdie.into(),
+			amount: 1,
+			nonce,
+		}).unwrap();
+		let block = builder.build().unwrap().block;
+		hashes.push(block.hash());
+		client.import_as_final(BlockOrigin::Own, block).unwrap();
+	}
+
+	// the consensus data refers to block 2, so its header is exported as well.
+	let mut snapshot = Vec::new();
+	export_state_snapshot(backend.clone(), None, |header: &Header| {
+		assert_eq!(header.hash(), hashes[2]);
+		Ok((2, vec![(b"consensus".to_vec(), b"data".to_vec())]))
+	}, &mut snapshot).unwrap();
+
+	let genesis_storage = substrate_test_runtime_client::GenesisParameters::default().genesis_storage();
+	let import = |snapshot: &[u8], required_aux: &[&[u8]]| {
+		let imported = Backend::<Block>::new_test(u32::max_value(), u64::max_value());
+		let result = import_state_snapshot(
+			&imported,
+			&genesis_storage,
+			required_aux,
+			std::io::Cursor::new(snapshot),
+		);
+		(imported, result)
+	};
+
+	let (imported, result) = import(&snapshot, &[b"consensus"]);
+	assert_eq!(result.unwrap().hash(), hashes[2]);
+	let info = imported.blockchain().info();
+	assert_eq!(info.genesis_hash, client.chain_info().genesis_hash);
+	assert_eq!(info.finalized_hash, hashes[2]);
+	assert_eq!(imported.blockchain().hash(2).unwrap(), Some(hashes[1]));
+	assert_eq!(imported.blockchain().hash(1).unwrap(), None);
+	assert_eq!(imported.get_aux(b"consensus").unwrap(), Some(b"data".to_vec()));
+	assert_eq!(
+		imported.state_at(BlockId::Hash(hashes[2])).unwrap().pairs(),
+		backend.state_at(BlockId::Hash(hashes[2])).unwrap().pairs(),
+	);
+
+	// the chain continues on top of the snapshot.
+	let mut client = TestClientBuilder::with_backend(Arc::new(imported)).build();
+	let block = client.new_block(Default::default()).unwrap().build().unwrap().block;
+	client.import(BlockOrigin::Own, block).unwrap();
+	assert_eq!(client.chain_info().best_number, 4);
+
+	// snapshots without the consensus data of the node are refused.
+	let (imported, result) = import(&snapshot, &[b"consensus", b"other"]);
+	assert!(result.is_err());
+	assert_eq!(imported.blockchain().info().best_number, 0);
+
+	// corrupted snapshots are refused before the import starts.
+	let mut corrupted = snapshot.clone();
+	let len = corrupted.len();
+	corrupted[len - 1] ^= 1;
+	let (imported, result) = import(&corrupted, &[b"consensus"]);
+	assert!(result.is_err());
+	assert_eq!(imported.blockchain().info().best_number, 0);
+}
@@ -193,6 +193,24 @@ impl<B: ChainApi> Pool<B> {
 		res.expect("One extrinsic passed; one result returned; qed")
 	}
 
+	/// Imports one unverified extrinsic to the pool, without ever propagating it to other nodes.
+	pub async fn submit_one_without_propagation(
+		&self,
+		at: &BlockId<B::Block>,
+		source: TransactionSource,
+		xt: ExtrinsicFor<B>,
+	) -> Result<ExtrinsicHash<B>, B::Error> {
+		let block_number = self.resolve_block_number(at)?;
+		let (_, tx) = self.verify_one(
+			at,
+			block_number,
+			source,
+			xt,
+			CheckBannedBeforeVerify::Yes,
+		).await;
+		self.validated_pool.submit_without_propagation(tx)
+	}
+
 	/// Import a single extrinsic and starts to watch their progress in the pool.
 	pub async fn submit_and_watch(
 		&self,
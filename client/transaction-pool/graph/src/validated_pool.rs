@@ -101,6 +101,9 @@ pub struct ValidatedPool<B: ChainApi> {
 	>>,
 	import_notification_sinks: Mutex<Vec<Sender<ExtrinsicHash<B>>>>,
 	rotator: PoolRotator<ExtrinsicHash<B>>,
+	/// Transactions that were submitted without propagation, and must remain unpropagable after
+	/// being revalidated.
+	unpropagable: RwLock<HashSet<ExtrinsicHash<B>>>,
 }
 
 #[cfg(not(target_os = "unknown"))]
@@ -125,6 +128,7 @@ impl<B: ChainApi> ValidatedPool<B> {
 			pool: RwLock::new(base_pool),
 			import_notification_sinks: Default::default(),
 			rotator: Default::default(),
+			unpropagable: Default::default(),
 		}
 	}
 
@@ -179,11 +183,43 @@ impl<B: ChainApi> ValidatedPool<B> {
 		}).collect()
 	}
 
+	/// Imports a pre-validated transaction that must never be propagated to other nodes.
+	pub fn submit_without_propagation(
+		&self,
+		tx: ValidatedTransactionFor<B>,
+	) -> Result<ExtrinsicHash<B>, B::Error> {
+		let tx = match tx {
+			ValidatedTransaction::Valid(mut tx) => {
+				tx.propagate = false;
+				ValidatedTransaction::Valid(tx)
+			},
+			other => other,
+		};
+
+		let result = self.submit(std::iter::once(tx)).pop()
+			.expect("One transaction passed; one result returned; qed");
+		if let Ok(ref hash) = result {
+			self.unpropagable.write().insert(hash.clone());
+		}
+		result
+	}
+
+	/// Overrides the validity of transactions that were submitted without propagation.
+	fn keep_unpropagable(
+		&self,
+		mut tx: base::Transaction<ExtrinsicHash<B>, ExtrinsicFor<B>>,
+	) -> base::Transaction<ExtrinsicHash<B>, ExtrinsicFor<B>> {
+		if self.unpropagable.read().contains(&tx.hash) {
+			tx.propagate = false;
+		}
+		tx
+	}
+
 	/// Submit single pre-validated transaction to the pool.
 	fn submit_one(&self, tx: ValidatedTransactionFor<B>) -> Result<ExtrinsicHash<B>, B::Error> {
 		match tx {
 			ValidatedTransaction::Valid(tx) => {
-				let imported = self.pool.write().import(tx)?;
+				let imported = self.pool.write().import(self.keep_unpropagable(tx))?;
 
 				if let base::Imported::Ready { ref hash, .. } = imported {
 					self.import_notification_sinks.lock()
@@ -338,7 +374,7 @@ impl<B: ChainApi> ValidatedPool<B> {
 				let mut final_statuses = HashMap::new();
 				for (hash, tx_to_resubmit) in txs_to_resubmit {
 					match tx_to_resubmit {
-						ValidatedTransaction::Valid(tx) => match pool.import(tx) {
+						ValidatedTransaction::Valid(tx) => match pool.import(self.keep_unpropagable(tx)) {
 							Ok(imported) => match imported {
 								base::Imported::Ready { promoted, failed, removed, .. } => {
 									final_statuses.insert(hash, Status::Ready);
@@ -522,6 +558,11 @@ impl<B: ChainApi> ValidatedPool<B> {
 		self.remove_invalid(&futures_to_remove);
 		// clear banned transactions timeouts
 		self.rotator.clear_timeouts(&now);
+		// forget about the unpropagable transactions that are gone
+		{
+			let pool = self.pool.read();
+			self.unpropagable.write().retain(|hash| pool.is_imported(hash));
+		}
 
 		Ok(())
 	}
@@ -263,6 +263,20 @@ impl<PoolApi, Block> TransactionPool for BasicPool<PoolApi, Block>
 		async move { pool.submit_one(&at, source, xt).await }.boxed()
 	}
 
+	fn submit_one_without_propagation(
+		&self,
+		at: &BlockId<Self::Block>,
+		source: TransactionSource,
+		xt: TransactionFor<Self>,
+	) -> PoolFuture<TxHash<Self>, Self::Error> {
+		let pool = self.pool.clone();
+		let at = *at;
+
+		self.metrics.report(|metrics| metrics.submitted_transactions.inc());
+
+		async move { pool.submit_one_without_propagation(&at, source, xt).await }.boxed()
+	}
+
 	fn submit_and_watch(
 		&self,
 		at: &BlockId<Self::Block>,
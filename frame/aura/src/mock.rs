@@ -19,15 +19,23 @@
 
 #![cfg(test)]
 
-use crate::{Trait, Module, GenesisConfig};
-use sp_consensus_aura::ed25519::AuthorityId;
+use crate::{Trait, Module, GenesisConfig, AuraEquivocationOffence, HandleEquivocation};
+use std::cell::RefCell;
+use codec::{Encode, Decode};
+use sp_consensus_aura::{EquivocationProof, SlotNumber, AURA_ENGINE_ID};
+use sp_consensus_aura::ed25519::{AuthorityId, AuthorityPair};
 use sp_runtime::{
-	traits::IdentityLookup, Perbill,
-	testing::{Header, UintAuthorityId},
+	traits::{IdentityLookup, Header as _}, Perbill, KeyTypeId, DispatchResult,
+	testing::{Digest, DigestItem, Header, UintAuthorityId},
 };
-use frame_support::{impl_outer_origin, parameter_types, weights::Weight};
+use sp_session::{GetSessionNumber, GetValidatorCount};
+use sp_staking::{SessionIndex, offence::{Offence, OffenceError}};
+use frame_support::{
+	impl_outer_origin, parameter_types, weights::Weight, traits::{Get, KeyOwnerProofSystem},
+};
+use pallet_session::OneSessionHandler;
 use sp_io;
-use sp_core::H256;
+use sp_core::{H256, Pair};
 
 impl_outer_origin!{
 	pub enum Origin for Test where system = frame_system {}
@@ -82,6 +90,117 @@ impl pallet_timestamp::Trait for Test {
 
 impl Trait for Test {
 	type AuthorityId = AuthorityId;
+	type KeyOwnerProof = TestKeyOwnerProof;
+	type KeyOwnerIdentification = u64;
+	type KeyOwnerProofSystem = TestKeyOwnerProofSystem;
+	type CurrentSession = TestCurrentSession;
+	type HandleEquivocation = TestEquivocationHandler;
+}
+
+/// A key ownership proof that identifies the owner by the index of its key in the authority set.
+#[derive(Clone, PartialEq, Eq, Debug, Encode, Decode)]
+pub struct TestKeyOwnerProof {
+	pub session: SessionIndex,
+	pub validator_count: u32,
+	pub owner: u64,
+}
+
+impl GetSessionNumber for TestKeyOwnerProof {
+	fn session(&self) -> SessionIndex {
+		self.session
+	}
+}
+
+impl GetValidatorCount for TestKeyOwnerProof {
+	fn validator_count(&self) -> u32 {
+		self.validator_count
+	}
+}
+
+pub struct TestKeyOwnerProofSystem;
+
+impl KeyOwnerProofSystem<(KeyTypeId, AuthorityId)> for TestKeyOwnerProofSystem {
+	type Proof = TestKeyOwnerProof;
+	type IdentificationTuple = u64;
+
+	fn prove((_, key): (KeyTypeId, AuthorityId)) -> Option<Self::Proof> {
+		let authorities = Aura::authorities();
+		let owner = authorities.iter().position(|authority| *authority == key)?;
+		Some(TestKeyOwnerProof {
+			session: TestCurrentSession::get(),
+			validator_count: authorities.len() as u32,
+			owner: owner as u64,
+		})
+	}
+
+	fn check_proof((_, key): (KeyTypeId, AuthorityId), proof: Self::Proof) -> Option<u64> {
+		if Aura::authorities().get(proof.owner as usize) == Some(&key) {
+			Some(proof.owner)
+		} else {
+			None
+		}
+	}
+}
+
+thread_local! {
+	/// Index of the current session.
+	pub static SESSION: RefCell<SessionIndex> = RefCell::new(0);
+}
+
+pub struct TestCurrentSession;
+
+impl Get<SessionIndex> for TestCurrentSession {
+	fn get() -> SessionIndex {
+		SESSION.with(|s| *s.borrow())
+	}
+}
+
+/// Starts a new session at the given slot.
+pub fn start_session(slot: SlotNumber) {
+	SESSION.with(|s| *s.borrow_mut() += 1);
+	frame_system::Module::<Test>::deposit_log(DigestItem::PreRuntime(AURA_ENGINE_ID, slot.encode()));
+	let no_validators = || std::iter::empty::<(&u64, AuthorityId)>();
+	Aura::on_new_session(false, no_validators(), no_validators());
+}
+
+thread_local! {
+	/// The reported offences as `(reporters, offender, slot, session)`.
+	pub static OFFENCES: RefCell<Vec<(Vec<u64>, u64, SlotNumber, SessionIndex)>> = RefCell::new(vec![]);
+}
+
+pub struct TestEquivocationHandler;
+
+impl HandleEquivocation<Test> for TestEquivocationHandler {
+	fn report_offence(
+		reporters: Vec<u64>,
+		offence: AuraEquivocationOffence<u64>,
+	) -> Result<(), OffenceError> {
+		if Self::is_known_offence(&offence.offenders(), &offence.time_slot()) {
+			return Err(OffenceError::DuplicateReport);
+		}
+
+		OFFENCES.with(|o| o.borrow_mut().push(
+			(reporters, offence.offender, offence.slot, offence.session_index)
+		));
+		Ok(())
+	}
+
+	fn is_known_offence(offenders: &[u64], time_slot: &SlotNumber) -> bool {
+		OFFENCES.with(|o| o.borrow().iter().any(|(_, offender, slot, _)|
+			offenders.contains(offender) && slot == time_slot
+		))
+	}
+
+	fn submit_unsigned_equivocation_report(
+		_equivocation_proof: EquivocationProof<Header, AuthorityId>,
+		_key_owner_proof: TestKeyOwnerProof,
+	) -> DispatchResult {
+		Ok(())
+	}
+
+	fn block_author() -> Option<u64> {
+		None
+	}
 }
 
 pub fn new_test_ext(authorities: Vec<u64>) -> sp_io::TestExternalities {
@@ -92,4 +211,48 @@ pub fn new_test_ext(authorities: Vec<u64>) -> sp_io::TestExternalities {
 	t.into()
 }
 
+/// Creates a test externality with authorities whose key pairs are known.
+pub fn new_test_ext_with_pairs(authorities_len: usize) -> (Vec<AuthorityPair>, sp_io::TestExternalities) {
+	let pairs = (0..authorities_len)
+		.map(|i| AuthorityPair::from_seed(&[i as u8; 32]))
+		.collect::<Vec<_>>();
+
+	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	GenesisConfig::<Test>{
+		authorities: pairs.iter().map(|pair| pair.public()).collect(),
+	}.assimilate_storage(&mut t).unwrap();
+	OFFENCES.with(|o| o.borrow_mut().clear());
+	SESSION.with(|s| *s.borrow_mut() = 0);
+
+	let mut ext: sp_io::TestExternalities = t.into();
+	ext.execute_with(|| Aura::on_genesis_session(std::iter::empty::<(&u64, AuthorityId)>()));
+
+	(pairs, ext)
+}
+
+/// Creates a header at the given slot, sealed by `pair`. Headers at the same slot differ by
+/// `number`.
+pub fn sealed_header(pair: &AuthorityPair, slot: SlotNumber, number: u64) -> Header {
+	let mut digest = Digest::default();
+	digest.push(DigestItem::PreRuntime(AURA_ENGINE_ID, slot.encode()));
+	let mut header = Header::new(number, Default::default(), Default::default(), Default::default(), digest);
+
+	let signature = pair.sign(header.hash().as_ref());
+	header.digest_mut().push(DigestItem::Seal(AURA_ENGINE_ID, signature.encode()));
+	header
+}
+
+/// Creates an equivocation of `pair` at the given slot.
+pub fn generate_equivocation_proof(
+	pair: &AuthorityPair,
+	slot_number: SlotNumber,
+) -> EquivocationProof<Header, AuthorityId> {
+	EquivocationProof {
+		offender: pair.public(),
+		slot_number,
+		first_header: sealed_header(pair, slot_number, 1),
+		second_header: sealed_header(pair, slot_number, 2),
+	}
+}
+
 pub type Aura = Module<Test>;
@@ -19,7 +19,13 @@
 
 #![cfg(test)]
 
-use crate::mock::{Aura, new_test_ext};
+use crate::{Error, mock::*};
+use frame_support::{assert_err, assert_ok, traits::KeyOwnerProofSystem};
+use sp_consensus_aura::ed25519::AuthorityPair;
+use sp_core::Pair;
+use sp_runtime::RuntimeAppPublic;
+
+type AuthorityId = sp_consensus_aura::ed25519::AuthorityId;
 
 #[test]
 fn initial_values() {
@@ -28,3 +34,153 @@ fn initial_values() {
 		assert_eq!(Aura::authorities().len(), 4);
 	});
 }
+
+fn key_owner_proof(pair: &AuthorityPair) -> TestKeyOwnerProof {
+	TestKeyOwnerProofSystem::prove((AuthorityId::ID, pair.public())).unwrap()
+}
+
+#[test]
+fn report_equivocation_works() {
+	let (pairs, mut ext) = new_test_ext_with_pairs(3);
+
+	ext.execute_with(|| {
+		let equivocation_proof = generate_equivocation_proof(&pairs[1], 7);
+
+		assert_ok!(Aura::report_equivocation(
+			Origin::signed(42),
+			equivocation_proof,
+			key_owner_proof(&pairs[1]),
+		));
+
+		assert_eq!(OFFENCES.with(|o| o.borrow().clone()), vec![(vec![42], 1, 7, 0)]);
+	});
+}
+
+#[test]
+fn report_equivocation_invalid_equivocation_proof() {
+	let (pairs, mut ext) = new_test_ext_with_pairs(3);
+
+	ext.execute_with(|| {
+		let assert_invalid_equivocation_proof = |equivocation_proof| {
+			assert_err!(
+				Aura::report_equivocation(
+					Origin::signed(42),
+					equivocation_proof,
+					key_owner_proof(&pairs[0]),
+				),
+				Error::<Test>::InvalidEquivocationProof,
+			);
+		};
+
+		// both headers are the same
+		let mut equivocation_proof = generate_equivocation_proof(&pairs[0], 7);
+		equivocation_proof.second_header = equivocation_proof.first_header.clone();
+		assert_invalid_equivocation_proof(equivocation_proof);
+
+		// the headers are for different slots
+		let mut equivocation_proof = generate_equivocation_proof(&pairs[0], 7);
+		equivocation_proof.second_header = sealed_header(&pairs[0], 8, 2);
+		assert_invalid_equivocation_proof(equivocation_proof);
+
+		// the slot of the proof doesn't match the headers
+		let mut equivocation_proof = generate_equivocation_proof(&pairs[0], 7);
+		equivocation_proof.slot_number = 8;
+		assert_invalid_equivocation_proof(equivocation_proof);
+
+		// one of the headers is signed by another authority
+		let mut equivocation_proof = generate_equivocation_proof(&pairs[0], 7);
+		equivocation_proof.second_header = sealed_header(&pairs[1], 7, 2);
+		assert_invalid_equivocation_proof(equivocation_proof);
+
+		assert!(OFFENCES.with(|o| o.borrow().is_empty()));
+	});
+}
+
+#[test]
+fn report_equivocation_invalid_key_owner_proof() {
+	let (pairs, mut ext) = new_test_ext_with_pairs(3);
+
+	ext.execute_with(|| {
+		let equivocation_proof = generate_equivocation_proof(&pairs[0], 7);
+
+		assert_err!(
+			Aura::report_equivocation(
+				Origin::signed(42),
+				equivocation_proof,
+				key_owner_proof(&pairs[1]),
+			),
+			Error::<Test>::InvalidKeyOwnershipProof,
+		);
+	});
+}
+
+#[test]
+fn report_equivocation_rejects_duplicates() {
+	let (pairs, mut ext) = new_test_ext_with_pairs(3);
+
+	ext.execute_with(|| {
+		let equivocation_proof = generate_equivocation_proof(&pairs[2], 7);
+
+		assert_ok!(Aura::report_equivocation(
+			Origin::signed(42),
+			equivocation_proof.clone(),
+			key_owner_proof(&pairs[2]),
+		));
+		assert_err!(
+			Aura::report_equivocation(
+				Origin::signed(42),
+				equivocation_proof,
+				key_owner_proof(&pairs[2]),
+			),
+			Error::<Test>::DuplicateOffenceReport,
+		);
+	});
+}
+
+#[test]
+fn report_equivocation_checks_session_of_slot() {
+	let (pairs, mut ext) = new_test_ext_with_pairs(3);
+
+	ext.execute_with(|| {
+		let genesis_key_owner_proof = key_owner_proof(&pairs[0]);
+		start_session(10);
+		let session_key_owner_proof = key_owner_proof(&pairs[0]);
+		assert_eq!(session_key_owner_proof.session, 1);
+
+		// the equivocation happened in session 0, not in session 1
+		assert_err!(
+			Aura::report_equivocation(
+				Origin::signed(42),
+				generate_equivocation_proof(&pairs[0], 7),
+				session_key_owner_proof.clone(),
+			),
+			Error::<Test>::InvalidKeyOwnershipProof,
+		);
+
+		// the equivocation happened in session 1, not in session 0
+		assert_err!(
+			Aura::report_equivocation(
+				Origin::signed(42),
+				generate_equivocation_proof(&pairs[0], 10),
+				genesis_key_owner_proof.clone(),
+			),
+			Error::<Test>::InvalidKeyOwnershipProof,
+		);
+
+		assert_ok!(Aura::report_equivocation(
+			Origin::signed(42),
+			generate_equivocation_proof(&pairs[0], 7),
+			genesis_key_owner_proof,
+		));
+		assert_ok!(Aura::report_equivocation(
+			Origin::signed(42),
+			generate_equivocation_proof(&pairs[0], 12),
+			session_key_owner_proof,
+		));
+
+		assert_eq!(
+			OFFENCES.with(|o| o.borrow().clone()),
+			vec![(vec![42], 0, 7, 0), (vec![42], 0, 12, 1)],
+		);
+	});
+}
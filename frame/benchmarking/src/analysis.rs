@@ -34,6 +34,7 @@ pub enum BenchmarkSelector {
 	StorageRootTime,
 	Reads,
 	Writes,
+	ProofSize,
 }
 
 impl Analysis {
@@ -48,6 +49,7 @@ impl Analysis {
 				BenchmarkSelector::StorageRootTime => result.storage_root_time,
 				BenchmarkSelector::Reads => result.reads.into(),
 				BenchmarkSelector::Writes => result.writes.into(),
+				BenchmarkSelector::ProofSize => result.proof_size.into(),
 			}
 		).collect();
 
@@ -88,6 +90,7 @@ impl Analysis {
 						BenchmarkSelector::StorageRootTime => result.storage_root_time,
 						BenchmarkSelector::Reads => result.reads.into(),
 						BenchmarkSelector::Writes => result.writes.into(),
+						BenchmarkSelector::ProofSize => result.proof_size.into(),
 					};
 					(result.components[i].1, data)
 				})
@@ -152,6 +155,7 @@ impl Analysis {
 					BenchmarkSelector::StorageRootTime => result.storage_root_time,
 					BenchmarkSelector::Reads => result.reads.into(),
 					BenchmarkSelector::Writes => result.writes.into(),
+					BenchmarkSelector::ProofSize => result.proof_size.into(),
 				})
 		}
 
@@ -299,6 +303,7 @@ mod tests {
 			repeat_reads: 0,
 			writes,
 			repeat_writes: 0,
+			proof_size: 0,
 		}
 	}
 
@@ -353,4 +358,24 @@ mod tests {
 		assert_eq!(writes.base, 0);
 		assert_eq!(writes.slopes, vec![0, 2]);
 	}
+
+	#[test]
+	fn analysis_proof_size_should_work() {
+		let data = [(1, 5), (2, 5), (3, 5), (4, 5), (3, 1), (3, 3), (3, 7), (3, 10)].iter()
+			.map(|&(n, m)| BenchmarkResults {
+				proof_size: 500 + 40 * n + 120 * m,
+				..benchmark_result(
+					vec![(BenchmarkParameter::n, n), (BenchmarkParameter::m, m)], 0, 0, 0, 0,
+				)
+			})
+			.collect::<Vec<_>>();
+
+		let proof_size = Analysis::median_slopes(&data, BenchmarkSelector::ProofSize).unwrap();
+		assert_eq!(proof_size.base, 500);
+		assert_eq!(proof_size.slopes, vec![40, 120]);
+
+		let proof_size = Analysis::min_squares_iqr(&data, BenchmarkSelector::ProofSize).unwrap();
+		assert_eq!(proof_size.base, 500);
+		assert_eq!(proof_size.slopes, vec![40, 120]);
+	}
 }
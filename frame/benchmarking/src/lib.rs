@@ -746,6 +746,11 @@ macro_rules! impl_benchmark {
 							target: "benchmark",
 							"Read/Write Count {:?}", read_write_count
 						);
+						let proof_size = $crate::benchmarking::proof_size();
+						frame_support::debug::trace!(
+							target: "benchmark",
+							"Proof Size {} bytes", proof_size
+						);
 
 						// Time the storage root recalculation.
 						let start_storage_root = $crate::benchmarking::current_time();
@@ -761,6 +766,7 @@ macro_rules! impl_benchmark {
 							repeat_reads: read_write_count.1,
 							writes: read_write_count.2,
 							repeat_writes: read_write_count.3,
+							proof_size,
 						});
 
 						// Wipe the DB back to the genesis state.
@@ -888,6 +894,31 @@ macro_rules! impl_benchmark_test {
 }
 
 
+/// This macro adds the benchmarks of a pallet to a `Vec<BenchmarkList>` object.
+///
+/// It is used to implement `benchmark_metadata` of the `Benchmark` runtime api, with the same
+/// crate names and module structs that are passed to [`add_benchmark`]:
+///
+/// ```ignore
+/// let mut list = Vec::<BenchmarkList>::new();
+/// list_benchmark!(list, pallet_balances, Balances);
+/// list_benchmark!(list, pallet_session, SessionBench::<Runtime>);
+/// ```
+#[macro_export]
+macro_rules! list_benchmark {
+	( $list:ident, $name:ident, $( $location:tt )* ) => (
+		let pallet_string = stringify!($name).as_bytes();
+		let benchmarks = $( $location )*::benchmarks()
+			.into_iter()
+			.map(|benchmark| benchmark.to_vec())
+			.collect();
+		$list.push($crate::BenchmarkList {
+			pallet: pallet_string.to_vec(),
+			benchmarks,
+		});
+	)
+}
+
 /// This macro adds pallet benchmarks to a `Vec<BenchmarkBatch>` object.
 ///
 /// First create an object that holds in the input parameters for the benchmark:
@@ -53,11 +53,25 @@ pub struct BenchmarkResults {
 	pub repeat_reads: u32,
 	pub writes: u32,
 	pub repeat_writes: u32,
+	pub proof_size: u32,
+}
+
+/// The benchmarks available for a FRAME pallet.
+#[derive(Encode, Decode, Default, Clone, PartialEq, Debug)]
+pub struct BenchmarkList {
+	/// The name of the pallet.
+	pub pallet: Vec<u8>,
+	/// The names of the benchmarks of the pallet.
+	pub benchmarks: Vec<Vec<u8>>,
 }
 
 sp_api::decl_runtime_apis! {
 	/// Runtime api for benchmarking a FRAME runtime.
+	#[api_version(2)]
 	pub trait Benchmark {
+		/// Get the benchmarks available in the runtime, by pallet.
+		fn benchmark_metadata() -> Vec<BenchmarkList>;
+
 		/// Dispatch the given benchmark.
 		fn dispatch_benchmark(
 			pallet: Vec<u8>,
@@ -106,6 +120,11 @@ pub trait Benchmarking {
 	fn set_whitelist(&mut self, new: Vec<Vec<u8>>) {
 		self.set_whitelist(new)
 	}
+
+	/// Get the encoded size of the storage proof since the read/write count was reset.
+	fn proof_size(&self) -> u32 {
+		self.proof_size()
+	}
 }
 
 /// The pallet benchmarking trait.
@@ -58,7 +58,7 @@ use frame_support::{
 use sp_core::crypto::KeyTypeId;
 use sp_runtime::{
 	RuntimeDebug,
-	offchain::{http, Duration, storage::StorageValueRef},
+	offchain::{http, Duration, StorageKind, storage::StorageValueRef},
 	traits::Zero,
 	transaction_validity::{
 		InvalidTransaction, ValidTransaction, TransactionValidity, TransactionSource,
@@ -81,6 +81,14 @@ mod tests;
 /// The keys can be inserted manually via RPC (see `author_insertKey`).
 pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"btc!");
 
+/// The URL the price is fetched from.
+pub const PRICE_URL: &str = "https://min-api.cryptocompare.com/data/price?fsym=BTC&tsyms=USD";
+
+/// Key of the persistent local storage entry that replaces [`PRICE_URL`] on a node, e.g. to
+/// use a different price source. The value is the URL as UTF-8 bytes and can be set with the
+/// `offchain_localStorageSet` RPC.
+pub const PRICE_URL_KEY: &[u8] = b"example_ocw::price_url";
+
 /// Based on the above `KeyTypeId` we need to generate a pallet-specific crypto type wrappers.
 /// We can use from supported crypto kinds (`sr25519`, `ed25519` and `ecdsa`) and augment
 /// the types with this pallet-specific identifier.
@@ -543,9 +551,13 @@ impl<T: Trait> Module<T> {
 		// you can find in `sp_io`. The API is trying to be similar to `reqwest`, but
 		// since we are running in a custom WASM execution environment we can't simply
 		// import the library here.
-		let request = http::Request::get(
-			"https://min-api.cryptocompare.com/data/price?fsym=BTC&tsyms=USD"
-		);
+		//
+		// The URL can be changed per node through the local storage, see `PRICE_URL_KEY`.
+		let custom_url = sp_io::offchain::local_storage_get(StorageKind::PERSISTENT, PRICE_URL_KEY);
+		let url = custom_url.as_deref()
+			.and_then(|url| sp_std::str::from_utf8(url).ok())
+			.unwrap_or(PRICE_URL);
+		let request = http::Request::get(url);
 		// We set the deadline for sending of the request, note that awaiting response can
 		// have a separate deadline. Next we send the request, before that it's also possible
 		// to alter request headers or stream body content in case of non-GET requests.
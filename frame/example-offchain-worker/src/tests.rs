@@ -20,11 +20,13 @@ use crate::*;
 use codec::{Encode, Decode};
 use frame_support::{
 	assert_ok, impl_outer_origin, parameter_types,
+	traits::OffchainWorker,
 	weights::Weight,
 };
+use sc_offchain::testing::{Fixture, HttpMockServer, MockResponse, offchain_api};
 use sp_core::{
 	H256,
-	offchain::{OffchainExt, TransactionPoolExt, testing},
+	offchain::{OffchainExt, StorageKind, TransactionPoolExt, testing},
 	sr25519::Signature,
 	testing::KeyStore,
 	traits::KeystoreExt,
@@ -365,6 +367,60 @@ fn should_submit_raw_unsigned_transaction_on_chain() {
 	});
 }
 
+/// Runs the offchain worker of block 3, which sends a signed transaction, with the HTTP client
+/// of a node and the price source replaced by `server`. Returns the submitted call.
+fn run_offchain_worker_against(server: &HttpMockServer) -> Call<Test> {
+	const PHRASE: &str = "news slush supreme milk chapter athlete soap sausage put clutch what kitten";
+	let (pool, pool_state) = testing::TestTransactionPoolExt::new();
+	let keystore = KeyStore::new();
+	keystore.write().sr25519_generate_new(
+		crate::crypto::Public::ID,
+		Some(&format!("{}/hunter1", PHRASE))
+	).unwrap();
+
+	let mut t = sp_io::TestExternalities::default();
+	t.register_extension(OffchainExt::new(offchain_api()));
+	t.register_extension(TransactionPoolExt::new(pool));
+	t.register_extension(KeystoreExt(keystore));
+
+	t.execute_with(|| {
+		let url = server.url("/data/price?fsym=BTC&tsyms=USD");
+		sp_io::offchain::local_storage_set(StorageKind::PERSISTENT, PRICE_URL_KEY, url.as_bytes());
+
+		Example::offchain_worker(3);
+	});
+
+	let tx = pool_state.write().transactions.pop().expect("The worker submits a transaction");
+	assert!(pool_state.read().transactions.is_empty());
+	Extrinsic::decode(&mut &*tx).unwrap().call
+}
+
+#[test]
+fn offchain_worker_replays_recorded_price() {
+	// The recorder forwards to a second mock server, which stands in for the price source.
+	let upstream = HttpMockServer::replay(vec![
+		Fixture::respond(
+			"GET",
+			"/data/price?fsym=BTC&tsyms=USD",
+			MockResponse::ok(&br#"{"USD": 155.23}"#[..]),
+		),
+	]);
+	let recorder = HttpMockServer::record(&upstream.url(""));
+
+	assert_eq!(run_offchain_worker_against(&recorder), Call::submit_price(15523));
+	upstream.assert_done();
+
+	let path = std::env::temp_dir()
+		.join(format!("example-offchain-worker-fixtures-{}.json", std::process::id()));
+	recorder.save(&path).unwrap();
+	let replay = HttpMockServer::load(&path).unwrap();
+	let _ = std::fs::remove_file(&path);
+	drop(upstream);
+
+	assert_eq!(run_offchain_worker_against(&replay), Call::submit_price(15523));
+	replay.assert_done();
+}
+
 fn price_oracle_response(state: &mut testing::OffchainState) {
 	state.expect_request(testing::PendingRequest {
 		method: "GET".into(),
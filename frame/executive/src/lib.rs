@@ -118,7 +118,7 @@
 use sp_std::{prelude::*, marker::PhantomData};
 use frame_support::{
 	storage::StorageValue, weights::{GetDispatchInfo, DispatchInfo, DispatchClass},
-	traits::{OnInitialize, OnFinalize, OnRuntimeUpgrade, OffchainWorker},
+	traits::{OnInitialize, OnFinalize, OnRuntimeUpgrade, OffchainWorker, FilteredOffchainWorker},
 	dispatch::PostDispatchInfo,
 };
 use sp_runtime::{
@@ -424,6 +424,40 @@ where
 
 	/// Start an offchain worker and generate extrinsics.
 	pub fn offchain_worker(header: &System::Header) {
+		Self::initialize_offchain_worker(header);
+
+		<AllModules as OffchainWorker<System::BlockNumber>>::offchain_worker(
+			// to maintain backward compatibility we call module offchain workers
+			// with parent block number.
+			header.number().saturating_sub(1.into())
+		)
+	}
+
+	/// Start the offchain workers of all modules, except for the modules named in
+	/// `disabled_pallets` by `construct_runtime!`.
+	///
+	/// Returns the names of the modules whose workers ran, with the duration of each run in
+	/// milliseconds.
+	pub fn offchain_worker_filtered(
+		header: &System::Header,
+		disabled_pallets: &[Vec<u8>],
+	) -> Vec<(Vec<u8>, u64)> where
+		AllModules: FilteredOffchainWorker<System::BlockNumber>,
+	{
+		Self::initialize_offchain_worker(header);
+
+		let mut runs = Vec::new();
+		<AllModules as FilteredOffchainWorker<System::BlockNumber>>::offchain_worker_filtered::<
+			System::ModuleToIndex,
+		>(
+			header.number().saturating_sub(1.into()),
+			disabled_pallets,
+			&mut runs,
+		);
+		runs
+	}
+
+	fn initialize_offchain_worker(header: &System::Header) {
 		// We need to keep events available for offchain workers,
 		// hence we initialize the block manually.
 		// OffchainWorker RuntimeApi should skip initialization.
@@ -440,12 +474,6 @@ where
 		// Initialize logger, so the log messages are visible
 		// also when running WASM.
 		frame_support::debug::RuntimeLogger::init();
-
-		<AllModules as OffchainWorker<System::BlockNumber>>::offchain_worker(
-			// to maintain backward compatibility we call module offchain workers
-			// with parent block number.
-			header.number().saturating_sub(1.into())
-		)
 	}
 }
 
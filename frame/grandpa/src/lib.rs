@@ -34,7 +34,7 @@ pub use sp_finality_grandpa as fg_primitives;
 use sp_std::prelude::*;
 
 use codec::{self as codec, Decode, Encode};
-pub use fg_primitives::{AuthorityId, AuthorityList, AuthorityWeight, VersionedAuthorityList};
+pub use fg_primitives::{AuthorityId, AuthorityList, AuthorityWeight, StoredState, VersionedAuthorityList};
 use fg_primitives::{
 	ConsensusLog, EquivocationProof, ScheduledChange, SetId, GRANDPA_AUTHORITIES_KEY,
 	GRANDPA_ENGINE_ID,
@@ -140,34 +140,6 @@ impl<N: Decode> Decode for StoredPendingChange<N> {
 	}
 }
 
-/// Current state of the GRANDPA authority set. State transitions must happen in
-/// the same order of states defined below, e.g. `Paused` implies a prior
-/// `PendingPause`.
-#[derive(Decode, Encode)]
-#[cfg_attr(test, derive(Debug, PartialEq))]
-pub enum StoredState<N> {
-	/// The current authority set is live, and GRANDPA is enabled.
-	Live,
-	/// There is a pending pause event which will be enacted at the given block
-	/// height.
-	PendingPause {
-		/// Block at which the intention to pause was scheduled.
-		scheduled_at: N,
-		/// Number of blocks after which the change will be enacted.
-		delay: N
-	},
-	/// The current GRANDPA authority set is paused.
-	Paused,
-	/// There is a pending resume event which will be enacted at the given block
-	/// height.
-	PendingResume {
-		/// Block at which the intention to resume was scheduled.
-		scheduled_at: N,
-		/// Number of blocks after which the change will be enacted.
-		delay: N,
-	},
-}
-
 decl_event! {
 	pub enum Event {
 		/// New authority set has been applied. [authority_set]
@@ -0,0 +1,428 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The storage and event related parts of decoded runtime metadata, for clients that inspect
+//! the state or the events of a chain.
+
+use codec::Decode;
+use sp_core::hashing::{blake2_128, blake2_256, twox_128, twox_256, twox_64};
+use crate::{
+	DecodeDifferent, RuntimeMetadata, RuntimeMetadataPrefixed, StorageEntryModifier,
+	StorageEntryType, StorageHasher, META_RESERVED,
+};
+
+/// The layout of a storage entry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EntryKind {
+	/// A single value.
+	Plain,
+	/// A map with one key.
+	Map {
+		/// Hasher of the key.
+		hasher: StorageHasher,
+		/// Type name of the key.
+		key: String,
+	},
+	/// A map with two keys.
+	DoubleMap {
+		/// Hasher of the first key.
+		hasher: StorageHasher,
+		/// Type name of the first key.
+		key1: String,
+		/// Hasher of the second key.
+		key2_hasher: StorageHasher,
+		/// Type name of the second key.
+		key2: String,
+	},
+}
+
+/// A storage entry of a module.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StorageEntry {
+	/// Name of the entry, e.g. `Account`.
+	pub name: String,
+	/// Layout of the entry.
+	pub kind: EntryKind,
+	/// Type name of the value.
+	pub value: String,
+	/// The value of a missing entry, if the entry has a default.
+	pub default: Option<Vec<u8>>,
+}
+
+/// Name and argument types of a single event variant.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EventDescription {
+	/// Name of the variant, e.g. `Transfer`.
+	pub name: String,
+	/// Type names of the arguments, as written in `decl_event!`.
+	pub arguments: Vec<String>,
+}
+
+/// The storage entries and events of a module.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ModuleInfo {
+	/// Name of the module, as given in `construct_runtime!`.
+	pub name: String,
+	/// Prefix of the storage entries of the module, e.g. `System`.
+	pub storage_prefix: Option<String>,
+	/// The storage entries of the module.
+	pub storage: Vec<StorageEntry>,
+	/// The events of the module, in the order of their encoding index.
+	pub events: Option<Vec<EventDescription>>,
+}
+
+/// The storage entries and events of all modules of a runtime.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RuntimeInfo {
+	modules: Vec<ModuleInfo>,
+}
+
+impl RuntimeInfo {
+	/// Create an instance from already decoded modules.
+	pub fn new(modules: Vec<ModuleInfo>) -> Self {
+		RuntimeInfo { modules }
+	}
+
+	/// Extract the storage entries and events from SCALE encoded `RuntimeMetadataPrefixed`.
+	pub fn decode(mut encoded: &[u8]) -> Result<Self, String> {
+		let prefixed = RuntimeMetadataPrefixed::decode(&mut encoded)
+			.map_err(|e| format!("Invalid runtime metadata: {:?}", e))?;
+		if prefixed.0 != META_RESERVED {
+			return Err("Invalid runtime metadata: missing magic number".into());
+		}
+		let metadata = match prefixed.1 {
+			RuntimeMetadata::V11(metadata) => metadata,
+			_ => return Err("Unsupported runtime metadata version".into()),
+		};
+
+		let mut modules = Vec::new();
+		for module in decoded(metadata.modules)? {
+			let (storage_prefix, storage) = match module.storage {
+				Some(storage) => {
+					let storage = decoded(storage)?;
+					let entries = decoded(storage.entries)?.into_iter()
+						.map(|entry| Ok(StorageEntry {
+							name: decoded(entry.name)?,
+							default: match entry.modifier {
+								StorageEntryModifier::Default => Some(decoded(entry.default)?),
+								StorageEntryModifier::Optional => None,
+							},
+							kind: match &entry.ty {
+								StorageEntryType::Plain(_) => EntryKind::Plain,
+								StorageEntryType::Map { hasher, key, .. } => EntryKind::Map {
+									hasher: hasher.clone(),
+									key: decoded(key.clone())?,
+								},
+								StorageEntryType::DoubleMap { hasher, key1, key2, key2_hasher, .. } =>
+									EntryKind::DoubleMap {
+										hasher: hasher.clone(),
+										key1: decoded(key1.clone())?,
+										key2_hasher: key2_hasher.clone(),
+										key2: decoded(key2.clone())?,
+									},
+							},
+							value: decoded(match entry.ty {
+								StorageEntryType::Plain(value) => value,
+								StorageEntryType::Map { value, .. } => value,
+								StorageEntryType::DoubleMap { value, .. } => value,
+							})?,
+						}))
+						.collect::<Result<_, String>>()?;
+					(Some(decoded(storage.prefix)?), entries)
+				},
+				None => (None, Vec::new()),
+			};
+			let events = match module.event {
+				Some(events) => Some(
+					decoded(events)?.into_iter()
+						.map(|event| Ok(EventDescription {
+							name: decoded(event.name)?,
+							arguments: decoded(event.arguments)?,
+						}))
+						.collect::<Result<_, String>>()?
+				),
+				None => None,
+			};
+
+			modules.push(ModuleInfo { name: decoded(module.name)?, storage_prefix, storage, events });
+		}
+
+		Ok(RuntimeInfo { modules })
+	}
+
+	/// Returns the module with the given name or storage prefix and its storage entry.
+	pub fn storage_entry(
+		&self,
+		module: &str,
+		entry: &str,
+	) -> Result<(&ModuleInfo, &StorageEntry), String> {
+		let module = self.modules.iter()
+			.find(|m| m.name == module || m.storage_prefix.as_deref() == Some(module))
+			.ok_or_else(|| format!("Unknown module `{}`", module))?;
+		let entry = module.storage.iter()
+			.find(|e| e.name == entry)
+			.ok_or_else(|| format!("Unknown storage entry `{}::{}`", module.name, entry))?;
+		Ok((module, entry))
+	}
+
+	/// Returns the storage entry whose key starts with the first 32 bytes of `key`.
+	pub fn entry_of_key(&self, key: &[u8]) -> Option<(&ModuleInfo, &StorageEntry)> {
+		if key.len() < 32 {
+			return None;
+		}
+		self.modules.iter()
+			.filter(|module| {
+				module.storage_prefix.as_ref()
+					.map_or(false, |prefix| twox_128(prefix.as_bytes()) == key[..16])
+			})
+			.flat_map(|module| module.storage.iter().map(move |entry| (module, entry)))
+			.find(|(_, entry)| twox_128(entry.name.as_bytes()) == key[16..32])
+	}
+
+	/// Returns the module with the given index in the outer event enum.
+	pub fn event_module(&self, index: u8) -> Option<&ModuleInfo> {
+		// Modules without events are not part of the outer event enum.
+		self.modules.iter()
+			.filter(|module| module.events.is_some())
+			.nth(index as usize)
+	}
+
+	/// Returns the module `module_index` of the outer event enum and its event variant
+	/// `event_index`.
+	pub fn event(&self, module_index: u8, event_index: u8) -> Option<(&ModuleInfo, &EventDescription)> {
+		let module = self.event_module(module_index)?;
+		let event = module.events.as_ref()?.get(event_index as usize)?;
+		Some((module, event))
+	}
+}
+
+impl StorageEntry {
+	/// Returns the number of keys of the entry.
+	pub fn key_count(&self) -> usize {
+		match self.kind {
+			EntryKind::Plain => 0,
+			EntryKind::Map { .. } => 1,
+			EntryKind::DoubleMap { .. } => 2,
+		}
+	}
+
+	/// Returns the type names of the keys of the entry.
+	pub fn key_types(&self) -> Vec<&str> {
+		match &self.kind {
+			EntryKind::Plain => Vec::new(),
+			EntryKind::Map { key, .. } => vec![key],
+			EntryKind::DoubleMap { key1, key2, .. } => vec![key1, key2],
+		}
+	}
+
+	/// Computes the storage key of the entry, given the SCALE encoded keys.
+	///
+	/// If fewer keys than the entry has are given, the result is the prefix of all the values
+	/// that share the given keys.
+	pub fn storage_key(&self, prefix: &str, keys: &[Vec<u8>]) -> Result<Vec<u8>, String> {
+		if keys.len() > self.key_count() {
+			return Err(format!(
+				"`{}` has {} key(s), {} given",
+				self.name,
+				self.key_count(),
+				keys.len(),
+			));
+		}
+
+		let mut storage_key = twox_128(prefix.as_bytes()).to_vec();
+		storage_key.extend_from_slice(&twox_128(self.name.as_bytes()));
+		let hashers = match &self.kind {
+			EntryKind::Plain => Vec::new(),
+			EntryKind::Map { hasher, .. } => vec![hasher],
+			EntryKind::DoubleMap { hasher, key2_hasher, .. } => vec![hasher, key2_hasher],
+		};
+		for (hasher, key) in hashers.into_iter().zip(keys) {
+			storage_key.extend(hash(hasher, key));
+		}
+		Ok(storage_key)
+	}
+}
+
+/// Hashes a SCALE encoded map key with the given hasher.
+pub fn hash(hasher: &StorageHasher, key: &[u8]) -> Vec<u8> {
+	match hasher {
+		StorageHasher::Blake2_128 => blake2_128(key).to_vec(),
+		StorageHasher::Blake2_256 => blake2_256(key).to_vec(),
+		StorageHasher::Blake2_128Concat => [&blake2_128(key)[..], key].concat(),
+		StorageHasher::Twox128 => twox_128(key).to_vec(),
+		StorageHasher::Twox256 => twox_256(key).to_vec(),
+		StorageHasher::Twox64Concat => [&twox_64(key)[..], key].concat(),
+		StorageHasher::Identity => key.to_vec(),
+	}
+}
+
+/// Returns the decoded side of a `DecodeDifferent`, which is the only one a client sees.
+pub fn decoded<B, O>(value: DecodeDifferent<B, O>) -> Result<O, String> {
+	match value {
+		DecodeDifferent::Decoded(value) => Ok(value),
+		DecodeDifferent::Encode(_) => Err("Runtime metadata is not decoded".into()),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::Encode;
+	use crate::{
+		DefaultByteGetter, EventMetadata, ExtrinsicMetadata, FnEncode, ModuleMetadata,
+		RuntimeMetadataV11, StorageEntryMetadata, StorageMetadata,
+	};
+
+	fn system_events() -> &'static [EventMetadata] {
+		&[
+			EventMetadata {
+				name: DecodeDifferent::Encode("ExtrinsicSuccess"),
+				arguments: DecodeDifferent::Encode(&["DispatchInfo"]),
+				documentation: DecodeDifferent::Encode(&[]),
+			},
+		]
+	}
+
+	fn balances_events() -> &'static [EventMetadata] {
+		&[
+			EventMetadata {
+				name: DecodeDifferent::Encode("Transfer"),
+				arguments: DecodeDifferent::Encode(&["AccountId", "AccountId", "Balance"]),
+				documentation: DecodeDifferent::Encode(&[]),
+			},
+		]
+	}
+
+	struct ZeroAccount;
+
+	impl crate::DefaultByte for ZeroAccount {
+		fn default_byte(&self) -> Vec<u8> {
+			vec![0; 4]
+		}
+	}
+
+	fn system_storage() -> StorageMetadata {
+		StorageMetadata {
+			prefix: DecodeDifferent::Encode("System"),
+			entries: DecodeDifferent::Encode(&[
+				StorageEntryMetadata {
+					name: DecodeDifferent::Encode("Account"),
+					modifier: StorageEntryModifier::Default,
+					ty: StorageEntryType::Map {
+						hasher: StorageHasher::Blake2_128Concat,
+						key: DecodeDifferent::Encode("T::AccountId"),
+						value: DecodeDifferent::Encode("AccountInfo<T::Index, T::AccountData>"),
+						unused: false,
+					},
+					default: DecodeDifferent::Encode(DefaultByteGetter(&ZeroAccount)),
+					documentation: DecodeDifferent::Encode(&[]),
+				},
+			]),
+		}
+	}
+
+	fn module(
+		name: &'static str,
+		storage: Option<fn() -> StorageMetadata>,
+		event: Option<fn() -> &'static [EventMetadata]>,
+	) -> ModuleMetadata {
+		ModuleMetadata {
+			name: DecodeDifferent::Encode(name),
+			storage: storage.map(|storage| DecodeDifferent::Encode(FnEncode(storage))),
+			calls: None,
+			event: event.map(|event| DecodeDifferent::Encode(FnEncode(event))),
+			constants: DecodeDifferent::Encode(FnEncode(|| &[])),
+			errors: DecodeDifferent::Encode(FnEncode(|| &[])),
+		}
+	}
+
+	/// Runtime with `System`, `Timestamp` (no events) and `Balances`.
+	fn runtime_info() -> RuntimeInfo {
+		let modules = vec![
+			module("System", Some(system_storage), Some(system_events)),
+			module("Timestamp", None, None),
+			module("Balances", None, Some(balances_events)),
+		];
+		let encoded = RuntimeMetadataPrefixed(META_RESERVED, RuntimeMetadata::V11(RuntimeMetadataV11 {
+			modules: DecodeDifferent::Decoded(modules),
+			extrinsic: ExtrinsicMetadata { version: 4, signed_extensions: Vec::new() },
+		})).encode();
+
+		RuntimeInfo::decode(&encoded).unwrap()
+	}
+
+	fn account() -> StorageEntry {
+		StorageEntry {
+			name: "Account".into(),
+			kind: EntryKind::Map {
+				hasher: StorageHasher::Blake2_128Concat,
+				key: "T::AccountId".into(),
+			},
+			value: "AccountInfo<T::Index, T::AccountData>".into(),
+			default: Some(vec![0; 4]),
+		}
+	}
+
+	#[test]
+	fn storage_keys_are_hashed() {
+		let entry = account();
+		let prefix = entry.storage_key("System", &[]).unwrap();
+		assert_eq!(prefix, [twox_128(b"System"), twox_128(b"Account")].concat());
+
+		let key = entry.storage_key("System", &[vec![1; 32]]).unwrap();
+		assert_eq!(key.len(), 32 + 16 + 32);
+		assert_eq!(&key[..32], &prefix[..]);
+		assert_eq!(&key[32..48], &blake2_128(&[1; 32])[..]);
+		assert_eq!(&key[48..], &[1; 32][..]);
+
+		assert!(entry.storage_key("System", &[vec![1], vec![2]]).is_err());
+	}
+
+	#[test]
+	fn keys_are_resolved_to_entries() {
+		let info = runtime_info();
+		let key = account().storage_key("System", &[vec![1; 32]]).unwrap();
+
+		let (module, entry) = info.entry_of_key(&key).unwrap();
+		assert_eq!((module.name.as_str(), entry), ("System", &account()));
+		assert!(info.entry_of_key(&[0; 40]).is_none());
+		assert_eq!(info.storage_entry("System", "Account").unwrap().1, &account());
+		assert!(info.storage_entry("System", "Events").is_err());
+	}
+
+	#[test]
+	fn modules_without_events_are_skipped() {
+		let info = runtime_info();
+
+		assert_eq!(info.event_module(0).unwrap().name, "System");
+		assert_eq!(info.event_module(1).unwrap().name, "Balances");
+		assert!(info.event_module(2).is_none());
+		let (module, event) = info.event(1, 0).unwrap();
+		assert_eq!(module.name, "Balances");
+		assert_eq!(event, &EventDescription {
+			name: "Transfer".into(),
+			arguments: vec!["AccountId".into(), "AccountId".into(), "Balance".into()],
+		});
+		assert!(info.event(1, 1).is_none());
+	}
+
+	#[test]
+	fn invalid_metadata_is_rejected() {
+		assert!(RuntimeInfo::decode(&[1, 2, 3]).is_err());
+		assert!(RuntimeInfo::decode(&0u32.encode()).is_err());
+	}
+}
@@ -31,6 +31,9 @@ use codec::{Encode, Output};
 use sp_std::vec::Vec;
 use sp_core::RuntimeDebug;
 
+#[cfg(feature = "std")]
+pub mod decoded;
+
 #[cfg(feature = "std")]
 type StringBuf = String;
 
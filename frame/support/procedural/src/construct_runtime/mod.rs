@@ -381,12 +381,13 @@ fn decl_module_to_index<'a>(
 	num_modules: usize,
 	scrate: &TokenStream2,
 ) -> TokenStream2 {
-	let names = module_declarations.map(|d| &d.name);
+	let names = module_declarations.map(|d| &d.name).collect::<Vec<_>>();
+	let name_strings = names.iter().map(|name| name.to_string());
 	let indices = 0..num_modules;
 
 	quote!(
 		/// Provides an implementation of `ModuleToIndex` to map a module
-		/// to its index in the runtime.
+		/// to its index and its name in the runtime.
 		pub struct ModuleToIndex;
 
 		impl #scrate::traits::ModuleToIndex for ModuleToIndex {
@@ -400,6 +401,17 @@ fn decl_module_to_index<'a>(
 
 				None
 			}
+
+			fn module_to_name<M: 'static>() -> Option<&'static str> {
+				let type_id = #scrate::sp_std::any::TypeId::of::<M>();
+				#(
+					if type_id == #scrate::sp_std::any::TypeId::of::<#names>() {
+						return Some(#name_strings)
+					}
+				)*
+
+				None
+			}
 		}
 	)
 }
@@ -1379,6 +1379,19 @@ macro_rules! decl_module {
 		{
 			fn offchain_worker(_block_number_not_used: $trait_instance::BlockNumber) { $( $impl )* }
 		}
+
+		impl<$trait_instance: $trait_name$(<I>, $instance: $instantiable)?>
+			$crate::traits::FilteredOffchainWorker<$trait_instance::BlockNumber>
+			for $module<$trait_instance$(, $instance)?> where $( $other_where_bounds )*
+		{
+			fn offchain_worker_filtered<M: $crate::traits::ModuleToIndex>(
+				n: $trait_instance::BlockNumber,
+				_: &[$crate::sp_std::vec::Vec<u8>],
+				_: &mut $crate::sp_std::vec::Vec<($crate::sp_std::vec::Vec<u8>, u64)>,
+			) {
+				<Self as $crate::traits::OffchainWorker<$trait_instance::BlockNumber>>::offchain_worker(n)
+			}
+		}
 	};
 
 	(@impl_offchain
@@ -1392,6 +1405,19 @@ macro_rules! decl_module {
 		{
 			fn offchain_worker($param: $param_ty) { $( $impl )* }
 		}
+
+		impl<$trait_instance: $trait_name$(<I>, $instance: $instantiable)?>
+			$crate::traits::FilteredOffchainWorker<$trait_instance::BlockNumber>
+			for $module<$trait_instance$(, $instance)?> where $( $other_where_bounds )*
+		{
+			fn offchain_worker_filtered<M: $crate::traits::ModuleToIndex>(
+				n: $trait_instance::BlockNumber,
+				_: &[$crate::sp_std::vec::Vec<u8>],
+				_: &mut $crate::sp_std::vec::Vec<($crate::sp_std::vec::Vec<u8>, u64)>,
+			) {
+				<Self as $crate::traits::OffchainWorker<$trait_instance::BlockNumber>>::offchain_worker(n)
+			}
+		}
 	};
 
 	(@impl_offchain
@@ -1402,6 +1428,18 @@ macro_rules! decl_module {
 			$crate::traits::OffchainWorker<$trait_instance::BlockNumber>
 			for $module<$trait_instance$(, $instance)?> where $( $other_where_bounds )*
 		{}
+
+		impl<$trait_instance: $trait_name$(<I>, $instance: $instantiable)?>
+			$crate::traits::FilteredOffchainWorker<$trait_instance::BlockNumber>
+			for $module<$trait_instance$(, $instance)?> where $( $other_where_bounds )*
+		{
+			fn offchain_worker_filtered<M: $crate::traits::ModuleToIndex>(
+				_: $trait_instance::BlockNumber,
+				_: &[$crate::sp_std::vec::Vec<u8>],
+				_: &mut $crate::sp_std::vec::Vec<($crate::sp_std::vec::Vec<u8>, u64)>,
+			) {
+			}
+		}
 	};
 
 	// Expansion for _origin_ dispatch functions with no return type.
@@ -1380,6 +1380,9 @@ pub trait ValidatorRegistration<ValidatorId> {
 pub trait ModuleToIndex {
 	/// Convert the given module `M` into an index.
 	fn module_to_index<M: 'static>() -> Option<usize>;
+
+	/// Convert the given module `M` into the name it is given in `construct_runtime!`.
+	fn module_to_name<M: 'static>() -> Option<&'static str> { None }
 }
 
 impl ModuleToIndex for () {
@@ -1486,6 +1489,50 @@ pub trait OffchainWorker<BlockNumber> {
 	fn offchain_worker(_n: BlockNumber) {}
 }
 
+/// Off-chain computation of a set of modules that skips the workers of some of them.
+///
+/// Implemented for modules by `decl_module!` and for tuples of modules, like the `AllModules`
+/// type of `construct_runtime!`.
+pub trait FilteredOffchainWorker<BlockNumber> {
+	/// Runs the offchain workers of all modules, except for the modules named in `disabled`.
+	///
+	/// Modules are named by `M`, usually the `ModuleToIndex` type of `construct_runtime!`, e.g.
+	/// `ImOnline`. The name of every module whose worker ran is added to `runs`, together with
+	/// the duration of the run in milliseconds. Must only be called from within an offchain
+	/// worker.
+	fn offchain_worker_filtered<M: ModuleToIndex>(
+		n: BlockNumber,
+		disabled: &[Vec<u8>],
+		runs: &mut Vec<(Vec<u8>, u64)>,
+	);
+}
+
+#[impl_for_tuples(30)]
+#[tuple_types_no_default_trait_bound]
+impl<BlockNumber: Clone> FilteredOffchainWorker<BlockNumber> for Tuple {
+	for_tuples!( where #( Tuple: OffchainWorker<BlockNumber> + FilteredOffchainWorker<BlockNumber> + 'static )* );
+
+	fn offchain_worker_filtered<M: ModuleToIndex>(
+		n: BlockNumber,
+		disabled: &[Vec<u8>],
+		runs: &mut Vec<(Vec<u8>, u64)>,
+	) {
+		for_tuples!( #(
+			match M::module_to_name::<Tuple>() {
+				Some(name) if disabled.iter().any(|d| &d[..] == name.as_bytes()) => (),
+				Some(name) => {
+					let started = sp_io::offchain::timestamp();
+					Tuple::offchain_worker(n.clone());
+					let duration = sp_io::offchain::timestamp().diff(&started).millis();
+					runs.push((name.as_bytes().to_vec(), duration));
+				},
+				// not a module of the runtime, e.g. a nested tuple of modules
+				None => Tuple::offchain_worker_filtered::<M>(n.clone(), disabled, runs),
+			}
+		)* );
+	}
+}
+
 pub mod schedule {
 	use super::*;
 
@@ -1674,4 +1721,65 @@ mod tests {
 		assert_eq!(<(Test, Test)>::on_initialize(0), 20);
 		assert_eq!(<(Test, Test)>::on_runtime_upgrade(), 40);
 	}
+
+	#[test]
+	fn filtered_offchain_worker_skips_disabled_modules() {
+		use sp_core::offchain::{OffchainExt, testing::TestOffchainExt};
+
+		thread_local! {
+			static RUNS: std::cell::RefCell<Vec<u8>> = std::cell::RefCell::new(Vec::new());
+		}
+		struct First;
+		struct Second;
+		impl OffchainWorker<u8> for First {
+			fn offchain_worker(_n: u8) {
+				RUNS.with(|runs| runs.borrow_mut().push(1));
+			}
+		}
+		impl OffchainWorker<u8> for Second {
+			fn offchain_worker(_n: u8) {
+				RUNS.with(|runs| runs.borrow_mut().push(2));
+			}
+		}
+		impl FilteredOffchainWorker<u8> for First {
+			fn offchain_worker_filtered<M: ModuleToIndex>(n: u8, _: &[Vec<u8>], _: &mut Vec<(Vec<u8>, u64)>) {
+				Self::offchain_worker(n)
+			}
+		}
+		impl FilteredOffchainWorker<u8> for Second {
+			fn offchain_worker_filtered<M: ModuleToIndex>(n: u8, _: &[Vec<u8>], _: &mut Vec<(Vec<u8>, u64)>) {
+				Self::offchain_worker(n)
+			}
+		}
+		struct Names;
+		impl ModuleToIndex for Names {
+			fn module_to_index<M: 'static>() -> Option<usize> { None }
+			fn module_to_name<M: 'static>() -> Option<&'static str> {
+				let type_id = sp_std::any::TypeId::of::<M>();
+				if type_id == sp_std::any::TypeId::of::<First>() {
+					Some("First")
+				} else if type_id == sp_std::any::TypeId::of::<Second>() {
+					Some("Second")
+				} else {
+					None
+				}
+			}
+		}
+
+		let (offchain, _) = TestOffchainExt::new();
+		let mut ext = sp_io::TestExternalities::default();
+		ext.register_extension(OffchainExt::new(offchain));
+		ext.execute_with(|| {
+			// nested like the `AllModules` of `construct_runtime!`
+			let mut runs = Vec::new();
+			<(First, (Second, ()))>::offchain_worker_filtered::<Names>(0, &[b"Third".to_vec()], &mut runs);
+			assert_eq!(RUNS.with(|runs| runs.replace(Vec::new())), vec![1, 2]);
+			assert_eq!(runs, vec![(b"First".to_vec(), 0), (b"Second".to_vec(), 0)]);
+
+			let mut runs = Vec::new();
+			<(First, (Second, ()))>::offchain_worker_filtered::<Names>(0, &[b"Second".to_vec()], &mut runs);
+			assert_eq!(RUNS.with(|runs| runs.replace(Vec::new())), vec![1]);
+			assert_eq!(runs, vec![(b"First".to_vec(), 0)]);
+		});
+	}
 }
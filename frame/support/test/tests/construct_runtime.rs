@@ -157,3 +157,14 @@ fn integrity_test_works() {
 	__construct_runtime_integrity_test::runtime_integrity_tests();
 	assert_eq!(INTEGRITY_TEST_EXEC.with(|i| *i.borrow()), 1);
 }
+
+#[test]
+fn module_to_name_returns_the_construct_runtime_names() {
+	use frame_support::traits::ModuleToIndex as _;
+
+	assert_eq!(ModuleToIndex::module_to_name::<System>(), Some("System"));
+	assert_eq!(ModuleToIndex::module_to_name::<Module1_1>(), Some("Module1_1"));
+	assert_eq!(ModuleToIndex::module_to_name::<Module1_2>(), Some("Module1_2"));
+	assert_eq!(ModuleToIndex::module_to_name::<Module2>(), Some("Module2"));
+	assert_eq!(ModuleToIndex::module_to_name::<Runtime>(), None);
+}
@@ -93,7 +93,7 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 #[cfg(feature = "std")]
-use serde::Serialize;
+use serde::{Serialize, Deserialize};
 use sp_std::prelude::*;
 #[cfg(any(feature = "std", test))]
 use sp_std::map;
@@ -296,7 +296,7 @@ pub type KeyValue = (Vec<u8>, Vec<u8>);
 
 /// A phase of a block's execution.
 #[derive(Encode, Decode, RuntimeDebug)]
-#[cfg_attr(feature = "std", derive(Serialize, PartialEq, Eq, Clone))]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize, PartialEq, Eq, Clone))]
 pub enum Phase {
 	/// Applying an extrinsic.
 	ApplyExtrinsic(u32),
@@ -1243,6 +1243,9 @@ impl<T: Trait> Module<T> {
 		ensure_root(origin)?;
 
 		let current_version = T::Version::get();
+		// The executor transparently decompresses code that was compressed by `wasm-builder`,
+		// so the version is read from either form. Decompression is bounded, so a compression
+		// bomb fails here instead of on every node that loads the new runtime.
 		let new_version = sp_io::misc::runtime_version(&code)
 			.and_then(|v| RuntimeVersion::decode(&mut &v[..]).ok())
 			.ok_or_else(|| Error::<T>::FailedToExtractRuntimeVersion)?;
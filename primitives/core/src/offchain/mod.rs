@@ -55,6 +55,15 @@ pub trait OffchainStorage: Clone + Send + Sync {
 	) -> bool;
 }
 
+/// Controls which offchain workers of the runtime the node runs.
+#[cfg(feature = "std")]
+pub trait OffchainWorkerControl: Send + Sync + std::fmt::Debug {
+	/// Enables or disables the worker of the given pallet for subsequent blocks.
+	///
+	/// Pallets are named as in the runtime's `construct_runtime!`, e.g. `ImOnline`.
+	fn set_pallet_enabled(&self, pallet: &str, enabled: bool);
+}
+
 /// A type of supported crypto.
 #[derive(Clone, Copy, PartialEq, Eq, Encode, Decode, RuntimeDebug, PassByEnum)]
 #[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
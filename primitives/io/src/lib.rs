@@ -381,6 +381,8 @@ pub trait Misc {
 	/// Returns `None` if calling the function failed for any reason or `Some(Vec<u8>)` where
 	/// the `Vec<u8>` holds the SCALE encoded runtime version.
 	///
+	/// The wasm blob may be compressed with `sp-maybe-compressed-blob`.
+	///
 	/// # Performance
 	///
 	/// Calling this function is very expensive and should only be done very occasionally.
@@ -23,9 +23,11 @@
 /// Re-export of parent module scope storage prefix.
 pub use sp_core::offchain::STORAGE_PREFIX as STORAGE_PREFIX;
 
+use sp_runtime::sp_std::vec::Vec;
+
 sp_api::decl_runtime_apis! {
 	/// The offchain worker api.
-	#[api_version(2)]
+	#[api_version(3)]
 	pub trait OffchainWorkerApi {
 		/// Starts the off-chain task for given block number.
 		#[skip_initialize_block]
@@ -35,5 +37,16 @@ sp_api::decl_runtime_apis! {
 		/// Starts the off-chain task for given block header.
 		#[skip_initialize_block]
 		fn offchain_worker(header: &Block::Header);
+
+		/// Starts the off-chain task for given block header, except for the workers of the
+		/// pallets in `disabled_pallets`, given by their name in the runtime, e.g. `ImOnline`.
+		///
+		/// Returns the names of the pallets whose workers ran, with the duration of each run in
+		/// milliseconds.
+		#[skip_initialize_block]
+		fn offchain_worker_filtered(
+			header: &Block::Header,
+			disabled_pallets: Vec<Vec<u8>>,
+		) -> Vec<(Vec<u8>, u64)>;
 	}
 }
@@ -230,6 +230,11 @@ pub trait Backend<H: Hasher>: std::fmt::Debug {
 	fn set_whitelist(&self, _: Vec<Vec<u8>>) {
 		unimplemented!()
 	}
+
+	/// Get the encoded size of the proof of the db reads since the last reset
+	fn proof_size(&self) -> u32 {
+		unimplemented!()
+	}
 }
 
 impl<'a, T: Backend<H>, H: Hasher> Backend<H> for &'a T {
@@ -328,6 +328,10 @@ impl Externalities for BasicExternalities {
 	fn set_whitelist(&mut self, _: Vec<Vec<u8>>) {
 		unimplemented!("set_whitelist is not supported in Basic")
 	}
+
+	fn proof_size(&self) -> u32 {
+		unimplemented!("proof_size is not supported in Basic")
+	}
 }
 
 impl sp_externalities::ExtensionStore for BasicExternalities {
@@ -612,6 +612,10 @@ where
 	fn set_whitelist(&mut self, new: Vec<Vec<u8>>) {
 		self.backend.set_whitelist(new)
 	}
+
+	fn proof_size(&self) -> u32 {
+		self.backend.proof_size()
+	}
 }
 
 
@@ -64,6 +64,9 @@ pub enum Error {
 	/// The pool is not accepting future transactions.
 	#[display(fmt="The pool is not accepting future transactions")]
 	RejectedFutureTransaction,
+	/// The pool can't keep a transaction from being propagated to other nodes.
+	#[display(fmt="The pool doesn't support submitting transactions without propagation")]
+	PropagationControlUnsupported,
 }
 
 impl std::error::Error for Error {}
@@ -204,6 +204,22 @@ pub trait TransactionPool: Send + Sync {
 		xt: TransactionFor<Self>,
 	) -> PoolFuture<TxHash<Self>, Self::Error>;
 
+	/// Returns a future that imports one unverified transaction to the pool, making sure that
+	/// it is never propagated to other nodes.
+	///
+	/// Pools that can't keep a transaction from being propagated reject it with
+	/// `Error::PropagationControlUnsupported`, which is what the default implementation does.
+	fn submit_one_without_propagation(
+		&self,
+		_at: &BlockId<Self::Block>,
+		_source: TransactionSource,
+		_xt: TransactionFor<Self>,
+	) -> PoolFuture<TxHash<Self>, Self::Error> {
+		Box::pin(futures::future::ready(
+			Err(crate::error::Error::PropagationControlUnsupported.into())
+		))
+	}
+
 	/// Returns a future that import a single transaction and starts to watch their progress in the pool.
 	fn submit_and_watch(
 		&self,
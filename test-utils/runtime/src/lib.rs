@@ -697,6 +697,23 @@ cfg_if! {
 						AuraId::from(authority)
 					}).collect()
 				}
+
+				fn submit_report_equivocation_unsigned_extrinsic(
+					_equivocation_proof: sp_consensus_aura::EquivocationProof<
+						<Block as BlockT>::Header,
+						AuraId,
+					>,
+					_key_owner_proof: sp_consensus_aura::OpaqueKeyOwnershipProof,
+				) -> Option<()> {
+					None
+				}
+
+				fn generate_key_ownership_proof(
+					_slot_number: sp_consensus_aura::SlotNumber,
+					_authority_id: AuraId,
+				) -> Option<sp_consensus_aura::OpaqueKeyOwnershipProof> {
+					None
+				}
 			}
 
 			impl sp_consensus_babe::BabeApi<Block> for Runtime {
@@ -738,6 +755,18 @@ cfg_if! {
 					let ex = Extrinsic::IncludeData(header.number.encode());
 					sp_io::offchain::submit_transaction(ex.encode()).unwrap();
 				}
+
+				fn offchain_worker_filtered(
+					header: &<Block as BlockT>::Header,
+					disabled_pallets: Vec<Vec<u8>>,
+				) -> Vec<(Vec<u8>, u64)> {
+					if disabled_pallets.iter().any(|pallet| &pallet[..] == b"TestRuntime") {
+						return Vec::new();
+					}
+					let ex = Extrinsic::IncludeData(header.number.encode());
+					sp_io::offchain::submit_transaction(ex.encode()).unwrap();
+					vec![(b"TestRuntime".to_vec(), 0)]
+				}
 			}
 
 			impl sp_session::SessionKeys<Block> for Runtime {
@@ -939,6 +968,23 @@ cfg_if! {
 						AuraId::from(authority)
 					}).collect()
 				}
+
+				fn submit_report_equivocation_unsigned_extrinsic(
+					_equivocation_proof: sp_consensus_aura::EquivocationProof<
+						<Block as BlockT>::Header,
+						AuraId,
+					>,
+					_key_owner_proof: sp_consensus_aura::OpaqueKeyOwnershipProof,
+				) -> Option<()> {
+					None
+				}
+
+				fn generate_key_ownership_proof(
+					_slot_number: sp_consensus_aura::SlotNumber,
+					_authority_id: AuraId,
+				) -> Option<sp_consensus_aura::OpaqueKeyOwnershipProof> {
+					None
+				}
 			}
 
 			impl sp_consensus_babe::BabeApi<Block> for Runtime {
@@ -980,6 +1026,18 @@ cfg_if! {
 					let ex = Extrinsic::IncludeData(header.number.encode());
 					sp_io::offchain::submit_transaction(ex.encode()).unwrap()
 				}
+
+				fn offchain_worker_filtered(
+					header: &<Block as BlockT>::Header,
+					disabled_pallets: Vec<Vec<u8>>,
+				) -> Vec<(Vec<u8>, u64)> {
+					if disabled_pallets.iter().any(|pallet| &pallet[..] == b"TestRuntime") {
+						return Vec::new();
+					}
+					let ex = Extrinsic::IncludeData(header.number.encode());
+					sp_io::offchain::submit_transaction(ex.encode()).unwrap();
+					vec![(b"TestRuntime".to_vec(), 0)]
+				}
 			}
 
 			impl sp_session::SessionKeys<Block> for Runtime {
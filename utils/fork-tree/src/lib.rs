@@ -259,6 +259,21 @@ impl<H, N, V> ForkTree<H, N, V> where
 		self.node_iter().map(|node| (&node.hash, &node.number, &node.data))
 	}
 
+	/// Iterates the nodes in the tree in pre-order, together with the hash of
+	/// their parent node (`None` for the roots).
+	pub fn iter_with_parents(&self) -> impl Iterator<Item=(Option<&H>, &H, &N, &V)> {
+		let mut stack: Vec<(Option<&H>, &Node<H, N, V>)> =
+			self.roots.iter().map(|node| (None, node)).collect();
+
+		std::iter::from_fn(move || {
+			stack.pop().map(|(parent, node)| {
+				// same ordering as `ForkTreeIterator`.
+				stack.extend(node.children.iter().rev().map(|child| (Some(&node.hash), child)));
+				(parent, &node.hash, &node.number, &node.data)
+			})
+		})
+	}
+
 	/// Find a node in the tree that is the deepest ancestor of the given
 	/// block hash and which passes the given predicate. The given function
 	/// `is_descendent_of` should return `true` if the second hash (target)
@@ -1363,6 +1378,25 @@ mod test {
 		);
 	}
 
+	#[test]
+	fn iter_with_parents_reports_parent_nodes() {
+		let (tree, ..) = test_fork_tree();
+		assert_eq!(
+			tree.iter_with_parents()
+				.map(|(p, h, _, _)| (p.cloned(), h.clone()))
+				.collect::<Vec<_>>(),
+			vec![
+				(None, "A"),
+				(Some("A"), "B"), (Some("B"), "C"), (Some("C"), "D"), (Some("D"), "E"),
+				(Some("A"), "F"),
+				(Some("F"), "G"),
+				(Some("F"), "H"), (Some("H"), "I"),
+				(Some("H"), "L"), (Some("L"), "M"), (Some("L"), "O"),
+				(Some("A"), "J"), (Some("J"), "K"),
+			],
+		);
+	}
+
 	#[test]
 	fn minimizes_calls_to_is_descendent_of() {
 		use std::sync::atomic::{AtomicUsize, Ordering};
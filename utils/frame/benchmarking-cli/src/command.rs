@@ -17,7 +17,7 @@
 
 use crate::BenchmarkCmd;
 use codec::{Decode, Encode};
-use frame_benchmarking::{Analysis, BenchmarkBatch, BenchmarkSelector};
+use frame_benchmarking::{Analysis, BenchmarkBatch, BenchmarkList, BenchmarkSelector};
 use sc_cli::{SharedParams, CliConfiguration, ExecutionStrategy, Result};
 use sc_client_db::BenchmarkingState;
 use sc_executor::NativeExecutor;
@@ -46,8 +46,6 @@ impl BenchmarkCmd {
 		let strategy = self.execution.unwrap_or(ExecutionStrategy::Native);
 
 		let genesis_storage = spec.build_storage()?;
-		let mut changes = Default::default();
-		let mut offchain_changes = Default::default();
 		let cache_size = Some(self.database_cache_size as usize);
 		let state = BenchmarkingState::<BB>::new(genesis_storage, cache_size)?;
 		let executor = NativeExecutor::<ExecDispatch>::new(
@@ -56,121 +54,236 @@ impl BenchmarkCmd {
 			2, // The runtime instances cache size.
 		);
 
-		let mut extensions = Extensions::default();
-		extensions.register(KeystoreExt(KeyStore::new()));
-		let (offchain, _) = TestOffchainExt::new();
-		extensions.register(OffchainExt::new(offchain));
-
-		let result = StateMachine::<_, _, NumberFor<BB>, _>::new(
-			&state,
-			None,
-			&mut changes,
-			&mut offchain_changes,
-			&executor,
-			"Benchmark_dispatch_benchmark",
-			&(
-				&self.pallet,
-				&self.extrinsic,
-				self.lowest_range_values.clone(),
-				self.highest_range_values.clone(),
-				self.steps.clone(),
+		let call = |method: &str, data: &[u8]| -> Result<Vec<u8>> {
+			let mut changes = Default::default();
+			let mut offchain_changes = Default::default();
+			let mut extensions = Extensions::default();
+			extensions.register(KeystoreExt(KeyStore::new()));
+			let (offchain, _) = TestOffchainExt::new();
+			extensions.register(OffchainExt::new(offchain));
+
+			let result = StateMachine::<_, _, NumberFor<BB>, _>::new(
+				&state,
+				None,
+				&mut changes,
+				&mut offchain_changes,
+				&executor,
+				method,
+				data,
+				extensions,
+				&sp_state_machine::backend::BackendRuntimeCode::new(&state).runtime_code()?,
+				sp_core::testing::TaskExecutor::new(),
+			)
+			.execute(strategy.into())
+			.map_err(|e| format!("Error executing runtime benchmark: {:?}", e))?;
+			Ok(result)
+		};
+
+		let mut batches = Vec::new();
+		let mut failed = Vec::new();
+		for (pallet, extrinsic) in self.selected_benchmarks(&call)? {
+			let result = call(
+				"Benchmark_dispatch_benchmark",
+				&(
+					&pallet,
+					&extrinsic,
+					self.lowest_range_values.clone(),
+					self.highest_range_values.clone(),
+					self.steps.clone(),
+					self.repeat,
+				).encode(),
+			)?;
+
+			let results = <std::result::Result<Vec<BenchmarkBatch>, String> as Decode>::decode(&mut &result[..])
+				.map_err(|e| format!("Failed to decode benchmark results: {:?}", e))?;
+
+			match results {
+				Ok(results) => batches.extend(results),
+				Err(error) => {
+					let name = format!(
+						"{}::{}",
+						String::from_utf8_lossy(&pallet),
+						String::from_utf8_lossy(&extrinsic),
+					);
+					eprintln!("Error: {:?} ({})", error, name);
+					failed.push(name);
+				},
+			}
+		}
+
+		if let Some(path) = &self.json_file {
+			crate::writer::write_json(&batches, path)?;
+		}
+		if let Some(path) = &self.csv_file {
+			crate::writer::write_csv(&batches, path)?;
+		}
+
+		// If we are going to output results to a file...
+		if self.output {
+			if self.weight_trait {
+				let mut file = crate::writer::open_file("traits.rs")?;
+				crate::writer::write_trait(&mut file, batches.clone())?;
+			} else {
+				crate::writer::write_results(&batches, self)?;
+			}
+		}
+
+		for batch in batches.into_iter() {
+			// Print benchmark metadata
+			println!(
+				"Pallet: {:?}, Extrinsic: {:?}, Lowest values: {:?}, Highest values: {:?}, Steps: {:?}, Repeat: {:?}",
+				String::from_utf8(batch.pallet).expect("Encoded from String; qed"),
+				String::from_utf8(batch.benchmark).expect("Encoded from String; qed"),
+				self.lowest_range_values,
+				self.highest_range_values,
+				self.steps,
 				self.repeat,
-			).encode(),
-			extensions,
-			&sp_state_machine::backend::BackendRuntimeCode::new(&state).runtime_code()?,
-			sp_core::testing::TaskExecutor::new(),
-		)
-		.execute(strategy.into())
-		.map_err(|e| format!("Error executing runtime benchmark: {:?}", e))?;
-
-		let results = <std::result::Result<Vec<BenchmarkBatch>, String> as Decode>::decode(&mut &result[..])
-			.map_err(|e| format!("Failed to decode benchmark results: {:?}", e))?;
-
-		match results {
-			Ok(batches) => {
-				// If we are going to output results to a file...
-				if self.output {
-					if self.weight_trait {
-						let mut file = crate::writer::open_file("traits.rs")?;
-						crate::writer::write_trait(&mut file, batches.clone())?;
-					} else {
-						let mut file = crate::writer::open_file("benchmarks.rs")?;
-						crate::writer::write_results(&mut file, batches.clone())?;
-					}
-				}
+			);
+
+			// Skip raw data + analysis if there are no results
+			if batch.results.is_empty() { continue }
+
+			if self.raw_data {
+				// Print the table header
+				batch.results[0].components.iter().for_each(|param| print!("{:?},", param.0));
 
-				for batch in batches.into_iter() {
-					// Print benchmark metadata
-					println!(
-						"Pallet: {:?}, Extrinsic: {:?}, Lowest values: {:?}, Highest values: {:?}, Steps: {:?}, Repeat: {:?}",
-						String::from_utf8(batch.pallet).expect("Encoded from String; qed"),
-						String::from_utf8(batch.benchmark).expect("Encoded from String; qed"),
-						self.lowest_range_values,
-						self.highest_range_values,
-						self.steps,
-						self.repeat,
+				print!("extrinsic_time,storage_root_time,reads,repeat_reads,writes,repeat_writes,proof_size\n");
+				// Print the values
+				batch.results.iter().for_each(|result| {
+					let parameters = &result.components;
+					parameters.iter().for_each(|param| print!("{:?},", param.1));
+					// Print extrinsic time and storage root time
+					print!("{:?},{:?},{:?},{:?},{:?},{:?},{:?}\n",
+						result.extrinsic_time,
+						result.storage_root_time,
+						result.reads,
+						result.repeat_reads,
+						result.writes,
+						result.repeat_writes,
+						result.proof_size,
 					);
+				});
 
-					// Skip raw data + analysis if there are no results
-					if batch.results.is_empty() { continue }
-
-					if self.raw_data {
-						// Print the table header
-						batch.results[0].components.iter().for_each(|param| print!("{:?},", param.0));
-
-						print!("extrinsic_time,storage_root_time,reads,repeat_reads,writes,repeat_writes\n");
-						// Print the values
-						batch.results.iter().for_each(|result| {
-							let parameters = &result.components;
-							parameters.iter().for_each(|param| print!("{:?},", param.1));
-							// Print extrinsic time and storage root time
-							print!("{:?},{:?},{:?},{:?},{:?},{:?}\n",
-								result.extrinsic_time,
-								result.storage_root_time,
-								result.reads,
-								result.repeat_reads,
-								result.writes,
-								result.repeat_writes,
-							);
-						});
-
-						println!();
-					}
-
-					// Conduct analysis.
-					if !self.no_median_slopes {
-						println!("Median Slopes Analysis\n========");
-						if let Some(analysis) = Analysis::median_slopes(&batch.results, BenchmarkSelector::ExtrinsicTime) {
-							println!("-- Extrinsic Time --\n{}", analysis);
-						}
-						if let Some(analysis) = Analysis::median_slopes(&batch.results, BenchmarkSelector::Reads) {
-							println!("Reads = {:?}", analysis);
-						}
-						if let Some(analysis) = Analysis::median_slopes(&batch.results, BenchmarkSelector::Writes) {
-							println!("Writes = {:?}", analysis);
-						}
-					}
-					if !self.no_min_squares {
-						println!("Min Squares Analysis\n========");
-						if let Some(analysis) = Analysis::min_squares_iqr(&batch.results, BenchmarkSelector::ExtrinsicTime) {
-							println!("-- Extrinsic Time --\n{}", analysis);
-						}
-						if let Some(analysis) = Analysis::min_squares_iqr(&batch.results, BenchmarkSelector::Reads) {
-							println!("Reads = {:?}", analysis);
-						}
-						if let Some(analysis) = Analysis::min_squares_iqr(&batch.results, BenchmarkSelector::Writes) {
-							println!("Writes = {:?}", analysis);
-						}
-					}
+				println!();
+			}
+
+			// Conduct analysis.
+			if !self.no_median_slopes {
+				println!("Median Slopes Analysis\n========");
+				if let Some(analysis) = Analysis::median_slopes(&batch.results, BenchmarkSelector::ExtrinsicTime) {
+					println!("-- Extrinsic Time --\n{}", analysis);
 				}
-			},
-			Err(error) => eprintln!("Error: {:?}", error),
+				if let Some(analysis) = Analysis::median_slopes(&batch.results, BenchmarkSelector::Reads) {
+					println!("Reads = {:?}", analysis);
+				}
+				if let Some(analysis) = Analysis::median_slopes(&batch.results, BenchmarkSelector::Writes) {
+					println!("Writes = {:?}", analysis);
+				}
+				if let Some(analysis) = Analysis::median_slopes(&batch.results, BenchmarkSelector::ProofSize) {
+					println!("Proof Size = {:?}", analysis);
+				}
+			}
+			if !self.no_min_squares {
+				println!("Min Squares Analysis\n========");
+				if let Some(analysis) = Analysis::min_squares_iqr(&batch.results, BenchmarkSelector::ExtrinsicTime) {
+					println!("-- Extrinsic Time --\n{}", analysis);
+				}
+				if let Some(analysis) = Analysis::min_squares_iqr(&batch.results, BenchmarkSelector::Reads) {
+					println!("Reads = {:?}", analysis);
+				}
+				if let Some(analysis) = Analysis::min_squares_iqr(&batch.results, BenchmarkSelector::Writes) {
+					println!("Writes = {:?}", analysis);
+				}
+				if let Some(analysis) = Analysis::min_squares_iqr(&batch.results, BenchmarkSelector::ProofSize) {
+					println!("Proof Size = {:?}", analysis);
+				}
+			}
+		}
+
+		if !failed.is_empty() {
+			return Err(format!("{} benchmarks failed: {}", failed.len(), failed.join(", ")).into());
 		}
 
 		Ok(())
 	}
 }
 
+impl BenchmarkCmd {
+	/// Returns the pallets and extrinsics selected for benchmarking.
+	///
+	/// Names with wildcards are matched against the benchmarks listed by the runtime. If the
+	/// runtime can't list its benchmarks, the selection is passed to it as is, which only
+	/// supports exact names and `*`.
+	fn selected_benchmarks(
+		&self,
+		call: &dyn Fn(&str, &[u8]) -> Result<Vec<u8>>,
+	) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+		let as_is = vec![(self.pallet.as_bytes().to_vec(), self.extrinsic.as_bytes().to_vec())];
+		if !self.pallet.contains('*') && !self.extrinsic.contains('*') {
+			return Ok(as_is)
+		}
+
+		let list = match call("Benchmark_benchmark_metadata", &[]) {
+			Ok(list) => list,
+			Err(e) => {
+				eprintln!("Runtime doesn't list its benchmarks, wildcards are not supported: {:?}", e);
+				return Ok(as_is)
+			},
+		};
+		let list = <Vec<BenchmarkList> as Decode>::decode(&mut &list[..])
+			.map_err(|e| format!("Failed to decode benchmark list: {:?}", e))?;
+
+		let selected = list.into_iter()
+			.filter(|item| wildcard_match(&self.pallet, &String::from_utf8_lossy(&item.pallet)))
+			.flat_map(|item| {
+				let pallet = item.pallet;
+				item.benchmarks.into_iter()
+					.filter(|benchmark| {
+						wildcard_match(&self.extrinsic, &String::from_utf8_lossy(benchmark))
+					})
+					.map(move |benchmark| (pallet.clone(), benchmark))
+			})
+			.collect::<Vec<_>>();
+
+		if selected.is_empty() {
+			return Err(format!(
+				"No benchmarks match pallet `{}` and extrinsic `{}`",
+				self.pallet,
+				self.extrinsic,
+			).into())
+		}
+		Ok(selected)
+	}
+}
+
+/// Returns whether `name` matches `pattern`, in which `*` matches any sequence of characters.
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+	let pattern = pattern.as_bytes();
+	let name = name.as_bytes();
+	let (mut p, mut n) = (0, 0);
+	// Position of the last `*` in the pattern and of the name when it was reached.
+	let mut backtrack = None;
+
+	while n < name.len() {
+		if p < pattern.len() && pattern[p] == b'*' {
+			backtrack = Some((p, n));
+			p += 1;
+		} else if p < pattern.len() && pattern[p] == name[n] {
+			p += 1;
+			n += 1;
+		} else if let Some((star, matched)) = backtrack {
+			// Let the last `*` match one more character.
+			p = star + 1;
+			n = matched + 1;
+			backtrack = Some((star, matched + 1));
+		} else {
+			return false
+		}
+	}
+
+	pattern[p..].iter().all(|c| *c == b'*')
+}
+
 impl CliConfiguration for BenchmarkCmd {
 	fn shared_params(&self) -> &SharedParams {
 		&self.shared_params
@@ -183,3 +296,21 @@ impl CliConfiguration for BenchmarkCmd {
 		})
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::wildcard_match;
+
+	#[test]
+	fn wildcards_match_any_sequence() {
+		assert!(wildcard_match("*", "pallet_balances"));
+		assert!(wildcard_match("pallet_balances", "pallet_balances"));
+		assert!(!wildcard_match("pallet_balances", "pallet_balances_extra"));
+		assert!(wildcard_match("pallet_*", "pallet_balances"));
+		assert!(!wildcard_match("pallet_*", "frame_system"));
+		assert!(wildcard_match("*transfer*", "transfer_keep_alive"));
+		assert!(wildcard_match("*_keep_alive", "transfer_keep_alive"));
+		assert!(wildcard_match("t*a*e", "transfer_keep_alive"));
+		assert!(!wildcard_match("t*x*e", "transfer_keep_alive"));
+	}
+}
@@ -0,0 +1,158 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Compares the results of two benchmark runs.
+
+use std::collections::BTreeMap;
+use sc_cli::Result;
+
+use crate::BenchmarkCompareCmd;
+use crate::writer::{read_json, BenchmarkData, ComponentSlope};
+
+/// The worst case cost of a benchmark.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Cost {
+	weight: u128,
+	reads: u128,
+	writes: u128,
+}
+
+impl Cost {
+	/// Evaluates the analysis of a benchmark at the highest benchmarked value of each component.
+	fn worst_case(data: &BenchmarkData) -> Self {
+		let evaluate = |base: u128, slopes: &[ComponentSlope]| {
+			slopes.iter().fold(base, |total, slope| {
+				let max = data.component_ranges.iter()
+					.find(|range| range.name == slope.name)
+					.map_or(0, |range| range.max);
+				total.saturating_add(slope.slope.saturating_mul(max.into()))
+			})
+		};
+
+		Cost {
+			weight: evaluate(data.base_weight, &data.component_weight),
+			reads: evaluate(data.base_reads, &data.component_reads),
+			writes: evaluate(data.base_writes, &data.component_writes),
+		}
+	}
+}
+
+/// Returns the relative change from `old` to `new` in percent.
+fn change(old: u128, new: u128) -> f64 {
+	match (old, new) {
+		(0, 0) => 0.0,
+		(0, _) => f64::INFINITY,
+		_ => (new as f64 - old as f64) / old as f64 * 100.0,
+	}
+}
+
+impl BenchmarkCompareCmd {
+	/// Compares the benchmarks of the two result files.
+	///
+	/// Fails if the weight, reads or writes of any benchmark grew by more than the threshold.
+	pub fn run(&self) -> Result<()> {
+		let costs = |path: &std::path::Path| -> Result<BTreeMap<(String, String), Cost>> {
+			Ok(read_json(path)?.into_iter()
+				.map(|record| (
+					(record.pallet, record.analysis.name.clone()),
+					Cost::worst_case(&record.analysis),
+				))
+				.collect())
+		};
+		let old = costs(&self.old)?;
+		let new = costs(&self.new)?;
+
+		println!(
+			"{:<30} {:<40} {:>20} {:>20} {:>9} {:>11} {:>12}",
+			"Pallet", "Extrinsic", "Old Weight", "New Weight", "Change", "Reads", "Writes",
+		);
+		let mut regressions = 0;
+		for ((pallet, extrinsic), new_cost) in &new {
+			let old_cost = match old.get(&(pallet.clone(), extrinsic.clone())) {
+				Some(old_cost) => old_cost,
+				None => {
+					println!("{:<30} {:<40} {:>20} {:>20}", pallet, extrinsic, "-", new_cost.weight);
+					continue
+				},
+			};
+
+			let changes = [
+				change(old_cost.weight, new_cost.weight),
+				change(old_cost.reads, new_cost.reads),
+				change(old_cost.writes, new_cost.writes),
+			];
+			let regressed = changes.iter().any(|change| *change > self.threshold);
+			if regressed {
+				regressions += 1;
+			}
+			println!(
+				"{:<30} {:<40} {:>20} {:>20} {:>+8.2}% {:>5} -> {:<3} {:>5} -> {:<3}{}",
+				pallet,
+				extrinsic,
+				old_cost.weight,
+				new_cost.weight,
+				changes[0],
+				old_cost.reads,
+				new_cost.reads,
+				old_cost.writes,
+				new_cost.writes,
+				if regressed { "  REGRESSION" } else { "" },
+			);
+		}
+		for (pallet, extrinsic) in old.keys().filter(|key| !new.contains_key(key)) {
+			println!("{:<30} {:<40} {:>20} {:>20}", pallet, extrinsic, "removed", "-");
+		}
+
+		if regressions > 0 {
+			return Err(format!(
+				"{} benchmark(s) grew by more than {}%",
+				regressions,
+				self.threshold,
+			).into())
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::writer::ComponentRange;
+
+	#[test]
+	fn worst_case_uses_highest_component_values() {
+		let data = BenchmarkData {
+			name: "transfer".into(),
+			component_ranges: vec![ComponentRange { name: "b".into(), min: 1, max: 100 }],
+			base_weight: 1_000,
+			base_reads: 2,
+			component_weight: vec![ComponentSlope { name: "b".into(), slope: 10 }],
+			component_writes: vec![ComponentSlope { name: "b".into(), slope: 1 }],
+			..Default::default()
+		};
+
+		assert_eq!(Cost::worst_case(&data), Cost { weight: 2_000, reads: 2, writes: 100 });
+	}
+
+	#[test]
+	fn change_is_relative() {
+		assert_eq!(change(100, 110), 10.0);
+		assert_eq!(change(100, 50), -50.0);
+		assert_eq!(change(0, 0), 0.0);
+		assert_eq!(change(0, 1), f64::INFINITY);
+	}
+}
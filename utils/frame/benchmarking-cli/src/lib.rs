@@ -16,6 +16,7 @@
 // limitations under the License.
 
 mod command;
+mod compare;
 mod writer;
 
 use sc_cli::{ExecutionStrategy, WasmExecutionMethod};
@@ -24,11 +25,15 @@ use std::fmt::Debug;
 /// The `benchmark` command used to benchmark FRAME Pallets.
 #[derive(Debug, structopt::StructOpt)]
 pub struct BenchmarkCmd {
-	/// Select a FRAME Pallet to benchmark, or `*` for all (in which case `extrinsic` must be `*`).
+	/// Select a FRAME Pallet to benchmark.
+	///
+	/// `*` matches any sequence of characters, e.g. `*` selects all pallets and `pallet_*`
+	/// all pallets whose name starts with `pallet_`.
 	#[structopt(short, long)]
 	pub pallet: String,
 
-	/// Select an extrinsic inside the pallet to benchmark, or `*` for all.
+	/// Select an extrinsic inside the selected pallets to benchmark, `*` matches any sequence
+	/// of characters.
 	#[structopt(short, long)]
 	pub extrinsic: String,
 
@@ -60,10 +65,30 @@ pub struct BenchmarkCmd {
 	#[structopt(long)]
 	pub no_min_squares: bool,
 
-	/// Output the benchmarks to a Rust file.
+	/// Output the benchmarks to a Rust file per pallet, named `<pallet>.rs`.
 	#[structopt(long)]
 	pub output: bool,
 
+	/// Render the output files with the given Handlebars template instead of the built-in one.
+	///
+	/// See the documentation of `writer.rs` for the data passed to the template.
+	#[structopt(long, parse(from_os_str))]
+	pub template: Option<std::path::PathBuf>,
+
+	/// Add the contents of the given file, e.g. a license header, to the top of the output files.
+	#[structopt(long, parse(from_os_str))]
+	pub header: Option<std::path::PathBuf>,
+
+	/// Write the raw results and the analysis of the benchmarks to a JSON file.
+	///
+	/// Two such files can be compared with the `benchmark-compare` command.
+	#[structopt(long, parse(from_os_str))]
+	pub json_file: Option<std::path::PathBuf>,
+
+	/// Write the analysis of the benchmarks to a CSV file.
+	#[structopt(long, parse(from_os_str))]
+	pub csv_file: Option<std::path::PathBuf>,
+
 	/// Output the trait definition to a Rust file.
 	#[structopt(long)]
 	pub weight_trait: bool,
@@ -99,3 +124,20 @@ pub struct BenchmarkCmd {
 	#[structopt(long = "db-cache", value_name = "MiB", default_value = "128")]
 	pub database_cache_size: u32,
 }
+
+/// The `benchmark-compare` command used to find weight regressions between two benchmark runs.
+#[derive(Debug, structopt::StructOpt)]
+pub struct BenchmarkCompareCmd {
+	/// The results of the baseline run, as written by `benchmark --json-file`.
+	#[structopt(parse(from_os_str))]
+	pub old: std::path::PathBuf,
+
+	/// The results to compare with the baseline.
+	#[structopt(parse(from_os_str))]
+	pub new: std::path::PathBuf,
+
+	/// Fail if the worst case weight, reads or writes of a benchmark grew by more than this
+	/// many percent.
+	#[structopt(long, default_value = "10")]
+	pub threshold: f64,
+}
@@ -16,11 +16,132 @@
 // limitations under the License.
 
 // Outputs benchmark results to Rust files that can be ingested by the runtime.
+//
+// The weight files are rendered with a Handlebars template, the default one is `template.hbs`.
+// Templates are rendered once per pallet with the following data:
+//
+// - `header`: contents of the `--header` file, or an empty string.
+// - `version`: version of the benchmarking CLI.
+// - `date`: date of the run, as `YYYY-MM-DD`.
+// - `args`: the command line the benchmarks were run with.
+// - `cmd`: the parameters of the run: `steps`, `repeat`, `lowest_range_values`,
+//   `highest_range_values`, `execution`, `wasm_execution`, `chain` and `db_cache`.
+// - `pallet`: name of the pallet.
+// - `benchmarks`: one entry per benchmark of the pallet, with
+//   - `name`: name of the benchmark;
+//   - `components`: the components of the benchmark as `name` and `is_used`, the latter being
+//     false if no slope of the benchmark depends on the component;
+//   - `component_ranges`: the lowest and highest value of each component as `name`, `min`
+//     and `max`;
+//   - `base_weight`, `base_reads`, `base_writes`, `base_proof_size`: the weight in picoseconds,
+//     the number of database reads and writes and the storage proof size in bytes that do not
+//     depend on any component;
+//   - `component_weight`, `component_reads`, `component_writes`, `component_proof_size`: the
+//     non-zero slopes of the weight, reads, writes and proof size as `name` of the component
+//     and `slope`.
+//
+// The same analysis is written to the JSON output, next to the raw results of each benchmark.
+//
+// Besides the built-in helpers, templates can use `underscore` to format a number with
+// underscores as thousands separators and `join` to join an array with commas.
 
+use std::collections::HashMap;
 use std::fs::{File, OpenOptions};
 use std::io::prelude::*;
+use std::path::Path;
 use frame_benchmarking::{BenchmarkBatch, BenchmarkSelector, Analysis};
-use inflector::Inflector;
+use sc_cli::ExecutionStrategy;
+use serde::{Deserialize, Serialize};
+
+use crate::BenchmarkCmd;
+
+/// The built-in template, which produces a `WeightInfo` trait, its `SubstrateWeight<T>`
+/// implementation and an implementation for `()`.
+const TEMPLATE: &str = include_str!("./template.hbs");
+
+/// Data of a pallet that is passed to the template.
+#[derive(Serialize, Default, Debug, Clone)]
+struct TemplateData {
+	args: Vec<String>,
+	date: String,
+	version: String,
+	pallet: String,
+	header: String,
+	cmd: CmdData,
+	benchmarks: Vec<BenchmarkData>,
+}
+
+/// Data of a single benchmark that is passed to the template.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub(crate) struct BenchmarkData {
+	pub(crate) name: String,
+	pub(crate) components: Vec<Component>,
+	pub(crate) component_ranges: Vec<ComponentRange>,
+	pub(crate) base_weight: u128,
+	pub(crate) base_reads: u128,
+	pub(crate) base_writes: u128,
+	pub(crate) base_proof_size: u128,
+	pub(crate) component_weight: Vec<ComponentSlope>,
+	pub(crate) component_reads: Vec<ComponentSlope>,
+	pub(crate) component_writes: Vec<ComponentSlope>,
+	pub(crate) component_proof_size: Vec<ComponentSlope>,
+}
+
+/// The parameters of the benchmark run that are passed to the template.
+#[derive(Serialize, Default, Debug, Clone)]
+struct CmdData {
+	steps: Vec<u32>,
+	repeat: u32,
+	lowest_range_values: Vec<u32>,
+	highest_range_values: Vec<u32>,
+	execution: String,
+	wasm_execution: String,
+	chain: String,
+	db_cache: u32,
+}
+
+/// A component of a benchmark.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub(crate) struct Component {
+	pub(crate) name: String,
+	pub(crate) is_used: bool,
+}
+
+/// The slope of the weight, reads or writes of a benchmark in one of its components.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub(crate) struct ComponentSlope {
+	pub(crate) name: String,
+	pub(crate) slope: u128,
+}
+
+/// The range of values a component was benchmarked with.
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub(crate) struct ComponentRange {
+	pub(crate) name: String,
+	pub(crate) min: u32,
+	pub(crate) max: u32,
+}
+
+/// A benchmark as written to the JSON output: its raw results and their analysis.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct BenchmarkRecord {
+	pub(crate) pallet: String,
+	pub(crate) analysis: BenchmarkData,
+	pub(crate) results: Vec<RawResult>,
+}
+
+/// A single raw result of a benchmark.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub(crate) struct RawResult {
+	pub(crate) components: Vec<(String, u32)>,
+	pub(crate) extrinsic_time: u128,
+	pub(crate) storage_root_time: u128,
+	pub(crate) reads: u32,
+	pub(crate) repeat_reads: u32,
+	pub(crate) writes: u32,
+	pub(crate) repeat_writes: u32,
+	pub(crate) proof_size: u32,
+}
 
 pub fn open_file(path: &str) -> Result<File, std::io::Error> {
 	OpenOptions::new()
@@ -112,87 +233,331 @@ pub fn write_trait(file: &mut File, batches: Vec<BenchmarkBatch>) -> Result<(),
 	Ok(())
 }
 
-pub fn write_results(file: &mut File, batches: Vec<BenchmarkBatch>) -> Result<(), std::io::Error> {
-	let mut current_pallet = Vec::<u8>::new();
+/// Renders the weight file of every pallet of the batches into `<pallet>.rs`.
+///
+/// Uses the template and header files given to `cmd`, if any.
+pub fn write_results(
+	batches: &[BenchmarkBatch],
+	cmd: &BenchmarkCmd,
+) -> Result<(), std::io::Error> {
+	let template = match &cmd.template {
+		Some(path) => std::fs::read_to_string(path)?,
+		None => TEMPLATE.to_owned(),
+	};
+	let header = match &cmd.header {
+		Some(path) => std::fs::read_to_string(path)?,
+		None => String::new(),
+	};
+
+	let mut handlebars = handlebars::Handlebars::new();
+	handlebars.register_helper("underscore", Box::new(UnderscoreHelper));
+	handlebars.register_helper("join", Box::new(JoinHelper));
+	// Don't HTML escape any characters.
+	handlebars.register_escape_fn(|s| -> String { s.to_string() });
+
+	for data in template_data(batches, cmd, header)? {
+		let file_path = format!("{}.rs", data.pallet);
+		let file = File::create(Path::new(&file_path))?;
+		handlebars.render_template_to_write(&template, &data, file)
+			.map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))?;
+		println!("Created file: {}", file_path);
+	}
 
-	// Skip writing if there are no batches
-	if batches.is_empty() { return Ok(()) }
+	Ok(())
+}
 
-	// general imports
-	write!(file, "use frame_support::weights::{{Weight, constants::RocksDbWeight as DbWeight}};\n").unwrap();
+/// Writes the raw results and the analysis of every benchmark to a JSON file.
+pub fn write_json(batches: &[BenchmarkBatch], path: &Path) -> Result<(), std::io::Error> {
+	let records = batches.iter()
+		.filter(|batch| !batch.results.is_empty())
+		.map(|batch| Ok(BenchmarkRecord {
+			pallet: String::from_utf8_lossy(&batch.pallet).into_owned(),
+			analysis: benchmark_data(batch)?,
+			results: batch.results.iter().map(|result| RawResult {
+				components: result.components.iter()
+					.map(|(param, value)| (format!("{:?}", param), *value))
+					.collect(),
+				extrinsic_time: result.extrinsic_time,
+				storage_root_time: result.storage_root_time,
+				reads: result.reads,
+				repeat_reads: result.repeat_reads,
+				writes: result.writes,
+				repeat_writes: result.repeat_writes,
+				proof_size: result.proof_size,
+			}).collect(),
+		}))
+		.collect::<Result<Vec<_>, std::io::Error>>()?;
+
+	let file = File::create(path)?;
+	serde_json::to_writer_pretty(file, &records)?;
+	println!("Created file: {}", path.display());
+	Ok(())
+}
 
-	for batch in &batches {
-		// Skip writing if there are no results
-		if batch.results.is_empty() { continue }
+/// Reads the benchmarks written by [`write_json`].
+pub(crate) fn read_json(path: &Path) -> Result<Vec<BenchmarkRecord>, std::io::Error> {
+	let file = File::open(path)?;
+	Ok(serde_json::from_reader(std::io::BufReader::new(file))?)
+}
 
-		let pallet_string = String::from_utf8(batch.pallet.clone()).unwrap();
-		let benchmark_string = String::from_utf8(batch.benchmark.clone()).unwrap();
+/// Writes the analysis of every benchmark to a CSV file, one benchmark per row.
+///
+/// Slopes are written as space separated `component=slope` pairs.
+pub fn write_csv(batches: &[BenchmarkBatch], path: &Path) -> Result<(), std::io::Error> {
+	let slopes = |slopes: &[ComponentSlope]| slopes.iter()
+		.map(|s| format!("{}={}", s.name, s.slope))
+		.collect::<Vec<_>>()
+		.join(" ");
+
+	let mut file = File::create(path)?;
+	writeln!(
+		file,
+		"pallet,benchmark,base_weight,base_reads,base_writes,base_proof_size,\
+		component_weight,component_reads,component_writes,component_proof_size",
+	)?;
+	for batch in batches.iter().filter(|batch| !batch.results.is_empty()) {
+		let data = benchmark_data(batch)?;
+		writeln!(
+			file,
+			"{},{},{},{},{},{},{},{},{},{}",
+			String::from_utf8_lossy(&batch.pallet),
+			data.name,
+			data.base_weight,
+			data.base_reads,
+			data.base_writes,
+			data.base_proof_size,
+			slopes(&data.component_weight),
+			slopes(&data.component_reads),
+			slopes(&data.component_writes),
+			slopes(&data.component_proof_size),
+		)?;
+	}
+	println!("Created file: {}", path.display());
+	Ok(())
+}
 
-		// only create new trait definitions when we go to a new pallet
-		if batch.pallet != current_pallet {
-			if !current_pallet.is_empty() {
-				// close trait
-				write!(file, "}}\n").unwrap();
-			}
+/// Collects the data of each pallet of the batches, in the order of the batches.
+fn template_data(
+	batches: &[BenchmarkBatch],
+	cmd: &BenchmarkCmd,
+	header: String,
+) -> Result<Vec<TemplateData>, std::io::Error> {
+	let cmd_data = CmdData {
+		steps: cmd.steps.clone(),
+		repeat: cmd.repeat,
+		lowest_range_values: cmd.lowest_range_values.clone(),
+		highest_range_values: cmd.highest_range_values.clone(),
+		execution: cmd.execution.unwrap_or(ExecutionStrategy::Native).to_string(),
+		wasm_execution: cmd.wasm_method.to_string(),
+		chain: cmd.shared_params.chain.clone().unwrap_or_else(|| "dev".into()),
+		db_cache: cmd.database_cache_size,
+	};
+
+	let mut pallets = Vec::<TemplateData>::new();
+	let mut index = HashMap::<Vec<u8>, usize>::new();
+	for batch in batches {
+		// Skip writing if there are no results
+		if batch.results.is_empty() { continue }
 
-			// struct for weights
-			write!(file, "pub struct WeightFor{};\n",
-				pallet_string.to_pascal_case(),
-			).unwrap();
+		let position = *index.entry(batch.pallet.clone()).or_insert_with(|| {
+			pallets.push(TemplateData {
+				args: std::env::args().collect(),
+				date: chrono::Utc::now().format("%Y-%m-%d").to_string(),
+				version: env!("CARGO_PKG_VERSION").to_owned(),
+				pallet: String::from_utf8_lossy(&batch.pallet).into_owned(),
+				header: header.clone(),
+				cmd: cmd_data.clone(),
+				benchmarks: Vec::new(),
+			});
+			pallets.len() - 1
+		});
+		pallets[position].benchmarks.push(benchmark_data(batch)?);
+	}
 
-			// trait wrapper
-			write!(file, "impl {}::WeightInfo for WeightFor{} {{\n",
-				pallet_string,
-				pallet_string.to_pascal_case(),
-			).unwrap();
+	Ok(pallets)
+}
 
-			current_pallet = batch.pallet.clone()
+/// Analyses the results of a single benchmark.
+///
+/// Fails if the results can't be fitted, e.g. because they don't vary at all.
+fn benchmark_data(batch: &BenchmarkBatch) -> Result<BenchmarkData, std::io::Error> {
+	let slopes = |selector| {
+		let analysis = Analysis::min_squares_iqr(&batch.results, selector).ok_or_else(|| {
+			std::io::Error::new(std::io::ErrorKind::Other, format!(
+				"Failed to analyse the results of benchmark {}::{}",
+				String::from_utf8_lossy(&batch.pallet),
+				String::from_utf8_lossy(&batch.benchmark),
+			))
+		})?;
+		let slopes = analysis.slopes.iter().zip(analysis.names.iter())
+			.filter(|(slope, _)| **slope != 0)
+			.map(|(slope, name)| ComponentSlope { name: name.clone(), slope: *slope })
+			.collect::<Vec<_>>();
+		Ok((analysis.base, slopes))
+	};
+	let (base_weight, mut component_weight) = slopes(BenchmarkSelector::ExtrinsicTime)?;
+	let (base_reads, component_reads) = slopes(BenchmarkSelector::Reads)?;
+	let (base_writes, component_writes) = slopes(BenchmarkSelector::Writes)?;
+	let (base_proof_size, component_proof_size) = slopes(BenchmarkSelector::ProofSize)?;
+
+	// Extrinsic time is measured in nanoseconds, weight is in picoseconds.
+	component_weight.iter_mut().for_each(|cw| cw.slope = cw.slope.saturating_mul(1000));
+
+	let used = |name: &str| component_weight.iter()
+		.chain(component_reads.iter())
+		.chain(component_writes.iter())
+		.any(|slope| slope.name == name);
+	let names = batch.results[0].components.iter()
+		.map(|(param, _)| format!("{:?}", param))
+		.collect::<Vec<_>>();
+	let components = names.iter()
+		.map(|name| Component { name: name.clone(), is_used: used(name) })
+		.collect();
+	let component_ranges = names.iter().enumerate().map(|(i, name)| {
+		let values = batch.results.iter().map(|result| result.components[i].1);
+		ComponentRange {
+			name: name.clone(),
+			min: values.clone().min().unwrap_or_default(),
+			max: values.max().unwrap_or_default(),
 		}
+	}).collect();
+
+	Ok(BenchmarkData {
+		name: String::from_utf8_lossy(&batch.benchmark).into_owned(),
+		components,
+		component_ranges,
+		base_weight: base_weight.saturating_mul(1000),
+		base_reads,
+		base_writes,
+		base_proof_size,
+		component_weight,
+		component_reads,
+		component_writes,
+		component_proof_size,
+	})
+}
 
-		// function name
-		write!(file, "\tfn {}(", benchmark_string).unwrap();
-
-		// params
-		let components = &batch.results[0].components;
-		for component in components {
-			write!(file, "{:?}: u32, ", component.0).unwrap();
+/// Adds underscores as thousands separators to a number, e.g. `1_000_000`.
+fn underscore<Number>(i: Number) -> String where Number: std::string::ToString {
+	let mut s = String::new();
+	let i_str = i.to_string();
+	let a = i_str.chars().rev().enumerate();
+	for (idx, val) in a {
+		if idx != 0 && idx % 3 == 0 {
+			s.insert(0, '_');
 		}
-		// return value
-		write!(file, ") -> Weight {{\n").unwrap();
-
-		let extrinsic_time = Analysis::min_squares_iqr(&batch.results, BenchmarkSelector::ExtrinsicTime).unwrap();
-		write!(file, "\t\t({} as Weight)\n", extrinsic_time.base.saturating_mul(1000)).unwrap();
-		extrinsic_time.slopes.iter().zip(extrinsic_time.names.iter()).for_each(|(slope, name)| {
-			write!(file, "\t\t\t.saturating_add(({} as Weight).saturating_mul({} as Weight))\n",
-				slope.saturating_mul(1000),
-				name,
-			).unwrap();
-		});
+		s.insert(0, val);
+	}
+	s
+}
 
-		let reads = Analysis::min_squares_iqr(&batch.results, BenchmarkSelector::Reads).unwrap();
-		write!(file, "\t\t\t.saturating_add(DbWeight::get().reads({} as Weight))\n", reads.base).unwrap();
-		reads.slopes.iter().zip(reads.names.iter()).for_each(|(slope, name)| {
-			write!(file, "\t\t\t.saturating_add(DbWeight::get().reads(({} as Weight).saturating_mul({} as Weight)))\n",
-				slope,
-				name,
-			).unwrap();
-		});
+/// A Handlebars helper to add underscores as thousands separators to a number.
+#[derive(Clone, Copy)]
+struct UnderscoreHelper;
+impl handlebars::HelperDef for UnderscoreHelper {
+	fn call<'reg: 'rc, 'rc>(
+		&self,
+		h: &handlebars::Helper,
+		_: &handlebars::Handlebars,
+		_: &handlebars::Context,
+		_rc: &mut handlebars::RenderContext,
+		out: &mut dyn handlebars::Output,
+	) -> handlebars::HelperResult {
+		use handlebars::JsonRender;
+		let param = h.param(0)
+			.ok_or_else(|| handlebars::RenderError::new("`underscore` expects a number"))?;
+		out.write(&underscore(param.value().render()))?;
+		Ok(())
+	}
+}
 
-		let writes = Analysis::min_squares_iqr(&batch.results, BenchmarkSelector::Writes).unwrap();
-		write!(file, "\t\t\t.saturating_add(DbWeight::get().writes({} as Weight))\n", writes.base).unwrap();
-		writes.slopes.iter().zip(writes.names.iter()).for_each(|(slope, name)| {
-			write!(file, "\t\t\t.saturating_add(DbWeight::get().writes(({} as Weight).saturating_mul({} as Weight)))\n",
-				slope,
-				name,
-			).unwrap();
-		});
+/// A Handlebars helper to join an array with commas.
+#[derive(Clone, Copy)]
+struct JoinHelper;
+impl handlebars::HelperDef for JoinHelper {
+	fn call<'reg: 'rc, 'rc>(
+		&self,
+		h: &handlebars::Helper,
+		_: &handlebars::Handlebars,
+		_: &handlebars::Context,
+		_rc: &mut handlebars::RenderContext,
+		out: &mut dyn handlebars::Output,
+	) -> handlebars::HelperResult {
+		use handlebars::JsonRender;
+		let param = h.param(0)
+			.ok_or_else(|| handlebars::RenderError::new("`join` expects an array"))?;
+		let joined = match param.value().as_array() {
+			Some(values) => values.iter().map(|v| v.render()).collect::<Vec<_>>().join(", "),
+			None => param.value().render(),
+		};
+		out.write(&joined)?;
+		Ok(())
+	}
+}
 
-		// close function
-		write!(file, "\t}}\n").unwrap();
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use frame_benchmarking::{BenchmarkParameter, BenchmarkResults};
+	use structopt::StructOpt;
+
+	fn result(b: u32, extrinsic_time: u128, reads: u32) -> BenchmarkResults {
+		BenchmarkResults {
+			components: vec![(BenchmarkParameter::b, b)],
+			extrinsic_time,
+			reads,
+			..Default::default()
+		}
 	}
 
-	// final close trait
-	write!(file, "}}\n").unwrap();
+	#[test]
+	fn default_template_renders_weight_module() {
+		let cmd = BenchmarkCmd::from_iter(&[
+			"benchmark", "--pallet", "*", "--extrinsic", "*", "--steps", "3",
+		]);
+		let batches = vec![BenchmarkBatch {
+			pallet: b"pallet_test".to_vec(),
+			benchmark: b"transfer".to_vec(),
+			results: vec![result(1, 20_000, 3), result(2, 30_000, 3), result(3, 40_000, 3)],
+		}];
+		let data = template_data(&batches, &cmd, String::new()).unwrap();
+		assert_eq!(data.len(), 1);
+
+		let mut handlebars = handlebars::Handlebars::new();
+		handlebars.register_helper("underscore", Box::new(UnderscoreHelper));
+		handlebars.register_helper("join", Box::new(JoinHelper));
+		handlebars.register_escape_fn(|s| -> String { s.to_string() });
+		let output = handlebars.render_template(TEMPLATE, &data[0]).unwrap();
+
+		assert!(output.contains("fn transfer(b: u32, ) -> Weight;"));
+		assert!(output.contains("fn transfer(b: u32, ) -> Weight {"));
+		assert!(output.contains("(10_000_000 as Weight)"));
+		assert!(output.contains(".saturating_add((10_000_000 as Weight).saturating_mul(b as Weight))"));
+		assert!(output.contains(".saturating_add(T::DbWeight::get().reads(3 as Weight))"));
+		assert!(!output.contains("writes("));
+		assert!(output.contains("/// The range of component `b` is `[1, 3]`."));
+	}
 
-	Ok(())
+	#[test]
+	fn results_that_cannot_be_fitted_are_an_error() {
+		let cmd = BenchmarkCmd::from_iter(&[
+			"benchmark", "--pallet", "*", "--extrinsic", "*", "--steps", "3",
+		]);
+		let batches = vec![BenchmarkBatch {
+			pallet: b"pallet_test".to_vec(),
+			benchmark: b"transfer".to_vec(),
+			results: vec![result(1, 20_000, 3), result(1, 20_000, 3), result(1, 20_000, 3)],
+		}];
+
+		let error = template_data(&batches, &cmd, String::new()).err().unwrap();
+		assert!(error.to_string().contains("pallet_test::transfer"));
+	}
+
+	#[test]
+	fn underscore_adds_thousands_separators() {
+		assert_eq!(underscore(0), "0");
+		assert_eq!(underscore(999), "999");
+		assert_eq!(underscore(1000), "1_000");
+		assert_eq!(underscore(12_345_678), "12_345_678");
+	}
 }
@@ -0,0 +1,776 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! FRAME events exposed over Substrate RPC.
+//!
+//! Instead of subscribing to `System::Events` and decoding the whole vector on every block,
+//! clients subscribe to a stream of event records that is filtered on the node by module,
+//! event variant and topics. Module and event names are resolved with the runtime metadata
+//! of the block the events were deposited in, while the event records themselves are decoded
+//! with the outer event type of the node's runtime.
+//!
+//! The dispatch results of historical transactions are read from the same events, see
+//! [`TransactionApi`].
+
+#![warn(missing_docs)]
+
+mod transaction;
+
+use std::{convert::TryInto, marker::PhantomData, sync::Arc};
+
+use codec::{Decode, Encode};
+use futures::{future::ready, StreamExt, TryStreamExt};
+use jsonrpc_core::{
+	Error as RpcError, ErrorCode, Result as RpcResult,
+	futures::{Future, Sink, Stream},
+};
+use jsonrpc_derive::rpc;
+use jsonrpc_pubsub::{typed::Subscriber, SubscriptionId, manager::SubscriptionManager};
+use log::warn;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use frame_support::Parameter;
+use frame_system::{EventRecord, Phase};
+use sc_client_api::{Backend, BlockchainEvents, BlockImportNotification, StorageProvider};
+use sp_api::{Metadata, ProvideRuntimeApi};
+use sp_blockchain::{Error as ClientError, HeaderBackend};
+use sp_core::{Bytes, storage::{well_known_keys, StorageKey}, twox_128};
+use sp_rpc::number::NumberOrHex;
+use sp_runtime::{
+	generic::BlockId,
+	traits::{Block as BlockT, Header as HeaderT, Member, NumberFor, UniqueSaturatedInto},
+};
+
+pub use frame_metadata::decoded::{EventDescription, RuntimeInfo};
+pub use self::gen_client::Client as EventsClient;
+pub use self::transaction::{
+	DispatchFailure, DispatchOutcome, FullTransactions, HistoricalTransaction, TransactionApi,
+	TransactionClient,
+};
+
+/// Maximal number of blocks that are replayed when a subscription starts in the past.
+pub const MAX_CATCH_UP_BLOCKS: u64 = 4096;
+
+/// Events RPC methods.
+#[rpc]
+pub trait EventsApi<Hash> {
+	/// RPC Metadata
+	type Metadata;
+
+	/// Subscribe to the events of every new best block that pass `filter`.
+	///
+	/// If `from` is given, the best chain starting at that block number is replayed
+	/// before new blocks are delivered. Blocks that are retracted by a re-org are reported,
+	/// so that clients can revert the events they have seen from them.
+	///
+	/// The events are decoded with the event type of the node's native runtime. Blocks whose
+	/// events can't be decoded with it, e.g. blocks of older runtimes, are skipped.
+	#[pubsub(subscription = "events_filtered", subscribe, name = "events_subscribeFiltered")]
+	fn subscribe_filtered(
+		&self,
+		metadata: Self::Metadata,
+		subscriber: Subscriber<EventsNotification<Hash>>,
+		filter: EventFilter<Hash>,
+		from: Option<NumberOrHex>,
+	);
+
+	/// Unsubscribe from the filtered events subscription.
+	#[pubsub(subscription = "events_filtered", unsubscribe, name = "events_unsubscribeFiltered")]
+	fn unsubscribe_filtered(
+		&self,
+		metadata: Option<Self::Metadata>,
+		id: SubscriptionId,
+	) -> RpcResult<bool>;
+}
+
+/// Selects a module either by its index in the outer event enum or by its name.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ModuleSelector {
+	/// Index of the module in the outer event enum of the runtime.
+	Index(u8),
+	/// Name of the module, as given in `construct_runtime!`.
+	Name(String),
+}
+
+/// Filter that is applied to the events of every block.
+///
+/// An empty list does not restrict the events, all given criteria have to match.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct EventFilter<Hash> {
+	/// Only deliver events of one of these modules.
+	#[serde(default)]
+	pub modules: Vec<ModuleSelector>,
+	/// Only deliver events with one of these variant names, e.g. `Transfer`.
+	#[serde(default)]
+	pub events: Vec<String>,
+	/// Only deliver events that were deposited with at least one of these topics.
+	#[serde(default)]
+	pub topics: Vec<Hash>,
+}
+
+impl<Hash: PartialEq> EventFilter<Hash> {
+	/// Returns true if the given event passes this filter.
+	pub fn matches(&self, event: &FilteredEvent<Hash>) -> bool {
+		let module = self.modules.is_empty() || self.modules.iter().any(|selector| match selector {
+			ModuleSelector::Index(index) => *index == event.module_index,
+			ModuleSelector::Name(name) => event.module.as_ref() == Some(name),
+		});
+		let variant = self.events.is_empty() ||
+			event.event.as_ref().map_or(false, |name| self.events.contains(name));
+		let topic = self.topics.is_empty() ||
+			event.topics.iter().any(|topic| self.topics.contains(topic));
+
+		module && variant && topic
+	}
+}
+
+/// An event record of a block that passed the subscription filter.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FilteredEvent<Hash> {
+	/// Position of the record in the block's `System::Events`.
+	pub index: u32,
+	/// The phase of the block the event was deposited in.
+	pub phase: Phase,
+	/// Index of the emitting module in the outer event enum.
+	pub module_index: u8,
+	/// Name of the emitting module, if known to the runtime metadata.
+	pub module: Option<String>,
+	/// Index of the variant in the module's event enum.
+	pub event_index: u8,
+	/// Name of the variant, if known to the runtime metadata.
+	pub event: Option<String>,
+	/// Type names of the event arguments, as given in the runtime metadata.
+	pub arguments: Vec<String>,
+	/// SCALE encoded event arguments.
+	pub data: Bytes,
+	/// The topics the event was deposited with.
+	pub topics: Vec<Hash>,
+}
+
+/// Item of the filtered events subscription.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum EventsNotification<Hash> {
+	/// A block became part of the best chain.
+	///
+	/// Sent for every block, even if none of its events passed the filter.
+	#[serde(rename_all = "camelCase")]
+	Block {
+		/// Hash of the block.
+		hash: Hash,
+		/// Number of the block.
+		number: u64,
+		/// The events of the block that passed the filter.
+		events: Vec<FilteredEvent<Hash>>,
+	},
+	/// A previously delivered block was retracted from the best chain by a re-org.
+	#[serde(rename_all = "camelCase")]
+	Retracted {
+		/// Hash of the block.
+		hash: Hash,
+		/// Number of the block.
+		number: u64,
+	},
+}
+
+/// Error type of this RPC api.
+#[derive(Debug, derive_more::Display, derive_more::From)]
+pub enum Error {
+	/// Client error.
+	#[display(fmt="Client error: {}", _0)]
+	Client(ClientError),
+	/// The events or the runtime metadata could not be decoded.
+	#[display(fmt="Decoding error: {}", _0)]
+	#[from(ignore)]
+	Decode(String),
+	/// The requested catch up range is not valid.
+	#[display(fmt="Invalid block range: {}", _0)]
+	#[from(ignore)]
+	InvalidRange(String),
+}
+
+impl std::error::Error for Error {}
+
+impl From<Error> for RpcError {
+	fn from(e: Error) -> Self {
+		let code = match e {
+			Error::Client(_) => 1,
+			Error::Decode(_) => 2,
+			Error::InvalidRange(_) => 3,
+		};
+		RpcError {
+			code: ErrorCode::ServerError(code),
+			message: e.to_string(),
+			data: None,
+		}
+	}
+}
+
+/// Storage key of `System::Events`.
+fn events_key() -> StorageKey {
+	let mut key = twox_128(b"System").to_vec();
+	key.extend_from_slice(&twox_128(b"Events"));
+	StorageKey(key)
+}
+
+/// Reads and filters the events of blocks.
+struct EventsReader<C, Block: BlockT, BE, E> {
+	client: Arc<C>,
+	/// Metadata of the last seen runtime, keyed by the hash of its code.
+	metadata: Arc<Mutex<Option<(Block::Hash, Arc<RuntimeInfo>)>>>,
+	_marker: PhantomData<(BE, E)>,
+}
+
+impl<C, Block: BlockT, BE, E> Clone for EventsReader<C, Block, BE, E> {
+	fn clone(&self) -> Self {
+		EventsReader {
+			client: self.client.clone(),
+			metadata: self.metadata.clone(),
+			_marker: PhantomData,
+		}
+	}
+}
+
+impl<C, Block: BlockT, BE, E> EventsReader<C, Block, BE, E> {
+	fn new(client: Arc<C>) -> Self {
+		EventsReader {
+			client,
+			metadata: Arc::new(Mutex::new(None)),
+			_marker: PhantomData,
+		}
+	}
+}
+
+impl<C, Block, BE, E> EventsReader<C, Block, BE, E> where
+	Block: BlockT,
+	BE: Backend<Block>,
+	C: StorageProvider<Block, BE> + HeaderBackend<Block> + ProvideRuntimeApi<Block>,
+	C::Api: Metadata<Block, Error = ClientError>,
+	E: Parameter + Member,
+{
+	/// Returns the metadata of the runtime of the given block.
+	fn metadata_at(&self, at: &BlockId<Block>) -> Result<Arc<RuntimeInfo>, Error> {
+		let code_hash = self.client.storage_hash(at, &StorageKey(well_known_keys::CODE.to_vec()))?;
+
+		let mut cache = self.metadata.lock();
+		if let (Some(code_hash), Some((cached_hash, metadata))) = (code_hash, &*cache) {
+			if code_hash == *cached_hash {
+				return Ok(metadata.clone());
+			}
+		}
+
+		let encoded = self.client.runtime_api().metadata(at)?;
+		let metadata = Arc::new(RuntimeInfo::decode(&encoded).map_err(Error::Decode)?);
+		*cache = code_hash.map(|code_hash| (code_hash, metadata.clone()));
+		Ok(metadata)
+	}
+
+	/// Returns the events of the given block that pass `filter`.
+	fn events_at(
+		&self,
+		hash: Block::Hash,
+		filter: &EventFilter<Block::Hash>,
+	) -> Result<Vec<FilteredEvent<Block::Hash>>, Error> {
+		let at = BlockId::Hash(hash);
+		let records = match self.client.storage(&at, &events_key())? {
+			Some(data) => <Vec<EventRecord<E, Block::Hash>>>::decode(&mut &data.0[..])
+				.map_err(|e| Error::Decode(format!("Invalid `System::Events`: {:?}", e)))?,
+			None => return Ok(Vec::new()),
+		};
+		let metadata = self.metadata_at(&at)?;
+
+		let mut events = Vec::new();
+		for (index, record) in records.into_iter().enumerate() {
+			let event = filtered_event(&metadata, index as u32, record)?;
+			if filter.matches(&event) {
+				events.push(event);
+			}
+		}
+		Ok(events)
+	}
+
+	/// Returns the notification for a block that became part of the best chain.
+	fn block(
+		&self,
+		hash: Block::Hash,
+		number: NumberFor<Block>,
+		filter: &EventFilter<Block::Hash>,
+	) -> Result<EventsNotification<Block::Hash>, Error> {
+		Ok(EventsNotification::Block {
+			hash,
+			number: number.unique_saturated_into(),
+			events: self.events_at(hash, filter)?,
+		})
+	}
+
+	/// Returns the numbers of the first and the last block that are replayed for a subscription
+	/// starting at `from`, or `None` if `from` is above the best block.
+	fn catch_up_range(&self, from: NumberOrHex) -> Result<Option<(u32, u32)>, Error> {
+		// FIXME <2329>: Database seems to limit the block number to u32 for no reason
+		let from: u32 = from.try_into().map_err(|_| Error::InvalidRange(format!(
+			"`{:?}` > u32::max_value(), the max block number is u32.",
+			from,
+		)))?;
+		let best: u32 = self.client.info().best_number.unique_saturated_into();
+		if from > best {
+			return Ok(None);
+		}
+
+		let count = u64::from(best - from) + 1;
+		if count > MAX_CATCH_UP_BLOCKS {
+			return Err(Error::InvalidRange(format!(
+				"catching up on {} blocks, at most {} are allowed.",
+				count,
+				MAX_CATCH_UP_BLOCKS,
+			)));
+		}
+		Ok(Some((from, best)))
+	}
+
+	/// Returns the notification for the best chain block with the given number while catching
+	/// up, or `None` if its events can't be read.
+	fn caught_up_block(
+		&self,
+		number: u32,
+		filter: &EventFilter<Block::Hash>,
+	) -> Option<EventsNotification<Block::Hash>> {
+		let number = NumberFor::<Block>::from(number);
+		let block = self.client.hash(number).map_err(Error::from).and_then(|hash| {
+			let hash = hash.ok_or_else(|| ClientError::UnknownBlock(format!("{}", number)))?;
+			self.block(hash, number, filter)
+		});
+
+		match block {
+			Ok(block) => Some(block),
+			Err(e) => {
+				warn!("Failed to read events of block #{}: {}", number, e);
+				None
+			},
+		}
+	}
+
+	/// Returns the notifications for a newly imported best block, including the re-org
+	/// that made it the best block.
+	fn new_best(
+		&self,
+		notification: &BlockImportNotification<Block>,
+		filter: &EventFilter<Block::Hash>,
+	) -> Vec<EventsNotification<Block::Hash>> {
+		let mut notifications = Vec::new();
+		let mut enacted = Vec::new();
+
+		if let Some(tree_route) = &notification.tree_route {
+			notifications.extend(tree_route.retracted().iter().map(|block| {
+				EventsNotification::Retracted {
+					hash: block.hash,
+					number: block.number.unique_saturated_into(),
+				}
+			}));
+			enacted.extend(tree_route.enacted().iter().map(|block| (block.hash, block.number)));
+		}
+		enacted.push((notification.hash, *notification.header.number()));
+
+		for (hash, number) in enacted {
+			match self.block(hash, number, filter) {
+				Ok(block) => notifications.push(block),
+				Err(e) => warn!("Failed to read events of block {:?}: {}", hash, e),
+			}
+		}
+		notifications
+	}
+}
+
+/// Splits an event record into module, variant and arguments.
+fn filtered_event<E: Parameter + Member, Hash>(
+	metadata: &RuntimeInfo,
+	index: u32,
+	record: EventRecord<E, Hash>,
+) -> Result<FilteredEvent<Hash>, Error> {
+	let encoded = record.event.encode();
+	if encoded.len() < 2 {
+		return Err(Error::Decode(format!("Event record {} is too short", index)));
+	}
+	let (module_index, event_index) = (encoded[0], encoded[1]);
+	let description = metadata.event(module_index, event_index).map(|(_, event)| event);
+
+	Ok(FilteredEvent {
+		index,
+		phase: record.phase,
+		module_index,
+		module: metadata.event_module(module_index).map(|module| module.name.clone()),
+		event_index,
+		event: description.map(|event| event.name.clone()),
+		arguments: description.map(|event| event.arguments.clone()).unwrap_or_default(),
+		data: encoded[2..].to_vec().into(),
+		topics: record.topics,
+	})
+}
+
+/// An implementation of events specific RPC methods on full client.
+pub struct FullEvents<C, Block: BlockT, BE, E> {
+	reader: EventsReader<C, Block, BE, E>,
+	subscriptions: SubscriptionManager,
+}
+
+impl<C, Block: BlockT, BE, E> FullEvents<C, Block, BE, E> {
+	/// Create new `FullEvents` given client and subscriptions manager.
+	///
+	/// `E` is the outer event type of the runtime.
+	pub fn new(client: Arc<C>, subscriptions: SubscriptionManager) -> Self {
+		FullEvents {
+			reader: EventsReader::new(client),
+			subscriptions,
+		}
+	}
+}
+
+impl<C, Block, BE, E> EventsApi<Block::Hash> for FullEvents<C, Block, BE, E> where
+	Block: BlockT + 'static,
+	BE: Backend<Block> + 'static,
+	C: StorageProvider<Block, BE> + HeaderBackend<Block> + ProvideRuntimeApi<Block>
+		+ BlockchainEvents<Block> + Send + Sync + 'static,
+	C::Api: Metadata<Block, Error = ClientError>,
+	E: Parameter + Member,
+{
+	type Metadata = sc_rpc::Metadata;
+
+	fn subscribe_filtered(
+		&self,
+		_metadata: Self::Metadata,
+		subscriber: Subscriber<EventsNotification<Block::Hash>>,
+		filter: EventFilter<Block::Hash>,
+		from: Option<NumberOrHex>,
+	) {
+		// Listen for imports before catching up, so that no block gets lost in between.
+		let imports = self.reader.client.import_notification_stream();
+
+		let catch_up = match from.map(|from| self.reader.catch_up_range(from)).transpose() {
+			Ok(range) => range.flatten(),
+			Err(e) => {
+				let _ = subscriber.reject(e.into());
+				return;
+			},
+		};
+		let caught_up_to = catch_up.map(|(_, best)| u64::from(best));
+
+		let reader = self.reader.clone();
+		self.subscriptions.add(subscriber, move |sink| {
+			// The blocks are replayed by the subscription task, one at a time.
+			let catch_up_reader = reader.clone();
+			let catch_up_filter = filter.clone();
+			let catch_up = futures::stream::iter(catch_up.into_iter().flat_map(|(from, best)| from..=best))
+				.filter_map(move |number| ready(catch_up_reader.caught_up_block(number, &catch_up_filter)));
+
+			let imports = imports
+				.filter(|notification| ready(notification.is_new_best))
+				.filter(move |notification| {
+					// Blocks imported while catching up were already delivered.
+					let number: u64 = (*notification.header.number()).unique_saturated_into();
+					let delivered = notification.tree_route.is_none() &&
+						caught_up_to.map_or(false, |caught_up_to| number <= caught_up_to);
+					ready(!delivered)
+				})
+				.map(move |notification| {
+					futures::stream::iter(reader.new_best(&notification, &filter))
+				})
+				.flatten();
+
+			let stream = catch_up
+				.chain(imports)
+				.map(|notification| Ok::<_, ()>(Ok(notification)))
+				.compat();
+
+			sink
+				.sink_map_err(|e| warn!("Error sending notifications: {:?}", e))
+				.send_all(stream)
+				// we ignore the resulting Stream (if the first stream is over we are unsubscribed)
+				.map(|_| ())
+		});
+	}
+
+	fn unsubscribe_filtered(
+		&self,
+		_metadata: Option<Self::Metadata>,
+		id: SubscriptionId,
+	) -> RpcResult<bool> {
+		Ok(self.subscriptions.cancel(id))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use frame_metadata::{
+		DecodeDifferent, EventMetadata, ExtrinsicMetadata, FnEncode, ModuleMetadata,
+		RuntimeMetadata, RuntimeMetadataPrefixed, RuntimeMetadataV11, META_RESERVED,
+	};
+	use futures::{compat::Future01CompatExt, executor};
+	use sc_block_builder::BlockBuilderProvider;
+	use sp_core::H256;
+	use substrate_test_runtime_client::{prelude::*, runtime::Block, sp_consensus::BlockOrigin};
+
+	fn system_events() -> &'static [EventMetadata] {
+		&[
+			EventMetadata {
+				name: DecodeDifferent::Encode("ExtrinsicSuccess"),
+				arguments: DecodeDifferent::Encode(&["DispatchInfo"]),
+				documentation: DecodeDifferent::Encode(&[]),
+			},
+		]
+	}
+
+	fn balances_events() -> &'static [EventMetadata] {
+		&[
+			EventMetadata {
+				name: DecodeDifferent::Encode("Transfer"),
+				arguments: DecodeDifferent::Encode(&["AccountId", "AccountId", "Balance"]),
+				documentation: DecodeDifferent::Encode(&[]),
+			},
+		]
+	}
+
+	fn module(
+		name: &'static str,
+		event: Option<fn() -> &'static [EventMetadata]>,
+	) -> ModuleMetadata {
+		ModuleMetadata {
+			name: DecodeDifferent::Encode(name),
+			storage: None,
+			calls: None,
+			event: event.map(|event| DecodeDifferent::Encode(FnEncode(event))),
+			constants: DecodeDifferent::Encode(FnEncode(|| &[])),
+			errors: DecodeDifferent::Encode(FnEncode(|| &[])),
+		}
+	}
+
+	/// Encoded metadata of a runtime with `System`, `Timestamp` (no events) and `Balances`.
+	fn test_metadata() -> Vec<u8> {
+		let modules = vec![
+			module("System", Some(system_events)),
+			module("Timestamp", None),
+			module("Balances", Some(balances_events)),
+		];
+		RuntimeMetadataPrefixed(META_RESERVED, RuntimeMetadata::V11(RuntimeMetadataV11 {
+			modules: DecodeDifferent::Decoded(modules),
+			extrinsic: ExtrinsicMetadata { version: 4, signed_extensions: Vec::new() },
+		})).encode()
+	}
+
+	#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+	enum TestEvent {
+		#[codec(index = "0")]
+		System(u8),
+		#[codec(index = "1")]
+		Balances(BalancesEvent),
+	}
+
+	#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+	enum BalancesEvent {
+		Transfer(u64, u64, u128),
+	}
+
+	fn transfer() -> FilteredEvent<H256> {
+		let metadata = RuntimeInfo::decode(&test_metadata()).unwrap();
+		let record = EventRecord {
+			phase: Phase::ApplyExtrinsic(2),
+			event: TestEvent::Balances(BalancesEvent::Transfer(1, 2, 10)),
+			topics: vec![H256::repeat_byte(7)],
+		};
+
+		filtered_event(&metadata, 3, record).unwrap()
+	}
+
+	fn filter(
+		modules: Vec<ModuleSelector>,
+		events: Vec<&str>,
+		topics: Vec<H256>,
+	) -> EventFilter<H256> {
+		EventFilter {
+			modules,
+			events: events.into_iter().map(Into::into).collect(),
+			topics,
+		}
+	}
+
+	fn events(amount: u128) -> Vec<u8> {
+		vec![EventRecord {
+			phase: Phase::ApplyExtrinsic(0),
+			event: TestEvent::Balances(BalancesEvent::Transfer(1, 2, amount)),
+			topics: Vec::<H256>::new(),
+		}].encode()
+	}
+
+	fn new_api(
+		client: Arc<TestClient>,
+	) -> FullEvents<TestClient, Block, Backend, TestEvent> {
+		let executor = sc_rpc::SubscriptionTaskExecutor::new(sp_core::testing::TaskExecutor::new());
+		let api = FullEvents::new(client.clone(), SubscriptionManager::new(Arc::new(executor)));
+
+		// The test runtime has no metadata, so the reader is seeded with the test metadata.
+		let code_hash = client.storage_hash(
+			&BlockId::Number(0),
+			&StorageKey(well_known_keys::CODE.to_vec()),
+		).unwrap().unwrap();
+		let metadata = RuntimeInfo::decode(&test_metadata()).unwrap();
+		*api.reader.metadata.lock() = Some((code_hash, Arc::new(metadata)));
+		api
+	}
+
+	fn import_block(client: &mut Arc<TestClient>, parent: H256, events: Vec<u8>) -> H256 {
+		let mut builder = client.new_block_at(&BlockId::Hash(parent), Default::default(), false)
+			.unwrap();
+		// The test runtime doesn't reset the events, every block writes its own.
+		builder.push_storage_change(events_key().0, Some(events)).unwrap();
+		let block = builder.build().unwrap().block;
+		let hash = block.header.hash();
+		client.import(BlockOrigin::Own, block).unwrap();
+		hash
+	}
+
+	fn notifications(
+		mut transport: jsonrpc_core::futures::sync::mpsc::Receiver<String>,
+		count: usize,
+	) -> Vec<EventsNotification<H256>> {
+		let mut notifications = Vec::new();
+		for _ in 0..count {
+			let (notification, next) = executor::block_on(transport.into_future().compat()).unwrap();
+			let notification: serde_json::Value = serde_json::from_str(&notification.unwrap()).unwrap();
+			notifications.push(serde_json::from_value(notification["params"]["result"].clone()).unwrap());
+			transport = next;
+		}
+		notifications
+	}
+
+	fn block_hash_and_amounts(notification: &EventsNotification<H256>) -> (H256, Vec<Bytes>) {
+		match notification {
+			EventsNotification::Block { hash, events, .. } =>
+				(*hash, events.iter().map(|event| event.data.clone()).collect()),
+			EventsNotification::Retracted { .. } => panic!("Expected a block, got {:?}", notification),
+		}
+	}
+
+	#[test]
+	fn subscription_catches_up_and_follows_the_best_chain() {
+		let mut client = Arc::new(substrate_test_runtime_client::new());
+		let genesis = client.info().genesis_hash;
+		let block1 = import_block(&mut client, genesis, events(10));
+
+		let api = new_api(client.clone());
+		let (subscriber, id, transport) = Subscriber::new_test("test");
+		api.subscribe_filtered(Default::default(), subscriber, filter(vec![], vec![], vec![]), Some(1u64.into()));
+		assert!(matches!(executor::block_on(id.compat()), Ok(Ok(SubscriptionId::String(_)))));
+
+		let block2 = import_block(&mut client, block1, events(20));
+
+		let notifications = notifications(transport, 2);
+		assert_eq!(
+			notifications.iter().map(block_hash_and_amounts).collect::<Vec<_>>(),
+			vec![
+				(block1, vec![(1u64, 2u64, 10u128).encode().into()]),
+				(block2, vec![(1u64, 2u64, 20u128).encode().into()]),
+			],
+		);
+	}
+
+	#[test]
+	fn subscription_reports_retracted_blocks() {
+		let mut client = Arc::new(substrate_test_runtime_client::new());
+		let genesis = client.info().genesis_hash;
+
+		let api = new_api(client.clone());
+		let (subscriber, id, transport) = Subscriber::new_test("test");
+		api.subscribe_filtered(Default::default(), subscriber, filter(vec![], vec![], vec![]), None);
+		assert!(matches!(executor::block_on(id.compat()), Ok(Ok(SubscriptionId::String(_)))));
+
+		let a1 = import_block(&mut client, genesis, events(10));
+		let b1 = import_block(&mut client, genesis, events(20));
+		let b2 = import_block(&mut client, b1, events(30));
+
+		let notifications = notifications(transport, 4);
+		assert_eq!(block_hash_and_amounts(&notifications[0]).0, a1);
+		assert_eq!(notifications[1], EventsNotification::Retracted { hash: a1, number: 1 });
+		assert_eq!(block_hash_and_amounts(&notifications[2]), (b1, vec![(1u64, 2u64, 20u128).encode().into()]));
+		assert_eq!(block_hash_and_amounts(&notifications[3]), (b2, vec![(1u64, 2u64, 30u128).encode().into()]));
+	}
+
+	#[test]
+	fn undecodable_blocks_are_skipped_while_catching_up() {
+		let mut client = Arc::new(substrate_test_runtime_client::new());
+		let genesis = client.info().genesis_hash;
+		let block1 = import_block(&mut client, genesis, vec![4, 0xff, 0xff]);
+		let block2 = import_block(&mut client, block1, events(20));
+
+		let api = new_api(client.clone());
+		let (subscriber, id, transport) = Subscriber::new_test("test");
+		api.subscribe_filtered(Default::default(), subscriber, filter(vec![], vec![], vec![]), Some(1u64.into()));
+		assert!(matches!(executor::block_on(id.compat()), Ok(Ok(SubscriptionId::String(_)))));
+
+		let notifications = notifications(transport, 1);
+		assert_eq!(
+			block_hash_and_amounts(&notifications[0]),
+			(block2, vec![(1u64, 2u64, 20u128).encode().into()]),
+		);
+	}
+
+	#[test]
+	fn event_record_is_split_with_metadata() {
+		assert_eq!(transfer(), FilteredEvent {
+			index: 3,
+			phase: Phase::ApplyExtrinsic(2),
+			module_index: 1,
+			module: Some("Balances".into()),
+			event_index: 0,
+			event: Some("Transfer".into()),
+			arguments: vec!["AccountId".into(), "AccountId".into(), "Balance".into()],
+			data: (1u64, 2u64, 10u128).encode().into(),
+			topics: vec![H256::repeat_byte(7)],
+		});
+	}
+
+	#[test]
+	fn filter_matches_all_criteria() {
+		let event = transfer();
+
+		assert!(filter(vec![], vec![], vec![]).matches(&event));
+		assert!(filter(vec![ModuleSelector::Index(1)], vec![], vec![]).matches(&event));
+		assert!(filter(vec![ModuleSelector::Name("Balances".into())], vec![], vec![]).matches(&event));
+		assert!(!filter(vec![ModuleSelector::Name("System".into())], vec![], vec![]).matches(&event));
+		assert!(filter(vec![], vec!["Deposit", "Transfer"], vec![]).matches(&event));
+		assert!(!filter(vec![], vec!["Deposit"], vec![]).matches(&event));
+		assert!(filter(vec![], vec![], vec![H256::repeat_byte(7)]).matches(&event));
+		assert!(!filter(vec![], vec![], vec![H256::repeat_byte(8)]).matches(&event));
+		assert!(!filter(
+			vec![ModuleSelector::Index(1)],
+			vec!["Transfer"],
+			vec![H256::repeat_byte(8)],
+		).matches(&event));
+	}
+
+	#[test]
+	fn filter_is_deserialized_from_names_and_indices() {
+		let filter: EventFilter<H256> = serde_json::from_str(
+			r#"{"modules":["Balances",0],"events":["Transfer"]}"#
+		).unwrap();
+
+		assert_eq!(filter.modules, vec![ModuleSelector::Name("Balances".into()), ModuleSelector::Index(0)]);
+		assert_eq!(filter.events, vec!["Transfer".to_string()]);
+		assert!(filter.topics.is_empty());
+	}
+}
@@ -0,0 +1,339 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2020 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Lookup of historical transactions by hash.
+//!
+//! The location of a transaction is taken from the client's [`ExtrinsicIndex`], its dispatch
+//! result from the `System::ExtrinsicSuccess` or `System::ExtrinsicFailed` event it deposited.
+
+use std::sync::Arc;
+
+use codec::Decode;
+use jsonrpc_core::Result as RpcResult;
+use jsonrpc_derive::rpc;
+use log::debug;
+use serde::{Deserialize, Serialize};
+use frame_support::{Parameter, weights::{DispatchClass, DispatchInfo, Pays, Weight}};
+use frame_system::Phase;
+use sc_client_api::{Backend, ExtrinsicIndex, StorageProvider};
+use sp_api::{Metadata, ProvideRuntimeApi};
+use sp_blockchain::{Error as ClientError, HeaderBackend};
+use sp_runtime::{DispatchError, traits::{Block as BlockT, Member, NumberFor}};
+use sp_transaction_pool::TransactionStatus;
+
+use crate::{Error, EventFilter, EventsReader, FilteredEvent, ModuleSelector};
+
+pub use self::gen_client::Client as TransactionClient;
+
+/// Historical transaction RPC methods.
+#[rpc]
+pub trait TransactionApi<Hash, Number> {
+	/// Returns the inclusion and dispatch result of the transaction with the given hash.
+	///
+	/// Only transactions of the canonical chain that are still covered by the client's
+	/// extrinsic index are found. The dispatch result is only known if the state of the
+	/// including block is still available and its events can be decoded by the node.
+	#[rpc(name = "index_transactionStatus")]
+	fn transaction_status(
+		&self,
+		hash: Hash,
+	) -> RpcResult<Option<HistoricalTransaction<Hash, Number>>>;
+}
+
+/// Inclusion and dispatch result of a transaction of the canonical chain.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoricalTransaction<Hash, Number> {
+	/// `InBlock` or `Finalized`, with the hash of the including block.
+	pub status: TransactionStatus<Hash, Hash>,
+	/// Number of the including block.
+	pub block_number: Number,
+	/// Index of the transaction in the block body.
+	pub index: u32,
+	/// Result of the dispatch, `None` if the state of the block was pruned or its events
+	/// can't be decoded.
+	pub dispatch: Option<DispatchOutcome>,
+}
+
+/// Result of the dispatch of a transaction.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DispatchOutcome {
+	/// Whether the dispatch succeeded.
+	pub success: bool,
+	/// Why the dispatch failed.
+	pub error: Option<DispatchFailure>,
+	/// Weight of the dispatch.
+	pub weight: Weight,
+	/// Class of the dispatch.
+	pub class: DispatchClass,
+	/// Whether the transaction paid a fee.
+	pub pays_fee: bool,
+}
+
+/// Reason why the dispatch of a transaction failed.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DispatchFailure {
+	/// Some error occurred.
+	Other,
+	/// Failed to lookup some data.
+	CannotLookup,
+	/// A bad origin.
+	BadOrigin,
+	/// A custom error in a module.
+	#[serde(rename_all = "camelCase")]
+	Module {
+		/// Index of the module in the runtime metadata.
+		index: u8,
+		/// Module specific error value.
+		error: u8,
+	},
+}
+
+impl From<DispatchError> for DispatchFailure {
+	fn from(error: DispatchError) -> Self {
+		match error {
+			DispatchError::Other(_) => DispatchFailure::Other,
+			DispatchError::CannotLookup => DispatchFailure::CannotLookup,
+			DispatchError::BadOrigin => DispatchFailure::BadOrigin,
+			DispatchError::Module { index, error, .. } => DispatchFailure::Module { index, error },
+		}
+	}
+}
+
+impl DispatchOutcome {
+	fn new(info: DispatchInfo, error: Option<DispatchError>) -> Self {
+		DispatchOutcome {
+			success: error.is_none(),
+			error: error.map(Into::into),
+			weight: info.weight,
+			class: info.class,
+			pays_fee: info.pays_fee == Pays::Yes,
+		}
+	}
+}
+
+/// Returns the dispatch outcome of the extrinsic at `index` from the events of its block.
+fn dispatch_outcome<Hash>(
+	events: &[FilteredEvent<Hash>],
+	index: u32,
+) -> Result<Option<DispatchOutcome>, Error> {
+	// The outcome is deposited last, after the events of the dispatched call and its fee.
+	let is_outcome = |event: &&FilteredEvent<Hash>| {
+		let name = event.event.as_ref().map(String::as_str);
+		event.module.as_ref().map(String::as_str) == Some("System") &&
+			(name == Some("ExtrinsicSuccess") || name == Some("ExtrinsicFailed"))
+	};
+	let event = match events.iter()
+		.filter(|event| event.phase == Phase::ApplyExtrinsic(index))
+		.find(is_outcome)
+	{
+		Some(event) => event,
+		None => return Ok(None),
+	};
+	let invalid = |e: codec::Error| {
+		Error::Decode(format!("Invalid dispatch result of extrinsic {}: {:?}", index, e))
+	};
+
+	let outcome = match event.event.as_ref().map(String::as_str) {
+		Some("ExtrinsicSuccess") => {
+			let info = DispatchInfo::decode(&mut &event.data[..]).map_err(invalid)?;
+			DispatchOutcome::new(info, None)
+		},
+		Some("ExtrinsicFailed") => {
+			let (error, info) = <(DispatchError, DispatchInfo)>::decode(&mut &event.data[..])
+				.map_err(invalid)?;
+			DispatchOutcome::new(info, Some(error))
+		},
+		_ => return Ok(None),
+	};
+	Ok(Some(outcome))
+}
+
+/// An implementation of the historical transaction RPC methods on full client.
+pub struct FullTransactions<C, Block: BlockT, BE, E> {
+	reader: EventsReader<C, Block, BE, E>,
+	index: Arc<dyn ExtrinsicIndex<Block>>,
+}
+
+impl<C, Block: BlockT, BE, E> FullTransactions<C, Block, BE, E> {
+	/// Create new `FullTransactions` given client and the client's extrinsic index.
+	///
+	/// `E` is the outer event type of the runtime.
+	pub fn new(client: Arc<C>, index: Arc<dyn ExtrinsicIndex<Block>>) -> Self {
+		FullTransactions {
+			reader: EventsReader::new(client),
+			index,
+		}
+	}
+}
+
+impl<C, Block, BE, E> FullTransactions<C, Block, BE, E> where
+	Block: BlockT,
+	BE: Backend<Block>,
+	C: StorageProvider<Block, BE> + HeaderBackend<Block> + ProvideRuntimeApi<Block>,
+	C::Api: Metadata<Block, Error = ClientError>,
+	E: Parameter + Member,
+{
+	fn status(
+		&self,
+		hash: Block::Hash,
+	) -> Result<Option<HistoricalTransaction<Block::Hash, NumberFor<Block>>>, Error> {
+		let location = match self.index.extrinsic_by_hash(&hash)? {
+			Some(location) => location,
+			None => return Ok(None),
+		};
+		// the index follows the best chain with a delay, so it might lag behind a re-org
+		if self.reader.client.hash(location.block_number)? != Some(location.block_hash) {
+			return Ok(None);
+		}
+
+		let status = if location.block_number <= self.reader.client.info().finalized_number {
+			TransactionStatus::Finalized(location.block_hash)
+		} else {
+			TransactionStatus::InBlock(location.block_hash)
+		};
+
+		let filter = EventFilter {
+			modules: vec![ModuleSelector::Name("System".into())],
+			events: vec!["ExtrinsicSuccess".into(), "ExtrinsicFailed".into()],
+			topics: Vec::new(),
+		};
+		let dispatch = match self.reader.events_at(location.block_hash, &filter) {
+			Ok(events) => dispatch_outcome(&events, location.index)?,
+			Err(Error::Client(e)) => {
+				debug!("Events of block {} are not available: {}", location.block_hash, e);
+				None
+			},
+			// blocks of older runtimes might deposit events the node can't decode anymore
+			Err(Error::Decode(e)) => {
+				debug!("Events of block {} can't be decoded: {}", location.block_hash, e);
+				None
+			},
+			Err(e) => return Err(e),
+		};
+
+		Ok(Some(HistoricalTransaction {
+			status,
+			block_number: location.block_number,
+			index: location.index,
+			dispatch,
+		}))
+	}
+}
+
+impl<C, Block, BE, E> TransactionApi<Block::Hash, NumberFor<Block>>
+	for FullTransactions<C, Block, BE, E>
+where
+	Block: BlockT + 'static,
+	BE: Backend<Block> + 'static,
+	C: StorageProvider<Block, BE> + HeaderBackend<Block> + ProvideRuntimeApi<Block>
+		+ Send + Sync + 'static,
+	C::Api: Metadata<Block, Error = ClientError>,
+	E: Parameter + Member,
+{
+	fn transaction_status(
+		&self,
+		hash: Block::Hash,
+	) -> RpcResult<Option<HistoricalTransaction<Block::Hash, NumberFor<Block>>>> {
+		self.status(hash).map_err(Into::into)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::Encode;
+	use sp_core::H256;
+
+	fn event(phase: Phase, name: &str, data: Vec<u8>) -> FilteredEvent<H256> {
+		module_event(phase, "System", name, data)
+	}
+
+	fn module_event(phase: Phase, module: &str, name: &str, data: Vec<u8>) -> FilteredEvent<H256> {
+		FilteredEvent {
+			index: 0,
+			phase,
+			module_index: 0,
+			module: Some(module.into()),
+			event_index: 0,
+			event: Some(name.into()),
+			arguments: Vec::new(),
+			data: data.into(),
+			topics: Vec::new(),
+		}
+	}
+
+	#[test]
+	fn dispatch_outcome_is_read_from_events() {
+		let info = DispatchInfo { weight: 10, class: DispatchClass::Normal, pays_fee: Pays::Yes };
+		let error = DispatchError::Module { index: 4, error: 2, message: None };
+		let transfer = (1u64, 2u64, 10u128).encode();
+		let events = vec![
+			module_event(Phase::ApplyExtrinsic(0), "Balances", "Transfer", transfer.clone()),
+			// events of other modules with the same name don't count as the outcome
+			module_event(Phase::ApplyExtrinsic(0), "Other", "ExtrinsicFailed", vec![]),
+			event(Phase::ApplyExtrinsic(0), "ExtrinsicSuccess", info.encode()),
+			module_event(Phase::ApplyExtrinsic(1), "Balances", "Transfer", transfer.clone()),
+			event(Phase::ApplyExtrinsic(1), "ExtrinsicFailed", (error, info).encode()),
+			event(Phase::Finalization, "ExtrinsicSuccess", info.encode()),
+			module_event(Phase::ApplyExtrinsic(3), "Balances", "Transfer", transfer),
+		];
+
+		assert_eq!(dispatch_outcome(&events, 0).unwrap(), Some(DispatchOutcome {
+			success: true,
+			error: None,
+			weight: 10,
+			class: DispatchClass::Normal,
+			pays_fee: true,
+		}));
+		assert_eq!(dispatch_outcome(&events, 1).unwrap(), Some(DispatchOutcome {
+			success: false,
+			error: Some(DispatchFailure::Module { index: 4, error: 2 }),
+			weight: 10,
+			class: DispatchClass::Normal,
+			pays_fee: true,
+		}));
+		assert_eq!(dispatch_outcome(&events, 2).unwrap(), None);
+		assert_eq!(dispatch_outcome(&events, 3).unwrap(), None);
+		let truncated = event(Phase::ApplyExtrinsic(0), "ExtrinsicSuccess", vec![]);
+		assert!(dispatch_outcome(&[truncated], 0).is_err());
+	}
+
+	#[test]
+	fn historical_transaction_is_serialized() {
+		let transaction = HistoricalTransaction {
+			status: TransactionStatus::Finalized(H256::repeat_byte(1)),
+			block_number: 5u32,
+			index: 1,
+			dispatch: Some(DispatchOutcome {
+				success: false,
+				error: Some(DispatchFailure::BadOrigin),
+				weight: 10,
+				class: DispatchClass::Operational,
+				pays_fee: false,
+			}),
+		};
+
+		let json = serde_json::to_value(&transaction).unwrap();
+		assert_eq!(json["status"]["finalized"], format!("{:?}", H256::repeat_byte(1)));
+		assert_eq!(json["blockNumber"], 5);
+		assert_eq!(json["dispatch"]["error"], "badOrigin");
+		assert_eq!(json["dispatch"]["paysFee"], false);
+	}
+}
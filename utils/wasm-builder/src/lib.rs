@@ -77,11 +77,31 @@
 //!                            to be absolute.
 //! - `WASM_BUILD_TOOLCHAIN` - The toolchain that should be used to build the wasm binaries. The
 //!                            format needs to be the same as used by cargo, e.g. `nightly-2020-02-20`.
+//! - `WASM_BUILD_COMPRESS` - Compress the compact wasm binary with zstd. `WASM_BINARY` will then
+//!                           contain the compressed binary, which the executor decompresses when
+//!                           loading it.
+//! - `WASM_BUILD_REPRODUCIBLE` - Build the wasm binary reproducibly, see below.
 //!
 //! Each project can be skipped individually by using the environment variable `SKIP_PROJECT_NAME_WASM_BUILD`.
 //! Where `PROJECT_NAME` needs to be replaced by the name of the cargo project, e.g. `node-runtime` will
 //! be `NODE_RUNTIME`.
 //!
+//! ## Reproducible builds
+//!
+//! With `WASM_BUILD_REPRODUCIBLE` set, two builds of the same sources with the same toolchain
+//! produce the same wasm binary, independent of the machine and of where the sources are
+//! checked out:
+//!
+//! - `WASM_BUILD_TOOLCHAIN` is required, so the toolchain is pinned.
+//! - The paths of the workspace, of the wasm workspace and of the cargo home are remapped, so no
+//!   absolute path ends up in the binary. As the remappings are passed through `RUSTFLAGS`,
+//!   these paths must not contain whitespace. Incremental compilation is disabled.
+//! - Custom sections, e.g. names and producers, are stripped from the compact binary.
+//!
+//! Besides the binary, a `<binary name>.manifest.json` is written. It contains the toolchain and
+//! the blake2-256 hash of `WASM_BINARY`, which is the same as the hash of `:code` once the binary
+//! is set on chain. If `WASM_TARGET_DIRECTORY` is set, the manifest is copied there as well.
+//!
 //! ## Prerequisites:
 //!
 //! Wasm builder requires the following prerequisites for building the Wasm binary:
@@ -120,6 +140,12 @@ const WASM_BUILD_NO_COLOR: &str = "WASM_BUILD_NO_COLOR";
 /// Environment variable to set the toolchain used to compile the wasm binary.
 const WASM_BUILD_TOOLCHAIN: &str = "WASM_BUILD_TOOLCHAIN";
 
+/// Environment variable to compress the compact wasm binary.
+const WASM_BUILD_COMPRESS: &str = "WASM_BUILD_COMPRESS";
+
+/// Environment variable to build the wasm binary reproducibly.
+const WASM_BUILD_REPRODUCIBLE: &str = "WASM_BUILD_REPRODUCIBLE";
+
 /// Build the currently built project as wasm binary.
 ///
 /// The current project is determined by using the `CARGO_MANIFEST_DIR` environment variable.
@@ -158,6 +184,15 @@ pub fn build_project_with_default_rustflags(
 		panic!("'{}' no valid path to a `Cargo.toml`!", cargo_manifest.display());
 	}
 
+	if env::var(WASM_BUILD_REPRODUCIBLE).is_ok() && env::var(WASM_BUILD_TOOLCHAIN).is_err() {
+		eprintln!(
+			"`{}` requires the toolchain to be pinned with `{}`, e.g. `nightly-2020-02-20`.",
+			WASM_BUILD_REPRODUCIBLE,
+			WASM_BUILD_TOOLCHAIN,
+		);
+		process::exit(1);
+	}
+
 	if let Some(err_msg) = prerequisites::check() {
 		eprintln!("{}", err_msg);
 		process::exit(1);
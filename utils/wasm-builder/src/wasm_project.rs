@@ -106,7 +106,7 @@ pub fn create_and_compile(
 	let project = create_project(cargo_manifest, &wasm_workspace, &crate_metadata);
 	create_wasm_workspace_project(&wasm_workspace, &crate_metadata.workspace_root);
 
-	build_project(&project, default_rustflags);
+	build_project(&project, default_rustflags, &crate_metadata.workspace_root, &wasm_workspace);
 	let (wasm_binary, bloaty) = compact_wasm_file(
 		&project,
 		cargo_manifest,
@@ -115,6 +115,10 @@ pub fn create_and_compile(
 
 	copy_wasm_to_target_directory(cargo_manifest, &wasm_binary);
 
+	if is_reproducible_build() {
+		write_build_manifest(cargo_manifest, &project, &wasm_binary);
+	}
+
 	generate_rerun_if_changed_instructions(cargo_manifest, &project, &wasm_workspace);
 
 	(wasm_binary, bloaty)
@@ -430,17 +434,76 @@ fn is_release_build() -> bool {
 	}
 }
 
+/// Returns `true` if the WASM binary should be built reproducibly.
+fn is_reproducible_build() -> bool {
+	env::var(crate::WASM_BUILD_REPRODUCIBLE).is_ok()
+}
+
+/// Returns the cargo home, whose sources are remapped for a reproducible build.
+fn cargo_home() -> Option<PathBuf> {
+	env::var("CARGO_HOME").map(PathBuf::from).ok()
+		.or_else(|| env::var("HOME").ok().map(|home| PathBuf::from(home).join(".cargo")))
+}
+
+/// Returns the `RUSTFLAGS` that remap the machine specific paths for a reproducible build.
+///
+/// rustc applies the last matching remapping, so the more specific paths come last: the wasm
+/// workspace usually is inside of the workspace.
+///
+/// Panics if a path contains whitespace, as `RUSTFLAGS` is split at whitespace.
+fn remap_path_rustflags(
+	workspace_root: &Path,
+	wasm_workspace: &Path,
+	cargo_home: Option<PathBuf>,
+) -> String {
+	let mut remaps = Vec::new();
+	if let Some(cargo_home) = cargo_home {
+		remaps.push((cargo_home, "/cargo"));
+	}
+	remaps.push((workspace_root.to_path_buf(), "/workspace"));
+	remaps.push((wasm_workspace.to_path_buf(), "/wbuild"));
+
+	remaps.into_iter()
+		.map(|(from, to)| {
+			let from = from.display().to_string();
+			if from.contains(char::is_whitespace) {
+				panic!(
+					"`{}` contains whitespace and can not be remapped with `RUSTFLAGS`. \
+					Move it to a path without whitespace or unset `{}`.",
+					from,
+					crate::WASM_BUILD_REPRODUCIBLE,
+				);
+			}
+			format!("--remap-path-prefix={}={}", from, to)
+		})
+		.join(" ")
+}
+
 /// Build the project to create the WASM binary.
-fn build_project(project: &Path, default_rustflags: &str) {
+fn build_project(
+	project: &Path,
+	default_rustflags: &str,
+	workspace_root: &Path,
+	wasm_workspace: &Path,
+) {
 	let manifest_path = project.join("Cargo.toml");
 	let mut build_cmd = crate::get_nightly_cargo().command();
 
-	let rustflags = format!(
+	let mut rustflags = format!(
 		"-C link-arg=--export-table {} {}",
 		default_rustflags,
 		env::var(crate::WASM_BUILD_RUSTFLAGS_ENV).unwrap_or_default(),
 	);
 
+	if is_reproducible_build() {
+		rustflags = format!(
+			"{} {}",
+			rustflags,
+			remap_path_rustflags(workspace_root, wasm_workspace, cargo_home()),
+		);
+		build_cmd.env("CARGO_INCREMENTAL", "0");
+	}
+
 	build_cmd.args(&["rustc", "--target=wasm32-unknown-unknown"])
 		.arg(format!("--manifest-path={}", manifest_path.display()))
 		.env("RUSTFLAGS", rustflags)
@@ -480,9 +543,109 @@ fn compact_wasm_file(
 	wasm_gc::garbage_collect_file(&wasm_file, &wasm_compact_file)
 		.expect("Failed to compact generated WASM binary.");
 
+	if is_reproducible_build() {
+		strip_custom_sections(&wasm_compact_file);
+	}
+
+	if env::var(crate::WASM_BUILD_COMPRESS).is_ok() {
+		let wasm_compressed_file = project.join(format!("{}.compact.compressed.wasm", wasm_binary));
+		compress_wasm(&wasm_compact_file, &wasm_compressed_file);
+		return (WasmBinary(wasm_compressed_file), WasmBinaryBloaty(wasm_file))
+	}
+
 	(WasmBinary(wasm_compact_file), WasmBinaryBloaty(wasm_file))
 }
 
+/// Remove the custom sections from the given WASM binary.
+///
+/// Custom sections like `name` and `producers` are not needed to execute the binary, but they
+/// depend on the toolchain installation and on symbol names.
+fn strip_custom_sections(wasm_binary: &Path) {
+	use parity_wasm::elements::{Module, Section};
+
+	let mut module: Module = parity_wasm::deserialize_file(wasm_binary)
+		.expect("Failed to deserialize compact WASM binary");
+	module.sections_mut().retain(|section| match section {
+		Section::Custom(_) | Section::Name(_) | Section::Reloc(_) => false,
+		_ => true,
+	});
+	parity_wasm::serialize_to_file(wasm_binary, module)
+		.expect("Failed to write stripped WASM binary");
+}
+
+/// Write the build manifest of a reproducible build next to the WASM binary.
+fn write_build_manifest(cargo_manifest: &Path, project: &Path, wasm_binary: &WasmBinary) {
+	let code = fs::read(wasm_binary.wasm_binary_path()).expect("Failed to read WASM binary");
+	let cargo_version = crate::get_nightly_cargo().command()
+		.arg("--version")
+		.output()
+		.map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+		.unwrap_or_default();
+
+	let manifest = build_manifest(
+		&get_crate_name(cargo_manifest),
+		&env::var(crate::WASM_BUILD_TOOLCHAIN).unwrap_or_default(),
+		&cargo_version,
+		&code,
+	);
+
+	let manifest_name = format!("{}.manifest.json", get_wasm_binary_name(cargo_manifest));
+	let manifest_file = project.join(&manifest_name);
+	fs::write(&manifest_file, manifest).expect("Failed to write build manifest");
+
+	if let Ok(target_dir) = env::var(crate::WASM_TARGET_DIRECTORY) {
+		fs::copy(&manifest_file, PathBuf::from(target_dir).join(manifest_name))
+			.expect("Copies build manifest to `WASM_TARGET_DIRECTORY`.");
+	}
+
+	println!("cargo:warning=Reproducible WASM build, code hash: {}", code_hash(&code));
+}
+
+/// Returns the build manifest of the given WASM binary, as JSON.
+///
+/// The manifest contains the blake2-256 hash of the WASM binary, which equals the on-chain
+/// hash of `:code` after the binary was set as runtime code.
+fn build_manifest(name: &str, toolchain: &str, cargo: &str, code: &[u8]) -> String {
+	let manifest = serde_json::json!({
+		"name": name,
+		"toolchain": toolchain,
+		"cargo": cargo,
+		"compressed": sp_maybe_compressed_blob::is_compressed(code),
+		"size": code.len(),
+		"code_hash": code_hash(code),
+	});
+
+	let mut manifest = serde_json::to_string_pretty(&manifest)
+		.expect("Serializing a JSON value does not fail");
+	manifest.push('\n');
+	manifest
+}
+
+/// Returns the hex encoded blake2-256 hash of the given WASM binary.
+fn code_hash(code: &[u8]) -> String {
+	let hash = blake2_rfc::blake2b::blake2b(32, &[], code).as_bytes().iter()
+		.map(|b| format!("{:02x}", b))
+		.join("");
+	format!("0x{}", hash)
+}
+
+/// Compress the given WASM binary with zstd, prefixed by the magic bytes the executor uses to
+/// detect compressed code.
+fn compress_wasm(wasm_binary: &Path, compressed_binary: &Path) {
+	let data = fs::read(wasm_binary).expect("Failed to read WASM binary");
+
+	let compressed = sp_maybe_compressed_blob::compress(
+		&data,
+		sp_maybe_compressed_blob::CODE_BLOB_BOMB_LIMIT,
+	).unwrap_or_else(|| panic!(
+		"WASM binary of {} bytes is larger than the decompression limit of {} bytes",
+		data.len(),
+		sp_maybe_compressed_blob::CODE_BLOB_BOMB_LIMIT,
+	));
+
+	fs::write(compressed_binary, &compressed).expect("Failed to write compressed WASM binary");
+}
+
 /// Custom wrapper for a [`cargo_metadata::Package`] to store it in
 /// a `HashSet`.
 #[derive(Debug)]
@@ -583,6 +746,8 @@ fn generate_rerun_if_changed_instructions(
 	println!("cargo:rerun-if-env-changed={}", crate::WASM_BUILD_RUSTFLAGS_ENV);
 	println!("cargo:rerun-if-env-changed={}", crate::WASM_TARGET_DIRECTORY);
 	println!("cargo:rerun-if-env-changed={}", crate::WASM_BUILD_TOOLCHAIN);
+	println!("cargo:rerun-if-env-changed={}", crate::WASM_BUILD_COMPRESS);
+	println!("cargo:rerun-if-env-changed={}", crate::WASM_BUILD_REPRODUCIBLE);
 }
 
 /// Track files and paths related to the given package to rerun `build.rs` on any relevant change.
@@ -632,3 +797,72 @@ fn copy_wasm_to_target_directory(cargo_manifest: &Path, wasm_binary: &WasmBinary
 		target_dir.join(format!("{}.wasm", get_wasm_binary_name(cargo_manifest))),
 	).expect("Copies WASM binary to `WASM_TARGET_DIRECTORY`.");
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn more_specific_paths_are_remapped_last() {
+		let rustflags = remap_path_rustflags(
+			Path::new("/src/substrate"),
+			Path::new("/src/substrate/target/debug/wbuild"),
+			Some(PathBuf::from("/home/user/.cargo")),
+		);
+
+		assert_eq!(
+			rustflags,
+			"--remap-path-prefix=/home/user/.cargo=/cargo \
+			--remap-path-prefix=/src/substrate=/workspace \
+			--remap-path-prefix=/src/substrate/target/debug/wbuild=/wbuild",
+		);
+	}
+
+	#[test]
+	#[should_panic(expected = "contains whitespace")]
+	fn paths_with_whitespace_are_rejected() {
+		remap_path_rustflags(
+			Path::new("/src/my substrate"),
+			Path::new("/src/my substrate/target/debug/wbuild"),
+			None,
+		);
+	}
+
+	#[test]
+	fn code_hash_is_blake2_256() {
+		assert_eq!(
+			code_hash(&[]),
+			"0x0e5751c026e543b2e8ab2eb06099daa1d1e5df47778f7787faab45cdf12fe3a8",
+		);
+	}
+
+	#[test]
+	fn build_manifest_is_valid_json() {
+		let toolchain = "nightly-2020-07-20 \"custom\" \\ \u{1F980}";
+		let cargo = "cargo 1.47.0-nightly\n";
+		let code = b"\0asm\x01\0\0\0".to_vec();
+
+		let manifest: serde_json::Value = serde_json::from_str(
+			&build_manifest("node-runtime", toolchain, cargo, &code),
+		).unwrap();
+
+		assert_eq!(manifest["name"], "node-runtime");
+		assert_eq!(manifest["toolchain"], toolchain);
+		assert_eq!(manifest["cargo"], cargo);
+		assert_eq!(manifest["compressed"], false);
+		assert_eq!(manifest["size"], code.len());
+		assert_eq!(manifest["code_hash"], code_hash(&code));
+	}
+
+	#[test]
+	fn build_manifest_reports_compressed_code() {
+		let code = sp_maybe_compressed_blob::compress(&[0; 1024], 1024 * 1024).unwrap();
+
+		let manifest: serde_json::Value = serde_json::from_str(
+			&build_manifest("node-runtime", "", "", &code),
+		).unwrap();
+
+		assert_eq!(manifest["compressed"], true);
+		assert_eq!(manifest["size"], code.len());
+	}
+}